@@ -0,0 +1,230 @@
+//! Audit log of what left this machine
+//!
+//! Every push appends one line per file it sent to `audit.jsonl` in the state
+//! directory: the remote and branch, the commit pushed, when, and for session
+//! files the UUIDs of the entries the remote didn't have yet. The log is only
+//! ever appended to, so it still answers "did this conversation leave my
+//! laptop, and when?" after the session is deleted or purged.
+//!
+//! What a push sends is the difference between the pushed commit and its
+//! common ancestor with the remote branch it builds on, so content pulled from
+//! other machines is not recorded as leaving this one.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::config::ConfigManager;
+use crate::purge::file_session_id;
+use crate::scm::Scm;
+
+/// One file sent to a remote by one push
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub pushed_at: DateTime<Utc>,
+    pub remote: String,
+    pub branch: String,
+    /// Commit pushed
+    pub commit: String,
+    /// Path within the sync repo
+    pub path: String,
+    /// Session the file belongs to (session files only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// UUIDs of the entries the remote didn't have yet
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub uuids: Vec<String>,
+}
+
+/// The append-only audit log
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Open the audit log in the state directory
+    pub fn open() -> Result<Self> {
+        Ok(Self::at(ConfigManager::audit_log_path()?))
+    }
+
+    /// Open an audit log at `path`
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Append records to the log
+    pub fn append(&self, records: &[AuditRecord]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .with_context(|| format!("Failed to append to {}", self.path.display()))
+    }
+
+    /// All records, oldest first
+    pub fn records(&self) -> Result<Vec<AuditRecord>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", self.path.display()))
+            }
+        };
+        let mut records = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(record) => records.push(record),
+                Err(e) => log::warn!(
+                    "Skipping unreadable line {} of {}: {}",
+                    number + 1,
+                    self.path.display(),
+                    e
+                ),
+            }
+        }
+        Ok(records)
+    }
+}
+
+/// Push `branch` to `remote` and record what it sent in the audit log
+///
+/// `base_branch` is the remote branch the push builds on: `branch` itself,
+/// or the sync branch when pushing a new temp branch. A push that went
+/// through is never failed because the log couldn't be written.
+pub fn push_audited(repo: &dyn Scm, remote: &str, branch: &str, base_branch: &str) -> Result<()> {
+    let outgoing = outgoing_records(repo, remote, branch, base_branch);
+    repo.push(remote, branch)?;
+    if let Err(e) = outgoing.and_then(|records| AuditLog::open()?.append(&records)) {
+        log::warn!(
+            "Failed to record push to {}/{} in the audit log: {}",
+            remote,
+            branch,
+            e
+        );
+        println!(
+            "  {} Could not record push to {}/{} in the audit log: {}",
+            "!".yellow().bold(),
+            remote,
+            branch,
+            e
+        );
+    }
+    Ok(())
+}
+
+/// Records for the files the current commit would send to `remote`
+fn outgoing_records(
+    repo: &dyn Scm,
+    remote: &str,
+    branch: &str,
+    base_branch: &str,
+) -> Result<Vec<AuditRecord>> {
+    let commit = repo.current_commit_hash()?;
+    // No common ancestor (e.g. the remote is empty): everything is new
+    let base = repo
+        .merge_base(&format!("{remote}/{base_branch}"), &commit)
+        .ok();
+    let paths = match &base {
+        Some(base) => repo.changed_files(base, &commit)?,
+        None => repo.list_files_at(&commit, ".")?,
+    };
+
+    let pushed_at = Utc::now();
+    let mut records = Vec::new();
+    for path in paths {
+        // Removed files send nothing
+        let Ok(content) = repo.read_file_at(&commit, &path) else {
+            continue;
+        };
+        let uuids = if path.ends_with(".jsonl") {
+            let known: HashSet<String> = base
+                .as_ref()
+                .and_then(|base| repo.read_file_at(base, &path).ok())
+                .map(|old| entry_uuids(&old).collect())
+                .unwrap_or_default();
+            entry_uuids(&content)
+                .filter(|uuid| !known.contains(uuid))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        records.push(AuditRecord {
+            pushed_at,
+            remote: remote.to_string(),
+            branch: branch.to_string(),
+            commit: commit.clone(),
+            session_id: file_session_id(&path),
+            path,
+            uuids,
+        });
+    }
+    Ok(records)
+}
+
+/// UUIDs of the entries in a JSONL file
+fn entry_uuids(content: &[u8]) -> impl Iterator<Item = String> + '_ {
+    content
+        .split(|&b| b == b'\n')
+        .filter_map(|line| serde_json::from_slice::<serde_json::Value>(line).ok())
+        .filter_map(|entry| entry.get("uuid")?.as_str().map(|s| s.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::MockScm;
+
+    #[test]
+    fn test_push_audited_records_only_new_files_and_entries() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let log = AuditLog::at(temp.path().join("audit.jsonl"));
+        let repo = MockScm::new();
+        repo.add_remote("origin", "mock://origin").unwrap();
+        repo.write_file("p/s1.jsonl", "{\"uuid\":\"a\"}\n");
+        repo.write_file("tags.json", "{}\n");
+        repo.stage_all().unwrap();
+        repo.commit("first").unwrap();
+
+        let first = outgoing_records(&repo, "origin", "main", "main").unwrap();
+        let paths: Vec<_> = first.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, ["p/s1.jsonl", "tags.json"]);
+        assert_eq!(first[0].session_id.as_deref(), Some("s1"));
+        assert_eq!(first[0].uuids, ["a"]);
+        assert!(first[1].session_id.is_none() && first[1].uuids.is_empty());
+        log.append(&first).unwrap();
+        repo.push("origin", "main").unwrap();
+
+        repo.write_file("p/s1.jsonl", "{\"uuid\":\"a\"}\n{\"uuid\":\"b\"}\n");
+        repo.stage_all().unwrap();
+        repo.commit("second").unwrap();
+        let second = outgoing_records(&repo, "origin", "main", "main").unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].uuids, ["b"]);
+        log.append(&second).unwrap();
+
+        let records = log.records().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2], second[0]);
+    }
+}
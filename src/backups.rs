@@ -0,0 +1,115 @@
+//! Backups of local session files before they are overwritten
+//!
+//! Any pull step that replaces an existing file in `~/.claude` (a `KeepRemote`
+//! resolution, a smart merge written back locally, a teammate's session
+//! mirrored over a local copy) first copies the current file into
+//! `backups/<timestamp>/` under the config directory. Each pull gets one
+//! timestamped directory, only created if something was backed up, and only
+//! the most recent [`MAX_BACKUP_RUNS`] directories are kept.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ConfigManager;
+
+/// Number of backup directories (one per pull) kept before the oldest are pruned
+pub const MAX_BACKUP_RUNS: usize = 20;
+
+/// Backup directory for the current operation (not created until first used)
+pub fn new_run_dir() -> Result<PathBuf> {
+    let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    Ok(ConfigManager::backups_dir()?.join(timestamp.to_string()))
+}
+
+/// Copy `local_path` into `run_dir` before it gets overwritten
+///
+/// Returns `false` (and copies nothing) when the file doesn't exist yet.
+pub fn backup_file(run_dir: &Path, local_path: &Path, relative_path: &Path) -> Result<bool> {
+    if !local_path.exists() {
+        return Ok(false);
+    }
+    let dest = run_dir.join(relative_path);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::copy(local_path, &dest).with_context(|| {
+        format!(
+            "Failed to back up {} to {}",
+            local_path.display(),
+            dest.display()
+        )
+    })?;
+    log::debug!("Backed up {} to {}", local_path.display(), dest.display());
+    Ok(true)
+}
+
+/// Prune old backups and return `run_dir` if anything was backed up into it
+pub fn finish_run(run_dir: &Path) -> Option<PathBuf> {
+    if let Some(backups_dir) = run_dir.parent() {
+        if let Err(e) = prune(backups_dir, MAX_BACKUP_RUNS) {
+            log::warn!("Failed to prune old backups: {}", e);
+        }
+    }
+    run_dir.exists().then(|| run_dir.to_path_buf())
+}
+
+/// Remove all but the newest `keep` backup directories
+///
+/// Directory names are timestamps, so name order is age order. Returns the
+/// number of directories removed.
+pub fn prune(backups_dir: &Path, keep: usize) -> Result<usize> {
+    if !backups_dir.exists() {
+        return Ok(0);
+    }
+    let mut runs: Vec<PathBuf> = fs::read_dir(backups_dir)
+        .with_context(|| format!("Failed to read {}", backups_dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    runs.sort();
+
+    let excess = runs.len().saturating_sub(keep);
+    for run in &runs[..excess] {
+        fs::remove_dir_all(run)
+            .with_context(|| format!("Failed to remove old backup {}", run.display()))?;
+    }
+    Ok(excess)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backup_file() {
+        let temp = TempDir::new().unwrap();
+        let run = temp.path().join("backups/20250101-000000");
+        let local = temp.path().join("s.jsonl");
+        let relative = Path::new("-proj/s.jsonl");
+
+        assert!(!backup_file(&run, &local, relative).unwrap());
+        assert!(!run.exists());
+
+        fs::write(&local, "{}\n").unwrap();
+        assert!(backup_file(&run, &local, relative).unwrap());
+        assert_eq!(fs::read_to_string(run.join(relative)).unwrap(), "{}\n");
+    }
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let temp = TempDir::new().unwrap();
+        for name in ["20250103-000000", "20250101-000000", "20250102-000000"] {
+            fs::create_dir_all(temp.path().join(name)).unwrap();
+        }
+
+        assert_eq!(prune(temp.path(), 2).unwrap(), 1);
+        assert!(!temp.path().join("20250101-000000").exists());
+        assert!(temp.path().join("20250102-000000").exists());
+        assert!(temp.path().join("20250103-000000").exists());
+        assert_eq!(prune(temp.path(), 2).unwrap(), 0);
+    }
+}
@@ -4,6 +4,7 @@
 //! (same sessionId + timestamp pairs).
 
 use anyhow::{Context, Result};
+use claude_code_sync::text::{short_id, truncate_to_width, Keep};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{BufRead, BufReader};
@@ -202,10 +203,10 @@ fn main() -> Result<()> {
             println!();
             println!("=== Entries only in {} (first 10) ===", host1_name);
             for entry in host1_only.iter().take(10) {
-                let display_truncated: String = entry.display.chars().take(50).collect();
+                let display_truncated = truncate_to_width(&entry.display, 50, Keep::Start);
                 println!(
                     "  {} | {} | {}",
-                    &entry.session_id[..8],
+                    short_id(&entry.session_id, 8),
                     entry.timestamp,
                     display_truncated
                 );
@@ -219,10 +220,10 @@ fn main() -> Result<()> {
             println!();
             println!("=== Entries only in {} (first 10) ===", host2_name);
             for entry in host2_only.iter().take(10) {
-                let display_truncated: String = entry.display.chars().take(50).collect();
+                let display_truncated = truncate_to_width(&entry.display, 50, Keep::Start);
                 println!(
                     "  {} | {} | {}",
-                    &entry.session_id[..8],
+                    short_id(&entry.session_id, 8),
                     entry.timestamp,
                     display_truncated
                 );
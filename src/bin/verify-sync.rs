@@ -2,8 +2,19 @@
 //!
 //! Compares session files to ensure they're identical or one is a prefix of the other
 //! (same entries, just one has more recent messages appended).
-
-use anyhow::{Context, Result};
+//!
+//! With `--against <REV>` (e.g. `origin/main`), a local projects directory is
+//! compared with the sync repo's tree at that revision, read through the SCM
+//! layer after fetching, so the remote doesn't need to be checked out.
+//!
+//! With `--manifest`, the sync repo's files (at `HEAD`, or the `--against`
+//! revision) are checked against its checksum manifest instead.
+
+use anyhow::{bail, Context, Result};
+use claude_code_sync::filter::FilterConfig;
+use claude_code_sync::manifest;
+use claude_code_sync::scm;
+use claude_code_sync::sync::{claude_projects_dir, SyncState};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -68,8 +79,53 @@ fn discover_sessions(base_path: &Path) -> Result<HashMap<String, SessionInfo>> {
     Ok(sessions)
 }
 
+/// Read sessions from the sync repo's tree at `rev`, fetching its remote first
+fn discover_sessions_at_revision(
+    repo_path: &Path,
+    rev: &str,
+) -> Result<HashMap<String, SessionInfo>> {
+    let repo = scm::open(repo_path)?;
+    if let Some((remote, _)) = rev.split_once('/') {
+        if repo.has_remote(remote) {
+            repo.fetch(remote)
+                .with_context(|| format!("Failed to fetch from {remote}"))?;
+        }
+    }
+
+    let subdir = FilterConfig::load()?
+        .projects_subdir()
+        .to_string_lossy()
+        .replace('\\', "/");
+    let prefix = format!("{}/", subdir.trim_end_matches('/'));
+
+    let mut sessions = HashMap::new();
+    for file in repo.list_files_at(rev, &subdir)? {
+        if !file.ends_with(".jsonl") {
+            continue;
+        }
+        let relative_path = file.strip_prefix(&prefix).unwrap_or(&file).to_string();
+        let content = repo.read_file_at(rev, &file)?;
+        let (entry_count, uuids) = parse_uuids(&String::from_utf8_lossy(&content));
+        sessions.insert(
+            relative_path.clone(),
+            SessionInfo {
+                path: PathBuf::from(&file),
+                relative_path,
+                entry_count,
+                uuids,
+            },
+        );
+    }
+
+    Ok(sessions)
+}
+
 fn parse_session_uuids(path: &Path) -> Result<(usize, Vec<String>)> {
     let content = fs::read_to_string(path).context("Failed to read file")?;
+    Ok(parse_uuids(&content))
+}
+
+fn parse_uuids(content: &str) -> (usize, Vec<String>) {
     let mut uuids = Vec::new();
     let mut entry_count = 0;
 
@@ -87,7 +143,7 @@ fn parse_session_uuids(path: &Path) -> Result<(usize, Vec<String>)> {
         }
     }
 
-    Ok((entry_count, uuids))
+    (entry_count, uuids)
 }
 
 /// Check if vec1 is a prefix of vec2
@@ -154,43 +210,191 @@ fn find_divergence_point(uuids1: &[String], uuids2: &[String]) -> usize {
         .unwrap_or(uuids1.len().min(uuids2.len()))
 }
 
-fn main() -> Result<()> {
-    let args: Vec<String> = std::env::args().collect();
-
-    if args.len() != 3 {
-        eprintln!("Usage: verify-sync <path1> <path2>");
-        eprintln!();
-        eprintln!("Compares two .claude/projects directories to verify sync status.");
-        eprintln!("Sessions should be identical or one should be a prefix of the other.");
-        eprintln!();
-        eprintln!("Example:");
-        eprintln!("  verify-sync /tmp/arm-claude /tmp/x86-claude");
+/// What to compare, from the command line
+enum Target {
+    /// Two local directories
+    Directories(PathBuf, PathBuf),
+    /// A local directory against the sync repo at a revision
+    Revision {
+        local: PathBuf,
+        repo: Option<PathBuf>,
+        rev: String,
+    },
+    /// The sync repo at a revision against its checksum manifest
+    Manifest { repo: Option<PathBuf>, rev: String },
+}
+
+fn parse_args(args: &[String]) -> Result<Target> {
+    let mut against = None;
+    let mut repo = None;
+    let mut manifest = false;
+    let mut positional = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--against" => against = Some(iter.next().context("--against requires a revision")?.clone()),
+            "--repo" => repo = Some(PathBuf::from(iter.next().context("--repo requires a path")?)),
+            "--manifest" => manifest = true,
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    if manifest {
+        if !positional.is_empty() {
+            bail!("invalid arguments");
+        }
+        return Ok(Target::Manifest {
+            repo,
+            rev: against.unwrap_or_else(|| "HEAD".to_string()),
+        });
+    }
+
+    match (against, positional.len()) {
+        (Some(rev), 0 | 1) => Ok(Target::Revision {
+            local: match positional.pop() {
+                Some(path) => path,
+                None => claude_projects_dir()?,
+            },
+            repo,
+            rev,
+        }),
+        (None, 2) if repo.is_none() => {
+            let path2 = positional.pop().unwrap_or_default();
+            let path1 = positional.pop().unwrap_or_default();
+            Ok(Target::Directories(path1, path2))
+        }
+        _ => bail!("invalid arguments"),
+    }
+}
+
+/// Check the sync repo at `rev` against its checksum manifest, exiting with
+/// an error code if any file is missing or doesn't match
+fn verify_manifest(repo: Option<PathBuf>, rev: &str) -> Result<()> {
+    let repo_path = match repo {
+        Some(repo) => repo,
+        None => SyncState::load()?.sync_repo_path,
+    };
+    let repo = scm::open(&repo_path)?;
+    if let Some((remote, _)) = rev.split_once('/') {
+        if repo.has_remote(remote) {
+            repo.fetch(remote)
+                .with_context(|| format!("Failed to fetch from {remote}"))?;
+        }
+    }
+
+    println!("=== Checksum Manifest Verification ===");
+    println!();
+    println!("Checking {} in {}...", rev, repo_path.display());
+    let Some(report) = manifest::verify_at(repo.as_ref(), rev, None)? else {
+        bail!("{} has no {}", rev, manifest::MANIFEST_FILE);
+    };
+
+    println!();
+    println!("Results:");
+    println!("  ✓ Intact:     {}", report.checked - report.mismatched.len());
+    println!("  ✗ Mismatched: {}", report.mismatched.len());
+    println!("  ✗ Missing:    {}", report.missing.len());
+    println!("  ◦ Unlisted:   {}", report.unlisted.len());
+    for path in &report.mismatched {
+        println!("    mismatched: {}", path);
+    }
+    for path in &report.missing {
+        println!("    missing:    {}", path);
+    }
+    for path in &report.unlisted {
+        println!("    unlisted:   {}", path);
+    }
+    println!();
+
+    if report.is_ok() {
+        println!("✅ All files listed in the manifest are intact");
+        Ok(())
+    } else {
+        println!("⚠️  The sync repo does not match its checksum manifest!");
         std::process::exit(1);
     }
+}
 
-    let path1 = PathBuf::from(&args[1]);
-    let path2 = PathBuf::from(&args[2]);
+fn print_usage() {
+    eprintln!("Usage: verify-sync <path1> <path2>");
+    eprintln!("       verify-sync --against <REV> [--repo <SYNC_REPO>] [path]");
+    eprintln!("       verify-sync --manifest [--against <REV>] [--repo <SYNC_REPO>]");
+    eprintln!();
+    eprintln!("Compares two .claude/projects directories to verify sync status.");
+    eprintln!("Sessions should be identical or one should be a prefix of the other.");
+    eprintln!();
+    eprintln!("With --against, compares a local projects directory (default: the");
+    eprintln!("configured one) with the sync repo's tree at REV, fetching first.");
+    eprintln!();
+    eprintln!("With --manifest, checks the sync repo's files at REV (default: HEAD)");
+    eprintln!("against its checksum manifest.");
+    eprintln!();
+    eprintln!("Examples:");
+    eprintln!("  verify-sync /tmp/arm-claude /tmp/x86-claude");
+    eprintln!("  verify-sync --against origin/main");
+    eprintln!("  verify-sync --manifest --against origin/main");
+}
 
-    // Extract host names from paths for display
-    let host1_name = path1
-        .file_name()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_else(|| "host1".to_string());
-    let host2_name = path2
-        .file_name()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_else(|| "host2".to_string());
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let target = match parse_args(&args) {
+        Ok(target) => target,
+        Err(_) => {
+            print_usage();
+            std::process::exit(1);
+        }
+    };
+
+    let dir_name = |path: &Path, fallback: &str| {
+        path.file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| fallback.to_string())
+    };
+
+    if let Target::Manifest { repo, rev } = target {
+        return verify_manifest(repo, &rev);
+    }
 
     println!("=== Claude Code Session Sync Verification ===");
     println!();
 
-    println!("Scanning {}...", path1.display());
-    let host1_sessions = discover_sessions(&path1)?;
-    println!("  Found {} sessions", host1_sessions.len());
-
-    println!("Scanning {}...", path2.display());
-    let host2_sessions = discover_sessions(&path2)?;
-    println!("  Found {} sessions", host2_sessions.len());
+    let (host1_name, host1_sessions, host2_name, host2_sessions) = match target {
+        Target::Directories(path1, path2) => {
+            println!("Scanning {}...", path1.display());
+            let host1_sessions = discover_sessions(&path1)?;
+            println!("  Found {} sessions", host1_sessions.len());
+
+            println!("Scanning {}...", path2.display());
+            let host2_sessions = discover_sessions(&path2)?;
+            println!("  Found {} sessions", host2_sessions.len());
+
+            // Extract host names from paths for display
+            (
+                dir_name(&path1, "host1"),
+                host1_sessions,
+                dir_name(&path2, "host2"),
+                host2_sessions,
+            )
+        }
+        Target::Revision { local, repo, rev } => {
+            println!("Scanning {}...", local.display());
+            let local_sessions = discover_sessions(&local)?;
+            println!("  Found {} sessions", local_sessions.len());
+
+            let repo = match repo {
+                Some(repo) => repo,
+                None => SyncState::load()?.sync_repo_path,
+            };
+            println!("Reading {} in {}...", rev, repo.display());
+            let remote_sessions = discover_sessions_at_revision(&repo, &rev)?;
+            println!("  Found {} sessions", remote_sessions.len());
+
+            ("local".to_string(), local_sessions, rev, remote_sessions)
+        }
+        Target::Manifest { .. } => unreachable!("handled above"),
+    };
 
     println!();
     println!("=== Comparing Sessions ===");
@@ -0,0 +1,156 @@
+//! Per-entry provenance of a session in the sync repo
+//!
+//! Walks the sync repo history of one session's files, continuation files
+//! included, and finds for each entry UUID the commit that first contained
+//! it. Pulls commit their merge as "Merge local changes from
+//! sync-local-<machine>-...", so for those commits the machine is known;
+//! for others only the commit author is.
+
+use anyhow::Result;
+use std::collections::HashSet;
+
+use crate::purge::file_session_id;
+use crate::scm::{CommitInfo, Scm};
+use crate::sync::temp_branch_machine;
+
+/// The commit that introduced one entry of a session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryOrigin {
+    pub uuid: String,
+    /// Entry type (`user`, `assistant`, ...)
+    pub entry_type: String,
+    /// The entry's own timestamp, if it has one
+    pub timestamp: Option<String>,
+    pub commit: CommitInfo,
+    /// Machine whose pull merged the entry, when the commit says
+    pub machine: Option<String>,
+    /// Whether the entry is still in the session at HEAD
+    pub present: bool,
+}
+
+/// Paths of a session's files anywhere in the current branch's history
+pub fn session_files_in_history(repo: &dyn Scm, session_id: &str) -> Result<Vec<String>> {
+    Ok(repo
+        .list_files_in_history()?
+        .into_iter()
+        .filter(|path| file_session_id(path).as_deref() == Some(session_id))
+        .collect())
+}
+
+/// Machine named by a pull's merge commit summary
+pub fn commit_machine(summary: &str) -> Option<String> {
+    let branch = summary
+        .strip_prefix("Merge local changes from ")?
+        .split_whitespace()
+        .next()?;
+    temp_branch_machine(branch).map(|m| m.to_string())
+}
+
+/// Origin of every entry a session ever had, in the order they appeared
+pub fn blame_session(repo: &dyn Scm, session_id: &str) -> Result<Vec<EntryOrigin>> {
+    let paths = session_files_in_history(repo, session_id)?;
+    let mut origins = Vec::new();
+    let mut seen = HashSet::new();
+
+    for commit in repo.log_paths(&paths)? {
+        for path in &paths {
+            // Files absent from this commit contribute nothing
+            let Ok(content) = repo.read_file_at(&commit.id, path) else {
+                continue;
+            };
+            for entry in entries(&content) {
+                let Some(uuid) = entry.get("uuid").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                if !seen.insert(uuid.to_string()) {
+                    continue;
+                }
+                origins.push(EntryOrigin {
+                    uuid: uuid.to_string(),
+                    entry_type: entry
+                        .get("type")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    timestamp: entry
+                        .get("timestamp")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string()),
+                    machine: commit_machine(&commit.summary),
+                    commit: commit.clone(),
+                    present: false,
+                });
+            }
+        }
+    }
+
+    let current: HashSet<String> = paths
+        .iter()
+        .filter_map(|path| repo.read_file_at("HEAD", path).ok())
+        .flat_map(|content| {
+            entries(&content)
+                .filter_map(|entry| Some(entry.get("uuid")?.as_str()?.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    for origin in &mut origins {
+        origin.present = current.contains(&origin.uuid);
+    }
+    Ok(origins)
+}
+
+/// Parsed entries of a JSONL file, skipping unreadable lines
+fn entries(content: &[u8]) -> impl Iterator<Item = serde_json::Value> + '_ {
+    content
+        .split(|&b| b == b'\n')
+        .filter_map(|line| serde_json::from_slice(line).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::MockScm;
+
+    fn entry(uuid: &str) -> String {
+        format!(
+            "{{\"type\":\"user\",\"uuid\":\"{uuid}\",\"timestamp\":\"2025-01-01T00:00:00Z\"}}\n"
+        )
+    }
+
+    #[test]
+    fn test_blame_session_finds_introducing_commits() {
+        let repo = MockScm::new();
+        repo.write_file("projects/p/s1.jsonl", entry("a"));
+        repo.write_file("projects/p/other.jsonl", entry("x"));
+        repo.stage_all().unwrap();
+        repo.commit("Sync at 2025-01-01").unwrap();
+
+        repo.write_file("projects/p/s1.jsonl", entry("a") + &entry("b"));
+        repo.write_file("projects/p/s1.part01.jsonl", entry("c"));
+        repo.stage_all().unwrap();
+        repo.commit(
+            "Merge local changes from sync-local-laptop-1a2b3c4d-20250102-120000-a1b2c3 (2025-01-02)",
+        )
+        .unwrap();
+
+        repo.write_file("projects/p/s1.jsonl", entry("b"));
+        repo.stage_all().unwrap();
+        repo.commit("Drop a").unwrap();
+
+        let origins = blame_session(&repo, "s1").unwrap();
+        let summary: Vec<_> = origins
+            .iter()
+            .map(|o| (o.uuid.as_str(), o.machine.as_deref(), o.present))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("a", None, false),
+                ("b", Some("laptop-1a2b3c4d"), true),
+                ("c", Some("laptop-1a2b3c4d"), true),
+            ]
+        );
+        assert_eq!(origins[0].commit.summary, "Sync at 2025-01-01");
+        assert_eq!(origins[1].entry_type, "user");
+    }
+}
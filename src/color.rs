@@ -0,0 +1,102 @@
+//! Terminal color control
+//!
+//! `colored` styling is turned off when `--no-color` is passed, when
+//! `NO_COLOR` is set to a non-empty value (<https://no-color.org>), or when
+//! stdout is not a terminal (e.g. output redirected to a log file). The
+//! `color` setting in `config.toml` can force it on or off instead; only
+//! `--no-color` overrides that.
+
+use std::ffi::OsString;
+use std::io::IsTerminal;
+
+use crate::filter::FilterConfig;
+
+/// The `color` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and `NO_COLOR` isn't set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Parse a color setting from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        }
+    }
+}
+
+/// Whether colored output should be used
+pub fn colors_enabled(
+    no_color_flag: bool,
+    choice: ColorChoice,
+    no_color_env: Option<OsString>,
+    stdout_is_tty: bool,
+) -> bool {
+    if no_color_flag {
+        return false;
+    }
+    match choice {
+        ColorChoice::Always => return true,
+        ColorChoice::Never => return false,
+        ColorChoice::Auto => {}
+    }
+    if no_color_env.is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    stdout_is_tty
+}
+
+/// Apply the color setting for this process
+///
+/// Must run before any styled output is printed.
+pub fn configure(no_color_flag: bool) {
+    // A config that can't be read is reported by the command itself
+    let choice = FilterConfig::load()
+        .ok()
+        .and_then(|config| config.color().ok())
+        .unwrap_or_default();
+    let enabled = colors_enabled(
+        no_color_flag,
+        choice,
+        std::env::var_os("NO_COLOR"),
+        std::io::stdout().is_terminal(),
+    );
+    colored::control::set_override(enabled);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colors_enabled() {
+        let auto = ColorChoice::Auto;
+        assert!(colors_enabled(false, auto, None, true));
+        assert!(!colors_enabled(true, auto, None, true));
+        assert!(!colors_enabled(false, auto, Some("1".into()), true));
+        // An empty NO_COLOR does not disable colors
+        assert!(colors_enabled(false, auto, Some("".into()), true));
+        assert!(!colors_enabled(false, auto, None, false));
+
+        // The setting overrides NO_COLOR and terminal detection, but not the flag
+        assert!(colors_enabled(false, ColorChoice::Always, Some("1".into()), false));
+        assert!(!colors_enabled(true, ColorChoice::Always, None, true));
+        assert!(!colors_enabled(false, ColorChoice::Never, None, true));
+    }
+}
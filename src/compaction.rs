@@ -0,0 +1,213 @@
+//! Conversation compaction
+//!
+//! Old sessions are often dominated by a handful of huge payloads: tool results
+//! that dumped whole files or command output, and `file-history-snapshot`
+//! entries. Compaction replaces payloads above a size threshold with small
+//! placeholders before a session is saved to the sync repo. Entry UUIDs,
+//! parent links, types, and timestamps are left untouched so merging and
+//! deduplication behave exactly as before.
+//!
+//! Compaction only affects the copy in the sync repo; the local session file
+//! is never rewritten. Originals can additionally be kept in a local-only
+//! archive (see [`archive_original`]).
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ConfigManager;
+use crate::parser::{ConversationEntry, ConversationSession};
+
+/// Counts from compacting a session
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionStats {
+    /// Entries that had at least one payload replaced
+    pub entries_compacted: usize,
+    /// Approximate bytes removed (serialized payload size minus placeholder)
+    pub bytes_saved: usize,
+}
+
+/// Check whether a session's latest activity is older than `days`
+///
+/// Sessions without any parseable timestamp are treated as not old.
+pub fn is_older_than(session: &ConversationSession, days: u32) -> bool {
+    let Some(latest) = session.latest_timestamp() else {
+        return false;
+    };
+    let Ok(latest) = chrono::DateTime::parse_from_rfc3339(&latest) else {
+        return false;
+    };
+    let age = chrono::Utc::now().signed_duration_since(latest.with_timezone(&chrono::Utc));
+    age > chrono::Duration::days(days as i64)
+}
+
+/// Compact a session, replacing payloads larger than `min_bytes` with placeholders
+pub fn compact_session(
+    session: &ConversationSession,
+    min_bytes: usize,
+) -> (ConversationSession, CompactionStats) {
+    let mut stats = CompactionStats::default();
+    let entries = session
+        .entries
+        .iter()
+        .map(|entry| {
+            let (entry, saved) = compact_entry(entry, min_bytes);
+            if saved > 0 {
+                stats.entries_compacted += 1;
+                stats.bytes_saved += saved;
+            }
+            entry
+        })
+        .collect();
+
+    (
+        ConversationSession {
+            session_id: session.session_id.clone(),
+            entries,
+            file_path: session.file_path.clone(),
+        },
+        stats,
+    )
+}
+
+/// Compact a single entry, returning the new entry and bytes saved
+fn compact_entry(entry: &ConversationEntry, min_bytes: usize) -> (ConversationEntry, usize) {
+    let mut compacted = entry.clone();
+    let mut saved = 0;
+
+    if let Some(extra) = compacted.extra.as_object_mut() {
+        for field in ["snapshot", "toolUseResult"] {
+            if let Some(value) = extra.get_mut(field) {
+                saved += replace_if_large(value, min_bytes);
+            }
+        }
+    }
+
+    // Tool results embedded in user message content
+    if let Some(content) = compacted
+        .message
+        .as_mut()
+        .and_then(|m| m.get_mut("content"))
+        .and_then(|c| c.as_array_mut())
+    {
+        for block in content {
+            if block.get("type").and_then(|t| t.as_str()) == Some("tool_result") {
+                if let Some(value) = block.get_mut("content") {
+                    saved += replace_if_large(value, min_bytes);
+                }
+            }
+        }
+    }
+
+    if saved > 0 {
+        // The original line no longer matches this entry
+        compacted.raw_line = None;
+        crate::parser::mark_derived(&mut compacted, entry);
+    }
+
+    (compacted, saved)
+}
+
+/// Replace `value` with a placeholder if its serialized size exceeds `min_bytes`
+fn replace_if_large(value: &mut Value, min_bytes: usize) -> usize {
+    let size = serde_json::to_string(value).map(|s| s.len()).unwrap_or(0);
+    if size <= min_bytes {
+        return 0;
+    }
+
+    let placeholder = if value.is_string() {
+        Value::String(format!("[compacted by claude-code-sync: {size} bytes]"))
+    } else {
+        json!({ "compacted": true, "originalBytes": size })
+    };
+    let placeholder_size = serde_json::to_string(&placeholder).map(|s| s.len()).unwrap_or(0);
+    *value = placeholder;
+    size.saturating_sub(placeholder_size)
+}
+
+/// Copy an uncompacted session file into the local-only compaction archive
+///
+/// The archive lives in the config directory (never in the sync repo), under
+/// the same relative path the session has in the projects directory.
+pub fn archive_original(source: &Path, relative_path: &Path) -> Result<PathBuf> {
+    let dest = ConfigManager::compaction_archive_dir()?.join(relative_path);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::copy(source, &dest).with_context(|| {
+        format!(
+            "Failed to archive {} to {}",
+            source.display(),
+            dest.display()
+        )
+    })?;
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(line: &str) -> ConversationEntry {
+        let mut entry: ConversationEntry = serde_json::from_str(line).unwrap();
+        entry.raw_line = Some(line.to_string());
+        entry
+    }
+
+    #[test]
+    fn test_compacts_large_payloads_only() {
+        let big = "x".repeat(500);
+        let session = ConversationSession {
+            session_id: "s".to_string(),
+            entries: vec![
+                entry(&format!(
+                    r#"{{"type":"user","uuid":"1","toolUseResult":{{"stdout":"{big}"}},"message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"t","content":"{big}"}}]}}}}"#
+                )),
+                entry(r#"{"type":"assistant","uuid":"2","message":{"content":"small"}}"#),
+                entry(&format!(
+                    r#"{{"type":"file-history-snapshot","messageId":"1","snapshot":{{"data":"{big}"}}}}"#
+                )),
+            ],
+            file_path: "s.jsonl".to_string(),
+        };
+
+        let (compacted, stats) = compact_session(&session, 100);
+        assert_eq!(stats.entries_compacted, 2);
+        assert!(stats.bytes_saved > 1000);
+
+        let first = &compacted.entries[0];
+        assert_eq!(first.uuid.as_deref(), Some("1"));
+        assert_eq!(first.extra["toolUseResult"]["compacted"], true);
+        assert!(first.message.as_ref().unwrap()["content"][0]["content"]
+            .as_str()
+            .unwrap()
+            .starts_with("[compacted"));
+        assert!(first.raw_line.is_none());
+        assert!(crate::parser::is_derived_from(first, &session.entries[0]));
+
+        // Small entries are untouched, including their raw line
+        assert!(compacted.entries[1].raw_line.is_some());
+        assert!(crate::parser::original_hash(&compacted.entries[1]).is_none());
+        assert_eq!(compacted.entries[2].extra["snapshot"]["compacted"], true);
+    }
+
+    #[test]
+    fn test_is_older_than() {
+        let old = ConversationSession {
+            session_id: "s".to_string(),
+            entries: vec![entry(
+                r#"{"type":"user","uuid":"1","timestamp":"2020-01-01T00:00:00.000Z"}"#,
+            )],
+            file_path: "s.jsonl".to_string(),
+        };
+        assert!(is_older_than(&old, 30));
+
+        let undated = ConversationSession {
+            entries: vec![entry(r#"{"type":"user","uuid":"1"}"#)],
+            ..old
+        };
+        assert!(!is_older_than(&undated, 30));
+    }
+}
@@ -1,11 +1,45 @@
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Environment variable to override the config directory location.
 /// Useful for testing and automation.
 pub const CONFIG_DIR_ENV_VAR: &str = "CLAUDE_CODE_SYNC_CONFIG_DIR";
 
+/// Files and directories that used to live in the config directory and now
+/// belong in the state directory
+const STATE_ENTRIES: &[&str] = &[
+    "state.json",
+    "state.json.bak",
+    "operation-history.json",
+    "machine-id",
+    "latest-conflict-report.json",
+    "claude-code-sync.log",
+    "claude-code-sync.log.old",
+    "snapshots",
+    "backups",
+    "quarantine",
+    "compaction-archive",
+    "conflict-reports",
+    ".trash",
+];
+
+/// Entries that used to live in the config directory and now belong in the
+/// cache directory
+const CACHE_ENTRIES: &[&str] = &["subscriptions"];
+
 /// Cross-platform configuration directory manager
+///
+/// Files are split by how they are used, following the XDG base directory
+/// spec on Linux and the platform conventions elsewhere:
+/// - config ([`Self::config_dir`]): settings the user edits or would copy to
+///   another machine (`config.toml`, `init.toml`, `subscriptions.toml`)
+/// - state ([`Self::state_dir`]): data the tool keeps between runs
+///   (`state.json`, operation history, locks, logs, backups)
+/// - cache ([`Self::cache_dir`]): data that can be rebuilt, such as clones of
+///   subscribed repositories
+///
+/// The default sync repository clone stays in the config directory, where
+/// existing `state.json` files point to it.
 pub struct ConfigManager;
 
 impl ConfigManager {
@@ -60,9 +94,116 @@ impl ConfigManager {
         }
     }
 
+    /// Get the state directory path.
+    ///
+    /// If `CLAUDE_CODE_SYNC_CONFIG_DIR` is set, uses that path directly.
+    /// Otherwise follows platform conventions:
+    /// - Linux: $XDG_STATE_HOME/claude-code-sync or ~/.local/state/claude-code-sync
+    /// - macOS: ~/Library/Application Support/claude-code-sync (same as config)
+    /// - Windows: %LOCALAPPDATA%\claude-code-sync
+    pub fn state_dir() -> Result<PathBuf> {
+        if std::env::var_os(CONFIG_DIR_ENV_VAR).is_some() {
+            return Self::config_dir();
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(xdg_state) = std::env::var("XDG_STATE_HOME") {
+                Ok(PathBuf::from(xdg_state).join("claude-code-sync"))
+            } else {
+                let home = dirs::home_dir().context("Failed to get home directory")?;
+                Ok(home.join(".local").join("state").join("claude-code-sync"))
+            }
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            Ok(dirs::data_local_dir()
+                .context("Failed to get Windows local data directory")?
+                .join("claude-code-sync"))
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+        {
+            Self::config_dir()
+        }
+    }
+
+    /// Get the cache directory path.
+    ///
+    /// If `CLAUDE_CODE_SYNC_CONFIG_DIR` is set, uses that path directly.
+    /// Otherwise follows platform conventions:
+    /// - Linux: $XDG_CACHE_HOME/claude-code-sync or ~/.cache/claude-code-sync
+    /// - macOS: ~/Library/Caches/claude-code-sync
+    /// - Windows: %LOCALAPPDATA%\claude-code-sync\cache
+    pub fn cache_dir() -> Result<PathBuf> {
+        if std::env::var_os(CONFIG_DIR_ENV_VAR).is_some() {
+            return Self::config_dir();
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            if let Ok(xdg_cache) = std::env::var("XDG_CACHE_HOME") {
+                Ok(PathBuf::from(xdg_cache).join("claude-code-sync"))
+            } else {
+                let home = dirs::home_dir().context("Failed to get home directory")?;
+                Ok(home.join(".cache").join("claude-code-sync"))
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let home = dirs::home_dir().context("Failed to get home directory")?;
+            Ok(home.join("Library").join("Caches").join("claude-code-sync"))
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            Ok(Self::state_dir()?.join("cache"))
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+        {
+            Ok(Self::config_dir()?.join("cache"))
+        }
+    }
+
+    /// Move state and cache files left in the config directory by older
+    /// versions to their current locations
+    ///
+    /// Entries that already exist at the new location are left alone.
+    /// Returns the number of entries moved.
+    pub fn migrate_layout() -> Result<usize> {
+        let config_dir = Self::config_dir()?;
+        let mut moved = 0;
+        for (entries, dest_dir) in [
+            (STATE_ENTRIES, Self::state_dir()?),
+            (CACHE_ENTRIES, Self::cache_dir()?),
+        ] {
+            if dest_dir == config_dir {
+                continue;
+            }
+            for name in entries {
+                let from = config_dir.join(name);
+                let to = dest_dir.join(name);
+                if from.symlink_metadata().is_err() || to.symlink_metadata().is_ok() {
+                    continue;
+                }
+                std::fs::create_dir_all(&dest_dir).with_context(|| {
+                    format!("Failed to create directory: {}", dest_dir.display())
+                })?;
+                move_path(&from, &to).with_context(|| {
+                    format!("Failed to move {} to {}", from.display(), to.display())
+                })?;
+                moved += 1;
+            }
+        }
+        Ok(moved)
+    }
+
     /// Get the state file path (state.json)
     pub fn state_file_path() -> Result<PathBuf> {
-        Ok(Self::config_dir()?.join("state.json"))
+        Ok(Self::state_dir()?.join("state.json"))
     }
 
     /// Get the filter config file path (config.toml)
@@ -72,12 +213,90 @@ impl ConfigManager {
 
     /// Get the operation history file path
     pub fn operation_history_path() -> Result<PathBuf> {
-        Ok(Self::config_dir()?.join("operation-history.json"))
+        Ok(Self::state_dir()?.join("operation-history.json"))
+    }
+
+    /// Get the audit log path (audit.jsonl), recording everything pushed
+    pub fn audit_log_path() -> Result<PathBuf> {
+        Ok(Self::state_dir()?.join("audit.jsonl"))
     }
 
     /// Get the snapshots directory path
     pub fn snapshots_dir() -> Result<PathBuf> {
-        Ok(Self::config_dir()?.join("snapshots"))
+        Ok(Self::state_dir()?.join("snapshots"))
+    }
+
+    /// Get the local-only archive directory for sessions compacted before sync
+    pub fn compaction_archive_dir() -> Result<PathBuf> {
+        Ok(Self::state_dir()?.join("compaction-archive"))
+    }
+
+    /// Get the directory holding backups of local sessions taken before overwrites
+    pub fn backups_dir() -> Result<PathBuf> {
+        Ok(Self::state_dir()?.join("backups"))
+    }
+
+    /// Get the directory where sessions that a sync would have shrunk are preserved
+    pub fn quarantine_dir() -> Result<PathBuf> {
+        Ok(Self::state_dir()?.join("quarantine"))
+    }
+
+    /// Get the trash directory, where cleanup operations move files instead of deleting them
+    pub fn trash_dir() -> Result<PathBuf> {
+        Ok(Self::state_dir()?.join(".trash"))
+    }
+
+    /// Get the directory holding past conflict reports and their index
+    pub fn conflict_reports_dir() -> Result<PathBuf> {
+        Ok(Self::state_dir()?.join("conflict-reports"))
+    }
+
+    /// Get the subscriptions file path (subscriptions.toml)
+    pub fn subscriptions_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("subscriptions.toml"))
+    }
+
+    /// Get the directory holding clones of subscribed repositories
+    pub fn subscriptions_dir() -> Result<PathBuf> {
+        Ok(Self::cache_dir()?.join("subscriptions"))
+    }
+
+    /// Get the machine ID file path (machine-id)
+    pub fn machine_id_path() -> Result<PathBuf> {
+        Ok(Self::state_dir()?.join("machine-id"))
+    }
+
+    /// Get this machine's stable identity, creating it on first use
+    ///
+    /// The ID is the sanitized hostname plus a short random suffix, so two
+    /// machines with the same hostname still get distinct IDs.
+    pub fn machine_id() -> Result<String> {
+        let path = Self::machine_id_path()?;
+        if let Ok(existing) = std::fs::read_to_string(&path) {
+            let existing = existing.trim();
+            if !existing.is_empty() {
+                return Ok(existing.to_string());
+            }
+        }
+
+        let hostname = std::env::var("HOSTNAME")
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .ok()
+            .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+            .unwrap_or_default();
+        let hostname: String = hostname
+            .trim()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        let hostname = if hostname.is_empty() { "machine".to_string() } else { hostname };
+        let suffix = &uuid::Uuid::new_v4().simple().to_string()[..8];
+        let id = format!("{hostname}-{suffix}");
+
+        Self::ensure_state_dir()?;
+        std::fs::write(&path, format!("{id}\n"))
+            .with_context(|| format!("Failed to write machine ID: {}", path.display()))?;
+        Ok(id)
     }
 
     /// Get the default repository clone directory
@@ -88,12 +307,12 @@ impl ConfigManager {
     /// Get the latest conflict report path
     #[allow(dead_code)]
     pub fn conflict_report_path() -> Result<PathBuf> {
-        Ok(Self::config_dir()?.join("latest-conflict-report.json"))
+        Ok(Self::state_dir()?.join("latest-conflict-report.json"))
     }
 
     /// Get the log file path
     pub fn log_file_path() -> Result<PathBuf> {
-        Ok(Self::config_dir()?.join("claude-code-sync.log"))
+        Ok(Self::state_dir()?.join("claude-code-sync.log"))
     }
 
     /// Ensure the configuration directory exists
@@ -108,6 +327,15 @@ impl ConfigManager {
         Ok(config_dir)
     }
 
+    /// Ensure the state directory exists
+    pub fn ensure_state_dir() -> Result<PathBuf> {
+        let state_dir = Self::state_dir()?;
+        std::fs::create_dir_all(&state_dir).with_context(|| {
+            format!("Failed to create state directory: {}", state_dir.display())
+        })?;
+        Ok(state_dir)
+    }
+
     /// Ensure the snapshots directory exists
     #[allow(dead_code)]
     pub fn ensure_snapshots_dir() -> Result<PathBuf> {
@@ -122,6 +350,30 @@ impl ConfigManager {
     }
 }
 
+/// Rename `from` to `to`, copying and deleting when they are on different
+/// filesystems
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if from.is_dir() {
+        for entry in walkdir::WalkDir::new(from) {
+            let entry = entry?;
+            let target = to.join(entry.path().strip_prefix(from)?);
+            if entry.file_type().is_dir() {
+                std::fs::create_dir_all(&target)?;
+            } else {
+                std::fs::copy(entry.path(), &target)?;
+            }
+        }
+        std::fs::remove_dir_all(from)?;
+    } else {
+        std::fs::copy(from, to)?;
+        std::fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,6 +412,7 @@ mod tests {
 
     #[test]
     #[cfg(target_os = "linux")]
+    #[serial_test::file_serial]
     fn test_xdg_config_home_respected() {
         // Set XDG_CONFIG_HOME and verify it's used
         std::env::set_var("XDG_CONFIG_HOME", "/tmp/test-xdg-config");
@@ -170,6 +423,46 @@ mod tests {
         std::env::remove_var("XDG_CONFIG_HOME");
     }
 
+    #[test]
+    #[cfg(target_os = "linux")]
+    #[serial_test::file_serial]
+    fn test_migrate_layout_moves_state_and_cache() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let vars = ["XDG_CONFIG_HOME", "XDG_STATE_HOME", "XDG_CACHE_HOME"];
+        for (var, dir) in vars.iter().zip(["config", "state", "cache"]) {
+            std::env::set_var(var, temp.path().join(dir));
+        }
+
+        let config_dir = ConfigManager::ensure_config_dir().unwrap();
+        std::fs::write(config_dir.join("config.toml"), "").unwrap();
+        std::fs::write(config_dir.join("state.json"), "{}").unwrap();
+        std::fs::create_dir_all(config_dir.join("backups/1")).unwrap();
+        std::fs::create_dir_all(config_dir.join("subscriptions/team")).unwrap();
+
+        assert_eq!(ConfigManager::migrate_layout().unwrap(), 3);
+        assert!(config_dir.join("config.toml").exists());
+        assert_eq!(
+            ConfigManager::state_file_path().unwrap(),
+            temp.path().join("state/claude-code-sync/state.json")
+        );
+        assert!(ConfigManager::state_file_path().unwrap().exists());
+        assert!(ConfigManager::backups_dir().unwrap().join("1").is_dir());
+        assert!(ConfigManager::subscriptions_dir().unwrap().join("team").is_dir());
+        assert!(!config_dir.join("state.json").exists());
+
+        // A file already at the new location is never overwritten
+        std::fs::write(config_dir.join("state.json"), "old").unwrap();
+        assert_eq!(ConfigManager::migrate_layout().unwrap(), 0);
+        assert_eq!(
+            std::fs::read_to_string(ConfigManager::state_file_path().unwrap()).unwrap(),
+            "{}"
+        );
+
+        for var in vars {
+            std::env::remove_var(var);
+        }
+    }
+
     #[test]
     #[cfg(target_os = "macos")]
     fn test_macos_library_path() {
@@ -1,10 +1,15 @@
 use anyhow::Result;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+use crate::filter::{ModifiedEntryPolicy, PullPrecedence};
 use crate::merge;
-use crate::parser::ConversationSession;
+use crate::parser::{
+    canonical_json, content_keys, is_derived_from, ContentKeyStrategies, ConversationEntry,
+    ConversationSession,
+};
 
 /// Represents a conflict between local and remote versions of the same conversation session.
 ///
@@ -296,6 +301,8 @@ impl Conflict {
 /// Conflict detector for conversation sessions
 pub struct ConflictDetector {
     conflicts: Vec<Conflict>,
+    /// Treat sessions with entries edited in place as conflicts
+    fork_modified_entries: bool,
 }
 
 impl ConflictDetector {
@@ -344,13 +351,23 @@ impl ConflictDetector {
     pub fn new() -> Self {
         ConflictDetector {
             conflicts: Vec::new(),
+            fork_modified_entries: false,
         }
     }
 
+    /// Report sessions with entries edited in place as conflicts, instead of
+    /// leaving them to [`reconcile_modified_entries`]
+    pub fn fork_modified_entries(mut self, fork: bool) -> Self {
+        self.fork_modified_entries = fork;
+        self
+    }
+
     /// Compare local and remote sessions and detect conflicts
     ///
     /// Only reports TRUE conflicts where both sides have diverged.
     /// Simple extensions (one side has more messages) are NOT conflicts.
+    /// Sessions are hashed and compared in parallel; conflicts are reported
+    /// in the order of `remote_sessions`.
     pub fn detect(
         &mut self,
         local_sessions: &[ConversationSession],
@@ -362,47 +379,59 @@ impl ConflictDetector {
             .map(|s| (s.session_id.clone(), s))
             .collect();
 
-        // Check each remote session against local
-        for remote in remote_sessions {
-            if let Some(local) = local_map.get(&remote.session_id) {
-                // Session exists in both - analyze relationship
-                let relationship = analyze_session_relationship(local, remote);
-
-                match relationship {
-                    SessionRelationship::Identical => {
-                        // No action needed - sessions are the same
-                    }
-                    SessionRelationship::LocalIsPrefix => {
-                        // Remote has more messages - NOT a conflict
-                        // This will be handled as a normal "Modified" copy in pull
-                        log::debug!(
-                            "Session {} is extended in remote ({} -> {} entries)",
-                            local.session_id,
-                            local.entries.len(),
-                            remote.entries.len()
-                        );
-                    }
-                    SessionRelationship::RemoteIsPrefix => {
-                        // Local has more messages - NOT a conflict
-                        // Keep local, no action needed during pull
-                        log::debug!(
-                            "Session {} is extended locally ({} -> {} entries), keeping local",
-                            local.session_id,
-                            remote.entries.len(),
-                            local.entries.len()
-                        );
-                    }
-                    SessionRelationship::Diverged => {
-                        // TRUE conflict - both have unique entries
-                        let conflict = Conflict::new(local, remote);
-                        self.conflicts.push(conflict);
-                        log::info!(
-                            "True conflict detected in session {} (local: {}, remote: {} entries)",
-                            local.session_id,
-                            local.entries.len(),
-                            remote.entries.len()
-                        );
-                    }
+        // Analyze each session that exists in both
+        let relationships: Vec<_> = remote_sessions
+            .par_iter()
+            .filter_map(|remote| {
+                let local = *local_map.get(&remote.session_id)?;
+                Some((local, remote, analyze_session_relationship(local, remote)))
+            })
+            .collect();
+
+        for (local, remote, relationship) in relationships {
+            match relationship {
+                SessionRelationship::Identical => {
+                    // No action needed - sessions are the same
+                }
+                SessionRelationship::LocalIsPrefix => {
+                    // Remote has more messages - NOT a conflict
+                    // This will be handled as a normal "Modified" copy in pull
+                    log::debug!(
+                        "Session {} is extended in remote ({} -> {} entries)",
+                        local.session_id,
+                        local.entries.len(),
+                        remote.entries.len()
+                    );
+                }
+                SessionRelationship::RemoteIsPrefix => {
+                    // Local has more messages - NOT a conflict
+                    // Keep local, no action needed during pull
+                    log::debug!(
+                        "Session {} is extended locally ({} -> {} entries), keeping local",
+                        local.session_id,
+                        remote.entries.len(),
+                        local.entries.len()
+                    );
+                }
+                SessionRelationship::EntriesModified
+                    if !self.fork_modified_entries || only_derived_copies(local, remote) =>
+                {
+                    // Edited in place - reconciled per entry, NOT a conflict
+                    log::debug!(
+                        "Session {} has entries modified in place, reconciling per entry",
+                        local.session_id
+                    );
+                }
+                SessionRelationship::EntriesModified | SessionRelationship::Diverged => {
+                    // TRUE conflict - both have unique entries
+                    let conflict = Conflict::new(local, remote);
+                    self.conflicts.push(conflict);
+                    log::info!(
+                        "True conflict detected in session {} (local: {}, remote: {} entries)",
+                        local.session_id,
+                        local.entries.len(),
+                        remote.entries.len()
+                    );
                 }
             }
         }
@@ -452,7 +481,7 @@ impl Default for ConflictDetector {
 }
 
 /// Relationship between two sessions with the same ID
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SessionRelationship {
     /// Sessions are identical (same content hash)
     Identical,
@@ -460,6 +489,9 @@ pub enum SessionRelationship {
     LocalIsPrefix,
     /// Remote is a prefix of local (local has more messages, all remote messages exist in local)
     RemoteIsPrefix,
+    /// Neither side added messages the other lacks (beyond a plain extension),
+    /// but some messages present on both sides differ in content (edited in place)
+    EntriesModified,
     /// True divergence - both have unique messages not in the other (actual conflict)
     Diverged,
 }
@@ -473,6 +505,33 @@ pub enum SessionRelationship {
 /// - This is NOT a conflict - Machine B just has more messages
 ///
 /// True conflicts only occur when BOTH sides have added different messages.
+///
+/// Sessions are compared by [`ConversationSession::content_hash`] first, then
+/// by the UUIDs of their entries; entries with the same UUID on both sides
+/// are compared by their [`canonical_json`]. Entries without a UUID only
+/// count towards the hash. This is the comparison pull uses to decide what
+/// to copy and what to merge, so tools outside the crate get the same
+/// answers from it.
+///
+/// # Examples
+///
+/// ```
+/// use claude_code_sync::{analyze_session_relationship, SessionRelationship};
+/// # use claude_code_sync::parser::{ConversationSession, SessionIdSource};
+/// # fn session(lines: &str) -> ConversationSession {
+/// #     ConversationSession::from_reader_with(lines.as_bytes(), "s1.jsonl", SessionIdSource::Entry)
+/// #         .unwrap()
+/// # }
+/// let a = r#"{"type":"user","uuid":"a","sessionId":"s1","timestamp":"2025-01-01T00:00:00Z"}"#;
+/// let b = r#"{"type":"user","uuid":"b","parentUuid":"a","sessionId":"s1","timestamp":"2025-01-01T00:01:00Z"}"#;
+/// let backup = session(a);
+/// let current = session(&format!("{a}\n{b}\n"));
+///
+/// assert_eq!(
+///     analyze_session_relationship(&backup, &current),
+///     SessionRelationship::LocalIsPrefix
+/// );
+/// ```
 pub fn analyze_session_relationship(
     local: &ConversationSession,
     remote: &ConversationSession,
@@ -499,18 +558,17 @@ pub fn analyze_session_relationship(
     let local_only: HashSet<_> = local_uuids.difference(&remote_uuids).collect();
     let remote_only: HashSet<_> = remote_uuids.difference(&local_uuids).collect();
 
-    // If local has no unique entries, local is a prefix of remote
-    if local_only.is_empty() && !remote_only.is_empty() {
-        // Verify common entries are identical
-        if verify_common_entries_identical(local, remote) {
+    // At most one side has unique entries; it is an extension of the other
+    // unless entries on both sides were modified in place
+    if local_only.is_empty() || remote_only.is_empty() {
+        let common_identical = verify_common_entries_identical(local, remote);
+        if !common_identical {
+            return SessionRelationship::EntriesModified;
+        }
+        if local_only.is_empty() && !remote_only.is_empty() {
             return SessionRelationship::LocalIsPrefix;
         }
-    }
-
-    // If remote has no unique entries, remote is a prefix of local
-    if remote_only.is_empty() && !local_only.is_empty() {
-        // Verify common entries are identical
-        if verify_common_entries_identical(local, remote) {
+        if remote_only.is_empty() && !local_only.is_empty() {
             return SessionRelationship::RemoteIsPrefix;
         }
     }
@@ -519,40 +577,198 @@ pub fn analyze_session_relationship(
     SessionRelationship::Diverged
 }
 
-/// Verifies that entries with the same UUID have identical content
-fn verify_common_entries_identical(
+/// Result of reconciling a session whose entries were edited in place
+#[derive(Debug, Clone)]
+pub struct EntryReconciliation {
+    /// Reconciled entries, in the order of the longer side
+    pub entries: Vec<ConversationEntry>,
+    /// Number of entries whose content differed between the sides
+    pub modified: usize,
+    /// Remote versions that replaced a different local version
+    pub remote_wins: Vec<ConversationEntry>,
+}
+
+/// Reconcile two versions of a session related by
+/// [`SessionRelationship::EntriesModified`]
+///
+/// Entries whose content differs are resolved one by one according to
+/// `policy`, with `precedence` breaking timestamp ties; everything else is
+/// taken from whichever side has more entries. An entry that differs only
+/// because one side is a compacted or scrubbed copy of the other is not
+/// counted as modified: the copy goes in the result (which is saved to the
+/// sync repo) and is never among the `remote_wins`. [`ModifiedEntryPolicy::Fork`]
+/// is treated like `PreferNewer` here, since forking happens through conflict
+/// detection instead.
+pub fn reconcile_modified_entries(
     local: &ConversationSession,
     remote: &ConversationSession,
-) -> bool {
+    policy: ModifiedEntryPolicy,
+    precedence: PullPrecedence,
+    key_strategies: &ContentKeyStrategies,
+) -> EntryReconciliation {
     use std::collections::HashMap;
 
-    // Build map of UUID -> serialized entry for local
-    let local_map: HashMap<String, String> = local
+    let (base, other, base_is_local) = if local.entries.len() >= remote.entries.len() {
+        (local, remote, true)
+    } else {
+        (remote, local, false)
+    };
+    let other_by_uuid: HashMap<&str, &ConversationEntry> = other
         .entries
         .iter()
-        .filter_map(|e| {
-            e.uuid.as_ref().and_then(|uuid| {
-                serde_json::to_string(e).ok().map(|json| (uuid.clone(), json))
-            })
-        })
+        .filter_map(|e| e.uuid.as_deref().map(|uuid| (uuid, e)))
         .collect();
 
-    // Check each remote entry with a UUID
-    for entry in &remote.entries {
-        if let Some(uuid) = &entry.uuid {
-            if let Some(local_json) = local_map.get(uuid) {
-                // This UUID exists in both - check if content is identical
-                if let Ok(remote_json) = serde_json::to_string(entry) {
-                    if &remote_json != local_json {
-                        // Same UUID but different content - entries were modified
-                        return false;
-                    }
+    let mut reconciliation = EntryReconciliation {
+        entries: Vec::with_capacity(base.entries.len()),
+        modified: 0,
+        remote_wins: Vec::new(),
+    };
+    for entry in &base.entries {
+        let counterpart = entry.uuid.as_deref().and_then(|u| other_by_uuid.get(u));
+        let Some(&counterpart) = counterpart.filter(|c| !same_content(entry, c)) else {
+            reconciliation.entries.push(entry.clone());
+            continue;
+        };
+
+        let (local_entry, remote_entry) = if base_is_local {
+            (entry, counterpart)
+        } else {
+            (counterpart, entry)
+        };
+        // A compacted or scrubbed copy isn't an edit: the sync repo keeps it,
+        // and the local original is never replaced by it
+        if let Some(derived) = derived_copy(local_entry, remote_entry) {
+            reconciliation.entries.push(derived.clone());
+            continue;
+        }
+
+        reconciliation.modified += 1;
+        let keep_remote = match policy {
+            ModifiedEntryPolicy::PreferLocal => false,
+            ModifiedEntryPolicy::PreferRemote => true,
+            ModifiedEntryPolicy::PreferNewer | ModifiedEntryPolicy::Fork => {
+                match remote_entry.timestamp.cmp(&local_entry.timestamp) {
+                    std::cmp::Ordering::Equal => precedence == PullPrecedence::RemoteFirst,
+                    ordering => ordering.is_gt(),
                 }
             }
+        };
+        if keep_remote {
+            reconciliation.remote_wins.push(remote_entry.clone());
+            reconciliation.entries.push(remote_entry.clone());
+        } else {
+            reconciliation.entries.push(local_entry.clone());
         }
     }
 
-    true
+    // Entries without UUIDs that only the shorter side has
+    let base_keys: HashSet<String> = content_keys(&base.entries, key_strategies)
+        .into_iter()
+        .flatten()
+        .collect();
+    reconciliation.entries.extend(
+        other
+            .entries
+            .iter()
+            .zip(content_keys(&other.entries, key_strategies))
+            .filter(|(_, key)| key.as_ref().is_some_and(|k| !base_keys.contains(k)))
+            .map(|(entry, _)| entry.clone()),
+    );
+
+    reconciliation
+}
+
+/// Find sessions whose local and sync repo copies have diverged
+///
+/// Sessions present on only one side are ignored. Returns the IDs of
+/// diverged sessions, sorted.
+pub fn find_diverged_sessions(
+    local_sessions: &[ConversationSession],
+    repo_sessions: &[ConversationSession],
+) -> Vec<String> {
+    use std::collections::HashMap;
+
+    let local_map: HashMap<_, _> = local_sessions
+        .iter()
+        .map(|s| (s.session_id.as_str(), s))
+        .collect();
+
+    let mut diverged: Vec<String> = repo_sessions
+        .iter()
+        .filter(|repo| {
+            local_map.get(repo.session_id.as_str()).is_some_and(|local| {
+                analyze_session_relationship(local, repo) == SessionRelationship::Diverged
+            })
+        })
+        .map(|s| s.session_id.clone())
+        .collect();
+    diverged.sort();
+    diverged
+}
+
+/// Verifies that entries with the same UUID have identical content
+fn verify_common_entries_identical(
+    local: &ConversationSession,
+    remote: &ConversationSession,
+) -> bool {
+    use std::collections::HashMap;
+
+    // Build map of UUID -> entry for local
+    let local_map: HashMap<&str, &ConversationEntry> = local
+        .entries
+        .iter()
+        .filter_map(|e| e.uuid.as_deref().map(|uuid| (uuid, e)))
+        .collect();
+
+    // Check each remote entry with a UUID that also exists locally
+    remote.entries.iter().all(|entry| {
+        entry
+            .uuid
+            .as_deref()
+            .and_then(|uuid| local_map.get(uuid))
+            .is_none_or(|local_entry| same_content(local_entry, entry))
+    })
+}
+
+/// Whether two entries have the same canonical JSON
+fn same_content(a: &ConversationEntry, b: &ConversationEntry) -> bool {
+    canonical_json(a) == canonical_json(b)
+}
+
+/// Of two differing versions of an entry, the one that is a compacted or
+/// scrubbed copy of the other, made for the sync repo rather than edited
+fn derived_copy<'a>(
+    a: &'a ConversationEntry,
+    b: &'a ConversationEntry,
+) -> Option<&'a ConversationEntry> {
+    if is_derived_from(a, b) {
+        Some(a)
+    } else if is_derived_from(b, a) {
+        Some(b)
+    } else {
+        None
+    }
+}
+
+/// Whether every entry that differs between two sessions is a compacted or
+/// scrubbed copy, so nothing was edited in place
+fn only_derived_copies(local: &ConversationSession, remote: &ConversationSession) -> bool {
+    use std::collections::HashMap;
+
+    let remote_by_uuid: HashMap<&str, &ConversationEntry> = remote
+        .entries
+        .iter()
+        .filter_map(|e| e.uuid.as_deref().map(|uuid| (uuid, e)))
+        .collect();
+    local.entries.iter().all(|entry| {
+        match entry.uuid.as_deref().and_then(|u| remote_by_uuid.get(u)) {
+            Some(counterpart) if !same_content(entry, counterpart) => {
+                derived_copy(entry, counterpart).is_some()
+            }
+            _ => true,
+        }
+    })
 }
 
 #[cfg(test)]
@@ -579,6 +795,7 @@ mod tests {
                 version: None,
                 git_branch: None,
                 extra: serde_json::Value::Null,
+                raw_line: None,
             });
         }
 
@@ -611,6 +828,7 @@ mod tests {
                 version: None,
                 git_branch: None,
                 extra: serde_json::Value::Null,
+                raw_line: None,
             };
             local_entries.push(entry.clone());
             remote_entries.push(entry);
@@ -628,6 +846,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         });
 
         // Remote adds message 5-remote (unique to remote)
@@ -642,6 +861,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         });
 
         let local = ConversationSession {
@@ -696,6 +916,22 @@ mod tests {
         assert_eq!(relationship, SessionRelationship::Diverged);
     }
 
+    #[test]
+    fn test_find_diverged_sessions() {
+        let (local, remote) = create_diverged_sessions("session-div");
+        let local_sessions = vec![local, create_test_session("session-ext", 10)];
+        let repo_sessions = vec![
+            remote,
+            create_test_session("session-ext", 5),
+            create_test_session("session-repo-only", 3),
+        ];
+
+        assert_eq!(
+            find_diverged_sessions(&local_sessions, &repo_sessions),
+            vec!["session-div".to_string()]
+        );
+    }
+
     #[test]
     fn test_conflict_detection_only_diverged() {
         // This is the KEY test: extensions should NOT be conflicts
@@ -727,6 +963,128 @@ mod tests {
         assert_eq!(detector.conflict_count(), 1);
     }
 
+    #[test]
+    fn test_conflicts_keep_remote_order() {
+        let (mut locals, mut remotes) = (Vec::new(), Vec::new());
+        for i in 0..50 {
+            let (local, remote) = create_diverged_sessions(&format!("session-{i:02}"));
+            locals.push(local);
+            remotes.push(remote);
+        }
+        // Unmatched and extended sessions in between aren't conflicts
+        remotes.insert(10, create_test_session("remote-only", 3));
+        locals.push(create_test_session("session-ext", 5));
+        remotes.insert(20, create_test_session("session-ext", 8));
+        locals.reverse();
+
+        let mut detector = ConflictDetector::new();
+        detector.detect(&locals, &remotes);
+        let ids: Vec<&str> = detector
+            .conflicts()
+            .iter()
+            .map(|c| c.session_id.as_str())
+            .collect();
+        let expected: Vec<String> = (0..50).map(|i| format!("session-{i:02}")).collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_entry_modified_in_place() {
+        let local = create_test_session("session-edit", 3);
+        let mut remote = create_test_session("session-edit", 4);
+        remote.entries[1].git_branch = Some("edited".to_string());
+        remote.entries[1].timestamp = Some("2025-01-02T00:00:00Z".to_string());
+
+        assert_eq!(
+            analyze_session_relationship(&local, &remote),
+            SessionRelationship::EntriesModified
+        );
+        let mut detector = ConflictDetector::new();
+        detector.detect(std::slice::from_ref(&local), std::slice::from_ref(&remote));
+        assert!(!detector.has_conflicts());
+        let mut detector = ConflictDetector::new().fork_modified_entries(true);
+        detector.detect(std::slice::from_ref(&local), std::slice::from_ref(&remote));
+        assert!(detector.has_conflicts());
+
+        let newer = reconcile_modified_entries(
+            &local,
+            &remote,
+            ModifiedEntryPolicy::PreferNewer,
+            PullPrecedence::LocalFirst,
+            &ContentKeyStrategies::default(),
+        );
+        assert_eq!(newer.entries.len(), 4);
+        assert_eq!(newer.modified, 1);
+        assert_eq!(newer.entries[1].git_branch.as_deref(), Some("edited"));
+        assert_eq!(newer.remote_wins.len(), 1);
+
+        let kept = reconcile_modified_entries(
+            &local,
+            &remote,
+            ModifiedEntryPolicy::PreferLocal,
+            PullPrecedence::RemoteFirst,
+            &ContentKeyStrategies::default(),
+        );
+        assert_eq!(kept.entries.len(), 4);
+        assert_eq!(kept.entries[1].git_branch, None);
+        assert!(kept.remote_wins.is_empty());
+    }
+
+    #[test]
+    fn test_compacted_copy_is_not_an_edit() {
+        let mut local = create_test_session("session-compacted", 3);
+        local.entries[1].extra = serde_json::json!({ "toolUseResult": "x".repeat(500) });
+        let (remote, _) = crate::compaction::compact_session(&local, 100);
+
+        assert_eq!(
+            analyze_session_relationship(&local, &remote),
+            SessionRelationship::EntriesModified
+        );
+        let mut detector = ConflictDetector::new().fork_modified_entries(true);
+        detector.detect(std::slice::from_ref(&local), std::slice::from_ref(&remote));
+        assert!(!detector.has_conflicts());
+
+        // Whichever side the compacted copy is on, it is what the sync repo
+        // keeps, and it never replaces the local original
+        for (ours, theirs) in [(&local, &remote), (&remote, &local)] {
+            let reconciled = reconcile_modified_entries(
+                ours,
+                theirs,
+                ModifiedEntryPolicy::PreferRemote,
+                PullPrecedence::RemoteFirst,
+                &ContentKeyStrategies::default(),
+            );
+            assert_eq!(reconciled.modified, 0);
+            assert!(reconciled.remote_wins.is_empty());
+            assert!(is_derived_from(&reconciled.entries[1], &local.entries[1]));
+        }
+    }
+
+    #[test]
+    fn test_edit_that_looks_scrubbed_is_still_an_edit() {
+        let local = create_test_session("session-edited", 3);
+        let mut remote = local.clone();
+        remote.entries[1].message = Some(serde_json::json!({
+            "role": "user",
+            "content": "Write to [email] or [compacted by claude-code-sync: 9 bytes]"
+        }));
+        remote.entries[1].raw_line = None;
+
+        let mut detector = ConflictDetector::new().fork_modified_entries(true);
+        detector.detect(std::slice::from_ref(&local), std::slice::from_ref(&remote));
+        assert!(detector.has_conflicts());
+
+        let reconciled = reconcile_modified_entries(
+            &local,
+            &remote,
+            ModifiedEntryPolicy::PreferRemote,
+            PullPrecedence::RemoteFirst,
+            &ContentKeyStrategies::default(),
+        );
+        assert_eq!(reconciled.modified, 1);
+        assert_eq!(reconciled.entries[1].message, remote.entries[1].message);
+    }
+
     #[test]
     fn test_no_conflict_same_content() {
         let local_session = create_test_session("session-1", 5);
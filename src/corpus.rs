@@ -0,0 +1,367 @@
+//! Read-only queries over the sessions in the sync repo
+//!
+//! `mcp-serve` and `serve` answer the same questions about the synced
+//! history (which projects there are, which sessions, which messages match
+//! a search) for different clients; this module answers them once, from the
+//! sessions read out of the sync repo.
+
+use anyhow::{bail, ensure, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::filter::FilterConfig;
+use crate::parser::{ConversationEntry, ConversationSession};
+use crate::paths::{decode_project_dir, project_dir_name};
+use crate::text::{truncate_to_width, Keep};
+use crate::timeline::message_preview;
+
+/// Words of context kept on each side of a search match
+const SNIPPET_WORDS: usize = 20;
+
+/// Width of session titles
+const TITLE_WIDTH: usize = 120;
+
+/// A project with synced sessions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProjectSummary {
+    /// Directory name in the projects directory
+    pub project: String,
+    /// The project's path, decoded from the directory name
+    pub path: String,
+    pub sessions: usize,
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// A synced session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub project: String,
+    pub started: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub messages: usize,
+    /// The first user message, shortened
+    pub title: Option<String>,
+}
+
+/// A message matching a search
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchMatch {
+    pub session_id: String,
+    pub project: String,
+    pub timestamp: Option<String>,
+    /// `user` or `assistant`
+    pub role: String,
+    /// The words around the first match
+    pub snippet: String,
+}
+
+/// The newest matches of a search, and how many there were in all
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchResults {
+    pub total_matches: usize,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// The sessions of the main conversation in a projects directory
+pub struct Corpus {
+    /// Subagent transcripts are left out
+    sessions: Vec<ConversationSession>,
+    projects_dir: PathBuf,
+}
+
+impl Corpus {
+    /// Query `sessions`, found under `projects_dir`
+    pub fn new(sessions: Vec<ConversationSession>, projects_dir: &Path) -> Self {
+        Self {
+            sessions: sessions
+                .into_iter()
+                .filter(|s| !s.is_agent_file())
+                .collect(),
+            projects_dir: projects_dir.to_path_buf(),
+        }
+    }
+
+    /// Read the sessions in the sync repo
+    pub fn load() -> Result<Self> {
+        let state = crate::sync::SyncState::load()?;
+        let filter = FilterConfig::load()?;
+        let projects_dir = state.sync_repo_path.join(filter.projects_subdir());
+        let sessions = crate::sync::discover_sessions(&projects_dir, &FilterConfig::default())?;
+        Ok(Self::new(sessions, &projects_dir))
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Directory name of the project `session` belongs to
+    pub fn project_of(&self, session: &ConversationSession) -> String {
+        Path::new(&session.file_path)
+            .strip_prefix(&self.projects_dir)
+            .ok()
+            .and_then(project_dir_name)
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// Every project, by directory name
+    pub fn projects(&self) -> Vec<ProjectSummary> {
+        let mut projects: BTreeMap<String, (usize, Option<DateTime<Utc>>)> = BTreeMap::new();
+        for session in &self.sessions {
+            let project = projects.entry(self.project_of(session)).or_default();
+            project.0 += 1;
+            project.1 = project
+                .1
+                .max(session.entries.iter().filter_map(timestamp).max());
+        }
+        projects
+            .into_iter()
+            .map(|(project, (sessions, last_activity))| ProjectSummary {
+                path: decode_project_dir(&project).display().to_string(),
+                project,
+                sessions,
+                last_activity,
+            })
+            .collect()
+    }
+
+    /// Sessions of projects whose directory name contains `project` (all
+    /// with `None`), most recently active first
+    pub fn sessions(&self, project: Option<&str>) -> Vec<SessionSummary> {
+        let mut listed: Vec<SessionSummary> = self
+            .sessions
+            .iter()
+            .map(|session| self.summary(session))
+            .filter(|summary| project.is_none_or(|p| summary.project.contains(p)))
+            .collect();
+        listed.sort_by_key(|summary| std::cmp::Reverse(summary.last_activity));
+        listed
+    }
+
+    pub fn summary(&self, session: &ConversationSession) -> SessionSummary {
+        let times: Vec<DateTime<Utc>> = session.entries.iter().filter_map(timestamp).collect();
+        SessionSummary {
+            session_id: session.session_id.clone(),
+            project: self.project_of(session),
+            started: times.iter().min().copied(),
+            last_activity: times.iter().max().copied(),
+            messages: session.message_count(),
+            title: messages(session)
+                .filter(|e| e.entry_type == "user")
+                .find_map(message_preview)
+                .map(|text| truncate_to_width(&text, TITLE_WIDTH, Keep::Start)),
+        }
+    }
+
+    /// Messages containing every word of `query` (case-insensitive), in
+    /// projects whose directory name contains `project`, newest first
+    ///
+    /// A message a fork copied from its parent is matched once.
+    pub fn search(
+        &self,
+        query: &str,
+        project: Option<&str>,
+        limit: usize,
+    ) -> Result<SearchResults> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        ensure!(!words.is_empty(), "The query is empty");
+
+        let mut seen = HashSet::new();
+        let mut matches = Vec::new();
+        for session in &self.sessions {
+            let session_project = self.project_of(session);
+            if project.is_some_and(|p| !session_project.contains(p)) {
+                continue;
+            }
+            for entry in messages(session) {
+                let Some(text) = message_preview(entry) else {
+                    continue;
+                };
+                let lower = text.to_lowercase();
+                if !words.iter().all(|w| lower.contains(w.as_str())) {
+                    continue;
+                }
+                if entry.uuid.as_ref().is_some_and(|u| !seen.insert(u.clone())) {
+                    continue;
+                }
+                matches.push((
+                    timestamp(entry),
+                    SearchMatch {
+                        session_id: session.session_id.clone(),
+                        project: session_project.clone(),
+                        timestamp: entry.timestamp.clone(),
+                        role: entry.entry_type.clone(),
+                        snippet: snippet(&text, &words[0]),
+                    },
+                ));
+            }
+        }
+        matches.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+        Ok(SearchResults {
+            total_matches: matches.len(),
+            matches: matches.into_iter().take(limit).map(|(_, m)| m).collect(),
+        })
+    }
+
+    /// The session with ID `reference`, or the only one it is a prefix of
+    pub fn find_session(&self, reference: &str) -> Result<&ConversationSession> {
+        if let Some(exact) = self.sessions.iter().find(|s| s.session_id == reference) {
+            return Ok(exact);
+        }
+        let mut matching = self
+            .sessions
+            .iter()
+            .filter(|s| s.session_id.starts_with(reference));
+        match (matching.next(), matching.next()) {
+            (Some(only), None) => Ok(only),
+            (None, _) => bail!("No session matches '{reference}'"),
+            (Some(_), Some(_)) => {
+                bail!("'{reference}' matches several sessions; give more of the ID")
+            }
+        }
+    }
+}
+
+/// User and assistant messages of the main conversation
+pub fn messages(session: &ConversationSession) -> impl Iterator<Item = &ConversationEntry> {
+    session
+        .entries
+        .iter()
+        .filter(|e| matches!(e.entry_type.as_str(), "user" | "assistant") && !e.is_sidechain())
+}
+
+pub fn timestamp(entry: &ConversationEntry) -> Option<DateTime<Utc>> {
+    entry
+        .timestamp
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// The words of `text` around the first one containing `word`
+fn snippet(text: &str, word: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let hit = words
+        .iter()
+        .position(|w| w.to_lowercase().contains(word))
+        .unwrap_or(0);
+    let start = hit.saturating_sub(SNIPPET_WORDS);
+    let end = (hit + SNIPPET_WORDS + 1).min(words.len());
+    let mut snippet = words[start..end].join(" ");
+    if start > 0 {
+        snippet.insert_str(0, "… ");
+    }
+    if end < words.len() {
+        snippet.push_str(" …");
+    }
+    snippet
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Two projects: a login bug discussion in `api`, and one message in `web`
+    pub(crate) fn corpus() -> Corpus {
+        let entry = |uuid: &str, role: &str, time: &str, text: &str| -> ConversationEntry {
+            serde_json::from_value(json!({
+                "type": role,
+                "uuid": uuid,
+                "timestamp": time,
+                "message": { "role": role, "content": text },
+            }))
+            .unwrap()
+        };
+        let session = |id: &str, project: &str, entries| ConversationSession {
+            session_id: id.to_string(),
+            entries,
+            file_path: format!("/repo/projects/{project}/{id}.jsonl"),
+        };
+        Corpus::new(
+            vec![
+                session(
+                    "aaaa-1",
+                    "-home-me-api",
+                    vec![
+                        entry(
+                            "a",
+                            "user",
+                            "2025-01-01T10:00:00Z",
+                            "Why does the login test flake?",
+                        ),
+                        entry(
+                            "b",
+                            "assistant",
+                            "2025-01-01T10:01:00Z",
+                            "The login test races the token refresh.",
+                        ),
+                    ],
+                ),
+                session(
+                    "bbbb-2",
+                    "-home-me-web",
+                    vec![entry(
+                        "c",
+                        "user",
+                        "2025-01-02T10:00:00Z",
+                        "Restyle the login page",
+                    )],
+                ),
+            ],
+            Path::new("/repo/projects"),
+        )
+    }
+
+    #[test]
+    fn test_search() {
+        let found = corpus().search("LOGIN test", None, 10).unwrap();
+        assert_eq!(found.total_matches, 2);
+        // Newest first
+        assert_eq!(found.matches[0].role, "assistant");
+        assert_eq!(found.matches[0].session_id, "aaaa-1");
+
+        let found = corpus().search("login", Some("web"), 10).unwrap();
+        assert_eq!(found.total_matches, 1);
+        assert_eq!(found.matches[0].snippet, "Restyle the login page");
+
+        let found = corpus().search("login", None, 1).unwrap();
+        assert_eq!((found.total_matches, found.matches.len()), (3, 1));
+        assert!(corpus().search("  ", None, 10).is_err());
+    }
+
+    #[test]
+    fn test_sessions_and_projects() {
+        let corpus = corpus();
+        let sessions = corpus.sessions(None);
+        assert_eq!(sessions[0].session_id, "bbbb-2");
+        assert_eq!(
+            sessions[1].title.as_deref(),
+            Some("Why does the login test flake?")
+        );
+        assert_eq!(corpus.sessions(Some("api")).len(), 1);
+
+        let projects = corpus.projects();
+        assert_eq!(projects[0].path, "/home/me/api");
+        assert_eq!(projects[0].sessions, 1);
+        assert!(corpus.find_session("aaaa").is_ok());
+        assert!(corpus.find_session("cccc").is_err());
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = (0..60)
+            .map(|i| format!("w{i}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let around = snippet(&text, "w30");
+        assert!(around.starts_with("… w10 "), "{around}");
+        assert!(around.ends_with(" w50 …"), "{around}");
+    }
+}
@@ -0,0 +1,99 @@
+//! Secrets stored in the OS keychain
+//!
+//! Tokens and keys live in the macOS Keychain, the Secret Service (GNOME
+//! Keyring, KWallet) or the Windows Credential Manager under the service
+//! name `claude-code-sync`, never in `config.toml`. Names are free-form;
+//! the ones this tool reads itself are:
+//!
+//! - `https-token:<host>`, then `https-token`: the token sent when git
+//!   pushes to or fetches from an HTTPS remote on that host (user name
+//!   `x-access-token`, which GitHub, GitLab and Gitea all accept)
+//! - `api-token`: the bearer token `serve` requires before running a sync
+
+use anyhow::{Context, Result};
+use keyring::Entry;
+
+/// Keychain service name every credential is stored under
+pub const SERVICE: &str = "claude-code-sync";
+
+/// Credential name of the token for HTTPS remotes
+pub const HTTPS_TOKEN: &str = "https-token";
+
+/// Credential name of the token `serve` requires on `POST /sync`
+pub const API_TOKEN: &str = "api-token";
+
+fn entry(name: &str) -> Result<Entry> {
+    Entry::new(SERVICE, name).with_context(|| format!("Failed to open keychain entry '{name}'"))
+}
+
+/// Store a secret, replacing any previous value
+pub fn set(name: &str, secret: &str) -> Result<()> {
+    entry(name)?
+        .set_password(secret)
+        .with_context(|| format!("Failed to store '{name}' in the OS keychain"))
+}
+
+/// Read a secret, or `None` if it isn't stored
+pub fn get(name: &str) -> Result<Option<String>> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read '{name}' from the OS keychain")),
+    }
+}
+
+/// Remove a secret, returning whether it was stored
+pub fn remove(name: &str) -> Result<bool> {
+    match entry(name)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e).with_context(|| format!("Failed to remove '{name}' from the OS keychain")),
+    }
+}
+
+/// Token for an HTTPS remote URL: the host's own token if one is stored,
+/// else the shared one
+///
+/// Returns `None` for other URLs, and when the keychain can't be reached
+/// (a headless machine without a Secret Service, say), so git falls back
+/// to its own credential helpers.
+pub fn https_token_for(url: &str) -> Option<String> {
+    let host = https_host(url)?;
+    [format!("{HTTPS_TOKEN}:{host}"), HTTPS_TOKEN.to_string()]
+        .iter()
+        .find_map(|name| match get(name) {
+            Ok(token) => token,
+            Err(e) => {
+                log::debug!("Skipping keychain token '{}': {:#}", name, e);
+                None
+            }
+        })
+}
+
+/// Host (with port, without user info) of an `https://` URL
+fn https_host(url: &str) -> Option<&str> {
+    let rest = url.strip_prefix("https://")?;
+    let authority = rest.split('/').next()?;
+    let host = authority.rsplit('@').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_https_host() {
+        assert_eq!(
+            https_host("https://github.com/me/repo.git"),
+            Some("github.com")
+        );
+        assert_eq!(
+            https_host("https://me@git.example.com:8443/repo"),
+            Some("git.example.com:8443")
+        );
+        assert_eq!(https_host("git@github.com:me/repo.git"), None);
+        assert_eq!(https_host("http://github.com/me/repo.git"), None);
+        assert_eq!(https_host("/tmp/remote.git"), None);
+    }
+}
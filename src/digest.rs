@@ -0,0 +1,386 @@
+//! Weekly digest of sync activity
+//!
+//! `report --weekly` sums up the last seven days of operation history: how
+//! many pulls, pushes, and syncs ran, which sessions each project gained or
+//! changed, the conflicts found, and how much the sync repo grew. It is
+//! written as Markdown (or JSON) to stdout or a file, to be mailed or posted
+//! by whatever runs it on a schedule.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::filter::FilterConfig;
+use crate::history::{OperationHistory, OperationRecord, SyncOperation};
+use crate::paths::{decode_project_dir, project_dir_name};
+
+/// Days a weekly digest covers
+const WEEK_DAYS: i64 = 7;
+
+/// A project's sessions touched in the period
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProjectActivity {
+    pub added: usize,
+    /// Sessions changed but not added in the period
+    pub modified: usize,
+    pub conflicts: usize,
+}
+
+/// How the sync repo changed in the period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RepoGrowth {
+    pub files_changed: usize,
+    /// Net change in size of the changed files
+    pub bytes: i64,
+}
+
+/// The sync repo's session files now
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RepoSize {
+    pub sessions: usize,
+    pub bytes: u64,
+}
+
+/// Sync activity over a period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Digest {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Operations run, by type
+    pub operations: BTreeMap<String, usize>,
+    /// Operations that couldn't reach the remote or had a network step fail
+    pub network_problems: usize,
+    /// Activity by project directory name
+    pub projects: BTreeMap<String, ProjectActivity>,
+    pub repo: Option<RepoSize>,
+    /// `None` when no push in the period recorded the commit it started from
+    pub growth: Option<RepoGrowth>,
+}
+
+impl Digest {
+    /// Sum up the operations in `operations` (newest first, as the history
+    /// keeps them) run from `since` to `until`
+    pub fn build(
+        operations: &[OperationRecord],
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Self {
+        let mut counts = BTreeMap::new();
+        let mut network_problems = 0;
+        let mut added: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+        let mut modified: BTreeMap<String, BTreeSet<&str>> = BTreeMap::new();
+        let mut conflicts: BTreeMap<String, usize> = BTreeMap::new();
+
+        // Oldest first, so a session added and later modified counts as added
+        for op in operations
+            .iter()
+            .rev()
+            .filter(|op| op.timestamp >= since && op.timestamp < until)
+        {
+            *counts
+                .entry(op.operation_type.as_str().to_string())
+                .or_insert(0) += 1;
+            if op.remote_unreachable || !op.network_failures.is_empty() {
+                network_problems += 1;
+            }
+            for conversation in &op.affected_conversations {
+                let project = project_dir_name(Path::new(&conversation.project_path))
+                    .unwrap_or_else(|| "unknown".to_string());
+                let id = conversation.session_id.as_str();
+                match conversation.operation {
+                    SyncOperation::Added => {
+                        added.entry(project).or_default().insert(id);
+                    }
+                    SyncOperation::Modified => {
+                        if !added.get(&project).is_some_and(|ids| ids.contains(id)) {
+                            modified.entry(project).or_default().insert(id);
+                        }
+                    }
+                    SyncOperation::Conflict => {
+                        *conflicts.entry(project).or_insert(0) += 1;
+                    }
+                    SyncOperation::Unchanged => {}
+                }
+            }
+        }
+
+        let mut projects: BTreeMap<String, ProjectActivity> = BTreeMap::new();
+        for (project, ids) in added {
+            projects.entry(project).or_default().added = ids.len();
+        }
+        for (project, ids) in modified {
+            projects.entry(project).or_default().modified = ids.len();
+        }
+        for (project, count) in conflicts {
+            projects.entry(project).or_default().conflicts = count;
+        }
+
+        Self {
+            since,
+            until,
+            operations: counts,
+            network_problems,
+            projects,
+            repo: None,
+            growth: None,
+        }
+    }
+
+    fn totals(&self) -> ProjectActivity {
+        self.projects
+            .values()
+            .fold(ProjectActivity::default(), |total, p| ProjectActivity {
+                added: total.added + p.added,
+                modified: total.modified + p.modified,
+                conflicts: total.conflicts + p.conflicts,
+            })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!(
+            "# Claude Code sync digest: {} to {}\n\n",
+            self.since.format("%Y-%m-%d"),
+            self.until.format("%Y-%m-%d")
+        );
+
+        md.push_str("## Summary\n\n");
+        let runs: usize = self.operations.values().sum();
+        if runs == 0 {
+            md.push_str("- No pulls, pushes, or syncs ran\n");
+        } else {
+            let by_type: Vec<String> = self
+                .operations
+                .iter()
+                .map(|(kind, count)| format!("{count} {kind}"))
+                .collect();
+            md.push_str(&format!("- {} operations: {}", runs, by_type.join(", ")));
+            if self.network_problems > 0 {
+                md.push_str(&format!(
+                    " ({} with network problems)",
+                    self.network_problems
+                ));
+            }
+            md.push('\n');
+        }
+        let totals = self.totals();
+        md.push_str(&format!(
+            "- {} sessions added, {} modified, {} conflicts\n",
+            totals.added, totals.modified, totals.conflicts
+        ));
+        if let Some(repo) = self.repo {
+            md.push_str(&format!(
+                "- Sync repo: {} session files, {}",
+                repo.sessions,
+                format_size(repo.bytes as i64)
+            ));
+            match self.growth {
+                Some(growth) => md.push_str(&format!(
+                    " ({}{} this week across {} files)\n",
+                    if growth.bytes >= 0 { "+" } else { "-" },
+                    format_size(growth.bytes.abs()),
+                    growth.files_changed
+                )),
+                None => md.push('\n'),
+            }
+        }
+
+        if !self.projects.is_empty() {
+            md.push_str("\n## By project\n\n");
+            md.push_str("| Project | Added | Modified | Conflicts |\n");
+            md.push_str("|---------|-------|----------|-----------|\n");
+            let mut rows: Vec<_> = self.projects.iter().collect();
+            rows.sort_by_key(|(name, p)| (std::cmp::Reverse(p.added + p.modified), *name));
+            for (name, activity) in rows {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    decode_project_dir(name)
+                        .display()
+                        .to_string()
+                        .replace('|', "\\|"),
+                    activity.added,
+                    activity.modified,
+                    activity.conflicts
+                ));
+            }
+        }
+        md
+    }
+}
+
+/// `bytes` as KB or MB
+fn format_size(bytes: i64) -> String {
+    if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Session files under `projects_dir` and their total size
+fn repo_size(projects_dir: &Path) -> RepoSize {
+    let mut size = RepoSize {
+        sessions: 0,
+        bytes: 0,
+    };
+    for entry in WalkDir::new(projects_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
+    {
+        size.sessions += 1;
+        size.bytes += entry.metadata().map_or(0, |m| m.len());
+    }
+    size
+}
+
+/// Net change in size of the files changed between `from` and the working
+/// tree's `HEAD`
+fn repo_growth(sync_repo: &Path, from: &str) -> Result<RepoGrowth> {
+    let scm = crate::scm::open(sync_repo)?;
+    let changed = scm.changed_files(from, "HEAD")?;
+    let mut bytes = 0i64;
+    for file in &changed {
+        let before = scm.read_file_at(from, file).map_or(0, |b| b.len() as i64);
+        let after = fs::metadata(sync_repo.join(file)).map_or(0, |m| m.len() as i64);
+        bytes += after - before;
+    }
+    Ok(RepoGrowth {
+        files_changed: changed.len(),
+        bytes,
+    })
+}
+
+/// Write the digest of the last week to `output`, or print it
+pub fn generate_weekly_report(format: &str, output: Option<&Path>) -> Result<()> {
+    let history = OperationHistory::load().context("Failed to load operation history")?;
+    let until = Utc::now();
+    let since = until - Duration::days(WEEK_DAYS);
+    let operations = history.list_operations();
+    let mut digest = Digest::build(operations, since, until);
+
+    if let Ok(state) = crate::sync::SyncState::load() {
+        let filter = FilterConfig::load()?;
+        digest.repo = Some(repo_size(
+            &state.sync_repo_path.join(filter.projects_subdir()),
+        ));
+        // The commit the week's first push started from
+        let start = operations
+            .iter()
+            .rev()
+            .filter(|op| op.timestamp >= since)
+            .find_map(|op| op.commit_hash.as_deref());
+        if let Some(start) = start {
+            match repo_growth(&state.sync_repo_path, start) {
+                Ok(growth) => digest.growth = Some(growth),
+                Err(e) => log::warn!("Couldn't measure repo growth: {:#}", e),
+            }
+        }
+    }
+
+    let content = match format.to_lowercase().as_str() {
+        "markdown" | "md" => digest.to_markdown(),
+        "json" => serde_json::to_string_pretty(&digest)? + "\n",
+        other => bail!("Unknown digest format: '{}'. Use markdown or json.", other),
+    };
+    match output {
+        Some(path) => fs::write(path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{content}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{ConversationSummary, OperationType};
+
+    fn operation(
+        kind: OperationType,
+        days_ago: i64,
+        now: DateTime<Utc>,
+        touched: &[(&str, &str, SyncOperation)],
+    ) -> OperationRecord {
+        let mut record = OperationRecord::new(
+            kind,
+            None,
+            touched
+                .iter()
+                .map(|(id, project, operation)| {
+                    ConversationSummary::new(
+                        id.to_string(),
+                        format!("{project}/{id}.jsonl"),
+                        None,
+                        2,
+                        *operation,
+                    )
+                    .unwrap()
+                })
+                .collect(),
+        );
+        record.timestamp = now - Duration::days(days_ago);
+        record
+    }
+
+    #[test]
+    fn test_weekly_digest() {
+        let now = Utc::now();
+        // Newest first, as the history keeps them
+        let mut last_pull = operation(
+            OperationType::Pull,
+            1,
+            now,
+            &[
+                ("s1", "-home-me-app", SyncOperation::Modified),
+                ("s3", "-home-me-web", SyncOperation::Conflict),
+            ],
+        );
+        last_pull.remote_unreachable = true;
+        let operations = [
+            last_pull,
+            operation(
+                OperationType::Push,
+                3,
+                now,
+                &[
+                    ("s1", "-home-me-app", SyncOperation::Added),
+                    ("s2", "-home-me-app", SyncOperation::Modified),
+                ],
+            ),
+            operation(
+                OperationType::Push,
+                10,
+                now,
+                &[("s0", "-home-me-app", SyncOperation::Added)],
+            ),
+        ];
+
+        let digest = Digest::build(&operations, now - Duration::days(WEEK_DAYS), now);
+        assert_eq!(digest.operations.values().sum::<usize>(), 2);
+        assert_eq!(digest.network_problems, 1);
+        assert_eq!(
+            digest.projects["-home-me-app"],
+            ProjectActivity {
+                added: 1,
+                modified: 1,
+                conflicts: 0
+            }
+        );
+        assert_eq!(digest.projects["-home-me-web"].conflicts, 1);
+
+        let md = digest.to_markdown();
+        assert!(
+            md.contains("- 2 operations: 1 pull, 1 push (1 with network problems)\n"),
+            "{md}"
+        );
+        assert!(
+            md.contains("- 1 sessions added, 1 modified, 1 conflicts\n"),
+            "{md}"
+        );
+        assert!(md.contains("| /home/me/app | 1 | 1 | 0 |\n"), "{md}");
+    }
+}
@@ -0,0 +1,241 @@
+//! Thread families of forked sessions
+//!
+//! Claude Code can fork a session into a new session ID whose file starts
+//! with a copy of the original's entries, UUIDs and all. Pull finds such
+//! sessions by their shared UUID prefix and records which session each one
+//! was forked from in a sidecar index (`families.json`) at the root of the
+//! sync repo, next to `tags.json`, so `timeline` and `status` can show the
+//! sessions of one conversation as a family rather than unrelated files.
+//!
+//! Of two sessions sharing a prefix, the original is the one that added
+//! nothing after it, else the one that went on first; detection only looks
+//! at the sessions' entries, so every machine records the same links.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::parser::ConversationSession;
+
+/// File name of the family index within the sync repo
+pub const FAMILY_INDEX_FILE: &str = "families.json";
+
+/// The session a fork was made from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkLink {
+    pub parent: String,
+    /// Leading entries (with a UUID) the fork shares with its parent
+    pub shared_entries: usize,
+}
+
+/// Mapping of forked session ID to the session it was forked from
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FamilyIndex {
+    #[serde(flatten)]
+    pub forks: BTreeMap<String, ForkLink>,
+}
+
+impl FamilyIndex {
+    /// Path of the family index within a sync repo
+    pub fn path(sync_repo: &Path) -> PathBuf {
+        sync_repo.join(FAMILY_INDEX_FILE)
+    }
+
+    /// Load the family index from a sync repo (empty if it doesn't exist)
+    pub fn load(sync_repo: &Path) -> Result<Self> {
+        let path = Self::path(sync_repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read family index: {}", path.display()))?;
+        serde_json::from_str(&content).context("Failed to parse family index")
+    }
+
+    /// Save the family index to a sync repo
+    pub fn save(&self, sync_repo: &Path) -> Result<()> {
+        let path = Self::path(sync_repo);
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize family index")?;
+        fs::write(&path, content + "\n")
+            .with_context(|| format!("Failed to write family index: {}", path.display()))
+    }
+
+    /// Record that `fork` was forked from `link.parent`, returning false if
+    /// that was already recorded
+    pub fn link(&mut self, fork: &str, link: ForkLink) -> bool {
+        if self.forks.get(fork) == Some(&link) {
+            return false;
+        }
+        self.forks.insert(fork.to_string(), link);
+        true
+    }
+
+    /// The session the family of `session_id` started from (itself if it
+    /// isn't a fork)
+    pub fn root_of<'a>(&'a self, session_id: &'a str) -> &'a str {
+        let mut current = session_id;
+        // Bounded, in case a hand-edited index links sessions in a cycle
+        for _ in 0..=self.forks.len() {
+            match self.forks.get(current) {
+                Some(link) if link.parent != session_id => current = &link.parent,
+                _ => break,
+            }
+        }
+        current
+    }
+
+    /// The session `session_id` was forked from, if it is a fork
+    pub fn parent_of(&self, session_id: &str) -> Option<&str> {
+        self.forks.get(session_id).map(|link| link.parent.as_str())
+    }
+}
+
+/// Forks among `sessions`, as (fork ID, link to its parent)
+///
+/// Each fork is linked to the session it shares the longest UUID prefix with
+/// among those it could have been forked from. Subagent transcripts are
+/// left out, and of a session given more than once only the first copy
+/// counts.
+pub fn detect_forks<'a>(
+    sessions: impl IntoIterator<Item = &'a ConversationSession>,
+) -> Vec<(String, ForkLink)> {
+    let mut seen = HashSet::new();
+    let mut by_first_uuid: HashMap<&str, Vec<&ConversationSession>> = HashMap::new();
+    for session in sessions {
+        if session.is_agent_file() || !seen.insert(session.session_id.as_str()) {
+            continue;
+        }
+        if let Some(first) = session.entries.iter().find_map(|e| e.uuid.as_deref()) {
+            by_first_uuid.entry(first).or_default().push(session);
+        }
+    }
+
+    let mut forks = Vec::new();
+    for group in by_first_uuid.values().filter(|group| group.len() > 1) {
+        for fork in group {
+            let parent = group
+                .iter()
+                .filter(|other| other.session_id != fork.session_id)
+                .map(|other| (other, shared_prefix(other, fork)))
+                .filter(|(other, shared)| original_first(other, fork, *shared) == Ordering::Less)
+                .max_by(|(a, a_shared), (b, b_shared)| {
+                    a_shared
+                        .cmp(b_shared)
+                        .then_with(|| b.session_id.cmp(&a.session_id))
+                });
+            if let Some((parent, shared)) = parent {
+                forks.push((
+                    fork.session_id.clone(),
+                    ForkLink {
+                        parent: parent.session_id.clone(),
+                        shared_entries: shared,
+                    },
+                ));
+            }
+        }
+    }
+    forks.sort_by(|a, b| a.0.cmp(&b.0));
+    forks
+}
+
+/// UUIDs of a session's entries, in file order
+fn uuids(session: &ConversationSession) -> impl Iterator<Item = &str> {
+    session.entries.iter().filter_map(|e| e.uuid.as_deref())
+}
+
+/// How many leading UUIDs two sessions share
+fn shared_prefix(a: &ConversationSession, b: &ConversationSession) -> usize {
+    uuids(a).zip(uuids(b)).take_while(|(x, y)| x == y).count()
+}
+
+/// Whether `a` comes before `b` as the original of the pair: the session
+/// with nothing after the shared prefix, else the one whose first entry
+/// after it is older, else the smaller session ID
+fn original_first(a: &ConversationSession, b: &ConversationSession, shared: usize) -> Ordering {
+    let went_on_at = |session: &ConversationSession| {
+        session
+            .entries
+            .iter()
+            .filter(|e| e.uuid.is_some())
+            .nth(shared)
+            .map(|e| e.timestamp.clone().unwrap_or_default())
+    };
+    match (went_on_at(a), went_on_at(b)) {
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(x), Some(y)) if x != y => x.cmp(&y),
+        _ => a.session_id.cmp(&b.session_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ConversationEntry;
+
+    fn session(id: &str, uuids: &[(&str, &str)]) -> ConversationSession {
+        let entries = uuids
+            .iter()
+            .map(|(uuid, timestamp)| {
+                serde_json::from_value::<ConversationEntry>(serde_json::json!({
+                    "type": "user",
+                    "uuid": uuid,
+                    "timestamp": timestamp,
+                }))
+                .unwrap()
+            })
+            .collect();
+        ConversationSession {
+            session_id: id.to_string(),
+            entries,
+            file_path: format!("/p/{id}.jsonl"),
+        }
+    }
+
+    #[test]
+    fn test_detect_forks() {
+        let original = session("orig", &[("a", "t1"), ("b", "t2"), ("c", "t3")]);
+        let fork = session("fork", &[("a", "t1"), ("b", "t2"), ("x", "t4")]);
+        // Forked from the fork, which had added nothing since
+        let nested = session(
+            "nested",
+            &[("a", "t1"), ("b", "t2"), ("x", "t4"), ("y", "t5")],
+        );
+        let unrelated = session("other", &[("z", "t1")]);
+
+        let found = detect_forks(&[original, fork, nested, unrelated]);
+        let links: Vec<_> = found
+            .iter()
+            .map(|(fork, link)| (fork.as_str(), link.parent.as_str(), link.shared_entries))
+            .collect();
+        assert_eq!(links, [("fork", "orig", 2), ("nested", "fork", 3)]);
+
+        let mut index = FamilyIndex::default();
+        for (fork, link) in found {
+            assert!(index.link(&fork, link.clone()));
+            assert!(!index.link(&fork, link));
+        }
+        assert_eq!(index.root_of("nested"), "orig");
+        assert_eq!(index.root_of("orig"), "orig");
+        assert_eq!(index.parent_of("nested"), Some("fork"));
+    }
+
+    #[test]
+    fn test_root_of_survives_cycles() {
+        let mut index = FamilyIndex::default();
+        for (fork, parent) in [("a", "b"), ("b", "a")] {
+            index.link(
+                fork,
+                ForkLink {
+                    parent: parent.to_string(),
+                    shared_entries: 1,
+                },
+            );
+        }
+        assert_eq!(index.root_of("a"), "b");
+    }
+}
@@ -1,10 +1,17 @@
 use anyhow::{bail, Context, Result};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::glyphs;
+use crate::parser::{
+    ContentKeyStrategies, ContentKeyStrategy, ConversationSession, SessionIdSource,
+};
 use crate::scm::Backend;
+use crate::sync::TodoMergeMode;
 
 /// Filter configuration for syncing Claude Code history
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +32,12 @@ pub struct FilterConfig {
     #[serde(default = "default_max_file_size")]
     pub max_file_size_bytes: u64,
 
+    /// What happens to large session files when they are saved to the sync
+    /// repo, by size band; a file gets the action of the largest band it
+    /// reaches (default: warn from 10MB)
+    #[serde(default = "default_large_file_bands")]
+    pub large_file_bands: Vec<LargeFileBand>,
+
     /// Exclude file attachments (images, PDFs, etc.)
     #[serde(default)]
     pub exclude_attachments: bool,
@@ -57,6 +70,462 @@ pub struct FilterConfig {
     /// Use this to sync from a non-standard location
     #[serde(skip_serializing_if = "Option::is_none")]
     pub claude_projects_dir: Option<String>,
+
+    /// Session IDs that are always synced, even when age or size filters would exclude them
+    /// Pinned sessions are also processed before all other sessions
+    #[serde(default)]
+    pub pinned_sessions: Vec<String>,
+
+    /// Sessions never copied into the sync repo, even when include patterns
+    /// match them: session IDs, project directory names (as under
+    /// `~/.claude/projects`), or glob patterns matched against the file path
+    #[serde(default)]
+    pub local_only: Vec<String>,
+
+    /// Write session entries back byte-identical to the lines Claude Code wrote
+    /// Only merged or newly built entries are re-serialized, which keeps git
+    /// deltas small and diffs readable
+    #[serde(default)]
+    pub preserve_raw_lines: bool,
+
+    /// Sync Claude Code todo lists (~/.claude/todos/*.json) alongside sessions
+    #[serde(default)]
+    pub sync_todos: bool,
+
+    /// How to merge a todo file changed on both machines: "last-writer-wins" or "per-item"
+    #[serde(default = "default_todo_merge_mode")]
+    pub todo_merge_mode: String,
+
+    /// Sync per-session file backups (~/.claude/file-history/)
+    #[serde(default)]
+    pub sync_file_history: bool,
+
+    /// Sync captured shell environments (~/.claude/shell-snapshots/)
+    #[serde(default)]
+    pub sync_shell_snapshots: bool,
+
+    /// Maximum size of a single file-history or shell snapshot file to sync (default: 1MB)
+    #[serde(default = "default_artifact_max_file_size")]
+    pub artifact_max_file_size_bytes: u64,
+
+    /// How subagent (sidechain) content is handled when saving sessions to the sync repo:
+    /// "keep" (default), "drop-entries", or "drop-sessions"
+    #[serde(default = "default_sidechain_mode")]
+    pub sidechain_mode: String,
+
+    /// Compact sessions whose latest activity is older than N days before syncing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compact_older_than_days: Option<u32>,
+
+    /// Threads used by parallel phases such as session discovery (default:
+    /// one per CPU core)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallelism: Option<usize>,
+
+    /// Split session files in the sync repo holding more than this many
+    /// entries into continuation files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_entries_per_file: Option<usize>,
+
+    /// Payloads (tool outputs, file snapshots) larger than this are compacted (default: 32KB)
+    #[serde(default = "default_compact_min_payload_bytes")]
+    pub compact_min_payload_bytes: u64,
+
+    /// Keep a local-only copy of each session before it is compacted
+    #[serde(default)]
+    pub compaction_archive: bool,
+
+    /// Built-in PII scrubbing rules applied to sessions saved to the sync
+    /// repo: "email", "phone", "ip"
+    #[serde(default)]
+    pub scrub_rules: Vec<String>,
+
+    /// Extra regexes scrubbed from sessions saved to the sync repo
+    #[serde(default)]
+    pub scrub_regexes: Vec<String>,
+
+    /// Regexes for matches that are never scrubbed (e.g. a public support
+    /// address)
+    #[serde(default)]
+    pub scrub_allowlist: Vec<String>,
+
+    /// Only sync sessions carrying one of these tags (see `tag add`)
+    #[serde(default)]
+    pub include_tags: Vec<String>,
+
+    /// Never sync sessions carrying one of these tags
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+
+    /// After each pull, re-check that no session in .claude has diverged from
+    /// the sync repo, and fail the pull if one has
+    #[serde(default)]
+    pub verify_after_pull: bool,
+
+    /// Write a manifest of per-file content hashes on push, and fail a pull
+    /// whose fetched files don't match the remote's manifest
+    #[serde(default)]
+    pub checksum_manifest: bool,
+
+    /// Commit each touched project directory separately, with per-project
+    /// stats in the message, instead of one commit per sync
+    #[serde(default)]
+    pub commit_per_project: bool,
+
+    /// Write an `INDEX.md` summarizing the sessions of each project
+    /// directory in the sync repo on push
+    #[serde(default)]
+    pub project_index: bool,
+
+    /// Times a push rejected because the remote moved on is retried, after
+    /// merging the remote's new commits (0 = fail right away)
+    #[serde(default = "default_push_retries")]
+    pub push_retries: u32,
+
+    /// Seconds a lease on origin taken by `sync` and `push` lasts, so
+    /// machines take turns pushing (0 = no lease)
+    #[serde(default)]
+    pub remote_lease_secs: u64,
+
+    /// Seconds that must pass after an operation before a scheduled or API
+    /// sync runs (0 = no minimum)
+    #[serde(default)]
+    pub min_sync_interval_secs: u64,
+
+    /// URL requested after each successful pull, push, or sync, for
+    /// dead man's switch monitoring (healthchecks.io and the like)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthcheck_url: Option<String>,
+
+    /// URL the error is posted to when a pull, push, or sync fails
+    /// (default: `healthcheck_url` with `/fail` appended)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthcheck_fail_url: Option<String>,
+
+    /// Seconds since a session's last write during which a pull treats it
+    /// as in use by Claude Code and only appends to it (0 = never)
+    ///
+    /// Sessions of projects a running `claude` process works in count as in
+    /// use too.
+    #[serde(default)]
+    pub active_session_window_secs: u64,
+
+    /// Tombstone sessions whose files disappear from .claude between pulls,
+    /// so the deletion propagates to other machines
+    #[serde(default)]
+    pub detect_deletions: bool,
+
+    /// How sessions are stored in the sync repo: "files" (default) or "oplog"
+    #[serde(default = "default_sync_mode")]
+    pub sync_mode: String,
+
+    /// Store this user's sessions under `users/<name>/` in a team-shared sync repo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_namespace: Option<String>,
+
+    /// Extra remotes that every successful push to origin is mirrored to
+    #[serde(default)]
+    pub mirror_remotes: Vec<String>,
+
+    /// Conversations listed per project in pull summaries (0 = no limit)
+    ///
+    /// When the cap is hit on a terminal, the full listing is shown in
+    /// `$PAGER` instead of being cut off.
+    #[serde(default = "default_max_conversations_display")]
+    pub max_conversations_display: usize,
+
+    /// How diverged sessions are resolved on pull, unless a project policy
+    /// applies: "smart-merge" (default), "prefer-local", "prefer-remote", or
+    /// "keep-both"
+    #[serde(default = "default_conflict_policy")]
+    pub conflict_policy: String,
+
+    /// Per-project conflict policies, consulted in order before
+    /// `conflict_policy`; the first matching pattern wins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub project_conflict_policies: Vec<ProjectConflictPolicy>,
+
+    /// How an entry edited in place (same UUID, different content) is
+    /// reconciled: "prefer-newer", "prefer-local", "prefer-remote", or "fork"
+    #[serde(default = "default_modified_entry_policy")]
+    pub modified_entry_policy: String,
+
+    /// Which side wins on pull when local and remote hold the same session
+    /// entry or history.jsonl entry in different versions and no policy
+    /// above decides: "local-first" (default) or "remote-first"
+    #[serde(default = "default_pull_precedence")]
+    pub pull_precedence: String,
+
+    /// Where a session's ID comes from: "entry" (first entry with a
+    /// sessionId, default) or "filename"
+    #[serde(default = "default_session_id_source")]
+    pub session_id_source: String,
+
+    /// Output of pull, push, and sync without `-q` or `--verbose`:
+    /// "quiet", "summary", "normal" (default), or "verbose"
+    #[serde(default = "default_verbosity")]
+    pub verbosity: String,
+
+    /// Colored output: "auto" (default; when stdout is a terminal and
+    /// NO_COLOR isn't set), "always", or "never"
+    #[serde(default = "default_color")]
+    pub color: String,
+
+    /// Language of sync summaries: "en" (default), "de", or "auto" to follow
+    /// LC_ALL, LC_MESSAGES, or LANG. JSON output stays in English.
+    #[serde(default = "default_locale")]
+    pub locale: String,
+
+    /// Mark results with ASCII ("[ok]", "->") instead of glyphs like ✓ and
+    /// ↳, as `--ascii` does
+    #[serde(default)]
+    pub ascii: bool,
+
+    /// How entries without UUIDs of a given type are deduplicated, by entry
+    /// type: "full-entry" (default), "message", or "occurrence"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub content_key_strategies: BTreeMap<String, String>,
+
+    /// Maximum number of past conflict reports kept (0 = no limit)
+    #[serde(default = "default_conflict_report_max_count")]
+    pub conflict_report_max_count: usize,
+
+    /// Days past conflict reports are kept (0 = no limit)
+    #[serde(default = "default_conflict_report_retention_days")]
+    pub conflict_report_retention_days: u32,
+}
+
+/// A size band for large session files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LargeFileBand {
+    /// Smallest file size (in MB) the band applies to
+    pub min_size_mb: u64,
+    /// Action name (see [`LargeFileAction::parse`])
+    pub action: String,
+}
+
+/// What happens to a session file in a [`LargeFileBand`] when it is saved
+/// to the sync repo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LargeFileAction {
+    /// Save it and print a warning
+    Warn,
+    /// Leave it out of the sync repo
+    Skip,
+    /// Save it and track it with Git LFS
+    Lfs,
+    /// Compact its large payloads (like `compact_older_than_days`,
+    /// regardless of age)
+    Compress,
+}
+
+impl LargeFileAction {
+    /// Parse an action name from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "warn" => Some(LargeFileAction::Warn),
+            "skip" => Some(LargeFileAction::Skip),
+            "lfs" => Some(LargeFileAction::Lfs),
+            "compress" => Some(LargeFileAction::Compress),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LargeFileAction::Warn => "warn",
+            LargeFileAction::Skip => "skip",
+            LargeFileAction::Lfs => "lfs",
+            LargeFileAction::Compress => "compress",
+        }
+    }
+}
+
+/// A conflict policy for sessions whose path matches a project pattern
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectConflictPolicy {
+    /// Glob-style pattern matched against the session file path, like
+    /// `include_patterns`
+    pub pattern: String,
+    /// Policy name (see [`ConflictPolicy::parse`])
+    pub policy: String,
+}
+
+/// How a diverged session is resolved during pull
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Combine both versions, falling back to manual resolution or keep-both
+    SmartMerge,
+    /// Keep the local version in the sync repo, discarding remote changes
+    PreferLocal,
+    /// Overwrite the local copy with the remote version (after a backup)
+    PreferRemote,
+    /// Keep local and save the remote version next to it with a conflict suffix
+    KeepBoth,
+}
+
+impl ConflictPolicy {
+    /// Parse a policy name from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "smart-merge" => Some(ConflictPolicy::SmartMerge),
+            "prefer-local" => Some(ConflictPolicy::PreferLocal),
+            "prefer-remote" => Some(ConflictPolicy::PreferRemote),
+            "keep-both" => Some(ConflictPolicy::KeepBoth),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictPolicy::SmartMerge => "smart-merge",
+            ConflictPolicy::PreferLocal => "prefer-local",
+            ConflictPolicy::PreferRemote => "prefer-remote",
+            ConflictPolicy::KeepBoth => "keep-both",
+        }
+    }
+}
+
+/// How an entry present on both sides with different content is reconciled
+///
+/// Only applies when neither side has entries the other lacks beyond a
+/// plain extension; sessions where both sides added entries are diverged
+/// and handled by [`ConflictPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifiedEntryPolicy {
+    /// Keep the version with the newer timestamp ([`PullPrecedence`] on a tie)
+    PreferNewer,
+    PreferLocal,
+    PreferRemote,
+    /// Treat the session as diverged and fork it
+    Fork,
+}
+
+impl ModifiedEntryPolicy {
+    /// Parse a policy name from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "prefer-newer" => Some(ModifiedEntryPolicy::PreferNewer),
+            "prefer-local" => Some(ModifiedEntryPolicy::PreferLocal),
+            "prefer-remote" => Some(ModifiedEntryPolicy::PreferRemote),
+            "fork" => Some(ModifiedEntryPolicy::Fork),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModifiedEntryPolicy::PreferNewer => "prefer-newer",
+            ModifiedEntryPolicy::PreferLocal => "prefer-local",
+            ModifiedEntryPolicy::PreferRemote => "prefer-remote",
+            ModifiedEntryPolicy::Fork => "fork",
+        }
+    }
+}
+
+/// Which side's version wins when a pull combines local and remote data
+///
+/// Applies to diverged sessions merged inline, ties between edited entries
+/// under [`ModifiedEntryPolicy::PreferNewer`], and both history.jsonl merges
+/// (saving local history to the sync repo and merging it back). Explicit
+/// policies such as `prefer-remote` still take precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PullPrecedence {
+    #[default]
+    LocalFirst,
+    RemoteFirst,
+}
+
+impl PullPrecedence {
+    /// Parse a precedence name from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "local-first" => Some(PullPrecedence::LocalFirst),
+            "remote-first" => Some(PullPrecedence::RemoteFirst),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PullPrecedence::LocalFirst => "local-first",
+            PullPrecedence::RemoteFirst => "remote-first",
+        }
+    }
+}
+
+/// How subagent (sidechain) content is handled when saving sessions to the sync repo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SidechainMode {
+    /// Sync everything
+    Keep,
+    /// Drop individual entries marked `isSidechain` (and sessions left empty)
+    DropEntries,
+    /// Drop sessions that consist only of sidechain messages
+    DropSessions,
+}
+
+impl SidechainMode {
+    /// Parse a mode name from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "keep" => Some(SidechainMode::Keep),
+            "drop-entries" => Some(SidechainMode::DropEntries),
+            "drop-sessions" => Some(SidechainMode::DropSessions),
+            _ => None,
+        }
+    }
+
+    /// Apply this mode to a session
+    ///
+    /// Returns `None` if the session should be skipped entirely.
+    pub fn apply<'a>(&self, session: &'a ConversationSession) -> Option<Cow<'a, ConversationSession>> {
+        match self {
+            SidechainMode::Keep => Some(Cow::Borrowed(session)),
+            SidechainMode::DropSessions => {
+                (!session.is_sidechain()).then_some(Cow::Borrowed(session))
+            }
+            SidechainMode::DropEntries => {
+                if !session.entries.iter().any(|e| e.is_sidechain()) {
+                    return Some(Cow::Borrowed(session));
+                }
+                let entries: Vec<_> = session
+                    .entries
+                    .iter()
+                    .filter(|e| !e.is_sidechain())
+                    .cloned()
+                    .collect();
+                if entries.is_empty() {
+                    return None;
+                }
+                Some(Cow::Owned(ConversationSession {
+                    session_id: session.session_id.clone(),
+                    entries,
+                    file_path: session.file_path.clone(),
+                }))
+            }
+        }
+    }
+}
+
+/// How sessions are stored in the sync repo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncMode {
+    /// Session files, merged on pull
+    Files,
+    /// Per-machine logs of entries, materialized on pull (see `sync::oplog`)
+    OpLog,
+}
+
+impl SyncMode {
+    /// Parse a mode name from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "files" => Some(SyncMode::Files),
+            "oplog" | "op-log" => Some(SyncMode::OpLog),
+            _ => None,
+        }
+    }
 }
 
 fn default_lfs_patterns() -> Vec<String> {
@@ -67,6 +536,78 @@ fn default_max_file_size() -> u64 {
     10 * 1024 * 1024 // 10MB
 }
 
+fn default_large_file_bands() -> Vec<LargeFileBand> {
+    vec![LargeFileBand {
+        min_size_mb: 10,
+        action: "warn".to_string(),
+    }]
+}
+
+fn default_artifact_max_file_size() -> u64 {
+    1024 * 1024 // 1MB
+}
+
+fn default_compact_min_payload_bytes() -> u64 {
+    32 * 1024 // 32KB
+}
+
+fn default_max_conversations_display() -> usize {
+    10
+}
+
+fn default_session_id_source() -> String {
+    "entry".to_string()
+}
+
+fn default_modified_entry_policy() -> String {
+    "prefer-newer".to_string()
+}
+
+fn default_pull_precedence() -> String {
+    "local-first".to_string()
+}
+
+fn default_verbosity() -> String {
+    "normal".to_string()
+}
+
+fn default_color() -> String {
+    "auto".to_string()
+}
+
+fn default_locale() -> String {
+    "en".to_string()
+}
+
+/// The locales with a catalog, quoted, for error messages
+fn locale_names() -> String {
+    crate::i18n::Locale::ALL
+        .iter()
+        .map(|l| format!("'{}'", l.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn default_conflict_report_max_count() -> usize {
+    50
+}
+
+fn default_conflict_report_retention_days() -> u32 {
+    90
+}
+
+fn default_conflict_policy() -> String {
+    "smart-merge".to_string()
+}
+
+fn default_sync_mode() -> String {
+    "files".to_string()
+}
+
+fn default_sidechain_mode() -> String {
+    "keep".to_string()
+}
+
 fn default_scm_backend() -> String {
     "git".to_string()
 }
@@ -75,6 +616,14 @@ fn default_sync_subdirectory() -> String {
     "projects".to_string()
 }
 
+fn default_todo_merge_mode() -> String {
+    "last-writer-wins".to_string()
+}
+
+fn default_push_retries() -> u32 {
+    3
+}
+
 fn default_temp_branch_retention_hours() -> u32 {
     24 // Keep temp branches for 24 hours by default
 }
@@ -86,6 +635,7 @@ impl Default for FilterConfig {
             include_patterns: Vec::new(),
             exclude_patterns: Vec::new(),
             max_file_size_bytes: default_max_file_size(),
+            large_file_bands: default_large_file_bands(),
             exclude_attachments: false,
             enable_lfs: false,
             lfs_patterns: default_lfs_patterns(),
@@ -93,6 +643,52 @@ impl Default for FilterConfig {
             sync_subdirectory: default_sync_subdirectory(),
             temp_branch_retention_hours: default_temp_branch_retention_hours(),
             claude_projects_dir: None,
+            pinned_sessions: Vec::new(),
+            local_only: Vec::new(),
+            preserve_raw_lines: false,
+            sync_todos: false,
+            todo_merge_mode: default_todo_merge_mode(),
+            sync_file_history: false,
+            sync_shell_snapshots: false,
+            artifact_max_file_size_bytes: default_artifact_max_file_size(),
+            sidechain_mode: default_sidechain_mode(),
+            compact_older_than_days: None,
+            max_entries_per_file: None,
+            parallelism: None,
+            compact_min_payload_bytes: default_compact_min_payload_bytes(),
+            compaction_archive: false,
+            scrub_rules: Vec::new(),
+            scrub_regexes: Vec::new(),
+            scrub_allowlist: Vec::new(),
+            include_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+            verify_after_pull: false,
+            checksum_manifest: false,
+            commit_per_project: false,
+            project_index: false,
+            push_retries: default_push_retries(),
+            remote_lease_secs: 0,
+            min_sync_interval_secs: 0,
+            healthcheck_url: None,
+            healthcheck_fail_url: None,
+            active_session_window_secs: 0,
+            detect_deletions: false,
+            sync_mode: default_sync_mode(),
+            user_namespace: None,
+            mirror_remotes: Vec::new(),
+            max_conversations_display: default_max_conversations_display(),
+            conflict_policy: default_conflict_policy(),
+            project_conflict_policies: Vec::new(),
+            modified_entry_policy: default_modified_entry_policy(),
+            pull_precedence: default_pull_precedence(),
+            verbosity: default_verbosity(),
+            color: default_color(),
+            locale: default_locale(),
+            ascii: false,
+            session_id_source: default_session_id_source(),
+            content_key_strategies: BTreeMap::new(),
+            conflict_report_max_count: default_conflict_report_max_count(),
+            conflict_report_retention_days: default_conflict_report_retention_days(),
         }
     }
 }
@@ -106,31 +702,21 @@ impl FilterConfig {
             return Ok(Self::default());
         }
 
-        let content = fs::read_to_string(&config_path)
-            .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
-
-        let config: FilterConfig =
-            toml::from_str(&content).context("Failed to parse config file")?;
-
-        Ok(config)
+        crate::state_file::read_with_recovery(&config_path, "config file", |content| {
+            toml::from_str(content).context("Failed to parse config file")
+        })
     }
 
     /// Save configuration to file
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path()?;
 
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent).with_context(|| {
-                format!("Failed to create config directory: {}", parent.display())
-            })?;
-        }
-
         let content = toml::to_string_pretty(self).context("Failed to serialize config")?;
 
-        fs::write(&config_path, content)
-            .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
-
-        Ok(())
+        crate::state_file::write_atomic(&config_path, &content, |current| {
+            toml::from_str::<FilterConfig>(current).is_ok()
+        })
+        .with_context(|| format!("Failed to write config file: {}", config_path.display()))
     }
 
     /// Get the path to the config file
@@ -138,22 +724,71 @@ impl FilterConfig {
         crate::config::ConfigManager::filter_config_path()
     }
 
+    /// Check if a session file is pinned (its file stem matches a pinned session ID)
+    pub fn is_pinned(&self, file_path: &Path) -> bool {
+        if self.pinned_sessions.is_empty() {
+            return false;
+        }
+        file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|stem| self.pinned_sessions.iter().any(|id| id == stem))
+            .unwrap_or(false)
+    }
+
+    /// Check if a session file is local-only: its file stem is a listed
+    /// session ID, a directory above it is a listed project or session, or
+    /// its path matches a listed glob pattern
+    pub fn is_local_only(&self, file_path: &Path) -> bool {
+        if self.local_only.is_empty() {
+            return false;
+        }
+        let path_str = file_path.to_string_lossy();
+        let stem = file_path.file_stem().and_then(|s| s.to_str());
+        self.local_only.iter().any(|entry| {
+            if entry.contains('*') {
+                return glob_match(entry, &path_str);
+            }
+            stem == Some(entry.as_str())
+                || file_path
+                    .ancestors()
+                    .skip(1)
+                    .any(|dir| dir.file_name().is_some_and(|name| name == entry.as_str()))
+        })
+    }
+
     /// Check if a file should be included based on filters
+    ///
+    /// Pinned sessions bypass the size and age filters but still honor
+    /// the attachment and project pattern filters.
     pub fn should_include(&self, file_path: &Path) -> bool {
+        self.exclusion_reason(file_path).is_none()
+    }
+
+    /// Why the filters exclude a file, or `None` if it's included
+    pub fn exclusion_reason(&self, file_path: &Path) -> Option<String> {
         // Only process .jsonl files (exclude attachments if configured)
         if self.exclude_attachments {
             if let Some(ext) = file_path.extension() {
                 if ext != "jsonl" {
                     // This is an attachment (image, PDF, etc.)
-                    return false;
+                    return Some("attachment (exclude_attachments)".to_string());
                 }
             }
         }
 
-        // Check file size
-        if let Ok(metadata) = fs::metadata(file_path) {
-            if metadata.len() > self.max_file_size_bytes {
-                return false;
+        let pinned = self.is_pinned(file_path);
+
+        // Check file size (pinned sessions are exempt)
+        if !pinned {
+            if let Ok(metadata) = fs::metadata(file_path) {
+                if metadata.len() > self.max_file_size_bytes {
+                    return Some(format!(
+                        "larger than max_file_size ({} > {} bytes)",
+                        metadata.len(),
+                        self.max_file_size_bytes
+                    ));
+                }
             }
         }
 
@@ -163,7 +798,7 @@ impl FilterConfig {
         if !self.exclude_patterns.is_empty() {
             for pattern in &self.exclude_patterns {
                 if glob_match(pattern, &path_str) {
-                    return false;
+                    return Some(format!("matches exclude pattern '{pattern}'"));
                 }
             }
         }
@@ -178,12 +813,12 @@ impl FilterConfig {
                 }
             }
             if !matches_include {
-                return false;
+                return Some("matches no include pattern".to_string());
             }
         }
 
-        // Check age filter
-        if let Some(max_days) = self.exclude_older_than_days {
+        // Check age filter (pinned sessions are exempt)
+        if let Some(max_days) = self.exclude_older_than_days.filter(|_| !pinned) {
             if let Ok(metadata) = fs::metadata(file_path) {
                 if let Ok(modified) = metadata.modified() {
                     let age = std::time::SystemTime::now()
@@ -192,13 +827,15 @@ impl FilterConfig {
 
                     let max_age = std::time::Duration::from_secs((max_days as u64) * 24 * 60 * 60);
                     if age > max_age {
-                        return false;
+                        return Some(format!(
+                            "older than exclude_older_than_days ({max_days} days)"
+                        ));
                     }
                 }
             }
         }
 
-        true
+        None
     }
 
     /// Get the configured SCM backend.
@@ -210,9 +847,160 @@ impl FilterConfig {
         }
     }
 
+    /// Path of this user's projects directory, relative to the sync repo root
+    ///
+    /// With a user namespace this is `users/<name>/<sync_subdirectory>`.
+    pub fn projects_subdir(&self) -> PathBuf {
+        match &self.user_namespace {
+            Some(user) => crate::sync::user_projects_subdir(user, &self.sync_subdirectory),
+            None => PathBuf::from(&self.sync_subdirectory),
+        }
+    }
+
+    /// Get the configured todo merge mode.
+    pub fn todo_merge_mode(&self) -> Result<TodoMergeMode> {
+        TodoMergeMode::parse(&self.todo_merge_mode).with_context(|| {
+            format!(
+                "Unknown todo merge mode: '{}'. Use 'last-writer-wins' or 'per-item'.",
+                self.todo_merge_mode
+            )
+        })
+    }
+
+    /// Get the configured sidechain mode.
+    pub fn sidechain_mode(&self) -> Result<SidechainMode> {
+        SidechainMode::parse(&self.sidechain_mode).with_context(|| {
+            format!(
+                "Unknown sidechain mode: '{}'. Use 'keep', 'drop-entries', or 'drop-sessions'.",
+                self.sidechain_mode
+            )
+        })
+    }
+
+    /// Conflict policy for the session at `file_path`
+    ///
+    /// The first project policy whose pattern matches wins; otherwise the
+    /// global `conflict_policy` applies.
+    pub fn conflict_policy_for(&self, file_path: &Path) -> Result<ConflictPolicy> {
+        let path_str = file_path.to_string_lossy();
+        let name = self
+            .project_conflict_policies
+            .iter()
+            .find(|p| glob_match(&p.pattern, &path_str))
+            .map_or(self.conflict_policy.as_str(), |p| p.policy.as_str());
+        parse_conflict_policy(name)
+    }
+
+    /// Action for a session file of `size` bytes, from the largest band it
+    /// reaches (`None` below every band)
+    pub fn large_file_action(&self, size: u64) -> Result<Option<LargeFileAction>> {
+        self.large_file_bands
+            .iter()
+            .filter(|band| size >= band.min_size_mb * 1024 * 1024)
+            .max_by_key(|band| band.min_size_mb)
+            .map(|band| parse_large_file_action(&band.action))
+            .transpose()
+    }
+
+    /// Get the configured policy for entries edited in place.
+    pub fn modified_entry_policy(&self) -> Result<ModifiedEntryPolicy> {
+        ModifiedEntryPolicy::parse(&self.modified_entry_policy).with_context(|| {
+            format!(
+                "Unknown modified entry policy: '{}'. Use 'prefer-newer', 'prefer-local', 'prefer-remote', or 'fork'.",
+                self.modified_entry_policy
+            )
+        })
+    }
+
+    /// Get the configured pull precedence.
+    pub fn pull_precedence(&self) -> Result<PullPrecedence> {
+        PullPrecedence::parse(&self.pull_precedence).with_context(|| {
+            format!(
+                "Unknown pull precedence: '{}'. Use 'local-first' or 'remote-first'.",
+                self.pull_precedence
+            )
+        })
+    }
+
+    /// Get the configured default verbosity.
+    pub fn verbosity(&self) -> Result<crate::VerbosityLevel> {
+        crate::VerbosityLevel::parse(&self.verbosity).with_context(|| {
+            format!(
+                "Unknown verbosity: '{}'. Use 'quiet', 'summary', 'normal', or 'verbose'.",
+                self.verbosity
+            )
+        })
+    }
+
+    /// Get the configured color setting.
+    pub fn color(&self) -> Result<crate::color::ColorChoice> {
+        crate::color::ColorChoice::parse(&self.color).with_context(|| {
+            format!(
+                "Unknown color setting: '{}'. Use 'auto', 'always', or 'never'.",
+                self.color
+            )
+        })
+    }
+
+    /// Get the configured locale, resolving "auto" from the environment.
+    pub fn locale(&self) -> Result<crate::i18n::Locale> {
+        if self.locale.eq_ignore_ascii_case("auto") {
+            return Ok(crate::i18n::Locale::from_env());
+        }
+        crate::i18n::Locale::parse(&self.locale).with_context(|| {
+            format!(
+                "Unknown locale: '{}'. Use {}, or 'auto'.",
+                self.locale,
+                locale_names()
+            )
+        })
+    }
+
+    /// Compile the configured PII scrubbing rules.
+    pub fn scrubber(&self) -> Result<crate::scrubbing::Scrubber> {
+        crate::scrubbing::Scrubber::new(
+            &self.scrub_rules,
+            &self.scrub_regexes,
+            &self.scrub_allowlist,
+        )
+    }
+
+    /// Get the configured session ID source.
+    pub fn session_id_source(&self) -> Result<SessionIdSource> {
+        SessionIdSource::parse(&self.session_id_source).with_context(|| {
+            format!(
+                "Unknown session ID source: '{}'. Use 'entry' or 'filename'.",
+                self.session_id_source
+            )
+        })
+    }
+
+    /// Get the configured content key strategies for entries without UUIDs.
+    pub fn content_key_strategies(&self) -> Result<ContentKeyStrategies> {
+        let mut by_type = HashMap::new();
+        for (entry_type, strategy) in &self.content_key_strategies {
+            by_type.insert(entry_type.clone(), parse_content_key_strategy(strategy)?);
+        }
+        Ok(ContentKeyStrategies::new(by_type))
+    }
+
+    /// Get the configured sync mode.
+    pub fn sync_mode(&self) -> Result<SyncMode> {
+        SyncMode::parse(&self.sync_mode).with_context(|| {
+            format!(
+                "Unknown sync mode: '{}'. Use 'files' or 'oplog'.",
+                self.sync_mode
+            )
+        })
+    }
+
     /// Validate the configuration.
     ///
-    /// Returns an error if LFS is enabled with a non-git backend.
+    /// Returns an error if LFS is enabled with a non-git backend, or if the
+    /// todo merge mode, sidechain mode, sync mode, a conflict policy, a large
+    /// file action, the modified entry policy, the pull precedence, the
+    /// verbosity, the color setting, the locale, or user namespace is not
+    /// valid.
     pub fn validate(&self) -> Result<()> {
         if self.enable_lfs && self.scm_backend.to_lowercase() != "git" {
             bail!(
@@ -221,12 +1009,79 @@ impl FilterConfig {
                 self.scm_backend
             );
         }
+        self.todo_merge_mode()?;
+        self.sidechain_mode()?;
+        self.sync_mode()?;
+        parse_conflict_policy(&self.conflict_policy)?;
+        for rule in &self.project_conflict_policies {
+            parse_conflict_policy(&rule.policy)?;
+        }
+        for band in &self.large_file_bands {
+            parse_large_file_action(&band.action)?;
+        }
+        self.modified_entry_policy()?;
+        self.pull_precedence()?;
+        self.verbosity()?;
+        self.color()?;
+        self.locale()?;
+        self.scrubber()?;
+        self.content_key_strategies()?;
+        self.session_id_source()?;
+        if let Some(user) = &self.user_namespace {
+            crate::sync::validate_user_name(user)?;
+        }
+        if self.mirror_remotes.iter().any(|r| r == "origin") {
+            bail!("'origin' is the primary remote and cannot also be a mirror remote");
+        }
         Ok(())
     }
 }
 
+fn parse_conflict_policy(name: &str) -> Result<ConflictPolicy> {
+    ConflictPolicy::parse(name).with_context(|| {
+        format!(
+            "Unknown conflict policy: '{}'. Use 'smart-merge', 'prefer-local', 'prefer-remote', or 'keep-both'.",
+            name
+        )
+    })
+}
+
+fn parse_large_file_action(name: &str) -> Result<LargeFileAction> {
+    LargeFileAction::parse(name).with_context(|| {
+        format!(
+            "Unknown large file action: '{}'. Use 'warn', 'skip', 'lfs', or 'compress'.",
+            name
+        )
+    })
+}
+
+/// A healthcheck URL setting: `None` for an empty one, which turns the
+/// setting off
+fn parse_healthcheck_url(url: &str) -> Result<Option<String>> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Ok(None);
+    }
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        bail!(
+            "Invalid healthcheck URL: '{}'. Use an http:// or https:// URL.",
+            url
+        );
+    }
+    Ok(Some(url.to_string()))
+}
+
+fn parse_content_key_strategy(name: &str) -> Result<ContentKeyStrategy> {
+    ContentKeyStrategy::parse(name).with_context(|| {
+        format!(
+            "Unknown content key strategy: '{}'. Use 'full-entry', 'message', or 'occurrence'.",
+            name
+        )
+    })
+}
+
 /// Simple glob pattern matching
-fn glob_match(pattern: &str, text: &str) -> bool {
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
     // Simple implementation - for production, use the `glob` crate
     if pattern.contains('*') {
         let parts: Vec<_> = pattern.split('*').collect();
@@ -272,6 +1127,51 @@ pub fn update_config(
     sync_subdirectory: Option<String>,
     temp_branch_retention: Option<u32>,
     claude_projects_dir: Option<String>,
+    preserve_raw_lines: Option<bool>,
+    sync_todos: Option<bool>,
+    todo_merge_mode: Option<String>,
+    sync_file_history: Option<bool>,
+    sync_shell_snapshots: Option<bool>,
+    artifact_max_size_mb: Option<u64>,
+    sidechain_mode: Option<String>,
+    compact_older_than: Option<u32>,
+    compact_min_payload_kb: Option<u64>,
+    compaction_archive: Option<bool>,
+    include_tags: Option<String>,
+    exclude_tags: Option<String>,
+    user_namespace: Option<String>,
+    sync_mode: Option<String>,
+    verify_after_pull: Option<bool>,
+    detect_deletions: Option<bool>,
+    mirror_remotes: Option<String>,
+    max_conversations_display: Option<usize>,
+    conflict_policy: Option<String>,
+    project_conflict_policies: Option<String>,
+    conflict_report_max_count: Option<usize>,
+    conflict_report_retention_days: Option<u32>,
+    modified_entry_policy: Option<String>,
+    pull_precedence: Option<String>,
+    content_key_strategies: Option<String>,
+    session_id_source: Option<String>,
+    max_entries_per_file: Option<usize>,
+    large_file_actions: Option<String>,
+    parallelism: Option<usize>,
+    scrub_rules: Option<String>,
+    scrub_regexes: Option<Vec<String>>,
+    scrub_allowlist: Option<Vec<String>>,
+    checksum_manifest: Option<bool>,
+    commit_per_project: Option<bool>,
+    project_index: Option<bool>,
+    active_session_window: Option<u64>,
+    remote_lease: Option<u64>,
+    min_sync_interval: Option<u64>,
+    push_retries: Option<u32>,
+    healthcheck_url: Option<String>,
+    healthcheck_fail_url: Option<String>,
+    verbosity: Option<String>,
+    color: Option<String>,
+    locale: Option<String>,
+    ascii: Option<bool>,
 ) -> Result<()> {
     let mut config = FilterConfig::load()?;
 
@@ -380,17 +1280,579 @@ pub fn update_config(
         }
     }
 
-    // Validate configuration before saving
-    config.validate()?;
+    if let Some(preserve) = preserve_raw_lines {
+        config.preserve_raw_lines = preserve;
+        println!(
+            "{}",
+            format!(
+                "Raw line preservation: {}",
+                if preserve { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
 
-    config.save()?;
-    println!("{}", "Configuration saved successfully!".green().bold());
+    if let Some(todos) = sync_todos {
+        config.sync_todos = todos;
+        println!(
+            "{}",
+            format!("Todo sync: {}", if todos { "enabled" } else { "disabled" }).green()
+        );
+    }
 
-    Ok(())
-}
+    if let Some(mode) = todo_merge_mode {
+        let mode_lower = mode.trim().to_lowercase();
+        if TodoMergeMode::parse(&mode_lower).is_none() {
+            bail!("Invalid todo merge mode: '{}'. Use 'last-writer-wins' or 'per-item'.", mode);
+        }
+        config.todo_merge_mode = mode_lower;
+        println!(
+            "{}",
+            format!("Set todo merge mode: {}", config.todo_merge_mode).green()
+        );
+    }
 
-/// Show the current filter configuration
-pub fn show_config() -> Result<()> {
+    if let Some(file_history) = sync_file_history {
+        config.sync_file_history = file_history;
+        println!(
+            "{}",
+            format!(
+                "File history sync: {}",
+                if file_history { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
+
+    if let Some(shell_snapshots) = sync_shell_snapshots {
+        config.sync_shell_snapshots = shell_snapshots;
+        println!(
+            "{}",
+            format!(
+                "Shell snapshot sync: {}",
+                if shell_snapshots { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
+
+    if let Some(mb) = artifact_max_size_mb {
+        config.artifact_max_file_size_bytes = mb * 1024 * 1024;
+        println!(
+            "{}",
+            format!("Set artifact max file size: {mb} MB").green()
+        );
+    }
+
+    if let Some(mode) = sidechain_mode {
+        let mode_lower = mode.trim().to_lowercase();
+        if SidechainMode::parse(&mode_lower).is_none() {
+            bail!(
+                "Invalid sidechain mode: '{}'. Use 'keep', 'drop-entries', or 'drop-sessions'.",
+                mode
+            );
+        }
+        config.sidechain_mode = mode_lower;
+        println!(
+            "{}",
+            format!("Set sidechain mode: {}", config.sidechain_mode).green()
+        );
+    }
+
+    if let Some(days) = compact_older_than {
+        if days == 0 {
+            config.compact_older_than_days = None;
+            println!("{}", "Compaction disabled".green());
+        } else {
+            config.compact_older_than_days = Some(days);
+            println!(
+                "{}",
+                format!("Compacting sessions older than {days} days").green()
+            );
+        }
+    }
+
+    if let Some(kb) = compact_min_payload_kb {
+        config.compact_min_payload_bytes = kb * 1024;
+        println!(
+            "{}",
+            format!("Set compaction threshold: {kb} KB").green()
+        );
+    }
+
+    if let Some(archive) = compaction_archive {
+        config.compaction_archive = archive;
+        println!(
+            "{}",
+            format!(
+                "Compaction archive: {}",
+                if archive { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
+
+    if let Some(tags) = include_tags {
+        config.include_tags = tags.split(',').filter_map(crate::tags::normalize_tag).collect();
+        println!(
+            "{}",
+            format!("Set include tags: {:?}", config.include_tags).green()
+        );
+    }
+
+    if let Some(tags) = exclude_tags {
+        config.exclude_tags = tags.split(',').filter_map(crate::tags::normalize_tag).collect();
+        println!(
+            "{}",
+            format!("Set exclude tags: {:?}", config.exclude_tags).green()
+        );
+    }
+
+    if let Some(user) = user_namespace {
+        let user = user.trim();
+        if user.is_empty() {
+            config.user_namespace = None;
+            println!("{}", "User namespace cleared".green());
+        } else {
+            crate::sync::validate_user_name(user)?;
+            config.user_namespace = Some(user.to_string());
+            println!("{}", format!("Set user namespace: {user}").green());
+        }
+    }
+
+    if let Some(mode) = sync_mode {
+        let mode_lower = mode.trim().to_lowercase();
+        if SyncMode::parse(&mode_lower).is_none() {
+            bail!("Invalid sync mode: '{}'. Use 'files' or 'oplog'.", mode);
+        }
+        config.sync_mode = mode_lower;
+        println!(
+            "{}",
+            format!("Set sync mode: {}", config.sync_mode).green()
+        );
+    }
+
+    if let Some(verify) = verify_after_pull {
+        config.verify_after_pull = verify;
+        println!(
+            "{}",
+            format!(
+                "Verify after pull: {}",
+                if verify { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
+
+    if let Some(detect) = detect_deletions {
+        config.detect_deletions = detect;
+        println!(
+            "{}",
+            format!(
+                "Detect deleted sessions: {}",
+                if detect { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
+
+    if let Some(remotes) = mirror_remotes {
+        config.mirror_remotes = remotes
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        println!(
+            "{}",
+            format!("Set mirror remotes: {:?}", config.mirror_remotes).green()
+        );
+    }
+
+    if let Some(policy) = conflict_policy {
+        let policy = parse_conflict_policy(policy.trim())?;
+        config.conflict_policy = policy.as_str().to_string();
+        println!(
+            "{}",
+            format!("Set conflict policy: {}", config.conflict_policy).green()
+        );
+    }
+
+    if let Some(rules) = project_conflict_policies {
+        let mut parsed = Vec::new();
+        for rule in rules.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let Some((pattern, policy)) = rule.split_once('=') else {
+                bail!("Invalid project conflict policy: '{}'. Use PATTERN=POLICY.", rule);
+            };
+            let policy = parse_conflict_policy(policy.trim())?;
+            parsed.push(ProjectConflictPolicy {
+                pattern: pattern.trim().to_string(),
+                policy: policy.as_str().to_string(),
+            });
+        }
+        config.project_conflict_policies = parsed;
+        if config.project_conflict_policies.is_empty() {
+            println!("{}", "Cleared project conflict policies".green());
+        } else {
+            for rule in &config.project_conflict_policies {
+                println!(
+                    "{}",
+                    format!("Conflict policy for {}: {}", rule.pattern, rule.policy).green()
+                );
+            }
+        }
+    }
+
+    if let Some(rules) = large_file_actions {
+        let mut parsed = Vec::new();
+        for rule in rules.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let band = rule.split_once('=').and_then(|(size, action)| {
+                let size = size.trim().trim_end_matches(['M', 'm', 'B', 'b']);
+                Some((size.parse::<u64>().ok()?, action.trim()))
+            });
+            let Some((min_size_mb, action)) = band else {
+                bail!("Invalid large file action: '{}'. Use SIZE_MB=ACTION.", rule);
+            };
+            let action = parse_large_file_action(action)?;
+            parsed.push(LargeFileBand {
+                min_size_mb,
+                action: action.as_str().to_string(),
+            });
+        }
+        parsed.sort_by_key(|band| band.min_size_mb);
+        config.large_file_bands = parsed;
+        if config.large_file_bands.is_empty() {
+            println!("{}", "Cleared large file actions".green());
+        } else {
+            for band in &config.large_file_bands {
+                println!(
+                    "{}",
+                    format!("Files from {} MB: {}", band.min_size_mb, band.action).green()
+                );
+            }
+        }
+    }
+
+    if let Some(source) = session_id_source {
+        let Some(parsed) = SessionIdSource::parse(source.trim()) else {
+            bail!(
+                "Invalid session ID source: '{}'. Use 'entry' or 'filename'.",
+                source
+            );
+        };
+        config.session_id_source = parsed.as_str().to_string();
+        println!(
+            "{}",
+            format!("Set session ID source: {}", config.session_id_source).green()
+        );
+    }
+
+    if let Some(rules) = scrub_rules {
+        config.scrub_rules = rules
+            .split(',')
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect();
+        println!(
+            "{}",
+            format!("Set scrubbing rules: {:?}", config.scrub_rules).green()
+        );
+    }
+
+    if let Some(regexes) = scrub_regexes {
+        config.scrub_regexes = regexes.into_iter().filter(|r| !r.is_empty()).collect();
+        println!(
+            "{}",
+            format!("Set scrubbing regexes: {:?}", config.scrub_regexes).green()
+        );
+    }
+
+    if let Some(allowlist) = scrub_allowlist {
+        config.scrub_allowlist = allowlist.into_iter().filter(|r| !r.is_empty()).collect();
+        println!(
+            "{}",
+            format!("Set scrubbing allowlist: {:?}", config.scrub_allowlist).green()
+        );
+    }
+
+    if let Some(enabled) = checksum_manifest {
+        config.checksum_manifest = enabled;
+        println!(
+            "{}",
+            format!(
+                "Checksum manifest: {}",
+                if enabled { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
+
+    if let Some(enabled) = commit_per_project {
+        config.commit_per_project = enabled;
+        println!(
+            "{}",
+            format!(
+                "Commit per project: {}",
+                if enabled { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
+
+    if let Some(enabled) = project_index {
+        config.project_index = enabled;
+        println!(
+            "{}",
+            format!(
+                "Project index: {}",
+                if enabled { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
+
+    if let Some(secs) = active_session_window {
+        config.active_session_window_secs = secs;
+        println!(
+            "{}",
+            if secs == 0 {
+                "Active session guard disabled".to_string()
+            } else {
+                format!("Sessions written in the last {secs} seconds are only appended to on pull")
+            }
+            .green()
+        );
+    }
+
+    if let Some(retries) = push_retries {
+        config.push_retries = retries;
+        println!(
+            "{}",
+            format!("Rejected pushes are retried up to {retries} times").green()
+        );
+    }
+
+    if let Some(secs) = remote_lease {
+        config.remote_lease_secs = secs;
+        println!(
+            "{}",
+            if secs == 0 {
+                "Remote lease disabled".to_string()
+            } else {
+                format!("Sync and push take a {secs}-second lease on origin")
+            }
+            .green()
+        );
+    }
+
+    if let Some(secs) = min_sync_interval {
+        config.min_sync_interval_secs = secs;
+        println!(
+            "{}",
+            if secs == 0 {
+                "No minimum interval between automatic syncs".to_string()
+            } else {
+                format!("Scheduled and API syncs run at most every {secs} seconds")
+            }
+            .green()
+        );
+    }
+
+    if let Some(url) = healthcheck_url {
+        config.healthcheck_url = parse_healthcheck_url(&url)?;
+        println!(
+            "{}",
+            match &config.healthcheck_url {
+                Some(url) => format!("Pulls, pushes, and syncs ping {url}"),
+                None => "Healthcheck pings disabled".to_string(),
+            }
+            .green()
+        );
+    }
+
+    if let Some(url) = healthcheck_fail_url {
+        config.healthcheck_fail_url = parse_healthcheck_url(&url)?;
+        println!(
+            "{}",
+            match &config.healthcheck_fail_url {
+                Some(url) => format!("Failures are posted to {url}"),
+                None => "Failures are posted to the healthcheck URL's /fail".to_string(),
+            }
+            .green()
+        );
+    }
+
+    if let Some(level) = verbosity {
+        let Some(parsed) = crate::VerbosityLevel::parse(level.trim()) else {
+            bail!(
+                "Invalid verbosity: '{}'. Use 'quiet', 'summary', 'normal', or 'verbose'.",
+                level
+            );
+        };
+        config.verbosity = parsed.as_str().to_string();
+        println!(
+            "{}",
+            format!("Set default verbosity: {}", config.verbosity).green()
+        );
+    }
+
+    if let Some(choice) = color {
+        let Some(parsed) = crate::color::ColorChoice::parse(choice.trim()) else {
+            bail!(
+                "Invalid color setting: '{}'. Use 'auto', 'always', or 'never'.",
+                choice
+            );
+        };
+        config.color = parsed.as_str().to_string();
+        println!("{}", format!("Set color: {}", config.color).green());
+    }
+
+    if let Some(locale) = locale {
+        let locale = locale.trim();
+        config.locale = if locale.eq_ignore_ascii_case("auto") {
+            "auto".to_string()
+        } else {
+            let Some(parsed) = crate::i18n::Locale::parse(locale) else {
+                bail!(
+                    "Invalid locale: '{}'. Use {}, or 'auto'.",
+                    locale,
+                    locale_names()
+                );
+            };
+            parsed.as_str().to_string()
+        };
+        println!("{}", format!("Set locale: {}", config.locale).green());
+    }
+
+    if let Some(enabled) = ascii {
+        config.ascii = enabled;
+        println!(
+            "{}",
+            format!(
+                "ASCII output {}",
+                if enabled { "enabled" } else { "disabled" }
+            )
+            .green()
+        );
+    }
+
+    if let Some(threads) = parallelism {
+        if threads == 0 {
+            config.parallelism = None;
+            println!("{}", "Using one thread per CPU core".green());
+        } else {
+            config.parallelism = Some(threads);
+            println!("{}", format!("Using up to {threads} threads").green());
+        }
+    }
+
+    if let Some(max) = max_entries_per_file {
+        if max == 0 {
+            config.max_entries_per_file = None;
+            println!("{}", "Session splitting disabled".green());
+        } else {
+            config.max_entries_per_file = Some(max);
+            println!(
+                "{}",
+                format!("Splitting session files over {max} entries").green()
+            );
+        }
+    }
+
+    if let Some(rules) = content_key_strategies {
+        let mut parsed = BTreeMap::new();
+        for rule in rules.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let Some((entry_type, strategy)) = rule.split_once('=') else {
+                bail!("Invalid content key strategy: '{}'. Use TYPE=STRATEGY.", rule);
+            };
+            let strategy = parse_content_key_strategy(strategy.trim())?;
+            parsed.insert(entry_type.trim().to_string(), strategy.as_str().to_string());
+        }
+        config.content_key_strategies = parsed;
+        if config.content_key_strategies.is_empty() {
+            println!("{}", "Cleared content key strategies".green());
+        } else {
+            for (entry_type, strategy) in &config.content_key_strategies {
+                println!(
+                    "{}",
+                    format!("Content key strategy for {entry_type}: {strategy}").green()
+                );
+            }
+        }
+    }
+
+    if let Some(policy) = modified_entry_policy {
+        let policy_lower = policy.trim().to_lowercase();
+        if ModifiedEntryPolicy::parse(&policy_lower).is_none() {
+            bail!(
+                "Invalid modified entry policy: '{}'. Use 'prefer-newer', 'prefer-local', 'prefer-remote', or 'fork'.",
+                policy
+            );
+        }
+        config.modified_entry_policy = policy_lower;
+        println!(
+            "{}",
+            format!(
+                "Set modified entry policy: {}",
+                config.modified_entry_policy
+            )
+            .green()
+        );
+    }
+
+    if let Some(precedence) = pull_precedence {
+        let Some(parsed) = PullPrecedence::parse(precedence.trim()) else {
+            bail!(
+                "Invalid pull precedence: '{}'. Use 'local-first' or 'remote-first'.",
+                precedence
+            );
+        };
+        config.pull_precedence = parsed.as_str().to_string();
+        println!(
+            "{}",
+            format!("Set pull precedence: {}", config.pull_precedence).green()
+        );
+    }
+
+    if let Some(max) = conflict_report_max_count {
+        config.conflict_report_max_count = max;
+        println!(
+            "{}",
+            format!("Set conflict report max count: {max}").green()
+        );
+    }
+
+    if let Some(days) = conflict_report_retention_days {
+        config.conflict_report_retention_days = days;
+        println!(
+            "{}",
+            format!("Set conflict report retention: {days} days").green()
+        );
+    }
+
+    if let Some(max) = max_conversations_display {
+        config.max_conversations_display = max;
+        println!(
+            "{}",
+            if max == 0 {
+                "Pull summaries list all conversations".to_string()
+            } else {
+                format!("Pull summaries list up to {max} conversations per project")
+            }
+            .green()
+        );
+    }
+
+    // Validate configuration before saving
+    config.validate()?;
+
+    config.save()?;
+    println!("{}", "Configuration saved successfully!".green().bold());
+
+    Ok(())
+}
+
+/// Show the current filter configuration
+pub fn show_config() -> Result<()> {
     let config = FilterConfig::load()?;
 
     println!("{}", "Current Filter Configuration:".bold());
@@ -454,6 +1916,189 @@ pub fn show_config() -> Result<()> {
         "Sync subdirectory".cyan(),
         config.sync_subdirectory.green()
     );
+    println!(
+        "  {}: {}",
+        "Sync mode".cyan(),
+        config.sync_mode.green()
+    );
+    println!(
+        "  {}: {}",
+        "Verify after pull".cyan(),
+        if config.verify_after_pull { "Yes".green() } else { "No".yellow() }
+    );
+    println!(
+        "  {}: {}",
+        "Checksum manifest".cyan(),
+        if config.checksum_manifest { "Yes".green() } else { "No".yellow() }
+    );
+    println!(
+        "  {}: {}",
+        "Commit per project".cyan(),
+        if config.commit_per_project { "Yes".green() } else { "No".yellow() }
+    );
+    println!(
+        "  {}: {}",
+        "Project index".cyan(),
+        if config.project_index { "Yes".green() } else { "No".yellow() }
+    );
+    println!(
+        "  {}: {}",
+        "Push retries".cyan(),
+        config.push_retries
+    );
+    println!(
+        "  {}: {}",
+        "Remote lease".cyan(),
+        if config.remote_lease_secs == 0 {
+            "Disabled".yellow()
+        } else {
+            format!("{} seconds", config.remote_lease_secs).green()
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Minimum sync interval".cyan(),
+        if config.min_sync_interval_secs == 0 {
+            "Disabled".yellow()
+        } else {
+            format!("{} seconds", config.min_sync_interval_secs).green()
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Default verbosity".cyan(),
+        config.verbosity.green()
+    );
+    println!("  {}: {}", "Color".cyan(), config.color.green());
+    println!("  {}: {}", "Locale".cyan(), config.locale.green());
+    println!(
+        "  {}: {}",
+        "ASCII output".cyan(),
+        if config.ascii { "Yes".green() } else { "No".yellow() }
+    );
+    println!(
+        "  {}: {}",
+        "Healthcheck URL".cyan(),
+        match &config.healthcheck_url {
+            Some(url) => url.green(),
+            None => "Disabled".yellow(),
+        }
+    );
+    if let Some(url) = &config.healthcheck_fail_url {
+        println!("  {}: {}", "Healthcheck failure URL".cyan(), url.green());
+    }
+    println!(
+        "  {}: {}",
+        "Active session window".cyan(),
+        if config.active_session_window_secs == 0 {
+            "Disabled".yellow()
+        } else {
+            format!("{} seconds", config.active_session_window_secs).green()
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Detect deleted sessions".cyan(),
+        if config.detect_deletions { "Yes".green() } else { "No".yellow() }
+    );
+    println!(
+        "  {}: {}",
+        "Conflict policy".cyan(),
+        config.conflict_policy.green()
+    );
+    for rule in &config.project_conflict_policies {
+        println!(
+            "    {} {} {}",
+            glyphs::detail().dimmed(),
+            rule.pattern,
+            format!("{} {}", glyphs::arrow(), rule.policy).green()
+        );
+    }
+    println!(
+        "  {}: {}",
+        "Large file actions".cyan(),
+        if config.large_file_bands.is_empty() {
+            "None".yellow()
+        } else {
+            format!("{} bands", config.large_file_bands.len()).green()
+        }
+    );
+    for band in &config.large_file_bands {
+        println!(
+            "    {} from {} MB {}",
+            glyphs::detail().dimmed(),
+            band.min_size_mb,
+            format!("{} {}", glyphs::arrow(), band.action).green()
+        );
+    }
+    println!(
+        "  {}: {}",
+        "Modified entry policy".cyan(),
+        config.modified_entry_policy.green()
+    );
+    println!(
+        "  {}: {}",
+        "Pull precedence".cyan(),
+        config.pull_precedence.green()
+    );
+    println!(
+        "  {}: {}",
+        "Session ID source".cyan(),
+        config.session_id_source.green()
+    );
+    println!(
+        "  {}: {}",
+        "Parallelism".cyan(),
+        match config.parallelism {
+            Some(threads) => format!("{threads} threads").green(),
+            None => "One thread per CPU core".green(),
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Split sessions".cyan(),
+        match config.max_entries_per_file {
+            Some(max) => format!("Over {max} entries per file").green(),
+            None => "Disabled".yellow(),
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Content key strategy".cyan(),
+        "full-entry".green()
+    );
+    for (entry_type, strategy) in &config.content_key_strategies {
+        println!(
+            "    {} {} {}",
+            glyphs::detail().dimmed(),
+            entry_type,
+            format!("{} {strategy}", glyphs::arrow()).green()
+        );
+    }
+    println!(
+        "  {}: {}",
+        "Conflict reports kept".cyan(),
+        match (
+            config.conflict_report_max_count,
+            config.conflict_report_retention_days
+        ) {
+            (0, 0) => "all".to_string(),
+            (max, 0) => format!("last {max}"),
+            (0, days) => format!("{days} days"),
+            (max, days) => format!("last {max}, up to {days} days"),
+        }
+        .green()
+    );
+    println!(
+        "  {}: {}",
+        "Conversations listed per project".cyan(),
+        if config.max_conversations_display == 0 {
+            "No limit".to_string()
+        } else {
+            config.max_conversations_display.to_string()
+        }
+        .green()
+    );
     println!(
         "  {}: {}",
         "Temp branch retention".cyan(),
@@ -473,6 +2118,127 @@ pub fn show_config() -> Result<()> {
             .unwrap_or("~/.claude/projects (default)")
             .green()
     );
+    println!(
+        "  {}: {}",
+        "Preserve raw lines".cyan(),
+        if config.preserve_raw_lines {
+            "Yes (byte-identical round trip)".green()
+        } else {
+            "No (entries re-serialized)".yellow()
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Sync todos".cyan(),
+        if config.sync_todos {
+            format!("Yes ({})", config.todo_merge_mode).green()
+        } else {
+            "No".yellow()
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Sync file history".cyan(),
+        if config.sync_file_history { "Yes".green() } else { "No".yellow() }
+    );
+    println!(
+        "  {}: {}",
+        "Sync shell snapshots".cyan(),
+        if config.sync_shell_snapshots { "Yes".green() } else { "No".yellow() }
+    );
+    println!(
+        "  {}: {:.2} MB",
+        "Artifact max file size".cyan(),
+        config.artifact_max_file_size_bytes as f64 / (1024.0 * 1024.0)
+    );
+    println!(
+        "  {}: {}",
+        "Sidechain mode".cyan(),
+        config.sidechain_mode.green()
+    );
+    println!(
+        "  {}: {}",
+        "Compaction".cyan(),
+        match config.compact_older_than_days {
+            Some(days) => format!(
+                "Sessions older than {} days, payloads over {} KB{}",
+                days,
+                config.compact_min_payload_bytes / 1024,
+                if config.compaction_archive { " (originals archived)" } else { "" }
+            )
+            .green(),
+            None => "Disabled".yellow(),
+        }
+    );
+    println!(
+        "  {}: {}",
+        "PII scrubbing".cyan(),
+        if config.scrub_rules.is_empty() && config.scrub_regexes.is_empty() {
+            "Disabled".yellow()
+        } else {
+            let mut rules = config.scrub_rules.clone();
+            if !config.scrub_regexes.is_empty() {
+                rules.push(format!("{} custom regexes", config.scrub_regexes.len()));
+            }
+            if !config.scrub_allowlist.is_empty() {
+                rules.push(format!("{} allowlisted", config.scrub_allowlist.len()));
+            }
+            rules.join(", ").green()
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Include tags".cyan(),
+        if config.include_tags.is_empty() {
+            "None (all included)".to_string()
+        } else {
+            config.include_tags.join(", ")
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Exclude tags".cyan(),
+        if config.exclude_tags.is_empty() {
+            "None".to_string()
+        } else {
+            config.exclude_tags.join(", ")
+        }
+    );
+    println!(
+        "  {}: {}",
+        "User namespace".cyan(),
+        match &config.user_namespace {
+            Some(user) => format!("users/{user}/").green(),
+            None => "None (single-user repo)".yellow(),
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Mirror remotes".cyan(),
+        if config.mirror_remotes.is_empty() {
+            "None".to_string()
+        } else {
+            config.mirror_remotes.join(", ")
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Pinned sessions".cyan(),
+        if config.pinned_sessions.is_empty() {
+            "None".to_string()
+        } else {
+            config.pinned_sessions.join(", ")
+        }
+    );
+    println!(
+        "  {}: {}",
+        "Local-only".cyan(),
+        if config.local_only.is_empty() {
+            "None".to_string()
+        } else {
+            config.local_only.join(", ")
+        }
+    );
 
     Ok(())
 }
@@ -489,6 +2255,65 @@ mod tests {
         assert!(!glob_match("test*", "no match"));
     }
 
+    #[test]
+    fn test_conflict_policy_for() {
+        let config = FilterConfig {
+            conflict_policy: "keep-both".to_string(),
+            project_conflict_policies: vec![
+                ProjectConflictPolicy {
+                    pattern: "*-work-*".to_string(),
+                    policy: "prefer-remote".to_string(),
+                },
+                ProjectConflictPolicy {
+                    pattern: "*-work-scratch*".to_string(),
+                    policy: "prefer-local".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let policy = |path: &str| config.conflict_policy_for(Path::new(path)).unwrap();
+        assert_eq!(policy("/p/-home-me-work-api/s.jsonl"), ConflictPolicy::PreferRemote);
+        // First match wins
+        assert_eq!(policy("/p/-home-me-work-scratch/s.jsonl"), ConflictPolicy::PreferRemote);
+        assert_eq!(policy("/p/-home-me-personal/s.jsonl"), ConflictPolicy::KeepBoth);
+
+        let invalid = FilterConfig {
+            project_conflict_policies: vec![ProjectConflictPolicy {
+                pattern: "*".to_string(),
+                policy: "coin-flip".to_string(),
+            }],
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_large_file_action_bands() {
+        let band = |min_size_mb, action: &str| LargeFileBand {
+            min_size_mb,
+            action: action.to_string(),
+        };
+        let config = FilterConfig {
+            large_file_bands: vec![band(50, "compress"), band(10, "warn"), band(200, "skip")],
+            ..Default::default()
+        };
+        let mb = 1024 * 1024;
+        assert_eq!(config.large_file_action(mb).unwrap(), None);
+        assert_eq!(config.large_file_action(10 * mb).unwrap(), Some(LargeFileAction::Warn));
+        assert_eq!(
+            config.large_file_action(120 * mb).unwrap(),
+            Some(LargeFileAction::Compress)
+        );
+        assert_eq!(config.large_file_action(300 * mb).unwrap(), Some(LargeFileAction::Skip));
+
+        let invalid = FilterConfig {
+            large_file_bands: vec![band(1, "shred")],
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_filter_config_default() {
         let config = FilterConfig::default();
@@ -496,6 +2321,18 @@ mod tests {
         assert!(config.include_patterns.is_empty());
         assert!(config.exclude_patterns.is_empty());
         assert!(!config.exclude_attachments);
+        assert_eq!(config.pull_precedence().unwrap(), PullPrecedence::LocalFirst);
+    }
+
+    #[test]
+    fn test_pull_precedence_validation() {
+        let mut config = FilterConfig {
+            pull_precedence: "Remote-First".to_string(),
+            ..FilterConfig::default()
+        };
+        assert_eq!(config.pull_precedence().unwrap(), PullPrecedence::RemoteFirst);
+        config.pull_precedence = "newest".to_string();
+        assert!(config.validate().is_err());
     }
 
     #[test]
@@ -548,6 +2385,120 @@ mod tests {
         assert!(config.should_include(&PathBuf::from("/path/prod/session.jsonl")));
     }
 
+    #[test]
+    fn test_pinned_sessions_bypass_size_and_age() {
+        use std::io::Write;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let pinned_path = temp.path().join("pinned-session.jsonl");
+        let other_path = temp.path().join("other-session.jsonl");
+        for path in [&pinned_path, &other_path] {
+            let mut file = fs::File::create(path).unwrap();
+            writeln!(file, "{{\"type\":\"user\"}}").unwrap();
+        }
+
+        let config = FilterConfig {
+            max_file_size_bytes: 1,
+            pinned_sessions: vec!["pinned-session".to_string()],
+            ..Default::default()
+        };
+
+        assert!(config.is_pinned(&pinned_path));
+        assert!(!config.is_pinned(&other_path));
+        assert!(config.should_include(&pinned_path));
+        assert!(!config.should_include(&other_path));
+
+        // Pinned sessions still honor exclude patterns
+        let config = FilterConfig {
+            exclude_patterns: vec!["*pinned*".to_string()],
+            ..config
+        };
+        assert!(!config.should_include(&pinned_path));
+    }
+
+    #[test]
+    fn test_exclusion_reason() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("prod/session.jsonl");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "{}\n").unwrap();
+
+        assert_eq!(FilterConfig::default().exclusion_reason(&path), None);
+        let config = FilterConfig {
+            max_file_size_bytes: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.exclusion_reason(&path).unwrap(),
+            "larger than max_file_size (3 > 1 bytes)"
+        );
+        let config = FilterConfig {
+            exclude_patterns: vec!["*prod*".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.exclusion_reason(&path).unwrap(),
+            "matches exclude pattern '*prod*'"
+        );
+        let config = FilterConfig {
+            include_patterns: vec!["*test*".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.exclusion_reason(&path).unwrap(),
+            "matches no include pattern"
+        );
+    }
+
+    #[test]
+    fn test_local_only_matches_sessions_projects_and_globs() {
+        let projects = Path::new("/home/me/.claude/projects");
+        let secret = projects.join("-home-me-secret/abc.jsonl");
+        let subagent = projects.join("-home-me-app/def/subagents/agent-1.jsonl");
+        let other = projects.join("-home-me-app/ghi.jsonl");
+
+        let config = FilterConfig {
+            local_only: vec!["-home-me-secret".to_string(), "def".to_string()],
+            ..Default::default()
+        };
+        assert!(config.is_local_only(&secret));
+        assert!(config.is_local_only(&subagent));
+        assert!(!config.is_local_only(&other));
+
+        let config = FilterConfig {
+            local_only: vec!["ghi".to_string(), "*-app/*".to_string()],
+            ..Default::default()
+        };
+        assert!(config.is_local_only(&other));
+        assert!(config.is_local_only(&subagent));
+        assert!(!config.is_local_only(&secret));
+    }
+
+    #[test]
+    fn test_sidechain_mode_apply() {
+        let session: ConversationSession = ConversationSession {
+            session_id: "s".to_string(),
+            entries: vec![
+                serde_json::from_str(r#"{"type":"user","uuid":"1","isSidechain":false}"#).unwrap(),
+                serde_json::from_str(r#"{"type":"assistant","uuid":"2","isSidechain":true}"#)
+                    .unwrap(),
+            ],
+            file_path: "s.jsonl".to_string(),
+        };
+
+        assert_eq!(SidechainMode::Keep.apply(&session).unwrap().entries.len(), 2);
+        assert_eq!(SidechainMode::DropSessions.apply(&session).unwrap().entries.len(), 2);
+        assert_eq!(SidechainMode::DropEntries.apply(&session).unwrap().entries.len(), 1);
+
+        let subagent = ConversationSession {
+            entries: vec![session.entries[1].clone()],
+            ..session.clone()
+        };
+        assert!(SidechainMode::DropSessions.apply(&subagent).is_none());
+        assert!(SidechainMode::DropEntries.apply(&subagent).is_none());
+        assert!(SidechainMode::parse("bogus").is_none());
+    }
+
     #[test]
     fn test_filter_config_serialization() {
         let config = FilterConfig {
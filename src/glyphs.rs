@@ -0,0 +1,87 @@
+//! Status glyphs in terminal output
+//!
+//! Output marks results and details with ✓, ✗, ⚠, ℹ, ↳, →, •, and a few
+//! emoji, and cuts long text with …. With `--ascii` (or `ascii = true` in
+//! `config.toml`) each is replaced by a plain ASCII marker such as `[ok]`
+//! or `->`, which screen readers announce sensibly and terminal
+//! multiplexers don't mangle.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::filter::FilterConfig;
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Use ASCII markers for the rest of the process if `--ascii` was passed
+/// or the `ascii` setting is on
+pub fn configure(ascii_flag: bool) {
+    let configured = FilterConfig::load().is_ok_and(|config| config.ascii);
+    ASCII.store(ascii_flag || configured, Ordering::Relaxed);
+}
+
+pub fn ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+fn pick(unicode: &'static str, ascii_marker: &'static str) -> &'static str {
+    if ascii() {
+        ascii_marker
+    } else {
+        unicode
+    }
+}
+
+/// A step that succeeded
+pub fn ok() -> &'static str {
+    pick("✓", "[ok]")
+}
+
+/// A step that failed
+pub fn fail() -> &'static str {
+    pick("✗", "[error]")
+}
+
+/// Something that needs attention but didn't fail
+pub fn warn() -> &'static str {
+    pick("⚠️", "[warn]")
+}
+
+/// A note about what happened or what to do next
+pub fn info() -> &'static str {
+    pick("ℹ", "[info]")
+}
+
+/// A detail of the line above
+pub fn detail() -> &'static str {
+    pick("↳", "-")
+}
+
+/// Where something goes, or what it becomes
+pub fn arrow() -> &'static str {
+    pick("→", "->")
+}
+
+/// A way back out of a menu
+pub fn back() -> &'static str {
+    pick("←", "<-")
+}
+
+/// An item in a list
+pub fn bullet() -> &'static str {
+    pick("•", "*")
+}
+
+/// The start of interactive setup
+pub fn setup() -> &'static str {
+    pick("⚙️ ", "[setup]")
+}
+
+/// Setup from a config file
+pub fn config_file() -> &'static str {
+    pick("📄", "[config]")
+}
+
+/// Where text was cut short
+pub fn ellipsis() -> &'static str {
+    pick("…", "...")
+}
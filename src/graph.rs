@@ -0,0 +1,273 @@
+//! Message thread graph of a session
+//!
+//! Each entry names the entry it follows through `parentUuid`, so a session
+//! is a tree of messages: rewinding or editing a prompt in Claude Code, or a
+//! fork-merge by pull, leaves an entry with several children. `export
+//! --graph` writes that tree as DOT for Graphviz or as JSON, with forks and
+//! entries whose parent is missing from the session called out.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::parser::ConversationSession;
+use crate::text::{short_id, truncate_to_width, Keep};
+
+/// Width of message previews in DOT node labels
+const LABEL_PREVIEW_WIDTH: usize = 40;
+
+/// Output format of a session graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+impl GraphFormat {
+    /// Parse a format name ("dot" or "json")
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "dot" | "graphviz" => Ok(GraphFormat::Dot),
+            "json" => Ok(GraphFormat::Json),
+            other => bail!("Unknown graph format: '{}'. Use 'dot' or 'json'.", other),
+        }
+    }
+}
+
+/// One entry of the session in the graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    pub uuid: String,
+    pub parent: Option<String>,
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    pub timestamp: Option<String>,
+    /// The message's text on one line, for user and assistant messages
+    pub preview: Option<String>,
+    /// Written by a subagent
+    pub sidechain: bool,
+}
+
+/// A parent-to-child link between two entries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// The `parentUuid` graph of a session's entries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionGraph {
+    pub session_id: String,
+    /// Entries with a UUID, in file order
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// Entries with more than one child, where the conversation branches
+    pub forks: Vec<String>,
+    /// Entries with no parent
+    pub roots: Vec<String>,
+    /// Parents referenced by entries but not in the session
+    pub missing_parents: Vec<String>,
+}
+
+impl SessionGraph {
+    /// Build the graph of `session`
+    ///
+    /// Entries without a UUID (summaries, file snapshots) aren't part of the
+    /// thread and are left out; an entry repeated in the file counts once.
+    pub fn build(session: &ConversationSession) -> Self {
+        let mut seen = HashSet::new();
+        let nodes: Vec<GraphNode> = session
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let uuid = entry.uuid.clone()?;
+                seen.insert(uuid.clone()).then(|| GraphNode {
+                    uuid,
+                    parent: entry.parent_uuid.clone(),
+                    entry_type: entry.entry_type.clone(),
+                    timestamp: entry.timestamp.clone(),
+                    preview: crate::timeline::message_preview(entry),
+                    sidechain: entry.is_sidechain(),
+                })
+            })
+            .collect();
+
+        let mut children: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut edges = Vec::new();
+        let mut roots = Vec::new();
+        let mut missing_parents = Vec::new();
+        for node in &nodes {
+            let Some(parent) = &node.parent else {
+                roots.push(node.uuid.clone());
+                continue;
+            };
+            *children.entry(parent).or_default() += 1;
+            edges.push(GraphEdge {
+                from: parent.clone(),
+                to: node.uuid.clone(),
+            });
+            if !seen.contains(parent) && !missing_parents.contains(parent) {
+                missing_parents.push(parent.clone());
+            }
+        }
+        // In file order, like the nodes
+        let forks = nodes
+            .iter()
+            .filter(|node| children.get(node.uuid.as_str()).is_some_and(|&n| n > 1))
+            .map(|node| node.uuid.clone())
+            .collect();
+
+        SessionGraph {
+            session_id: session.session_id.clone(),
+            nodes,
+            edges,
+            forks,
+            roots,
+            missing_parents,
+        }
+    }
+
+    /// Render in `format`
+    pub fn render(&self, format: GraphFormat) -> Result<String> {
+        match format {
+            GraphFormat::Dot => Ok(self.to_dot()),
+            GraphFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+        }
+    }
+
+    /// Graphviz source: user messages blue, assistant messages yellow, forks
+    /// outlined in red, and missing parents as dashed placeholders
+    pub fn to_dot(&self) -> String {
+        let mut dot = format!("digraph {} {{\n", dot_string(&self.session_id));
+        dot.push_str("  rankdir=TB;\n");
+        dot.push_str("  node [shape=box, style=\"rounded,filled\", fillcolor=white, fontname=\"Helvetica\"];\n");
+
+        let forks: HashSet<&str> = self.forks.iter().map(String::as_str).collect();
+        for node in &self.nodes {
+            let mut label = format!("{} {}", node.entry_type, short_id(&node.uuid, 8));
+            if let Some(preview) = &node.preview {
+                label.push('\n');
+                label.push_str(&truncate_to_width(
+                    preview,
+                    LABEL_PREVIEW_WIDTH,
+                    Keep::Start,
+                ));
+            }
+            let mut attributes = vec![format!("label={}", dot_string(&label))];
+            match node.entry_type.as_str() {
+                "user" => attributes.push("fillcolor=lightblue".to_string()),
+                "assistant" => attributes.push("fillcolor=lightyellow".to_string()),
+                _ => {}
+            }
+            if forks.contains(node.uuid.as_str()) {
+                attributes.push("color=red, penwidth=2".to_string());
+            }
+            if node.sidechain {
+                attributes.push("style=\"rounded,filled,dotted\"".to_string());
+            }
+            dot.push_str(&format!(
+                "  {} [{}];\n",
+                dot_string(&node.uuid),
+                attributes.join(", ")
+            ));
+        }
+        for parent in &self.missing_parents {
+            let label = format!("missing {}", short_id(parent, 8));
+            dot.push_str(&format!(
+                "  {} [label={}, style=dashed];\n",
+                dot_string(parent),
+                dot_string(&label)
+            ));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!(
+                "  {} -> {};\n",
+                dot_string(&edge.from),
+                dot_string(&edge.to)
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// `value` as a quoted DOT string
+fn dot_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ConversationEntry;
+
+    fn entry(uuid: Option<&str>, parent: Option<&str>, entry_type: &str) -> ConversationEntry {
+        serde_json::from_value(serde_json::json!({
+            "type": entry_type,
+            "uuid": uuid,
+            "parentUuid": parent,
+            "message": {"role": entry_type, "content": format!("message {}", uuid.unwrap_or("-"))},
+        }))
+        .unwrap()
+    }
+
+    fn session(entries: Vec<ConversationEntry>) -> ConversationSession {
+        ConversationSession {
+            session_id: "s1".to_string(),
+            entries,
+            file_path: "s1.jsonl".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_build_finds_forks_roots_and_missing_parents() {
+        let graph = SessionGraph::build(&session(vec![
+            entry(Some("a"), None, "user"),
+            entry(Some("b"), Some("a"), "assistant"),
+            entry(Some("c"), Some("a"), "assistant"),
+            entry(Some("c"), Some("a"), "assistant"),
+            entry(None, None, "summary"),
+            entry(Some("d"), Some("gone"), "user"),
+        ]));
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.edges.len(), 3);
+        assert_eq!(graph.forks, ["a"]);
+        assert_eq!(graph.roots, ["a"]);
+        assert_eq!(graph.missing_parents, ["gone"]);
+        assert_eq!(graph.nodes[1].preview.as_deref(), Some("message b"));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let graph = SessionGraph::build(&session(vec![
+            entry(Some("a"), None, "user"),
+            entry(Some("b"), Some("a"), "assistant"),
+            entry(Some("c"), Some("a"), "assistant"),
+            entry(Some("d"), Some("gone"), "user"),
+        ]));
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph \"s1\" {\n"), "{dot}");
+        assert!(
+            dot.contains(
+                "\"a\" [label=\"user a\\nmessage a\", fillcolor=lightblue, color=red, penwidth=2];"
+            ),
+            "{dot}"
+        );
+        assert!(dot.contains("\"a\" -> \"c\";"), "{dot}");
+        assert!(
+            dot.contains("\"gone\" [label=\"missing gone\", style=dashed];"),
+            "{dot}"
+        );
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_dot_string_escapes() {
+        assert_eq!(dot_string("say \"hi\"\n\\"), r#""say \"hi\"\n\\""#);
+    }
+}
@@ -0,0 +1,101 @@
+//! Audit command handlers
+//!
+//! Handles listing the audit log of files and session entries pushed off
+//! this machine.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::audit::{AuditLog, AuditRecord};
+use crate::glyphs;
+use crate::session_ref::normalize_session_ref;
+use crate::table::{Column, Keep, OutputFormat, Table};
+
+/// Handle audit list command
+pub fn handle_audit_list(session: Option<&str>, format: &str) -> Result<()> {
+    let format = OutputFormat::parse(format)?;
+    let session = session.map(normalize_session_ref).transpose()?;
+    let records: Vec<AuditRecord> = AuditLog::open()?
+        .records()?
+        .into_iter()
+        .filter(|record| match &session {
+            Some(prefix) => record
+                .session_id
+                .as_ref()
+                .is_some_and(|id| id.starts_with(prefix.as_str())),
+            None => true,
+        })
+        .collect();
+
+    if format != OutputFormat::Table {
+        println!("{}", records_table(&records, true).render(format)?);
+        return Ok(());
+    }
+
+    if records.is_empty() {
+        match &session {
+            Some(session) => println!(
+                "{}",
+                format!("Nothing from session {session} has been pushed.").yellow()
+            ),
+            None => println!("{}", "Nothing has been pushed yet.".yellow()),
+        }
+        return Ok(());
+    }
+
+    match &session {
+        Some(session) => println!("{} {}", "Audit Log for".cyan().bold(), session.bold()),
+        None => println!("{}", "Audit Log".cyan().bold()),
+    }
+    print!("{}", records_table(&records, false).to_text());
+    let entries: usize = records.iter().map(|r| r.uuids.len()).sum();
+    println!(
+        "\n  {} {} files and {} entries pushed; use --format json for entry UUIDs",
+        glyphs::info().cyan(),
+        records.len(),
+        entries
+    );
+
+    Ok(())
+}
+
+/// One row per pushed file, with its entry UUIDs when `with_uuids` is set
+fn records_table(records: &[AuditRecord], with_uuids: bool) -> Table {
+    let mut columns = vec![
+        Column::new("Pushed", "pushed_at"),
+        Column::new("Remote", "remote"),
+        Column::new("Branch", "branch").max_width(40, Keep::Start),
+        Column::new("Commit", "commit"),
+        Column::new("Path", "path").max_width(60, Keep::End),
+        Column::new("Entries", "entries").right(),
+    ];
+    if with_uuids {
+        columns.push(Column::new("Session", "session_id"));
+        columns.push(Column::new("UUIDs", "uuids"));
+    }
+    let mut table = Table::new(columns);
+    for record in records {
+        let mut row = vec![
+            if with_uuids {
+                record.pushed_at.to_rfc3339()
+            } else {
+                record.pushed_at.format("%Y-%m-%d %H:%M UTC").to_string()
+            },
+            record.remote.clone(),
+            record.branch.clone(),
+            if with_uuids {
+                record.commit.clone()
+            } else {
+                record.commit.chars().take(12).collect()
+            },
+            record.path.clone(),
+            record.uuids.len().to_string(),
+        ];
+        if with_uuids {
+            row.push(record.session_id.clone().unwrap_or_default());
+            row.push(record.uuids.join(" "));
+        }
+        table.push_row(row);
+    }
+    table
+}
@@ -0,0 +1,108 @@
+//! Blame command handler
+//!
+//! Handles reporting, per entry of a session, the commit, machine, and date
+//! that brought it into the sync repo.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use std::collections::BTreeSet;
+
+use crate::blame::{blame_session, EntryOrigin};
+use crate::glyphs;
+use crate::scm;
+use crate::session_ref::resolve_session_id;
+use crate::sync::SyncState;
+use crate::table::{Column, Keep, OutputFormat, Table};
+
+/// Handle blame command
+pub fn handle_blame(session: &str, format: &str) -> Result<()> {
+    let format = OutputFormat::parse(format)?;
+    let session_id = resolve_session_id(session)?;
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+
+    let origins = blame_session(repo.as_ref(), &session_id)?;
+    if origins.is_empty() {
+        bail!("Session {session_id} has no committed entries in the sync repo");
+    }
+
+    if format != OutputFormat::Table {
+        println!("{}", origins_table(&origins, true).render(format)?);
+        return Ok(());
+    }
+
+    println!("{} {}", "Blame for".cyan().bold(), session_id.bold());
+    print!("{}", origins_table(&origins, false).to_text());
+    let commits: BTreeSet<&str> = origins.iter().map(|o| o.commit.id.as_str()).collect();
+    let machines: BTreeSet<&str> = origins
+        .iter()
+        .filter_map(|o| o.machine.as_deref())
+        .collect();
+    println!(
+        "\n  {} {} entries from {} commits{}",
+        glyphs::info().cyan(),
+        origins.len(),
+        commits.len(),
+        if machines.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " on {}",
+                machines.into_iter().collect::<Vec<_>>().join(", ")
+            )
+        }
+    );
+    let removed = origins.iter().filter(|o| !o.present).count();
+    if removed > 0 {
+        println!(
+            "  {} {} entries are no longer in the session",
+            glyphs::info().cyan(),
+            removed
+        );
+    }
+
+    Ok(())
+}
+
+/// One row per entry; `full` keeps whole commit IDs and the summary
+fn origins_table(origins: &[EntryOrigin], full: bool) -> Table {
+    let mut columns = vec![
+        Column::new("Entry", "uuid"),
+        Column::new("Type", "type"),
+        Column::new("Commit", "commit"),
+        Column::new("Date", "date"),
+        Column::new("Machine", "machine").max_width(30, Keep::Start),
+        Column::new("Author", "author").max_width(20, Keep::Start),
+        Column::new("State", "state"),
+    ];
+    if full {
+        columns.push(Column::new("Timestamp", "timestamp"));
+        columns.push(Column::new("Summary", "summary"));
+    }
+    let mut table = Table::new(columns);
+    for origin in origins {
+        let mut row = vec![
+            origin.uuid.clone(),
+            origin.entry_type.clone(),
+            if full {
+                origin.commit.id.clone()
+            } else {
+                origin.commit.id.chars().take(12).collect()
+            },
+            if full {
+                origin.commit.date.clone()
+            } else {
+                origin.commit.date.chars().take(10).collect()
+            },
+            origin.machine.clone().unwrap_or_default(),
+            origin.commit.author.clone(),
+            if origin.present { "present" } else { "removed" }.to_string(),
+        ];
+        if full {
+            row.push(origin.timestamp.clone().unwrap_or_default());
+            row.push(origin.commit.summary.clone());
+        }
+        table.push_row(row);
+    }
+    table
+}
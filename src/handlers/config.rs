@@ -8,6 +8,7 @@ use colored::Colorize;
 use inquire::{Confirm, MultiSelect, Text};
 
 use crate::filter::FilterConfig;
+use crate::glyphs;
 
 /// Handle interactive configuration menu
 ///
@@ -69,12 +70,12 @@ pub fn handle_config_interactive() -> Result<()> {
 
                 if input.trim().is_empty() {
                     modified_config.exclude_older_than_days = None;
-                    println!("  {} Unset exclude_older_than_days", "✓".green());
+                    println!("  {} Unset exclude_older_than_days", glyphs::ok().green());
                 } else {
                     let days: u32 = input.trim().parse()
                         .context("Invalid number. Must be a positive integer.")?;
                     modified_config.exclude_older_than_days = Some(days);
-                    println!("  {} Set exclude_older_than_days to {} days", "✓".green(), days);
+                    println!("  {} Set exclude_older_than_days to {} days", glyphs::ok().green(), days);
                 }
             }
 
@@ -91,14 +92,14 @@ pub fn handle_config_interactive() -> Result<()> {
 
                 if input.trim().is_empty() {
                     modified_config.include_patterns = Vec::new();
-                    println!("  {} Cleared include patterns", "✓".green());
+                    println!("  {} Cleared include patterns", glyphs::ok().green());
                 } else {
                     modified_config.include_patterns = input
                         .split(',')
                         .map(|s| s.trim().to_string())
                         .filter(|s| !s.is_empty())
                         .collect();
-                    println!("  {} Set include patterns: {:?}", "✓".green(), modified_config.include_patterns);
+                    println!("  {} Set include patterns: {:?}", glyphs::ok().green(), modified_config.include_patterns);
                 }
             }
 
@@ -115,14 +116,14 @@ pub fn handle_config_interactive() -> Result<()> {
 
                 if input.trim().is_empty() {
                     modified_config.exclude_patterns = Vec::new();
-                    println!("  {} Cleared exclude patterns", "✓".green());
+                    println!("  {} Cleared exclude patterns", glyphs::ok().green());
                 } else {
                     modified_config.exclude_patterns = input
                         .split(',')
                         .map(|s| s.trim().to_string())
                         .filter(|s| !s.is_empty())
                         .collect();
-                    println!("  {} Set exclude patterns: {:?}", "✓".green(), modified_config.exclude_patterns);
+                    println!("  {} Set exclude patterns: {:?}", glyphs::ok().green(), modified_config.exclude_patterns);
                 }
             }
 
@@ -135,7 +136,7 @@ pub fn handle_config_interactive() -> Result<()> {
                     .prompt()?;
 
                 modified_config.exclude_attachments = exclude;
-                println!("  {} Set exclude_attachments to {}", "✓".green(), exclude);
+                println!("  {} Set exclude_attachments to {}", glyphs::ok().green(), exclude);
             }
 
             "Max file size" => {
@@ -150,7 +151,7 @@ pub fn handle_config_interactive() -> Result<()> {
                     .context("Invalid number. Must be a positive number.")?;
 
                 modified_config.max_file_size_bytes = (size_mb * 1024.0 * 1024.0) as u64;
-                println!("  {} Set max_file_size to {:.1} MB", "✓".green(), size_mb);
+                println!("  {} Set max_file_size to {:.1} MB", glyphs::ok().green(), size_mb);
             }
 
             _ => {}
@@ -169,7 +170,7 @@ pub fn handle_config_interactive() -> Result<()> {
 
     if confirm {
         modified_config.save().context("Failed to save configuration")?;
-        println!("\n{} Configuration saved successfully!", "✓".green().bold());
+        println!("\n{} Configuration saved successfully!", glyphs::ok().green().bold());
     } else {
         println!("\n{}", "Configuration not saved.".yellow());
     }
@@ -213,10 +214,10 @@ pub fn handle_config_wizard() -> Result<()> {
         let days: u32 = input.trim().parse()
             .context("Invalid number. Must be a positive integer.")?;
         modified_config.exclude_older_than_days = Some(days);
-        println!("  {} Will exclude projects older than {} days\n", "✓".green(), days);
+        println!("  {} Will exclude projects older than {} days\n", glyphs::ok().green(), days);
     } else {
         modified_config.exclude_older_than_days = None;
-        println!("  {} Age filter disabled\n", "✓".green());
+        println!("  {} Age filter disabled\n", glyphs::ok().green());
     }
 
     // 2. Include patterns
@@ -245,10 +246,10 @@ pub fn handle_config_wizard() -> Result<()> {
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect();
-        println!("  {} Include patterns set: {:?}\n", "✓".green(), modified_config.include_patterns);
+        println!("  {} Include patterns set: {:?}\n", glyphs::ok().green(), modified_config.include_patterns);
     } else {
         modified_config.include_patterns = Vec::new();
-        println!("  {} All projects will be included\n", "✓".green());
+        println!("  {} All projects will be included\n", glyphs::ok().green());
     }
 
     // 3. Exclude patterns
@@ -277,10 +278,10 @@ pub fn handle_config_wizard() -> Result<()> {
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect();
-        println!("  {} Exclude patterns set: {:?}\n", "✓".green(), modified_config.exclude_patterns);
+        println!("  {} Exclude patterns set: {:?}\n", glyphs::ok().green(), modified_config.exclude_patterns);
     } else {
         modified_config.exclude_patterns = Vec::new();
-        println!("  {} No exclusion patterns\n", "✓".green());
+        println!("  {} No exclusion patterns\n", glyphs::ok().green());
     }
 
     // 4. Exclude attachments
@@ -297,7 +298,7 @@ pub fn handle_config_wizard() -> Result<()> {
 
     modified_config.exclude_attachments = exclude_attachments;
     println!("  {} Attachments will be {}\n",
-        "✓".green(),
+        glyphs::ok().green(),
         if exclude_attachments { "excluded" } else { "included" }
     );
 
@@ -319,9 +320,9 @@ pub fn handle_config_wizard() -> Result<()> {
             .context("Invalid number. Must be a positive number.")?;
 
         modified_config.max_file_size_bytes = (size_mb * 1024.0 * 1024.0) as u64;
-        println!("  {} Max file size set to {:.1} MB\n", "✓".green(), size_mb);
+        println!("  {} Max file size set to {:.1} MB\n", glyphs::ok().green(), size_mb);
     } else {
-        println!("  {} Keeping current max file size\n", "✓".green());
+        println!("  {} Keeping current max file size\n", glyphs::ok().green());
     }
 
     // Summary and confirmation
@@ -337,7 +338,7 @@ pub fn handle_config_wizard() -> Result<()> {
 
     if confirm {
         modified_config.save().context("Failed to save configuration")?;
-        println!("\n{} Configuration saved successfully!", "✓".green().bold());
+        println!("\n{} Configuration saved successfully!", glyphs::ok().green().bold());
     } else {
         println!("\n{}", "Configuration not saved.".yellow());
     }
@@ -0,0 +1,61 @@
+//! Conflict report command handlers
+//!
+//! Handles browsing conflict reports archived by past pulls.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::config::ConfigManager;
+use crate::glyphs;
+use crate::history::OperationHistory;
+use crate::report::{load_archived_report, output_report, ReportIndex};
+
+/// Handle conflicts history command
+pub fn handle_conflicts_history(limit: usize) -> Result<()> {
+    let index = ReportIndex::load(&ConfigManager::conflict_reports_dir()?)?;
+    if index.reports.is_empty() {
+        println!("{}", "No conflict reports recorded.".yellow());
+        return Ok(());
+    }
+
+    // Map report IDs back to the history entry of the operation that wrote them
+    let history = OperationHistory::load().unwrap_or_default();
+
+    println!("{}", "Conflict Reports".cyan().bold());
+    for entry in index.reports.iter().rev().take(limit) {
+        let operation = history
+            .list_operations()
+            .iter()
+            .find(|op| op.conflict_report.as_deref() == Some(entry.id.as_str()))
+            .map(|op| {
+                format!(
+                    "{} at {}",
+                    op.operation_type.as_str(),
+                    op.timestamp.format("%Y-%m-%d %H:%M:%S")
+                )
+            })
+            .unwrap_or_else(|| entry.operation.clone());
+        println!(
+            "  {} {} conflicts {}",
+            entry.id.bold(),
+            entry.total_conflicts,
+            format!("({operation})").dimmed()
+        );
+    }
+    println!(
+        "\n  {} Run 'claude-code-sync conflicts show <id>' for details",
+        glyphs::info().cyan()
+    );
+
+    Ok(())
+}
+
+/// Handle conflicts show command
+pub fn handle_conflicts_show(id: &str, format: &str, output: Option<&Path>) -> Result<()> {
+    let reports_dir = ConfigManager::conflict_reports_dir()?;
+    let index = ReportIndex::load(&reports_dir)?;
+    let entry = index.find(id)?;
+    let report = load_archived_report(&reports_dir, &entry.id)?;
+    output_report(&report, format, output)
+}
@@ -0,0 +1,59 @@
+//! Credentials command handlers
+//!
+//! Handles storing, reading, and removing tokens and keys in the OS
+//! keychain.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use inquire::Password;
+use std::io::{BufRead, IsTerminal};
+
+use crate::credentials;
+use crate::glyphs;
+
+/// Handle credentials set command
+///
+/// The secret is prompted for on a terminal and read from the first line of
+/// stdin otherwise, so it never appears in shell history or `ps`.
+pub fn handle_credentials_set(name: &str) -> Result<()> {
+    let secret = if std::io::stdin().is_terminal() {
+        Password::new(&format!("Value for '{name}':"))
+            .without_confirmation()
+            .prompt()?
+    } else {
+        let mut line = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .context("Failed to read the secret from stdin")?;
+        line.trim_end_matches(['\r', '\n']).to_string()
+    };
+    if secret.is_empty() {
+        bail!("Refusing to store an empty value for '{name}'");
+    }
+
+    credentials::set(name, &secret)?;
+    println!("{} Stored '{}' in the OS keychain", glyphs::ok().green(), name);
+    Ok(())
+}
+
+/// Handle credentials get command
+pub fn handle_credentials_get(name: &str) -> Result<()> {
+    match credentials::get(name)? {
+        Some(secret) => {
+            println!("{secret}");
+            Ok(())
+        }
+        None => bail!("No credential named '{name}' in the OS keychain"),
+    }
+}
+
+/// Handle credentials remove command
+pub fn handle_credentials_remove(name: &str) -> Result<()> {
+    if credentials::remove(name)? {
+        println!("{} Removed '{}' from the OS keychain", glyphs::ok().green(), name);
+    } else {
+        println!("{}", format!("No credential named '{}'", name).yellow());
+    }
+    Ok(())
+}
@@ -0,0 +1,153 @@
+//! Delete command handler
+//!
+//! Handles deleting a session everywhere: the local copy goes to the trash,
+//! the sync repo copy is removed and committed, and a tombstone makes every
+//! other machine drop the session at its next pull. The session's
+//! `history.jsonl` entries and tags are removed as well.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use inquire::Confirm;
+use std::fs;
+
+use crate::config::ConfigManager;
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::interactive_conflict::is_interactive;
+use crate::lock::SyncLock;
+use crate::manifest::write_manifest;
+use crate::scm;
+use crate::session_ref::resolve_session_id_strict;
+use crate::sync::{
+    claude_projects_dir, managed_paths, recover_from_purge, remove_history_sessions, SyncState,
+};
+use crate::tags::TagIndex;
+use crate::tombstones::{session_paths, TombstoneIndex};
+use crate::trash::Trash;
+use crate::VerbosityLevel;
+
+/// Handle delete command
+pub fn handle_delete(session: &str, yes: bool, push_remote: bool) -> Result<()> {
+    let session_id = resolve_session_id_strict(session)?;
+    let _lock = SyncLock::acquire()?;
+
+    let state = SyncState::load()?;
+    let filter = FilterConfig::load().context("Failed to load configuration")?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let branch = repo.current_branch()?;
+
+    // Start from the remote's latest state so the push goes through
+    if push_remote && state.has_remote {
+        recover_from_purge(
+            repo.as_ref(),
+            &state.sync_repo_path,
+            &branch,
+            VerbosityLevel::Normal,
+        )?;
+        if let Err(e) = repo.pull("origin", &branch) {
+            log::warn!("Failed to pull before deleting: {}", e);
+        }
+    }
+
+    let projects_dir = claude_projects_dir()?;
+    let local_paths = session_paths(&projects_dir, &session_id);
+    let repo_paths = session_paths(
+        &state.sync_repo_path.join(filter.projects_subdir()),
+        &session_id,
+    );
+    let mut tombstones = TombstoneIndex::load(&state.sync_repo_path)?;
+    if local_paths.is_empty() && repo_paths.is_empty() && tombstones.is_deleted(&session_id) {
+        println!(
+            "{}",
+            format!("Session {session_id} is already deleted").yellow()
+        );
+        return Ok(());
+    }
+
+    println!("{} {}", "Deleting session".cyan().bold(), session_id.bold());
+    for path in local_paths.iter().chain(&repo_paths) {
+        println!("  {} {}", glyphs::detail().dimmed(), path.display());
+    }
+    if !yes {
+        if !is_interactive() {
+            bail!("Refusing to delete without confirmation; pass --yes");
+        }
+        let confirm = Confirm::new("Delete this session on every machine?")
+            .with_default(false)
+            .prompt()
+            .context("Failed to get confirmation")?;
+        if !confirm {
+            println!("\n{}", "Delete cancelled.".yellow());
+            return Ok(());
+        }
+    }
+
+    let trash = Trash::open()?;
+    for path in &local_paths {
+        trash.put(path, "delete")?;
+    }
+    for path in &repo_paths {
+        if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+        .with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+
+    tombstones.add(&session_id, &ConfigManager::machine_id()?);
+    tombstones.save(&state.sync_repo_path)?;
+
+    let claude_dir = projects_dir.parent().unwrap_or(&projects_dir);
+    let mut history_removed = 0;
+    for history in [
+        claude_dir.join("history.jsonl"),
+        state.sync_repo_path.join("history.jsonl"),
+    ] {
+        history_removed += remove_history_sessions(&history, |id| id == session_id)?;
+    }
+
+    let mut tags = TagIndex::load(&state.sync_repo_path)?;
+    if tags.sessions.remove(&session_id).is_some() {
+        tags.save(&state.sync_repo_path)?;
+    }
+
+    if filter.checksum_manifest {
+        write_manifest(&state.sync_repo_path)?;
+    }
+    repo.stage_paths(&managed_paths(&filter))?;
+    if repo.has_staged_changes()? {
+        repo.commit(&format!("Delete session {session_id}"))?;
+    }
+    println!(
+        "  {} Removed {} local and {} sync repo files, {} history.jsonl entries",
+        glyphs::ok().green(),
+        local_paths.len(),
+        repo_paths.len(),
+        history_removed
+    );
+
+    if push_remote && state.has_remote {
+        crate::audit::push_audited(repo.as_ref(), "origin", &branch, &branch)
+            .context("Failed to push the deletion; run 'claude-code-sync push' to retry")?;
+        println!("  {} Pushed to origin/{}", glyphs::ok().green(), branch);
+    }
+
+    println!(
+        "\n{} Other machines drop the session at their next pull.",
+        glyphs::info().cyan()
+    );
+    if !local_paths.is_empty() {
+        println!(
+            "{} The local copy is in the trash; run {} to remove it for good.",
+            glyphs::info().cyan(),
+            "claude-code-sync trash empty".bold()
+        );
+    }
+    println!(
+        "{} Earlier commits of the sync repo still contain the session.",
+        glyphs::info().cyan()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,176 @@
+//! Doctor command handlers
+//!
+//! Runs basic health checks on the sync setup and, on request, scans
+//! local sessions for schema drift or repairs the sync repo's layout.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::parser::ConversationSession;
+use crate::schema;
+use crate::scm;
+use crate::sync::{self, SyncState};
+
+/// Session ID issues listed before summarizing the rest
+const MAX_LISTED_ISSUES: usize = 5;
+
+/// Handle doctor command
+pub fn handle_doctor(schema_report: bool, output: Option<&Path>, fix_paths: bool) -> Result<()> {
+    if schema_report {
+        return schema::generate_schema_report(output);
+    }
+    if fix_paths {
+        println!("{}", "Fixing session paths in the sync repo".cyan().bold());
+        sync::fix_nested_paths(crate::VerbosityLevel::Normal)?;
+        return Ok(());
+    }
+
+    println!("{}", "Doctor".cyan().bold());
+
+    let mut problems = 0;
+
+    match FilterConfig::load().and_then(|c| c.validate()) {
+        Ok(()) => println!("  {} Configuration is valid", glyphs::ok().green()),
+        Err(e) => {
+            problems += 1;
+            println!("  {} Configuration: {}", "!".yellow().bold(), e);
+        }
+    }
+
+    match SyncState::load() {
+        Ok(state) => match scm::open(&state.sync_repo_path) {
+            Ok(_) => {
+                println!(
+                    "  {} Sync repository: {}",
+                    glyphs::ok().green(),
+                    state.sync_repo_path.display()
+                );
+                if !check_nested_paths(&state) {
+                    problems += 1;
+                }
+            }
+            Err(e) => {
+                problems += 1;
+                println!("  {} Sync repository: {}", "!".yellow().bold(), e);
+            }
+        },
+        Err(e) => {
+            problems += 1;
+            println!("  {} Sync state: {}", "!".yellow().bold(), e);
+        }
+    }
+
+    match sync::claude_projects_dir() {
+        Ok(dir) if dir.is_dir() => {
+            println!("  {} Claude projects dir: {}", glyphs::ok().green(), dir.display())
+        }
+        Ok(dir) => {
+            problems += 1;
+            println!(
+                "  {} Claude projects dir does not exist: {}",
+                "!".yellow().bold(),
+                dir.display()
+            );
+        }
+        Err(e) => {
+            problems += 1;
+            println!("  {} Claude projects dir: {}", "!".yellow().bold(), e);
+        }
+    }
+
+    match check_session_ids() {
+        Ok(issues) if issues.is_empty() => {
+            println!("  {} Session IDs are consistent", glyphs::ok().green())
+        }
+        Ok(issues) => {
+            problems += 1;
+            println!(
+                "  {} {} session files have inconsistent session IDs (see `config --session-id-source`)",
+                "!".yellow().bold(),
+                issues.len()
+            );
+            for (path, issue) in issues.iter().take(MAX_LISTED_ISSUES) {
+                println!("    {} {}: {}", glyphs::detail().dimmed(), path, issue);
+            }
+            if issues.len() > MAX_LISTED_ISSUES {
+                println!(
+                    "    {} and {} more",
+                    glyphs::detail().dimmed(),
+                    issues.len() - MAX_LISTED_ISSUES
+                );
+            }
+        }
+        Err(e) => println!("  {} Session IDs: {}", "!".yellow().bold(), e),
+    }
+
+    println!();
+    if problems == 0 {
+        println!("{}", "No problems found.".green().bold());
+    } else {
+        println!("{}", format!("{} problem(s) found.", problems).yellow().bold());
+    }
+    println!(
+        "  {} Run {} to check sessions for unknown entry types and fields",
+        glyphs::info().cyan(),
+        "claude-code-sync doctor --schema-report".bold()
+    );
+
+    Ok(())
+}
+
+/// Report sessions stored under nested paths in the sync repo; `false` if
+/// there are any
+fn check_nested_paths(state: &SyncState) -> bool {
+    let Ok(filter) = FilterConfig::load() else {
+        return true;
+    };
+    let nested = sync::find_nested_sessions(&state.sync_repo_path.join(filter.projects_subdir()));
+    if nested.is_empty() {
+        return true;
+    }
+    println!(
+        "  {} {} sessions are stored under nested paths in the sync repo (fix with `doctor --fix-paths`)",
+        "!".yellow().bold(),
+        nested.len()
+    );
+    for session in nested.iter().take(MAX_LISTED_ISSUES) {
+        println!("    {} {}", glyphs::detail().dimmed(), session.path);
+    }
+    if nested.len() > MAX_LISTED_ISSUES {
+        println!(
+            "    {} and {} more",
+            glyphs::detail().dimmed(),
+            nested.len() - MAX_LISTED_ISSUES
+        );
+    }
+    false
+}
+
+/// Local session files whose entries disagree about their session ID, as
+/// (file, issue) pairs
+fn check_session_ids() -> Result<Vec<(String, String)>> {
+    let filter = FilterConfig::load()?;
+    let projects_dir = sync::claude_projects_dir()?;
+    let id_source = filter.session_id_source()?;
+
+    let mut issues = Vec::new();
+    for entry in WalkDir::new(&projects_dir).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Ok(session) = ConversationSession::from_file_with(path, id_source) else {
+            continue;
+        };
+        let relative = path.strip_prefix(&projects_dir).unwrap_or(path);
+        for issue in session.session_id_issues() {
+            issues.push((relative.display().to_string(), issue));
+        }
+    }
+    Ok(issues)
+}
@@ -0,0 +1,99 @@
+//! Export command handler
+//!
+//! Handles `export --graph`, writing the message thread graph of one session
+//! as DOT or JSON, and `export --site`, writing a static HTML browser of the
+//! sessions in the sync repo. Bundle exports go straight to the sync module.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::graph::{GraphFormat, SessionGraph};
+use crate::parser::ConversationSession;
+use crate::session_ref::session_id_or_pick;
+use crate::sync::SyncState;
+use crate::tombstones::session_paths;
+
+/// Handle `export --graph`, offering a picker when no session is given
+pub fn handle_export_graph(
+    session: Option<&str>,
+    format: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    let format = GraphFormat::parse(format)?;
+    let session_id = session_id_or_pick(session)?;
+    let session = load_session(&session_id)?;
+    let graph = SessionGraph::build(&session);
+    let rendered = graph.render(format)?;
+
+    match output {
+        Some(path) => {
+            fs::write(path, &rendered)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!(
+                "{} Wrote the graph of {} ({} entries, {} forks) to {}",
+                glyphs::ok().green(),
+                session_id,
+                graph.nodes.len(),
+                graph.forks.len(),
+                path.display()
+            );
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+/// Handle `export --site`
+pub fn handle_export_site(out_dir: &Path, verbosity: crate::VerbosityLevel) -> Result<()> {
+    use crate::VerbosityLevel;
+
+    let state = SyncState::load()?;
+    let filter = FilterConfig::load()?;
+    let projects_dir = state.sync_repo_path.join(filter.projects_subdir());
+    let sessions = crate::sync::discover_sessions(&projects_dir, &FilterConfig::default())?;
+    let stats = crate::site::write_site(&sessions, &projects_dir, out_dir)?;
+
+    if verbosity >= VerbosityLevel::Summary {
+        println!(
+            "{} Wrote {} sessions from {} projects to {}",
+            glyphs::ok().green(),
+            stats.sessions,
+            stats.projects,
+            out_dir.join("index.html").display()
+        );
+    }
+    Ok(())
+}
+
+/// The session from `~/.claude`, or from the sync repo when it isn't local
+fn load_session(session_id: &str) -> Result<ConversationSession> {
+    let filter = FilterConfig::load()?;
+    let id_source = filter.session_id_source()?;
+    let mut dirs = vec![crate::sync::claude_projects_dir()?];
+    if let Ok(state) = SyncState::load() {
+        dirs.push(state.sync_repo_path.join(filter.projects_subdir()));
+    }
+
+    for dir in dirs {
+        // The session file and its continuation files, not subagent transcripts
+        let files: Vec<_> = session_paths(&dir, session_id)
+            .into_iter()
+            .filter(|path| path.is_file())
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+        let parts = files
+            .iter()
+            .map(|path| ConversationSession::from_file_with(path, id_source))
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(session) = crate::parts::reassemble(parts).into_iter().next() {
+            return Ok(session);
+        }
+    }
+    bail!("Session {session_id} not found locally or in the sync repo")
+}
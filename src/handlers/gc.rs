@@ -0,0 +1,150 @@
+//! Garbage collection command handlers
+//!
+//! Removes this machine's temp branches past the retention window, and on
+//! request sync leftovers that no single machine cleans up on its own, such
+//! as temp branches pushed by machines that no longer exist.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+use crate::config::ConfigManager;
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::scm::{self, Scm};
+use crate::sync::{
+    cleanup_old_temp_branches, stale_own_temp_branches, stale_remote_temp_branches, SyncState,
+};
+use crate::VerbosityLevel;
+
+/// Temp branches younger than this are never collected, since their pull may still be running
+const MIN_TEMP_BRANCH_AGE_HOURS: u32 = 24;
+
+/// Handle gc command
+pub fn handle_gc(
+    remote_temp_branches: bool,
+    older_than_hours: Option<u32>,
+    dry_run: bool,
+) -> Result<()> {
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let retention_hours = FilterConfig::load()?.temp_branch_retention_hours;
+
+    collect_own_temp_branches(repo.as_ref(), state.has_remote, retention_hours, dry_run)?;
+
+    if remote_temp_branches {
+        if !state.has_remote {
+            bail!("The sync repository has no remote configured");
+        }
+        let max_age_hours = older_than_hours
+            .unwrap_or(retention_hours)
+            .max(MIN_TEMP_BRANCH_AGE_HOURS);
+        collect_remote_temp_branches(repo.as_ref(), max_age_hours, dry_run)?;
+    }
+
+    if dry_run {
+        println!("\n  {} Dry run: nothing was deleted", glyphs::info().cyan());
+    }
+    Ok(())
+}
+
+/// Delete this machine's temp branches past the retention window, as every
+/// pull and push does
+fn collect_own_temp_branches(
+    repo: &dyn Scm,
+    has_remote: bool,
+    retention_hours: u32,
+    dry_run: bool,
+) -> Result<()> {
+    let machine_id = ConfigManager::machine_id()?;
+    let stale = stale_own_temp_branches(repo, &machine_id, retention_hours)?;
+    if stale.is_empty() {
+        println!(
+            "  {} No temp branches of this machine past the retention window",
+            glyphs::ok().green()
+        );
+        return Ok(());
+    }
+
+    if dry_run {
+        println!(
+            "{}",
+            format!(
+                "Temp branches of this machine older than {} hours ({}):",
+                retention_hours,
+                stale.len()
+            )
+            .cyan()
+            .bold()
+        );
+        for branch in &stale {
+            println!("  {} {}", glyphs::detail().dimmed(), branch);
+        }
+        return Ok(());
+    }
+
+    cleanup_old_temp_branches(
+        repo,
+        &machine_id,
+        has_remote,
+        retention_hours,
+        VerbosityLevel::Normal,
+    )?;
+    Ok(())
+}
+
+/// Delete temp branches on the remote, from any machine, older than
+/// `max_age_hours`
+fn collect_remote_temp_branches(repo: &dyn Scm, max_age_hours: u32, dry_run: bool) -> Result<()> {
+    let stale = stale_remote_temp_branches(repo, "origin", max_age_hours)?;
+
+    if stale.is_empty() {
+        println!(
+            "  {} No remote temp branches older than {} hours",
+            glyphs::ok().green(),
+            max_age_hours
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        format!(
+            "Remote temp branches older than {} hours ({}):",
+            max_age_hours,
+            stale.len()
+        )
+        .cyan()
+        .bold()
+    );
+
+    let mut deleted = 0;
+    for branch in &stale {
+        if dry_run {
+            println!("  {} {}", glyphs::detail().dimmed(), branch);
+            continue;
+        }
+        match repo.delete_remote_branch("origin", branch) {
+            Ok(()) => {
+                deleted += 1;
+                println!("  {} Deleted origin/{}", glyphs::ok().green(), branch);
+            }
+            Err(e) => println!(
+                "  {} Could not delete origin/{}: {}",
+                "!".yellow().bold(),
+                branch,
+                e
+            ),
+        }
+    }
+
+    if !dry_run {
+        println!(
+            "\n  {} Deleted {} of {} branches",
+            glyphs::ok().green(),
+            deleted,
+            stale.len()
+        );
+    }
+
+    Ok(())
+}
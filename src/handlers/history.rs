@@ -6,14 +6,26 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 use inquire::Select;
+use std::fs;
+use std::path::Path;
 
+use crate::glyphs;
 use crate::history;
 use crate::interactive_conflict;
+use crate::table::{Column, OutputFormat, Table};
 
 /// Handle history list command
-pub fn handle_history_list(limit: usize) -> Result<()> {
+pub fn handle_history_list(limit: usize, format: &str) -> Result<()> {
+    let format = OutputFormat::parse(format)?;
     let history = history::OperationHistory::load().context("Failed to load operation history")?;
 
+    if format != OutputFormat::Table {
+        let operations = history.list_operations();
+        let shown = &operations[..operations.len().min(limit)];
+        println!("{}", operations_table(shown).render(format)?);
+        return Ok(());
+    }
+
     if history.is_empty() {
         println!("{}", "No operations in history.".yellow());
         return Ok(());
@@ -30,6 +42,7 @@ pub fn handle_history_list(limit: usize) -> Result<()> {
         let op_type = match op.operation_type {
             history::OperationType::Pull => "PULL".green(),
             history::OperationType::Push => "PUSH".blue(),
+            history::OperationType::Sync => "SYNC".magenta(),
         };
 
         println!("\n{} {}", num.bold(), op_type.bold());
@@ -63,6 +76,14 @@ pub fn handle_history_list(limit: usize) -> Result<()> {
         if op.snapshot_path.is_some() {
             println!("   {} {}", "Snapshot:".dimmed(), "Available".green());
         }
+
+        if op.is_inconsistent() {
+            println!(
+                "   {} {} diverged sessions after verification",
+                "Inconsistent:".red().bold(),
+                op.inconsistent_sessions.len()
+            );
+        }
     }
 
     if operations.len() > display_count {
@@ -77,8 +98,92 @@ pub fn handle_history_list(limit: usize) -> Result<()> {
     Ok(())
 }
 
+/// Handle history export command
+///
+/// Writes one row per conversation per recorded operation.
+pub fn handle_history_export(format: &str, output: Option<&Path>) -> Result<()> {
+    let format = OutputFormat::parse(format)?;
+    if format == OutputFormat::Table {
+        anyhow::bail!("history export supports 'csv' or 'json'");
+    }
+    let history = history::OperationHistory::load().context("Failed to load operation history")?;
+    let content = conversations_table(history.list_operations()).render(format)?;
+
+    match output {
+        Some(path) => {
+            fs::write(path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!(
+                "{} {}",
+                "History exported to:".green().bold(),
+                path.display().to_string().cyan()
+            );
+        }
+        None => print!("{content}"),
+    }
+    Ok(())
+}
+
+/// One row per operation with its change counts
+fn operations_table(operations: &[history::OperationRecord]) -> Table {
+    let mut table = Table::new(vec![
+        Column::new("Timestamp", "timestamp"),
+        Column::new("Type", "type"),
+        Column::new("Branch", "branch"),
+        Column::new("Conversations", "conversations").right(),
+        Column::new("Added", "added").right(),
+        Column::new("Modified", "modified").right(),
+        Column::new("Conflicts", "conflicts").right(),
+        Column::new("Unchanged", "unchanged").right(),
+        Column::new("Commit", "commit"),
+    ]);
+    for op in operations {
+        let stats = op.operation_stats();
+        let count = |sync_op| stats.get(&sync_op).copied().unwrap_or(0).to_string();
+        table.push_row(vec![
+            op.timestamp.to_rfc3339(),
+            op.operation_type.as_str().to_string(),
+            op.branch.clone().unwrap_or_default(),
+            op.affected_conversations.len().to_string(),
+            count(history::SyncOperation::Added),
+            count(history::SyncOperation::Modified),
+            count(history::SyncOperation::Conflict),
+            count(history::SyncOperation::Unchanged),
+            op.commit_hash.clone().unwrap_or_default(),
+        ]);
+    }
+    table
+}
+
+/// One row per conversation affected by each operation
+fn conversations_table(operations: &[history::OperationRecord]) -> Table {
+    let mut table = Table::new(vec![
+        Column::new("Operation Time", "operation_timestamp"),
+        Column::new("Operation", "operation_type"),
+        Column::new("Session ID", "session_id"),
+        Column::new("Project Path", "project_path"),
+        Column::new("Change", "change"),
+        Column::new("Messages", "message_count").right(),
+        Column::new("Conversation Time", "timestamp"),
+    ]);
+    for op in operations {
+        for conv in &op.affected_conversations {
+            table.push_row(vec![
+                op.timestamp.to_rfc3339(),
+                op.operation_type.as_str().to_string(),
+                conv.session_id.clone(),
+                conv.project_path.clone(),
+                conv.operation.as_str().to_string(),
+                conv.message_count.to_string(),
+                conv.timestamp.clone().unwrap_or_default(),
+            ]);
+        }
+    }
+    table
+}
+
 /// Handle history last command
-pub fn handle_history_last(operation_type: Option<&str>) -> Result<()> {
+pub fn handle_history_last(operation_type: Option<&str>, format: &str) -> Result<()> {
     let history = history::OperationHistory::load().context("Failed to load operation history")?;
 
     let operation = if let Some(op_type) = operation_type {
@@ -86,9 +191,10 @@ pub fn handle_history_last(operation_type: Option<&str>) -> Result<()> {
         let filter_type = match op_type.to_lowercase().as_str() {
             "pull" => history::OperationType::Pull,
             "push" => history::OperationType::Push,
+            "sync" => history::OperationType::Sync,
             _ => {
                 return Err(anyhow::anyhow!(
-                    "Invalid operation type '{op_type}'. Must be 'pull' or 'push'."
+                    "Invalid operation type '{op_type}'. Must be 'pull', 'push', or 'sync'."
                 ));
             }
         };
@@ -105,12 +211,28 @@ pub fn handle_history_last(operation_type: Option<&str>) -> Result<()> {
             .ok_or_else(|| anyhow::anyhow!("No operations in history."))?
     };
 
+    match format {
+        "table" => {}
+        "json" => {
+            let json = serde_json::to_string_pretty(operation)
+                .context("Failed to serialize operation")?;
+            println!("{json}");
+            return Ok(());
+        }
+        other => {
+            return Err(anyhow::anyhow!(
+                "Invalid format '{other}'. Must be 'table' or 'json'."
+            ));
+        }
+    }
+
     println!("{}", "Last Operation Details".cyan().bold());
     println!("{}", "=".repeat(80).cyan());
 
     let op_type = match operation.operation_type {
         history::OperationType::Pull => "PULL".green(),
         history::OperationType::Push => "PUSH".blue(),
+        history::OperationType::Sync => "SYNC".magenta(),
     };
 
     println!("\n{} {}", "Type:".bold(), op_type.bold());
@@ -124,6 +246,14 @@ pub fn handle_history_last(operation_type: Option<&str>) -> Result<()> {
         println!("{} {}", "Branch:".bold(), branch);
     }
 
+    if operation.remote_unreachable {
+        println!(
+            "{} {}",
+            "Remote:".bold(),
+            "unreachable (local only)".yellow()
+        );
+    }
+
     println!(
         "{} {}",
         "Total Conversations:".bold(),
@@ -153,6 +283,60 @@ pub fn handle_history_last(operation_type: Option<&str>) -> Result<()> {
         );
     }
 
+    if let Some(backup_path) = &operation.backup_path {
+        println!(
+            "{} {}",
+            "Backups:".bold(),
+            backup_path.display().to_string().dimmed()
+        );
+    }
+
+    if let Some(report_id) = &operation.conflict_report {
+        println!(
+            "{} {} {}",
+            "Conflict report:".bold(),
+            report_id,
+            format!("(conflicts show {report_id})").dimmed()
+        );
+    }
+
+    if !operation.phase_timings.is_empty() {
+        println!("{}", "Timings:".bold());
+        for timing in &operation.phase_timings {
+            println!(
+                "  {:<20} {:>8.2}s",
+                timing.phase,
+                timing.millis as f64 / 1000.0
+            );
+        }
+    }
+
+    if operation.is_inconsistent() {
+        println!(
+            "\n{} {}",
+            "Inconsistent sessions:".red().bold(),
+            operation.inconsistent_sessions.join(", ")
+        );
+    }
+
+    if !operation.skipped_sessions.is_empty() {
+        println!(
+            "\n{} {}",
+            "Skipped Sessions:".yellow().bold(),
+            operation.skipped_sessions.len()
+        );
+        for skip in &operation.skipped_sessions {
+            println!("  {} {}", skip.path, format!("({})", skip.reason).dimmed());
+        }
+    }
+
+    if !operation.network_failures.is_empty() {
+        println!("\n{}", "Network Failures:".yellow().bold());
+        for failure in &operation.network_failures {
+            println!("  {failure}");
+        }
+    }
+
     // Show some conversation details
     if !operation.affected_conversations.is_empty() {
         println!("\n{}", "Affected Conversations:".bold());
@@ -249,6 +433,7 @@ pub fn handle_history_review(limit: usize) -> Result<()> {
             let op_type = match op.operation_type {
                 history::OperationType::Pull => "PULL".to_string(),
                 history::OperationType::Push => "PUSH".to_string(),
+                history::OperationType::Sync => "SYNC".to_string(),
             };
 
             let timestamp = op.timestamp.format("%Y-%m-%d %H:%M").to_string();
@@ -267,8 +452,9 @@ pub fn handle_history_review(limit: usize) -> Result<()> {
         .collect();
 
     // Add an option to exit
+    let exit = format!("{} Exit review", glyphs::back());
     let mut options_with_exit = options.clone();
-    options_with_exit.push("← Exit review".to_string());
+    options_with_exit.push(exit.clone());
 
     loop {
         let selection = Select::new("Select an operation to review (or Exit):", options_with_exit.clone())
@@ -277,7 +463,7 @@ pub fn handle_history_review(limit: usize) -> Result<()> {
 
         match selection {
             Ok(selected) => {
-                if selected == "← Exit review" {
+                if selected == exit {
                     println!("\n{}", "Exiting review.".dimmed());
                     break;
                 }
@@ -295,6 +481,7 @@ pub fn handle_history_review(limit: usize) -> Result<()> {
                         let op_type = match operation.operation_type {
                             history::OperationType::Pull => "PULL".green(),
                             history::OperationType::Push => "PUSH".blue(),
+                            history::OperationType::Sync => "SYNC".magenta(),
                         };
 
                         println!("\n{} {}", "Type:".bold(), op_type.bold());
@@ -0,0 +1,96 @@
+//! History index command handlers
+//!
+//! Handles repairing the local `history.jsonl`, the session index behind
+//! Claude Code's `--resume` picker.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::HashSet;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::glyphs;
+use crate::sync::{claude_projects_dir, fix_history_file};
+
+/// Handle history-index fix command
+pub fn handle_history_index_fix(prune_missing: bool, dry_run: bool) -> Result<()> {
+    let projects_dir = claude_projects_dir()?;
+    let claude_dir = projects_dir.parent().unwrap_or(&projects_dir);
+    let history_path = claude_dir.join("history.jsonl");
+    if !history_path.exists() {
+        println!(
+            "{}",
+            format!("No {} found.", history_path.display()).yellow()
+        );
+        return Ok(());
+    }
+
+    let existing = prune_missing.then(|| local_session_ids(&projects_dir));
+    // Inspect first so the file is only backed up when it will change
+    let summary = fix_history_file(&history_path, existing.as_ref(), true)?;
+    if !summary.needs_rewrite() {
+        println!(
+            "{} {} is clean ({} entries)",
+            glyphs::ok().green(),
+            history_path.display(),
+            summary.kept
+        );
+        return Ok(());
+    }
+
+    println!("{}", history_path.display().to_string().cyan().bold());
+    for (count, label) in [
+        (summary.duplicates, "duplicate entries"),
+        (summary.invalid, "invalid lines"),
+        (summary.orphaned, "entries for missing sessions"),
+    ] {
+        if count > 0 {
+            println!("  {} {} {}", glyphs::detail().dimmed(), count, label);
+        }
+    }
+    if summary.unsorted {
+        println!("  {} entries out of order", glyphs::detail().dimmed());
+    }
+
+    if dry_run {
+        println!(
+            "\n  {} Dry run: {} entries would be kept",
+            glyphs::info().cyan(),
+            summary.kept
+        );
+        return Ok(());
+    }
+
+    let backup_dir = crate::backups::new_run_dir()?;
+    crate::backups::backup_file(&backup_dir, &history_path, Path::new("history.jsonl"))?;
+    let summary = fix_history_file(&history_path, existing.as_ref(), false)
+        .with_context(|| format!("Failed to rewrite {}", history_path.display()))?;
+    println!(
+        "\n{} Rewrote history.jsonl with {} entries",
+        glyphs::ok().green(),
+        summary.kept
+    );
+    if let Some(backup) = crate::backups::finish_run(&backup_dir) {
+        println!(
+            "  {} Previous file backed up to {}",
+            glyphs::info().cyan(),
+            backup.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Session IDs of the session files under the projects directory
+fn local_session_ids(projects_dir: &Path) -> HashSet<String> {
+    WalkDir::new(projects_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+        .filter_map(|e| {
+            e.path()
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+        })
+        .collect()
+}
@@ -0,0 +1,109 @@
+//! Mark command handlers
+//!
+//! Handles marking sessions and projects local-only, so they are never
+//! copied into the sync repo even when include patterns match them.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeSet;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::parts::split_part;
+use crate::paths::encode_project_path;
+use crate::session_ref::resolve_session_id;
+use crate::sync::{claude_projects_dir, SyncState};
+
+/// Handle mark local-only command (lists local-only entries without a target)
+pub fn handle_mark_local_only(target: Option<&str>, undo: bool) -> Result<()> {
+    let mut config = FilterConfig::load().context("Failed to load configuration")?;
+
+    let Some(target) = target else {
+        if config.local_only.is_empty() {
+            println!("{}", "No local-only sessions or projects.".yellow());
+            return Ok(());
+        }
+        println!("{}", "Local-only Sessions and Projects".cyan().bold());
+        for entry in &config.local_only {
+            println!("  {} {}", glyphs::bullet().cyan(), entry);
+        }
+        return Ok(());
+    };
+    let entry = local_only_entry(target)?;
+
+    if undo {
+        let before = config.local_only.len();
+        config.local_only.retain(|e| e != &entry);
+        if config.local_only.len() == before {
+            println!("{}", format!("{} is not local-only", entry).yellow());
+            return Ok(());
+        }
+        config.save()?;
+        println!("{}", format!("{} syncs again", entry).green());
+        return Ok(());
+    }
+
+    if config.local_only.contains(&entry) {
+        println!("{}", format!("{} is already local-only", entry).yellow());
+        return Ok(());
+    }
+    config.local_only.push(entry.clone());
+    config.save()?;
+    println!("{}", format!("Marked {} local-only", entry).green());
+
+    // Copies pushed before the mark stay in the sync repo's history
+    if let Ok(state) = SyncState::load() {
+        let synced = synced_sessions(
+            &state.sync_repo_path.join(config.projects_subdir()),
+            &config,
+        );
+        if !synced.is_empty() {
+            println!(
+                "  {} {} matching session(s) are already in the sync repo; run `claude-code-sync purge <session>` to remove them from its history",
+                "!".yellow().bold(),
+                synced.len()
+            );
+            for session_id in &synced {
+                println!("    {} {}", glyphs::detail().dimmed(), session_id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Turn a target into a `local_only` entry: glob patterns are kept as is,
+/// paths become project directory names, names of existing project
+/// directories are kept, and anything else is resolved as a session
+fn local_only_entry(target: &str) -> Result<String> {
+    if target.contains('*') {
+        return Ok(target.to_string());
+    }
+    if target.contains('/') || target == "." || target == ".." {
+        let path = std::env::current_dir()?.join(target);
+        let path = path.canonicalize().unwrap_or(path);
+        return Ok(encode_project_path(&path));
+    }
+    if claude_projects_dir()?.join(target).is_dir() {
+        return Ok(target.to_string());
+    }
+    resolve_session_id(target)
+}
+
+/// IDs of sessions in a sync repo projects directory that are local-only
+fn synced_sessions(projects_dir: &Path, config: &FilterConfig) -> BTreeSet<String> {
+    WalkDir::new(projects_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+        .map(|e| split_part(e.path()).map_or_else(|| e.path().to_path_buf(), |(base, _)| base))
+        .filter(|path| config.is_local_only(path))
+        .filter_map(|path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .filter(|id| !id.starts_with("agent-"))
+        .collect()
+}
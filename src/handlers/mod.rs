@@ -3,11 +3,52 @@
 //! This module contains all command handler functions extracted from main.rs,
 //! organized by functionality area.
 
+pub mod audit;
+pub mod blame;
 pub mod config;
+pub mod conflicts;
+pub mod credentials;
+pub mod delete;
+pub mod doctor;
+pub mod export;
+pub mod gc;
 pub mod history;
+pub mod history_index;
+pub mod mark;
 pub mod onboarding;
+pub mod pin;
+pub mod purge;
+pub mod snapshot;
+pub mod stress;
+pub mod subscribe;
+pub mod tag;
+pub mod trash;
 
 // Re-export all public handler functions for convenient use
+pub use audit::handle_audit_list;
+pub use blame::handle_blame;
 pub use config::{handle_config_interactive, handle_config_wizard};
-pub use history::{handle_history_clear, handle_history_last, handle_history_list, handle_history_review};
+pub use conflicts::{handle_conflicts_history, handle_conflicts_show};
+pub use credentials::{
+    handle_credentials_get, handle_credentials_remove, handle_credentials_set,
+};
+pub use delete::handle_delete;
+pub use doctor::handle_doctor;
+pub use export::{handle_export_graph, handle_export_site};
+pub use gc::handle_gc;
+pub use history::{
+    handle_history_clear, handle_history_export, handle_history_last, handle_history_list,
+    handle_history_review,
+};
+pub use history_index::handle_history_index_fix;
+pub use mark::handle_mark_local_only;
 pub use onboarding::{is_initialized, run_init_from_config, run_onboarding_flow, try_init_from_config};
+pub use pin::{handle_pin_add, handle_pin_list, handle_pin_remove};
+pub use purge::handle_purge;
+pub use snapshot::handle_snapshot;
+pub use stress::handle_stress;
+pub use subscribe::{
+    handle_subscribe_add, handle_subscribe_list, handle_subscribe_pull, handle_subscribe_remove,
+};
+pub use tag::{handle_tag_add, handle_tag_list, handle_tag_remove};
+pub use trash::{handle_trash_empty, handle_trash_list, handle_trash_restore};
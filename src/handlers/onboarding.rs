@@ -9,6 +9,7 @@ use std::path::Path;
 
 use crate::config;
 use crate::filter;
+use crate::glyphs;
 use crate::onboarding::{self, InitConfig};
 use crate::scm;
 use crate::sync;
@@ -29,12 +30,12 @@ pub fn run_onboarding_flow() -> Result<()> {
     if onboarding_config.is_cloned {
         if let Some(ref remote_url) = onboarding_config.remote_url {
             println!();
-            println!("{}", "✓ Cloning repository...".cyan());
+            println!("{}", format!("{} Cloning repository...", glyphs::ok()).cyan());
 
             scm::clone(remote_url, &onboarding_config.repo_path)
                 .context("Failed to clone repository")?;
 
-            println!("{}", "✓ Repository cloned successfully!".green());
+            println!("{}", format!("{} Repository cloned successfully!", glyphs::ok()).green());
         }
     }
 
@@ -56,7 +57,7 @@ pub fn run_onboarding_flow() -> Result<()> {
         .save()
         .context("Failed to save filter configuration")?;
 
-    println!("{}", "✓ Ready to sync!".green().bold());
+    println!("{}", format!("{} Ready to sync!", glyphs::ok()).green().bold());
     println!();
 
     Ok(())
@@ -80,7 +81,9 @@ pub fn run_init_from_config<P: AsRef<Path>>(config_path: Option<P>) -> Result<()
 
     println!(
         "{}",
-        "📄 Initializing from config file...".cyan().bold()
+        format!("{} Initializing from config file...", glyphs::config_file())
+            .cyan()
+            .bold()
     );
 
     // Convert to onboarding config
@@ -94,7 +97,7 @@ pub fn run_init_from_config<P: AsRef<Path>>(config_path: Option<P>) -> Result<()
             scm::clone(remote_url, &onboarding_config.repo_path)
                 .context("Failed to clone repository")?;
 
-            println!("{}", "  ✓ Repository cloned".green());
+            println!("{}", format!("  {} Repository cloned", glyphs::ok()).green());
         }
     }
 
@@ -119,7 +122,7 @@ pub fn run_init_from_config<P: AsRef<Path>>(config_path: Option<P>) -> Result<()
         .save()
         .context("Failed to save filter configuration")?;
 
-    println!("{}", "✓ Initialization complete!".green().bold());
+    println!("{}", format!("{} Initialization complete!", glyphs::ok()).green().bold());
     println!("  {} {}", "Repo:".cyan(), onboarding_config.repo_path.display());
     if let Some(ref url) = onboarding_config.remote_url {
         println!("  {} {}", "Remote:".cyan(), url);
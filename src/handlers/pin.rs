@@ -0,0 +1,64 @@
+//! Pin command handlers
+//!
+//! Handles pinning sessions so they always sync (bypassing age and size
+//! filters) and are processed before all other sessions.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::session_ref::session_id_or_pick;
+
+/// Handle pin add command
+pub fn handle_pin_add(session_id: Option<&str>) -> Result<()> {
+    let session_id = session_id_or_pick(session_id)?;
+    let mut config = FilterConfig::load().context("Failed to load configuration")?;
+
+    if config.pinned_sessions.iter().any(|id| id == &session_id) {
+        println!("{}", format!("Session {} is already pinned", session_id).yellow());
+        return Ok(());
+    }
+
+    config.pinned_sessions.push(session_id.clone());
+    config.save()?;
+    println!("{}", format!("Pinned session {}", session_id).green());
+
+    Ok(())
+}
+
+/// Handle pin remove command
+pub fn handle_pin_remove(session_id: Option<&str>) -> Result<()> {
+    let session_id = session_id_or_pick(session_id)?;
+    let mut config = FilterConfig::load().context("Failed to load configuration")?;
+
+    let before = config.pinned_sessions.len();
+    config.pinned_sessions.retain(|id| id != &session_id);
+
+    if config.pinned_sessions.len() == before {
+        println!("{}", format!("Session {} is not pinned", session_id).yellow());
+        return Ok(());
+    }
+
+    config.save()?;
+    println!("{}", format!("Unpinned session {}", session_id).green());
+
+    Ok(())
+}
+
+/// Handle pin list command
+pub fn handle_pin_list() -> Result<()> {
+    let config = FilterConfig::load().context("Failed to load configuration")?;
+
+    if config.pinned_sessions.is_empty() {
+        println!("{}", "No pinned sessions.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", "Pinned Sessions".cyan().bold());
+    for session_id in &config.pinned_sessions {
+        println!("  {} {}", glyphs::bullet().cyan(), session_id);
+    }
+
+    Ok(())
+}
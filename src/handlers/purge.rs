@@ -0,0 +1,205 @@
+//! Purge command handler
+//!
+//! Handles removing sessions from every commit of the sync repo, for secrets
+//! that were already pushed. `delete` only removes a session from the
+//! current tree; purge rewrites the history and force-pushes it.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use inquire::Confirm;
+use std::collections::BTreeSet;
+
+use crate::config::ConfigManager;
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::interactive_conflict::is_interactive;
+use crate::lock::SyncLock;
+use crate::manifest::write_manifest;
+use crate::purge::{file_session_id, PurgeIndex, PurgeTarget};
+use crate::scm::{self, HistoryRewrite};
+use crate::session_ref::resolve_session_id_strict;
+use crate::sync::{
+    claude_projects_dir, managed_paths, recover_from_purge, remove_history_sessions,
+    remove_snapshots, remove_temp_branches, SyncState, TEMP_BRANCH_PREFIX,
+};
+use crate::tags::TagIndex;
+use crate::tombstones::{session_paths, TombstoneIndex};
+use crate::trash::Trash;
+use crate::VerbosityLevel;
+
+/// Number of purged paths listed before the confirmation prompt
+const LISTED_PATHS: usize = 20;
+
+/// Handle purge command
+pub fn handle_purge(target: &str, yes: bool, push_remote: bool) -> Result<()> {
+    let target = if PurgeTarget::is_pattern(target) {
+        PurgeTarget::Pattern(target.to_string())
+    } else {
+        PurgeTarget::Session(resolve_session_id_strict(target)?)
+    };
+    let _lock = SyncLock::acquire()?;
+
+    let state = SyncState::load()?;
+    let filter = FilterConfig::load().context("Failed to load configuration")?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let branch = repo.current_branch()?;
+    if branch.starts_with(TEMP_BRANCH_PREFIX) {
+        bail!(
+            "The sync repository is on temp branch '{}' left by an interrupted pull. \
+             Run 'claude-code-sync pull' first.",
+            branch
+        );
+    }
+    if repo.has_changes()? {
+        bail!("The sync repository has uncommitted changes. Run 'claude-code-sync sync' first.");
+    }
+
+    // The rewritten branch replaces the remote's, so it must include
+    // everything already pushed
+    let remote = push_remote && state.has_remote;
+    if remote {
+        recover_from_purge(
+            repo.as_ref(),
+            &state.sync_repo_path,
+            &branch,
+            VerbosityLevel::Normal,
+        )?;
+        repo.pull("origin", &branch)
+            .context("Failed to pull before purging")?;
+    }
+
+    let files = repo.list_files_in_history()?;
+    let paths: Vec<String> = target.matching_paths(&files).into_iter().cloned().collect();
+    let sessions: BTreeSet<String> = match &target {
+        PurgeTarget::Session(id) => BTreeSet::from([id.clone()]),
+        PurgeTarget::Pattern(_) => paths.iter().filter_map(|p| file_session_id(p)).collect(),
+    };
+    if paths.is_empty() {
+        bail!(
+            "Nothing in the history of '{}' matches {:?}",
+            branch,
+            target
+        );
+    }
+
+    println!(
+        "{} {} files ({} sessions) from every commit on '{}':",
+        "Purging".cyan().bold(),
+        paths.len(),
+        sessions.len(),
+        branch
+    );
+    for path in paths.iter().take(LISTED_PATHS) {
+        println!("  {} {}", glyphs::detail().dimmed(), path);
+    }
+    if paths.len() > LISTED_PATHS {
+        println!(
+            "  {} ... and {} more",
+            glyphs::detail().dimmed(),
+            paths.len() - LISTED_PATHS
+        );
+    }
+    println!(
+        "\n{} This rewrites the sync repo history{}, and deletes all temp branches.",
+        "!".yellow().bold(),
+        if remote {
+            " and force-pushes it to origin"
+        } else {
+            ""
+        }
+    );
+    if !yes {
+        if !is_interactive() {
+            bail!("Refusing to rewrite history without confirmation; pass --yes");
+        }
+        let confirm = Confirm::new("Purge these files from the sync repo history?")
+            .with_default(false)
+            .prompt()
+            .context("Failed to get confirmation")?;
+        if !confirm {
+            println!("\n{}", "Purge cancelled.".yellow());
+            return Ok(());
+        }
+    }
+
+    repo.rewrite_history(&HistoryRewrite {
+        remove_paths: paths.clone(),
+        scrub_path: Some("history.jsonl".to_string()),
+        drop_lines: sessions
+            .iter()
+            .map(|id| format!("\"sessionId\":\"{id}\""))
+            .collect(),
+    })?;
+    println!("  {} Rewrote the history of '{}'", glyphs::ok().green(), branch);
+
+    // Local copies and the sidecar indexes
+    let projects_dir = claude_projects_dir()?;
+    let trash = Trash::open()?;
+    let mut trashed = 0;
+    for session_id in &sessions {
+        for path in session_paths(&projects_dir, session_id) {
+            trash.put(&path, "purge")?;
+            trashed += 1;
+        }
+    }
+    let claude_dir = projects_dir.parent().unwrap_or(&projects_dir);
+    remove_history_sessions(&claude_dir.join("history.jsonl"), |id| {
+        sessions.contains(id)
+    })?;
+
+    let machine_id = ConfigManager::machine_id()?;
+    let mut tombstones = TombstoneIndex::load(&state.sync_repo_path)?;
+    let mut tags = TagIndex::load(&state.sync_repo_path)?;
+    for session_id in &sessions {
+        tombstones.add(session_id, &machine_id);
+        tags.sessions.remove(session_id);
+    }
+    tombstones.save(&state.sync_repo_path)?;
+    tags.save(&state.sync_repo_path)?;
+    let mut purges = PurgeIndex::load(&state.sync_repo_path)?;
+    purges.add(&machine_id, sessions.clone());
+    purges.save(&state.sync_repo_path)?;
+    if filter.checksum_manifest {
+        write_manifest(&state.sync_repo_path)?;
+    }
+
+    repo.stage_paths(&managed_paths(&filter))?;
+    repo.commit(&format!("Purge {} sessions from history", sessions.len()))?;
+
+    if remote {
+        repo.force_push("origin", &branch).context(
+            "Failed to force-push the rewritten history; run 'claude-code-sync purge' again",
+        )?;
+        println!("  {} Force-pushed to origin/{}", glyphs::ok().green(), branch);
+    }
+    let removed_branches = remove_temp_branches(repo.as_ref(), remote)?;
+    let removed_snapshots = remove_snapshots(repo.as_ref(), remote)?;
+    repo.prune_unreachable()?;
+    println!(
+        "  {} Moved {} local files to the trash, deleted {} temp branches and {} snapshots",
+        glyphs::ok().green(),
+        trashed,
+        removed_branches,
+        removed_snapshots
+    );
+
+    println!(
+        "\n{} Other machines reset to the rewritten history at their next pull.",
+        glyphs::info().cyan()
+    );
+    if trashed > 0 {
+        println!(
+            "{} The local copies are in the trash; run {} to remove them for good.",
+            glyphs::info().cyan(),
+            "claude-code-sync trash empty".bold()
+        );
+    }
+    println!(
+        "{} Other clones of the sync repo (backups, forks) still hold the old commits; \
+         delete and clone them again. Git hosts may keep unreachable commits cached \
+         until they garbage-collect.",
+        glyphs::info().cyan()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Snapshot command handlers
+//!
+//! Handles taking, listing, deleting, and restoring named snapshots of the
+//! sync repo.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::glyphs;
+use crate::scm::{self, TagInfo};
+use crate::sync::{self, SyncState};
+use crate::table::{Column, Keep, OutputFormat, Table};
+
+/// Handle snapshot command: take snapshot `name`, or list snapshots
+/// without one
+pub fn handle_snapshot(
+    name: Option<&str>,
+    message: Option<&str>,
+    delete: bool,
+    format: &str,
+    verbosity: crate::VerbosityLevel,
+) -> Result<()> {
+    match name {
+        Some(name) if delete => {
+            sync::delete_snapshot(name)?;
+            println!("{} Deleted snapshot {}", glyphs::ok().green(), name.bold());
+            Ok(())
+        }
+        Some(name) => sync::create_snapshot(name, message, verbosity),
+        None => handle_snapshot_list(format),
+    }
+}
+
+/// List snapshots, oldest first
+fn handle_snapshot_list(format: &str) -> Result<()> {
+    let format = OutputFormat::parse(format)?;
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let snapshots = sync::list_snapshots(repo.as_ref())?;
+
+    if format != OutputFormat::Table {
+        println!("{}", snapshots_table(&snapshots, true).render(format)?);
+        return Ok(());
+    }
+
+    if snapshots.is_empty() {
+        println!("{}", "No snapshots.".yellow());
+        println!(
+            "{} Take one with {}",
+            glyphs::info().cyan(),
+            "claude-code-sync snapshot <name>".bold()
+        );
+        return Ok(());
+    }
+    println!("{}", "Snapshots".cyan().bold());
+    print!("{}", snapshots_table(&snapshots, false).to_text());
+    println!(
+        "\n{} Restore one with {}",
+        glyphs::info().cyan(),
+        "claude-code-sync restore --snapshot <name>".bold()
+    );
+    Ok(())
+}
+
+/// One row per snapshot; `full` keeps whole commit IDs and exact dates
+fn snapshots_table(snapshots: &[TagInfo], full: bool) -> Table {
+    let mut message = Column::new("Message", "message");
+    if !full {
+        message = message.max_width(60, Keep::Start);
+    }
+    let mut table = Table::new(vec![
+        Column::new("Name", "name"),
+        Column::new("Commit", "commit"),
+        Column::new("Date", "date"),
+        message,
+    ]);
+    for snapshot in snapshots {
+        table.push_row(vec![
+            snapshot.name.clone(),
+            if full {
+                snapshot.commit.clone()
+            } else {
+                snapshot.commit.chars().take(12).collect()
+            },
+            if full {
+                snapshot.date.clone()
+            } else {
+                snapshot
+                    .date
+                    .chars()
+                    .take(16)
+                    .collect::<String>()
+                    .replace('T', " ")
+            },
+            snapshot.message.clone(),
+        ]);
+    }
+    table
+}
@@ -0,0 +1,422 @@
+//! Concurrent-sync stress test
+//!
+//! Sets up N simulated machines in a scratch directory, each with its own
+//! `~/.claude`, config directory, and clone of one shared bare remote. Every
+//! round each machine makes random edits (new sessions, or entries appended
+//! to sessions it has, including ones pulled from other machines), then all
+//! machines run `sync` at the same time as separate processes. After the
+//! rounds every machine syncs until they have all seen each other's work,
+//! and every entry ever written must be present on every machine.
+//!
+//! Machines are separate processes of this binary, so they share the real
+//! git backend; the in-memory mock can't be shared across processes.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+use crate::config::CONFIG_DIR_ENV_VAR;
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::paths::encode_project_path;
+use crate::scm;
+use crate::sync::SyncState;
+
+/// Working directory of the sessions the simulated machines create
+const STRESS_PROJECT: &str = "/stress/project";
+
+/// Small deterministic PRNG (xorshift64), so a failing run can be replayed
+/// with the same `--seed`
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in `0..n` (n > 0)
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+struct Machine {
+    name: String,
+    home: PathBuf,
+    config_dir: PathBuf,
+    /// Entry UUIDs this machine has written
+    written: Vec<String>,
+    sessions_created: usize,
+}
+
+impl Machine {
+    fn projects_dir(&self) -> PathBuf {
+        self.home.join(".claude").join("projects")
+    }
+
+    fn sync(&self) -> Result<std::process::Output> {
+        let exe = std::env::current_exe().context("Failed to locate this executable")?;
+        Command::new(exe)
+            .args(["sync", "--quiet", "--no-color"])
+            .env(CONFIG_DIR_ENV_VAR, &self.config_dir)
+            .env("HOME", &self.home)
+            .env("GIT_AUTHOR_NAME", &self.name)
+            .env("GIT_AUTHOR_EMAIL", "stress@localhost")
+            .env("GIT_COMMITTER_NAME", &self.name)
+            .env("GIT_COMMITTER_EMAIL", "stress@localhost")
+            .output()
+            .with_context(|| format!("Failed to run sync on {}", self.name))
+    }
+
+    /// Local session files, sorted so edits are reproducible
+    fn session_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = WalkDir::new(self.projects_dir())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .map(|e| e.into_path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Every entry UUID in this machine's local sessions
+    fn local_uuids(&self) -> BTreeSet<String> {
+        let mut uuids = BTreeSet::new();
+        for file in self.session_files() {
+            let Ok(content) = fs::read_to_string(&file) else {
+                continue;
+            };
+            for line in content.lines() {
+                if let Ok(value) = serde_json::from_str::<serde_json::Value>(line) {
+                    if let Some(uuid) = value.get("uuid").and_then(|u| u.as_str()) {
+                        uuids.insert(uuid.to_string());
+                    }
+                }
+            }
+        }
+        uuids
+    }
+
+    /// Make one to three random edits
+    fn edit(&mut self, rng: &mut Rng) -> Result<()> {
+        for _ in 0..=rng.below(3) {
+            let files = self.session_files();
+            if files.is_empty() || rng.below(10) < 3 {
+                self.create_session(rng)?;
+            } else {
+                let file = &files[rng.below(files.len())];
+                self.append(file, 1 + rng.below(3))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn create_session(&mut self, rng: &mut Rng) -> Result<()> {
+        self.sessions_created += 1;
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let dir = self
+            .projects_dir()
+            .join(encode_project_path(Path::new(STRESS_PROJECT)));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{session_id}.jsonl"));
+        fs::write(&path, "")?;
+        self.append(&path, 1 + rng.below(3))
+    }
+
+    /// Append `count` entries, continuing the session's last entry
+    fn append(&mut self, path: &Path, count: usize) -> Result<()> {
+        let content = fs::read_to_string(path)?;
+        let last: Option<serde_json::Value> = content
+            .lines()
+            .rev()
+            .find_map(|line| serde_json::from_str(line).ok());
+        let session_id = last
+            .as_ref()
+            .and_then(|v| v.get("sessionId"))
+            .and_then(|s| s.as_str())
+            .map(str::to_string)
+            .or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        let mut parent = last
+            .as_ref()
+            .and_then(|v| v.get("uuid"))
+            .and_then(|u| u.as_str())
+            .map(str::to_string);
+
+        let mut lines = String::new();
+        for _ in 0..count {
+            let uuid = uuid::Uuid::new_v4().to_string();
+            let role = if self.written.len().is_multiple_of(2) {
+                "user"
+            } else {
+                "assistant"
+            };
+            let entry = serde_json::json!({
+                "parentUuid": parent,
+                "isSidechain": false,
+                "userType": "external",
+                "cwd": STRESS_PROJECT,
+                "sessionId": session_id,
+                "type": role,
+                "message": {
+                    "role": role,
+                    "content": format!("{} entry {}", self.name, self.written.len() + 1),
+                },
+                "uuid": uuid,
+                "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            });
+            lines.push_str(&entry.to_string());
+            lines.push('\n');
+            self.written.push(uuid.clone());
+            parent = Some(uuid);
+        }
+
+        let mut content = content;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&lines);
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Create the shared remote and the machines' homes, configs, and clones
+fn set_up(root: &Path, machines: usize) -> Result<Vec<Machine>> {
+    let remote = root.join("remote.git");
+    let output = Command::new("git")
+        .args(["init", "--bare"])
+        .arg(&remote)
+        .output()
+        .context("Failed to run 'git init --bare'")?;
+    if !output.status.success() {
+        bail!(
+            "git init --bare failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let remote_url = remote.to_string_lossy().to_string();
+
+    let seed_dir = root.join("seed");
+    let seed = scm::init(&seed_dir)?;
+    fs::write(
+        seed_dir.join("README.md"),
+        "# Stress test sync repository\n",
+    )?;
+    seed.stage_all()?;
+    seed.commit("Initial commit")?;
+    seed.add_remote("origin", &remote_url)?;
+    seed.push("origin", &seed.current_branch()?)?;
+
+    (1..=machines)
+        .map(|number| {
+            let name = format!("machine-{number}");
+            let home = root.join(&name);
+            let config_dir = home.join("sync-config");
+            let repo_dir = home.join("sync-repo");
+            fs::create_dir_all(home.join(".claude").join("projects"))?;
+            fs::create_dir_all(&config_dir)?;
+            scm::clone(&remote_url, &repo_dir)?;
+
+            let state = SyncState {
+                sync_repo_path: repo_dir,
+                has_remote: true,
+                is_cloned_repo: true,
+                rsync_remote: None,
+            };
+            fs::write(
+                config_dir.join("state.json"),
+                serde_json::to_string_pretty(&state)?,
+            )?;
+            let filter = FilterConfig {
+                claude_projects_dir: Some(
+                    home.join(".claude")
+                        .join("projects")
+                        .to_string_lossy()
+                        .to_string(),
+                ),
+                ..FilterConfig::default()
+            };
+            fs::write(
+                config_dir.join("config.toml"),
+                toml::to_string_pretty(&filter)?,
+            )?;
+
+            Ok(Machine {
+                name,
+                home,
+                config_dir,
+                written: Vec::new(),
+                sessions_created: 0,
+            })
+        })
+        .collect()
+}
+
+/// Run every machine's sync at once; returns the number that failed
+fn sync_all(machines: &[Machine], verbose: bool) -> Result<usize> {
+    let outputs: Vec<Result<std::process::Output>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = machines
+            .iter()
+            .map(|machine| scope.spawn(move || machine.sync()))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("sync thread panicked"))
+            .collect()
+    });
+
+    let mut failed = 0;
+    for (machine, output) in machines.iter().zip(outputs) {
+        let output = output?;
+        if !output.status.success() {
+            failed += 1;
+            if verbose {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                println!(
+                    "    {} {}: {}",
+                    glyphs::detail().dimmed(),
+                    machine.name,
+                    stderr
+                        .lines()
+                        .find(|l| l.starts_with("Error:"))
+                        .unwrap_or("(no output)")
+                );
+            }
+        }
+    }
+    Ok(failed)
+}
+
+/// Handle the stress command
+pub fn handle_stress(
+    machines: usize,
+    rounds: usize,
+    seed: Option<u64>,
+    keep: bool,
+    verbose: bool,
+) -> Result<()> {
+    if machines < 2 {
+        bail!("The stress test needs at least 2 machines");
+    }
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(1)
+    });
+    let root = std::env::temp_dir().join(format!(
+        "claude-code-sync-stress-{}-{}",
+        seed,
+        std::process::id()
+    ));
+    fs::create_dir_all(&root).with_context(|| format!("Failed to create {}", root.display()))?;
+
+    println!(
+        "{} {} machines, {} rounds, seed {}",
+        "Stress test:".cyan().bold(),
+        machines,
+        rounds,
+        seed
+    );
+    if verbose {
+        println!("  {} {}", glyphs::detail().dimmed(), root.display());
+    }
+
+    let mut rng = Rng::new(seed);
+    let mut fleet = set_up(&root, machines)?;
+    let mut failed_syncs = 0;
+    for round in 1..=rounds {
+        for machine in &mut fleet {
+            machine.edit(&mut rng)?;
+        }
+        let failed = sync_all(&fleet, verbose)?;
+        failed_syncs += failed;
+        if verbose || failed > 0 {
+            println!(
+                "  Round {:>3}: {} syncs, {} failed",
+                round,
+                fleet.len(),
+                failed
+            );
+        }
+    }
+
+    // Two passes in turn: the first collects everything on the remote, the
+    // second brings it to every machine
+    for _ in 0..2 {
+        for machine in &fleet {
+            let output = machine.sync()?;
+            if !output.status.success() {
+                bail!(
+                    "Final sync on {} failed (scratch directory kept at {}):\n{}",
+                    machine.name,
+                    root.display(),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+        }
+    }
+
+    let written: BTreeSet<String> = fleet
+        .iter()
+        .flat_map(|m| m.written.iter().cloned())
+        .collect();
+    let mut lost = 0;
+    for machine in &fleet {
+        let present = machine.local_uuids();
+        let missing = written.difference(&present).count();
+        if missing > 0 {
+            lost += 1;
+            println!(
+                "  {} {} is missing {} of {} entries",
+                glyphs::fail().red(),
+                machine.name,
+                missing,
+                written.len()
+            );
+        }
+    }
+
+    let sessions: usize = fleet.iter().map(|m| m.sessions_created).sum();
+    if lost > 0 {
+        bail!(
+            "Entries were lost on {} of {} machines (seed {}, scratch directory kept at {})",
+            lost,
+            fleet.len(),
+            seed,
+            root.display()
+        );
+    }
+
+    println!(
+        "{} No entries lost: {} entries in {} sessions present on all {} machines ({} concurrent syncs failed and were retried)",
+        glyphs::ok().green(),
+        written.len(),
+        sessions,
+        fleet.len(),
+        failed_syncs
+    );
+    if keep {
+        println!(
+            "  {} Scratch directory kept at {}",
+            glyphs::info().cyan(),
+            root.display()
+        );
+    } else {
+        fs::remove_dir_all(&root)
+            .with_context(|| format!("Failed to remove {}", root.display()))?;
+    }
+    Ok(())
+}
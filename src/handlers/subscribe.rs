@@ -0,0 +1,121 @@
+//! Subscribe command handlers
+//!
+//! Handles registering, removing, listing, and refreshing read-only
+//! subscriptions to other sync repositories.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::path::PathBuf;
+
+use crate::glyphs;
+use crate::subscriptions::{Subscription, Subscriptions};
+use crate::sync::claude_projects_dir;
+use crate::trash::Trash;
+
+/// The `~/.claude` directory that holds the projects directory
+fn claude_base_dir() -> Result<PathBuf> {
+    let projects = claude_projects_dir()?;
+    Ok(projects.parent().unwrap_or(&projects).to_path_buf())
+}
+
+/// Handle subscribe add command
+pub fn handle_subscribe_add(name: &str, url: &str, subdirectory: &str) -> Result<()> {
+    let mut subs = Subscriptions::load()?;
+    let subscription = Subscription {
+        name: name.to_string(),
+        url: url.to_string(),
+        subdirectory: subdirectory.to_string(),
+    };
+    subs.add(subscription.clone())?;
+    subs.save()?;
+    println!("{}", format!("Subscribed to '{name}' ({url})").green());
+
+    let base = claude_base_dir()?;
+    let written = subscription
+        .refresh(&base)
+        .context("Subscription saved, but the initial pull failed")?;
+    println!(
+        "  {} Pulled {} sessions into {}",
+        glyphs::ok().green(),
+        written,
+        subscription.local_dir(&base).display()
+    );
+
+    Ok(())
+}
+
+/// Handle subscribe remove command
+///
+/// The clone is moved to the trash; sessions already mirrored locally are kept.
+pub fn handle_subscribe_remove(name: &str) -> Result<()> {
+    let mut subs = Subscriptions::load()?;
+    let Some(subscription) = subs.remove(name) else {
+        println!("{}", format!("No subscription named '{name}'").yellow());
+        return Ok(());
+    };
+    subs.save()?;
+
+    let clone_dir = subscription.clone_dir()?;
+    if clone_dir.exists() {
+        Trash::open()?.put(&clone_dir, "subscribe remove")?;
+    }
+
+    println!("{}", format!("Unsubscribed from '{name}'").green());
+    println!(
+        "  {} Mirrored sessions remain in {}",
+        glyphs::info().cyan(),
+        subscription.local_dir(&claude_base_dir()?).display()
+    );
+
+    Ok(())
+}
+
+/// Handle subscribe list command
+pub fn handle_subscribe_list() -> Result<()> {
+    let subs = Subscriptions::load()?;
+    if subs.subscriptions.is_empty() {
+        println!("{}", "No subscriptions.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", "Subscriptions".cyan().bold());
+    for subscription in &subs.subscriptions {
+        println!(
+            "  {} {} {}",
+            subscription.name.bold(),
+            subscription.url,
+            format!("({})", subscription.subdirectory).dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+/// Handle subscribe pull command (one subscription, or all of them)
+pub fn handle_subscribe_pull(name: Option<&str>) -> Result<()> {
+    let subs = Subscriptions::load()?;
+    let selected: Vec<_> = match name {
+        Some(name) => vec![subs
+            .get(name)
+            .with_context(|| format!("No subscription named '{name}'"))?],
+        None => subs.subscriptions.iter().collect(),
+    };
+
+    if selected.is_empty() {
+        println!("{}", "No subscriptions.".yellow());
+        return Ok(());
+    }
+
+    let base = claude_base_dir()?;
+    for subscription in selected {
+        let written = subscription.refresh(&base)?;
+        println!(
+            "  {} {}: {} sessions updated",
+            glyphs::ok().green(),
+            subscription.name,
+            written
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Tag command handlers
+//!
+//! Handles adding, removing, and listing session tags. Tag changes are
+//! committed to the sync repo immediately so they travel with the next push.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::path::PathBuf;
+
+use crate::scm;
+use crate::session_ref::resolve_session_id;
+use crate::sync::SyncState;
+use crate::tags::{normalize_tag, TagIndex, TAG_INDEX_FILE};
+
+/// Handle tag add command
+pub fn handle_tag_add(session_id: &str, tag: &str) -> Result<()> {
+    let Some(tag) = normalize_tag(tag) else {
+        bail!("Tag cannot be empty");
+    };
+    let session_id = &resolve_session_id(session_id)?;
+    let state = SyncState::load()?;
+    let mut index = TagIndex::load(&state.sync_repo_path)?;
+
+    if !index.add(session_id, &tag) {
+        println!("{}", format!("Session {session_id} is already tagged '{tag}'").yellow());
+        return Ok(());
+    }
+
+    index.save(&state.sync_repo_path)?;
+    commit_tags(&state, &format!("Tag {session_id} with {tag}"))?;
+    println!("{}", format!("Tagged {session_id} with '{tag}'").green());
+
+    Ok(())
+}
+
+/// Handle tag remove command
+pub fn handle_tag_remove(session_id: &str, tag: &str) -> Result<()> {
+    let Some(tag) = normalize_tag(tag) else {
+        bail!("Tag cannot be empty");
+    };
+    let session_id = &resolve_session_id(session_id)?;
+    let state = SyncState::load()?;
+    let mut index = TagIndex::load(&state.sync_repo_path)?;
+
+    if !index.remove(session_id, &tag) {
+        println!("{}", format!("Session {session_id} is not tagged '{tag}'").yellow());
+        return Ok(());
+    }
+
+    index.save(&state.sync_repo_path)?;
+    commit_tags(&state, &format!("Untag {session_id} from {tag}"))?;
+    println!("{}", format!("Removed tag '{tag}' from {session_id}").green());
+
+    Ok(())
+}
+
+/// Handle tag list command
+pub fn handle_tag_list(tag: Option<&str>) -> Result<()> {
+    let state = SyncState::load()?;
+    let index = TagIndex::load(&state.sync_repo_path)?;
+
+    let sessions: Vec<_> = index
+        .sessions
+        .keys()
+        .filter(|id| tag.is_none_or(|t| index.has_tag(id, t)))
+        .collect();
+
+    if sessions.is_empty() {
+        println!("{}", "No tagged sessions.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", "Tagged Sessions".cyan().bold());
+    for session_id in sessions {
+        println!(
+            "  {} {}",
+            session_id,
+            index.tags_for(session_id).join(", ").cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Commit the tag index on the current branch of the sync repo
+fn commit_tags(state: &SyncState, message: &str) -> Result<()> {
+    let repo = scm::open(&state.sync_repo_path)?;
+    repo.stage_paths(&[PathBuf::from(TAG_INDEX_FILE)])?;
+    if repo.has_staged_changes()? {
+        repo.commit(message).context("Failed to commit tag index")?;
+    }
+    Ok(())
+}
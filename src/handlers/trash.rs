@@ -0,0 +1,56 @@
+//! Trash command handlers
+//!
+//! Handles listing, restoring, and emptying files moved aside by cleanup
+//! operations.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::glyphs;
+use crate::trash::{Trash, TRASH_RETENTION_DAYS};
+
+/// Handle trash list command
+pub fn handle_trash_list() -> Result<()> {
+    let entries = Trash::open()?.list()?;
+    if entries.is_empty() {
+        println!("{}", "Trash is empty.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", "Trash".cyan().bold());
+    for entry in &entries {
+        println!(
+            "  {} {} {}",
+            entry.id.bold(),
+            entry.original_path.display(),
+            format!("({})", entry.reason).dimmed()
+        );
+    }
+    println!(
+        "\n  {} Entries are removed permanently after {} days",
+        glyphs::info().cyan(),
+        TRASH_RETENTION_DAYS
+    );
+
+    Ok(())
+}
+
+/// Handle trash restore command
+pub fn handle_trash_restore(id: &str) -> Result<()> {
+    let entry = Trash::open()?.restore(id)?;
+    println!(
+        "{}",
+        format!("Restored {}", entry.original_path.display()).green()
+    );
+    Ok(())
+}
+
+/// Handle trash empty command
+pub fn handle_trash_empty() -> Result<()> {
+    let removed = Trash::open()?.empty()?;
+    println!(
+        "{}",
+        format!("Permanently removed {removed} trashed items").green()
+    );
+    Ok(())
+}
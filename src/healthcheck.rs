@@ -0,0 +1,123 @@
+//! Dead man's switch pings after pulls, pushes, and syncs
+//!
+//! With `healthcheck_url` set, a `pull`, `push`, or `sync` that succeeds
+//! requests that URL, as healthchecks.io and compatible services expect, and
+//! one that fails posts its error to `healthcheck_fail_url` (the URL with
+//! `/fail` appended unless set). The service then alerts when a machine
+//! reports a failure or stops reporting at all. A sync that only got as far
+//! as the local repo (remote unreachable, without `--strict`) pings
+//! neither, so an outage that lasts past the service's grace period is
+//! reported without alerting on every brief one.
+//!
+//! Pings go through `curl`, as the tree has no HTTP client. A ping that
+//! fails is logged and never fails the operation.
+
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::filter::FilterConfig;
+use crate::sync::SyncOutcome;
+
+/// Seconds each attempt at a ping may take
+const PING_TIMEOUT_SECS: &str = "10";
+
+/// Most of an error message sent with a failure ping
+const MAX_FAILURE_BYTES: usize = 10_000;
+
+/// URL pinged when an operation fails, given the success URL and the
+/// configured failure URL
+fn fail_url(url: &str, fail_url: Option<&str>) -> String {
+    match fail_url {
+        Some(fail_url) => fail_url.to_string(),
+        None => format!("{}/fail", url.trim_end_matches('/')),
+    }
+}
+
+/// Ping the configured URL for how an operation ended
+pub fn report(result: std::result::Result<SyncOutcome, &anyhow::Error>) {
+    let config = match FilterConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Couldn't read the healthcheck settings: {:#}", e);
+            return;
+        }
+    };
+    let Some(url) = config.healthcheck_url.as_deref() else {
+        return;
+    };
+    let pinged = match result {
+        Ok(SyncOutcome::Complete) => ping(url, None),
+        Ok(SyncOutcome::LocalOnly) => {
+            log::debug!("Remote unreachable; not pinging the healthcheck");
+            return;
+        }
+        Err(e) => {
+            let mut message = format!("{e:#}");
+            if message.len() > MAX_FAILURE_BYTES {
+                let mut end = MAX_FAILURE_BYTES;
+                while !message.is_char_boundary(end) {
+                    end -= 1;
+                }
+                message.truncate(end);
+            }
+            ping(
+                &fail_url(url, config.healthcheck_fail_url.as_deref()),
+                Some(&message),
+            )
+        }
+    };
+    if let Err(e) = pinged {
+        log::warn!("Healthcheck ping failed: {:#}", e);
+    }
+}
+
+/// Request `url`, posting `body` if given
+fn ping(url: &str, body: Option<&str>) -> Result<()> {
+    let mut command = Command::new("curl");
+    command
+        .args(["--fail", "--silent", "--show-error", "--retry", "2"])
+        .args(["--max-time", PING_TIMEOUT_SECS])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    if body.is_some() {
+        command.args(["--data-binary", "@-"]).stdin(Stdio::piped());
+    } else {
+        command.stdin(Stdio::null());
+    }
+    command.arg("--").arg(url);
+
+    let mut child = command
+        .spawn()
+        .context("Failed to run curl; is it installed?")?;
+    if let (Some(body), Some(mut stdin)) = (body, child.stdin.take()) {
+        stdin.write_all(body.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "curl {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    log::debug!("Pinged healthcheck {}", url);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fail_url() {
+        assert_eq!(
+            fail_url("https://hc-ping.com/abc/", None),
+            "https://hc-ping.com/abc/fail"
+        );
+        assert_eq!(
+            fail_url("https://hc-ping.com/abc", Some("https://example.com/down")),
+            "https://example.com/down"
+        );
+    }
+}
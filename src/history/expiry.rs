@@ -0,0 +1,133 @@
+//! Expiring the safety data linked from operation records
+//!
+//! A record points at what its operation left behind for recovery: the
+//! backup of overwritten local files, the undo snapshot, the archived
+//! conflict report, and a retained temp branch. Once the record rotates out
+//! of [`OperationHistory`](super::OperationHistory) nothing refers to that
+//! data anymore, so it is removed along with the record rather than left to
+//! each artifact's own count or age limit.
+
+use std::fs;
+use std::path::Path;
+
+use super::record::OperationRecord;
+use crate::scm::Scm;
+
+/// Remove a backup or snapshot file or directory, if it still exists
+fn remove_path(path: &Path) -> bool {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.exists() {
+        fs::remove_file(path)
+    } else {
+        return false;
+    };
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("Failed to remove {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// Remove the artifacts linked from expired operation records (best effort)
+///
+/// Temp branches are deleted from `repo`, and from `origin` when
+/// `has_remote` is set; without a repo they are left to the temp branch
+/// retention window. Returns the number of artifacts removed.
+pub fn expire_artifacts(
+    records: &[OperationRecord],
+    repo: Option<&dyn Scm>,
+    has_remote: bool,
+    conflict_reports_dir: &Path,
+) -> usize {
+    let mut removed = 0;
+    for record in records {
+        for path in [&record.backup_path, &record.snapshot_path]
+            .into_iter()
+            .flatten()
+        {
+            if remove_path(path) {
+                log::debug!("Removed {} of expired operation", path.display());
+                removed += 1;
+            }
+        }
+
+        if let Some(id) = &record.conflict_report {
+            match crate::report::remove_archived_report(conflict_reports_dir, id) {
+                Ok(true) => removed += 1,
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to remove conflict report {}: {}", id, e),
+            }
+        }
+
+        if let (Some(branch), Some(repo)) = (&record.temp_branch, repo) {
+            if !repo.branch_exists(branch) {
+                continue;
+            }
+            if has_remote {
+                if let Err(e) = repo.delete_remote_branch("origin", branch) {
+                    log::debug!("Failed to delete remote branch {}: {}", branch, e);
+                }
+            }
+            match repo.delete_branch(branch) {
+                Ok(()) => removed += 1,
+                Err(e) => log::warn!("Failed to delete temp branch {}: {}", branch, e),
+            }
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::OperationType;
+    use crate::report::{archive_report, ReportIndex};
+    use crate::scm::MockScm;
+
+    #[test]
+    fn test_expire_artifacts_removes_everything_linked() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let backup = temp.path().join("backups/20250101-000000");
+        fs::create_dir_all(&backup).unwrap();
+        fs::write(backup.join("s.jsonl"), "{}\n").unwrap();
+        let reports_dir = temp.path().join("conflict-reports");
+        let report = crate::report::ConflictReport::from_conflicts(&[]);
+        let report_id = archive_report(&reports_dir, &report, "pull", 0, 0).unwrap();
+        let kept_id = archive_report(&reports_dir, &report, "pull", 0, 0).unwrap();
+
+        let branch = "sync-local-laptop-20250101-000000-abc123";
+        let repo = MockScm::new();
+        repo.write_file("README.md", "# sync\n");
+        repo.stage_all().unwrap();
+        repo.commit("Initial commit").unwrap();
+        repo.add_remote("origin", "mock://origin").unwrap();
+        repo.create_branch(branch).unwrap();
+        repo.push("origin", branch).unwrap();
+
+        let mut record = OperationRecord::new(OperationType::Pull, None, Vec::new());
+        record.backup_path = Some(backup.clone());
+        record.conflict_report = Some(report_id.clone());
+        record.temp_branch = Some(branch.to_string());
+
+        let removed = expire_artifacts(
+            std::slice::from_ref(&record),
+            Some(&repo),
+            true,
+            &reports_dir,
+        );
+        assert_eq!(removed, 3);
+        assert!(!backup.exists());
+        assert!(!reports_dir.join(format!("{report_id}.json")).exists());
+        let index = ReportIndex::load(&reports_dir).unwrap();
+        assert_eq!(index.reports.len(), 1);
+        assert_eq!(index.reports[0].id, kept_id);
+        assert!(!repo.branch_exists(branch));
+        assert!(repo.remote_branches("origin").is_empty());
+
+        // Expiring again finds nothing left
+        assert_eq!(expire_artifacts(&[record], Some(&repo), true, &reports_dir), 0);
+    }
+}
@@ -4,13 +4,15 @@
 //! conversations. Maintains a rolling history of recent operations with automatic
 //! rotation. Each operation record includes a snapshot path for undo functionality.
 
+mod expiry;
 mod record;
 mod storage;
 mod summary;
 mod types;
 
 // Re-export public types and functions
+pub use expiry::expire_artifacts;
 pub use record::OperationRecord;
 pub use storage::OperationHistory;
 pub use summary::ConversationSummary;
-pub use types::{OperationType, SyncOperation};
+pub use types::{OperationType, PhaseTiming, SkipKind, SkippedSession, SyncOperation};
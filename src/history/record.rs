@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 use super::summary::ConversationSummary;
-use super::types::{OperationType, SyncOperation};
+use super::types::{OperationType, PhaseTiming, SkippedSession, SyncOperation};
 
 /// Record of a single sync operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +35,45 @@ pub struct OperationRecord {
     /// This is much more efficient than storing file contents.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub commit_hash: Option<String>,
+
+    /// Directory holding copies of local session files this operation
+    /// overwrote (see `backups`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_path: Option<PathBuf>,
+
+    /// Time spent in each phase of the operation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub phase_timings: Vec<PhaseTiming>,
+
+    /// ID of the conflict report this operation generated (see
+    /// `conflicts history`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict_report: Option<String>,
+
+    /// Sessions found diverged between .claude and the sync repo after the
+    /// operation (only checked when `verify_after_pull` is enabled)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inconsistent_sessions: Vec<String>,
+
+    /// Temp branch the operation left in place (a pull's branch kept for the
+    /// retention window or for inspection)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_branch: Option<String>,
+
+    /// Session files the operation left out: excluded by the filters, too
+    /// large, or unparsable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_sessions: Vec<SkippedSession>,
+
+    /// Network steps that failed without failing the operation (a fetch,
+    /// a temp branch or mirror push, a subscription refresh)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub network_failures: Vec<String>,
+
+    /// The remote couldn't be fetched from or pulled, so the operation only
+    /// covered what this machine already had
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub remote_unreachable: bool,
 }
 
 impl OperationRecord {
@@ -51,9 +90,35 @@ impl OperationRecord {
             affected_conversations,
             snapshot_path: None,
             commit_hash: None,
+            backup_path: None,
+            phase_timings: Vec::new(),
+            conflict_report: None,
+            inconsistent_sessions: Vec::new(),
+            temp_branch: None,
+            skipped_sessions: Vec::new(),
+            network_failures: Vec::new(),
+            remote_unreachable: false,
         }
     }
 
+    /// Why `--strict` fails this operation: sessions skipped for being too
+    /// large or unparsable, and network failures; empty if it doesn't
+    pub fn strict_failures(&self) -> Vec<String> {
+        let mut failures: Vec<String> = self
+            .skipped_sessions
+            .iter()
+            .filter(|skip| skip.kind.is_failure())
+            .map(|skip| format!("{}: {}", skip.path, skip.reason))
+            .collect();
+        failures.extend(self.network_failures.iter().cloned());
+        failures
+    }
+
+    /// Whether post-operation verification found diverged sessions
+    pub fn is_inconsistent(&self) -> bool {
+        !self.inconsistent_sessions.is_empty()
+    }
+
     /// Get a summary string for this operation
     ///
     /// This method will be used in future CLI commands to display
@@ -104,19 +104,24 @@ impl OperationHistory {
 
     /// Add a new operation record to history
     /// Automatically rotates older entries if history exceeds MAX_HISTORY_SIZE
-    pub fn add_operation(&mut self, record: OperationRecord) -> Result<()> {
+    ///
+    /// Returns the records rotated out, whose artifacts should be removed
+    /// with [`expire_artifacts`](super::expire_artifacts).
+    pub fn add_operation(&mut self, record: OperationRecord) -> Result<Vec<OperationRecord>> {
         // Insert at the beginning (most recent first)
         self.operations.insert(0, record);
 
         // Rotate if we exceed the maximum size
-        if self.operations.len() > MAX_HISTORY_SIZE {
-            self.operations.truncate(MAX_HISTORY_SIZE);
-        }
+        let expired = if self.operations.len() > MAX_HISTORY_SIZE {
+            self.operations.split_off(MAX_HISTORY_SIZE)
+        } else {
+            Vec::new()
+        };
 
         // Persist to disk
         self.save()?;
 
-        Ok(())
+        Ok(expired)
     }
 
     /// Get the most recent operation record
@@ -8,6 +8,8 @@ pub enum OperationType {
     Pull,
     /// Push operation: syncing from local to remote
     Push,
+    /// Sync operation: a pull followed by a push, recorded once
+    Sync,
 }
 
 impl OperationType {
@@ -16,10 +18,53 @@ impl OperationType {
         match self {
             OperationType::Pull => "pull",
             OperationType::Push => "push",
+            OperationType::Sync => "sync",
         }
     }
 }
 
+/// Wall-clock time spent in one phase of an operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    /// Phase name (e.g. "conflict detection")
+    pub phase: String,
+    /// Elapsed time in milliseconds
+    pub millis: u64,
+}
+
+/// A session file an operation left out, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedSession {
+    /// Path of the session file
+    pub path: String,
+    /// Why it was skipped (e.g. "larger than max_file_size (...)")
+    pub reason: String,
+    /// What kind of skip this was
+    #[serde(default)]
+    pub kind: SkipKind,
+}
+
+/// Why a session file was left out, broadly
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkipKind {
+    /// Excluded by the configured patterns or age filter
+    #[default]
+    Filtered,
+    /// Over `max_file_size`, or skipped by a `large_file_bands` band
+    TooLarge,
+    /// Couldn't be read or parsed
+    Unparsable,
+}
+
+impl SkipKind {
+    /// Whether `--strict` treats this skip as a failure; filtered sessions
+    /// are left out on purpose
+    pub fn is_failure(self) -> bool {
+        self != SkipKind::Filtered
+    }
+}
+
 /// Type of operation performed on a specific conversation during sync
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -54,6 +99,7 @@ mod tests {
     fn test_operation_type_as_str() {
         assert_eq!(OperationType::Pull.as_str(), "pull");
         assert_eq!(OperationType::Push.as_str(), "push");
+        assert_eq!(OperationType::Sync.as_str(), "sync");
     }
 
     #[test]
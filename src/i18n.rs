@@ -0,0 +1,242 @@
+//! Localized CLI messages
+//!
+//! Text printed for people is looked up by message ID in a small catalog
+//! per locale and filled in with named `{arguments}`. The locale is the
+//! `locale` setting in `config.toml`: `en` (the default), another catalog's
+//! language, or `auto` to follow `LC_ALL`, `LC_MESSAGES`, or `LANG`. A
+//! message missing from a catalog falls back to English.
+//!
+//! Only human-readable text goes through the catalog. JSON output, the
+//! operation history, reports, and log lines keep their English field
+//! names and values, so scripts read the same output in every locale.
+//!
+//! To add a language, add a `Locale` variant and a catalog with the same
+//! IDs as `EN`; `test_catalogs_match` checks they agree.
+
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use crate::filter::FilterConfig;
+
+/// A language messages can be shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    /// Every locale with a catalog
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    /// Parse a language code such as `de`, `de_DE.UTF-8`, or `de-AT`
+    pub fn parse(value: &str) -> Option<Self> {
+        let language = value
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+        }
+    }
+
+    /// The locale the environment asks for, or English for one without a
+    /// catalog
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::parse(&value))
+            .unwrap_or_default()
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::De => DE,
+        }
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Use the configured locale for the rest of the process
+pub fn configure() {
+    // A config that can't be read is reported by the command itself
+    let locale = FilterConfig::load()
+        .ok()
+        .and_then(|config| config.locale().ok())
+        .unwrap_or_default();
+    let _ = LOCALE.set(locale);
+}
+
+/// The locale messages are shown in
+pub fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+/// Message `id` in the configured locale with `args` filled in
+pub fn tr(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    tr_in(locale(), id, args)
+}
+
+/// `{n}` of the `<id>.one` or `<id>.other` message, by `n`
+pub fn count(id: &str, n: usize) -> String {
+    count_in(locale(), id, n)
+}
+
+fn lookup(locale: Locale, id: &str) -> &'static str {
+    [locale, Locale::En]
+        .iter()
+        .find_map(|l| {
+            l.catalog()
+                .iter()
+                .find(|(key, _)| *key == id)
+                .map(|(_, text)| *text)
+        })
+        .unwrap_or_else(|| panic!("No message with ID '{id}'"))
+}
+
+fn tr_in(locale: Locale, id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut message = lookup(locale, id).to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    message
+}
+
+fn count_in(locale: Locale, id: &str, n: usize) -> String {
+    // English and German both use the singular for exactly one
+    let form = if n == 1 { "one" } else { "other" };
+    tr_in(locale, &format!("{id}.{form}"), &[("n", &n)])
+}
+
+const EN: &[(&str, &str)] = &[
+    ("count.errors.one", "{n} error"),
+    ("count.errors.other", "{n} errors"),
+    ("count.forks.one", "{n} fork"),
+    ("count.forks.other", "{n} forks"),
+    (
+        "pull.summary_line",
+        "pull: +{added} added, {modified} modified, {forks}, {errors}",
+    ),
+    ("pull.local_only", ", local only (remote unreachable)"),
+    ("pull.heading", "=== Pull Summary ==="),
+    (
+        "pull.counts",
+        "{added} Added    {modified} Modified    {forks} Forked    {unchanged} Unchanged",
+    ),
+    (
+        "pull.kept_local_newer",
+        "(Kept {n} sessions where local was ahead of remote)",
+    ),
+    ("pull.complete", "Pull complete!"),
+    (
+        "pull.complete_local_only",
+        "Pull complete (LOCAL ONLY — remote unreachable)",
+    ),
+    ("push.summary_line", "push: {committed}, {pushed}, {errors}"),
+    ("push.nothing_line", "push: nothing to push"),
+    ("push.committed", "committed"),
+    ("push.no_new_changes", "no new changes"),
+    ("push.pushed_to", "pushed to origin/{branch}"),
+    ("push.uploaded", "uploaded"),
+    ("push.not_pushed", "not pushed (no remote)"),
+    ("push.no_changes", "No changes to push"),
+    ("push.complete", "Push complete!"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("count.errors.one", "{n} Fehler"),
+    ("count.errors.other", "{n} Fehler"),
+    ("count.forks.one", "{n} Fork"),
+    ("count.forks.other", "{n} Forks"),
+    (
+        "pull.summary_line",
+        "pull: +{added} hinzugefügt, {modified} geändert, {forks}, {errors}",
+    ),
+    ("pull.local_only", ", nur lokal (Remote nicht erreichbar)"),
+    ("pull.heading", "=== Pull-Zusammenfassung ==="),
+    (
+        "pull.counts",
+        "{added} Hinzugefügt    {modified} Geändert    {forks} Geforkt    {unchanged} Unverändert",
+    ),
+    (
+        "pull.kept_local_newer",
+        "({n} Sitzungen behalten, deren lokale Version neuer war)",
+    ),
+    ("pull.complete", "Pull abgeschlossen!"),
+    (
+        "pull.complete_local_only",
+        "Pull abgeschlossen (NUR LOKAL — Remote nicht erreichbar)",
+    ),
+    ("push.summary_line", "push: {committed}, {pushed}, {errors}"),
+    ("push.nothing_line", "push: nichts zu pushen"),
+    ("push.committed", "committet"),
+    ("push.no_new_changes", "keine neuen Änderungen"),
+    ("push.pushed_to", "nach origin/{branch} gepusht"),
+    ("push.uploaded", "hochgeladen"),
+    ("push.not_pushed", "nicht gepusht (kein Remote)"),
+    ("push.no_changes", "Keine Änderungen zu pushen"),
+    ("push.complete", "Push abgeschlossen!"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_catalogs_match() {
+        let ids = |locale: Locale| -> BTreeSet<&str> {
+            locale.catalog().iter().map(|(id, _)| *id).collect()
+        };
+        for locale in Locale::ALL {
+            assert_eq!(ids(locale), ids(Locale::En), "{}", locale.as_str());
+        }
+    }
+
+    #[test]
+    fn test_tr() {
+        let summary = |locale| {
+            tr_in(
+                locale,
+                "pull.summary_line",
+                &[
+                    ("added", &3),
+                    ("modified", &2),
+                    ("forks", &count_in(locale, "count.forks", 1)),
+                    ("errors", &count_in(locale, "count.errors", 0)),
+                ],
+            )
+        };
+        assert_eq!(
+            summary(Locale::En),
+            "pull: +3 added, 2 modified, 1 fork, 0 errors"
+        );
+        assert_eq!(
+            summary(Locale::De),
+            "pull: +3 hinzugefügt, 2 geändert, 1 Fork, 0 Fehler"
+        );
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Locale::parse("de_DE.UTF-8"), Some(Locale::De));
+        assert_eq!(Locale::parse("en-GB"), Some(Locale::En));
+        assert_eq!(Locale::parse("C"), Some(Locale::En));
+        assert_eq!(Locale::parse("fr_FR"), None);
+    }
+}
@@ -4,7 +4,9 @@ use inquire::{Confirm, Select};
 use std::path::{Path, PathBuf};
 
 use crate::conflict::{Conflict, ConflictResolution};
+use crate::glyphs;
 use crate::parser::ConversationSession;
+use crate::text::short_id;
 
 /// Resolution action chosen by the user
 #[derive(Debug, Clone)]
@@ -102,7 +104,7 @@ fn display_conflict_details(conflict: &Conflict) {
     if let Some(ts) = &conflict.local_timestamp {
         println!("  Last updated: {}", ts.dimmed());
     }
-    println!("  Content hash: {}", &conflict.local_hash[..16].dimmed());
+    println!("  Content hash: {}", short_id(&conflict.local_hash, 16).dimmed());
 
     println!(
         "\n{} {}",
@@ -116,26 +118,26 @@ fn display_conflict_details(conflict: &Conflict) {
     if let Some(ts) = &conflict.remote_timestamp {
         println!("  Last updated: {}", ts.dimmed());
     }
-    println!("  Content hash: {}", &conflict.remote_hash[..16].dimmed());
+    println!("  Content hash: {}", short_id(&conflict.remote_hash, 16).dimmed());
 
     // Highlight the differences
     let msg_diff = conflict.remote_message_count as i32 - conflict.local_message_count as i32;
     if msg_diff > 0 {
         println!(
             "\n{} Remote has {} more messages",
-            "→".yellow(),
+            glyphs::arrow().yellow(),
             msg_diff.to_string().yellow().bold()
         );
     } else if msg_diff < 0 {
         println!(
             "\n{} Local has {} more messages",
-            "→".green(),
+            glyphs::arrow().green(),
             (-msg_diff).to_string().green().bold()
         );
     } else {
         println!(
             "\n{} Both have the same number of messages, but content differs",
-            "→".cyan()
+            glyphs::arrow().cyan()
         );
     }
 
@@ -230,7 +232,7 @@ pub fn resolve_conflicts_interactive_with_sessions(
                                 {
                                     println!(
                                         "  {} Smart merged ({} local + {} remote = {} total, {} branches)",
-                                        "✓".green(),
+                                        glyphs::ok().green(),
                                         stats.local_messages,
                                         stats.remote_messages,
                                         stats.merged_messages,
@@ -240,32 +242,32 @@ pub fn resolve_conflicts_interactive_with_sessions(
                                 result.smart_merge.push(conflict.clone());
                             }
                             Err(e) => {
-                                eprintln!("  {} Smart merge failed: {}", "✗".red(), e);
+                                eprintln!("  {} Smart merge failed: {}", glyphs::fail().red(), e);
                                 eprintln!("  Please choose another resolution method...");
                                 // Don't add to result, user will be prompted again
                                 continue;
                             }
                         }
                     } else {
-                        eprintln!("  {} Cannot find local or remote session", "✗".red());
+                        eprintln!("  {} Cannot find local or remote session", glyphs::fail().red());
                         eprintln!("  Please choose another resolution method...");
                         continue;
                     }
                 } else {
-                    eprintln!("  {} Session maps not provided", "✗".red());
+                    eprintln!("  {} Session maps not provided", glyphs::fail().red());
                     eprintln!("  Please choose another resolution method...");
                     continue;
                 }
             }
             ResolutionAction::KeepLocal => {
-                println!("  {} Keeping local version", "✓".green());
+                println!("  {} Keeping local version", glyphs::ok().green());
                 conflict.resolution = ConflictResolution::KeepLocal;
                 result.keep_local.push(conflict.clone());
             }
             ResolutionAction::KeepRemote => {
                 println!(
                     "  {} Keeping remote version (will overwrite local)",
-                    "✓".yellow()
+                    glyphs::ok().yellow()
                 );
                 conflict.resolution = ConflictResolution::KeepRemote;
                 result.keep_remote.push(conflict.clone());
@@ -273,7 +275,7 @@ pub fn resolve_conflicts_interactive_with_sessions(
             ResolutionAction::KeepBoth => {
                 println!(
                     "  {} Keeping both versions (remote will be saved with conflict suffix)",
-                    "✓".cyan()
+                    glyphs::ok().cyan()
                 );
                 // Keep both is handled later with proper renaming
                 result.keep_both.push(conflict.clone());
@@ -335,6 +337,8 @@ pub fn resolve_conflicts_interactive(conflicts: &mut [Conflict]) -> Result<Resol
 /// * `remote_sessions` - All remote sessions (to find the ones we need)
 /// * `claude_dir` - The Claude projects directory
 /// * `_remote_projects_dir` - The remote sync repository projects directory (unused)
+/// * `preserve_raw` - Write entries byte-identical to their original lines when available
+/// * `backup_dir` - Where local files are copied before being overwritten
 ///
 /// # Returns
 /// List of (original_path, renamed_path) tuples for conflicts kept as both
@@ -343,6 +347,8 @@ pub fn apply_resolutions(
     remote_sessions: &[ConversationSession],
     claude_dir: &Path,
     _remote_projects_dir: &Path,
+    preserve_raw: bool,
+    backup_dir: &Path,
 ) -> Result<Vec<(PathBuf, PathBuf)>> {
     let mut renames = Vec::new();
 
@@ -359,9 +365,25 @@ pub fn apply_resolutions(
                 file_path: conflict.local_file.to_string_lossy().to_string(),
             };
 
+            // A merge must never drop local entries
+            let relative = conflict
+                .local_file
+                .strip_prefix(claude_dir)
+                .unwrap_or(&conflict.local_file);
+            if crate::quarantine::quarantine_if_shrinking(
+                &conflict.local_file,
+                relative,
+                merged_session.entries.len(),
+            )?
+            .is_some()
+            {
+                continue;
+            }
+            crate::backups::backup_file(backup_dir, &conflict.local_file, relative)?;
+
             // Write to local file
             merged_session
-                .write_to_file(&conflict.local_file)
+                .write_to_file_with(&conflict.local_file, preserve_raw)
                 .with_context(|| {
                     format!(
                         "Failed to write smart merged file: {}",
@@ -371,7 +393,7 @@ pub fn apply_resolutions(
 
             println!(
                 "  {} Wrote smart merged conversation: {}",
-                "✓".cyan(),
+                glyphs::ok().cyan(),
                 conflict.local_file.display()
             );
         }
@@ -384,9 +406,15 @@ pub fn apply_resolutions(
             .iter()
             .find(|s| s.session_id == conflict.session_id)
         {
+            let relative = conflict
+                .local_file
+                .strip_prefix(claude_dir)
+                .unwrap_or(&conflict.local_file);
+            crate::backups::backup_file(backup_dir, &conflict.local_file, relative)?;
+
             // Write remote session to local path (overwrite)
             remote_session
-                .write_to_file(&conflict.local_file)
+                .write_to_file_with(&conflict.local_file, preserve_raw)
                 .with_context(|| {
                     format!(
                         "Failed to overwrite local file with remote: {}",
@@ -396,7 +424,7 @@ pub fn apply_resolutions(
 
             println!(
                 "  {} Overwrote local with remote: {}",
-                "✓".yellow(),
+                glyphs::ok().yellow(),
                 conflict.local_file.display()
             );
         }
@@ -418,7 +446,7 @@ pub fn apply_resolutions(
             .find(|s| s.session_id == conflict.session_id)
         {
             remote_session
-                .write_to_file(&renamed_path)
+                .write_to_file_with(&renamed_path, preserve_raw)
                 .with_context(|| {
                     format!(
                         "Failed to write remote conflict version: {}",
@@ -431,7 +459,7 @@ pub fn apply_resolutions(
                 .unwrap_or(&renamed_path);
             println!(
                 "  {} Saved remote as: {}",
-                "✓".cyan(),
+                glyphs::ok().cyan(),
                 relative_renamed.display()
             );
 
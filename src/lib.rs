@@ -29,15 +29,102 @@
 //! - Operation tracking ([`history`])
 //! - User interface and reporting ([`onboarding`], [`report`], [`logger`])
 //! - Core synchronization logic ([`sync`])
+//!
+//! ## Comparing Sessions
+//!
+//! The comparison sync relies on is part of the public API, so tools built on
+//! this crate (backup checks, for example) reach the same verdicts instead of
+//! reimplementing prefix checks:
+//!
+//! - [`analyze_session_relationship`] and [`SessionRelationship`]: whether two
+//!   copies of a session are identical, one extends the other, entries were
+//!   edited in place, or both sides added entries
+//! - [`ConversationSession::content_hash`]: the stable hash behind `Identical`
+//! - [`parser::canonical_json`] and [`parser::make_content_key`]: how single
+//!   entries are compared and deduplicated
+//! - [`manifest::content_hash`]: the file hash in the sync repo's checksum
+//!   manifest
+//!
+//! ```no_run
+//! use claude_code_sync::{analyze_session_relationship, ConversationSession, SessionRelationship};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let backup = ConversationSession::from_file("backup/s1.jsonl")?;
+//! let current = ConversationSession::from_file("/home/me/.claude/projects/-home-me-app/s1.jsonl")?;
+//! match analyze_session_relationship(&backup, &current) {
+//!     SessionRelationship::Identical | SessionRelationship::LocalIsPrefix => {}
+//!     other => println!("backup of {} is stale: {other:?}", current.session_id),
+//! }
+//! # Ok(())
+//! # }
+//! ```
 
 /// Verbosity level for command output
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Levels are ordered, so `verbosity >= VerbosityLevel::Normal` means
+/// "normal or more detailed".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum VerbosityLevel {
-    Quiet,   // Minimal output
+    Quiet,   // No output except errors
+    Summary, // One summary line per operation (for cron)
     Normal,  // Standard output
     Verbose, // Detailed output
 }
 
+impl VerbosityLevel {
+    /// Parse a level name from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "quiet" => Some(VerbosityLevel::Quiet),
+            "summary" => Some(VerbosityLevel::Summary),
+            "normal" => Some(VerbosityLevel::Normal),
+            "verbose" => Some(VerbosityLevel::Verbose),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerbosityLevel::Quiet => "quiet",
+            VerbosityLevel::Summary => "summary",
+            VerbosityLevel::Normal => "normal",
+            VerbosityLevel::Verbose => "verbose",
+        }
+    }
+}
+
+/// Terminal color control.
+///
+/// Disables colored output for `--no-color`, the `color` setting, a
+/// non-empty `NO_COLOR` environment variable, or when stdout is not a
+/// terminal.
+pub mod color;
+
+/// Status glyphs in terminal output.
+///
+/// Marks results with ✓, ✗, ℹ, and similar glyphs, or with plain ASCII
+/// markers for `--ascii` and the `ascii` setting.
+pub mod glyphs;
+
+/// Localized CLI messages.
+///
+/// Looks up human-readable summaries in a per-locale message catalog, while
+/// JSON output and history records stay in English.
+pub mod i18n;
+
+/// Compaction of large payloads in old sessions before they are synced.
+///
+/// Replaces oversized tool outputs and file snapshots with small placeholders
+/// while keeping entry identity (UUIDs, parents, timestamps) intact, optionally
+/// archiving the original session file locally.
+pub mod compaction;
+
+/// PII scrubbing of sessions before they are synced.
+///
+/// Replaces email addresses, phone numbers, IP addresses, and user-defined
+/// patterns in the sync repo copy of each session, counting substitutions.
+pub mod scrubbing;
+
 /// Platform-agnostic configuration directory management for claude-code-sync.
 ///
 /// Provides utilities for locating and managing configuration files and directories
@@ -115,6 +202,21 @@ pub mod onboarding;
 /// file snapshots, etc.) with metadata like timestamps, UUIDs, and session IDs.
 pub mod parser;
 
+/// Paging of long listings through `$PAGER` on interactive terminals.
+pub mod pager;
+
+/// Backups of local session files taken before a pull overwrites them.
+///
+/// Each pull gets one timestamped directory under the config dir; old
+/// directories are pruned automatically.
+pub mod backups;
+
+/// Quarantine for local sessions that a sync would shrink.
+///
+/// Whole-file writes that would leave a local session with fewer entries are
+/// refused; the current file is preserved under the config dir instead.
+pub mod quarantine;
+
 /// Conflict report generation and formatting.
 ///
 /// Generates detailed reports of sync conflicts in multiple formats (JSON, Markdown, console).
@@ -122,6 +224,191 @@ pub mod parser;
 /// and resolution strategies applied during the last sync operation.
 pub mod report;
 
+/// Schema drift detection for session files.
+///
+/// Scans conversation entries for entry types and top-level fields that are not
+/// part of the known Claude Code schema, so format changes are noticed instead
+/// of silently passing through the parser's catch-all field.
+pub mod schema;
+
+/// Read-only subscriptions to other people's sync repositories.
+///
+/// Subscribed repos are cloned into the config directory and their sessions
+/// mirrored into `~/.claude/projects-shared/`, never pushed back.
+pub mod subscriptions;
+
+/// Resolution of session ID arguments.
+///
+/// Accepts full IDs, unique prefixes, or fuzzy patterns matched against
+/// known sessions and their prompts, prompting when several match.
+pub mod session_ref;
+
+/// Conflict resolutions recorded in the sync repo.
+///
+/// Decisions made interactively on one machine are stored in
+/// `resolutions.json` and replayed when another machine hits the same fork.
+pub mod resolutions;
+
+/// Session tags stored in a sidecar index in the sync repo.
+///
+/// Tags are hierarchical labels (e.g. `work/infra`) used to organize sessions
+/// independently of project paths and to include or exclude them from sync.
+pub mod tags;
+
+/// Tombstones for deleted sessions stored in the sync repo.
+///
+/// Records which sessions were deleted and when, so pull removes them
+/// everywhere instead of copying them back.
+pub mod tombstones;
+
+/// Purge index for history rewrites.
+///
+/// Records sessions purged from the sync repo's history, so other clones
+/// reset to the rewritten history instead of merging the old one back.
+pub mod purge;
+
+/// Claude Code project directory names.
+///
+/// Encodes working directories into project directory names the way Claude
+/// Code does, and maps names back to real paths.
+pub mod paths;
+
+/// Crash-safe reading and writing of small state files.
+///
+/// Writes through a temporary file and rename, keeps a backup of the last
+/// good version, and restores it when the file turns out to be corrupt.
+pub mod state_file;
+
+/// Splitting of oversized sessions into continuation files.
+///
+/// Keeps session files in the sync repo under a maximum entry count by
+/// moving the tail into `<name>.partNN.jsonl` files, and reassembles the
+/// parts into whole sessions on discovery.
+pub mod parts;
+
+/// Throwaway sync environments for integration tests.
+///
+/// Builds a temporary home, config directory, and sync repository with
+/// sessions and an optional bare remote (`test_support` feature).
+#[cfg(feature = "test_support")]
+pub mod test_support;
+
+/// Unicode-safe width measurement and truncation of user-facing text.
+///
+/// Truncates by grapheme cluster and terminal width, so emoji-heavy session
+/// titles are never split or cause a panic.
+pub mod text;
+
+/// Width-aware rendering of summary tables.
+///
+/// Aligns columns by terminal cell width (so wide Unicode names line up),
+/// truncates long cells with an ellipsis, and renders the same rows as JSON
+/// or CSV for `--format`.
+pub mod table;
+
+/// Checksum manifest of the sync repo.
+///
+/// Hashes every file in the sync repo on push and checks fetched files
+/// against those hashes on pull and in `verify-sync --manifest`.
+pub mod manifest;
+
+/// Per-machine pull filters committed to the sync repo.
+///
+/// Reads `machines/<machine-id>.toml`, which narrows the projects and time
+/// window a machine pulls without touching what it pushes.
+pub mod machines;
+
+/// Secrets stored in the OS keychain.
+///
+/// Keeps tokens and keys in the macOS Keychain, Secret Service, or Windows
+/// Credential Manager instead of config files, and supplies the HTTPS token
+/// git authenticates to remotes with.
+pub mod credentials;
+
+/// Append-only audit log of what pushes sent off this machine.
+///
+/// Records every file and session entry UUID pushed to a remote, with the
+/// commit and time, for `audit list`.
+pub mod audit;
+
+/// Per-entry provenance of a session in the sync repo.
+///
+/// Finds, for every entry UUID a session ever had, the commit that introduced
+/// it and the machine whose pull merged it, for `blame`.
+pub mod blame;
+
+/// Trash area for files removed by cleanup operations.
+///
+/// Items are moved aside with their original path recorded, can be restored,
+/// and are deleted for good after a retention period.
+pub mod trash;
+
+/// Token usage and cost accounting.
+///
+/// Aggregates the API usage metadata carried by assistant entries into totals
+/// per project, model, session, or day, with estimated costs.
+pub mod usage;
+
+/// Chronological activity across all synced sessions.
+///
+/// Interleaves the messages of every session in the sync repo by timestamp,
+/// with project, session, and a preview of each message, for `timeline`.
+pub mod timeline;
+
+/// Message thread graph of a session.
+///
+/// Builds the `parentUuid` tree of a session's entries, with its forks and
+/// missing parents, and renders it as DOT or JSON for `export --graph`.
+pub mod graph;
+
+/// Thread families of forked sessions.
+///
+/// Detects sessions forked from another by their shared UUID prefix and
+/// keeps the fork links in `families.json` in the sync repo.
+pub mod families;
+
+/// Static HTML browser over the synced sessions.
+///
+/// Writes an index by project and date, a page per session, and client-side
+/// search over them for `export --site`.
+pub mod site;
+
+/// Read-only queries over the sessions in the sync repo.
+///
+/// Projects, sessions, and message search, shared by `mcp-serve` and
+/// `serve`.
+pub mod corpus;
+
+/// Model Context Protocol server over the synced history.
+///
+/// Answers MCP requests on stdin/stdout for `mcp-serve`, with tools to list
+/// projects, search messages, and read sessions from the sync repo.
+pub mod mcp;
+
+/// Weekly digest of sync activity.
+///
+/// Sums up a week of operation history by project, with conflicts and
+/// sync repo growth, for `report --weekly`.
+pub mod digest;
+
+/// Dead man's switch pings after pulls, pushes, and syncs.
+///
+/// Requests the configured healthcheck URL on success and posts the error
+/// to its failure URL otherwise.
+pub mod healthcheck;
+
+/// Prometheus metrics for `serve`.
+///
+/// Renders sync age, session and conflict counts, and server counters in
+/// the Prometheus text format for `GET /metrics`.
+pub mod metrics;
+
+/// Local HTTP API over the synced history.
+///
+/// Serves sessions, entries, search, and sync status as JSON for `serve`,
+/// with a token-protected endpoint that runs a sync.
+pub mod server;
+
 /// Core synchronization logic for pushing and pulling conversation history.
 ///
 /// Implements the main sync operations:
@@ -136,3 +423,6 @@ pub mod report;
 /// 4. Merged result is copied to ~/.claude
 /// 5. Temp branch is cleaned up
 pub mod sync;
+
+pub use conflict::{analyze_session_relationship, SessionRelationship};
+pub use parser::ConversationSession;
@@ -59,8 +59,8 @@ impl SyncLock {
     }
 
     fn lock_path() -> Result<PathBuf> {
-        let config_dir = ConfigManager::ensure_config_dir()?;
-        Ok(config_dir.join("sync.lock"))
+        let state_dir = ConfigManager::ensure_state_dir()?;
+        Ok(state_dir.join("sync.lock"))
     }
 }
 
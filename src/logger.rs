@@ -16,6 +16,9 @@ use crate::config::ConfigManager;
 /// - `RUST_LOG=debug` - Debug and above
 /// - `RUST_LOG=trace` - Everything
 ///
+/// Console messages go to stdout, or to stderr with `to_stderr` (for modes
+/// such as `mcp-serve` whose stdout is a protocol stream).
+///
 /// **File logging** always captures all levels and is stored at:
 /// - Linux: ~/.config/claude-code-sync/claude-code-sync.log or $XDG_CONFIG_HOME/claude-code-sync/claude-code-sync.log
 /// - macOS: ~/Library/Application Support/claude-code-sync/claude-code-sync.log
@@ -33,9 +36,9 @@ use crate::config::ConfigManager;
 /// # No console output (file logging continues)
 /// RUST_LOG=off claude-code-sync pull
 /// ```
-pub fn init_logger() -> Result<()> {
-    // Ensure config directory exists
-    ConfigManager::ensure_config_dir()?;
+pub fn init_logger(to_stderr: bool) -> Result<()> {
+    // Ensure state directory exists
+    ConfigManager::ensure_state_dir()?;
 
     // Determine if console logging should be enabled
     // By default, use Info level unless RUST_LOG is set
@@ -56,7 +59,11 @@ pub fn init_logger() -> Result<()> {
             )
         })
         .filter_level(default_level)
-        .target(env_logger::Target::Stdout)
+        .target(if to_stderr {
+            env_logger::Target::Stderr
+        } else {
+            env_logger::Target::Stdout
+        })
         .try_init()
         .ok(); // Ignore error if logger is already initialized
 
@@ -132,7 +139,7 @@ mod tests {
         std::env::set_var("HOME", temp_dir.path());
 
         // Should not panic - may fail if logger already initialized in process
-        let result = init_logger();
+        let result = init_logger(false);
         // Either succeeds or fails with "already initialized" which is fine
         if let Err(e) = &result {
             let err_str = e.to_string();
@@ -160,8 +167,8 @@ mod tests {
         let original_home = std::env::var("HOME").ok();
         std::env::set_var("HOME", temp_dir.path());
 
-        // Ensure config directory exists
-        ConfigManager::ensure_config_dir()?;
+        // Ensure state directory exists
+        ConfigManager::ensure_state_dir()?;
 
         log_to_file("Test log message")?;
 
@@ -189,8 +196,8 @@ mod tests {
         let original_home = std::env::var("HOME").ok();
         std::env::set_var("HOME", temp_dir.path());
 
-        // Ensure config directory exists first
-        ConfigManager::ensure_config_dir()?;
+        // Ensure state directory exists first
+        ConfigManager::ensure_state_dir()?;
 
         // Get the log path after setting env var
         let log_path = ConfigManager::log_file_path()?;
@@ -0,0 +1,137 @@
+//! Per-machine pull filters
+//!
+//! The sync repo can carry `machines/<machine-id>.toml` to narrow what one
+//! machine pulls, so a small dev VM can take only a few projects or the
+//! last few weeks while a workstation takes everything. The files are
+//! managed centrally by committing them to the repo; a machine without one
+//! pulls everything its own config allows. They never limit what a machine
+//! pushes.
+//!
+//! ```toml
+//! include_projects = ["*work*"]
+//! exclude_projects = ["*scratch*"]
+//! newer_than_days = 30
+//! ```
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::filter::glob_match;
+use crate::parser::ConversationSession;
+
+/// Directory of per-machine filters within the sync repo
+pub const MACHINES_DIR: &str = "machines";
+
+/// What one machine pulls from the sync repo
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MachineFilter {
+    /// Only pull projects whose directory name matches one of these globs
+    #[serde(default)]
+    pub include_projects: Vec<String>,
+    /// Never pull projects whose directory name matches one of these globs
+    #[serde(default)]
+    pub exclude_projects: Vec<String>,
+    /// Only pull sessions with an entry from the last N days
+    #[serde(default)]
+    pub newer_than_days: Option<u32>,
+}
+
+impl MachineFilter {
+    /// Path of a machine's filter within a sync repo
+    pub fn path(sync_repo: &Path, machine_id: &str) -> PathBuf {
+        sync_repo
+            .join(MACHINES_DIR)
+            .join(format!("{machine_id}.toml"))
+    }
+
+    /// Load a machine's filter from a sync repo (`None` if it has none)
+    pub fn load(sync_repo: &Path, machine_id: &str) -> Result<Option<Self>> {
+        let path = Self::path(sync_repo, machine_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read machine filter: {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse machine filter: {}", path.display()))
+            .map(Some)
+    }
+
+    /// Whether a session should be pulled, given its path relative to the
+    /// projects directory
+    pub fn passes(&self, session: &ConversationSession, relative_path: &Path) -> bool {
+        let project = relative_path
+            .components()
+            .next()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .unwrap_or_default();
+        if self
+            .exclude_projects
+            .iter()
+            .any(|pattern| glob_match(pattern, &project))
+        {
+            return false;
+        }
+        if !self.include_projects.is_empty()
+            && !self
+                .include_projects
+                .iter()
+                .any(|pattern| glob_match(pattern, &project))
+        {
+            return false;
+        }
+
+        // Sessions without a readable timestamp are kept
+        if let Some(days) = self.newer_than_days {
+            let latest = session
+                .latest_timestamp()
+                .and_then(|ts| DateTime::parse_from_rfc3339(&ts).ok());
+            if let Some(latest) = latest {
+                let age = Utc::now().signed_duration_since(latest);
+                if age > chrono::Duration::days(days as i64) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_at(timestamp: &str) -> ConversationSession {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            temp.path(),
+            format!(
+                "{{\"type\":\"user\",\"sessionId\":\"s1\",\"uuid\":\"u1\",\"timestamp\":\"{timestamp}\"}}\n"
+            ),
+        )
+        .unwrap();
+        ConversationSession::from_file(temp.path()).unwrap()
+    }
+
+    #[test]
+    fn test_machine_filter_projects_and_age() {
+        let filter: MachineFilter = toml::from_str(
+            "include_projects = [\"*work*\"]\nexclude_projects = [\"*scratch*\"]\nnewer_than_days = 30\n",
+        )
+        .unwrap();
+        let recent = session_at(&Utc::now().to_rfc3339());
+        let old = session_at("2020-01-01T00:00:00Z");
+
+        assert!(filter.passes(&recent, Path::new("-home-me-work-api/s1.jsonl")));
+        assert!(!filter.passes(&recent, Path::new("-home-me-personal/s1.jsonl")));
+        assert!(!filter.passes(&recent, Path::new("-home-me-work-scratch/s1.jsonl")));
+        assert!(!filter.passes(&old, Path::new("-home-me-work-api/s1.jsonl")));
+
+        assert!(toml::from_str::<MachineFilter>("include_project = []").is_err());
+    }
+}
@@ -1,17 +1,54 @@
+mod audit;
+mod backups;
+mod blame;
+mod color;
+mod compaction;
 mod config;
 mod conflict;
+mod corpus;
+mod credentials;
+mod digest;
+mod families;
 mod filter;
+mod glyphs;
+mod graph;
 mod handlers;
+mod healthcheck;
 mod history;
+mod i18n;
 mod interactive_conflict;
 mod lock;
 mod logger;
+mod machines;
+mod manifest;
+mod mcp;
 mod merge;
+mod metrics;
 mod onboarding;
+mod pager;
 mod parser;
+mod parts;
+mod paths;
+mod purge;
+mod quarantine;
 mod report;
+mod resolutions;
+mod schema;
 mod scm;
+mod scrubbing;
+mod server;
+mod session_ref;
+mod site;
+mod state_file;
+mod subscriptions;
 mod sync;
+mod table;
+mod tags;
+mod text;
+mod timeline;
+mod tombstones;
+mod trash;
+mod usage;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
@@ -30,9 +67,27 @@ use claude_code_sync::VerbosityLevel;
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Disable colored output (also disabled by the color setting, NO_COLOR, or when stdout is not a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Mark results with ASCII ([ok], [error], ->) instead of glyphs like ✓ and ↳ (also set by the ascii setting)
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// Abort pull/push right after the named phase (for testing crash recovery)
+    #[arg(
+        long,
+        global = true,
+        hide = true,
+        value_parser = clap::builder::PossibleValuesParser::new(sync::FAIL_POINT_PHASES)
+    )]
+    fail_at: Option<String>,
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     /// Initialize a new sync repository
     Init {
@@ -75,9 +130,13 @@ enum Commands {
         #[arg(short, long)]
         verbose: bool,
 
-        /// Show minimal quiet output
-        #[arg(short, long, conflicts_with = "verbose")]
-        quiet: bool,
+        /// Print only a one-line summary (-qq: print nothing)
+        #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "verbose")]
+        quiet: u8,
+
+        /// Fail if a mirror push fails, instead of only warning
+        #[arg(long)]
+        strict: bool,
     },
 
     /// Pull and merge history from the sync repository
@@ -98,9 +157,27 @@ enum Commands {
         #[arg(short, long)]
         verbose: bool,
 
-        /// Show minimal quiet output
-        #[arg(short, long, conflicts_with = "verbose")]
-        quiet: bool,
+        /// Print only a one-line summary (-qq: print nothing)
+        #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "verbose")]
+        quiet: u8,
+
+        /// Also pull a teammate's shared sessions read-only (repeatable)
+        #[arg(long = "include-user", value_name = "NAME")]
+        include_users: Vec<String>,
+
+        /// Leave history.jsonl (the --resume session index) alone
+        #[arg(long)]
+        no_history: bool,
+
+        /// Fail if any session is skipped for being too large or
+        /// unparsable, or a network step fails, instead of only warning
+        #[arg(long)]
+        strict: bool,
+
+        /// Only add what's missing locally from the sync repo as of this
+        /// commit or tag, leaving the sync repo untouched
+        #[arg(long, value_name = "REF", conflicts_with_all = ["branch", "interactive", "include_users", "strict"])]
+        at: Option<String>,
     },
 
     /// Sync bidirectionally (pull then push)
@@ -125,9 +202,35 @@ enum Commands {
         #[arg(short, long)]
         verbose: bool,
 
-        /// Show minimal quiet output
-        #[arg(short, long, conflicts_with = "verbose")]
-        quiet: bool,
+        /// Print only a one-line summary (-qq: print nothing)
+        #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "verbose")]
+        quiet: u8,
+
+        /// Also pull a teammate's shared sessions read-only (repeatable)
+        #[arg(long = "include-user", value_name = "NAME")]
+        include_users: Vec<String>,
+
+        /// Leave history.jsonl (the --resume session index) alone
+        #[arg(long)]
+        no_history: bool,
+
+        /// Skip the pull half (only push)
+        #[arg(long, conflicts_with = "no_push")]
+        no_pull: bool,
+
+        /// Skip the push half (only pull)
+        #[arg(long)]
+        no_push: bool,
+
+        /// Fail if any session is skipped for being too large or
+        /// unparsable, or a network step fails, instead of only warning
+        #[arg(long)]
+        strict: bool,
+
+        /// Run from a schedule: skip the sync if the last operation ran less
+        /// than the minimum sync interval ago
+        #[arg(long)]
+        scheduled: bool,
     },
 
     /// Show sync status and conflicts
@@ -183,6 +286,186 @@ enum Commands {
         #[arg(long)]
         claude_projects_dir: Option<String>,
 
+        /// Write entries back byte-identical to the original lines (true/false)
+        #[arg(long)]
+        preserve_raw_lines: Option<bool>,
+
+        /// Sync Claude Code todo lists (~/.claude/todos) alongside sessions
+        #[arg(long)]
+        sync_todos: Option<bool>,
+
+        /// How to merge todo files changed on both machines: last-writer-wins or per-item
+        #[arg(long)]
+        todo_merge_mode: Option<String>,
+
+        /// Sync per-session file backups (~/.claude/file-history)
+        #[arg(long)]
+        sync_file_history: Option<bool>,
+
+        /// Sync captured shell environments (~/.claude/shell-snapshots)
+        #[arg(long)]
+        sync_shell_snapshots: Option<bool>,
+
+        /// Skip file-history and shell snapshot files larger than this many MB (default: 1)
+        #[arg(long)]
+        artifact_max_size_mb: Option<u64>,
+
+        /// Subagent (sidechain) handling: keep, drop-entries, or drop-sessions
+        #[arg(long)]
+        sidechain_mode: Option<String>,
+
+        /// Compact large payloads in sessions older than N days before syncing (0 = disable)
+        #[arg(long)]
+        compact_older_than: Option<u32>,
+
+        /// Compact tool outputs and file snapshots larger than this many KB (default: 32)
+        #[arg(long)]
+        compact_min_payload_kb: Option<u64>,
+
+        /// Keep a local-only copy of sessions before compacting them
+        #[arg(long)]
+        compaction_archive: Option<bool>,
+
+        /// Only sync sessions with these tags (comma-separated, empty to clear)
+        #[arg(long)]
+        include_tags: Option<String>,
+
+        /// Never sync sessions with these tags (comma-separated, empty to clear)
+        #[arg(long)]
+        exclude_tags: Option<String>,
+
+        /// Store your sessions under users/<name>/ in a team-shared repo (empty to clear)
+        #[arg(long)]
+        user_namespace: Option<String>,
+
+        /// Sync mode: files (merge session files) or oplog (per-machine entry logs)
+        #[arg(long)]
+        sync_mode: Option<String>,
+
+        /// Fail a pull if any session in .claude ends up diverged from the sync repo
+        #[arg(long)]
+        verify_after_pull: Option<bool>,
+
+        /// Propagate sessions deleted from .claude between pulls to other machines
+        #[arg(long)]
+        detect_deletions: Option<bool>,
+
+        /// Comma-separated remotes that each push is mirrored to (e.g., "backup,offsite")
+        #[arg(long)]
+        mirror_remotes: Option<String>,
+
+        /// Conversations listed per project after a pull (0 for no limit)
+        #[arg(long)]
+        max_conversations_display: Option<usize>,
+
+        /// Default pull conflict policy: smart-merge, prefer-local, prefer-remote, or keep-both
+        #[arg(long)]
+        conflict_policy: Option<String>,
+
+        /// Comma-separated PATTERN=POLICY pairs checked before the default (empty to clear)
+        #[arg(long)]
+        project_conflict_policies: Option<String>,
+
+        /// Maximum number of past conflict reports kept (0 = no limit)
+        #[arg(long)]
+        conflict_report_max_count: Option<usize>,
+
+        /// Days past conflict reports are kept (0 = no limit)
+        #[arg(long)]
+        conflict_report_retention_days: Option<u32>,
+
+        /// How entries edited in place are reconciled on pull: prefer-newer, prefer-local, prefer-remote, or fork
+        #[arg(long)]
+        modified_entry_policy: Option<String>,
+
+        /// Which side wins when local and remote hold different versions of the same entry: local-first or remote-first
+        #[arg(long)]
+        pull_precedence: Option<String>,
+
+        /// Comma-separated TYPE=STRATEGY pairs for deduplicating entries without UUIDs: full-entry, message, or occurrence (empty to clear)
+        #[arg(long)]
+        content_key_strategies: Option<String>,
+
+        /// Where session IDs come from: entry (first entry with a sessionId) or filename
+        #[arg(long)]
+        session_id_source: Option<String>,
+
+        /// Split session files in the sync repo over this many entries into continuation files (0 = never)
+        #[arg(long)]
+        max_entries_per_file: Option<usize>,
+
+        /// Comma-separated SIZE_MB=ACTION bands for large session files: warn, skip, lfs, or compress (empty to clear)
+        #[arg(long)]
+        large_file_actions: Option<String>,
+
+        /// Threads used by parallel phases such as session discovery (0 = one per CPU core)
+        #[arg(long)]
+        parallelism: Option<usize>,
+
+        /// Comma-separated built-in PII scrubbing rules: email, phone, ip (empty to clear)
+        #[arg(long)]
+        scrub_rules: Option<String>,
+
+        /// Regex scrubbed from sessions saved to the sync repo (repeatable; '' to clear)
+        #[arg(long = "scrub-regex", value_name = "REGEX")]
+        scrub_regexes: Option<Vec<String>>,
+
+        /// Regex for matches never scrubbed (repeatable; '' to clear)
+        #[arg(long = "scrub-allow", value_name = "REGEX")]
+        scrub_allowlist: Option<Vec<String>>,
+
+        /// Write a checksum manifest on push and verify fetched files against it on pull
+        #[arg(long)]
+        checksum_manifest: Option<bool>,
+
+        /// Create one commit per touched project directory, with per-project stats
+        #[arg(long)]
+        commit_per_project: Option<bool>,
+
+        /// Write an INDEX.md summarizing each project's sessions to the sync repo on push
+        #[arg(long)]
+        project_index: Option<bool>,
+
+        /// Only append to sessions written within this many seconds, or open in a running claude process (0 to disable)
+        #[arg(long, value_name = "SECS")]
+        active_session_window: Option<u64>,
+
+        /// Take a lease on origin lasting this many seconds before sync and push, so machines take turns (0 to disable)
+        #[arg(long, value_name = "SECS")]
+        remote_lease: Option<u64>,
+
+        /// Seconds that must pass after an operation before `sync --scheduled` or the API's /sync runs (0 to disable)
+        #[arg(long, value_name = "SECS")]
+        min_sync_interval: Option<u64>,
+
+        /// Times a push rejected by new remote commits is retried after merging them (0 to fail right away)
+        #[arg(long, value_name = "N")]
+        push_retries: Option<u32>,
+
+        /// URL to request after each successful pull, push, or sync, e.g. a healthchecks.io check (empty to disable)
+        #[arg(long, value_name = "URL")]
+        healthcheck_url: Option<String>,
+
+        /// URL to post the error to when a pull, push, or sync fails (default: the healthcheck URL + /fail; empty to reset)
+        #[arg(long, value_name = "URL")]
+        healthcheck_fail_url: Option<String>,
+
+        /// Output of pull, push, and sync when neither -q nor --verbose is given: quiet, summary, normal, or verbose
+        #[arg(long, value_name = "LEVEL")]
+        verbosity: Option<String>,
+
+        /// Colored output: auto (when stdout is a terminal and NO_COLOR isn't set), always, or never
+        #[arg(long, value_name = "WHEN")]
+        color: Option<String>,
+
+        /// Language of sync summaries: en, de, or auto (from LC_ALL, LC_MESSAGES, or LANG)
+        #[arg(long, value_name = "LOCALE")]
+        locale: Option<String>,
+
+        /// Always mark results with ASCII instead of glyphs like ✓ and ↳, for screen readers and terminal multiplexers
+        #[arg(long)]
+        ascii: Option<bool>,
+
         /// Show current configuration
         #[arg(long)]
         show: bool,
@@ -196,7 +479,7 @@ enum Commands {
         wizard: bool,
     },
 
-    /// View conflict reports
+    /// View conflict reports, or a digest of the week's sync activity
     Report {
         /// Output format: json or markdown
         #[arg(short, long, default_value = "markdown")]
@@ -205,6 +488,16 @@ enum Commands {
         /// Output file (default: print to stdout)
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Summarize the last 7 days of pulls, pushes, and syncs instead of the latest conflicts
+        #[arg(long)]
+        weekly: bool,
+    },
+
+    /// Browse past conflict reports
+    Conflicts {
+        #[command(subcommand)]
+        action: ConflictsAction,
     },
 
     /// Manage git remote configuration
@@ -218,6 +511,396 @@ enum Commands {
         #[command(subcommand)]
         action: HistoryAction,
     },
+
+    /// Check and repair the local history.jsonl (the --resume session index)
+    HistoryIndex {
+        #[command(subcommand)]
+        action: HistoryIndexAction,
+    },
+
+    /// Sync only history.jsonl (the --resume session index), not sessions
+    HistorySync {
+        /// Commit locally without pushing to the remote
+        #[arg(long)]
+        no_push: bool,
+
+        /// Show detailed verbose output
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Print only a one-line summary (-qq: print nothing)
+        #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "verbose")]
+        quiet: u8,
+    },
+
+    /// Run concurrent syncs on simulated machines and check no entries are lost
+    #[command(hide = true)]
+    Stress {
+        /// Number of simulated machines
+        #[arg(long, default_value_t = 4)]
+        machines: usize,
+
+        /// Number of rounds of random edits followed by concurrent syncs
+        #[arg(long, default_value_t = 20)]
+        rounds: usize,
+
+        /// Seed for the random edits (printed, so a failing run can be replayed)
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Keep the scratch directory after a successful run
+        #[arg(long)]
+        keep: bool,
+
+        /// Show each round and the errors of failed syncs
+        #[arg(short, long)]
+        verbose: bool,
+    },
+
+    /// Report token usage and estimated cost across synced sessions
+    Usage {
+        /// Group by: project, model, session, or day
+        #[arg(long, default_value = "project")]
+        by: String,
+
+        /// Only count usage within this window (e.g., 30d, 12h, 2w)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Output format: table, json, or csv
+        #[arg(short, long, default_value = "table")]
+        format: String,
+    },
+
+    /// Serve synced history to Claude over the Model Context Protocol (stdio)
+    McpServe,
+
+    /// Serve synced history as a JSON API over HTTP
+    Serve {
+        /// Address to listen on (use port 0 for any free port)
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: String,
+    },
+
+    /// Show messages from all synced sessions in chronological order
+    Timeline {
+        /// Only show activity within this window (e.g., 7d, 12h, 2w)
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Output format: table, json, or csv (json and csv keep whole messages)
+        #[arg(short, long, default_value = "table")]
+        format: String,
+    },
+
+    /// Check the installation and session files for problems
+    Doctor {
+        /// Report unknown entry types and fields found in local sessions
+        #[arg(long)]
+        schema_report: bool,
+
+        /// Write the schema report as JSON to this file instead of printing it
+        #[arg(short, long, requires = "schema_report")]
+        output: Option<PathBuf>,
+
+        /// Move sessions stored under nested paths in the sync repo to their
+        /// canonical paths, merging duplicates
+        #[arg(long, conflicts_with = "schema_report")]
+        fix_paths: bool,
+    },
+
+    /// Tag sessions and list tags
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+
+    /// Pin sessions so they always sync first, bypassing age and size filters
+    Pin {
+        #[command(subcommand)]
+        action: PinAction,
+    },
+
+    /// Mark sessions or projects so they are never copied into the sync repo
+    Mark {
+        #[command(subcommand)]
+        action: MarkAction,
+    },
+
+    /// Subscribe read-only to another sync repo (mirrored into ~/.claude/projects-shared/)
+    Subscribe {
+        #[command(subcommand)]
+        action: SubscribeAction,
+    },
+
+    /// Remove this machine's expired temp branches, and optionally stale ones from any machine on the remote
+    Gc {
+        /// Delete remote temp branches (from any machine) older than the retention window
+        #[arg(long)]
+        remote_temp_branches: bool,
+
+        /// Age in hours after which a temp branch is stale (default: temp branch retention, at least 24)
+        #[arg(long, value_name = "HOURS")]
+        older_than: Option<u32>,
+
+        /// List what would be deleted without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// List, restore, or empty files moved aside by cleanup operations
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+
+    /// Delete a session everywhere: locally, in the sync repo, and on other machines at their next pull
+    Delete {
+        /// Session ID or unique prefix
+        session: String,
+
+        /// Delete without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Commit the deletion without pushing it
+        #[arg(long)]
+        no_push: bool,
+    },
+
+    /// Remove sessions from every commit of the sync repo and force-push the rewritten history
+    Purge {
+        /// Session ID or unique prefix, or a path pattern within the sync repo (contains '/' or '*')
+        target: String,
+
+        /// Purge without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Rewrite the local history without force-pushing it
+        #[arg(long)]
+        no_push: bool,
+    },
+
+    /// Show what was pushed off this machine: every file and session entry, by commit and time
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+
+    /// Show which commit, machine, and date introduced each entry of a session
+    Blame {
+        /// Session ID or unique prefix
+        session: String,
+
+        /// Output format: table, json, or csv (json and csv include full commit IDs)
+        #[arg(short, long, default_value = "table")]
+        format: String,
+    },
+
+    /// Pull, verify, and tag the result as a named recovery point (lists snapshots without a name)
+    Snapshot {
+        /// Snapshot name
+        name: Option<String>,
+
+        /// Tag message (default: the snapshot name and this machine)
+        #[arg(short, long, conflicts_with = "delete")]
+        message: Option<String>,
+
+        /// Delete the named snapshot, locally and on the remote
+        #[arg(long, requires = "name")]
+        delete: bool,
+
+        /// Output format for the list: table, json, or csv
+        #[arg(short, long, default_value = "table")]
+        format: String,
+
+        /// Show detailed verbose output
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Print only a one-line summary (-qq: print nothing)
+        #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "verbose")]
+        quiet: u8,
+    },
+
+    /// Add what's missing locally from a snapshot, leaving the sync repo untouched
+    Restore {
+        /// Name of the snapshot to restore
+        #[arg(long)]
+        snapshot: String,
+
+        /// Show detailed verbose output
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Print only a one-line summary (-qq: print nothing)
+        #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "verbose")]
+        quiet: u8,
+    },
+
+    /// Write the sync repo's new commits to a file, for machines without a shared remote,
+    /// or a session's message graph
+    Export {
+        /// Bundle file to write
+        #[arg(long, value_name = "FILE", required_unless_present_any = ["graph", "site"])]
+        git_bundle: Option<PathBuf>,
+
+        /// Export the whole history, not just what changed since the last export
+        #[arg(long, conflicts_with_all = ["graph", "site"])]
+        full: bool,
+
+        /// Write the parentUuid graph of a session's messages instead (ID, prefix, or
+        /// pattern; pick from recent sessions if omitted)
+        #[arg(long, value_name = "SESSION", num_args = 0..=1, conflicts_with = "git_bundle")]
+        graph: Option<Option<String>>,
+
+        /// Write a static HTML browser of the synced sessions to this directory instead
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["git_bundle", "graph"])]
+        site: Option<PathBuf>,
+
+        /// Graph format: dot or json
+        #[arg(short, long, default_value = "dot", requires = "graph")]
+        format: String,
+
+        /// Write the graph to a file instead of stdout
+        #[arg(short, long, value_name = "FILE", requires = "graph")]
+        output: Option<PathBuf>,
+
+        /// Show detailed verbose output
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Print only a one-line summary (-qq: print nothing)
+        #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "verbose")]
+        quiet: u8,
+    },
+
+    /// Pull from a file written by export on another machine
+    Import {
+        /// Bundle file to read
+        #[arg(long, value_name = "FILE")]
+        git_bundle: PathBuf,
+
+        /// Show detailed verbose output
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Print only a one-line summary (-qq: print nothing)
+        #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "verbose")]
+        quiet: u8,
+    },
+
+    /// Store, read, or remove tokens and keys in the OS keychain
+    Credentials {
+        #[command(subcommand)]
+        action: CredentialsAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum CredentialsAction {
+    /// Store a secret (prompted for, or read from stdin when piped)
+    Set {
+        /// Credential name, e.g. https-token or https-token:github.com
+        name: String,
+    },
+
+    /// Print a stored secret
+    Get {
+        /// Credential name
+        name: String,
+    },
+
+    /// Remove a stored secret
+    Remove {
+        /// Credential name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditAction {
+    /// List pushed files, oldest first
+    List {
+        /// Only files of this session (ID or prefix)
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Output format: table, json, or csv (json and csv include entry UUIDs)
+        #[arg(short, long, default_value = "table")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    /// List trashed items
+    List,
+
+    /// Move a trashed item back to where it came from
+    Restore {
+        /// Trash entry ID (from `trash list`)
+        id: String,
+    },
+
+    /// Permanently remove everything in the trash
+    Empty,
+}
+
+#[derive(Subcommand)]
+enum ConflictsAction {
+    /// List past conflict reports and the operations that generated them
+    History {
+        /// Number of reports to show (most recent first)
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Show a past conflict report
+    Show {
+        /// Report ID or unique prefix (from `conflicts history`)
+        id: String,
+
+        /// Output format: json or markdown
+        #[arg(short, long, default_value = "markdown")]
+        format: String,
+
+        /// Output file (default: print to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SubscribeAction {
+    /// Subscribe to a repository and pull its sessions
+    Add {
+        /// Short name for the subscription (used as the local directory name)
+        name: String,
+
+        /// Repository URL
+        url: String,
+
+        /// Subdirectory within the repo holding projects
+        #[arg(long, default_value = "projects")]
+        subdirectory: String,
+    },
+
+    /// Remove a subscription (mirrored sessions are kept)
+    Remove {
+        /// Subscription name
+        name: String,
+    },
+
+    /// List subscriptions
+    List,
+
+    /// Update subscriptions and mirror new sessions
+    Pull {
+        /// Only update this subscription
+        name: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -241,6 +924,29 @@ enum RemoteAction {
         #[arg(short, long, default_value = "origin")]
         name: String,
     },
+
+    /// Replicate sessions to a plain directory with rsync, no git server needed
+    SetRsync {
+        /// ssh://user@host/path, or an absolute local path such as a mounted share
+        url: String,
+    },
+
+    /// Stop replicating to the rsync directory
+    RemoveRsync,
+}
+
+#[derive(Subcommand)]
+enum HistoryIndexAction {
+    /// Dedupe history.jsonl by (sessionId, timestamp) and rewrite it sorted
+    Fix {
+        /// Also drop entries for sessions that no longer exist locally
+        #[arg(long)]
+        prune_missing: bool,
+
+        /// Report what would change without rewriting the file
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -250,13 +956,32 @@ enum HistoryAction {
         /// Number of operations to show (default: 10)
         #[arg(short, long, default_value_t = 10)]
         limit: usize,
+
+        /// Output format: table, json, or csv
+        #[arg(short, long, default_value = "table")]
+        format: String,
+    },
+
+    /// Export affected conversations from all recorded operations
+    Export {
+        /// Output format: csv or json
+        #[arg(short, long, default_value = "csv")]
+        format: String,
+
+        /// Output file (default: print to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Show details of the last operation
     Last {
-        /// Filter by operation type (pull or push)
+        /// Filter by operation type (pull, push, or sync)
         #[arg(short = 't', long)]
         operation_type: Option<String>,
+
+        /// Output format: table or json (json includes skipped sessions and reasons)
+        #[arg(short, long, default_value = "table")]
+        format: String,
     },
 
     /// Interactively review and select operations to view details
@@ -270,14 +995,126 @@ enum HistoryAction {
     Clear,
 }
 
+#[derive(Subcommand)]
+enum TagAction {
+    /// Add a tag to a session (e.g., work/infra)
+    Add {
+        /// Session ID
+        session_id: String,
+
+        /// Tag to add; use '/' for hierarchy
+        tag: String,
+    },
+
+    /// Remove a tag from a session
+    Remove {
+        /// Session ID
+        session_id: String,
+
+        /// Tag to remove
+        tag: String,
+    },
+
+    /// List tagged sessions
+    List {
+        /// Only show sessions with this tag (includes nested tags)
+        #[arg(short, long)]
+        tag: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MarkAction {
+    /// Keep a session or project out of the sync repo (lists local-only entries without a target)
+    LocalOnly {
+        /// Session ID or unique prefix, project path or directory name, or glob pattern
+        #[arg(allow_hyphen_values = true)]
+        target: Option<String>,
+
+        /// Sync the session or project again
+        #[arg(long, requires = "target")]
+        undo: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PinAction {
+    /// Pin a session by ID
+    Add {
+        /// Session ID or unique prefix (prompts for a recent session if omitted)
+        session_id: Option<String>,
+    },
+
+    /// Unpin a session by ID
+    Remove {
+        /// Session ID or unique prefix (prompts for a recent session if omitted)
+        session_id: Option<String>,
+    },
+
+    /// List pinned sessions
+    List,
+}
+
+/// Verbosity from `--verbose` and the number of `-q` flags, or the
+/// configured default when neither is given
+fn verbosity_from_flags(verbose: bool, quiet: u8) -> VerbosityLevel {
+    match (verbose, quiet) {
+        (true, _) => VerbosityLevel::Verbose,
+        (false, 0) => filter::FilterConfig::load()
+            .and_then(|config| config.verbosity())
+            .unwrap_or(VerbosityLevel::Normal),
+        (false, 1) => VerbosityLevel::Summary,
+        (false, _) => VerbosityLevel::Quiet,
+    }
+}
+
+/// Exit with a distinct code when a pull or sync couldn't reach the remote,
+/// so scripts can tell it from both success and failure
+fn exit_if_local_only(outcome: sync::SyncOutcome) {
+    if outcome == sync::SyncOutcome::LocalOnly {
+        std::process::exit(sync::SyncOutcome::LOCAL_ONLY_EXIT_CODE);
+    }
+}
+
 fn main() -> Result<()> {
+    // Move state and cache files out of the config directory first, so the
+    // logger finds its file in the state directory
+    let migrated = config::ConfigManager::migrate_layout();
+
+    // Parsed first, so the logger knows whether stdout is a protocol stream
+    let cli = Cli::parse();
+
     // Initialize logging (rotate log if needed, then set up logger)
     logger::rotate_log_if_needed().ok(); // Ignore errors during log rotation
-    logger::init_logger().ok(); // Ignore errors during logger init
+    let stdout_is_protocol = matches!(cli.command, Some(Commands::McpServe));
+    logger::init_logger(stdout_is_protocol).ok(); // Ignore errors during logger init
 
     log::debug!("claude-code-sync started");
+    match migrated {
+        Ok(0) => {}
+        Ok(moved) => log::info!(
+            "Moved {} state and cache entries out of the config directory",
+            moved
+        ),
+        Err(e) => log::warn!("Failed to migrate the config directory layout: {:#}", e),
+    }
 
-    let cli = Cli::parse();
+    color::configure(cli.no_color);
+    i18n::configure();
+    glyphs::configure(cli.ascii);
+    if let Some(phase) = &cli.fail_at {
+        std::env::set_var(sync::FAIL_AT_ENV_VAR, phase);
+    }
+
+    // Size the rayon pool shared by discovery and other parallel phases
+    if let Some(threads) = filter::FilterConfig::load().ok().and_then(|f| f.parallelism) {
+        if let Err(e) = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+        {
+            log::warn!("Failed to limit parallelism to {} threads: {}", threads, e);
+        }
+    }
 
     // Check if initialization is needed (before processing any command)
     let needs_onboarding = !is_initialized()?;
@@ -295,7 +1132,13 @@ fn main() -> Result<()> {
                 exclude_attachments: false,
                 interactive: false,
                 verbose: false,
-                quiet: false,
+                quiet: 0,
+                include_users: Vec::new(),
+                no_history: false,
+                no_pull: false,
+                no_push: false,
+                strict: false,
+                scheduled: false,
             }
         } else {
             // Already initialized, default to sync
@@ -305,13 +1148,24 @@ fn main() -> Result<()> {
                 exclude_attachments: false,
                 interactive: false,
                 verbose: false,
-                quiet: false,
+                quiet: 0,
+                include_users: Vec::new(),
+                no_history: false,
+                no_pull: false,
+                no_push: false,
+                strict: false,
+                scheduled: false,
             }
         }
     };
 
-    // Check if this is an Init command (skip auto-onboarding for Init)
-    let is_init_command = matches!(command, Commands::Init { .. });
+    // Check if this is an Init command (skip auto-onboarding for Init).
+    // Credentials are exempt too: an HTTPS token may be needed to clone the
+    // sync repo during onboarding.
+    let is_init_command = matches!(
+        command,
+        Commands::Init { .. } | Commands::Credentials { .. }
+    );
 
     // Run onboarding if needed (but not for Init command - it handles its own setup)
     if needs_onboarding && !is_init_command {
@@ -354,24 +1208,21 @@ fn main() -> Result<()> {
             interactive,
             verbose,
             quiet,
+            strict,
         } => {
-            // Determine verbosity level
-            let verbosity = if verbose {
-                VerbosityLevel::Verbose
-            } else if quiet {
-                VerbosityLevel::Quiet
-            } else {
-                VerbosityLevel::Normal
-            };
+            let verbosity = verbosity_from_flags(verbose, quiet);
 
-            sync::push_history(
+            let result = sync::push_history(
                 message.as_deref(),
                 push_remote,
                 branch.as_deref(),
                 exclude_attachments,
                 interactive,
+                strict,
                 verbosity,
-            )?;
+            );
+            healthcheck::report(result.as_ref().map(|()| sync::SyncOutcome::Complete));
+            result?;
         }
         Commands::Pull {
             fetch_remote,
@@ -379,17 +1230,29 @@ fn main() -> Result<()> {
             interactive,
             verbose,
             quiet,
+            include_users,
+            no_history,
+            strict,
+            at,
         } => {
-            // Determine verbosity level
-            let verbosity = if verbose {
-                VerbosityLevel::Verbose
-            } else if quiet {
-                VerbosityLevel::Quiet
-            } else {
-                VerbosityLevel::Normal
-            };
+            let verbosity = verbosity_from_flags(verbose, quiet);
 
-            sync::pull_history(fetch_remote, branch.as_deref(), interactive, verbosity)?;
+            if let Some(rev) = at {
+                sync::pull_at_revision(&rev, verbosity)?;
+                return Ok(());
+            }
+            let result = sync::pull_history(
+                fetch_remote,
+                branch.as_deref(),
+                interactive,
+                &include_users,
+                no_history,
+                strict,
+                verbosity,
+            );
+            healthcheck::report(result.as_ref().copied());
+            let outcome = result?;
+            exit_if_local_only(outcome);
         }
         Commands::Sync {
             message,
@@ -398,23 +1261,39 @@ fn main() -> Result<()> {
             interactive,
             verbose,
             quiet,
+            include_users,
+            no_history,
+            no_pull,
+            no_push,
+            strict,
+            scheduled,
         } => {
-            // Determine verbosity level
-            let verbosity = if verbose {
-                VerbosityLevel::Verbose
-            } else if quiet {
-                VerbosityLevel::Quiet
-            } else {
-                VerbosityLevel::Normal
-            };
+            let verbosity = verbosity_from_flags(verbose, quiet);
+            if let Some(wait) = scheduled.then(sync::automatic_sync_wait).flatten() {
+                if verbosity != VerbosityLevel::Quiet {
+                    println!(
+                        "Skipping scheduled sync: the minimum sync interval has {}s to go",
+                        wait.as_secs().max(1)
+                    );
+                }
+                return Ok(());
+            }
 
-            sync::sync_bidirectional(
+            let result = sync::sync_bidirectional(
                 message.as_deref(),
                 branch.as_deref(),
                 exclude_attachments,
                 interactive,
+                &include_users,
+                no_history,
+                no_pull,
+                no_push,
+                strict,
                 verbosity,
-            )?;
+            );
+            healthcheck::report(result.as_ref().copied());
+            let outcome = result?;
+            exit_if_local_only(outcome);
         }
         Commands::Status {
             show_conflicts,
@@ -433,6 +1312,51 @@ fn main() -> Result<()> {
             sync_subdirectory,
             temp_branch_retention,
             claude_projects_dir,
+            preserve_raw_lines,
+            sync_todos,
+            todo_merge_mode,
+            sync_file_history,
+            sync_shell_snapshots,
+            artifact_max_size_mb,
+            sidechain_mode,
+            compact_older_than,
+            compact_min_payload_kb,
+            compaction_archive,
+            include_tags,
+            exclude_tags,
+            user_namespace,
+            sync_mode,
+            verify_after_pull,
+            detect_deletions,
+            mirror_remotes,
+            max_conversations_display,
+            conflict_policy,
+            project_conflict_policies,
+            conflict_report_max_count,
+            conflict_report_retention_days,
+            modified_entry_policy,
+            pull_precedence,
+            content_key_strategies,
+            session_id_source,
+            max_entries_per_file,
+            large_file_actions,
+            parallelism,
+            scrub_rules,
+            scrub_regexes,
+            scrub_allowlist,
+            checksum_manifest,
+            commit_per_project,
+            project_index,
+            active_session_window,
+            remote_lease,
+            min_sync_interval,
+            push_retries,
+            healthcheck_url,
+            healthcheck_fail_url,
+            verbosity,
+            color,
+            locale,
+            ascii,
             show,
             interactive,
             wizard,
@@ -456,12 +1380,73 @@ fn main() -> Result<()> {
                     sync_subdirectory,
                     temp_branch_retention,
                     claude_projects_dir,
+                    preserve_raw_lines,
+                    sync_todos,
+                    todo_merge_mode,
+                    sync_file_history,
+                    sync_shell_snapshots,
+                    artifact_max_size_mb,
+                    sidechain_mode,
+                    compact_older_than,
+                    compact_min_payload_kb,
+                    compaction_archive,
+                    include_tags,
+                    exclude_tags,
+                    user_namespace,
+                    sync_mode,
+                    verify_after_pull,
+                    detect_deletions,
+                    mirror_remotes,
+                    max_conversations_display,
+                    conflict_policy,
+                    project_conflict_policies,
+                    conflict_report_max_count,
+                    conflict_report_retention_days,
+                    modified_entry_policy,
+                    pull_precedence,
+                    content_key_strategies,
+                    session_id_source,
+                    max_entries_per_file,
+                    large_file_actions,
+                    parallelism,
+                    scrub_rules,
+                    scrub_regexes,
+                    scrub_allowlist,
+                    checksum_manifest,
+                    commit_per_project,
+                    project_index,
+                    active_session_window,
+                    remote_lease,
+                    min_sync_interval,
+                    push_retries,
+                    healthcheck_url,
+                    healthcheck_fail_url,
+                    verbosity,
+                    color,
+                    locale,
+                    ascii,
                 )?;
             }
         }
-        Commands::Report { format, output } => {
-            report::generate_report(&format, output.as_deref())?;
+        Commands::Report {
+            format,
+            output,
+            weekly,
+        } => {
+            if weekly {
+                digest::generate_weekly_report(&format, output.as_deref())?;
+            } else {
+                report::generate_report(&format, output.as_deref())?;
+            }
         }
+        Commands::Conflicts { action } => match action {
+            ConflictsAction::History { limit } => {
+                handlers::handle_conflicts_history(limit)?;
+            }
+            ConflictsAction::Show { id, format, output } => {
+                handlers::handle_conflicts_show(&id, &format, output.as_deref())?;
+            }
+        },
         Commands::Remote { action } => match action {
             RemoteAction::Show => {
                 sync::show_remote()?;
@@ -472,13 +1457,25 @@ fn main() -> Result<()> {
             RemoteAction::Remove { name } => {
                 sync::remove_remote(&name)?;
             }
+            RemoteAction::SetRsync { url } => {
+                sync::set_rsync_remote(&url)?;
+            }
+            RemoteAction::RemoveRsync => {
+                sync::remove_rsync_remote()?;
+            }
         },
         Commands::History { action } => match action {
-            HistoryAction::List { limit } => {
-                handle_history_list(limit)?;
+            HistoryAction::List { limit, format } => {
+                handle_history_list(limit, &format)?;
             }
-            HistoryAction::Last { operation_type } => {
-                handle_history_last(operation_type.as_deref())?;
+            HistoryAction::Export { format, output } => {
+                handle_history_export(&format, output.as_deref())?;
+            }
+            HistoryAction::Last {
+                operation_type,
+                format,
+            } => {
+                handle_history_last(operation_type.as_deref(), &format)?;
             }
             HistoryAction::Review { limit } => {
                 handle_history_review(limit)?;
@@ -487,6 +1484,190 @@ fn main() -> Result<()> {
                 handle_history_clear()?;
             }
         },
+        Commands::HistoryIndex { action } => match action {
+            HistoryIndexAction::Fix {
+                prune_missing,
+                dry_run,
+            } => {
+                handle_history_index_fix(prune_missing, dry_run)?;
+            }
+        },
+        Commands::HistorySync {
+            no_push,
+            verbose,
+            quiet,
+        } => {
+            sync::sync_history_index(!no_push, verbosity_from_flags(verbose, quiet))?;
+        }
+        Commands::Stress {
+            machines,
+            rounds,
+            seed,
+            keep,
+            verbose,
+        } => {
+            handle_stress(machines, rounds, seed, keep, verbose)?;
+        }
+        Commands::Usage { by, since, format } => {
+            usage::show_usage(&by, since.as_deref(), &format)?;
+        }
+        Commands::McpServe => {
+            mcp::serve_stdio()?;
+        }
+        Commands::Serve { listen } => {
+            server::serve(&listen)?;
+        }
+        Commands::Timeline { since, format } => {
+            timeline::show_timeline(&since, &format)?;
+        }
+        Commands::Doctor {
+            schema_report,
+            output,
+            fix_paths,
+        } => {
+            handle_doctor(schema_report, output.as_deref(), fix_paths)?;
+        }
+        Commands::Tag { action } => match action {
+            TagAction::Add { session_id, tag } => {
+                handle_tag_add(&session_id, &tag)?;
+            }
+            TagAction::Remove { session_id, tag } => {
+                handle_tag_remove(&session_id, &tag)?;
+            }
+            TagAction::List { tag } => {
+                handle_tag_list(tag.as_deref())?;
+            }
+        },
+        Commands::Subscribe { action } => match action {
+            SubscribeAction::Add {
+                name,
+                url,
+                subdirectory,
+            } => {
+                handle_subscribe_add(&name, &url, &subdirectory)?;
+            }
+            SubscribeAction::Remove { name } => {
+                handle_subscribe_remove(&name)?;
+            }
+            SubscribeAction::List => {
+                handle_subscribe_list()?;
+            }
+            SubscribeAction::Pull { name } => {
+                handle_subscribe_pull(name.as_deref())?;
+            }
+        },
+        Commands::Gc {
+            remote_temp_branches,
+            older_than,
+            dry_run,
+        } => {
+            handle_gc(remote_temp_branches, older_than, dry_run)?;
+        }
+        Commands::Trash { action } => match action {
+            TrashAction::List => {
+                handle_trash_list()?;
+            }
+            TrashAction::Restore { id } => {
+                handle_trash_restore(&id)?;
+            }
+            TrashAction::Empty => {
+                handle_trash_empty()?;
+            }
+        },
+        Commands::Delete {
+            session,
+            yes,
+            no_push,
+        } => {
+            handle_delete(&session, yes, !no_push)?;
+        }
+        Commands::Purge {
+            target,
+            yes,
+            no_push,
+        } => {
+            handle_purge(&target, yes, !no_push)?;
+        }
+        Commands::Audit { action } => match action {
+            AuditAction::List { session, format } => {
+                handle_audit_list(session.as_deref(), &format)?;
+            }
+        },
+        Commands::Blame { session, format } => {
+            handle_blame(&session, &format)?;
+        }
+        Commands::Snapshot {
+            name,
+            message,
+            delete,
+            format,
+            verbose,
+            quiet,
+        } => {
+            handle_snapshot(
+                name.as_deref(),
+                message.as_deref(),
+                delete,
+                &format,
+                verbosity_from_flags(verbose, quiet),
+            )?;
+        }
+        Commands::Restore {
+            snapshot,
+            verbose,
+            quiet,
+        } => {
+            sync::restore_snapshot(&snapshot, verbosity_from_flags(verbose, quiet))?;
+        }
+        Commands::Export {
+            git_bundle,
+            full,
+            graph,
+            site,
+            format,
+            output,
+            verbose,
+            quiet,
+        } => match (graph, site, git_bundle) {
+            (Some(session), _, _) => {
+                handle_export_graph(session.as_deref(), &format, output.as_deref())?;
+            }
+            (None, Some(dir), _) => {
+                handle_export_site(&dir, verbosity_from_flags(verbose, quiet))?;
+            }
+            (None, None, Some(git_bundle)) => {
+                sync::export_bundle(&git_bundle, full, verbosity_from_flags(verbose, quiet))?;
+            }
+            (None, None, None) => unreachable!("clap requires --git-bundle, --graph, or --site"),
+        },
+        Commands::Import {
+            git_bundle,
+            verbose,
+            quiet,
+        } => {
+            sync::import_bundle(&git_bundle, verbosity_from_flags(verbose, quiet))?;
+        }
+        Commands::Credentials { action } => match action {
+            CredentialsAction::Set { name } => handle_credentials_set(&name)?,
+            CredentialsAction::Get { name } => handle_credentials_get(&name)?,
+            CredentialsAction::Remove { name } => handle_credentials_remove(&name)?,
+        },
+        Commands::Mark { action } => match action {
+            MarkAction::LocalOnly { target, undo } => {
+                handle_mark_local_only(target.as_deref(), undo)?;
+            }
+        },
+        Commands::Pin { action } => match action {
+            PinAction::Add { session_id } => {
+                handle_pin_add(session_id.as_deref())?;
+            }
+            PinAction::Remove { session_id } => {
+                handle_pin_remove(session_id.as_deref())?;
+            }
+            PinAction::List => {
+                handle_pin_list()?;
+            }
+        },
     }
 
     Ok(())
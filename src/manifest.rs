@@ -0,0 +1,209 @@
+//! Checksum manifest of the sync repo
+//!
+//! With `checksum_manifest` enabled, every push writes `manifest.json` to the
+//! sync repo root: the content hash of every other file in the repo. A pull
+//! checks the files it fetched against the manifest of the revision it
+//! fetched, before merging anything into `~/.claude`, so git-level
+//! corruption, a partial fetch, or a file edited by something other than
+//! this tool (say, in a git host's web editor) stops the pull instead of
+//! spreading.
+//!
+//! Hashes are XXH3-128, which catches accidents, not attackers: anyone who
+//! can push can rewrite the manifest too. Files stored in Git LFS are
+//! checked by LFS itself (its pointers carry a SHA-256) and skipped here.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::scm::Scm;
+
+/// Manifest file name, in the sync repo root
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// First line of a Git LFS pointer file
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/";
+
+/// Content hash of every file in the sync repo, by repo-relative path
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(flatten)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Hash every file in the working tree of the repo at `repo_root`
+    pub fn build(repo_root: &Path) -> Result<Self> {
+        let mut files = BTreeMap::new();
+        let walker = WalkDir::new(repo_root).into_iter().filter_entry(|e| {
+            e.depth() != 1 || (e.file_name() != ".git" && e.file_name() != ".hg")
+        });
+        for entry in walker {
+            let entry = entry.context("Failed to walk the sync repo")?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(repo_root)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .replace('\\', "/");
+            if relative == MANIFEST_FILE {
+                continue;
+            }
+            let content = fs::read(entry.path())
+                .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+            files.insert(relative, content_hash(&content));
+        }
+        Ok(Self { files })
+    }
+
+    /// Parse a manifest
+    pub fn parse(content: &str) -> Result<Self> {
+        serde_json::from_str(content).context("Failed to parse checksum manifest")
+    }
+
+    /// Write the manifest to the repo at `repo_root`
+    pub fn save(&self, repo_root: &Path) -> Result<()> {
+        let path = repo_root.join(MANIFEST_FILE);
+        let content = serde_json::to_string_pretty(self)? + "\n";
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Rebuild and save the manifest of the repo at `repo_root`
+pub fn write_manifest(repo_root: &Path) -> Result<()> {
+    Manifest::build(repo_root)?.save(repo_root)
+}
+
+/// Hash of a file's content as stored in the manifest
+pub fn content_hash(content: &[u8]) -> String {
+    format!("xxh3-128:{:032x}", xxhash_rust::xxh3::xxh3_128(content))
+}
+
+/// Files at a revision that don't match its manifest
+#[derive(Debug, Default)]
+pub struct ManifestReport {
+    /// Files checked against the manifest
+    pub checked: usize,
+    /// Files whose content doesn't match their hash
+    pub mismatched: Vec<String>,
+    /// Files listed in the manifest but absent
+    pub missing: Vec<String>,
+    /// Files present but not listed (pushed without updating the manifest)
+    pub unlisted: Vec<String>,
+}
+
+impl ManifestReport {
+    /// Whether every listed file is present and intact
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Check files at `rev` against the manifest at `rev`, returning `None` if
+/// there is no manifest
+///
+/// With `since`, only files changed between `since` and `rev` are hashed
+/// (every listed file is still checked for presence).
+pub fn verify_at(repo: &dyn Scm, rev: &str, since: Option<&str>) -> Result<Option<ManifestReport>> {
+    let present: BTreeSet<String> = repo.list_files_at(rev, ".")?.into_iter().collect();
+    if !present.contains(MANIFEST_FILE) {
+        return Ok(None);
+    }
+    let content = repo
+        .read_file_at(rev, MANIFEST_FILE)
+        .with_context(|| format!("Failed to read {MANIFEST_FILE} at {rev}"))?;
+    let manifest = Manifest::parse(&String::from_utf8_lossy(&content))?;
+    let candidates = match since {
+        Some(since) => repo.changed_files(since, rev)?,
+        None => present.iter().cloned().collect(),
+    };
+
+    let mut report = ManifestReport {
+        missing: manifest
+            .files
+            .keys()
+            .filter(|path| !present.contains(*path))
+            .cloned()
+            .collect(),
+        ..Default::default()
+    };
+    for path in candidates {
+        if path == MANIFEST_FILE || !present.contains(&path) {
+            continue;
+        }
+        let Some(expected) = manifest.files.get(&path) else {
+            report.unlisted.push(path);
+            continue;
+        };
+        let content = repo
+            .read_file_at(rev, &path)
+            .with_context(|| format!("Failed to read {path} at {rev}"))?;
+        if content.starts_with(LFS_POINTER_PREFIX) {
+            continue;
+        }
+        report.checked += 1;
+        if content_hash(&content) != *expected {
+            report.mismatched.push(path);
+        }
+    }
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::MockScm;
+
+    #[test]
+    fn test_build_skips_vcs_dir_and_manifest() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join(".git")).unwrap();
+        fs::create_dir_all(temp.path().join("projects/p")).unwrap();
+        fs::write(temp.path().join(".git/HEAD"), "ref").unwrap();
+        fs::write(temp.path().join("projects/p/s.jsonl"), "{}\n").unwrap();
+        fs::write(temp.path().join(MANIFEST_FILE), "{}").unwrap();
+
+        let manifest = Manifest::build(temp.path()).unwrap();
+        assert_eq!(
+            manifest.files.keys().collect::<Vec<_>>(),
+            ["projects/p/s.jsonl"]
+        );
+        assert_eq!(manifest.files["projects/p/s.jsonl"], content_hash(b"{}\n"));
+    }
+
+    #[test]
+    fn test_verify_at_finds_mismatched_missing_and_unlisted() {
+        let repo = MockScm::new();
+        let manifest = Manifest {
+            files: BTreeMap::from([
+                ("a.jsonl".to_string(), content_hash(b"a")),
+                ("b.jsonl".to_string(), content_hash(b"b")),
+                ("gone.jsonl".to_string(), content_hash(b"gone")),
+            ]),
+        };
+        repo.write_file(MANIFEST_FILE, serde_json::to_string(&manifest).unwrap());
+        repo.write_file("a.jsonl", "a");
+        repo.write_file("b.jsonl", "tampered");
+        repo.write_file("c.jsonl", "c");
+        repo.stage_all().unwrap();
+        repo.commit("Push").unwrap();
+
+        let report = verify_at(&repo, "HEAD", None).unwrap().unwrap();
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.mismatched, ["b.jsonl"]);
+        assert_eq!(report.missing, ["gone.jsonl"]);
+        assert_eq!(report.unlisted, ["c.jsonl"]);
+        assert!(!report.is_ok());
+
+        repo.remove_file(MANIFEST_FILE);
+        repo.stage_all().unwrap();
+        repo.commit("Drop manifest").unwrap();
+        assert!(verify_at(&repo, "HEAD", None).unwrap().is_none());
+    }
+}
@@ -0,0 +1,332 @@
+//! Model Context Protocol server over the synced history
+//!
+//! `mcp-serve` speaks MCP (JSON-RPC 2.0, one message per line on stdin and
+//! stdout) so Claude Code can be given the sessions of every machine as
+//! tools: `list_projects`, `search_history`, and `get_session`. The sessions
+//! are read from the sync repo once, when the server starts; restart it
+//! (Claude Code starts a fresh one per session) to see newer syncs.
+//!
+//! stdout carries only protocol messages, so the logger writes to stderr
+//! in this mode.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+use crate::corpus::{messages, timestamp, Corpus};
+use crate::paths::decode_project_dir;
+use crate::timeline::message_preview;
+
+/// Protocol revisions this server speaks, oldest first
+const PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+/// Matches returned by `search_history` unless the caller asks otherwise
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Messages returned by `get_session` unless the caller asks otherwise
+const DEFAULT_MESSAGE_LIMIT: usize = 200;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Answers MCP requests from a fixed set of sessions
+pub struct HistoryServer {
+    corpus: Corpus,
+}
+
+impl HistoryServer {
+    pub fn new(corpus: Corpus) -> Self {
+        Self { corpus }
+    }
+
+    /// Answer each line of `input` on `output` until `input` ends
+    pub fn serve(&self, input: impl BufRead, mut output: impl Write) -> Result<()> {
+        for line in input.lines() {
+            let line = line.context("Failed to read from stdin")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => self.handle_message(&message),
+                Err(e) => Some(error_response(
+                    Value::Null,
+                    PARSE_ERROR,
+                    &format!("Parse error: {e}"),
+                )),
+            };
+            if let Some(response) = response {
+                writeln!(output, "{response}").context("Failed to write to stdout")?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The response to one JSON-RPC message, or `None` for notifications
+    pub fn handle_message(&self, message: &Value) -> Option<Value> {
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to requests we never send, or garbage
+            return message
+                .get("id")
+                .map(|id| error_response(id.clone(), INVALID_REQUEST, "Expected a request"));
+        };
+        // Notifications (no ID) get no response
+        let id = message.get("id")?.clone();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        log::debug!("MCP request: {}", method);
+
+        let result = match method {
+            "initialize" => Ok(initialize_result(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(&params),
+            other => Err((METHOD_NOT_FOUND, format!("Unknown method: {other}"))),
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn call_tool(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        let outcome = match name {
+            "list_projects" => self.list_projects(),
+            "search_history" => self.search_history(&arguments),
+            "get_session" => self.get_session(&arguments),
+            other => return Err((INVALID_PARAMS, format!("Unknown tool: {other}"))),
+        };
+        // Tool failures go back to the model as results it can read
+        let (text, is_error) = match outcome {
+            Ok(text) => (text, false),
+            Err(e) => (format!("{e:#}"), true),
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error,
+        }))
+    }
+
+    /// Each project with its session count and latest activity, as JSON
+    fn list_projects(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.corpus.projects())?)
+    }
+
+    /// Messages containing every word of `query`, newest first, as JSON
+    fn search_history(&self, arguments: &Value) -> Result<String> {
+        let query = arguments
+            .get("query")
+            .and_then(Value::as_str)
+            .context("search_history needs a 'query'")?;
+        let project = arguments.get("project").and_then(Value::as_str);
+        let limit = arguments
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map_or(DEFAULT_SEARCH_LIMIT, |n| n as usize);
+        let found = self.corpus.search(query, project, limit)?;
+        Ok(serde_json::to_string_pretty(&found)?)
+    }
+
+    /// A session's messages as a plain-text transcript
+    fn get_session(&self, arguments: &Value) -> Result<String> {
+        let reference = arguments
+            .get("session_id")
+            .and_then(Value::as_str)
+            .context("get_session needs a 'session_id'")?;
+        let offset = arguments.get("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let limit = arguments
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map_or(DEFAULT_MESSAGE_LIMIT, |n| n as usize);
+        let session = self.corpus.find_session(reference)?;
+
+        let texts: Vec<String> = messages(session)
+            .filter_map(|entry| {
+                let text = message_preview(entry)?;
+                let time = timestamp(entry)
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                Some(format!("[{time}] {}: {text}", entry.entry_type))
+            })
+            .collect();
+        let mut transcript = format!(
+            "Session {} in {} ({} messages)\n",
+            session.session_id,
+            decode_project_dir(&self.corpus.project_of(session)).display(),
+            texts.len()
+        );
+        for line in texts.iter().skip(offset).take(limit) {
+            transcript.push('\n');
+            transcript.push_str(line);
+        }
+        if offset + limit < texts.len() {
+            transcript.push_str(&format!(
+                "\n\n({} more messages; call again with offset {})",
+                texts.len() - offset - limit,
+                offset + limit
+            ));
+        }
+        Ok(transcript)
+    }
+}
+
+/// Serve the sessions in the sync repo on stdin and stdout
+pub fn serve_stdio() -> Result<()> {
+    let corpus = Corpus::load()?;
+    log::info!("Serving {} sessions over MCP", corpus.len());
+
+    let server = HistoryServer::new(corpus);
+    server.serve(std::io::stdin().lock(), std::io::stdout().lock())
+}
+
+fn initialize_result(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[PROTOCOL_VERSIONS.len() - 1]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": {
+            "name": "claude-code-sync",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "instructions": "Conversation history from every machine synced with claude-code-sync. \
+            Search it for earlier discussions, decisions, and fixes before redoing work.",
+    })
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "list_projects",
+            "description": "List the projects with synced Claude Code sessions, with session counts and latest activity.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "search_history",
+            "description": "Search the messages of past Claude Code sessions from all synced machines. Returns the newest messages containing every word of the query, with their session IDs.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Words that must all appear in a message (case-insensitive)" },
+                    "project": { "type": "string", "description": "Only search projects whose directory name contains this" },
+                    "limit": { "type": "integer", "description": "Maximum matches to return (default 20)" },
+                },
+                "required": ["query"],
+            },
+        },
+        {
+            "name": "get_session",
+            "description": "Get the transcript of a past Claude Code session by ID or ID prefix.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session_id": { "type": "string", "description": "Session ID or a unique prefix of it" },
+                    "offset": { "type": "integer", "description": "Messages to skip (default 0)" },
+                    "limit": { "type": "integer", "description": "Maximum messages to return (default 200)" },
+                },
+                "required": ["session_id"],
+            },
+        },
+    ])
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> HistoryServer {
+        HistoryServer::new(crate::corpus::tests::corpus())
+    }
+
+    fn call(server: &HistoryServer, tool: &str, arguments: Value) -> (String, bool) {
+        let response = server
+            .handle_message(&json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "tools/call",
+                "params": { "name": tool, "arguments": arguments },
+            }))
+            .unwrap();
+        let result = &response["result"];
+        (
+            result["content"][0]["text"].as_str().unwrap().to_string(),
+            result["isError"].as_bool().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_initialize_and_notifications() {
+        let server = server();
+        let response = server
+            .handle_message(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2024-11-05" },
+            }))
+            .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert!(response["result"]["capabilities"]["tools"].is_object());
+
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert_eq!(server.handle_message(&notification), None);
+
+        let response = server
+            .handle_message(&json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/list" }))
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_search_history() {
+        let (text, is_error) = call(
+            &server(),
+            "search_history",
+            json!({ "query": "login", "project": "web" }),
+        );
+        assert!(!is_error);
+        let found: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(found["total_matches"], 1);
+        assert_eq!(found["matches"][0]["snippet"], "Restyle the login page");
+
+        let (text, is_error) = call(&server(), "search_history", json!({}));
+        assert!(is_error);
+        assert!(text.contains("needs a 'query'"), "{text}");
+    }
+
+    #[test]
+    fn test_get_session() {
+        let (text, is_error) = call(&server(), "get_session", json!({ "session_id": "aaaa" }));
+        assert!(!is_error, "{text}");
+        assert!(
+            text.starts_with("Session aaaa-1 in /home/me/api (2 messages)"),
+            "{text}"
+        );
+        assert!(
+            text.contains("[2025-01-01 10:01] assistant: The login test races the token refresh."),
+            "{text}"
+        );
+
+        let (text, is_error) = call(&server(), "get_session", json!({ "session_id": "zzzz" }));
+        assert!(is_error);
+        assert!(text.contains("No session matches"), "{text}");
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use std::collections::{HashMap, HashSet};
 
-use crate::parser::{ConversationEntry, ConversationSession};
+use crate::parser::{canonical_json, ConversationEntry, ConversationSession};
 
 /// Represents a node in the conversation message tree.
 ///
@@ -195,8 +195,8 @@ impl<'a> SmartMerger<'a> {
             let remote_entry = &remote_map[uuid];
 
             // Compare content to detect edits
-            let local_json = serde_json::to_string(local_entry)?;
-            let remote_json = serde_json::to_string(remote_entry)?;
+            let local_json = canonical_json(local_entry);
+            let remote_json = canonical_json(remote_entry);
 
             if local_json != remote_json {
                 // Edit detected - resolve by timestamp
@@ -576,17 +576,15 @@ impl<'a> SmartMerger<'a> {
             a_ts.cmp(&b_ts)
         });
 
-        // Remove duplicates by comparing JSON representation
+        // Remove duplicates by comparing canonical JSON representation
         let mut seen = HashSet::new();
         let mut unique_entries = Vec::new();
 
         for entry in all_entries {
-            if let Ok(json) = serde_json::to_string(&entry) {
-                if seen.insert(json.clone()) {
-                    unique_entries.push(entry);
-                } else {
-                    self.stats.duplicates_removed += 1;
-                }
+            if seen.insert(canonical_json(&entry)) {
+                unique_entries.push(entry);
+            } else {
+                self.stats.duplicates_removed += 1;
             }
         }
 
@@ -650,6 +648,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         }
     }
 
@@ -799,6 +798,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         };
 
         let entry_b = ConversationEntry {
@@ -812,6 +812,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         };
 
         let local = ConversationSession {
@@ -849,6 +850,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         };
 
         // Create a normal entry with no parent
@@ -0,0 +1,184 @@
+//! Prometheus metrics for `serve`
+//!
+//! `GET /metrics` reports how recently this machine synced, how many
+//! sessions the sync repo holds, how many conflicts the last pull left, and
+//! how many requests and syncs the server has handled, in the Prometheus
+//! text format, so a scraper can alert on a machine that stopped syncing.
+
+use chrono::{DateTime, Utc};
+
+/// Prefix of every metric name
+const PREFIX: &str = "claude_code_sync";
+
+/// Content type of the Prometheus text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// What the last operation in the history did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastSync {
+    pub time: DateTime<Utc>,
+    /// Sessions it pushed or pulled
+    pub sessions: usize,
+    pub remote_unreachable: bool,
+    /// Network steps that failed without failing it
+    pub network_failures: usize,
+}
+
+/// Counts kept by a running server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServerCounters {
+    pub requests: u64,
+    pub syncs: u64,
+    /// Requests answered with a server error, failed syncs included
+    pub errors: u64,
+}
+
+/// The state reported on a scrape
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub last_sync: Option<LastSync>,
+    /// Sessions in the sync repo
+    pub sessions: usize,
+    /// Conflicts in the latest conflict report
+    pub conflicts_pending: usize,
+    pub counters: ServerCounters,
+}
+
+/// `snapshot` in the Prometheus text format, as of `now`
+///
+/// The `last_sync` metrics are left out until a first sync, so an alert on
+/// their absence fires for machines that never synced.
+pub fn render(snapshot: &Snapshot, now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    if let Some(last) = &snapshot.last_sync {
+        let age = (now - last.time).num_milliseconds().max(0) as f64 / 1000.0;
+        metric(
+            &mut out,
+            "gauge",
+            "last_sync_timestamp_seconds",
+            "Unix time of the last pull, push, or sync",
+            last.time.timestamp() as f64,
+        );
+        metric(
+            &mut out,
+            "gauge",
+            "last_sync_age_seconds",
+            "Seconds since the last pull, push, or sync",
+            age,
+        );
+        metric(
+            &mut out,
+            "gauge",
+            "last_sync_sessions",
+            "Sessions the last pull, push, or sync transferred",
+            last.sessions as f64,
+        );
+        metric(
+            &mut out,
+            "gauge",
+            "last_sync_remote_unreachable",
+            "Whether the last sync couldn't reach the remote (1) or could (0)",
+            u8::from(last.remote_unreachable).into(),
+        );
+        metric(
+            &mut out,
+            "gauge",
+            "last_sync_network_failures",
+            "Network steps that failed during the last sync without failing it",
+            last.network_failures as f64,
+        );
+    }
+    metric(
+        &mut out,
+        "gauge",
+        "sessions",
+        "Sessions in the sync repo",
+        snapshot.sessions as f64,
+    );
+    metric(
+        &mut out,
+        "gauge",
+        "conflicts_pending",
+        "Conflicts in the latest conflict report",
+        snapshot.conflicts_pending as f64,
+    );
+    metric(
+        &mut out,
+        "counter",
+        "api_requests_total",
+        "Requests this server has answered",
+        snapshot.counters.requests as f64,
+    );
+    metric(
+        &mut out,
+        "counter",
+        "api_syncs_total",
+        "Syncs run through POST /sync",
+        snapshot.counters.syncs as f64,
+    );
+    metric(
+        &mut out,
+        "counter",
+        "errors_total",
+        "Requests this server failed, failed syncs included",
+        snapshot.counters.errors as f64,
+    );
+    out
+}
+
+fn metric(out: &mut String, kind: &str, name: &str, help: &str, value: f64) {
+    out.push_str(&format!(
+        "# HELP {PREFIX}_{name} {help}\n# TYPE {PREFIX}_{name} {kind}\n{PREFIX}_{name} {value}\n"
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let time = DateTime::parse_from_rfc3339("2025-01-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let snapshot = Snapshot {
+            last_sync: Some(LastSync {
+                time,
+                sessions: 3,
+                remote_unreachable: true,
+                network_failures: 0,
+            }),
+            sessions: 12,
+            conflicts_pending: 1,
+            counters: ServerCounters {
+                requests: 5,
+                syncs: 1,
+                errors: 0,
+            },
+        };
+        let text = render(&snapshot, time + chrono::Duration::milliseconds(90_500));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[..3],
+            [
+                "# HELP claude_code_sync_last_sync_timestamp_seconds Unix time of the last pull, push, or sync",
+                "# TYPE claude_code_sync_last_sync_timestamp_seconds gauge",
+                "claude_code_sync_last_sync_timestamp_seconds 1735725600",
+            ]
+        );
+        assert!(lines.contains(&"claude_code_sync_last_sync_age_seconds 90.5"));
+        assert!(lines.contains(&"claude_code_sync_last_sync_remote_unreachable 1"));
+        assert!(lines.contains(&"claude_code_sync_sessions 12"));
+        assert!(lines.contains(&"# TYPE claude_code_sync_api_requests_total counter"));
+
+        let never = render(
+            &Snapshot {
+                last_sync: None,
+                ..snapshot
+            },
+            time,
+        );
+        assert!(!never.contains("last_sync"));
+        assert!(never.contains("claude_code_sync_conflicts_pending 1\n"));
+    }
+}
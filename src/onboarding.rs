@@ -6,6 +6,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::config::ConfigManager;
+use crate::glyphs;
 
 /// Configuration file for non-interactive initialization.
 ///
@@ -280,9 +281,12 @@ pub struct OnboardingConfig {
 pub fn run_onboarding() -> Result<OnboardingConfig> {
     println!(
         "\n{}",
-        "⚙️  First time setup detected. Let's configure claude-code-sync!"
-            .cyan()
-            .bold()
+        format!(
+            "{} First time setup detected. Let's configure claude-code-sync!",
+            glyphs::setup()
+        )
+        .cyan()
+        .bold()
     );
     println!();
 
@@ -397,7 +401,7 @@ pub fn run_onboarding() -> Result<OnboardingConfig> {
     };
 
     println!();
-    println!("{}", "✓ Configuration complete!".green().bold());
+    println!("{}", format!("{} Configuration complete!", glyphs::ok()).green().bold());
 
     Ok(OnboardingConfig {
         repo_path,
@@ -0,0 +1,66 @@
+//! Paging long output through `$PAGER`
+
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Pager used when `$PAGER` is unset
+const DEFAULT_PAGER: &str = "less -FRX";
+
+/// Split a pager setting into program and arguments
+///
+/// Returns `None` when paging is disabled (an empty value or `cat`).
+fn pager_command(setting: Option<&str>) -> Option<Vec<String>> {
+    let parts: Vec<String> = setting
+        .unwrap_or(DEFAULT_PAGER)
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    match parts.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(parts),
+    }
+}
+
+/// Whether output can be paged (both stdin and stdout are terminals)
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+/// Show `content` in the user's pager
+///
+/// Returns `false` without printing anything if no pager could be started,
+/// so the caller can fall back to printing.
+pub fn page(content: &str) -> bool {
+    let setting = std::env::var("PAGER").ok();
+    let Some(command) = pager_command(setting.as_deref()) else {
+        return false;
+    };
+
+    let Ok(mut child) = Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .spawn()
+    else {
+        log::debug!("Failed to start pager '{}'", command.join(" "));
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user quitting the pager early closes the pipe; that's fine
+        let _ = stdin.write_all(content.as_bytes());
+    }
+    let _ = child.wait();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(None).unwrap(), vec!["less", "-FRX"]);
+        assert_eq!(pager_command(Some("more")).unwrap(), vec!["more"]);
+        assert!(pager_command(Some("")).is_none());
+        assert!(pager_command(Some("cat")).is_none());
+    }
+}
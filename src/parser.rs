@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
@@ -84,6 +85,40 @@ pub struct ConversationEntry {
     /// as the named fields when serializing/deserializing.
     #[serde(flatten)]
     pub extra: Value,
+
+    /// The original JSON line this entry was parsed from
+    ///
+    /// Re-serializing an entry reorders keys and normalizes formatting, so the
+    /// exact bytes Claude Code wrote are kept here. When raw line preservation
+    /// is enabled, writers emit this line verbatim instead of re-serializing.
+    /// Entries built in memory (or modified after parsing) should leave this `None`.
+    #[serde(skip)]
+    pub raw_line: Option<String>,
+}
+
+impl ConversationEntry {
+    /// Whether this entry belongs to a subagent (sidechain) conversation
+    ///
+    /// Claude Code marks entries written by subagents with `"isSidechain": true`.
+    pub fn is_sidechain(&self) -> bool {
+        self.extra
+            .get("isSidechain")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Render this entry as a single JSONL line (without trailing newline)
+    ///
+    /// If `preserve_raw` is true and the entry still carries its original line,
+    /// that line is returned unchanged. Otherwise the entry is serialized.
+    pub fn to_json_line(&self, preserve_raw: bool) -> Result<String> {
+        if preserve_raw {
+            if let Some(ref raw) = self.raw_line {
+                return Ok(raw.clone());
+            }
+        }
+        serde_json::to_string(self).context("Failed to serialize conversation entry")
+    }
 }
 
 /// Represents a complete conversation session
@@ -92,8 +127,9 @@ pub struct ConversationSession {
     /// Unique identifier for this conversation session
     ///
     /// Either extracted from the first entry that contains a sessionId field,
-    /// or derived from the filename (without extension) if no entries contain
-    /// a session ID. Used to group related conversation entries together.
+    /// or derived from the filename (without extension), depending on the
+    /// [`SessionIdSource`]; each falls back to the other. Used to group
+    /// related conversation entries together.
     pub session_id: String,
 
     /// All conversation entries in chronological order
@@ -111,14 +147,60 @@ pub struct ConversationSession {
     pub file_path: String,
 }
 
+/// Where a session's ID is taken from when loading a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionIdSource {
+    /// The first entry carrying a `sessionId`, else the file name (default)
+    Entry,
+    /// The file name, else the first entry carrying a `sessionId`
+    ///
+    /// Stable for files whose entries switch session IDs partway through.
+    Filename,
+}
+
+impl SessionIdSource {
+    /// Parse a source name from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "entry" => Some(SessionIdSource::Entry),
+            "filename" => Some(SessionIdSource::Filename),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionIdSource::Entry => "entry",
+            SessionIdSource::Filename => "filename",
+        }
+    }
+}
+
 impl ConversationSession {
     /// Parse a JSONL file into a ConversationSession
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_file_with(path, SessionIdSource::Entry)
+    }
+
+    /// Parse a JSONL file, taking the session ID from `id_source`
+    pub fn from_file_with<P: AsRef<Path>>(path: P, id_source: SessionIdSource) -> Result<Self> {
         let path = path.as_ref();
         let file =
             File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
+        Self::from_reader_with(BufReader::new(file), path, id_source)
+    }
 
-        let reader = BufReader::new(file);
+    /// Parse JSONL content that isn't on disk as it is, such as a file read
+    /// from a git revision or an archive
+    ///
+    /// `path` is where the session belongs; it names the session in errors,
+    /// supplies the file name for `id_source`, and becomes `file_path`.
+    pub fn from_reader_with<R: BufRead, P: AsRef<Path>>(
+        reader: R,
+        path: P,
+        id_source: SessionIdSource,
+    ) -> Result<Self> {
+        let path = path.as_ref();
         let mut entries = Vec::new();
         let mut session_id = None;
 
@@ -131,13 +213,14 @@ impl ConversationSession {
                 continue;
             }
 
-            let entry: ConversationEntry = serde_json::from_str(&line).with_context(|| {
+            let mut entry: ConversationEntry = serde_json::from_str(&line).with_context(|| {
                 format!(
                     "Failed to parse JSON at line {} in {}",
                     line_num + 1,
                     path.display()
                 )
             })?;
+            entry.raw_line = Some(line);
 
             // Extract session ID from first entry that has one
             if session_id.is_none() {
@@ -149,19 +232,21 @@ impl ConversationSession {
             entries.push(entry);
         }
 
-        // If no session ID in entries, use filename (without extension) as session ID
-        let session_id = session_id
-            .or_else(|| {
-                path.file_stem()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s.to_string())
-            })
-            .with_context(|| {
-                format!(
-                    "No session ID found in file or filename: {}",
-                    path.display()
-                )
-            })?;
+        // Fall back from one source to the other
+        let file_stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string());
+        let session_id = match id_source {
+            SessionIdSource::Entry => session_id.or(file_stem),
+            SessionIdSource::Filename => file_stem.or(session_id),
+        }
+        .with_context(|| {
+            format!(
+                "No session ID found in file or filename: {}",
+                path.display()
+            )
+        })?;
 
         Ok(ConversationSession {
             session_id,
@@ -170,8 +255,54 @@ impl ConversationSession {
         })
     }
 
+    /// Describe inconsistencies between this file's name and the session IDs
+    /// its entries carry (empty if consistent)
+    ///
+    /// Such files may be keyed differently on machines using a different
+    /// [`SessionIdSource`]. Subagent files (`agent-*.jsonl`) carry their
+    /// parent's session ID by design, so only mixed IDs are reported for them.
+    pub fn session_id_issues(&self) -> Vec<String> {
+        let mut ids: Vec<&str> = Vec::new();
+        for id in self.entries.iter().filter_map(|e| e.session_id.as_deref()) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        let stem = Path::new(&self.file_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("");
+
+        let mut issues = Vec::new();
+        if ids.len() > 1 {
+            issues.push(format!(
+                "entries carry {} different session IDs ({})",
+                ids.len(),
+                ids.join(", ")
+            ));
+        }
+        if let [first, ..] = ids.as_slice() {
+            if *first != stem && !self.is_agent_file() {
+                issues.push(format!(
+                    "first session ID {first} differs from file name {stem}"
+                ));
+            }
+        }
+        issues
+    }
+
     /// Write the conversation session to a JSONL file
+    #[allow(dead_code)]
     pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.write_to_file_with(path, false)
+    }
+
+    /// Write the conversation session to a JSONL file, optionally preserving raw lines
+    ///
+    /// When `preserve_raw` is true, entries read from disk are written back
+    /// byte-identical to their original lines; only entries without an original
+    /// line (e.g. built during a merge) are re-serialized.
+    pub fn write_to_file_with<P: AsRef<Path>>(&self, path: P, preserve_raw: bool) -> Result<()> {
         let path = path.as_ref();
 
         // Create parent directories if they don't exist
@@ -184,8 +315,7 @@ impl ConversationSession {
             .with_context(|| format!("Failed to create file: {}", path.display()))?;
 
         for entry in &self.entries {
-            let json =
-                serde_json::to_string(entry).context("Failed to serialize conversation entry")?;
+            let json = entry.to_json_line(preserve_raw)?;
             writeln!(file, "{json}")
                 .with_context(|| format!("Failed to write to file: {}", path.display()))?;
         }
@@ -201,6 +331,28 @@ impl ConversationSession {
             .max()
     }
 
+    /// Whether this is a subagent transcript (`agent-*.jsonl`), which carries
+    /// its parent's session ID rather than one of its own
+    pub fn is_agent_file(&self) -> bool {
+        Path::new(&self.file_path)
+            .file_name()
+            .and_then(|s| s.to_str())
+            .is_some_and(|name| name.starts_with("agent-"))
+    }
+
+    /// Whether this whole session is a subagent (sidechain) conversation
+    ///
+    /// True when the session has messages and every user/assistant entry is
+    /// marked as a sidechain.
+    pub fn is_sidechain(&self) -> bool {
+        let mut messages = self
+            .entries
+            .iter()
+            .filter(|e| e.entry_type == "user" || e.entry_type == "assistant")
+            .peekable();
+        messages.peek().is_some() && messages.all(|e| e.is_sidechain())
+    }
+
     /// Get the number of messages (user + assistant) in the conversation
     pub fn message_count(&self) -> usize {
         self.entries
@@ -210,7 +362,12 @@ impl ConversationSession {
     }
 
     /// Calculate a stable hash of the conversation content
-    /// Uses xxhash for cross-platform stability (same result on ARM and x86)
+    ///
+    /// 16 hex digits of xxh3-64 over the entries serialized in order, so
+    /// the result is the same on every platform (ARM and x86 alike) and
+    /// doesn't depend on the file's path or session ID. Two sessions with
+    /// equal hashes are [`Identical`](crate::conflict::SessionRelationship::Identical)
+    /// to [`analyze_session_relationship`](crate::conflict::analyze_session_relationship).
     pub fn content_hash(&self) -> String {
         let mut combined = String::new();
         for entry in &self.entries {
@@ -237,7 +394,20 @@ impl ConversationSession {
 /// - Existing file content is never modified
 /// - Uses `sync_all()` to ensure data reaches disk before returning
 /// - Partial writes during a crash are possible but won't corrupt existing data
+#[allow(dead_code)]
 pub fn append_entries_to_file<P: AsRef<Path>>(path: P, entries: &[ConversationEntry]) -> Result<()> {
+    append_entries_to_file_with(path, entries, false)
+}
+
+/// Append entries to a JSONL file, optionally preserving raw lines.
+///
+/// Same as [`append_entries_to_file`], but when `preserve_raw` is true entries
+/// that carry their original line are appended byte-identical to it.
+pub fn append_entries_to_file_with<P: AsRef<Path>>(
+    path: P,
+    entries: &[ConversationEntry],
+    preserve_raw: bool,
+) -> Result<()> {
     let path = path.as_ref();
 
     // Create parent directories if they don't exist
@@ -253,7 +423,7 @@ pub fn append_entries_to_file<P: AsRef<Path>>(path: P, entries: &[ConversationEn
         .with_context(|| format!("Failed to open file for appending: {}", path.display()))?;
 
     for entry in entries {
-        let json = serde_json::to_string(entry).context("Failed to serialize conversation entry")?;
+        let json = entry.to_json_line(preserve_raw)?;
         writeln!(file, "{json}")
             .with_context(|| format!("Failed to append to file: {}", path.display()))?;
     }
@@ -265,31 +435,327 @@ pub fn append_entries_to_file<P: AsRef<Path>>(path: P, entries: &[ConversationEn
     Ok(())
 }
 
+/// How entries without UUIDs are identified when deduplicating
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKeyStrategy {
+    /// Type, timestamp, and a hash of the whole canonical entry (default)
+    FullEntry,
+    /// Type, timestamp, and a hash of `message` only; other fields are ignored
+    Message,
+    /// Like `FullEntry`, but repeated identical entries stay distinct: the
+    /// nth copy in a session only matches the nth copy on the other side
+    Occurrence,
+}
+
+impl ContentKeyStrategy {
+    /// Parse a strategy name from config
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "full-entry" => Some(ContentKeyStrategy::FullEntry),
+            "message" => Some(ContentKeyStrategy::Message),
+            "occurrence" => Some(ContentKeyStrategy::Occurrence),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentKeyStrategy::FullEntry => "full-entry",
+            ContentKeyStrategy::Message => "message",
+            ContentKeyStrategy::Occurrence => "occurrence",
+        }
+    }
+}
+
+/// Content key strategy per entry type; unlisted types use `FullEntry`
+#[derive(Debug, Clone, Default)]
+pub struct ContentKeyStrategies {
+    by_type: HashMap<String, ContentKeyStrategy>,
+}
+
+impl ContentKeyStrategies {
+    pub fn new(by_type: HashMap<String, ContentKeyStrategy>) -> Self {
+        Self { by_type }
+    }
+
+    /// Strategy for entries of `entry_type`
+    pub fn for_type(&self, entry_type: &str) -> ContentKeyStrategy {
+        self.by_type
+            .get(entry_type)
+            .copied()
+            .unwrap_or(ContentKeyStrategy::FullEntry)
+    }
+}
+
 /// Generate a deduplication key for entries without UUIDs.
 ///
 /// For entries like `file-history-snapshot` that don't have UUIDs, we use
-/// a combination of (type, timestamp, content_hash) for deduplication.
+/// a combination of (type, timestamp, content_hash) for deduplication, where
+/// the hash covers the whole canonical entry.
 ///
 /// Uses xxhash for cross-platform stability (same result on ARM and x86).
 pub fn make_content_key(entry: &ConversationEntry) -> String {
+    make_content_key_with(entry, ContentKeyStrategy::FullEntry)
+}
+
+/// Generate a deduplication key for one entry using `strategy`
+///
+/// `Occurrence` needs the rest of the session to number repeats; see
+/// [`content_keys`]. On its own it produces the `FullEntry` key.
+pub fn make_content_key_with(entry: &ConversationEntry, strategy: ContentKeyStrategy) -> String {
     let ts = entry.timestamp.as_deref().unwrap_or("");
-    let content_hash = entry
-        .message
-        .as_ref()
-        .map(|m| {
-            let json = serde_json::to_string(m).unwrap_or_default();
-            xxhash_rust::xxh3::xxh3_64(json.as_bytes())
-        })
-        .unwrap_or(0);
+    let content_hash = match strategy {
+        ContentKeyStrategy::Message => entry
+            .message
+            .as_ref()
+            .map(|m| xxhash_rust::xxh3::xxh3_64(canonical_json(m).as_bytes()))
+            .unwrap_or(0),
+        ContentKeyStrategy::FullEntry | ContentKeyStrategy::Occurrence => {
+            xxhash_rust::xxh3::xxh3_64(canonical_json(entry).as_bytes())
+        }
+    };
     format!("{}:{}:{:016x}", entry.entry_type, ts, content_hash)
 }
 
+/// Deduplication keys for a session's entries, aligned with `entries`
+///
+/// Entries with a UUID get `None` (they are matched by UUID). Entries whose
+/// type uses [`ContentKeyStrategy::Occurrence`] get their repeat number
+/// appended, so two identical status records in one session are kept apart.
+pub fn content_keys(
+    entries: &[ConversationEntry],
+    strategies: &ContentKeyStrategies,
+) -> Vec<Option<String>> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    entries
+        .iter()
+        .map(|entry| {
+            if entry.uuid.is_some() {
+                return None;
+            }
+            let strategy = strategies.for_type(&entry.entry_type);
+            let key = make_content_key_with(entry, strategy);
+            if strategy != ContentKeyStrategy::Occurrence {
+                return Some(key);
+            }
+            let count = seen.entry(key.clone()).or_insert(0);
+            *count += 1;
+            Some(format!("{key}#{count}"))
+        })
+        .collect()
+}
+
+/// Entries of `candidates` that `existing` lacks
+///
+/// Entries with a UUID are matched by UUID, the rest by content key (see
+/// [`content_keys`]). An entry without a UUID is not missing either if it is
+/// a compacted or scrubbed copy of one in `existing`, or the original of one.
+pub fn missing_entries(
+    existing: &[ConversationEntry],
+    candidates: &[ConversationEntry],
+    strategies: &ContentKeyStrategies,
+) -> Vec<ConversationEntry> {
+    let uuids: HashSet<&str> = existing.iter().filter_map(|e| e.uuid.as_deref()).collect();
+    let keys: HashSet<String> = content_keys(existing, strategies)
+        .into_iter()
+        .flatten()
+        .collect();
+    let without_uuid = || existing.iter().filter(|e| e.uuid.is_none());
+    let hashes: HashSet<String> = without_uuid().map(entry_hash).collect();
+    let originals: HashSet<&str> = without_uuid().filter_map(original_hash).collect();
+    candidates
+        .iter()
+        .zip(content_keys(candidates, strategies))
+        .filter(|(entry, key)| match (&entry.uuid, key) {
+            (Some(uuid), _) => !uuids.contains(uuid.as_str()),
+            (None, key) => {
+                key.as_ref().is_some_and(|k| !keys.contains(k))
+                    && !original_hash(entry).is_some_and(|h| hashes.contains(h))
+                    && !originals.contains(entry_hash(entry).as_str())
+            }
+        })
+        .map(|(entry, _)| entry.clone())
+        .collect()
+}
+
+/// Field compaction and scrubbing add to an entry they rewrite for the sync
+/// repo, holding the [`entry_hash`] of the local original
+pub const ORIGINAL_HASH_FIELD: &str = "syncOriginalHash";
+
+/// 16 hex digits of xxh3-64 over an entry's canonical JSON, leaving out
+/// [`ORIGINAL_HASH_FIELD`]
+pub fn entry_hash(entry: &ConversationEntry) -> String {
+    let hash = |e: &ConversationEntry| {
+        format!(
+            "{:016x}",
+            xxhash_rust::xxh3::xxh3_64(canonical_json(e).as_bytes())
+        )
+    };
+    if original_hash(entry).is_none() {
+        return hash(entry);
+    }
+    let mut unmarked = entry.clone();
+    if let Some(extra) = unmarked.extra.as_object_mut() {
+        extra.remove(ORIGINAL_HASH_FIELD);
+    }
+    hash(&unmarked)
+}
+
+/// Hash of the original a compacted or scrubbed entry was made from
+pub fn original_hash(entry: &ConversationEntry) -> Option<&str> {
+    entry.extra.get(ORIGINAL_HASH_FIELD)?.as_str()
+}
+
+/// Record on `copy` that it is `original` rewritten for the sync repo
+///
+/// A copy of a copy keeps the hash of the first original, so an entry both
+/// compacted and scrubbed still points at the local one.
+pub fn mark_derived(copy: &mut ConversationEntry, original: &ConversationEntry) {
+    let hash = original_hash(original).map_or_else(|| entry_hash(original), str::to_string);
+    if !copy.extra.is_object() {
+        copy.extra = Value::Object(Default::default());
+    }
+    if let Some(extra) = copy.extra.as_object_mut() {
+        extra.insert(ORIGINAL_HASH_FIELD.to_string(), Value::String(hash));
+    }
+}
+
+/// Whether `copy` is `original` compacted or scrubbed for the sync repo
+pub fn is_derived_from(copy: &ConversationEntry, original: &ConversationEntry) -> bool {
+    original_hash(copy).is_some_and(|hash| hash == entry_hash(original))
+}
+
+/// Serialize `value` to canonical JSON for equality checks
+///
+/// Object keys are sorted and numbers normalized (`1.0` and `1` are the
+/// same), so two entries compare equal regardless of the field order or
+/// number formatting of the serde_json version or platform that wrote them.
+pub fn canonical_json<T: Serialize + ?Sized>(value: &T) -> String {
+    let mut out = String::new();
+    match serde_json::to_value(value) {
+        Ok(value) => write_canonical(&value, &mut out),
+        Err(e) => log::debug!("Failed to serialize value for comparison: {}", e),
+    }
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Null | Value::Bool(_) | Value::String(_) => out.push_str(&value.to_string()),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                out.push_str(&i.to_string());
+            } else if let Some(u) = n.as_u64() {
+                out.push_str(&u.to_string());
+            } else if let Some(f) = n.as_f64() {
+                // Integral floats within exact range print as integers
+                if f.fract() == 0.0 && f.abs() < 9_007_199_254_740_992.0 {
+                    out.push_str(&(f as i64).to_string());
+                } else {
+                    out.push_str(&f.to_string());
+                }
+            }
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_canonical_json_ignores_key_order_and_number_format() {
+        let a: Value = serde_json::from_str(r#"{"b":1.0,"a":[{"y":2,"x":"s"}]}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"a":[{"x":"s","y":2.0}],"b":1}"#).unwrap();
+        assert_eq!(canonical_json(&a), canonical_json(&b));
+        assert_eq!(canonical_json(&a), r#"{"a":[{"x":"s","y":2}],"b":1}"#);
+        assert_ne!(canonical_json(&a), canonical_json(&serde_json::json!({"b": 1.5})));
+    }
+
+    #[test]
+    fn test_derived_copies_are_not_missing() {
+        let entry = |line: &str| serde_json::from_str::<ConversationEntry>(line).unwrap();
+        let original = entry(r#"{"type":"summary","summary":"Mail bob@example.com"}"#);
+        let mut copy = entry(r#"{"type":"summary","summary":"Mail [email]"}"#);
+        mark_derived(&mut copy, &original);
+        assert!(is_derived_from(&copy, &original));
+        assert!(!is_derived_from(&original, &copy));
+
+        // A copy of the copy still points at the original
+        let mut again = copy.clone();
+        mark_derived(&mut again, &copy);
+        assert_eq!(original_hash(&again), original_hash(&copy));
+
+        let strategies = ContentKeyStrategies::default();
+        let (original, copy) = (vec![original], vec![copy]);
+        assert!(missing_entries(&original, &copy, &strategies).is_empty());
+        assert!(missing_entries(&copy, &original, &strategies).is_empty());
+
+        // The same text without the marker is an entry of its own
+        let edited = vec![entry(r#"{"type":"summary","summary":"Mail [email]"}"#)];
+        assert_eq!(missing_entries(&original, &edited, &strategies).len(), 1);
+    }
+
+    #[test]
+    fn test_session_id_source_and_issues() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("file-id.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"type":"summary"}"#,
+                "\n",
+                r#"{"type":"user","uuid":"1","sessionId":"old-id"}"#,
+                "\n",
+                r#"{"type":"user","uuid":"2","sessionId":"new-id"}"#,
+                "\n"
+            ),
+        )
+        .unwrap();
+
+        let by_entry = ConversationSession::from_file(&path).unwrap();
+        assert_eq!(by_entry.session_id, "old-id");
+        let by_name = ConversationSession::from_file_with(&path, SessionIdSource::Filename).unwrap();
+        assert_eq!(by_name.session_id, "file-id");
+
+        let issues = by_entry.session_id_issues();
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].contains("old-id, new-id"));
+
+        let consistent = dir.path().join("same.jsonl");
+        std::fs::write(&consistent, r#"{"type":"user","sessionId":"same"}"#).unwrap();
+        assert!(ConversationSession::from_file(&consistent)
+            .unwrap()
+            .session_id_issues()
+            .is_empty());
+    }
+
     #[test]
     fn test_parse_conversation_entry() {
         let json =
@@ -319,6 +785,54 @@ mod tests {
         assert_eq!(reloaded.entries.len(), session.entries.len());
     }
 
+    #[test]
+    fn test_write_preserving_raw_lines_is_byte_identical() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        // Key order and spacing differ from what serde would produce
+        let original = concat!(
+            r#"{"uuid":"1", "type":"user","sessionId":"test-123","timestamp":"2025-01-01T00:00:00Z"}"#,
+            "\n",
+            r#"{"timestamp":"2025-01-01T00:01:00Z","type":"assistant","uuid":"2","sessionId":"test-123"}"#,
+            "\n",
+        );
+        write!(temp_file, "{original}").unwrap();
+
+        let session = ConversationSession::from_file(temp_file.path()).unwrap();
+
+        let raw_out = NamedTempFile::new().unwrap();
+        session.write_to_file_with(raw_out.path(), true).unwrap();
+        assert_eq!(std::fs::read_to_string(raw_out.path()).unwrap(), original);
+
+        let serialized_out = NamedTempFile::new().unwrap();
+        session.write_to_file(serialized_out.path()).unwrap();
+        assert_ne!(std::fs::read_to_string(serialized_out.path()).unwrap(), original);
+
+        // Entries without an original line are still serialized
+        let mut built = session.entries[0].clone();
+        built.raw_line = None;
+        assert_eq!(
+            built.to_json_line(true).unwrap(),
+            serde_json::to_string(&built).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sidechain_detection() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, r#"{{"type":"user","sessionId":"s","uuid":"1","isSidechain":true}}"#).unwrap();
+        writeln!(temp_file, r#"{{"type":"assistant","sessionId":"s","uuid":"2","isSidechain":true}}"#).unwrap();
+        let session = ConversationSession::from_file(temp_file.path()).unwrap();
+        assert!(session.entries[0].is_sidechain());
+        assert!(session.is_sidechain());
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, r#"{{"type":"user","sessionId":"s","uuid":"1","isSidechain":false}}"#).unwrap();
+        writeln!(temp_file, r#"{{"type":"assistant","sessionId":"s","uuid":"2","isSidechain":true}}"#).unwrap();
+        let session = ConversationSession::from_file(temp_file.path()).unwrap();
+        assert!(!session.entries[0].is_sidechain());
+        assert!(!session.is_sidechain());
+    }
+
     #[test]
     fn test_session_id_from_filename() {
         use std::fs::File;
@@ -395,6 +909,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         }
     }
 
@@ -493,6 +1008,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         };
 
         let entry2 = ConversationEntry {
@@ -506,6 +1022,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         };
 
         let key1 = make_content_key(&entry1);
@@ -528,6 +1045,7 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         };
 
         let entry2 = ConversationEntry {
@@ -541,8 +1059,47 @@ mod tests {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         };
 
         assert_eq!(make_content_key(&entry1), make_content_key(&entry2));
     }
+
+    #[test]
+    fn test_content_key_strategies() {
+        let entry = |extra: Value| ConversationEntry {
+            entry_type: "progress".to_string(),
+            uuid: None,
+            parent_uuid: None,
+            session_id: None,
+            timestamp: Some("2025-01-01T00:00:00Z".to_string()),
+            message: None,
+            cwd: None,
+            version: None,
+            git_branch: None,
+            extra,
+            raw_line: None,
+        };
+        let a = entry(serde_json::json!({"step": 1}));
+        let b = entry(serde_json::json!({"step": 2}));
+
+        // Fields outside `message` count unless the type opts out
+        assert_ne!(make_content_key(&a), make_content_key(&b));
+        assert_eq!(
+            make_content_key_with(&a, ContentKeyStrategy::Message),
+            make_content_key_with(&b, ContentKeyStrategy::Message)
+        );
+
+        // Repeated identical records stay distinct with `occurrence`
+        let entries = vec![a.clone(), a.clone(), b];
+        let keys = content_keys(&entries, &ContentKeyStrategies::default());
+        assert_eq!(keys[0], keys[1]);
+        let occurrence = ContentKeyStrategies::new(HashMap::from([(
+            "progress".to_string(),
+            ContentKeyStrategy::Occurrence,
+        )]));
+        let keys = content_keys(&entries, &occurrence);
+        assert_ne!(keys[0], keys[1]);
+        assert_eq!(keys[0], content_keys(&entries[..1], &occurrence)[0]);
+    }
 }
@@ -0,0 +1,236 @@
+//! Splitting oversized sessions across continuation files
+//!
+//! With `max_entries_per_file` set, a session file in the sync repo that holds
+//! more entries than the limit keeps the first N, and the rest go to
+//! `<name>.part01.jsonl`, `<name>.part02.jsonl`, ... next to it. A session that
+//! keeps growing then only changes its last part, which keeps git deltas and
+//! merges small. Local `~/.claude` files are never split.
+//!
+//! Discovery reassembles the parts into one session, so relationship analysis
+//! and merging always see whole sessions. Writes to the sync repo always write
+//! the whole session to the base file; [`split_oversized`] then moves the tail
+//! back out into parts before the repo is committed (or, once splitting is
+//! turned off, folds existing parts back into the base file).
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::parser::{canonical_json, ConversationSession};
+
+/// Base file and part number of a continuation file (`None` for other files)
+pub fn split_part(path: &Path) -> Option<(PathBuf, usize)> {
+    let name = path.file_name()?.to_str()?;
+    let (stem, number) = name.strip_suffix(".jsonl")?.rsplit_once(".part")?;
+    if stem.is_empty() || number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let number = number.parse().ok().filter(|&n| n > 0)?;
+    Some((path.with_file_name(format!("{stem}.jsonl")), number))
+}
+
+/// Whether `path` is a continuation file of a split session
+pub fn is_part_file(path: &Path) -> bool {
+    split_part(path).is_some()
+}
+
+/// Path of continuation file `number` (1-based) of `base`
+pub fn part_path(base: &Path, number: usize) -> PathBuf {
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    base.with_file_name(format!("{stem}.part{number:02}.jsonl"))
+}
+
+/// Lay out the session in `path` and its continuation files so each file
+/// holds at most `max_entries` entries (`None` or 0: everything in `path`)
+///
+/// The base file either still holds the first part, or was overwritten with
+/// the whole session (it then contains the first entry of part 1, compared
+/// as canonical JSON); either way the session's entries are laid out again
+/// and stale continuation files are removed. Works on raw lines, so entries
+/// are moved byte for byte. Returns whether any file changed.
+pub fn split_file(path: &Path, max_entries: Option<usize>) -> Result<bool> {
+    let max_entries = max_entries.filter(|&max| max > 0);
+    if max_entries.is_none() && !part_path(path, 1).exists() {
+        return Ok(false);
+    }
+
+    let read = |path: &Path| {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+    };
+    let mut current = vec![read(path)?];
+    while part_path(path, current.len()).exists() {
+        current.push(read(&part_path(path, current.len()))?);
+    }
+
+    let lines_of = |content: &str| -> Vec<String> {
+        content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let mut lines = lines_of(&current[0]);
+    let continued = current
+        .get(1)
+        .and_then(|part| lines_of(part).into_iter().next())
+        .is_some_and(|first| !lines.iter().any(|l| same_entry(l, &first)));
+    if continued {
+        for part in &current[1..] {
+            lines.extend(lines_of(part));
+        }
+    }
+    if lines.is_empty() {
+        return Ok(false);
+    }
+
+    let desired: Vec<String> = lines
+        .chunks(max_entries.unwrap_or(usize::MAX))
+        .map(|chunk| chunk.join("\n") + "\n")
+        .collect();
+    if desired == current {
+        return Ok(false);
+    }
+    for (number, content) in desired.iter().enumerate() {
+        if current.get(number) != Some(content) {
+            let target = if number == 0 {
+                path.to_path_buf()
+            } else {
+                part_path(path, number)
+            };
+            fs::write(&target, content)
+                .with_context(|| format!("Failed to write {}", target.display()))?;
+        }
+    }
+    for number in desired.len()..current.len() {
+        fs::remove_file(part_path(path, number))?;
+    }
+    log::debug!(
+        "Laid out {} ({} entries) in {} files",
+        path.display(),
+        lines.len(),
+        desired.len()
+    );
+    Ok(true)
+}
+
+fn same_entry(a: &str, b: &str) -> bool {
+    let parse = |line: &str| serde_json::from_str::<serde_json::Value>(line).ok();
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => canonical_json(&a) == canonical_json(&b),
+        _ => a == b,
+    }
+}
+
+/// Lay out every session file under `dir` with [`split_file`]; returns the
+/// number of sessions whose files changed
+pub fn split_oversized(dir: &Path, max_entries: Option<usize>) -> Result<usize> {
+    let mut split = 0;
+    for entry in WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+        .filter(|e| !is_part_file(e.path()))
+    {
+        if split_file(entry.path(), max_entries)? {
+            split += 1;
+        }
+    }
+    Ok(split)
+}
+
+/// Append each continuation file's entries to its base session and drop the
+/// continuation files from the list
+///
+/// Parts whose base file is not in the list are dropped too.
+pub fn reassemble(sessions: Vec<ConversationSession>) -> Vec<ConversationSession> {
+    let mut parts: BTreeMap<PathBuf, Vec<(usize, ConversationSession)>> = BTreeMap::new();
+    let mut whole = Vec::with_capacity(sessions.len());
+    for session in sessions {
+        match split_part(Path::new(&session.file_path)) {
+            Some((base, number)) => parts.entry(base).or_default().push((number, session)),
+            None => whole.push(session),
+        }
+    }
+
+    for session in &mut whole {
+        if let Some(mut session_parts) = parts.remove(Path::new(&session.file_path)) {
+            session_parts.sort_by_key(|(number, _)| *number);
+            for (_, part) in session_parts {
+                session.entries.extend(part.entries);
+            }
+        }
+    }
+    for base in parts.keys() {
+        log::debug!("Ignoring continuation files of {}", base.display());
+    }
+    whole
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_part_names() {
+        let base = Path::new("/p/abc.jsonl");
+        assert_eq!(part_path(base, 3), Path::new("/p/abc.part03.jsonl"));
+        assert_eq!(
+            split_part(Path::new("/p/abc.part03.jsonl")),
+            Some((base.to_path_buf(), 3))
+        );
+        assert_eq!(split_part(base), None);
+        assert_eq!(split_part(Path::new("/p/abc.part.jsonl")), None);
+        assert_eq!(split_part(Path::new("/p/abc.part00.jsonl")), None);
+    }
+
+    #[test]
+    fn test_split_and_reassemble() {
+        let temp = TempDir::new().unwrap();
+        let base = temp.path().join("s1.jsonl");
+        let line = |i: usize| format!(r#"{{"type":"user","uuid":"u{i}","sessionId":"s1"}}"#);
+        let write = |count: usize| {
+            let lines: Vec<String> = (0..count).map(line).collect();
+            fs::write(&base, lines.join("\n") + "\n").unwrap();
+        };
+
+        write(7);
+        assert!(split_file(&base, Some(3)).unwrap());
+        assert!(part_path(&base, 2).exists());
+        assert!(!split_file(&base, Some(3)).unwrap());
+
+        let sessions: Vec<ConversationSession> =
+            [base.clone(), part_path(&base, 2), part_path(&base, 1)]
+                .iter()
+                .map(|p| ConversationSession::from_file(p).unwrap())
+                .collect();
+        let sessions = reassemble(sessions);
+        assert_eq!(sessions.len(), 1);
+        let uuids: Vec<_> = sessions[0]
+            .entries
+            .iter()
+            .filter_map(|e| e.uuid.clone())
+            .collect();
+        assert_eq!(uuids, (0..7).map(|i| format!("u{i}")).collect::<Vec<_>>());
+
+        // The whole session written back to the base file is laid out
+        // again without duplicating the parts
+        write(8);
+        assert!(split_file(&base, Some(5)).unwrap());
+        assert_eq!(
+            fs::read_to_string(part_path(&base, 1))
+                .unwrap()
+                .lines()
+                .count(),
+            3
+        );
+        assert!(!part_path(&base, 2).exists());
+
+        // Turning splitting off folds the parts back in
+        assert!(split_file(&base, None).unwrap());
+        assert_eq!(fs::read_to_string(&base).unwrap().lines().count(), 8);
+        assert!(!part_path(&base, 1).exists());
+    }
+}
@@ -0,0 +1,144 @@
+//! Claude Code project directory names
+//!
+//! Claude Code stores each project's sessions under `projects/<name>/`, where
+//! `<name>` is the project's working directory with every character other
+//! than an ASCII letter or digit replaced by `-` (`/home/me/my_app` becomes
+//! `-home-me-my-app`). The encoding is lossy, so turning a name back into a
+//! path is either a best guess ([`decode_project_dir`]) or a search of the
+//! filesystem for a directory that encodes to it ([`resolve_project_dir`]).
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Encode a project's working directory the way Claude Code names its
+/// projects directory
+pub fn encode_project_path(path: &Path) -> String {
+    path.to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Best-guess path for a project directory name, reading every `-` as `/`
+///
+/// Wrong for paths containing `-`, `_`, `.`, or spaces; use
+/// [`resolve_project_dir`] when the directory should exist locally.
+pub fn decode_project_dir(name: &str) -> PathBuf {
+    PathBuf::from(name.replace('-', "/"))
+}
+
+/// Find the existing directory whose encoding is `name`, searching from `/`
+pub fn resolve_project_dir(name: &str) -> Option<PathBuf> {
+    // Fast path: most paths contain no characters that encode to `-`
+    let guess = decode_project_dir(name);
+    if guess.is_dir() && encode_project_path(&guess) == name {
+        return Some(guess);
+    }
+    resolve_project_dir_in(Path::new("/"), name)
+}
+
+/// Find the directory under `root` whose encoding (as an absolute path
+/// below `root`) is `name`
+pub fn resolve_project_dir_in(root: &Path, name: &str) -> Option<PathBuf> {
+    let rest = name.strip_prefix('-')?;
+    if rest.is_empty() {
+        return Some(root.to_path_buf());
+    }
+
+    // Each path component encodes to a run of the name followed by `-` (or
+    // the end); try every child whose encoding fits
+    let children = fs::read_dir(root).ok()?;
+    for child in children.filter_map(|e| e.ok()) {
+        if !child.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let encoded = encode_project_path(Path::new(&child.file_name()));
+        let Some(after) = rest.strip_prefix(encoded.as_str()) else {
+            continue;
+        };
+        if after.is_empty() {
+            return Some(child.path());
+        }
+        if after.starts_with('-') {
+            if let Some(found) = resolve_project_dir_in(&child.path(), after) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// Human-readable project path for a directory name, e.g. `~/code/my-app`
+///
+/// Uses the real directory when it exists on this machine, else the
+/// best-guess decoding. Paths under the home directory are shown with `~`.
+pub fn project_display_name(name: &str) -> String {
+    let path = resolve_project_dir(name).unwrap_or_else(|| decode_project_dir(name));
+    match dirs::home_dir() {
+        Some(home) => shorten_home(&path, &home),
+        None => path.display().to_string(),
+    }
+}
+
+fn shorten_home(path: &Path, home: &Path) -> String {
+    match path.strip_prefix(home) {
+        Ok(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Ok(rest) => format!("~/{}", rest.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// Project directory name of a session file, given its path relative to a
+/// projects directory (`None` for files directly in the projects directory)
+pub fn project_dir_name(relative: &Path) -> Option<String> {
+    let mut components = relative.components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(project)), Some(_)) => Some(project.to_string_lossy().to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_encode_and_decode() {
+        assert_eq!(
+            encode_project_path(Path::new("/home/me/my_app.v2")),
+            "-home-me-my-app-v2"
+        );
+        assert_eq!(
+            decode_project_dir("-home-me-app"),
+            PathBuf::from("/home/me/app")
+        );
+        assert_eq!(
+            project_dir_name(Path::new("-home-me-app/s1.jsonl")),
+            Some("-home-me-app".to_string())
+        );
+        assert_eq!(project_dir_name(Path::new("s1.jsonl")), None);
+        assert_eq!(
+            shorten_home(Path::new("/home/me/code/app"), Path::new("/home/me")),
+            "~/code/app"
+        );
+        assert_eq!(
+            shorten_home(Path::new("/srv/app"), Path::new("/home/me")),
+            "/srv/app"
+        );
+    }
+
+    #[test]
+    fn test_resolve_handles_dashes_in_names() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("work/my-app/sub_dir");
+        fs::create_dir_all(&project).unwrap();
+        fs::create_dir_all(temp.path().join("work/my")).unwrap();
+
+        assert_eq!(
+            resolve_project_dir_in(temp.path(), "-work-my-app-sub-dir"),
+            Some(project)
+        );
+        assert_eq!(resolve_project_dir_in(temp.path(), "-work-missing"), None);
+    }
+}
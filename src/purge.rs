@@ -0,0 +1,189 @@
+//! Purging sessions from the sync repo's history
+//!
+//! Deleting a session leaves it in every earlier commit, and a secret that
+//! was pushed stays readable by anyone with the repo. A purge rewrites the
+//! history of the sync branch without the session's files and its
+//! `history.jsonl` entries, then force-pushes it.
+//!
+//! Other clones still hold the old history, and a plain pull would rebase it
+//! back onto the rewritten branch. Each purge is recorded in a sidecar index
+//! (`purges.json`) at the root of the sync repo; a pull that finds a purge in
+//! the remote's index but not its own resets to the remote instead of
+//! merging with it.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::parts::split_part;
+
+/// File name of the purge index within the sync repo
+pub const PURGE_INDEX_FILE: &str = "purges.json";
+
+/// Record of a history rewrite
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Purge {
+    pub purged_at: DateTime<Utc>,
+    /// Machine the purge ran on
+    pub machine_id: String,
+    /// Sessions removed from the history
+    pub sessions: BTreeSet<String>,
+}
+
+/// Mapping of purge ID to its record
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PurgeIndex {
+    #[serde(flatten)]
+    pub purges: BTreeMap<String, Purge>,
+}
+
+impl PurgeIndex {
+    /// Path of the purge index within a sync repo
+    pub fn path(sync_repo: &Path) -> PathBuf {
+        sync_repo.join(PURGE_INDEX_FILE)
+    }
+
+    /// Load the purge index from a sync repo (empty if it doesn't exist)
+    pub fn load(sync_repo: &Path) -> Result<Self> {
+        let path = Self::path(sync_repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read purge index: {}", path.display()))?;
+        Self::parse(&content)
+    }
+
+    /// Parse a purge index, e.g. one read at a remote revision
+    pub fn parse(content: &str) -> Result<Self> {
+        serde_json::from_str(content).context("Failed to parse purge index")
+    }
+
+    /// Save the purge index to a sync repo
+    pub fn save(&self, sync_repo: &Path) -> Result<()> {
+        let path = Self::path(sync_repo);
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize purge index")?;
+        fs::write(&path, content + "\n")
+            .with_context(|| format!("Failed to write purge index: {}", path.display()))
+    }
+
+    /// Record a purge made now, returning its ID
+    pub fn add(&mut self, machine_id: &str, sessions: BTreeSet<String>) -> String {
+        let purged_at = Utc::now();
+        let id = format!("{}-{}", purged_at.format("%Y%m%dT%H%M%S%.3f"), machine_id);
+        self.purges.insert(
+            id.clone(),
+            Purge {
+                purged_at,
+                machine_id: machine_id.to_string(),
+                sessions,
+            },
+        );
+        id
+    }
+
+    /// Purges in `other` that this index doesn't know about
+    pub fn missing_from<'a>(&self, other: &'a PurgeIndex) -> Vec<(&'a String, &'a Purge)> {
+        other
+            .purges
+            .iter()
+            .filter(|(id, _)| !self.purges.contains_key(*id))
+            .collect()
+    }
+}
+
+/// What to purge: a session, or files whose repo path matches a pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurgeTarget {
+    Session(String),
+    /// `*` wildcards; without one, matches paths containing the pattern
+    Pattern(String),
+}
+
+impl PurgeTarget {
+    /// Whether a reference is a path pattern rather than a session ID
+    pub fn is_pattern(reference: &str) -> bool {
+        reference.contains('*') || reference.contains('/')
+    }
+
+    /// Paths in `files` (repo-relative) that the target covers
+    pub fn matching_paths<'a>(&self, files: &'a [String]) -> Vec<&'a String> {
+        files
+            .iter()
+            .filter(|file| match self {
+                PurgeTarget::Session(id) => {
+                    file_session_id(file).as_deref() == Some(id.as_str())
+                        || file.split('/').any(|component| component == id)
+                }
+                PurgeTarget::Pattern(pattern) => crate::filter::glob_match(pattern, file),
+            })
+            .collect()
+    }
+}
+
+/// Session ID of a session file path (continuation files included), or
+/// `None` for other files such as `history.jsonl` and subagent transcripts
+pub fn file_session_id(path: &str) -> Option<String> {
+    let path = Path::new(path);
+    let base = split_part(path).map_or_else(|| path.to_path_buf(), |(base, _)| base);
+    if base.extension()? != "jsonl" || base.parent()?.as_os_str().is_empty() {
+        return None;
+    }
+    let stem = base.file_stem()?.to_string_lossy().to_string();
+    (!stem.starts_with("agent-")).then_some(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_target_matching() {
+        let files: Vec<String> = [
+            "history.jsonl",
+            "projects/-home-a/s1.jsonl",
+            "projects/-home-a/s1.part01.jsonl",
+            "projects/-home-a/s1/subagents/agent-1.jsonl",
+            "projects/-home-a/s2.jsonl",
+            "projects/-home-b/s3.jsonl",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let session = PurgeTarget::Session("s1".to_string());
+        assert_eq!(session.matching_paths(&files).len(), 3);
+        assert!(!PurgeTarget::is_pattern("s1"));
+
+        assert!(PurgeTarget::is_pattern("projects/-home-a/*"));
+        let pattern = PurgeTarget::Pattern("projects/-home-b/*".to_string());
+        assert_eq!(pattern.matching_paths(&files), [&files[5]]);
+
+        assert_eq!(file_session_id(&files[2]).as_deref(), Some("s1"));
+        assert_eq!(file_session_id(&files[3]), None);
+        assert_eq!(file_session_id("history.jsonl"), None);
+    }
+
+    #[test]
+    fn test_missing_purges() {
+        let temp = TempDir::new().unwrap();
+        let mut local = PurgeIndex::default();
+        let first = local.add("m1", BTreeSet::from(["s1".to_string()]));
+        local.save(temp.path()).unwrap();
+
+        let mut remote = PurgeIndex::load(temp.path()).unwrap();
+        assert!(local.missing_from(&remote).is_empty());
+        let second = remote.add("m2", BTreeSet::from(["s2".to_string()]));
+        assert_ne!(first, second);
+
+        let missing = local.missing_from(&remote);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].0, &second);
+        assert_eq!(missing[0].1.machine_id, "m2");
+    }
+}
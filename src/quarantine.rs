@@ -0,0 +1,126 @@
+//! Quarantine for local sessions that a sync would shrink
+//!
+//! Pulls only ever add entries to local session files. If a whole-file write
+//! would leave a local session with fewer entries than it has now, something
+//! has gone wrong: the write is skipped, the current file is copied into a
+//! timestamped quarantine directory under the config dir, and a warning is
+//! printed so the loss can't go unnoticed.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ConfigManager;
+use crate::glyphs;
+
+/// Count the entries (non-empty lines) in a session file
+pub fn count_entries(path: &Path) -> Result<usize> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(content.lines().filter(|l| !l.trim().is_empty()).count())
+}
+
+/// Quarantine `local_path` if replacing it with `new_entry_count` entries would shrink it
+///
+/// Returns the quarantine copy's path when the caller must skip its write,
+/// or `None` when the write is safe (including when the file doesn't exist).
+pub fn quarantine_if_shrinking(
+    local_path: &Path,
+    relative_path: &Path,
+    new_entry_count: usize,
+) -> Result<Option<PathBuf>> {
+    quarantine_into(
+        &ConfigManager::quarantine_dir()?,
+        local_path,
+        relative_path,
+        new_entry_count,
+    )
+}
+
+fn quarantine_into(
+    quarantine_dir: &Path,
+    local_path: &Path,
+    relative_path: &Path,
+    new_entry_count: usize,
+) -> Result<Option<PathBuf>> {
+    if !local_path.exists() {
+        return Ok(None);
+    }
+    let current = count_entries(local_path)?;
+    if new_entry_count >= current {
+        return Ok(None);
+    }
+
+    let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    let dest = quarantine_dir
+        .join(timestamp.to_string())
+        .join(relative_path);
+    copy_to(local_path, &dest)?;
+
+    log::error!(
+        "Refusing to shrink {} from {} to {} entries; quarantined copy at {}",
+        local_path.display(),
+        current,
+        new_entry_count,
+        dest.display()
+    );
+    eprintln!(
+        "  {} {}",
+        "!".red().bold(),
+        format!(
+            "Refused to shrink {} ({} -> {} entries)",
+            local_path.display(),
+            current,
+            new_entry_count
+        )
+        .red()
+        .bold()
+    );
+    eprintln!(
+        "    {} local file left untouched; copy saved to {}",
+        glyphs::detail().dimmed(),
+        dest.display()
+    );
+
+    Ok(Some(dest))
+}
+
+fn copy_to(source: &Path, dest: &Path) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::copy(source, dest).with_context(|| {
+        format!(
+            "Failed to quarantine {} to {}",
+            source.display(),
+            dest.display()
+        )
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_quarantine_only_when_shrinking() {
+        let temp = TempDir::new().unwrap();
+        let quarantine = temp.path().join("quarantine");
+        let local = temp.path().join("s.jsonl");
+        let relative = Path::new("-proj/s.jsonl");
+        assert_eq!(quarantine_into(&quarantine, &local, relative, 0).unwrap(), None);
+
+        fs::write(&local, "{}\n\n{}\n{}\n").unwrap();
+        assert_eq!(count_entries(&local).unwrap(), 3);
+        assert_eq!(quarantine_into(&quarantine, &local, relative, 3).unwrap(), None);
+
+        let dest = quarantine_into(&quarantine, &local, relative, 2).unwrap().unwrap();
+        assert!(dest.ends_with("-proj/s.jsonl"));
+        assert!(dest.starts_with(&quarantine));
+        assert_eq!(fs::read_to_string(dest).unwrap(), "{}\n\n{}\n{}\n");
+    }
+}
@@ -248,7 +248,11 @@ pub fn generate_report(format: &str, output: Option<&Path>) -> Result<()> {
     // Load the latest conflict report from the sync state
     // For now, we'll create a placeholder implementation
     let report = load_latest_report()?;
+    output_report(&report, format, output)
+}
 
+/// Print `report` in `format`, or save it to `output` if given
+pub fn output_report(report: &ConflictReport, format: &str, output: Option<&Path>) -> Result<()> {
     if let Some(output_path) = output {
         report.save(output_path, format)?;
     } else {
@@ -286,7 +290,10 @@ pub fn load_latest_report() -> Result<ConflictReport> {
 }
 
 /// Save a conflict report to the sync state
-pub fn save_conflict_report(report: &ConflictReport) -> Result<()> {
+///
+/// The report becomes the latest report (shown by `report`) and is archived
+/// in the report history. Returns the archived report's ID.
+pub fn save_conflict_report(report: &ConflictReport, operation: &str) -> Result<String> {
     let sync_state_path = get_sync_state_dir()?;
     fs::create_dir_all(&sync_state_path).context("Failed to create sync state directory")?;
 
@@ -296,12 +303,159 @@ pub fn save_conflict_report(report: &ConflictReport) -> Result<()> {
     fs::write(&report_path, content)
         .with_context(|| format!("Failed to write report to {}", report_path.display()))?;
 
-    Ok(())
+    let filter = crate::filter::FilterConfig::load().unwrap_or_default();
+    archive_report(
+        &crate::config::ConfigManager::conflict_reports_dir()?,
+        report,
+        operation,
+        filter.conflict_report_max_count,
+        filter.conflict_report_retention_days,
+    )
+}
+
+/// File listing archived reports, inside the reports directory
+const REPORT_INDEX_FILE: &str = "index.json";
+
+/// Summary of one archived conflict report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportIndexEntry {
+    /// Report ID (also its file name without `.json`)
+    pub id: String,
+    /// When the report was generated (RFC 3339)
+    pub timestamp: String,
+    /// Operation that generated the report (e.g. "pull")
+    pub operation: String,
+    pub total_conflicts: usize,
+    /// Sessions that were in conflict
+    pub session_ids: Vec<String>,
+}
+
+/// Index of archived conflict reports, oldest first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReportIndex {
+    pub reports: Vec<ReportIndexEntry>,
+}
+
+impl ReportIndex {
+    /// Load the index from a reports directory (empty if it doesn't exist)
+    pub fn load(reports_dir: &Path) -> Result<Self> {
+        let path = reports_dir.join(REPORT_INDEX_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read report index: {}", path.display()))?;
+        serde_json::from_str(&content).context("Failed to parse report index")
+    }
+
+    fn save(&self, reports_dir: &Path) -> Result<()> {
+        let path = reports_dir.join(REPORT_INDEX_FILE);
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize report index")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write report index: {}", path.display()))
+    }
+
+    /// Find an entry by ID or unique ID prefix
+    pub fn find(&self, id: &str) -> Result<&ReportIndexEntry> {
+        let matches: Vec<_> = self.reports.iter().filter(|r| r.id.starts_with(id)).collect();
+        match matches.as_slice() {
+            [] => Err(anyhow::anyhow!("No conflict report '{id}'")),
+            [entry] => Ok(entry),
+            _ => Err(anyhow::anyhow!(
+                "'{id}' matches {} conflict reports; use more of the ID",
+                matches.len()
+            )),
+        }
+    }
+}
+
+/// Load an archived report by ID
+pub fn load_archived_report(reports_dir: &Path, id: &str) -> Result<ConflictReport> {
+    let path = reports_dir.join(format!("{id}.json"));
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read report from {}", path.display()))?;
+    serde_json::from_str(&content).context("Failed to parse conflict report")
+}
+
+/// Remove an archived report and its index entry
+///
+/// Returns `false` if there was no such report (e.g. it was already pruned).
+pub fn remove_archived_report(reports_dir: &Path, id: &str) -> Result<bool> {
+    let mut index = ReportIndex::load(reports_dir)?;
+    let indexed = index.reports.len();
+    index.reports.retain(|entry| entry.id != id);
+    let listed = index.reports.len() != indexed;
+    if listed {
+        index.save(reports_dir)?;
+    }
+
+    let path = reports_dir.join(format!("{id}.json"));
+    let existed = path.exists();
+    if existed {
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove report {}", path.display()))?;
+    }
+    Ok(listed || existed)
+}
+
+/// Write `report` into `reports_dir`, add it to the index, and prune
+///
+/// Keeps at most `max_count` reports and drops reports older than
+/// `retention_days`; zero disables either limit.
+pub fn archive_report(
+    reports_dir: &Path,
+    report: &ConflictReport,
+    operation: &str,
+    max_count: usize,
+    retention_days: u32,
+) -> Result<String> {
+    fs::create_dir_all(reports_dir)
+        .with_context(|| format!("Failed to create directory: {}", reports_dir.display()))?;
+
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let id = format!(
+        "{}-{}",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+        &suffix[..6]
+    );
+    let path = reports_dir.join(format!("{id}.json"));
+    fs::write(&path, report.to_json()?)
+        .with_context(|| format!("Failed to write report to {}", path.display()))?;
+
+    let mut index = ReportIndex::load(reports_dir)?;
+    index.reports.push(ReportIndexEntry {
+        id: id.clone(),
+        timestamp: report.timestamp.clone(),
+        operation: operation.to_string(),
+        total_conflicts: report.total_conflicts,
+        session_ids: report.conflicts.iter().map(|c| c.session_id.clone()).collect(),
+    });
+
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(i64::from(retention_days));
+    let expired = |entry: &ReportIndexEntry| {
+        retention_days > 0
+            && chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+                .is_ok_and(|t| t < cutoff)
+    };
+    let mut keep_from = index.reports.iter().take_while(|e| expired(e)).count();
+    if max_count > 0 {
+        keep_from = keep_from.max(index.reports.len().saturating_sub(max_count));
+    }
+    for entry in index.reports.drain(..keep_from) {
+        let old = reports_dir.join(format!("{}.json", entry.id));
+        if let Err(e) = fs::remove_file(&old) {
+            log::warn!("Failed to remove old conflict report {}: {}", old.display(), e);
+        }
+    }
+
+    index.save(reports_dir)?;
+    Ok(id)
 }
 
 /// Get the sync state directory
 fn get_sync_state_dir() -> Result<std::path::PathBuf> {
-    crate::config::ConfigManager::config_dir()
+    crate::config::ConfigManager::state_dir()
 }
 
 #[cfg(test)]
@@ -315,6 +469,24 @@ mod tests {
         assert!(report.conflicts.is_empty());
     }
 
+    #[test]
+    fn test_archive_prunes_to_max_count() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let report = ConflictReport::from_conflicts(&[]);
+
+        let ids: Vec<_> = (0..3)
+            .map(|_| archive_report(temp.path(), &report, "pull", 2, 0).unwrap())
+            .collect();
+
+        let index = ReportIndex::load(temp.path()).unwrap();
+        let kept: Vec<_> = index.reports.iter().map(|r| r.id.clone()).collect();
+        assert_eq!(kept, ids[1..]);
+        assert!(!temp.path().join(format!("{}.json", ids[0])).exists());
+        assert!(load_archived_report(temp.path(), &ids[2]).is_ok());
+        assert_eq!(index.find(&ids[1]).unwrap().operation, "pull");
+        assert!(index.find("nope").is_err());
+    }
+
     #[test]
     fn test_markdown_generation() {
         let report = ConflictReport {
@@ -0,0 +1,209 @@
+//! Recorded conflict resolutions
+//!
+//! When a conflict is resolved by hand during pull, the decision is stored in
+//! a sidecar index (`resolutions.json`) at the root of the sync repo, next to
+//! `tags.json`, so it travels to other machines. A later pull on any machine
+//! that hits the same fork replays the decision instead of prompting again.
+//!
+//! A fork is identified by the entry UUIDs on both sides at decision time. A
+//! recorded decision only applies while neither side has entries outside
+//! that set; anything new since then means a new fork, which is prompted for.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::filter::ConflictPolicy;
+use crate::parser::ConversationSession;
+
+/// File name of the resolution index within the sync repo
+pub const RESOLUTION_INDEX_FILE: &str = "resolutions.json";
+
+/// Decisions kept per session; older ones are dropped
+const MAX_RESOLUTIONS_PER_SESSION: usize = 5;
+
+/// Which version(s) a conflict resolution kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kept {
+    Local,
+    Remote,
+    /// Local, with remote saved alongside under a conflict suffix
+    Both,
+}
+
+/// One recorded decision for a diverged session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResolution {
+    /// Version kept, from the perspective of the machine that chose it
+    pub kept: Kept,
+    /// Every entry UUID on either side when the decision was made
+    pub fork_uuids: BTreeSet<String>,
+    /// Entry UUIDs of the version that was kept
+    pub winning_uuids: BTreeSet<String>,
+    /// Machine the decision was made on
+    pub machine_id: String,
+    pub resolved_at: DateTime<Utc>,
+}
+
+/// Mapping of session ID to its recorded decisions, oldest first
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ResolutionIndex {
+    #[serde(flatten)]
+    pub sessions: BTreeMap<String, Vec<RecordedResolution>>,
+}
+
+impl ResolutionIndex {
+    /// Path of the resolution index within a sync repo
+    pub fn path(sync_repo: &Path) -> PathBuf {
+        sync_repo.join(RESOLUTION_INDEX_FILE)
+    }
+
+    /// Load the resolution index from a sync repo (empty if it doesn't exist)
+    pub fn load(sync_repo: &Path) -> Result<Self> {
+        let path = Self::path(sync_repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read resolution index: {}", path.display()))?;
+        serde_json::from_str(&content).context("Failed to parse resolution index")
+    }
+
+    /// Save the resolution index to a sync repo
+    pub fn save(&self, sync_repo: &Path) -> Result<()> {
+        let path = Self::path(sync_repo);
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize resolution index")?;
+        fs::write(&path, content + "\n")
+            .with_context(|| format!("Failed to write resolution index: {}", path.display()))
+    }
+
+    /// Record a decision made between `local` and `remote`
+    pub fn record(
+        &mut self,
+        kept: Kept,
+        local: &ConversationSession,
+        remote: &ConversationSession,
+        machine_id: &str,
+    ) {
+        let local_uuids = entry_uuids(local);
+        let remote_uuids = entry_uuids(remote);
+        let winning_uuids = match kept {
+            Kept::Local | Kept::Both => local_uuids.clone(),
+            Kept::Remote => remote_uuids.clone(),
+        };
+
+        let recorded = self.sessions.entry(local.session_id.clone()).or_default();
+        recorded.push(RecordedResolution {
+            kept,
+            fork_uuids: local_uuids.union(&remote_uuids).cloned().collect(),
+            winning_uuids,
+            machine_id: machine_id.to_string(),
+            resolved_at: Utc::now(),
+        });
+        let excess = recorded.len().saturating_sub(MAX_RESOLUTIONS_PER_SESSION);
+        recorded.drain(..excess);
+    }
+
+    /// The policy that replays a recorded decision for this fork, if any
+    ///
+    /// `Local`/`Remote` decisions are replayed by keeping whichever side now
+    /// holds the winning version, so a decision made on another machine (where
+    /// "local" and "remote" are swapped) still picks the same version.
+    pub fn replay(
+        &self,
+        local: &ConversationSession,
+        remote: &ConversationSession,
+    ) -> Option<ConflictPolicy> {
+        let local_uuids = entry_uuids(local);
+        let remote_uuids = entry_uuids(remote);
+
+        self.sessions
+            .get(&local.session_id)?
+            .iter()
+            .rev()
+            .filter(|r| {
+                local_uuids.is_subset(&r.fork_uuids) && remote_uuids.is_subset(&r.fork_uuids)
+            })
+            .find_map(|r| match r.kept {
+                Kept::Both => Some(ConflictPolicy::KeepBoth),
+                _ if local_uuids == r.winning_uuids => Some(ConflictPolicy::PreferLocal),
+                _ if remote_uuids == r.winning_uuids => Some(ConflictPolicy::PreferRemote),
+                _ => None,
+            })
+    }
+}
+
+fn entry_uuids(session: &ConversationSession) -> BTreeSet<String> {
+    session
+        .entries
+        .iter()
+        .filter_map(|e| e.uuid.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn session(uuids: &[&str]) -> ConversationSession {
+        ConversationSession {
+            session_id: "s1".to_string(),
+            entries: uuids
+                .iter()
+                .map(|u| {
+                    serde_json::from_str(&format!(r#"{{"type":"user","uuid":"{u}"}}"#)).unwrap()
+                })
+                .collect(),
+            file_path: "s1.jsonl".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_replay_on_other_machine() {
+        let a_version = session(&["1", "2", "a"]);
+        let b_version = session(&["1", "2", "b"]);
+
+        // Machine A keeps its own version
+        let mut index = ResolutionIndex::default();
+        index.record(Kept::Local, &a_version, &b_version, "machine-a");
+
+        // On machine B the sides are swapped; A's version (now remote) wins
+        assert_eq!(
+            index.replay(&b_version, &a_version),
+            Some(ConflictPolicy::PreferRemote)
+        );
+        // Back on A, the same fork keeps local
+        assert_eq!(
+            index.replay(&a_version, &b_version),
+            Some(ConflictPolicy::PreferLocal)
+        );
+        // New entries since the decision make it a new fork
+        assert_eq!(
+            index.replay(&session(&["1", "2", "b", "c"]), &a_version),
+            None
+        );
+    }
+
+    #[test]
+    fn test_save_load_and_cap() {
+        let temp = TempDir::new().unwrap();
+        let mut index = ResolutionIndex::default();
+        for _ in 0..MAX_RESOLUTIONS_PER_SESSION + 2 {
+            index.record(Kept::Both, &session(&["1"]), &session(&["2"]), "m");
+        }
+        index.save(temp.path()).unwrap();
+
+        let loaded = ResolutionIndex::load(temp.path()).unwrap();
+        assert_eq!(loaded.sessions["s1"].len(), MAX_RESOLUTIONS_PER_SESSION);
+        assert_eq!(
+            loaded.replay(&session(&["2"]), &session(&["1"])),
+            Some(ConflictPolicy::KeepBoth)
+        );
+    }
+}
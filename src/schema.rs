@@ -0,0 +1,270 @@
+//! JSONL schema drift detection
+//!
+//! Claude Code's session format is not versioned, and anything the parser does
+//! not model explicitly is carried through [`ConversationEntry::extra`]. That
+//! keeps sync tolerant of new fields, but it also means new record kinds pass
+//! through unnoticed. This module scans sessions and reports entry types and
+//! top-level fields that are not in the known schema.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::parser::{ConversationEntry, ConversationSession};
+
+/// Entry types Claude Code is known to write
+pub const KNOWN_ENTRY_TYPES: &[&str] = &[
+    "assistant",
+    "file-history-snapshot",
+    "queue-operation",
+    "summary",
+    "system",
+    "user",
+];
+
+/// Top-level fields that are known but stored in `extra` rather than modeled
+pub const KNOWN_EXTRA_FIELDS: &[&str] = &[
+    "agentId",
+    "compactMetadata",
+    "content",
+    "isApiErrorMessage",
+    "isCompactSummary",
+    "isMeta",
+    "isSidechain",
+    "isSnapshotUpdate",
+    "isVisibleInTranscriptOnly",
+    "leafUuid",
+    "level",
+    "logicalParentUuid",
+    "messageId",
+    "operation",
+    "requestId",
+    "slug",
+    "snapshot",
+    "subtype",
+    "summary",
+    "thinkingMetadata",
+    "todos",
+    "toolUseID",
+    "toolUseResult",
+    "userType",
+];
+
+/// Where and how often an unknown type or field was seen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaSighting {
+    /// Number of entries it appeared in
+    pub count: usize,
+
+    /// Session file of the first entry it appeared in
+    pub example_file: String,
+}
+
+/// Report of unknown entry types and fields found in session files
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SchemaReport {
+    /// ISO 8601 timestamp indicating when this report was generated
+    pub timestamp: String,
+
+    /// Number of session files scanned
+    pub sessions_scanned: usize,
+
+    /// Number of entries scanned
+    pub entries_scanned: usize,
+
+    /// Entry `type` values not in [`KNOWN_ENTRY_TYPES`]
+    pub unknown_entry_types: BTreeMap<String, SchemaSighting>,
+
+    /// Top-level fields not in the parser schema or [`KNOWN_EXTRA_FIELDS`]
+    pub unknown_fields: BTreeMap<String, SchemaSighting>,
+}
+
+impl Default for SchemaReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SchemaReport {
+    /// Create an empty report
+    pub fn new() -> Self {
+        SchemaReport {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            sessions_scanned: 0,
+            entries_scanned: 0,
+            unknown_entry_types: BTreeMap::new(),
+            unknown_fields: BTreeMap::new(),
+        }
+    }
+
+    /// Record all entries of a session
+    pub fn record_session(&mut self, session: &ConversationSession) {
+        self.sessions_scanned += 1;
+        for entry in &session.entries {
+            self.record_entry(entry, &session.file_path);
+        }
+    }
+
+    /// Record a single entry, noting any unknown type or fields
+    pub fn record_entry(&mut self, entry: &ConversationEntry, file_path: &str) {
+        self.entries_scanned += 1;
+
+        if !KNOWN_ENTRY_TYPES.contains(&entry.entry_type.as_str()) {
+            note(&mut self.unknown_entry_types, &entry.entry_type, file_path);
+        }
+
+        if let Some(extra) = entry.extra.as_object() {
+            for key in extra.keys() {
+                if !KNOWN_EXTRA_FIELDS.contains(&key.as_str()) {
+                    note(&mut self.unknown_fields, key, file_path);
+                }
+            }
+        }
+    }
+
+    /// True if nothing unknown was found
+    pub fn is_clean(&self) -> bool {
+        self.unknown_entry_types.is_empty() && self.unknown_fields.is_empty()
+    }
+
+    /// Convert the report to JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize schema report")
+    }
+
+    /// Print a summary to the console
+    pub fn print_summary(&self) {
+        println!("{}", "Schema Report".cyan().bold());
+        println!(
+            "  Scanned {} entries in {} sessions",
+            self.entries_scanned, self.sessions_scanned
+        );
+
+        if self.is_clean() {
+            println!("  {} No unknown entry types or fields", glyphs::ok().green());
+            return;
+        }
+
+        print_sightings("Unknown entry types", &self.unknown_entry_types);
+        print_sightings("Unknown fields", &self.unknown_fields);
+    }
+}
+
+fn note(sightings: &mut BTreeMap<String, SchemaSighting>, name: &str, file_path: &str) {
+    sightings
+        .entry(name.to_string())
+        .and_modify(|s| s.count += 1)
+        .or_insert_with(|| SchemaSighting {
+            count: 1,
+            example_file: file_path.to_string(),
+        });
+}
+
+fn print_sightings(title: &str, sightings: &BTreeMap<String, SchemaSighting>) {
+    if sightings.is_empty() {
+        return;
+    }
+    println!();
+    println!("  {} {}:", "!".yellow().bold(), title.bold());
+    for (name, sighting) in sightings {
+        println!(
+            "    {} ({} entries, e.g. {})",
+            name.yellow(),
+            sighting.count,
+            sighting.example_file.dimmed()
+        );
+    }
+}
+
+/// Scan local sessions and print or save a schema report
+pub fn generate_schema_report(output: Option<&Path>) -> Result<()> {
+    let filter = FilterConfig::load()?;
+    let claude_dir = crate::sync::claude_projects_dir()?;
+    let sessions = crate::sync::discover_sessions(&claude_dir, &filter)?;
+
+    let mut report = SchemaReport::new();
+    for session in &sessions {
+        report.record_session(session);
+    }
+
+    if let Some(output_path) = output {
+        fs::write(output_path, report.to_json()?)
+            .with_context(|| format!("Failed to write report to {}", output_path.display()))?;
+        println!(
+            "{}",
+            format!("Schema report saved to {}", output_path.display()).green()
+        );
+    } else {
+        report.print_summary();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> ConversationEntry {
+        serde_json::from_str(line).unwrap()
+    }
+
+    /// One representative line per known entry type, as Claude Code writes them
+    const FIXTURES: &[&str] = &[
+        r#"{"parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/work","sessionId":"s1","version":"2.0.0","gitBranch":"main","type":"user","message":{"role":"user","content":"hi"},"uuid":"u1","timestamp":"2025-01-01T00:00:00.000Z"}"#,
+        r#"{"parentUuid":"u1","isSidechain":false,"userType":"external","cwd":"/work","sessionId":"s1","version":"2.0.0","gitBranch":"main","message":{"role":"assistant","content":[]},"requestId":"req_1","type":"assistant","uuid":"a1","timestamp":"2025-01-01T00:00:01.000Z"}"#,
+        r#"{"parentUuid":"a1","isSidechain":false,"type":"system","subtype":"compact_boundary","content":"Conversation compacted","isMeta":false,"level":"info","compactMetadata":{"trigger":"auto"},"logicalParentUuid":"a1","uuid":"sys1","timestamp":"2025-01-01T00:00:02.000Z"}"#,
+        r#"{"type":"summary","summary":"Greeting","leafUuid":"a1"}"#,
+        r#"{"type":"file-history-snapshot","messageId":"u1","snapshot":{"trackedFileBackups":{}},"isSnapshotUpdate":false}"#,
+        r#"{"type":"queue-operation","operation":"enqueue","timestamp":"2025-01-01T00:00:03.000Z","sessionId":"s1","content":"next"}"#,
+    ];
+
+    #[test]
+    fn test_known_fixtures_are_clean() {
+        let mut report = SchemaReport::new();
+        for line in FIXTURES {
+            report.record_entry(&parse(line), "fixture.jsonl");
+        }
+        assert_eq!(report.entries_scanned, FIXTURES.len());
+        assert!(report.is_clean(), "unexpected drift: {:?}", report.unknown_fields);
+    }
+
+    #[test]
+    fn test_fixtures_round_trip_all_fields() {
+        // Snapshot: re-serializing must not drop any top-level field
+        for line in FIXTURES {
+            let original: serde_json::Value = serde_json::from_str(line).unwrap();
+            let reserialized: serde_json::Value =
+                serde_json::from_str(&serde_json::to_string(&parse(line)).unwrap()).unwrap();
+
+            let mut original_keys: Vec<_> = original.as_object().unwrap().keys().collect();
+            let mut round_trip_keys: Vec<_> =
+                reserialized.as_object().unwrap().keys().collect();
+            original_keys.retain(|k| !original[k.as_str()].is_null());
+            original_keys.sort();
+            round_trip_keys.sort();
+            assert_eq!(original_keys, round_trip_keys, "fields lost for {line}");
+        }
+    }
+
+    #[test]
+    fn test_unknown_type_and_field_reported() {
+        let mut report = SchemaReport::new();
+        report.record_entry(
+            &parse(r#"{"type":"hologram","uuid":"x","sparkle":true}"#),
+            "a.jsonl",
+        );
+        report.record_entry(&parse(r#"{"type":"user","sparkle":1}"#), "b.jsonl");
+
+        assert!(!report.is_clean());
+        assert_eq!(report.unknown_entry_types["hologram"].count, 1);
+        let sparkle = &report.unknown_fields["sparkle"];
+        assert_eq!(sparkle.count, 2);
+        assert_eq!(sparkle.example_file, "a.jsonl");
+    }
+}
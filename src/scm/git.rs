@@ -4,7 +4,11 @@ use anyhow::{anyhow, bail, Context, Result};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use super::Scm;
+use super::{CommitInfo, HistoryRewrite, Scm, TagInfo};
+use crate::credentials;
+
+/// Environment variable the keychain token is handed to git in
+const TOKEN_ENV: &str = "CLAUDE_CODE_SYNC_HTTPS_TOKEN";
 
 /// Git SCM implementation using the git CLI.
 pub struct GitScm {
@@ -64,7 +68,9 @@ impl GitScm {
                 .with_context(|| format!("Failed to create parent directory for '{}'", path.display()))?;
         }
 
-        let output = Command::new("git")
+        let mut command = Command::new("git");
+        with_https_token(&mut command, url);
+        let output = command
             .args(["clone", url, &path.to_string_lossy()])
             .output()
             .context("Failed to run 'git clone'")?;
@@ -81,21 +87,25 @@ impl GitScm {
 
     /// Run a git command and return stdout as a string.
     fn run_git(&self, args: &[&str]) -> Result<String> {
-        let output = Command::new("git")
-            .args(args)
-            .current_dir(&self.workdir)
-            .output()
-            .with_context(|| format!("Failed to run 'git {}'", args.join(" ")))?;
+        let mut command = Command::new("git");
+        command.current_dir(&self.workdir);
+        run_command(command, args)
+    }
 
-        if !output.status.success() {
-            return Err(anyhow!(
-                "git {} failed: {}",
-                args.join(" "),
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
+    /// Run a git command that talks to `remote` and return stdout as a string.
+    fn run_remote_git(&self, remote: &str, args: &[&str]) -> Result<String> {
+        run_command(self.remote_git(remote), args)
+    }
 
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    /// A git command for talking to `remote`, authenticated with the
+    /// keychain token when the remote is an HTTPS URL and one is stored.
+    fn remote_git(&self, remote: &str) -> Command {
+        let mut command = Command::new("git");
+        command.current_dir(&self.workdir);
+        if let Ok(url) = self.get_remote_url(remote) {
+            with_https_token(&mut command, &url);
+        }
+        command
     }
 
     /// Run a git command, returning Ok if it succeeds (ignoring stdout).
@@ -128,6 +138,26 @@ impl Scm for GitScm {
         self.run_git_ok(&["add", "-A"])
     }
 
+    fn stage_paths(&self, paths: &[PathBuf]) -> Result<()> {
+        let selected = super::select_paths(self.uncommitted_paths()?, paths);
+        if selected.is_empty() {
+            return Ok(());
+        }
+        // Passed through a file, as there may be more than fit on a command line
+        let paths_file = self.workdir.join(".git").join("stage-paths");
+        std::fs::write(&paths_file, selected.join("\0") + "\0")
+            .context("Failed to write paths to stage")?;
+        let result = self.run_git_ok(&[
+            "--literal-pathspecs",
+            "add",
+            "-A",
+            "--pathspec-file-nul",
+            &format!("--pathspec-from-file={}", paths_file.to_string_lossy()),
+        ]);
+        let _ = std::fs::remove_file(&paths_file);
+        result
+    }
+
     fn commit(&self, message: &str) -> Result<()> {
         self.run_git_ok(&["commit", "-m", message])
     }
@@ -137,6 +167,54 @@ impl Scm for GitScm {
         Ok(!output.is_empty())
     }
 
+    fn commit_paths(&self, message: &str, paths: &[PathBuf]) -> Result<()> {
+        let paths_file = self.workdir.join(".git").join("commit-paths");
+        let paths: Vec<String> = paths.iter().map(|p| p.to_string_lossy().to_string()).collect();
+        std::fs::write(&paths_file, paths.join("\0") + "\0")
+            .context("Failed to write paths to commit")?;
+        let result = self.run_git_ok(&[
+            "--literal-pathspecs",
+            "commit",
+            "-m",
+            message,
+            "--pathspec-file-nul",
+            &format!("--pathspec-from-file={}", paths_file.to_string_lossy()),
+        ]);
+        let _ = std::fs::remove_file(&paths_file);
+        result
+    }
+
+    fn has_staged_changes(&self) -> Result<bool> {
+        let output = self.run_git(&["diff", "--cached", "--name-only"])?;
+        Ok(!output.is_empty())
+    }
+
+    fn uncommitted_paths(&self) -> Result<Vec<String>> {
+        let staged = self.run_git(&[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--cached",
+            "--name-only",
+            "--no-renames",
+        ])?;
+        let unstaged = self.run_git(&[
+            "-c",
+            "core.quotePath=false",
+            "ls-files",
+            "--modified",
+            "--deleted",
+            "--others",
+            "--exclude-standard",
+        ])?;
+        let paths: std::collections::BTreeSet<&str> = staged
+            .lines()
+            .chain(unstaged.lines())
+            .filter(|line| !line.is_empty())
+            .collect();
+        Ok(paths.into_iter().map(|s| s.to_string()).collect())
+    }
+
     fn add_remote(&self, name: &str, url: &str) -> Result<()> {
         self.run_git_ok(&["remote", "add", name, url])
     }
@@ -167,9 +245,9 @@ impl Scm for GitScm {
     }
 
     fn push(&self, remote: &str, branch: &str) -> Result<()> {
-        let output = Command::new("git")
+        let output = self
+            .remote_git(remote)
             .args(["push", remote, branch])
-            .current_dir(&self.workdir)
             .output()
             .context("Failed to run 'git push'")?;
 
@@ -195,13 +273,34 @@ impl Scm for GitScm {
         // Always use --rebase to prevent divergent branches.
         // This ensures local commits are replayed on top of remote,
         // keeping a linear history and avoiding merge conflicts.
-        let output = Command::new("git")
+        let output = self
+            .remote_git(remote)
             .args(["pull", "--rebase", remote, branch])
-            .current_dir(&self.workdir)
             .output()
             .context("Failed to run 'git pull --rebase'")?;
 
         if !output.status.success() {
+            // A conflicting rebase would leave the repo mid-rebase, and every
+            // later command would fail. Put the branch back and merge instead,
+            // taking the remote side of conflicting hunks: sync callers keep
+            // local sessions on a temp branch and merge them back afterwards,
+            // and unpushed local commits would otherwise conflict forever.
+            if self.workdir.join(".git/rebase-merge").exists()
+                || self.workdir.join(".git/rebase-apply").exists()
+            {
+                let _ = self.run_git(&["rebase", "--abort"]);
+                let merged = self.git_succeeds(&[
+                    "merge",
+                    "--no-edit",
+                    "-X",
+                    "theirs",
+                    "FETCH_HEAD",
+                ]);
+                if merged {
+                    return Ok(());
+                }
+                let _ = self.run_git(&["merge", "--abort"]);
+            }
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Err(anyhow!(
                 "Failed to pull from remote '{}': {}",
@@ -233,7 +332,8 @@ impl Scm for GitScm {
     }
 
     fn delete_remote_branch(&self, remote: &str, branch: &str) -> Result<()> {
-        self.run_git_ok(&["push", remote, "--delete", branch])
+        self.run_remote_git(remote, &["push", remote, "--delete", branch])?;
+        Ok(())
     }
 
     fn branch_exists(&self, name: &str) -> bool {
@@ -241,7 +341,8 @@ impl Scm for GitScm {
     }
 
     fn fetch(&self, remote: &str) -> Result<()> {
-        self.run_git_ok(&["fetch", remote])
+        self.run_remote_git(remote, &["fetch", remote])?;
+        Ok(())
     }
 
     fn list_branches(&self) -> Result<Vec<String>> {
@@ -263,6 +364,250 @@ impl Scm for GitScm {
             .filter(|s| !s.is_empty())
             .collect())
     }
+
+    fn list_remote_branches(&self, remote: &str) -> Result<Vec<String>> {
+        let output = self.run_git(&["ls-remote", "--heads", remote])?;
+        // Format: "<hash>\trefs/heads/<name>"
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split('\t').nth(1))
+            .filter_map(|r| r.strip_prefix("refs/heads/"))
+            .map(|s| s.to_string())
+            .collect())
+    }
+
+    fn list_files_at(&self, rev: &str, path: &str) -> Result<Vec<String>> {
+        let output = self.run_git(&["ls-tree", "-r", "--name-only", rev, "--", path])?;
+        Ok(output.lines().map(|s| s.to_string()).collect())
+    }
+
+    fn read_file_at(&self, rev: &str, path: &str) -> Result<Vec<u8>> {
+        let spec = format!("{rev}:{path}");
+        let output = Command::new("git")
+            .args(["show", &spec])
+            .current_dir(&self.workdir)
+            .output()
+            .with_context(|| format!("Failed to run 'git show {spec}'"))?;
+
+        if !output.status.success() {
+            bail!(
+                "git show {} failed: {}",
+                spec,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(output.stdout)
+    }
+
+    fn list_files_in_history(&self) -> Result<Vec<String>> {
+        let output = self.run_git(&["log", "--format=", "--name-only", "HEAD"])?;
+        let files: std::collections::BTreeSet<&str> =
+            output.lines().filter(|l| !l.is_empty()).collect();
+        Ok(files.into_iter().map(|s| s.to_string()).collect())
+    }
+
+    fn merge_base(&self, a: &str, b: &str) -> Result<String> {
+        self.run_git(&["merge-base", a, b])
+    }
+
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let output = self.run_git(&["diff", "--name-only", "--no-renames", from, to])?;
+        Ok(output.lines().map(|s| s.to_string()).collect())
+    }
+
+    fn log_paths(&self, paths: &[String]) -> Result<Vec<CommitInfo>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut args = vec![
+            "--literal-pathspecs",
+            "log",
+            "--topo-order",
+            "--reverse",
+            "--format=%H%x1f%an%x1f%aI%x1f%s",
+            "HEAD",
+            "--",
+        ];
+        args.extend(paths.iter().map(|p| p.as_str()));
+        let output = self.run_git(&args)?;
+        Ok(output.lines().filter_map(super::parse_commit_line).collect())
+    }
+
+    fn create_tag(&self, name: &str, message: &str) -> Result<()> {
+        self.run_git_ok(&["tag", "-a", name, "-m", message])
+    }
+
+    fn list_tags(&self) -> Result<Vec<TagInfo>> {
+        // Annotated tags point at a tag object; `*objectname` peels it
+        let output = self.run_git(&[
+            "for-each-ref",
+            "--sort=creatordate",
+            "--format=%(refname:short)%1f%(if)%(*objectname)%(then)%(*objectname)%(else)%(objectname)%(end)%1f%(creatordate:iso-strict)%1f%(contents:subject)",
+            "refs/tags",
+        ])?;
+        Ok(output.lines().filter_map(super::parse_tag_line).collect())
+    }
+
+    fn delete_tag(&self, name: &str) -> Result<()> {
+        self.run_git_ok(&["tag", "-d", name])
+    }
+
+    fn push_tag(&self, remote: &str, name: &str) -> Result<()> {
+        self.run_remote_git(remote, &["push", remote, &format!("refs/tags/{name}")])?;
+        Ok(())
+    }
+
+    fn delete_remote_tag(&self, remote: &str, name: &str) -> Result<()> {
+        self.run_remote_git(remote, &["push", remote, "--delete", &format!("refs/tags/{name}")])?;
+        Ok(())
+    }
+
+    fn create_bundle(&self, path: &Path, branch: &str, since: Option<&str>) -> Result<()> {
+        let path = path.to_string_lossy();
+        let range = match since {
+            Some(since) => format!("{since}..{branch}"),
+            None => branch.to_string(),
+        };
+        self.run_git_ok(&["bundle", "create", "--quiet", &path, &range])
+    }
+
+    fn verify_bundle(&self, path: &Path) -> Result<()> {
+        self.run_git_ok(&["bundle", "verify", "--quiet", &path.to_string_lossy()])
+    }
+
+    fn rewrite_history(&self, rewrite: &HistoryRewrite) -> Result<()> {
+        // The filter runs once per commit in a scratch checkout, so paths and
+        // patterns are passed through files rather than the command line
+        let git_dir = self.workdir.join(".git");
+        let paths_file = git_dir.join("rewrite-paths");
+        let lines_file = git_dir.join("rewrite-lines");
+        let scratch_file = git_dir.join("rewrite-scratch");
+
+        let mut filter = String::new();
+        if !rewrite.remove_paths.is_empty() {
+            std::fs::write(&paths_file, rewrite.remove_paths.join("\n") + "\n")
+                .context("Failed to write paths to remove")?;
+            filter.push_str(&format!(
+                "git --literal-pathspecs rm -q -r --cached --ignore-unmatch --pathspec-from-file={} && ",
+                shell_quote(&paths_file.to_string_lossy())
+            ));
+        }
+        if let Some(scrub_path) = rewrite.scrub_path.as_deref() {
+            if !rewrite.drop_lines.is_empty() {
+                std::fs::write(&lines_file, rewrite.drop_lines.join("\n") + "\n")
+                    .context("Failed to write lines to remove")?;
+                let staged = shell_quote(&format!(":{scrub_path}"));
+                let scratch = shell_quote(&scratch_file.to_string_lossy());
+                filter.push_str(&format!(
+                    "if git cat-file -e {staged} 2>/dev/null; then \
+                     git cat-file blob {staged} | grep -v -F -f {lines} > {scratch}; \
+                     git update-index --cacheinfo 100644,$(git hash-object -w {scratch}),{path}; \
+                     fi && ",
+                    lines = shell_quote(&lines_file.to_string_lossy()),
+                    path = shell_quote(scrub_path),
+                ));
+            }
+        }
+        filter.push_str("true");
+
+        let output = Command::new("git")
+            .args([
+                "filter-branch",
+                "--force",
+                "--prune-empty",
+                "--index-filter",
+                &filter,
+                "HEAD",
+            ])
+            .env("FILTER_BRANCH_SQUELCH_WARNING", "1")
+            .current_dir(&self.workdir)
+            .output()
+            .context("Failed to run 'git filter-branch'")?;
+        for file in [&paths_file, &lines_file, &scratch_file] {
+            let _ = std::fs::remove_file(file);
+        }
+        if !output.status.success() {
+            bail!(
+                "git filter-branch failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        // filter-branch keeps the old history under refs/original
+        let originals = self.run_git(&["for-each-ref", "--format=%(refname)", "refs/original/"])?;
+        for reference in originals.lines().filter(|l| !l.is_empty()) {
+            self.run_git_ok(&["update-ref", "-d", reference])?;
+        }
+        Ok(())
+    }
+
+    fn force_push(&self, remote: &str, branch: &str) -> Result<()> {
+        self.run_remote_git(remote, &["push", "--force-with-lease", remote, branch])?;
+        Ok(())
+    }
+
+    fn reset_hard(&self, rev: &str) -> Result<()> {
+        self.run_git_ok(&["reset", "--hard", rev])
+    }
+
+    fn prune_unreachable(&self) -> Result<()> {
+        self.run_git_ok(&[
+            "reflog",
+            "expire",
+            "--expire=now",
+            "--expire-unreachable=now",
+            "--all",
+        ])?;
+        self.run_git_ok(&["gc", "--prune=now", "--quiet"])
+    }
+}
+
+/// Run a prepared git command with `args` and return stdout as a string.
+fn run_command(mut command: Command, args: &[&str]) -> Result<String> {
+    let output = command
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run 'git {}'", args.join(" ")))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Make `command` authenticate to the HTTPS remote at `url` with the token
+/// stored in the OS keychain, if there is one.
+///
+/// The token reaches git through the environment rather than the command
+/// line, and other credential helpers are switched off for the command so
+/// none of them can save it to disk.
+fn with_https_token(command: &mut Command, url: &str) {
+    if let Some(token) = credentials::https_token_for(url) {
+        command
+            .env(TOKEN_ENV, token)
+            .args(["-c", "credential.helper=", "-c"])
+            .arg(credential_helper_config());
+    }
+}
+
+/// `credential.helper` setting that answers git's `get` requests with the
+/// token in [`TOKEN_ENV`].
+fn credential_helper_config() -> String {
+    format!(
+        "credential.helper=!f() {{ test \"$1\" = get && echo username=x-access-token && echo \"password=${}\"; }}; f",
+        TOKEN_ENV
+    )
+}
+
+/// Quote a string for use as a single POSIX shell word.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
 }
 
 #[cfg(test)]
@@ -304,6 +649,153 @@ mod tests {
         assert_eq!(hash.len(), 40); // Full SHA
     }
 
+    #[test]
+    fn test_git_read_at_revision() {
+        let temp = TempDir::new().unwrap();
+        let scm = GitScm::init(temp.path()).unwrap();
+
+        std::fs::create_dir_all(temp.path().join("projects/p")).unwrap();
+        std::fs::write(temp.path().join("projects/p/s.jsonl"), "v1\n").unwrap();
+        scm.stage_all().unwrap();
+        scm.commit("v1").unwrap();
+        let v1 = scm.current_commit_hash().unwrap();
+
+        // Working tree changes don't affect reads at a revision
+        std::fs::write(temp.path().join("projects/p/s.jsonl"), "v2\n").unwrap();
+
+        assert_eq!(
+            scm.list_files_at(&v1, "projects").unwrap(),
+            vec!["projects/p/s.jsonl".to_string()]
+        );
+        assert_eq!(scm.read_file_at(&v1, "projects/p/s.jsonl").unwrap(), b"v1\n");
+        assert!(scm.read_file_at(&v1, "missing.jsonl").is_err());
+    }
+
+    #[test]
+    fn test_git_rewrite_history() {
+        let temp = TempDir::new().unwrap();
+        let scm = GitScm::init(temp.path()).unwrap();
+
+        std::fs::create_dir_all(temp.path().join("projects/p")).unwrap();
+        std::fs::write(temp.path().join("projects/p/secret.jsonl"), "key\n").unwrap();
+        std::fs::write(temp.path().join("projects/p/kept.jsonl"), "fine\n").unwrap();
+        std::fs::write(
+            temp.path().join("history.jsonl"),
+            "{\"sessionId\":\"secret\"}\n{\"sessionId\":\"kept\"}\n",
+        )
+        .unwrap();
+        scm.stage_all().unwrap();
+        scm.commit("Add sessions").unwrap();
+        std::fs::remove_file(temp.path().join("projects/p/secret.jsonl")).unwrap();
+        scm.stage_all().unwrap();
+        scm.commit("Remove secret").unwrap();
+        assert!(scm
+            .list_files_in_history()
+            .unwrap()
+            .contains(&"projects/p/secret.jsonl".to_string()));
+
+        scm.rewrite_history(&HistoryRewrite {
+            remove_paths: vec!["projects/p/secret.jsonl".to_string()],
+            scrub_path: Some("history.jsonl".to_string()),
+            drop_lines: vec!["\"sessionId\":\"secret\"".to_string()],
+        })
+        .unwrap();
+        scm.prune_unreachable().unwrap();
+
+        assert_eq!(
+            scm.list_files_in_history().unwrap(),
+            ["history.jsonl", "projects/p/kept.jsonl"]
+        );
+        assert_eq!(
+            scm.read_file_at("HEAD", "history.jsonl").unwrap(),
+            b"{\"sessionId\":\"kept\"}\n"
+        );
+        // The now-empty "Remove secret" commit is dropped
+        assert_eq!(scm.run_git(&["rev-list", "--count", "HEAD"]).unwrap(), "1");
+        assert!(scm
+            .run_git(&["for-each-ref", "refs/original/"])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_git_merge_base_and_changed_files() {
+        let temp = TempDir::new().unwrap();
+        let scm = GitScm::init(temp.path()).unwrap();
+
+        std::fs::write(temp.path().join("a.jsonl"), "1\n").unwrap();
+        std::fs::write(temp.path().join("b.jsonl"), "1\n").unwrap();
+        scm.stage_all().unwrap();
+        scm.commit("Base").unwrap();
+        let base = scm.current_commit_hash().unwrap();
+        scm.create_branch("side").unwrap();
+
+        std::fs::write(temp.path().join("a.jsonl"), "2\n").unwrap();
+        std::fs::remove_file(temp.path().join("b.jsonl")).unwrap();
+        std::fs::write(temp.path().join("c.jsonl"), "1\n").unwrap();
+        scm.stage_all().unwrap();
+        scm.commit("Change").unwrap();
+
+        assert_eq!(scm.merge_base("side", "HEAD").unwrap(), base);
+        assert_eq!(
+            scm.changed_files(&base, "HEAD").unwrap(),
+            ["a.jsonl", "b.jsonl", "c.jsonl"]
+        );
+    }
+
+    #[test]
+    fn test_git_stage_paths() {
+        let temp = TempDir::new().unwrap();
+        let scm = GitScm::init(temp.path()).unwrap();
+        std::fs::create_dir_all(temp.path().join("projects/p")).unwrap();
+        std::fs::write(temp.path().join("projects/p/old.jsonl"), "1\n").unwrap();
+        std::fs::write(temp.path().join("README.md"), "1\n").unwrap();
+        scm.stage_all().unwrap();
+        scm.commit("Base").unwrap();
+
+        std::fs::remove_file(temp.path().join("projects/p/old.jsonl")).unwrap();
+        std::fs::write(temp.path().join("projects/p/new.jsonl"), "1\n").unwrap();
+        std::fs::write(temp.path().join("README.md"), "2\n").unwrap();
+        std::fs::write(temp.path().join("notes.md"), "1\n").unwrap();
+        assert_eq!(
+            scm.uncommitted_paths().unwrap(),
+            ["README.md", "notes.md", "projects/p/new.jsonl", "projects/p/old.jsonl"]
+        );
+
+        scm.stage_paths(&[PathBuf::from("projects"), PathBuf::from("tags.json")])
+            .unwrap();
+        assert!(scm.has_staged_changes().unwrap());
+        scm.commit("Sync").unwrap();
+        assert!(!scm.has_staged_changes().unwrap());
+        assert_eq!(scm.uncommitted_paths().unwrap(), ["README.md", "notes.md"]);
+    }
+
+    #[test]
+    fn test_credential_helper_answers_with_env_token() {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        let mut child = Command::new("git")
+            .env(TOKEN_ENV, "s3cret")
+            .args(["-c", "credential.helper=", "-c"])
+            .arg(credential_helper_config())
+            .args(["credential", "fill"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"protocol=https\nhost=example.com\n\n")
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("username=x-access-token\n"), "{stdout}");
+        assert!(stdout.contains("password=s3cret\n"), "{stdout}");
+    }
+
     #[test]
     fn test_git_branch() {
         let temp = TempDir::new().unwrap();
@@ -330,4 +822,30 @@ mod tests {
         assert!(scm.has_remote("origin"));
         assert!(!scm.has_remote("upstream"));
     }
+
+    #[test]
+    fn test_git_list_remote_branches() {
+        let temp = TempDir::new().unwrap();
+        let remote_dir = temp.path().join("remote.git");
+        let output = Command::new("git")
+            .args(["init", "--bare"])
+            .arg(&remote_dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let local_dir = temp.path().join("local");
+        let scm = GitScm::init(&local_dir).unwrap();
+        std::fs::write(local_dir.join("test.txt"), "hello").unwrap();
+        scm.stage_all().unwrap();
+        scm.commit("Initial commit").unwrap();
+        scm.add_remote("origin", remote_dir.to_str().unwrap()).unwrap();
+        scm.create_branch("sync-local-20250101-000000").unwrap();
+        scm.push("origin", "sync-local-20250101-000000").unwrap();
+
+        assert_eq!(
+            scm.list_remote_branches("origin").unwrap(),
+            vec!["sync-local-20250101-000000".to_string()]
+        );
+    }
 }
@@ -4,12 +4,17 @@ use anyhow::{bail, Context, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
 
-use super::Scm;
+use super::{CommitInfo, HistoryRewrite, Scm, TagInfo};
 
 /// Mercurial SCM implementation using the `hg` CLI.
 pub struct HgScm {
     path: PathBuf,
+    /// Paths chosen by `stage_paths` since the last commit (`None`: commit
+    /// everything). Mercurial has no staging area, so the selection is
+    /// applied at commit.
+    staged: Mutex<Option<Vec<String>>>,
 }
 
 impl HgScm {
@@ -32,6 +37,7 @@ impl HgScm {
 
         Ok(Self {
             path: path.to_path_buf(),
+            staged: Mutex::new(None),
         })
     }
 
@@ -42,6 +48,7 @@ impl HgScm {
         }
         Ok(Self {
             path: path.to_path_buf(),
+            staged: Mutex::new(None),
         })
     }
 
@@ -62,6 +69,7 @@ impl HgScm {
 
         Ok(Self {
             path: path.to_path_buf(),
+            staged: Mutex::new(None),
         })
     }
 
@@ -207,11 +215,55 @@ impl Scm for HgScm {
     fn stage_all(&self) -> Result<()> {
         // In Mercurial, addremove stages new and removed files
         self.run_hg(&["addremove"])?;
+        *self.staged.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn stage_paths(&self, paths: &[PathBuf]) -> Result<()> {
+        let selected = super::select_paths(self.uncommitted_paths()?, paths);
+        if !selected.is_empty() {
+            let list_file = self.path.join(".hg").join("stage-paths");
+            fs::write(&list_file, selected.join("\n") + "\n")
+                .context("Failed to write paths to stage")?;
+            let result = self.run_hg(&["addremove", &format!("listfile:{}", list_file.display())]);
+            let _ = fs::remove_file(&list_file);
+            result?;
+        }
+        // Like git's index, the selection grows until the next commit
+        let mut staged = self.staged.lock().unwrap();
+        let mut all = staged.take().unwrap_or_default();
+        for path in selected {
+            if !all.contains(&path) {
+                all.push(path);
+            }
+        }
+        *staged = Some(all);
         Ok(())
     }
 
     fn commit(&self, message: &str) -> Result<()> {
-        self.run_hg(&["commit", "-m", message])?;
+        let staged = self.staged.lock().unwrap().take();
+        match staged {
+            None => {
+                self.run_hg(&["commit", "-m", message])?;
+            }
+            Some(paths) => {
+                if paths.is_empty() {
+                    bail!("nothing to commit");
+                }
+                let list_file = self.path.join(".hg").join("commit-paths");
+                fs::write(&list_file, paths.join("\n") + "\n")
+                    .context("Failed to write paths to commit")?;
+                let result = self.run_hg(&[
+                    "commit",
+                    "-m",
+                    message,
+                    &format!("listfile:{}", list_file.display()),
+                ]);
+                let _ = fs::remove_file(&list_file);
+                result?;
+            }
+        }
         Ok(())
     }
 
@@ -220,6 +272,45 @@ impl Scm for HgScm {
         Ok(!output.is_empty())
     }
 
+    fn commit_paths(&self, message: &str, paths: &[PathBuf]) -> Result<()> {
+        let mut staged = self.staged.lock().unwrap();
+        let candidates = match staged.as_ref() {
+            Some(staged) => staged.clone(),
+            None => self.uncommitted_paths()?,
+        };
+        let selected = super::select_paths(candidates, paths);
+        if selected.is_empty() {
+            bail!("nothing to commit");
+        }
+        let list_file = self.path.join(".hg").join("commit-paths");
+        fs::write(&list_file, selected.join("\n") + "\n")
+            .context("Failed to write paths to commit")?;
+        let result = self.run_hg(&[
+            "commit",
+            "-m",
+            message,
+            &format!("listfile:{}", list_file.display()),
+        ]);
+        let _ = fs::remove_file(&list_file);
+        result?;
+        if let Some(staged) = staged.as_mut() {
+            staged.retain(|path| !selected.contains(path));
+        }
+        Ok(())
+    }
+
+    fn has_staged_changes(&self) -> Result<bool> {
+        match self.staged.lock().unwrap().as_ref() {
+            Some(paths) => Ok(!paths.is_empty()),
+            None => self.has_changes(),
+        }
+    }
+
+    fn uncommitted_paths(&self) -> Result<Vec<String>> {
+        let output = self.run_hg(&["status", "-n"])?;
+        Ok(output.lines().map(|s| s.to_string()).collect())
+    }
+
     fn add_remote(&self, name: &str, url: &str) -> Result<()> {
         self.update_path(name, Some(url))
     }
@@ -326,6 +417,176 @@ impl Scm for HgScm {
             })
             .collect())
     }
+
+    fn list_remote_branches(&self, remote: &str) -> Result<Vec<String>> {
+        // Pulling brings the remote's bookmarks in, after which they are listed locally
+        self.fetch(remote)?;
+        self.list_branches()
+    }
+
+    fn list_files_at(&self, rev: &str, path: &str) -> Result<Vec<String>> {
+        // hg files fails when nothing matches; treat that as an empty listing
+        match self.run_hg(&["files", "-r", rev, path]) {
+            Ok(output) => Ok(output.lines().map(|s| s.to_string()).collect()),
+            Err(_) => Ok(Vec::new()),
+        }
+    }
+
+    fn read_file_at(&self, rev: &str, path: &str) -> Result<Vec<u8>> {
+        let output = Command::new("hg")
+            .args(["cat", "-r", rev, path])
+            .current_dir(&self.path)
+            .output()
+            .with_context(|| format!("Failed to run 'hg cat -r {rev} {path}'"))?;
+
+        if !output.status.success() {
+            bail!(
+                "hg cat -r {} {} failed: {}",
+                rev,
+                path,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        Ok(output.stdout)
+    }
+
+    fn list_files_in_history(&self) -> Result<Vec<String>> {
+        let output = self.run_hg(&[
+            "log",
+            "-r",
+            "::.",
+            "--template",
+            "{join(files, '\\n')}\\n",
+        ])?;
+        let files: std::collections::BTreeSet<&str> =
+            output.lines().filter(|l| !l.is_empty()).collect();
+        Ok(files.into_iter().map(|s| s.to_string()).collect())
+    }
+
+    fn merge_base(&self, a: &str, b: &str) -> Result<String> {
+        let revset = format!("ancestor({a}, {b})");
+        let node = self.run_hg(&["log", "-r", &revset, "--template", "{node}"])?;
+        if node.is_empty() {
+            bail!("No common ancestor of {} and {}", a, b);
+        }
+        Ok(node)
+    }
+
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let output = self.run_hg(&["status", "--rev", from, "--rev", to, "-n"])?;
+        Ok(output.lines().map(|s| s.to_string()).collect())
+    }
+
+    fn log_paths(&self, paths: &[String]) -> Result<Vec<CommitInfo>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let patterns: Vec<String> = paths.iter().map(|p| format!("path:{p}")).collect();
+        let mut args = vec![
+            "log",
+            "-r",
+            "::.",
+            "--template",
+            "{node}\x1f{author|person}\x1f{date|rfc3339date}\x1f{desc|firstline}\n",
+        ];
+        args.extend(patterns.iter().map(|p| p.as_str()));
+        let output = self.run_hg(&args)?;
+        Ok(output.lines().filter_map(super::parse_commit_line).collect())
+    }
+
+    fn create_tag(&self, name: &str, message: &str) -> Result<()> {
+        // Mercurial records tags in .hgtags, committing it
+        self.run_hg(&["tag", "-m", message, name])?;
+        Ok(())
+    }
+
+    fn list_tags(&self) -> Result<Vec<TagInfo>> {
+        // Tags have no date or message of their own; the tagged commit's are used
+        let output = self.run_hg(&[
+            "log",
+            "-r",
+            "tag() - tip",
+            "--template",
+            "{tags}\x1f{node}\x1f{date|rfc3339date}\x1f{desc|firstline}\n",
+        ])?;
+        let mut tags = Vec::new();
+        for tag in output.lines().filter_map(super::parse_tag_line) {
+            for name in tag.name.split_whitespace().filter(|name| *name != "tip") {
+                tags.push(TagInfo {
+                    name: name.to_string(),
+                    ..tag.clone()
+                });
+            }
+        }
+        Ok(tags)
+    }
+
+    fn delete_tag(&self, name: &str) -> Result<()> {
+        self.run_hg(&["tag", "--remove", "-m", &format!("Remove tag {name}"), name])?;
+        Ok(())
+    }
+
+    fn push_tag(&self, remote: &str, _name: &str) -> Result<()> {
+        // Tags travel with the commit that added them to .hgtags
+        self.run_hg(&["push", remote])?;
+        Ok(())
+    }
+
+    fn delete_remote_tag(&self, remote: &str, _name: &str) -> Result<()> {
+        // The removal is a .hgtags commit too
+        self.run_hg(&["push", remote])?;
+        Ok(())
+    }
+
+    fn create_bundle(&self, path: &Path, branch: &str, since: Option<&str>) -> Result<()> {
+        let path = path.to_string_lossy();
+        let mut args = vec!["bundle", "-r", branch];
+        match since {
+            Some(since) => args.extend(["--base", since]),
+            None => args.push("--all"),
+        }
+        args.push(&path);
+        self.run_hg(&args)?;
+        Ok(())
+    }
+
+    fn verify_bundle(&self, path: &Path) -> Result<()> {
+        // `incoming` reads the bundle and fails if its base is missing; it
+        // exits 1 when the bundle holds nothing new, which is fine
+        let output = Command::new("hg")
+            .args(["incoming", "--quiet"])
+            .arg(path)
+            .current_dir(&self.path)
+            .output()
+            .context("Failed to run 'hg incoming'")?;
+        if !matches!(output.status.code(), Some(0 | 1)) {
+            bail!(
+                "hg incoming {} failed: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(())
+    }
+
+    fn rewrite_history(&self, _rewrite: &HistoryRewrite) -> Result<()> {
+        bail!("Rewriting history is not supported for Mercurial repositories")
+    }
+
+    fn force_push(&self, _remote: &str, _branch: &str) -> Result<()> {
+        bail!("Force-pushing rewritten history is not supported for Mercurial repositories")
+    }
+
+    fn reset_hard(&self, rev: &str) -> Result<()> {
+        self.run_hg(&["update", "--clean", "-r", rev])?;
+        Ok(())
+    }
+
+    fn prune_unreachable(&self) -> Result<()> {
+        // Mercurial never rewrites history here, so nothing becomes unreachable
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,999 @@
+//! In-memory SCM backend for deterministic tests.
+//!
+//! `MockScm` keeps commits, branches, remotes, and the working tree in memory,
+//! so code that drives an [`Scm`] (temp branches, push/pull fallbacks) can be
+//! tested without spawning git or touching the filesystem. Remotes live inside
+//! the mock too; [`MockScm::push_from_elsewhere`] stands in for another
+//! machine pushing. Pushes can be made to fail and every call can be slowed
+//! down, and each call is recorded for assertions.
+//!
+//! Available in unit tests and with the `test_support` feature.
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use super::{CommitInfo, HistoryRewrite, Scm, TagInfo};
+
+type Files = BTreeMap<String, Vec<u8>>;
+
+#[derive(Debug, Clone)]
+struct Commit {
+    parent: Option<String>,
+    message: String,
+    files: Files,
+}
+
+#[derive(Debug, Default)]
+struct MockRemote {
+    url: String,
+    branches: BTreeMap<String, String>,
+    /// Tag name to commit ID
+    tags: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    commits: HashMap<String, Commit>,
+    next_commit: u64,
+    branches: BTreeMap<String, String>,
+    head: String,
+    worktree: Files,
+    index: Files,
+    remotes: BTreeMap<String, MockRemote>,
+    /// Remote-tracking refs (`origin/main`) as of the last fetch
+    tracking: BTreeMap<String, String>,
+    /// Tags in creation order
+    tags: Vec<TagInfo>,
+    push_failures: VecDeque<String>,
+    calls: Vec<String>,
+}
+
+impl State {
+    fn head_commit(&self) -> Option<&String> {
+        self.branches.get(&self.head)
+    }
+
+    fn files_of(&self, commit: Option<&String>) -> Files {
+        commit
+            .and_then(|id| self.commits.get(id))
+            .map(|c| c.files.clone())
+            .unwrap_or_default()
+    }
+
+    fn add_commit(&mut self, parent: Option<String>, message: &str, files: Files) -> String {
+        self.next_commit += 1;
+        let id = format!("{:040x}", self.next_commit);
+        self.commits.insert(
+            id.clone(),
+            Commit {
+                parent,
+                message: message.to_string(),
+                files,
+            },
+        );
+        id
+    }
+
+    /// Resolve a branch, remote-tracking ref, or commit ID
+    fn resolve(&self, rev: &str) -> Option<String> {
+        if rev == "HEAD" {
+            return self.head_commit().cloned();
+        }
+        self.branches
+            .get(rev)
+            .or_else(|| self.tracking.get(rev))
+            .cloned()
+            .or_else(|| {
+                self.tags
+                    .iter()
+                    .find(|tag| tag.name == rev)
+                    .map(|tag| tag.commit.clone())
+            })
+            .or_else(|| self.commits.contains_key(rev).then(|| rev.to_string()))
+    }
+
+    fn ancestors(&self, id: &str) -> Vec<String> {
+        let mut chain = Vec::new();
+        let mut current = Some(id.to_string());
+        while let Some(id) = current {
+            current = self.commits.get(&id).and_then(|c| c.parent.clone());
+            chain.push(id);
+        }
+        chain
+    }
+
+    fn is_ancestor(&self, ancestor: &str, of: &str) -> bool {
+        self.ancestors(of).iter().any(|id| id == ancestor)
+    }
+
+    fn merge_base(&self, a: &str, b: &str) -> Option<String> {
+        let of_b = self.ancestors(b);
+        self.ancestors(a).into_iter().find(|id| of_b.contains(id))
+    }
+
+    /// Files of `onto` with the changes made between `base` and `commit`
+    /// applied on top
+    fn apply_changes(&self, base: Option<&String>, commit: &str, onto: &Files) -> Files {
+        let base = self.files_of(base);
+        let changed = self.files_of(Some(&commit.to_string()));
+        let mut files = onto.clone();
+        for path in base.keys().filter(|p| !changed.contains_key(*p)) {
+            files.remove(path);
+        }
+        for (path, content) in changed {
+            if base.get(&path) != Some(&content) {
+                files.insert(path, content);
+            }
+        }
+        files
+    }
+
+    fn set_head_to(&mut self, id: String) {
+        let files = self.files_of(Some(&id));
+        self.branches.insert(self.head.clone(), id);
+        self.worktree = files.clone();
+        self.index = files;
+    }
+
+    fn fetch(&mut self, remote: &str) -> Result<()> {
+        let branches = match self.remotes.get(remote) {
+            Some(r) => r.branches.clone(),
+            None => bail!("No such remote: '{}'", remote),
+        };
+        self.tracking
+            .retain(|name, _| !name.starts_with(&format!("{remote}/")));
+        for (branch, id) in branches {
+            self.tracking.insert(format!("{remote}/{branch}"), id);
+        }
+        // Like git, fetching brings along tags not known locally
+        let tags = self.remotes[remote].tags.clone();
+        for (name, commit) in tags {
+            if !self.tags.iter().any(|tag| tag.name == name) {
+                self.tags.push(TagInfo {
+                    name,
+                    commit,
+                    date: String::new(),
+                    message: String::new(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// In-memory implementation of [`Scm`]
+#[derive(Debug)]
+pub struct MockScm {
+    state: Mutex<State>,
+    latency: Option<Duration>,
+}
+
+impl Default for MockScm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl MockScm {
+    /// Create an empty repository on branch `main` with no commits
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(State {
+                head: "main".to_string(),
+                ..State::default()
+            }),
+            latency: None,
+        }
+    }
+
+    /// Sleep for `latency` at the start of every call
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Make the next `count` pushes (including remote branch deletions) fail
+    pub fn fail_next_pushes(&self, count: usize) {
+        let mut state = self.lock();
+        for _ in 0..count {
+            state
+                .push_failures
+                .push_back("simulated push failure".to_string());
+        }
+    }
+
+    /// Write a file to the working tree
+    pub fn write_file(&self, path: &str, contents: impl Into<Vec<u8>>) {
+        self.lock()
+            .worktree
+            .insert(path.to_string(), contents.into());
+    }
+
+    /// Remove a file from the working tree
+    pub fn remove_file(&self, path: &str) {
+        self.lock().worktree.remove(path);
+    }
+
+    /// A file's contents in the working tree
+    pub fn file(&self, path: &str) -> Option<Vec<u8>> {
+        self.lock().worktree.get(path).cloned()
+    }
+
+    /// Commit `files` on top of `branch` of `remote` directly, as if another
+    /// machine had pushed them
+    pub fn push_from_elsewhere(
+        &self,
+        remote: &str,
+        branch: &str,
+        files: &[(&str, &str)],
+        message: &str,
+    ) -> Result<()> {
+        let mut state = self.lock();
+        let tip = state
+            .remotes
+            .get(remote)
+            .ok_or_else(|| anyhow!("No such remote: '{}'", remote))?
+            .branches
+            .get(branch)
+            .cloned();
+        let mut contents = state.files_of(tip.as_ref());
+        for (path, content) in files {
+            contents.insert(path.to_string(), content.as_bytes().to_vec());
+        }
+        let id = state.add_commit(tip, message, contents);
+        state
+            .remotes
+            .get_mut(remote)
+            .expect("remote checked above")
+            .branches
+            .insert(branch.to_string(), id);
+        Ok(())
+    }
+
+    /// Commit messages of `rev`, newest first
+    pub fn log(&self, rev: &str) -> Vec<String> {
+        let state = self.lock();
+        let Some(id) = state.resolve(rev) else {
+            return Vec::new();
+        };
+        state
+            .ancestors(&id)
+            .iter()
+            .filter_map(|id| state.commits.get(id))
+            .map(|c| c.message.clone())
+            .collect()
+    }
+
+    /// Branch names on `remote`, as stored in the mock
+    pub fn remote_branches(&self, remote: &str) -> Vec<String> {
+        self.lock()
+            .remotes
+            .get(remote)
+            .map(|r| r.branches.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Tag names on `remote`, as stored in the mock
+    pub fn remote_tags(&self, remote: &str) -> Vec<String> {
+        self.lock()
+            .remotes
+            .get(remote)
+            .map(|r| r.tags.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Every `Scm` call made so far, e.g. `push origin main`
+    pub fn calls(&self) -> Vec<String> {
+        self.lock().calls.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a call (after the injected latency) and return the state
+    fn call(&self, call: &str, args: &[&str]) -> MutexGuard<'_, State> {
+        if let Some(latency) = self.latency {
+            std::thread::sleep(latency);
+        }
+        let mut state = self.lock();
+        let mut line = call.to_string();
+        for arg in args {
+            line.push(' ');
+            line.push_str(arg);
+        }
+        state.calls.push(line);
+        state
+    }
+}
+
+impl Scm for MockScm {
+    fn current_branch(&self) -> Result<String> {
+        Ok(self.call("current_branch", &[]).head.clone())
+    }
+
+    fn current_commit_hash(&self) -> Result<String> {
+        let state = self.call("current_commit_hash", &[]);
+        state
+            .head_commit()
+            .cloned()
+            .ok_or_else(|| anyhow!("Branch '{}' has no commits yet", state.head))
+    }
+
+    fn stage_all(&self) -> Result<()> {
+        let mut state = self.call("stage_all", &[]);
+        state.index = state.worktree.clone();
+        Ok(())
+    }
+
+    fn stage_paths(&self, paths: &[PathBuf]) -> Result<()> {
+        let changed = self.uncommitted_paths()?;
+        let mut state = self.call("stage_paths", &[]);
+        for path in super::select_paths(changed, paths) {
+            match state.worktree.get(&path).cloned() {
+                Some(content) => state.index.insert(path, content),
+                None => state.index.remove(&path),
+            };
+        }
+        Ok(())
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        let mut state = self.call("commit", &[message]);
+        let parent = state.head_commit().cloned();
+        if state.index == state.files_of(parent.as_ref()) {
+            bail!("nothing to commit");
+        }
+        let files = state.index.clone();
+        let id = state.add_commit(parent, message, files);
+        let head = state.head.clone();
+        state.branches.insert(head, id);
+        Ok(())
+    }
+
+    fn has_changes(&self) -> Result<bool> {
+        let state = self.call("has_changes", &[]);
+        let committed = state.files_of(state.head_commit());
+        Ok(state.worktree != committed || state.index != committed)
+    }
+
+    fn commit_paths(&self, message: &str, paths: &[PathBuf]) -> Result<()> {
+        let mut state = self.call("commit_paths", &[message]);
+        let parent = state.head_commit().cloned();
+        let mut files = state.files_of(parent.as_ref());
+        let staged: Vec<String> = files.keys().chain(state.index.keys()).cloned().collect();
+        let selected = super::select_paths(staged, paths);
+        for path in &selected {
+            match state.index.get(path) {
+                Some(content) => files.insert(path.clone(), content.clone()),
+                None => files.remove(path),
+            };
+        }
+        if files == state.files_of(parent.as_ref()) {
+            bail!("nothing to commit");
+        }
+        let id = state.add_commit(parent, message, files);
+        let head = state.head.clone();
+        state.branches.insert(head, id);
+        Ok(())
+    }
+
+    fn has_staged_changes(&self) -> Result<bool> {
+        let state = self.call("has_staged_changes", &[]);
+        Ok(state.index != state.files_of(state.head_commit()))
+    }
+
+    fn uncommitted_paths(&self) -> Result<Vec<String>> {
+        let state = self.call("uncommitted_paths", &[]);
+        let committed = state.files_of(state.head_commit());
+        let paths: std::collections::BTreeSet<&String> = state
+            .worktree
+            .keys()
+            .chain(state.index.keys())
+            .chain(committed.keys())
+            .collect();
+        Ok(paths
+            .into_iter()
+            .filter(|path| {
+                state.worktree.get(*path) != committed.get(*path)
+                    || state.index.get(*path) != committed.get(*path)
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn add_remote(&self, name: &str, url: &str) -> Result<()> {
+        let mut state = self.call("add_remote", &[name, url]);
+        if state.remotes.contains_key(name) {
+            bail!("Remote '{}' already exists", name);
+        }
+        state.remotes.insert(
+            name.to_string(),
+            MockRemote {
+                url: url.to_string(),
+                ..MockRemote::default()
+            },
+        );
+        Ok(())
+    }
+
+    fn has_remote(&self, name: &str) -> bool {
+        self.call("has_remote", &[name]).remotes.contains_key(name)
+    }
+
+    fn get_remote_url(&self, name: &str) -> Result<String> {
+        let state = self.call("get_remote_url", &[name]);
+        state
+            .remotes
+            .get(name)
+            .map(|r| r.url.clone())
+            .ok_or_else(|| anyhow!("No such remote: '{}'", name))
+    }
+
+    fn set_remote_url(&self, name: &str, url: &str) -> Result<()> {
+        let mut state = self.call("set_remote_url", &[name, url]);
+        match state.remotes.get_mut(name) {
+            Some(remote) => {
+                remote.url = url.to_string();
+                Ok(())
+            }
+            None => bail!("No such remote: '{}'", name),
+        }
+    }
+
+    fn remove_remote(&self, name: &str) -> Result<()> {
+        let mut state = self.call("remove_remote", &[name]);
+        if state.remotes.remove(name).is_none() {
+            bail!("No such remote: '{}'", name);
+        }
+        state
+            .tracking
+            .retain(|tracked, _| !tracked.starts_with(&format!("{name}/")));
+        Ok(())
+    }
+
+    fn list_remotes(&self) -> Result<Vec<String>> {
+        Ok(self
+            .call("list_remotes", &[])
+            .remotes
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    fn push(&self, remote: &str, branch: &str) -> Result<()> {
+        let mut state = self.call("push", &[remote, branch]);
+        if let Some(failure) = state.push_failures.pop_front() {
+            bail!("Failed to push to remote '{}': {}", remote, failure);
+        }
+        let Some(local) = state.branches.get(branch).cloned() else {
+            bail!("src refspec {} does not match any", branch);
+        };
+        let Some(current) = state
+            .remotes
+            .get(remote)
+            .map(|r| r.branches.get(branch).cloned())
+        else {
+            bail!("No such remote: '{}'", remote);
+        };
+        if let Some(current) = current {
+            if !state.is_ancestor(&current, &local) {
+                bail!(
+                    "Failed to push to remote '{}': rejected (non-fast-forward)",
+                    remote
+                );
+            }
+        }
+        state
+            .remotes
+            .get_mut(remote)
+            .expect("remote checked above")
+            .branches
+            .insert(branch.to_string(), local.clone());
+        state.tracking.insert(format!("{remote}/{branch}"), local);
+        Ok(())
+    }
+
+    fn pull(&self, remote: &str, branch: &str) -> Result<()> {
+        let mut state = self.call("pull", &[remote, branch]);
+        state.fetch(remote)?;
+        let Some(theirs) = state.tracking.get(&format!("{remote}/{branch}")).cloned() else {
+            bail!("Couldn't find remote ref {}", branch);
+        };
+        let Some(ours) = state.head_commit().cloned() else {
+            state.set_head_to(theirs);
+            return Ok(());
+        };
+        if state.is_ancestor(&theirs, &ours) {
+            return Ok(());
+        }
+
+        // Replay our commits since the merge base on top of theirs (rebase)
+        let base = state.merge_base(&ours, &theirs);
+        let mut ours_only: Vec<String> = state
+            .ancestors(&ours)
+            .into_iter()
+            .take_while(|id| Some(id) != base.as_ref())
+            .collect();
+        ours_only.reverse();
+        let mut tip = theirs;
+        for id in ours_only {
+            let commit = state.commits[&id].clone();
+            let files =
+                state.apply_changes(commit.parent.as_ref(), &id, &state.files_of(Some(&tip)));
+            tip = state.add_commit(Some(tip), &commit.message, files);
+        }
+        state.set_head_to(tip);
+        Ok(())
+    }
+
+    fn reset_soft(&self, commit: &str) -> Result<()> {
+        let mut state = self.call("reset_soft", &[commit]);
+        let Some(id) = state.resolve(commit) else {
+            bail!("Unknown revision: '{}'", commit);
+        };
+        let head = state.head.clone();
+        state.branches.insert(head, id);
+        Ok(())
+    }
+
+    fn create_branch(&self, name: &str) -> Result<()> {
+        let mut state = self.call("create_branch", &[name]);
+        if state.branches.contains_key(name) {
+            bail!("A branch named '{}' already exists", name);
+        }
+        let Some(head) = state.head_commit().cloned() else {
+            bail!("Not a valid object name: '{}'", state.head);
+        };
+        state.branches.insert(name.to_string(), head);
+        Ok(())
+    }
+
+    fn checkout(&self, branch: &str) -> Result<()> {
+        let mut state = self.call("checkout", &[branch]);
+        let Some(id) = state.branches.get(branch).cloned() else {
+            bail!("pathspec '{}' did not match any branch", branch);
+        };
+        state.head = branch.to_string();
+        state.set_head_to(id);
+        Ok(())
+    }
+
+    fn merge(&self, branch: &str) -> Result<()> {
+        let mut state = self.call("merge", &[branch]);
+        let Some(theirs) = state.resolve(branch) else {
+            bail!("merge: {} - not something we can merge", branch);
+        };
+        let Some(ours) = state.head_commit().cloned() else {
+            state.set_head_to(theirs);
+            return Ok(());
+        };
+        if state.is_ancestor(&theirs, &ours) {
+            return Ok(());
+        }
+        if state.is_ancestor(&ours, &theirs) {
+            state.set_head_to(theirs);
+            return Ok(());
+        }
+
+        // Their changes since the merge base win over ours
+        let base = state.merge_base(&ours, &theirs);
+        let files = state.apply_changes(base.as_ref(), &theirs, &state.files_of(Some(&ours)));
+        let message = format!("Merge branch '{branch}'");
+        let id = state.add_commit(Some(ours), &message, files);
+        state.set_head_to(id);
+        Ok(())
+    }
+
+    fn delete_branch(&self, name: &str) -> Result<()> {
+        let mut state = self.call("delete_branch", &[name]);
+        if state.head == name {
+            bail!("Cannot delete branch '{}' checked out", name);
+        }
+        if state.branches.remove(name).is_none() {
+            bail!("branch '{}' not found", name);
+        }
+        Ok(())
+    }
+
+    fn delete_remote_branch(&self, remote: &str, branch: &str) -> Result<()> {
+        let mut state = self.call("delete_remote_branch", &[remote, branch]);
+        if let Some(failure) = state.push_failures.pop_front() {
+            bail!("Failed to push to remote '{}': {}", remote, failure);
+        }
+        let Some(remote_state) = state.remotes.get_mut(remote) else {
+            bail!("No such remote: '{}'", remote);
+        };
+        if remote_state.branches.remove(branch).is_none() {
+            bail!("unable to delete '{}': remote ref does not exist", branch);
+        }
+        state.tracking.remove(&format!("{remote}/{branch}"));
+        Ok(())
+    }
+
+    fn branch_exists(&self, name: &str) -> bool {
+        self.call("branch_exists", &[name]).resolve(name).is_some()
+    }
+
+    fn fetch(&self, remote: &str) -> Result<()> {
+        self.call("fetch", &[remote]).fetch(remote)
+    }
+
+    fn list_branches(&self) -> Result<Vec<String>> {
+        Ok(self
+            .call("list_branches", &[])
+            .branches
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    fn list_remote_branches(&self, remote: &str) -> Result<Vec<String>> {
+        let state = self.call("list_remote_branches", &[remote]);
+        state
+            .remotes
+            .get(remote)
+            .map(|r| r.branches.keys().cloned().collect())
+            .ok_or_else(|| anyhow!("No such remote: '{}'", remote))
+    }
+
+    fn list_files_at(&self, rev: &str, path: &str) -> Result<Vec<String>> {
+        let state = self.call("list_files_at", &[rev, path]);
+        let Some(id) = state.resolve(rev) else {
+            bail!("Not a valid object name: '{}'", rev);
+        };
+        let prefix = path.trim_end_matches('/');
+        Ok(state.commits[&id]
+            .files
+            .keys()
+            .filter(|file| {
+                prefix.is_empty()
+                    || prefix == "."
+                    || file.as_str() == prefix
+                    || file.starts_with(&format!("{prefix}/"))
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn read_file_at(&self, rev: &str, path: &str) -> Result<Vec<u8>> {
+        let state = self.call("read_file_at", &[rev, path]);
+        let Some(id) = state.resolve(rev) else {
+            bail!("Not a valid object name: '{}'", rev);
+        };
+        state.commits[&id]
+            .files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| anyhow!("Path '{}' does not exist in '{}'", path, rev))
+    }
+
+    fn list_files_in_history(&self) -> Result<Vec<String>> {
+        let state = self.call("list_files_in_history", &[]);
+        let Some(head) = state.head_commit() else {
+            return Ok(Vec::new());
+        };
+        let files: std::collections::BTreeSet<String> = state
+            .ancestors(head)
+            .iter()
+            .flat_map(|id| state.commits[id].files.keys().cloned())
+            .collect();
+        Ok(files.into_iter().collect())
+    }
+
+    fn merge_base(&self, a: &str, b: &str) -> Result<String> {
+        let state = self.call("merge_base", &[a, b]);
+        let (Some(a_id), Some(b_id)) = (state.resolve(a), state.resolve(b)) else {
+            bail!("Not a valid object name: '{}' or '{}'", a, b);
+        };
+        state
+            .merge_base(&a_id, &b_id)
+            .ok_or_else(|| anyhow!("No common ancestor of '{}' and '{}'", a, b))
+    }
+
+    fn log_paths(&self, paths: &[String]) -> Result<Vec<CommitInfo>> {
+        let state = self.call("log_paths", &[]);
+        let Some(head) = state.head_commit() else {
+            return Ok(Vec::new());
+        };
+        let mut commits = Vec::new();
+        for id in state.ancestors(head).into_iter().rev() {
+            let commit = &state.commits[&id];
+            let before = state.files_of(commit.parent.as_ref());
+            if paths.iter().any(|p| before.get(p) != commit.files.get(p)) {
+                commits.push(CommitInfo {
+                    id: id.clone(),
+                    author: "mock".to_string(),
+                    date: String::new(),
+                    summary: commit.message.lines().next().unwrap_or_default().to_string(),
+                });
+            }
+        }
+        Ok(commits)
+    }
+
+    fn create_tag(&self, name: &str, message: &str) -> Result<()> {
+        let mut state = self.call("create_tag", &[name, message]);
+        let Some(commit) = state.head_commit().cloned() else {
+            bail!("Failed to resolve 'HEAD' as a valid ref");
+        };
+        if state.tags.iter().any(|tag| tag.name == name) {
+            bail!("tag '{}' already exists", name);
+        }
+        state.tags.push(TagInfo {
+            name: name.to_string(),
+            commit,
+            date: String::new(),
+            message: message.lines().next().unwrap_or_default().to_string(),
+        });
+        Ok(())
+    }
+
+    fn list_tags(&self) -> Result<Vec<TagInfo>> {
+        Ok(self.call("list_tags", &[]).tags.clone())
+    }
+
+    fn delete_tag(&self, name: &str) -> Result<()> {
+        let mut state = self.call("delete_tag", &[name]);
+        let before = state.tags.len();
+        state.tags.retain(|tag| tag.name != name);
+        if state.tags.len() == before {
+            bail!("tag '{}' not found", name);
+        }
+        Ok(())
+    }
+
+    fn push_tag(&self, remote: &str, name: &str) -> Result<()> {
+        let mut state = self.call("push_tag", &[remote, name]);
+        if let Some(failure) = state.push_failures.pop_front() {
+            bail!("Failed to push to remote '{}': {}", remote, failure);
+        }
+        let Some(commit) = state.resolve(name) else {
+            bail!("src refspec {} does not match any", name);
+        };
+        let Some(remote_state) = state.remotes.get_mut(remote) else {
+            bail!("No such remote: '{}'", remote);
+        };
+        remote_state.tags.insert(name.to_string(), commit);
+        Ok(())
+    }
+
+    fn delete_remote_tag(&self, remote: &str, name: &str) -> Result<()> {
+        let mut state = self.call("delete_remote_tag", &[remote, name]);
+        let Some(remote_state) = state.remotes.get_mut(remote) else {
+            bail!("No such remote: '{}'", remote);
+        };
+        if remote_state.tags.remove(name).is_none() {
+            bail!("unable to delete '{}': remote ref does not exist", name);
+        }
+        Ok(())
+    }
+
+    fn create_bundle(&self, path: &std::path::Path, branch: &str, since: Option<&str>) -> Result<()> {
+        let state = self.call("create_bundle", &[branch, since.unwrap_or_default()]);
+        let Some(tip) = state.resolve(branch) else {
+            bail!("Not a valid object name: '{}'", branch);
+        };
+        // The mock keeps every commit in memory; the file only names the tip
+        std::fs::write(path, format!("{branch} {tip}\n"))?;
+        Ok(())
+    }
+
+    fn verify_bundle(&self, path: &std::path::Path) -> Result<()> {
+        let _state = self.call("verify_bundle", &[&path.to_string_lossy()]);
+        if !path.exists() {
+            bail!("could not open '{}'", path.display());
+        }
+        Ok(())
+    }
+
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<String>> {
+        let state = self.call("changed_files", &[from, to]);
+        let (Some(from_id), Some(to_id)) = (state.resolve(from), state.resolve(to)) else {
+            bail!("Not a valid object name: '{}' or '{}'", from, to);
+        };
+        let before = state.files_of(Some(&from_id));
+        let after = state.files_of(Some(&to_id));
+        let paths: std::collections::BTreeSet<&String> =
+            before.keys().chain(after.keys()).collect();
+        Ok(paths
+            .into_iter()
+            .filter(|path| before.get(*path) != after.get(*path))
+            .cloned()
+            .collect())
+    }
+
+    fn rewrite_history(&self, rewrite: &HistoryRewrite) -> Result<()> {
+        let mut state = self.call("rewrite_history", &[]);
+        let Some(head) = state.head_commit().cloned() else {
+            bail!("Nothing to rewrite on '{}'", state.head);
+        };
+        let removed = |path: &str| {
+            rewrite
+                .remove_paths
+                .iter()
+                .any(|r| path == r || path.starts_with(&format!("{r}/")))
+        };
+        let mut chain = state.ancestors(&head);
+        chain.reverse();
+        let mut tip: Option<String> = None;
+        for id in chain {
+            let commit = state.commits[&id].clone();
+            let mut files: Files = commit
+                .files
+                .into_iter()
+                .filter(|(path, _)| !removed(path))
+                .collect();
+            if let Some(content) = rewrite
+                .scrub_path
+                .as_ref()
+                .and_then(|path| files.get_mut(path))
+            {
+                let text = String::from_utf8_lossy(content).to_string();
+                *content = text
+                    .lines()
+                    .filter(|line| !rewrite.drop_lines.iter().any(|d| line.contains(d.as_str())))
+                    .flat_map(|line| [line, "\n"])
+                    .collect::<String>()
+                    .into_bytes();
+            }
+            if tip.is_some() && state.files_of(tip.as_ref()) == files {
+                continue;
+            }
+            tip = Some(state.add_commit(tip, &commit.message, files));
+        }
+        if let Some(tip) = tip {
+            state.set_head_to(tip);
+        }
+        Ok(())
+    }
+
+    fn force_push(&self, remote: &str, branch: &str) -> Result<()> {
+        let mut state = self.call("force_push", &[remote, branch]);
+        if let Some(failure) = state.push_failures.pop_front() {
+            bail!("Failed to push to remote '{}': {}", remote, failure);
+        }
+        let Some(local) = state.branches.get(branch).cloned() else {
+            bail!("src refspec {} does not match any", branch);
+        };
+        let Some(remote_state) = state.remotes.get_mut(remote) else {
+            bail!("No such remote: '{}'", remote);
+        };
+        remote_state
+            .branches
+            .insert(branch.to_string(), local.clone());
+        state.tracking.insert(format!("{remote}/{branch}"), local);
+        Ok(())
+    }
+
+    fn reset_hard(&self, rev: &str) -> Result<()> {
+        let mut state = self.call("reset_hard", &[rev]);
+        let Some(id) = state.resolve(rev) else {
+            bail!("Unknown revision: '{}'", rev);
+        };
+        state.set_head_to(id);
+        Ok(())
+    }
+
+    fn prune_unreachable(&self) -> Result<()> {
+        let mut state = self.call("prune_unreachable", &[]);
+        let reachable: std::collections::HashSet<String> = state
+            .branches
+            .values()
+            .chain(state.tracking.values())
+            .chain(state.remotes.values().flat_map(|r| r.branches.values()))
+            .flat_map(|id| state.ancestors(id))
+            .collect();
+        state.commits.retain(|id, _| reachable.contains(id));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_with_remote() -> MockScm {
+        let repo = MockScm::new();
+        repo.write_file("README.md", "# sync\n");
+        repo.stage_all().unwrap();
+        repo.commit("Initial commit").unwrap();
+        repo.add_remote("origin", "mock://origin").unwrap();
+        repo.push("origin", "main").unwrap();
+        repo
+    }
+
+    #[test]
+    fn test_commit_branch_and_merge() {
+        let repo = repo_with_remote();
+        assert!(!repo.has_changes().unwrap());
+        assert!(repo.commit("empty").is_err());
+
+        repo.create_branch("feature").unwrap();
+        repo.checkout("feature").unwrap();
+        repo.write_file("a.jsonl", "a\n");
+        assert!(repo.has_changes().unwrap());
+        repo.stage_all().unwrap();
+        repo.commit("Add a").unwrap();
+
+        repo.checkout("main").unwrap();
+        assert_eq!(repo.file("a.jsonl"), None);
+        repo.merge("feature").unwrap();
+        assert_eq!(repo.file("a.jsonl"), Some(b"a\n".to_vec()));
+        assert_eq!(repo.log("main"), ["Add a", "Initial commit"]);
+
+        assert!(repo.delete_branch("main").is_err());
+        repo.delete_branch("feature").unwrap();
+        assert_eq!(repo.list_branches().unwrap(), ["main"]);
+    }
+
+    #[test]
+    fn test_push_rejection_and_rebase_pull() {
+        let repo = repo_with_remote();
+        repo.push_from_elsewhere("origin", "main", &[("b.jsonl", "b\n")], "Add b")
+            .unwrap();
+
+        repo.write_file("a.jsonl", "a\n");
+        repo.stage_all().unwrap();
+        repo.commit("Add a").unwrap();
+        let err = repo.push("origin", "main").unwrap_err();
+        assert!(err.to_string().contains("non-fast-forward"));
+
+        repo.pull("origin", "main").unwrap();
+        assert_eq!(repo.log("main"), ["Add a", "Add b", "Initial commit"]);
+        assert_eq!(
+            repo.list_files_at("main", ".").unwrap(),
+            ["README.md", "a.jsonl", "b.jsonl"]
+        );
+        repo.push("origin", "main").unwrap();
+        assert_eq!(repo.read_file_at("origin/main", "a.jsonl").unwrap(), b"a\n");
+    }
+
+    #[test]
+    fn test_rewrite_history_and_force_push() {
+        let repo = repo_with_remote();
+        repo.write_file("secret.jsonl", "key\n");
+        repo.write_file("history.jsonl", "secret\nkept\n");
+        repo.stage_all().unwrap();
+        repo.commit("Add secret").unwrap();
+        repo.push("origin", "main").unwrap();
+
+        repo.rewrite_history(&HistoryRewrite {
+            remove_paths: vec!["secret.jsonl".to_string()],
+            scrub_path: Some("history.jsonl".to_string()),
+            drop_lines: vec!["secret".to_string()],
+        })
+        .unwrap();
+        assert_eq!(
+            repo.list_files_in_history().unwrap(),
+            ["README.md", "history.jsonl"]
+        );
+        assert_eq!(repo.file("history.jsonl"), Some(b"kept\n".to_vec()));
+
+        assert!(repo.push("origin", "main").is_err());
+        repo.force_push("origin", "main").unwrap();
+        repo.prune_unreachable().unwrap();
+        assert!(repo.read_file_at("origin/main", "secret.jsonl").is_err());
+        assert_eq!(repo.log("main"), ["Add secret", "Initial commit"]);
+    }
+
+    #[test]
+    fn test_injected_failures_and_call_log() {
+        let repo = repo_with_remote().with_latency(Duration::from_millis(1));
+        repo.create_branch("sync-local-x").unwrap();
+        repo.fail_next_pushes(1);
+        assert!(repo.push("origin", "sync-local-x").is_err());
+        repo.push("origin", "sync-local-x").unwrap();
+        assert_eq!(repo.remote_branches("origin"), ["main", "sync-local-x"]);
+
+        let calls = repo.calls();
+        assert_eq!(
+            calls[calls.len() - 2..],
+            ["push origin sync-local-x", "push origin sync-local-x"]
+        );
+    }
+}
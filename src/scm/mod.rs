@@ -6,12 +6,17 @@
 mod git;
 mod hg;
 pub mod lfs;
+#[cfg(any(test, feature = "test_support"))]
+mod mock;
 
 use anyhow::{anyhow, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub use git::GitScm;
 pub use hg::HgScm;
+#[cfg(any(test, feature = "test_support"))]
+#[allow(unused_imports)]
+pub use mock::MockScm;
 
 /// SCM backend types.
 ///
@@ -47,6 +52,32 @@ impl Backend {
     }
 }
 
+/// A rewrite of the current branch's history, for purging data that was
+/// already committed.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryRewrite {
+    /// Files (or directories) removed from every commit
+    pub remove_paths: Vec<String>,
+    /// File whose lines containing any of `drop_lines` are removed from every
+    /// commit
+    pub scrub_path: Option<String>,
+    /// Substrings marking the lines to remove from `scrub_path`
+    pub drop_lines: Vec<String>,
+}
+
+/// A commit, as listed by [`Scm::log_paths`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// Full commit ID
+    pub id: String,
+    /// Author name
+    pub author: String,
+    /// Author date (RFC 3339)
+    pub date: String,
+    /// First line of the commit message
+    pub summary: String,
+}
+
 /// Trait for source control management operations.
 pub trait Scm: Send + Sync {
     /// Get the current branch name.
@@ -58,12 +89,30 @@ pub trait Scm: Send + Sync {
     /// Stage all changes (add and remove).
     fn stage_all(&self) -> Result<()>;
 
+    /// Stage changes (additions, edits, and removals) under each of `paths`,
+    /// leaving the rest of the working tree unstaged.
+    ///
+    /// Paths are relative to the repository root and may be files or
+    /// directories; paths without changes are skipped.
+    fn stage_paths(&self, paths: &[PathBuf]) -> Result<()>;
+
     /// Commit staged changes with a message.
     fn commit(&self, message: &str) -> Result<()>;
 
+    /// Commit only the staged changes under `paths` (relative to the
+    /// repository root), leaving other staged changes staged.
+    fn commit_paths(&self, message: &str, paths: &[PathBuf]) -> Result<()>;
+
     /// Check if there are uncommitted changes.
     fn has_changes(&self) -> Result<bool>;
 
+    /// Check if there are staged changes to commit.
+    fn has_staged_changes(&self) -> Result<bool>;
+
+    /// List paths with uncommitted changes, staged or not, including
+    /// untracked files.
+    fn uncommitted_paths(&self) -> Result<Vec<String>>;
+
     /// Add a remote repository.
     fn add_remote(&self, name: &str, url: &str) -> Result<()>;
 
@@ -114,6 +163,128 @@ pub trait Scm: Send + Sync {
 
     /// List all local branches.
     fn list_branches(&self) -> Result<Vec<String>>;
+
+    /// List all branches on a remote.
+    fn list_remote_branches(&self, remote: &str) -> Result<Vec<String>>;
+
+    /// List files under `path` at a revision (e.g. `origin/main`).
+    ///
+    /// Paths are relative to the repository root.
+    fn list_files_at(&self, rev: &str, path: &str) -> Result<Vec<String>>;
+
+    /// Read a file's contents at a revision without checking it out.
+    fn read_file_at(&self, rev: &str, path: &str) -> Result<Vec<u8>>;
+
+    /// List every file path committed on the current branch, in any commit.
+    fn list_files_in_history(&self) -> Result<Vec<String>>;
+
+    /// Find the latest common ancestor of two revisions.
+    fn merge_base(&self, a: &str, b: &str) -> Result<String>;
+
+    /// List files added, modified, or removed between two revisions.
+    fn changed_files(&self, from: &str, to: &str) -> Result<Vec<String>>;
+
+    /// List the commits on the current branch that touched any of `paths`,
+    /// oldest first (parents before children).
+    fn log_paths(&self, paths: &[String]) -> Result<Vec<CommitInfo>>;
+
+    /// Create an annotated tag at the current commit.
+    fn create_tag(&self, name: &str, message: &str) -> Result<()>;
+
+    /// List all tags, oldest first.
+    fn list_tags(&self) -> Result<Vec<TagInfo>>;
+
+    /// Delete a tag locally.
+    fn delete_tag(&self, name: &str) -> Result<()>;
+
+    /// Push a tag to a remote.
+    fn push_tag(&self, remote: &str, name: &str) -> Result<()>;
+
+    /// Delete a tag on a remote.
+    fn delete_remote_tag(&self, remote: &str, name: &str) -> Result<()>;
+
+    /// Write `branch` to a bundle file, for moving history without a
+    /// shared remote.
+    ///
+    /// With `since`, only commits not reachable from it are included, and
+    /// the receiving repository must already have `since`. The bundle can
+    /// then be added as a remote and pulled from.
+    fn create_bundle(&self, path: &Path, branch: &str, since: Option<&str>) -> Result<()>;
+
+    /// Check that a bundle file is valid and that this repository has the
+    /// commits it builds on.
+    fn verify_bundle(&self, path: &Path) -> Result<()>;
+
+    /// Rewrite every commit on the current branch, changing commit IDs.
+    ///
+    /// The old commits stay in the repository until
+    /// [`prune_unreachable`](Scm::prune_unreachable) runs after nothing else
+    /// (temp branches, remote-tracking refs) points at them.
+    fn rewrite_history(&self, rewrite: &HistoryRewrite) -> Result<()>;
+
+    /// Push a branch whose history was rewritten, replacing the remote's.
+    fn force_push(&self, remote: &str, branch: &str) -> Result<()>;
+
+    /// Reset the current branch, index, and working directory to a revision.
+    fn reset_hard(&self, rev: &str) -> Result<()>;
+
+    /// Drop commits no branch or ref points at anymore (including reflogs).
+    fn prune_unreachable(&self) -> Result<()>;
+}
+
+/// A tag, as listed by [`Scm::list_tags`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagInfo {
+    /// Tag name
+    pub name: String,
+    /// Full ID of the tagged commit
+    pub commit: String,
+    /// When the tag was created (RFC 3339)
+    pub date: String,
+    /// First line of the tag message
+    pub message: String,
+}
+
+/// Parse a `<id>\x1f<author>\x1f<date>\x1f<summary>` line of log output
+fn parse_commit_line(line: &str) -> Option<CommitInfo> {
+    let mut fields = line.splitn(4, '\x1f');
+    Some(CommitInfo {
+        id: fields.next()?.to_string(),
+        author: fields.next()?.to_string(),
+        date: fields.next()?.to_string(),
+        summary: fields.next().unwrap_or_default().to_string(),
+    })
+}
+
+/// Parse a `<name>\x1f<commit>\x1f<date>\x1f<message>` line of tag output
+fn parse_tag_line(line: &str) -> Option<TagInfo> {
+    let mut fields = line.splitn(4, '\x1f');
+    Some(TagInfo {
+        name: fields.next()?.to_string(),
+        commit: fields.next()?.to_string(),
+        date: fields.next()?.to_string(),
+        message: fields.next().unwrap_or_default().to_string(),
+    })
+}
+
+/// Keep the paths in `changed` that lie under any of `paths` (the
+/// selection [`Scm::stage_paths`] stages).
+fn select_paths(changed: Vec<String>, paths: &[PathBuf]) -> Vec<String> {
+    let roots: Vec<String> = paths
+        .iter()
+        .map(|p| p.to_string_lossy().replace('\\', "/").trim_end_matches('/').to_string())
+        .collect();
+    changed
+        .into_iter()
+        .filter(|path| {
+            roots.iter().any(|root| {
+                path == root
+                    || path
+                        .strip_prefix(root.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+        })
+        .collect()
 }
 
 /// Check if a directory is a repository (Git or Mercurial).
@@ -0,0 +1,249 @@
+//! PII scrubbing
+//!
+//! Conversations pick up personal data: email addresses in git logs, phone
+//! numbers in pasted documents, IP addresses in command output. Scrubbing
+//! replaces matches of built-in rules (`email`, `phone`, `ip`) and of user
+//! regexes with placeholders such as `[email]` before a session is saved to
+//! the sync repo. Matches that also match an allowlist regex (say, a public
+//! support address) are kept.
+//!
+//! Like compaction, scrubbing only affects the copy in the sync repo; the
+//! local session file is never rewritten. Only string values inside message
+//! content and other payloads are scrubbed; UUIDs, session IDs, and
+//! timestamps are left untouched so merging behaves as before.
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::parser::{ConversationEntry, ConversationSession};
+
+/// Built-in rules: name and regex
+pub const BUILTIN_RULES: &[(&str, &str)] = &[
+    (
+        "email",
+        r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+    ),
+    (
+        "phone",
+        r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{3}\)|\b\d{3})[ .-]\d{3}[ .-]\d{4}\b",
+    ),
+    (
+        "ip",
+        r"\b(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\b",
+    ),
+];
+
+/// Rule name reported for user regexes
+pub const CUSTOM_RULE: &str = "custom";
+
+/// Substitutions made, by rule name
+pub type ScrubCounts = BTreeMap<String, usize>;
+
+struct Rule {
+    name: String,
+    regex: Regex,
+}
+
+/// Compiled scrubbing rules
+pub struct Scrubber {
+    rules: Vec<Rule>,
+    allowlist: Vec<Regex>,
+}
+
+impl Scrubber {
+    /// Compile built-in rules by name, user regexes, and allowlist regexes
+    pub fn new(builtin: &[String], regexes: &[String], allowlist: &[String]) -> Result<Self> {
+        let mut rules = Vec::new();
+        for name in builtin {
+            let Some((name, pattern)) = BUILTIN_RULES
+                .iter()
+                .find(|(rule, _)| rule.eq_ignore_ascii_case(name.trim()))
+            else {
+                bail!(
+                    "Unknown scrubbing rule: '{}'. Use {}.",
+                    name,
+                    BUILTIN_RULES
+                        .iter()
+                        .map(|(rule, _)| format!("'{rule}'"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            };
+            rules.push(Rule {
+                name: name.to_string(),
+                regex: Regex::new(pattern).expect("built-in scrubbing rules are valid"),
+            });
+        }
+        for pattern in regexes {
+            rules.push(Rule {
+                name: CUSTOM_RULE.to_string(),
+                regex: Regex::new(pattern)
+                    .with_context(|| format!("Invalid scrubbing regex: '{pattern}'"))?,
+            });
+        }
+        let allowlist = allowlist
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid scrubbing allowlist regex: '{pattern}'"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules, allowlist })
+    }
+
+    /// Whether there is nothing to scrub
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Scrub a string, returning the new text if anything was replaced
+    pub fn scrub_text(&self, text: &str, counts: &mut ScrubCounts) -> Option<String> {
+        let mut current: Option<String> = None;
+        for rule in &self.rules {
+            let input = current.as_deref().unwrap_or(text);
+            let mut replaced = 0;
+            let output = rule.regex.replace_all(input, |caps: &regex::Captures| {
+                let found = &caps[0];
+                if self.allowlist.iter().any(|allow| allow.is_match(found)) {
+                    found.to_string()
+                } else {
+                    replaced += 1;
+                    format!("[{}]", rule.name)
+                }
+            });
+            if replaced > 0 {
+                *counts.entry(rule.name.clone()).or_default() += replaced;
+                current = Some(output.into_owned());
+            }
+        }
+        current
+    }
+
+    /// Scrub a session, returning the scrubbed copy and the substitutions made
+    pub fn scrub_session(
+        &self,
+        session: &ConversationSession,
+    ) -> (ConversationSession, ScrubCounts) {
+        let mut counts = ScrubCounts::new();
+        let entries = session
+            .entries
+            .iter()
+            .map(|entry| self.scrub_entry(entry, &mut counts))
+            .collect();
+        (
+            ConversationSession {
+                session_id: session.session_id.clone(),
+                entries,
+                file_path: session.file_path.clone(),
+            },
+            counts,
+        )
+    }
+
+    fn scrub_entry(
+        &self,
+        entry: &ConversationEntry,
+        counts: &mut ScrubCounts,
+    ) -> ConversationEntry {
+        let mut scrubbed = entry.clone();
+        let mut replaced = false;
+        if let Some(message) = scrubbed.message.as_mut() {
+            replaced |= self.scrub_value(message, counts);
+        }
+        replaced |= self.scrub_value(&mut scrubbed.extra, counts);
+        if replaced {
+            // The original line no longer matches this entry
+            scrubbed.raw_line = None;
+            crate::parser::mark_derived(&mut scrubbed, entry);
+        }
+        scrubbed
+    }
+
+    /// Scrub every string inside a JSON value (object keys are left alone)
+    fn scrub_value(&self, value: &mut Value, counts: &mut ScrubCounts) -> bool {
+        match value {
+            Value::String(text) => match self.scrub_text(text, counts) {
+                Some(scrubbed) => {
+                    *text = scrubbed;
+                    true
+                }
+                None => false,
+            },
+            Value::Array(items) => items.iter_mut().fold(false, |replaced, item| {
+                self.scrub_value(item, counts) | replaced
+            }),
+            Value::Object(map) => map.values_mut().fold(false, |replaced, item| {
+                self.scrub_value(item, counts) | replaced
+            }),
+            _ => false,
+        }
+    }
+}
+
+/// Format counts as `3 email, 1 ip`
+pub fn describe_counts(counts: &ScrubCounts) -> String {
+    counts
+        .iter()
+        .map(|(rule, count)| format!("{count} {rule}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrubber(builtin: &[&str], regexes: &[&str], allowlist: &[&str]) -> Scrubber {
+        let owned = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        Scrubber::new(&owned(builtin), &owned(regexes), &owned(allowlist)).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_rules_and_allowlist() {
+        let scrubber = scrubber(&["email", "phone", "ip"], &[r"ACME-\d+"], &["^support@"]);
+        let mut counts = ScrubCounts::new();
+        let text = "Mail jane.doe@corp.example.com or support@corp.example.com, \
+                    call +1 (555) 123-4567, ssh 10.0.0.12, ticket ACME-42, v1.2.3";
+        let scrubbed = scrubber.scrub_text(text, &mut counts).unwrap();
+        assert_eq!(
+            scrubbed,
+            "Mail [email] or support@corp.example.com, \
+             call [phone], ssh [ip], ticket [custom], v1.2.3"
+        );
+        assert_eq!(describe_counts(&counts), "1 custom, 1 email, 1 ip, 1 phone");
+
+        assert!(scrubber.scrub_text("nothing here", &mut counts).is_none());
+        assert!(Scrubber::new(&["names".to_string()], &[], &[]).is_err());
+        assert!(Scrubber::new(&[], &["(".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_scrub_session_keeps_ids() {
+        let line = r#"{"type":"user","uuid":"u1","sessionId":"s","timestamp":"2025-01-01T00:00:00Z","cwd":"/home/a","message":{"role":"user","content":[{"type":"text","text":"I'm a@b.io"}]}}"#;
+        let mut entry: ConversationEntry = serde_json::from_str(line).unwrap();
+        entry.raw_line = Some(line.to_string());
+        let untouched = r#"{"type":"assistant","uuid":"u2","message":{"content":"ok"}}"#;
+        let mut other: ConversationEntry = serde_json::from_str(untouched).unwrap();
+        other.raw_line = Some(untouched.to_string());
+        let session = ConversationSession {
+            session_id: "s".to_string(),
+            entries: vec![entry, other],
+            file_path: "s.jsonl".to_string(),
+        };
+
+        let (scrubbed, counts) = scrubber(&["email"], &[], &[]).scrub_session(&session);
+        assert_eq!(counts["email"], 1);
+        let first = &scrubbed.entries[0];
+        assert_eq!(first.uuid.as_deref(), Some("u1"));
+        assert_eq!(
+            first.message.as_ref().unwrap()["content"][0]["text"],
+            "I'm [email]"
+        );
+        assert!(first.raw_line.is_none());
+        assert!(crate::parser::is_derived_from(first, &session.entries[0]));
+        assert!(scrubbed.entries[1].raw_line.is_some());
+        assert!(crate::parser::original_hash(&scrubbed.entries[1]).is_none());
+    }
+}
@@ -0,0 +1,695 @@
+//! Local HTTP API over the synced history
+//!
+//! `serve` answers JSON requests about the sessions in the sync repo, for
+//! dashboards and editor plugins that would otherwise shell out and parse
+//! command output:
+//!
+//! - `GET /status`: the sync repo, its branch, and the last operation
+//! - `GET /projects`
+//! - `GET /sessions?project=&offset=&limit=`, most recently active first
+//! - `GET /sessions/<id>` and `GET /sessions/<id>/entries?offset=&limit=`
+//! - `GET /search?q=&project=&limit=`
+//! - `POST /sync`: run a sync, with `Authorization: Bearer <token>`
+//! - `GET /metrics`: Prometheus metrics (see [`crate::metrics`])
+//!
+//! The token is the `api-token` credential (or `CLAUDE_CODE_SYNC_API_TOKEN`);
+//! without one, `/sync` is refused. Until `min_sync_interval_secs` have
+//! passed since the last operation, `/sync` answers 429. Requests are
+//! answered one at a time, each on a connection of its own, and the sessions
+//! are read again whenever a sync has run since they were last read.
+//!
+//! Requests whose `Host` isn't a loopback name or the listen address are
+//! refused, so a web page can't reach the API through a DNS name it
+//! controls. `/metrics` is exempt, since scrapers name the host as their
+//! configuration does and the metrics hold no history.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::corpus::Corpus;
+use crate::credentials;
+use crate::glyphs;
+use crate::history::OperationHistory;
+use crate::metrics::{LastSync, ServerCounters, Snapshot};
+
+/// Environment variable holding the token `/sync` requires, used instead of
+/// the `api-token` credential when set
+pub const API_TOKEN_ENV_VAR: &str = "CLAUDE_CODE_SYNC_API_TOKEN";
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Content type of every response but the metrics
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Largest request head (request line and headers) accepted
+const MAX_HEAD_BYTES: u64 = 64 * 1024;
+
+/// Largest request body read (and discarded); no endpoint takes one
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+
+/// Items returned by the list endpoints unless the caller asks otherwise
+const DEFAULT_LIMIT: usize = 100;
+
+/// Matches returned by `/search` unless the caller asks otherwise
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// An HTTP request, as far as the API reads it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string, percent-decoded
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    /// Header names are lowercased
+    pub headers: Vec<(String, String)>,
+}
+
+impl Request {
+    /// First value of query parameter `name`
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Value of header `name` (lowercase)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Query parameter `name` as a number, `default` when absent
+    fn number(&self, name: &str, default: usize) -> Result<usize, Response> {
+        match self.query(name) {
+            None => Ok(default),
+            Some(value) => value.parse().map_err(|_| {
+                Response::error(400, &format!("'{name}' must be a number, not '{value}'"))
+            }),
+        }
+    }
+}
+
+/// A response: JSON, or text of another content type as a JSON string
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: impl Serialize) -> Self {
+        match serde_json::to_value(body) {
+            Ok(body) => Self {
+                status: 200,
+                content_type: JSON_CONTENT_TYPE,
+                body,
+            },
+            Err(e) => Self::error(500, &format!("Failed to serialize response: {e}")),
+        }
+    }
+
+    fn text(content_type: &'static str, text: String) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body: Value::String(text),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: JSON_CONTENT_TYPE,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+/// A page of a longer list
+#[derive(Serialize)]
+struct Page<T> {
+    total: usize,
+    offset: usize,
+    items: Vec<T>,
+}
+
+impl<T> Page<T> {
+    fn of(all: Vec<T>, offset: usize, limit: usize) -> Self {
+        Self {
+            total: all.len(),
+            offset,
+            items: all.into_iter().skip(offset).take(limit).collect(),
+        }
+    }
+}
+
+/// Answers API requests from the sessions in the sync repo
+pub struct ApiServer {
+    corpus: Corpus,
+    /// Token `/sync` requires; `/sync` is refused without one
+    token: Option<String>,
+    /// When the last operation the corpus reflects ran
+    loaded_after: Option<DateTime<Utc>>,
+    counters: ServerCounters,
+}
+
+impl ApiServer {
+    pub fn new(corpus: Corpus, token: Option<String>) -> Self {
+        Self {
+            corpus,
+            token,
+            loaded_after: last_operation_time(),
+            counters: ServerCounters::default(),
+        }
+    }
+
+    /// Read the sessions again if a sync has run since they were read
+    fn refresh(&mut self) -> Result<()> {
+        let latest = last_operation_time();
+        if latest != self.loaded_after {
+            log::info!("Sync repo changed; reading sessions again");
+            self.corpus = Corpus::load()?;
+            self.loaded_after = latest;
+        }
+        Ok(())
+    }
+
+    /// The response to `request`
+    pub fn handle(&mut self, request: &Request) -> Response {
+        let segments: Vec<&str> = request
+            .path
+            .trim_matches('/')
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+        let get = request.method == "GET";
+        let result = match (segments.as_slice(), get) {
+            (["status"], true) => self.status(),
+            (["projects"], true) => Ok(Response::ok(self.corpus.projects())),
+            (["sessions"], true) => self.sessions(request),
+            (["sessions", id], true) => self.session(id),
+            (["sessions", id, "entries"], true) => self.entries(id, request),
+            (["search"], true) => self.search(request),
+            (["metrics"], true) => Ok(self.metrics()),
+            (["sync"], false) if request.method == "POST" => self.sync(request),
+            (["status" | "projects" | "sessions" | "search" | "metrics"], false)
+            | (["sessions", _] | ["sessions", _, "entries"], false) => {
+                Err(Response::error(405, "Only GET is allowed here"))
+            }
+            (["sync"], _) => Err(Response::error(405, "Only POST is allowed here")),
+            _ => Err(Response::error(
+                404,
+                &format!("No endpoint at {}", request.path),
+            )),
+        };
+        let response = result.unwrap_or_else(|response| response);
+        self.counters.requests += 1;
+        if response.status >= 500 {
+            self.counters.errors += 1;
+        }
+        response
+    }
+
+    fn status(&self) -> Result<Response, Response> {
+        let state = crate::sync::SyncState::load().map_err(internal)?;
+        let branch = crate::scm::open(&state.sync_repo_path)
+            .and_then(|scm| scm.current_branch())
+            .ok();
+        let history = OperationHistory::load().unwrap_or_default();
+        let last_operation = history.get_last_operation().map(|op| {
+            json!({
+                "operation": op.operation_type.as_str(),
+                "timestamp": op.timestamp,
+                "branch": op.branch,
+                "conversations": op.affected_conversations.len(),
+                "remote_unreachable": op.remote_unreachable,
+            })
+        });
+        Ok(Response::ok(json!({
+            "sync_repo": state.sync_repo_path,
+            "has_remote": state.has_remote,
+            "branch": branch,
+            "sessions": self.corpus.len(),
+            "last_operation": last_operation,
+        })))
+    }
+
+    fn metrics(&self) -> Response {
+        let history = OperationHistory::load().unwrap_or_default();
+        let snapshot = Snapshot {
+            last_sync: history.get_last_operation().map(|op| LastSync {
+                time: op.timestamp,
+                sessions: op.affected_conversations.len(),
+                remote_unreachable: op.remote_unreachable,
+                network_failures: op.network_failures.len(),
+            }),
+            sessions: self.corpus.len(),
+            conflicts_pending: crate::report::load_latest_report()
+                .map_or(0, |report| report.total_conflicts),
+            counters: self.counters,
+        };
+        Response::text(
+            crate::metrics::CONTENT_TYPE,
+            crate::metrics::render(&snapshot, Utc::now()),
+        )
+    }
+
+    fn sessions(&self, request: &Request) -> Result<Response, Response> {
+        let offset = request.number("offset", 0)?;
+        let limit = request.number("limit", DEFAULT_LIMIT)?;
+        let listed = self.corpus.sessions(request.query("project"));
+        Ok(Response::ok(Page::of(listed, offset, limit)))
+    }
+
+    fn session(&self, reference: &str) -> Result<Response, Response> {
+        let session = self.find(reference)?;
+        Ok(Response::ok(self.corpus.summary(session)))
+    }
+
+    fn entries(&self, reference: &str, request: &Request) -> Result<Response, Response> {
+        let offset = request.number("offset", 0)?;
+        let limit = request.number("limit", DEFAULT_LIMIT)?;
+        let session = self.find(reference)?;
+        Ok(Response::ok(Page::of(
+            session.entries.iter().collect(),
+            offset,
+            limit,
+        )))
+    }
+
+    fn search(&self, request: &Request) -> Result<Response, Response> {
+        let query = request
+            .query("q")
+            .ok_or_else(|| Response::error(400, "Missing query parameter 'q'"))?;
+        let limit = request.number("limit", DEFAULT_SEARCH_LIMIT)?;
+        self.corpus
+            .search(query, request.query("project"), limit)
+            .map(Response::ok)
+            .map_err(|e| Response::error(400, &format!("{e:#}")))
+    }
+
+    fn sync(&mut self, request: &Request) -> Result<Response, Response> {
+        let Some(token) = &self.token else {
+            return Err(Response::error(
+                403,
+                &format!(
+                    "No API token is set; store one with `credentials set {}` or set {}",
+                    credentials::API_TOKEN,
+                    API_TOKEN_ENV_VAR
+                ),
+            ));
+        };
+        let given = request
+            .header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if !constant_time_eq(given.trim().as_bytes(), token.as_bytes()) {
+            return Err(Response::error(401, "Missing or wrong API token"));
+        }
+        if let Some(wait) = crate::sync::automatic_sync_wait() {
+            let secs = wait.as_secs().max(1);
+            return Err(Response {
+                status: 429,
+                content_type: JSON_CONTENT_TYPE,
+                body: json!({
+                    "error": format!("The minimum sync interval has {secs}s to go"),
+                    "retry_after_secs": secs,
+                }),
+            });
+        }
+
+        log::info!("Sync requested over the API");
+        let outcome = crate::sync::sync_bidirectional(
+            None,
+            None,
+            false,
+            false,
+            &[],
+            false,
+            false,
+            false,
+            false,
+            crate::VerbosityLevel::Quiet,
+        )
+        .map_err(internal)?;
+        self.counters.syncs += 1;
+        self.refresh().map_err(internal)?;
+        Ok(Response::ok(json!({
+            "outcome": match outcome {
+                crate::sync::SyncOutcome::Complete => "complete",
+                crate::sync::SyncOutcome::LocalOnly => "local_only",
+            },
+            "sessions": self.corpus.len(),
+        })))
+    }
+
+    fn find(&self, reference: &str) -> Result<&crate::parser::ConversationSession, Response> {
+        self.corpus
+            .find_session(reference)
+            .map_err(|e| Response::error(404, &format!("{e:#}")))
+    }
+}
+
+fn internal(e: anyhow::Error) -> Response {
+    log::warn!("API request failed: {:#}", e);
+    Response::error(500, &format!("{e:#}"))
+}
+
+fn last_operation_time() -> Option<DateTime<Utc>> {
+    OperationHistory::load()
+        .ok()?
+        .get_last_operation()
+        .map(|op| op.timestamp)
+}
+
+/// The token `/sync` requires: the environment variable, else the keychain
+/// credential
+fn api_token() -> Option<String> {
+    if let Ok(token) = std::env::var(API_TOKEN_ENV_VAR) {
+        return Some(token).filter(|t| !t.trim().is_empty());
+    }
+    match credentials::get(credentials::API_TOKEN) {
+        Ok(token) => token.filter(|t| !t.trim().is_empty()),
+        Err(e) => {
+            log::warn!("Couldn't read the API token: {:#}", e);
+            None
+        }
+    }
+}
+
+/// Serve the API on `listen` until the process is stopped
+pub fn serve(listen: &str) -> Result<()> {
+    let listener =
+        TcpListener::bind(listen).with_context(|| format!("Failed to listen on {listen}"))?;
+    let address = listener.local_addr()?;
+    let token = api_token();
+    let mut server = ApiServer::new(Corpus::load()?, token);
+
+    println!("Listening on http://{address}");
+    if !address.ip().is_loopback() {
+        println!(
+            "{} {} isn't a loopback address; anyone who can reach it can read your history",
+            "!".yellow().bold(),
+            address.ip()
+        );
+    }
+    if server.corpus.is_empty() {
+        println!(
+            "{} The sync repo has no sessions yet; run a sync first",
+            glyphs::info().cyan()
+        );
+    }
+    if server.token.is_none() {
+        println!(
+            "{} No API token is set, so POST /sync is disabled (see `credentials set {}`)",
+            glyphs::info().cyan(),
+            credentials::API_TOKEN
+        );
+    }
+    std::io::stdout().flush()?;
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = answer(&mut server, stream, address) {
+                    log::debug!("API connection failed: {:#}", e);
+                }
+            }
+            Err(e) => log::warn!("Failed to accept API connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Read one request from `stream` and write its response
+fn answer(server: &mut ApiServer, mut stream: TcpStream, listen: SocketAddr) -> Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader) {
+        Ok(request)
+            if request.path != "/metrics" && !host_allowed(request.header("host"), listen) =>
+        {
+            Response::error(403, "Host not allowed")
+        }
+        Ok(request) => {
+            if let Err(e) = server.refresh() {
+                log::warn!("Failed to read sessions again: {:#}", e);
+            }
+            let response = server.handle(&request);
+            log::debug!("{} {} -> {}", request.method, request.path, response.status);
+            response
+        }
+        Err(e) => Response::error(400, &format!("{e:#}")),
+    };
+    write_response(&mut stream, &response)
+}
+
+/// Parse a request head from `reader`, discarding any body
+fn read_request(reader: &mut impl BufRead) -> Result<Request> {
+    let mut head = reader.take(MAX_HEAD_BYTES);
+    let mut line = String::new();
+    head.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        bail!("Malformed request line");
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: percent_decode(path, false),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(key, true), percent_decode(value, true))
+            })
+            .collect(),
+        headers: Vec::new(),
+    };
+
+    loop {
+        line.clear();
+        if head.read_line(&mut line)? == 0 {
+            bail!("Request headers ended early");
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let body_length: u64 = request
+        .header("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    std::io::copy(
+        &mut head.into_inner().take(body_length.min(MAX_BODY_BYTES)),
+        &mut std::io::sink(),
+    )?;
+    Ok(request)
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> Result<()> {
+    let body = match &response.body {
+        Value::String(text) if response.content_type != JSON_CONTENT_TYPE => text.clone(),
+        body => serde_json::to_string_pretty(body)? + "\n",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        429 => "Too Many Requests",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Whether a request with `Host` header `host` may be answered: clients
+/// other than browsers may leave it out, browsers must name a loopback
+/// host or the listen address
+fn host_allowed(host: Option<&str>, listen: SocketAddr) -> bool {
+    let Some(host) = host else {
+        return true;
+    };
+    // Strip the port, keeping IPv6 addresses whole
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback() || ip == listen.ip())
+}
+
+/// Decode `%XX` escapes (and `+` as a space in query strings)
+fn percent_decode(text: &str, plus_is_space: bool) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |b: u8| (b as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 3;
+                        continue;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            b'+' if plus_is_space => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Compare two secrets in time independent of where they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, target: &str, headers: &[(&str, &str)]) -> Request {
+        let mut raw = format!("{method} {target} HTTP/1.1\r\n");
+        for (name, value) in headers {
+            raw.push_str(&format!("{name}: {value}\r\n"));
+        }
+        raw.push_str("\r\n");
+        read_request(&mut raw.as_bytes()).unwrap()
+    }
+
+    fn server(token: Option<&str>) -> ApiServer {
+        ApiServer::new(crate::corpus::tests::corpus(), token.map(str::to_string))
+    }
+
+    #[test]
+    fn test_read_request() {
+        let parsed = request(
+            "GET",
+            "/search?q=login+test%21&project=web&empty",
+            &[("Host", "localhost:7878")],
+        );
+        assert_eq!(parsed.path, "/search");
+        assert_eq!(parsed.query("q"), Some("login test!"));
+        assert_eq!(parsed.query("project"), Some("web"));
+        assert_eq!(parsed.query("empty"), Some(""));
+        assert_eq!(parsed.header("host"), Some("localhost:7878"));
+        assert!(read_request(&mut "GET /\r\n\r\n".as_bytes()).is_err());
+        assert_eq!(percent_decode("100%", true), "100%");
+        assert_eq!(percent_decode("a%2Fb+c", false), "a/b+c");
+    }
+
+    #[test]
+    fn test_routes() {
+        let mut server = server(None);
+        let response = server.handle(&request("GET", "/sessions?project=api", &[]));
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["total"], 1);
+        assert_eq!(response.body["items"][0]["session_id"], "aaaa-1");
+
+        let response = server.handle(&request("GET", "/sessions/aaaa/entries?offset=1", &[]));
+        assert_eq!(response.body["total"], 2);
+        assert_eq!(response.body["items"][0]["uuid"], "b");
+
+        let response = server.handle(&request("GET", "/search?q=login&limit=1", &[]));
+        assert_eq!(response.body["total_matches"], 3);
+        assert_eq!(response.body["matches"].as_array().unwrap().len(), 1);
+
+        let response = server.handle(&request("GET", "/metrics", &[]));
+        assert_eq!(response.content_type, crate::metrics::CONTENT_TYPE);
+        assert!(response
+            .body
+            .as_str()
+            .unwrap()
+            .contains("claude_code_sync_sessions 2\n"));
+        assert!(response
+            .body
+            .as_str()
+            .unwrap()
+            .contains("claude_code_sync_api_requests_total 3\n"));
+
+        for (method, target, status) in [
+            ("GET", "/sessions/zzzz", 404),
+            ("GET", "/sessions?limit=many", 400),
+            ("GET", "/search", 400),
+            ("DELETE", "/sessions", 405),
+            ("GET", "/sync", 405),
+            ("GET", "/nothing", 404),
+        ] {
+            let response = server.handle(&request(method, target, &[]));
+            assert_eq!(response.status, status, "{method} {target}");
+            assert!(response.body["error"].is_string());
+        }
+    }
+
+    #[test]
+    fn test_sync_requires_token() {
+        let response = server(None).handle(&request("POST", "/sync", &[]));
+        assert_eq!(response.status, 403);
+
+        let mut server = server(Some("s3cret"));
+        for authorization in ["", "Bearer wrong", "s3cret"] {
+            let response = server.handle(&request(
+                "POST",
+                "/sync",
+                &[("Authorization", authorization)],
+            ));
+            assert_eq!(response.status, 401, "{authorization}");
+        }
+    }
+
+    #[test]
+    fn test_host_allowed() {
+        let local: SocketAddr = "127.0.0.1:7878".parse().unwrap();
+        let lan: SocketAddr = "192.168.1.5:7878".parse().unwrap();
+        assert!(host_allowed(None, local));
+        assert!(host_allowed(Some("localhost:7878"), local));
+        assert!(host_allowed(Some("127.0.0.1"), local));
+        assert!(host_allowed(Some("[::1]:7878"), local));
+        assert!(host_allowed(Some("192.168.1.5:7878"), lan));
+        assert!(!host_allowed(Some("192.168.1.5:7878"), local));
+        assert!(!host_allowed(Some("evil.example:7878"), local));
+    }
+}
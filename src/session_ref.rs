@@ -0,0 +1,410 @@
+//! Resolving abbreviated or fuzzy session references to full session IDs
+//!
+//! Commands that take a `<session-id>` accept, in order of preference:
+//! the full ID (or `<id>.jsonl`), a unique prefix of one (like a git short
+//! hash), or a fuzzy pattern matched against session IDs, project names,
+//! and the prompts recorded in `history.jsonl`. When several sessions match
+//! on a terminal, the user picks one; otherwise the candidates are listed in
+//! the error. Destructive commands (`delete`, `purge`) use
+//! [`resolve_session_id_strict`], which skips the fuzzy step and the picker,
+//! so a typo can never select a session the user didn't name.
+
+use anyhow::{bail, Context, Result};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use crate::filter::FilterConfig;
+use crate::paths::project_dir_name;
+use crate::sync::SyncState;
+use crate::text::{truncate_to_width, Keep};
+
+/// Length of a full (UUID) session ID; accepted even if no such session is known
+const FULL_SESSION_ID_LEN: usize = 36;
+
+/// Maximum number of candidates listed when a reference is ambiguous
+const MAX_LISTED_CANDIDATES: usize = 10;
+
+/// A known session and a human-readable description of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionCandidate {
+    pub id: String,
+    /// Project directory name, if known
+    pub project: Option<String>,
+    /// Latest prompt recorded for the session in `history.jsonl`, if any
+    pub display: Option<String>,
+}
+
+impl SessionCandidate {
+    fn label(&self) -> String {
+        let mut label = self.id.clone();
+        if let Some(project) = &self.project {
+            label.push_str(&format!("  {project}"));
+        }
+        if let Some(display) = &self.display {
+            let display = truncate_to_width(display, 60, Keep::Start);
+            label.push_str(&format!("  \"{display}\""));
+        }
+        label
+    }
+}
+
+/// Strip whitespace and a trailing `.jsonl` from a session reference
+pub fn normalize_session_ref(input: &str) -> Result<String> {
+    let trimmed = input.trim();
+    let id = trimmed.strip_suffix(".jsonl").unwrap_or(trimmed);
+    if id.is_empty() {
+        bail!("Session ID cannot be empty");
+    }
+    Ok(id.to_string())
+}
+
+/// Candidates matching `reference`, best first
+///
+/// An exact ID match wins outright, then prefix matches on the ID; only if
+/// there are none is the reference fuzzy-matched.
+pub fn match_candidates<'a>(
+    reference: &str,
+    candidates: &'a [SessionCandidate],
+) -> Vec<&'a SessionCandidate> {
+    let by_id = match_id_prefix(reference, candidates);
+    if !by_id.is_empty() {
+        return by_id;
+    }
+
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<_> = candidates
+        .iter()
+        .filter_map(|c| {
+            let haystack = format!(
+                "{} {} {}",
+                c.id,
+                c.project.as_deref().unwrap_or(""),
+                c.display.as_deref().unwrap_or("")
+            );
+            matcher
+                .fuzzy_match(&haystack, reference)
+                .map(|score| (score, c))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.id.cmp(&b.1.id)));
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+/// Candidates whose ID is `reference`, or else starts with it
+fn match_id_prefix<'a>(
+    reference: &str,
+    candidates: &'a [SessionCandidate],
+) -> Vec<&'a SessionCandidate> {
+    if let Some(exact) = candidates.iter().find(|c| c.id == reference) {
+        return vec![exact];
+    }
+    let lower = reference.to_lowercase();
+    candidates
+        .iter()
+        .filter(|c| c.id.to_lowercase().starts_with(&lower))
+        .collect()
+}
+
+/// Resolve a session reference to a full session ID, by the full ID or a
+/// unique prefix of one only
+///
+/// For commands that destroy data: no fuzzy matching, and no picker when
+/// the prefix is ambiguous.
+pub fn resolve_session_id_strict(input: &str) -> Result<String> {
+    let reference = normalize_session_ref(input)?;
+    let candidates = known_sessions()?;
+    match match_id_prefix(&reference, &candidates).as_slice() {
+        [] if reference.len() == FULL_SESSION_ID_LEN => Ok(reference),
+        [] => bail!(
+            "No session ID starts with '{}'. Give the session ID or a unique prefix of it.",
+            reference
+        ),
+        [only] => Ok(only.id.clone()),
+        several => bail!(
+            "'{}' is a prefix of {} session IDs; give more of the ID:\n{}",
+            reference,
+            several.len(),
+            listed(several)
+        ),
+    }
+}
+
+/// Resolve a session reference to a full session ID
+pub fn resolve_session_id(input: &str) -> Result<String> {
+    let reference = normalize_session_ref(input)?;
+    let candidates = known_sessions()?;
+    let matches = match_candidates(&reference, &candidates);
+
+    match matches.as_slice() {
+        [] if reference.len() == FULL_SESSION_ID_LEN => Ok(reference),
+        [] => bail!("No session matches '{}'", reference),
+        [only] => Ok(only.id.clone()),
+        several if crate::interactive_conflict::is_interactive() => {
+            let labels: Vec<String> = several.iter().map(|c| c.label()).collect();
+            let choice = inquire::Select::new(
+                &format!("Several sessions match '{reference}':"),
+                labels.clone(),
+            )
+            .prompt()
+            .context("Session selection cancelled")?;
+            let index = labels.iter().position(|l| *l == choice).unwrap_or(0);
+            Ok(several[index].id.clone())
+        }
+        several => bail!(
+            "'{}' matches {} sessions:\n{}",
+            reference,
+            several.len(),
+            listed(several)
+        ),
+    }
+}
+
+/// The first candidates, one per line, for an error message
+fn listed(candidates: &[&SessionCandidate]) -> String {
+    candidates
+        .iter()
+        .take(MAX_LISTED_CANDIDATES)
+        .map(|c| format!("  {}", c.label()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Sessions found locally and in the sync repo, described from `history.jsonl`
+pub fn known_sessions() -> Result<Vec<SessionCandidate>> {
+    let mut sessions: BTreeMap<String, SessionCandidate> = BTreeMap::new();
+
+    let local_projects = crate::sync::claude_projects_dir()?;
+    let mut project_dirs = vec![local_projects.clone()];
+    if let (Ok(state), Ok(filter)) = (SyncState::load(), FilterConfig::load()) {
+        project_dirs.push(state.sync_repo_path.join(filter.projects_subdir()));
+    }
+    for dir in &project_dirs {
+        collect_session_files(dir, &mut sessions);
+    }
+
+    if let Some(claude_dir) = local_projects.parent() {
+        apply_history_index(&claude_dir.join("history.jsonl"), &mut sessions);
+    }
+
+    Ok(sessions.into_values().collect())
+}
+
+fn collect_session_files(projects_dir: &Path, sessions: &mut BTreeMap<String, SessionCandidate>) {
+    for entry in WalkDir::new(projects_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+        .filter(|e| !crate::parts::is_part_file(e.path()))
+    {
+        let Some(id) = entry
+            .path()
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        let project = entry
+            .path()
+            .strip_prefix(projects_dir)
+            .ok()
+            .and_then(project_dir_name);
+        sessions.entry(id.clone()).or_insert(SessionCandidate {
+            id,
+            project,
+            display: None,
+        });
+    }
+}
+
+/// One prompt recorded in `history.jsonl`
+struct HistoryIndexEntry {
+    session_id: String,
+    /// Milliseconds since the epoch
+    timestamp: i64,
+    display: String,
+    /// Working directory the prompt was entered in
+    project: Option<String>,
+}
+
+fn read_history_index(history_path: &Path) -> Vec<HistoryIndexEntry> {
+    let Ok(content) = fs::read_to_string(history_path) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let value: serde_json::Value = serde_json::from_str(line).ok()?;
+            Some(HistoryIndexEntry {
+                session_id: value.get("sessionId")?.as_str()?.to_string(),
+                timestamp: value.get("timestamp").and_then(|v| v.as_i64()).unwrap_or(0),
+                display: value
+                    .get("display")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .trim()
+                    .to_string(),
+                project: value
+                    .get("project")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Attach the latest prompt per session from `history.jsonl`
+fn apply_history_index(history_path: &Path, sessions: &mut BTreeMap<String, SessionCandidate>) {
+    for entry in read_history_index(history_path) {
+        if let Some(session) = sessions.get_mut(&entry.session_id) {
+            session.display = Some(entry.display);
+        }
+    }
+}
+
+/// Sessions from `history.jsonl`, most recently used first
+///
+/// Each session is described by its latest prompt and the directory it was
+/// entered in.
+fn recent_sessions(history_path: &Path) -> Vec<SessionCandidate> {
+    let mut latest: BTreeMap<String, HistoryIndexEntry> = BTreeMap::new();
+    for entry in read_history_index(history_path) {
+        match latest.get(&entry.session_id) {
+            Some(existing) if existing.timestamp > entry.timestamp => {}
+            _ => {
+                latest.insert(entry.session_id.clone(), entry);
+            }
+        }
+    }
+
+    let mut entries: Vec<_> = latest.into_values().collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+    entries
+        .into_iter()
+        .map(|e| SessionCandidate {
+            id: e.session_id,
+            project: e.project,
+            display: Some(e.display).filter(|d| !d.is_empty()),
+        })
+        .collect()
+}
+
+/// Let the user pick one of their recent sessions
+///
+/// The list comes from `history.jsonl`, newest first, and can be filtered by
+/// typing.
+pub fn pick_recent_session() -> Result<String> {
+    let history_path = crate::sync::claude_projects_dir()?
+        .parent()
+        .map(|claude_dir| claude_dir.join("history.jsonl"))
+        .context("Failed to locate history.jsonl")?;
+    let sessions = recent_sessions(&history_path);
+    if sessions.is_empty() {
+        bail!("No recent sessions found in {}", history_path.display());
+    }
+
+    let labels: Vec<String> = sessions.iter().map(|c| c.label()).collect();
+    let choice = inquire::Select::new("Select a session:", labels.clone())
+        .with_page_size(15)
+        .prompt()
+        .context("Session selection cancelled")?;
+    let index = labels.iter().position(|l| *l == choice).unwrap_or(0);
+    Ok(sessions[index].id.clone())
+}
+
+/// Resolve an optional session argument, offering a picker when it is missing
+///
+/// Without a terminal to prompt on, a missing argument is an error.
+pub fn session_id_or_pick(input: Option<&str>) -> Result<String> {
+    match input {
+        Some(input) => resolve_session_id(input),
+        None if crate::interactive_conflict::is_interactive() => pick_recent_session(),
+        None => bail!("A session ID is required when not running interactively"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: &str, project: &str, display: &str) -> SessionCandidate {
+        SessionCandidate {
+            id: id.to_string(),
+            project: Some(project.to_string()),
+            display: Some(display.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_match_candidates() {
+        let candidates = vec![
+            candidate("3f2a9c1e-0000", "-home-me-api", "fix the login bug"),
+            candidate("3f2b0000-1111", "-home-me-web", "add dark mode"),
+            candidate("9d1e2222-2222", "-home-me-web", "refactor router"),
+        ];
+        let ids = |input| {
+            match_candidates(input, &candidates)
+                .iter()
+                .map(|c| c.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids("3f2a"), ["3f2a9c1e-0000"]);
+        assert_eq!(ids("3F2").len(), 2);
+        assert_eq!(ids("9d1e2222-2222"), ["9d1e2222-2222"]);
+        assert_eq!(ids("darkmode"), ["3f2b0000-1111"]);
+        assert!(ids("zzzz").is_empty());
+
+        // Destructive commands never fall back to fuzzy matches
+        let strict = |input| match_id_prefix(input, &candidates).len();
+        assert_eq!(strict("3f2a"), 1);
+        assert_eq!(strict("3f2"), 2);
+        assert_eq!(strict("darkmode"), 0);
+    }
+
+    #[test]
+    fn test_normalize_session_ref() {
+        assert_eq!(normalize_session_ref(" abc.jsonl ").unwrap(), "abc");
+        assert!(normalize_session_ref("  ").is_err());
+    }
+
+    #[test]
+    fn test_history_index_labels() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let projects = temp.path().join("projects");
+        fs::create_dir_all(projects.join("-proj")).unwrap();
+        fs::write(projects.join("-proj/s1.jsonl"), "{}\n").unwrap();
+        fs::write(
+            temp.path().join("history.jsonl"),
+            "{\"sessionId\":\"s1\",\"timestamp\":1,\"display\":\"first\"}\n\
+             {\"sessionId\":\"s1\",\"timestamp\":2,\"display\":\"latest\"}\n",
+        )
+        .unwrap();
+
+        let mut sessions = BTreeMap::new();
+        collect_session_files(&projects, &mut sessions);
+        apply_history_index(&temp.path().join("history.jsonl"), &mut sessions);
+        assert_eq!(sessions["s1"], candidate("s1", "-proj", "latest"));
+    }
+
+    #[test]
+    fn test_recent_sessions_newest_first() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let history = temp.path().join("history.jsonl");
+        fs::write(
+            &history,
+            "{\"sessionId\":\"old\",\"timestamp\":1,\"display\":\"a\",\"project\":\"/p\"}\n\
+             {\"sessionId\":\"new\",\"timestamp\":3,\"display\":\"b\",\"project\":\"/p\"}\n\
+             {\"sessionId\":\"old\",\"timestamp\":2,\"display\":\"c\",\"project\":\"/p\"}\n",
+        )
+        .unwrap();
+
+        let recent = recent_sessions(&history);
+        let ids: Vec<_> = recent.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, ["new", "old"]);
+        assert_eq!(recent[1].display.as_deref(), Some("c"));
+    }
+}
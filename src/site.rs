@@ -0,0 +1,444 @@
+//! Static HTML browser over the synced sessions
+//!
+//! `export --site <dir>` writes a self-contained site: an index of every
+//! session grouped by project, newest first, a page per session with its
+//! messages, and a search box that filters the index in the browser. Search
+//! runs over a data file next to the pages (`search-index.js`, loaded as a
+//! script so the site works straight from disk, where browsers block
+//! `fetch`), so no server is needed to browse it.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::parser::{ConversationEntry, ConversationSession};
+use crate::paths::{decode_project_dir, project_dir_name};
+use crate::text::{short_id, truncate_to_width, Keep};
+
+/// Marks an index page as written by this tool, so a later export may
+/// replace the site
+const GENERATOR_META: &str = r#"<meta name="generator" content="claude-code-sync">"#;
+
+/// Directory of the per-session pages within the site
+const SESSIONS_DIR: &str = "sessions";
+
+/// Width of session titles in the index
+const TITLE_WIDTH: usize = 120;
+
+/// Characters of each session's text kept for search
+const SEARCH_TEXT_LIMIT: usize = 20_000;
+
+const STYLE: &str = r#"body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; color: #1f2328; }
+a { color: #0969da; text-decoration: none; }
+a:hover { text-decoration: underline; }
+h2 { margin-top: 2rem; border-bottom: 1px solid #d0d7de; padding-bottom: .3rem; }
+ul.sessions { list-style: none; padding: 0; }
+ul.sessions li { padding: .3rem 0; }
+.meta { color: #656d76; font-size: .9em; }
+#search { width: 100%; padding: .5rem; font-size: 1rem; box-sizing: border-box; }
+.message { margin: 1rem 0; padding: .75rem 1rem; border-radius: 6px; }
+.user { background: #ddf4ff; }
+.assistant { background: #f6f8fa; }
+.text { white-space: pre-wrap; overflow-wrap: anywhere; }
+pre { white-space: pre-wrap; overflow-wrap: anywhere; background: #fff; padding: .5rem; border: 1px solid #d0d7de; border-radius: 4px; }
+"#;
+
+/// Filters the index by the words typed into the search box: a session is
+/// shown when its title or text contains all of them
+const SEARCH_SCRIPT: &str = r#"const search = document.getElementById("search");
+search.addEventListener("input", () => {
+  const words = search.value.toLowerCase().split(/\s+/).filter(Boolean);
+  const shown = new Set(SEARCH_INDEX
+    .filter(s => words.every(w => s.text.includes(w)))
+    .map(s => s.id));
+  for (const item of document.querySelectorAll("li[data-id]")) {
+    item.hidden = !shown.has(item.dataset.id);
+  }
+  for (const section of document.querySelectorAll("section")) {
+    section.hidden = !section.querySelector("li[data-id]:not([hidden])");
+  }
+});
+"#;
+
+/// What an export wrote
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SiteStats {
+    pub sessions: usize,
+    pub projects: usize,
+}
+
+/// A session as listed in the index
+struct SiteSession<'a> {
+    session: &'a ConversationSession,
+    page: String,
+    started: Option<DateTime<Utc>>,
+    title: String,
+}
+
+/// A session's entry in `search-index.js`
+#[derive(Serialize)]
+struct SearchEntry<'a> {
+    id: &'a str,
+    /// Lowercased title and message text
+    text: String,
+}
+
+/// Write the site for `sessions` (found under `projects_dir`) to `out_dir`
+///
+/// `out_dir` must be empty, missing, or a site an earlier export wrote;
+/// pages of sessions no longer present are removed. Subagent transcripts
+/// are left out.
+pub fn write_site(
+    sessions: &[ConversationSession],
+    projects_dir: &Path,
+    out_dir: &Path,
+) -> Result<SiteStats> {
+    prepare_out_dir(out_dir)?;
+
+    let mut projects: BTreeMap<String, Vec<SiteSession>> = BTreeMap::new();
+    for session in sessions.iter().filter(|s| !s.is_agent_file()) {
+        let project = Path::new(&session.file_path)
+            .strip_prefix(projects_dir)
+            .ok()
+            .and_then(project_dir_name)
+            .unwrap_or_else(|| "unknown".to_string());
+        projects.entry(project).or_default().push(SiteSession {
+            session,
+            page: format!("{}/{}.html", SESSIONS_DIR, page_name(&session.session_id)),
+            started: started_at(session),
+            title: session_title(session),
+        });
+    }
+
+    let sessions_dir = out_dir.join(SESSIONS_DIR);
+    fs::create_dir_all(&sessions_dir)
+        .with_context(|| format!("Failed to create {}", sessions_dir.display()))?;
+    let mut pages = HashSet::new();
+    let mut search = Vec::new();
+    for (project, listed) in &mut projects {
+        listed.sort_by_key(|s| std::cmp::Reverse(s.started));
+        for site_session in listed.iter() {
+            let path = out_dir.join(&site_session.page);
+            fs::write(&path, session_page(site_session, project))
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            pages.insert(path);
+            search.push(SearchEntry {
+                id: &site_session.session.session_id,
+                text: search_text(site_session),
+            });
+        }
+    }
+
+    // Pages of sessions an earlier export listed that are gone now
+    for entry in fs::read_dir(&sessions_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("html") && !pages.contains(&path) {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+    }
+
+    let search_index = format!(
+        "const SEARCH_INDEX = {};\n",
+        serde_json::to_string(&search)?
+    );
+    for (name, content) in [
+        ("index.html", index_page(&projects)),
+        ("search-index.js", search_index),
+        ("style.css", STYLE.to_string()),
+    ] {
+        let path = out_dir.join(name);
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    Ok(SiteStats {
+        sessions: pages.len(),
+        projects: projects.len(),
+    })
+}
+
+/// Create `out_dir`, refusing to write into a directory holding anything
+/// but an earlier export
+fn prepare_out_dir(out_dir: &Path) -> Result<()> {
+    if out_dir.exists() {
+        let is_empty = fs::read_dir(out_dir)
+            .with_context(|| format!("Failed to read {}", out_dir.display()))?
+            .next()
+            .is_none();
+        let is_site = fs::read_to_string(out_dir.join("index.html"))
+            .is_ok_and(|index| index.contains(GENERATOR_META));
+        if !is_empty && !is_site {
+            bail!(
+                "{} is not empty and wasn't written by export --site; choose an empty or new directory",
+                out_dir.display()
+            );
+        }
+    }
+    fs::create_dir_all(out_dir).with_context(|| format!("Failed to create {}", out_dir.display()))
+}
+
+/// `session_id` with anything but letters, digits, `-`, and `_` replaced,
+/// for use as a file name
+fn page_name(session_id: &str) -> String {
+    session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn started_at(session: &ConversationSession) -> Option<DateTime<Utc>> {
+    session
+        .entries
+        .iter()
+        .filter_map(|e| e.timestamp.as_deref())
+        .find_map(parse_timestamp)
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn format_time(time: Option<DateTime<Utc>>) -> String {
+    time.map_or_else(
+        || "unknown date".to_string(),
+        |t| t.format("%Y-%m-%d %H:%M UTC").to_string(),
+    )
+}
+
+/// Messages of the main conversation, leaving out subagent ones
+fn messages(session: &ConversationSession) -> impl Iterator<Item = &ConversationEntry> {
+    session
+        .entries
+        .iter()
+        .filter(|e| matches!(e.entry_type.as_str(), "user" | "assistant") && !e.is_sidechain())
+}
+
+/// The first user message with text, shortened, or the session ID
+fn session_title(session: &ConversationSession) -> String {
+    messages(session)
+        .filter(|e| e.entry_type == "user")
+        .find_map(crate::timeline::message_preview)
+        .map(|text| truncate_to_width(&text, TITLE_WIDTH, Keep::Start))
+        .unwrap_or_else(|| format!("Session {}", short_id(&session.session_id, 8)))
+}
+
+fn search_text(site_session: &SiteSession) -> String {
+    let mut text = site_session.title.clone();
+    for preview in messages(site_session.session).filter_map(crate::timeline::message_preview) {
+        text.push(' ');
+        text.push_str(&preview);
+    }
+    text.chars()
+        .take(SEARCH_TEXT_LIMIT)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn index_page(projects: &BTreeMap<String, Vec<SiteSession>>) -> String {
+    let mut body = String::from(
+        "<h1>Claude Code sessions</h1>\n<input id=\"search\" type=\"search\" placeholder=\"Search sessions\" autofocus>\n",
+    );
+    for (project, listed) in projects {
+        body.push_str(&format!(
+            "<section>\n<h2>{}</h2>\n<ul class=\"sessions\">\n",
+            escape_html(&decode_project_dir(project).display().to_string())
+        ));
+        for site_session in listed {
+            body.push_str(&format!(
+                "<li data-id=\"{}\"><a href=\"{}\">{}</a> <span class=\"meta\">{} · {} messages</span></li>\n",
+                escape_html(&site_session.session.session_id),
+                escape_html(&site_session.page),
+                escape_html(&site_session.title),
+                format_time(site_session.started),
+                site_session.session.message_count()
+            ));
+        }
+        body.push_str("</ul>\n</section>\n");
+    }
+    body.push_str("<script src=\"search-index.js\"></script>\n");
+    body.push_str(&format!("<script>\n{SEARCH_SCRIPT}</script>\n"));
+    page("Claude Code sessions", "style.css", &body)
+}
+
+fn session_page(site_session: &SiteSession, project: &str) -> String {
+    let session = site_session.session;
+    let mut body = format!(
+        "<p><a href=\"../index.html\">← All sessions</a></p>\n<h1>{}</h1>\n<p class=\"meta\">{} · {} · <code>{}</code></p>\n",
+        escape_html(&site_session.title),
+        escape_html(&decode_project_dir(project).display().to_string()),
+        format_time(site_session.started),
+        escape_html(&session.session_id)
+    );
+    for entry in messages(session) {
+        let content = message_html(entry);
+        if content.is_empty() {
+            continue;
+        }
+        body.push_str(&format!(
+            "<div class=\"message {}\">\n<p class=\"meta\">{} · {}</p>\n{}</div>\n",
+            entry.entry_type,
+            entry.entry_type,
+            format_time(entry.timestamp.as_deref().and_then(parse_timestamp)),
+            content
+        ));
+    }
+    page(&site_session.title, "../style.css", &body)
+}
+
+/// A message's content blocks: text as is, tool calls and results folded
+/// away, thinking left out
+fn message_html(entry: &ConversationEntry) -> String {
+    let Some(content) = entry.message.as_ref().and_then(|m| m.get("content")) else {
+        return String::new();
+    };
+    let blocks = match content {
+        Value::Array(blocks) => blocks.iter().collect(),
+        other => vec![other],
+    };
+    let mut html = String::new();
+    for block in blocks {
+        match block.get("type").and_then(Value::as_str) {
+            _ if block.is_string() => {
+                push_text(&mut html, block.as_str().unwrap_or_default());
+            }
+            Some("text") => {
+                push_text(
+                    &mut html,
+                    block
+                        .get("text")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default(),
+                );
+            }
+            Some("tool_use") => {
+                let name = block.get("name").and_then(Value::as_str).unwrap_or("tool");
+                let input = block
+                    .get("input")
+                    .map(|input| serde_json::to_string_pretty(input).unwrap_or_default())
+                    .unwrap_or_default();
+                push_details(&mut html, &format!("Tool call: {name}"), &input);
+            }
+            Some("tool_result") => {
+                push_details(&mut html, "Tool result", &tool_result_text(block));
+            }
+            _ => {}
+        }
+    }
+    html
+}
+
+fn push_text(html: &mut String, text: &str) {
+    if !text.trim().is_empty() {
+        html.push_str(&format!(
+            "<div class=\"text\">{}</div>\n",
+            escape_html(text)
+        ));
+    }
+}
+
+fn push_details(html: &mut String, summary: &str, content: &str) {
+    html.push_str(&format!(
+        "<details><summary>{}</summary><pre>{}</pre></details>\n",
+        escape_html(summary),
+        escape_html(content)
+    ));
+}
+
+/// Text of a tool result, whose content is a string or text blocks
+fn tool_result_text(block: &Value) -> String {
+    match block.get("content") {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn page(title: &str, stylesheet: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n{}\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"{}\">\n</head>\n<body>\n{}</body>\n</html>\n",
+        GENERATOR_META,
+        escape_html(title),
+        stylesheet,
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(json: Value) -> ConversationEntry {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_message_html_escapes_and_folds_tools() {
+        let html = message_html(&entry(serde_json::json!({
+            "type": "assistant",
+            "message": {"content": [
+                {"type": "thinking", "thinking": "hmm"},
+                {"type": "text", "text": "Use <b> & \"quotes\""},
+                {"type": "tool_use", "name": "Bash", "input": {"command": "ls"}},
+            ]},
+        })));
+        assert!(
+            html.contains("<div class=\"text\">Use &lt;b&gt; &amp; &quot;quotes&quot;</div>"),
+            "{html}"
+        );
+        assert!(
+            html.contains("<summary>Tool call: Bash</summary>"),
+            "{html}"
+        );
+        assert!(!html.contains("hmm"), "{html}");
+
+        let html = message_html(&entry(serde_json::json!({
+            "type": "user",
+            "message": {"content": [{"type": "tool_result", "content": [{"type": "text", "text": "a.txt"}]}]},
+        })));
+        assert_eq!(
+            html,
+            "<details><summary>Tool result</summary><pre>a.txt</pre></details>\n"
+        );
+    }
+
+    #[test]
+    fn test_prepare_out_dir_refuses_foreign_directories() {
+        let temp = tempfile::tempdir().unwrap();
+        let out = temp.path().join("site");
+        prepare_out_dir(&out).unwrap();
+        fs::write(out.join("notes.txt"), "mine").unwrap();
+        assert!(prepare_out_dir(&out).is_err());
+
+        fs::write(out.join("index.html"), page("t", "style.css", "")).unwrap();
+        prepare_out_dir(&out).unwrap();
+    }
+
+    #[test]
+    fn test_page_name() {
+        assert_eq!(page_name("abc-123_x"), "abc-123_x");
+        assert_eq!(page_name("../etc/passwd"), "___etc_passwd");
+    }
+}
@@ -0,0 +1,148 @@
+//! Crash-safe reading and writing of small state files
+//!
+//! `state.json` and `config.toml` are rewritten by many commands. A crash or
+//! full disk in the middle of a write used to leave a truncated file behind,
+//! and every later command then failed with a bare parse error. Writes now go
+//! to a temporary file in the same directory that is renamed over the
+//! original, so readers see either the old or the new version. Before each
+//! write the current version, if it parses, is kept as `<name>.bak`; loading
+//! falls back to that backup when the file itself doesn't parse.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Path of the last-good backup kept next to `path`
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, "bak")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Replace `path` with `contents` via a temporary file and a rename
+///
+/// The current file is copied to [`backup_path`] first when `is_valid`
+/// accepts it, so a corrupt file never overwrites a good backup.
+pub fn write_atomic(path: &Path, contents: &str, is_valid: impl Fn(&str) -> bool) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    if let Ok(current) = fs::read_to_string(path) {
+        if is_valid(&current) {
+            let backup = backup_path(path);
+            fs::write(&backup, &current)
+                .with_context(|| format!("Failed to write backup: {}", backup.display()))?;
+        }
+    }
+
+    let temp = with_suffix(path, "tmp");
+    fs::write(&temp, contents)
+        .with_context(|| format!("Failed to write temporary file: {}", temp.display()))?;
+    fs::rename(&temp, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+/// Read and parse `path`, restoring it from [`backup_path`] if it is corrupt
+///
+/// A corrupt file is kept as `<name>.corrupt` for inspection and the backup
+/// is copied into its place. `what` names the file in messages.
+pub fn read_with_recovery<T>(
+    path: &Path,
+    what: &str,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<T> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}: {}", what, path.display()))?;
+    let error = match parse(&content) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+
+    let backup = backup_path(path);
+    let restored = fs::read_to_string(&backup)
+        .ok()
+        .and_then(|saved| parse(&saved).ok().map(|value| (saved, value)));
+    let Some((saved, value)) = restored else {
+        return Err(error.context(format!(
+            "{} at {} is corrupt and there is no usable backup at {}",
+            what,
+            path.display(),
+            backup.display()
+        )));
+    };
+
+    let corrupt = with_suffix(path, "corrupt");
+    fs::rename(path, &corrupt)
+        .with_context(|| format!("Failed to move aside corrupt {}", path.display()))?;
+    write_atomic(path, &saved, |_| false)?;
+    log::warn!(
+        "{} at {} was corrupt ({:#}); restored the last good version (corrupt copy kept at {})",
+        what,
+        path.display(),
+        error,
+        corrupt.display()
+    );
+    eprintln!(
+        "Warning: {} was corrupt and has been restored from its backup (corrupt copy: {})",
+        path.display(),
+        corrupt.display()
+    );
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn parse(content: &str) -> Result<serde_json::Value> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    fn is_valid(content: &str) -> bool {
+        parse(content).is_ok()
+    }
+
+    #[test]
+    fn test_write_keeps_last_good_backup() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("state.json");
+
+        write_atomic(&path, r#"{"v":1}"#, is_valid).unwrap();
+        assert!(!backup_path(&path).exists());
+        write_atomic(&path, r#"{"v":2}"#, is_valid).unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), r#"{"v":1}"#);
+        assert!(!with_suffix(&path, "tmp").exists());
+
+        // A corrupt current file doesn't replace the good backup
+        fs::write(&path, r#"{"v":"#).unwrap();
+        write_atomic(&path, r#"{"v":3}"#, is_valid).unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), r#"{"v":1}"#);
+    }
+
+    #[test]
+    fn test_read_restores_from_backup() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("state.json");
+        write_atomic(&path, r#"{"v":1}"#, is_valid).unwrap();
+        write_atomic(&path, r#"{"v":2}"#, is_valid).unwrap();
+
+        fs::write(&path, r#"{"v":2"#).unwrap();
+        let value = read_with_recovery(&path, "sync state", parse).unwrap();
+        assert_eq!(value["v"], 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"v":1}"#);
+        assert_eq!(fs::read_to_string(with_suffix(&path, "corrupt")).unwrap(), r#"{"v":2"#);
+
+        // Without a usable backup the parse error is reported
+        fs::write(&path, "").unwrap();
+        fs::write(backup_path(&path), "").unwrap();
+        let err = read_with_recovery(&path, "sync state", parse).unwrap_err();
+        assert!(format!("{err:#}").contains("no usable backup"));
+    }
+}
@@ -0,0 +1,168 @@
+//! Read-only subscriptions to other sync repositories
+//!
+//! A subscription registers someone else's sync repo (a mentor's or a team's
+//! conversation library). It is cloned into the config directory and its
+//! sessions are mirrored into `~/.claude/projects-shared/<name>/`, a local
+//! area separate from `~/.claude/projects`. Nothing is ever pushed back.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ConfigManager;
+use crate::scm;
+
+/// Local directory (relative to `~/.claude`) that subscribed sessions are mirrored into
+pub const SHARED_PROJECTS_DIR: &str = "projects-shared";
+
+/// A subscribed repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subscription {
+    /// Short name, used as the local directory name
+    pub name: String,
+    /// Remote URL of the subscribed repo
+    pub url: String,
+    /// Subdirectory within the subscribed repo holding projects (default: "projects")
+    #[serde(default = "default_subdirectory")]
+    pub subdirectory: String,
+}
+
+fn default_subdirectory() -> String {
+    "projects".to_string()
+}
+
+/// All registered subscriptions
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Subscriptions {
+    #[serde(default, rename = "subscription")]
+    pub subscriptions: Vec<Subscription>,
+}
+
+impl Subscriptions {
+    /// Load subscriptions from the config directory (empty if none are registered)
+    pub fn load() -> Result<Self> {
+        let path = ConfigManager::subscriptions_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read subscriptions: {}", path.display()))?;
+        toml::from_str(&content).context("Failed to parse subscriptions")
+    }
+
+    /// Save subscriptions to the config directory
+    pub fn save(&self) -> Result<()> {
+        ConfigManager::ensure_config_dir()?;
+        let path = ConfigManager::subscriptions_path()?;
+        let content = toml::to_string_pretty(self).context("Failed to serialize subscriptions")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write subscriptions: {}", path.display()))
+    }
+
+    /// Look up a subscription by name
+    pub fn get(&self, name: &str) -> Option<&Subscription> {
+        self.subscriptions.iter().find(|s| s.name == name)
+    }
+
+    /// Register a subscription, failing if the name is taken
+    pub fn add(&mut self, subscription: Subscription) -> Result<()> {
+        validate_name(&subscription.name)?;
+        if self.get(&subscription.name).is_some() {
+            bail!("Subscription '{}' already exists", subscription.name);
+        }
+        self.subscriptions.push(subscription);
+        Ok(())
+    }
+
+    /// Remove a subscription, returning it if it existed
+    pub fn remove(&mut self, name: &str) -> Option<Subscription> {
+        let index = self.subscriptions.iter().position(|s| s.name == name)?;
+        Some(self.subscriptions.remove(index))
+    }
+}
+
+/// Subscription names become directory names, so keep them to one path component
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        bail!("Invalid subscription name: '{}'", name);
+    }
+    Ok(())
+}
+
+impl Subscription {
+    /// Where the subscribed repo is cloned
+    pub fn clone_dir(&self) -> Result<PathBuf> {
+        Ok(ConfigManager::subscriptions_dir()?.join(&self.name))
+    }
+
+    /// Where the subscribed sessions are mirrored locally
+    pub fn local_dir(&self, claude_base_dir: &Path) -> PathBuf {
+        claude_base_dir.join(SHARED_PROJECTS_DIR).join(&self.name)
+    }
+
+    /// Clone or update the subscribed repo and mirror its sessions locally
+    ///
+    /// Returns the number of session files written.
+    pub fn refresh(&self, claude_base_dir: &Path) -> Result<usize> {
+        let clone_dir = self.clone_dir()?;
+        if scm::is_repo(&clone_dir) {
+            let repo = scm::open(&clone_dir)?;
+            let branch = repo.current_branch()?;
+            repo.pull("origin", &branch)
+                .with_context(|| format!("Failed to update subscription '{}'", self.name))?;
+        } else {
+            if let Some(parent) = clone_dir.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            }
+            scm::clone(&self.url, &clone_dir)
+                .with_context(|| format!("Failed to clone subscription '{}'", self.name))?;
+        }
+
+        crate::sync::mirror_sessions(
+            &clone_dir.join(&self.subdirectory),
+            &self.local_dir(claude_base_dir),
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(name: &str) -> Subscription {
+        Subscription {
+            name: name.to_string(),
+            url: "https://example.com/library.git".to_string(),
+            subdirectory: default_subdirectory(),
+        }
+    }
+
+    #[test]
+    fn test_add_remove() {
+        let mut subs = Subscriptions::default();
+        subs.add(subscription("mentor")).unwrap();
+        assert!(subs.add(subscription("mentor")).is_err());
+        assert!(subs.add(subscription("../escape")).is_err());
+        assert_eq!(subs.remove("mentor"), Some(subscription("mentor")));
+        assert!(subs.remove("mentor").is_none());
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let mut subs = Subscriptions::default();
+        subs.add(subscription("team")).unwrap();
+        let content = toml::to_string_pretty(&subs).unwrap();
+        assert!(content.contains("[[subscription]]"));
+        let parsed: Subscriptions = toml::from_str(&content).unwrap();
+        assert_eq!(parsed.get("team"), Some(&subscription("team")));
+    }
+
+    #[test]
+    fn test_local_dir_is_outside_projects() {
+        let dir = subscription("mentor").local_dir(Path::new("/home/me/.claude"));
+        assert_eq!(dir, Path::new("/home/me/.claude/projects-shared/mentor"));
+    }
+}
@@ -0,0 +1,197 @@
+//! Sessions Claude Code may be writing to right now
+//!
+//! Claude Code appends to a session's file as the conversation goes on, so
+//! a pull that rewrites the file in place (entries edited on another
+//! machine, a move to the synced file name, a prefer-remote resolution)
+//! can lose whatever it writes meanwhile. With `active_session_window_secs`
+//! set, a pull treats a session as active when its file was written within
+//! the window, or when it is the newest session of a project a running
+//! `claude` process has as its working directory. Rewrites of active
+//! sessions are deferred to a later pull; missing entries are still
+//! appended.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+
+/// Session files not to rewrite during this pull, and those whose rewrite
+/// was deferred
+#[derive(Debug, Default)]
+pub(crate) struct ActiveSessionGuard {
+    active: BTreeSet<PathBuf>,
+    deferred: RefCell<BTreeSet<PathBuf>>,
+}
+
+impl ActiveSessionGuard {
+    /// Find the active sessions under `claude_dir`; none when the guard is
+    /// disabled
+    pub(crate) fn detect(claude_dir: &Path, filter: &FilterConfig) -> Result<Self> {
+        if filter.active_session_window_secs == 0 {
+            return Ok(Self::default());
+        }
+        let window = Duration::from_secs(filter.active_session_window_secs);
+        let now = SystemTime::now();
+
+        let mut active = BTreeSet::new();
+        for entry in WalkDir::new(claude_dir)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+            if modified.is_some_and(|m| now.duration_since(m).unwrap_or_default() < window) {
+                active.insert(path.to_path_buf());
+            }
+        }
+
+        for cwd in claude_process_dirs() {
+            let project_dir = claude_dir.join(crate::paths::encode_project_path(&cwd));
+            if let Some(newest) = newest_session(&project_dir) {
+                active.insert(newest);
+            }
+        }
+
+        for path in &active {
+            log::debug!("Active session: {}", path.display());
+        }
+        Ok(Self {
+            active,
+            deferred: RefCell::default(),
+        })
+    }
+
+    /// Whether a rewrite of `path` must wait; recorded for [`Self::report`]
+    pub(crate) fn defers(&self, path: &Path) -> bool {
+        if !self.active.contains(path) {
+            return false;
+        }
+        self.deferred.borrow_mut().insert(path.to_path_buf());
+        true
+    }
+
+    /// Print the sessions whose rewrite was deferred
+    pub(crate) fn report(&self, claude_dir: &Path, verbosity: crate::VerbosityLevel) {
+        let deferred = self.deferred.borrow();
+        if deferred.is_empty() || verbosity < crate::VerbosityLevel::Normal {
+            return;
+        }
+        println!(
+            "  {} Deferred rewriting {} sessions Claude Code may be writing to (missing entries were appended):",
+            "!".yellow().bold(),
+            deferred.len()
+        );
+        for path in deferred.iter() {
+            let relative = path.strip_prefix(claude_dir).unwrap_or(path);
+            println!("    {} {}", glyphs::detail().dimmed(), relative.display());
+        }
+        println!(
+            "  {} Pull again once those sessions are idle to finish",
+            glyphs::info().cyan()
+        );
+    }
+}
+
+/// Working directories of running `claude` processes (Linux only; empty
+/// elsewhere)
+fn claude_process_dirs() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter(|e| fs::read(e.path().join("cmdline")).is_ok_and(|c| is_claude_cmdline(&c)))
+        .filter_map(|e| fs::read_link(e.path().join("cwd")).ok())
+        .collect()
+}
+
+/// Whether a NUL-separated command line runs Claude Code, either the
+/// `claude` binary or node running the `claude` script
+fn is_claude_cmdline(cmdline: &[u8]) -> bool {
+    let mut args = cmdline
+        .split(|&b| b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned());
+    let is_claude = |arg: &str| {
+        Path::new(arg)
+            .file_name()
+            .is_some_and(|name| name == "claude")
+    };
+    match args.next() {
+        Some(program) if is_claude(&program) => true,
+        Some(program) if Path::new(&program).file_name().is_some_and(|n| n == "node") => {
+            args.next().is_some_and(|script| {
+                is_claude(&script) || script.contains("@anthropic-ai/claude-code")
+            })
+        }
+        _ => false,
+    }
+}
+
+/// The most recently written session file directly in `project_dir`
+fn newest_session(project_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(project_dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("jsonl"))
+        .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+        .max()
+        .map(|(_, path)| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_claude_cmdline() {
+        assert!(is_claude_cmdline(b"claude\0--resume\0"));
+        assert!(is_claude_cmdline(b"/usr/local/bin/claude\0"));
+        assert!(is_claude_cmdline(
+            b"node\0/usr/lib/node_modules/@anthropic-ai/claude-code/cli.js\0"
+        ));
+        assert!(is_claude_cmdline(
+            b"/usr/bin/node\0/home/me/.local/bin/claude\0"
+        ));
+        assert!(!is_claude_cmdline(b"claude-code-sync\0pull\0"));
+        assert!(!is_claude_cmdline(b"node\0server.js\0"));
+        assert!(!is_claude_cmdline(b""));
+    }
+
+    #[test]
+    fn test_detect_recent_writes() {
+        let temp = tempfile::tempdir().unwrap();
+        let session = temp.path().join("-p/s1.jsonl");
+        fs::create_dir_all(session.parent().unwrap()).unwrap();
+        fs::write(&session, "{}\n").unwrap();
+
+        let guard = ActiveSessionGuard::detect(temp.path(), &FilterConfig::default()).unwrap();
+        assert!(!guard.defers(&session));
+
+        let filter = FilterConfig {
+            active_session_window_secs: 300,
+            ..FilterConfig::default()
+        };
+        let guard = ActiveSessionGuard::detect(temp.path(), &filter).unwrap();
+        assert!(guard.defers(&session));
+        assert!(!guard.defers(&temp.path().join("-p/s2.jsonl")));
+        assert_eq!(guard.deferred.borrow().len(), 1);
+    }
+}
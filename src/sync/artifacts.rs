@@ -0,0 +1,145 @@
+//! Auxiliary artifact directory sync
+//!
+//! Besides session files, Claude Code keeps per-session file backups in
+//! `~/.claude/file-history/<session-id>/` and captured shell environments in
+//! `~/.claude/shell-snapshots/`. Files in both are written once under unique
+//! names and never edited, so they are synced as a union: each side receives
+//! the files it is missing, and nothing is overwritten or deleted.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Directory (relative to `~/.claude` and the sync repo root) for file history backups
+pub const FILE_HISTORY_DIR: &str = "file-history";
+
+/// Directory (relative to `~/.claude` and the sync repo root) for shell snapshots
+pub const SHELL_SNAPSHOTS_DIR: &str = "shell-snapshots";
+
+/// Counts of artifact files copied during a sync
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ArtifactSyncStats {
+    /// Files copied into the local directory
+    pub copied_to_local: usize,
+    /// Files copied into the sync repo
+    pub copied_to_repo: usize,
+    /// Files skipped because they exceed the size limit
+    pub skipped_too_large: usize,
+}
+
+/// Union-sync an artifact directory between `~/.claude` and the sync repo
+///
+/// Files larger than `max_file_size_bytes` are never copied in either direction.
+pub fn sync_artifact_dir(
+    local_dir: &Path,
+    repo_dir: &Path,
+    max_file_size_bytes: u64,
+) -> Result<ArtifactSyncStats> {
+    let mut stats = ArtifactSyncStats::default();
+
+    let (to_repo, skipped) = copy_missing(local_dir, repo_dir, max_file_size_bytes)?;
+    stats.copied_to_repo = to_repo;
+    stats.skipped_too_large += skipped;
+
+    let (to_local, skipped) = copy_missing(repo_dir, local_dir, max_file_size_bytes)?;
+    stats.copied_to_local = to_local;
+    stats.skipped_too_large += skipped;
+
+    Ok(stats)
+}
+
+/// Copy files from `src` that don't exist under `dst`, returning (copied, skipped)
+fn copy_missing(src: &Path, dst: &Path, max_file_size_bytes: u64) -> Result<(usize, usize)> {
+    if !src.exists() {
+        return Ok((0, 0));
+    }
+
+    let mut copied = 0;
+    let mut skipped = 0;
+
+    for entry in WalkDir::new(src)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let relative = entry.path().strip_prefix(src).unwrap_or(entry.path());
+        let dest_path = dst.join(relative);
+        if dest_path.exists() {
+            continue;
+        }
+
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        if size > max_file_size_bytes {
+            log::debug!(
+                "Skipping large artifact {} ({} bytes)",
+                entry.path().display(),
+                size
+            );
+            skipped += 1;
+            continue;
+        }
+
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        fs::copy(entry.path(), &dest_path).with_context(|| {
+            format!(
+                "Failed to copy {} to {}",
+                entry.path().display(),
+                dest_path.display()
+            )
+        })?;
+        copied += 1;
+    }
+
+    Ok((copied, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sync_artifact_dir_union_with_size_limit() {
+        let temp = TempDir::new().unwrap();
+        let local = temp.path().join("local");
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(local.join("session-a")).unwrap();
+        fs::create_dir_all(repo.join("session-b")).unwrap();
+        fs::write(local.join("session-a/abc@v1"), "local").unwrap();
+        fs::write(local.join("session-a/big@v1"), "x".repeat(100)).unwrap();
+        fs::write(repo.join("session-b/def@v1"), "remote").unwrap();
+
+        let stats = sync_artifact_dir(&local, &repo, 10).unwrap();
+        assert_eq!(
+            stats,
+            ArtifactSyncStats {
+                copied_to_local: 1,
+                copied_to_repo: 1,
+                skipped_too_large: 1,
+            }
+        );
+        assert!(repo.join("session-a/abc@v1").exists());
+        assert!(!repo.join("session-a/big@v1").exists());
+        assert!(local.join("session-b/def@v1").exists());
+    }
+
+    #[test]
+    fn test_sync_artifact_dir_never_overwrites() {
+        let temp = TempDir::new().unwrap();
+        let local = temp.path().join("local");
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(&local).unwrap();
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(local.join("snapshot.sh"), "local").unwrap();
+        fs::write(repo.join("snapshot.sh"), "remote").unwrap();
+
+        let stats = sync_artifact_dir(&local, &repo, u64::MAX).unwrap();
+        assert_eq!(stats, ArtifactSyncStats::default());
+        assert_eq!(fs::read_to_string(local.join("snapshot.sh")).unwrap(), "local");
+    }
+}
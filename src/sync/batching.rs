@@ -0,0 +1,185 @@
+//! One commit per project directory
+//!
+//! With `commit_per_project` enabled, staged changes under each project
+//! directory are committed separately, with a subject summarising what
+//! changed in that project, so the sync repo's history can be browsed and
+//! partially reverted per project. Anything staged outside a project
+//! directory goes into a final commit with the usual message.
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::filter::FilterConfig;
+use crate::scm::Scm;
+
+/// What changed in one project directory
+#[derive(Debug, Default)]
+struct ProjectStats {
+    new_sessions: usize,
+    updated_sessions: usize,
+    removed_sessions: usize,
+    other_files: usize,
+    entry_delta: i64,
+}
+
+impl ProjectStats {
+    /// Commit subject for a project, e.g.
+    /// `-home-me-api: 1 new, 2 updated sessions (+40 entries)`
+    fn subject(&self, project: &str) -> String {
+        let mut parts = Vec::new();
+        if self.new_sessions > 0 {
+            parts.push(format!("{} new", self.new_sessions));
+        }
+        if self.updated_sessions > 0 {
+            parts.push(format!("{} updated", self.updated_sessions));
+        }
+        if self.removed_sessions > 0 {
+            parts.push(format!("{} removed", self.removed_sessions));
+        }
+        let mut subject = format!("{project}:");
+        if !parts.is_empty() {
+            subject.push_str(&format!(" {} sessions", parts.join(", ")));
+            if self.entry_delta != 0 {
+                subject.push_str(&format!(" ({:+} entries)", self.entry_delta));
+            }
+        }
+        if self.other_files > 0 {
+            if !parts.is_empty() {
+                subject.push(',');
+            }
+            subject.push_str(&format!(" {} other files", self.other_files));
+        }
+        subject
+    }
+}
+
+/// Non-empty lines, i.e. JSONL entries
+fn count_entries(content: &[u8]) -> i64 {
+    content
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .count() as i64
+}
+
+/// Staged paths grouped by project directory name, with their stats
+fn project_changes(
+    repo: &dyn Scm,
+    repo_root: &Path,
+    filter: &FilterConfig,
+) -> Result<BTreeMap<String, ProjectStats>> {
+    let projects_subdir = filter.projects_subdir();
+    let prefix = format!("{}/", projects_subdir.to_string_lossy().trim_matches('/'));
+    let mut projects: BTreeMap<String, ProjectStats> = BTreeMap::new();
+
+    for path in repo.uncommitted_paths()? {
+        let Some((project, rest)) = path
+            .strip_prefix(&prefix)
+            .and_then(|relative| relative.split_once('/'))
+        else {
+            continue;
+        };
+        let stats = projects.entry(project.to_string()).or_default();
+        if !rest.ends_with(".jsonl") || rest.contains('/') {
+            stats.other_files += 1;
+            continue;
+        }
+
+        let before = repo.read_file_at("HEAD", &path).ok();
+        let after = fs::read(repo_root.join(&path)).ok();
+        match (&before, &after) {
+            (None, Some(_)) => stats.new_sessions += 1,
+            (Some(_), None) => stats.removed_sessions += 1,
+            _ => stats.updated_sessions += 1,
+        }
+        stats.entry_delta +=
+            after.as_deref().map_or(0, count_entries) - before.as_deref().map_or(0, count_entries);
+    }
+
+    Ok(projects)
+}
+
+/// Commit staged changes, one commit per project directory when
+/// `commit_per_project` is enabled
+///
+/// Call after staging; the remaining staged changes are committed with
+/// `message`. Returns the number of commits made.
+pub(crate) fn commit_staged(
+    repo: &dyn Scm,
+    repo_root: &Path,
+    filter: &FilterConfig,
+    message: &str,
+) -> Result<usize> {
+    if !filter.commit_per_project {
+        repo.commit(message)?;
+        return Ok(1);
+    }
+
+    let projects_subdir = filter.projects_subdir();
+    let mut commits = 0;
+    for (project, stats) in project_changes(repo, repo_root, filter)? {
+        let subject = stats.subject(&project);
+        let path: PathBuf = projects_subdir.join(&project);
+        // Unstaged changes are reported too, but have nothing to commit
+        if let Err(e) = repo.commit_paths(&format!("{subject}\n\n{message}"), &[path]) {
+            log::debug!("Nothing committed for project {}: {}", project, e);
+            continue;
+        }
+        log::info!("Committed {}", subject);
+        commits += 1;
+    }
+
+    if repo.has_staged_changes()? {
+        repo.commit(message)?;
+        commits += 1;
+    }
+    Ok(commits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::MockScm;
+
+    /// Write a file to both the mock repo and the directory it stands for
+    fn write(repo: &MockScm, root: &Path, path: &str, content: &str) {
+        repo.write_file(path, content);
+        fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+        fs::write(root.join(path), content).unwrap();
+    }
+
+    #[test]
+    fn test_commit_staged_per_project() {
+        let root = tempfile::tempdir().unwrap();
+        let repo = MockScm::new();
+        write(&repo, root.path(), "projects/a/s1.jsonl", "{}\n");
+        write(&repo, root.path(), "projects/b/s2.jsonl", "{}\n");
+        repo.stage_all().unwrap();
+        repo.commit("Init").unwrap();
+
+        write(&repo, root.path(), "projects/a/s1.jsonl", "{}\n{}\n{}\n");
+        write(&repo, root.path(), "projects/a/s3.jsonl", "{}\n");
+        write(&repo, root.path(), "projects/b/s4.jsonl", "{}\n");
+        write(&repo, root.path(), "history.jsonl", "{}\n");
+        repo.stage_all().unwrap();
+
+        let filter = FilterConfig {
+            commit_per_project: true,
+            ..Default::default()
+        };
+        let commits = commit_staged(&repo, root.path(), &filter, "Sync at now").unwrap();
+
+        assert_eq!(commits, 3);
+        assert_eq!(
+            repo.log("HEAD"),
+            [
+                "Sync at now",
+                "b: 1 new sessions (+1 entries)\n\nSync at now",
+                "a: 1 new, 1 updated sessions (+3 entries)\n\nSync at now",
+                "Init"
+            ]
+        );
+        assert!(!repo.has_changes().unwrap());
+    }
+}
@@ -0,0 +1,143 @@
+//! Offline transfer of the sync repo through bundle files
+//!
+//! For machines with no shared network, `export --git-bundle` writes the
+//! sync branch to a file that `import --git-bundle` on the other machine
+//! pulls from, through the same merge pipeline as a pull from origin. Each
+//! export only carries the commits made since the previous one, so the
+//! files stay small as long as every bundle is imported in turn.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ConfigManager;
+use crate::glyphs;
+use crate::lock::SyncLock;
+use crate::scm;
+
+use super::state::SyncState;
+
+/// Name of the remote an imported bundle is pulled through
+pub const BUNDLE_REMOTE: &str = "bundle";
+
+/// Path of the record of the last commit exported per branch
+fn exports_path() -> Result<PathBuf> {
+    Ok(ConfigManager::state_dir()?.join("bundle-exports.json"))
+}
+
+/// Last commit exported per branch
+fn load_exports() -> Result<BTreeMap<String, String>> {
+    let path = exports_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).context("Failed to parse bundle exports")
+}
+
+fn save_exports(exports: &BTreeMap<String, String>) -> Result<()> {
+    ConfigManager::ensure_state_dir()?;
+    let path = exports_path()?;
+    let content = serde_json::to_string(exports).context("Failed to serialize bundle exports")?;
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Pull local sessions into the sync repo and write the sync branch to a bundle at `path`
+///
+/// Only commits made since the last export of the branch are included,
+/// unless `full` is set.
+pub fn export_bundle(path: &Path, full: bool, verbosity: crate::VerbosityLevel) -> Result<()> {
+    use crate::VerbosityLevel;
+
+    let _lock = SyncLock::acquire()?;
+
+    // Bring local sessions into the sync repo with a pull that stays offline
+    if let Some(record) =
+        super::pull::pull_locked(false, None, false, &[], false, "origin", verbosity)?
+    {
+        super::save_operation_record(record);
+    }
+
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let branch = repo.current_branch()?;
+    let head = repo.current_commit_hash()?;
+
+    let mut exports = load_exports()?;
+    let since = if full {
+        None
+    } else {
+        exports.get(&branch).cloned()
+    };
+    if since.as_deref() == Some(head.as_str()) {
+        bail!(
+            "Nothing new on {} since the last export; use --full to export the whole history",
+            branch
+        );
+    }
+    repo.create_bundle(path, &branch, since.as_deref())
+        .with_context(|| format!("Failed to write bundle {}", path.display()))?;
+    exports.insert(branch.clone(), head);
+    save_exports(&exports)?;
+
+    if verbosity >= VerbosityLevel::Normal {
+        let what = match &since {
+            Some(since) => format!(
+                "commits since {}",
+                since.chars().take(12).collect::<String>()
+            ),
+            None => "full history".to_string(),
+        };
+        println!(
+            "  {} Exported {} ({}) to {}",
+            glyphs::ok().green(),
+            branch,
+            what,
+            path.display()
+        );
+        println!(
+            "  {} Import it on the other machine with {}",
+            glyphs::info().cyan(),
+            "claude-code-sync import --git-bundle <file>".bold()
+        );
+    } else if verbosity == VerbosityLevel::Summary {
+        println!("export {}: {}", branch, path.display());
+    }
+    Ok(())
+}
+
+/// Pull from the bundle at `path`, merging it like a pull from origin
+pub fn import_bundle(path: &Path, verbosity: crate::VerbosityLevel) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Failed to open bundle {}", path.display()))?;
+    let _lock = SyncLock::acquire()?;
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+
+    repo.verify_bundle(&path).with_context(|| {
+        format!(
+            "Bundle {} can't be applied here; if it builds on an export this machine \
+             never imported, export again with --full",
+            path.display()
+        )
+    })?;
+
+    let url = path.to_string_lossy();
+    if repo.has_remote(BUNDLE_REMOTE) {
+        repo.set_remote_url(BUNDLE_REMOTE, &url)?;
+    } else {
+        repo.add_remote(BUNDLE_REMOTE, &url)?;
+    }
+    let result = super::pull::pull_locked(true, None, false, &[], false, BUNDLE_REMOTE, verbosity);
+    if let Err(e) = repo.remove_remote(BUNDLE_REMOTE) {
+        log::debug!("Failed to remove the bundle remote: {}", e);
+    }
+    if let Some(record) = result? {
+        super::save_operation_record(record);
+    }
+    Ok(())
+}
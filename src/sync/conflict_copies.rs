@@ -0,0 +1,249 @@
+//! Conflict copies left by folder-sync tools
+//!
+//! When `.claude` or the sync repo lives inside a Syncthing or Dropbox
+//! folder, concurrent writes to a session leave a sibling such as
+//! `s1.sync-conflict-20250101-120000-ABCDEFG.jsonl` or
+//! `s1 (laptop's conflicted copy 2025-01-01).jsonl` next to `s1.jsonl`.
+//! Discovery skips these files; instead each one is folded into its session
+//! through the [`ConflictDetector`] as one more remote version, and removed
+//! once merged.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::active_sessions::ActiveSessionGuard;
+use crate::conflict::{analyze_session_relationship, ConflictDetector, SessionRelationship};
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::parser::ConversationSession;
+
+/// The session file a folder-sync conflict copy belongs to, or `None` if
+/// `path` isn't a conflict copy
+pub(crate) fn conflict_copy_base(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let (stem, extension) = name.rsplit_once('.')?;
+    // Syncthing: <stem>.sync-conflict-<date>-<time>-<device>.<ext>
+    let base_stem = match stem.split_once(".sync-conflict-") {
+        Some((base, _)) => base,
+        // Dropbox: <stem> (<who's> conflicted copy <date>).<ext>
+        None => {
+            let (base, suffix) = stem.rsplit_once(" (")?;
+            if !suffix.ends_with(')') || !suffix.contains("conflicted copy") {
+                return None;
+            }
+            base
+        }
+    };
+    if base_stem.is_empty() {
+        return None;
+    }
+    Some(path.with_file_name(format!("{base_stem}.{extension}")))
+}
+
+/// Fold the conflict copies of sessions under `scan_dir` into the matching
+/// sessions under `target_dir`, returning how many were folded
+///
+/// `scan_dir` and `target_dir` are the same directory for `.claude`; the
+/// sync repo's copies are folded into `.claude`, where the next pull picks
+/// them up. Files written over and the copies themselves are backed up in
+/// `backup_dir` first. Copies of split session parts, and of sessions
+/// `active_sessions` defers, are left alone.
+pub(crate) fn fold_conflict_copies(
+    scan_dir: &Path,
+    target_dir: &Path,
+    filter: &FilterConfig,
+    backup_dir: &Path,
+    active_sessions: &ActiveSessionGuard,
+    verbosity: crate::VerbosityLevel,
+) -> Result<usize> {
+    let copies: Vec<(PathBuf, PathBuf)> = WalkDir::new(scan_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let base = conflict_copy_base(path)?;
+            (base.extension().and_then(|s| s.to_str()) == Some("jsonl")
+                && crate::parts::split_part(&base).is_none()
+                && filter.should_include(&base))
+            .then(|| (path.to_path_buf(), base))
+        })
+        .collect();
+
+    let id_source = filter.session_id_source()?;
+    let mut folded = 0;
+    for (copy_path, base) in copies {
+        let relative = base.strip_prefix(scan_dir).unwrap_or(&base);
+        let target = target_dir.join(relative);
+        let mut copy = match ConversationSession::from_file_with(&copy_path, id_source) {
+            Ok(copy) => copy,
+            Err(e) => {
+                log::warn!("Failed to parse {}: {}", copy_path.display(), e);
+                continue;
+            }
+        };
+
+        if active_sessions.defers(&target) {
+            // Folded by a later pull, once Claude Code is done with it
+            continue;
+        }
+        if target.exists() {
+            let session = match ConversationSession::from_file_with(&target, id_source) {
+                Ok(session) => session,
+                Err(e) => {
+                    log::warn!("Failed to parse {}: {}", target.display(), e);
+                    continue;
+                }
+            };
+            // Filename-derived IDs differ between the copy and its session
+            copy.session_id = session.session_id.clone();
+            if let Some(entries) = fold_entries(&session, &copy)? {
+                crate::backups::backup_file(backup_dir, &target, relative)?;
+                ConversationSession { entries, ..session }
+                    .write_to_file_with(&target, filter.preserve_raw_lines)?;
+            }
+        } else {
+            copy.write_to_file_with(&target, filter.preserve_raw_lines)?;
+        }
+
+        let copy_relative = copy_path.strip_prefix(scan_dir).unwrap_or(&copy_path);
+        crate::backups::backup_file(backup_dir, &copy_path, copy_relative)?;
+        fs::remove_file(&copy_path)?;
+        folded += 1;
+        if verbosity == crate::VerbosityLevel::Verbose {
+            println!(
+                "    {} folded {} into {}",
+                glyphs::detail().dimmed(),
+                copy_relative.display(),
+                relative.display()
+            );
+        }
+    }
+
+    if folded > 0 && verbosity >= crate::VerbosityLevel::Normal {
+        println!(
+            "  {} Folded {} folder-sync conflict copies into their sessions",
+            glyphs::ok().green(),
+            folded
+        );
+    }
+    Ok(folded)
+}
+
+/// The entries `session` should have once `copy` is folded in, or `None`
+/// if it already has everything
+fn fold_entries(
+    session: &ConversationSession,
+    copy: &ConversationSession,
+) -> Result<Option<Vec<crate::parser::ConversationEntry>>> {
+    let mut detector = ConflictDetector::new().fork_modified_entries(true);
+    detector.detect(std::slice::from_ref(session), std::slice::from_ref(copy));
+    if let Some(conflict) = detector.conflicts_mut().first_mut() {
+        conflict.try_smart_merge(session, copy)?;
+        if let crate::conflict::ConflictResolution::SmartMerge { merged_entries, .. } =
+            &conflict.resolution
+        {
+            return Ok(Some(merged_entries.clone()));
+        }
+    }
+    // Not a conflict: at most one side extends the other
+    Ok(match analyze_session_relationship(session, copy) {
+        SessionRelationship::LocalIsPrefix => Some(copy.entries.clone()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: &str =
+        r#"{"type":"user","uuid":"a","sessionId":"s1","timestamp":"2025-01-01T00:00:00Z"}"#;
+    const B: &str = r#"{"type":"user","uuid":"b","parentUuid":"a","sessionId":"s1","timestamp":"2025-01-01T00:01:00Z"}"#;
+    const C: &str = r#"{"type":"user","uuid":"c","parentUuid":"a","sessionId":"s1","timestamp":"2025-01-01T00:02:00Z"}"#;
+
+    #[test]
+    fn test_conflict_copy_base() {
+        let dir = Path::new("/p/-proj");
+        assert_eq!(
+            conflict_copy_base(&dir.join("s1.sync-conflict-20250101-120000-ABCDEFG.jsonl")),
+            Some(dir.join("s1.jsonl"))
+        );
+        assert_eq!(
+            conflict_copy_base(&dir.join("s1 (laptop's conflicted copy 2025-01-01).jsonl")),
+            Some(dir.join("s1.jsonl"))
+        );
+        for name in [
+            "s1.jsonl",
+            "s1 (1).jsonl",
+            ".sync-conflict-x.jsonl",
+            "s1.part02.jsonl",
+        ] {
+            assert_eq!(conflict_copy_base(&dir.join(name)), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_fold_conflict_copies() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("-proj");
+        fs::create_dir_all(&dir).unwrap();
+        // Diverged copy, a copy that only extends, and one with no session
+        fs::write(dir.join("s1.jsonl"), format!("{A}\n{B}\n")).unwrap();
+        fs::write(
+            dir.join("s1.sync-conflict-20250101-120000-ABCDEFG.jsonl"),
+            format!("{A}\n{C}\n"),
+        )
+        .unwrap();
+        fs::write(dir.join("s2.jsonl"), format!("{A}\n")).unwrap();
+        fs::write(
+            dir.join("s2 (laptop's conflicted copy 2025-01-01).jsonl"),
+            format!("{A}\n{B}\n"),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("s3.sync-conflict-20250101-120000-ABCDEFG.jsonl"),
+            format!("{A}\n"),
+        )
+        .unwrap();
+
+        let backup = temp.path().join("backup");
+        let folded = fold_conflict_copies(
+            temp.path(),
+            temp.path(),
+            &FilterConfig::default(),
+            &backup,
+            &ActiveSessionGuard::default(),
+            crate::VerbosityLevel::Quiet,
+        )
+        .unwrap();
+        assert_eq!(folded, 3);
+
+        let uuids = |name: &str| -> Vec<String> {
+            ConversationSession::from_file(dir.join(name))
+                .unwrap()
+                .entries
+                .iter()
+                .filter_map(|e| e.uuid.clone())
+                .collect()
+        };
+        let mut s1 = uuids("s1.jsonl");
+        s1.sort();
+        assert_eq!(s1, ["a", "b", "c"]);
+        assert_eq!(uuids("s2.jsonl"), ["a", "b"]);
+        assert_eq!(uuids("s3.jsonl"), ["a"]);
+        let mut left: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["s1.jsonl", "s2.jsonl", "s3.jsonl"]);
+        assert!(backup
+            .join("-proj/s1.sync-conflict-20250101-120000-ABCDEFG.jsonl")
+            .exists());
+        assert!(backup.join("-proj/s1.jsonl").exists());
+    }
+}
@@ -6,14 +6,13 @@ use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::history::{SkipKind, SkippedSession};
 use crate::parser::ConversationSession;
 
-/// Threshold for warning about large conversation files (10 MB)
-pub(crate) const LARGE_FILE_WARNING_THRESHOLD: u64 = 10 * 1024 * 1024;
-
 /// Get the Claude Code projects directory
 /// Uses custom path from filter config if specified, otherwise defaults to ~/.claude/projects
-pub(crate) fn claude_projects_dir() -> Result<PathBuf> {
+pub fn claude_projects_dir() -> Result<PathBuf> {
     // Try to load filter config to check for custom path
     if let Ok(filter) = FilterConfig::load() {
         if let Some(ref custom_path) = filter.claude_projects_dir {
@@ -46,70 +45,168 @@ fn expand_tilde(path: &str) -> Result<PathBuf> {
 pub(crate) fn discover_sessions(
     base_path: &Path,
     filter: &FilterConfig,
+) -> Result<Vec<ConversationSession>> {
+    discover_sessions_with_skips(base_path, filter, &mut Vec::new())
+}
+
+/// [`discover_sessions`], adding the session files it leaves out (excluded
+/// by the filters, or unparsable) to `skipped`
+pub(crate) fn discover_sessions_with_skips(
+    base_path: &Path,
+    filter: &FilterConfig,
+    skipped: &mut Vec<SkippedSession>,
 ) -> Result<Vec<ConversationSession>> {
     // First, collect all matching file paths (sequential walk)
-    let paths: Vec<PathBuf> = WalkDir::new(base_path)
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for entry in WalkDir::new(base_path)
         .follow_links(false)
         .into_iter()
         .filter_map(|e| e.ok())
-        .filter(|entry| {
-            let path = entry.path();
-            path.extension().and_then(|s| s.to_str()) == Some("jsonl")
-                && filter.should_include(path)
-        })
-        .map(|entry| entry.path().to_path_buf())
-        .collect();
+    {
+        let path = entry.path();
+        // Folder-sync conflict copies are folded into their session instead
+        if path.extension().and_then(|s| s.to_str()) != Some("jsonl")
+            || super::conflict_copies::conflict_copy_base(path).is_some()
+        {
+            continue;
+        }
+        // Continuation files follow their base file, so a split session
+        // is included or excluded as a whole
+        let base = crate::parts::split_part(path).map(|(base, _)| base);
+        let base = base.as_deref().unwrap_or(path);
+        match filter.exclusion_reason(base) {
+            None => paths.push(path.to_path_buf()),
+            Some(reason) => {
+                let too_large = !filter.is_pinned(base)
+                    && fs::metadata(base).is_ok_and(|m| m.len() > filter.max_file_size_bytes);
+                skipped.push(SkippedSession {
+                    path: path.display().to_string(),
+                    reason,
+                    kind: if too_large {
+                        SkipKind::TooLarge
+                    } else {
+                        SkipKind::Filtered
+                    },
+                });
+            }
+        }
+    }
+
+    let corpus_bytes: u64 = paths
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    log::info!(
+        "Discovered {} session files ({:.1} MB) under {}",
+        paths.len(),
+        corpus_bytes as f64 / (1024.0 * 1024.0),
+        base_path.display()
+    );
+
+    // Pinned sessions go first (stable sort keeps walk order otherwise)
+    paths.sort_by_key(|path| !filter.is_pinned(path));
 
     // Parse files in parallel using rayon
-    let sessions: Vec<ConversationSession> = paths
+    let id_source = filter.session_id_source()?;
+    let parsed: Vec<(&PathBuf, Result<ConversationSession>)> = paths
         .par_iter()
-        .filter_map(|path| match ConversationSession::from_file(path) {
-            Ok(session) => Some(session),
+        .map(|path| (path, ConversationSession::from_file_with(path, id_source)))
+        .collect();
+    let mut sessions = Vec::with_capacity(parsed.len());
+    for (path, result) in parsed {
+        match result {
+            Ok(session) => sessions.push(session),
             Err(e) => {
                 log::warn!("Failed to parse {}: {}", path.display(), e);
-                None
+                skipped.push(SkippedSession {
+                    path: path.display().to_string(),
+                    reason: format!("failed to parse: {e:#}"),
+                    kind: SkipKind::Unparsable,
+                });
             }
+        }
+    }
+    let sessions = crate::parts::reassemble(sessions);
+
+    // Files whose entries disagree about their session ID can be keyed
+    // differently on another machine; flag them once rather than per file
+    let inconsistent = sessions
+        .iter()
+        .filter(|s| {
+            let issues = s.session_id_issues();
+            for issue in &issues {
+                log::debug!(
+                    "Inconsistent session IDs in {} (keyed as {}): {}",
+                    s.file_path,
+                    s.session_id,
+                    issue
+                );
+            }
+            !issues.is_empty()
         })
-        .collect();
+        .count();
+    if inconsistent > 0 {
+        log::warn!(
+            "{} session files under {} have inconsistent session IDs; run `claude-code-sync doctor` for details",
+            inconsistent,
+            base_path.display()
+        );
+    }
 
     Ok(sessions)
 }
 
-/// Check for large conversation files and emit warnings
+/// Warn about a large conversation file
 ///
 /// This helps users identify conversations that may be bloated with excessive
 /// file history, token usage, or other data. Large conversations can slow down
 /// sync operations and consume significant disk space.
-///
-/// # Arguments
-/// * `file_paths` - Iterator of file paths to check
-pub(crate) fn warn_large_files<P, I>(file_paths: I)
-where
-    P: AsRef<Path>,
-    I: IntoIterator<Item = P>,
-{
-    for path in file_paths {
-        let path = path.as_ref();
-
-        if let Ok(metadata) = fs::metadata(path) {
-            let size = metadata.len();
+pub(crate) fn warn_large_file(path: &Path, size: u64) {
+    let size_mb = size as f64 / (1024.0 * 1024.0);
+    println!(
+        "  {} Large conversation file detected: {} ({:.1} MB)",
+        format!("{} ", glyphs::warn()).yellow().bold(),
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown"),
+        size_mb
+    );
+    println!(
+        "     {}",
+        "Consider archiving or cleaning up this conversation to improve sync performance"
+            .dimmed()
+    );
+}
 
-            if size >= LARGE_FILE_WARNING_THRESHOLD {
-                let size_mb = size as f64 / (1024.0 * 1024.0);
-                println!(
-                    "  {} Large conversation file detected: {} ({:.1} MB)",
-                    "⚠️ ".yellow().bold(),
-                    path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("unknown"),
-                    size_mb
-                );
-                println!(
-                    "     {}",
-                    "Consider archiving or cleaning up this conversation to improve sync performance"
-                        .dimmed()
-                );
-            }
-        }
+/// Print the session files an operation left out and why
+///
+/// Lists the first few at normal verbosity and all of them at verbose;
+/// `history last` shows the full list afterwards.
+pub(crate) fn report_skipped(skipped: &[SkippedSession], verbosity: crate::VerbosityLevel) {
+    const SHOWN: usize = 10;
+    if skipped.is_empty() || verbosity < crate::VerbosityLevel::Normal {
+        return;
+    }
+    println!(
+        "  {} Skipped {} session files:",
+        "!".yellow().bold(),
+        skipped.len()
+    );
+    let shown = if verbosity == crate::VerbosityLevel::Verbose {
+        skipped.len()
+    } else {
+        skipped.len().min(SHOWN)
+    };
+    for skip in &skipped[..shown] {
+        println!("    {} {}: {}", glyphs::detail().dimmed(), skip.path, skip.reason.dimmed());
+    }
+    if skipped.len() > shown {
+        println!(
+            "    {} and {} more (see {})",
+            glyphs::detail().dimmed(),
+            skipped.len() - shown,
+            "claude-code-sync history last".bold()
+        );
     }
 }
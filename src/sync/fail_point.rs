@@ -0,0 +1,46 @@
+//! Simulated crashes for recovery testing
+//!
+//! Setting `CLAUDE_CODE_SYNC_FAIL_AT=<phase>` (or the hidden `--fail-at`
+//! flag) makes pull or push return an error as soon as the named phase has
+//! finished, as if the process had died there. Tests use it to check that
+//! the lock is released, temp branches are kept, and the next sync recovers.
+
+use anyhow::{bail, Result};
+
+/// Environment variable naming the phase to fail at
+pub const FAIL_AT_ENV_VAR: &str = "CLAUDE_CODE_SYNC_FAIL_AT";
+
+/// Phases that can be failed at, in the order they run
+///
+/// Pull phases are the ones reported under "Timings"; push adds its own.
+pub const PHASES: &[&str] = &[
+    "temp branch",
+    "discovery (local)",
+    "save local",
+    "fetch remote",
+    "discovery (remote)",
+    "conflict detection",
+    "confirmation",
+    "merge",
+    "append",
+    "history merge",
+    "verification",
+    "cleanup",
+    "push stage",
+    "push commit",
+    "push remote",
+];
+
+/// Fail if `phase` is the phase named by [`FAIL_AT_ENV_VAR`]
+pub(crate) fn check(phase: &str) -> Result<()> {
+    debug_assert!(PHASES.contains(&phase), "unknown fail point {phase:?}");
+    if std::env::var(FAIL_AT_ENV_VAR).is_ok_and(|target| target == phase) {
+        log::warn!("Simulating a failure after phase '{}'", phase);
+        bail!(
+            "Simulated failure after phase '{}' ({})",
+            phase,
+            FAIL_AT_ENV_VAR
+        );
+    }
+    Ok(())
+}
@@ -1,14 +1,17 @@
 //! History.jsonl merge utilities
 //!
 //! Provides functions to merge history.jsonl files from different sources,
-//! deduplicating entries by (sessionId, timestamp) tuple.
+//! deduplicating entries by (sessionId, timestamp) tuple, and to repair a
+//! single file in place the same way.
 
 use anyhow::Result;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
 
+use crate::filter::PullPrecedence;
+
 /// Represents a parsed history.jsonl entry with its deduplication key
 #[derive(Debug, Clone)]
 struct HistoryEntry {
@@ -18,8 +21,10 @@ struct HistoryEntry {
     session_id: String,
     /// Timestamp in milliseconds (required for valid entries)
     timestamp: i64,
-    /// Display text (for logging/debugging)
+    /// Display text (the prompt shown in the resume picker)
     display: String,
+    /// Working directory of the session, if recorded
+    project: Option<String>,
 }
 
 impl HistoryEntry {
@@ -35,6 +40,10 @@ impl HistoryEntry {
             .and_then(|v| v.as_str())
             .unwrap_or("")
             .to_string();
+        let project = value
+            .get("project")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
 
         // Reject entries with missing required fields
         if session_id.is_empty() {
@@ -51,6 +60,7 @@ impl HistoryEntry {
             session_id: session_id.to_string(),
             timestamp,
             display,
+            project,
         })
     }
 
@@ -63,26 +73,70 @@ impl HistoryEntry {
 /// Priority for merge operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MergePriority {
-    /// Source entries take priority (used when pushing local to sync repo)
+    /// Source entries take priority
     SourceFirst,
-    /// Target entries take priority (used when pulling to local)
+    /// Target entries take priority
     TargetFirst,
 }
 
+impl MergePriority {
+    /// The priority that gives the side `precedence` picks its way, where
+    /// `local_is_source` tells which side of the merge is the local file
+    pub fn from_precedence(precedence: PullPrecedence, local_is_source: bool) -> Self {
+        if (precedence == PullPrecedence::LocalFirst) == local_is_source {
+            MergePriority::SourceFirst
+        } else {
+            MergePriority::TargetFirst
+        }
+    }
+}
+
+/// A session that gained history entries in a merge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionHistoryChange {
+    pub session_id: String,
+    /// Entries the target did not have before
+    pub entries_added: usize,
+    /// Whether the target had no entries for this session before, i.e. it is
+    /// newly resumable
+    pub is_new: bool,
+    /// Display text of the session's earliest entry
+    pub display: String,
+    /// Working directory of the session, if recorded
+    pub project: Option<String>,
+}
+
+/// Outcome of [`merge_history_files`]
+#[derive(Debug, Clone, Default)]
+pub struct HistoryMergeSummary {
+    /// Entries in the merged file
+    pub total: usize,
+    /// Entries taken from the source
+    pub added: usize,
+    /// Sessions with entries the target did not have, ordered by their
+    /// earliest entry
+    pub sessions: Vec<SessionHistoryChange>,
+}
+
+impl HistoryMergeSummary {
+    /// Sessions that had no history in the target before the merge
+    pub fn new_sessions(&self) -> impl Iterator<Item = &SessionHistoryChange> {
+        self.sessions.iter().filter(|s| s.is_new)
+    }
+}
+
 /// Merge two history.jsonl files, deduplicating by (sessionId, timestamp)
 ///
 /// # Arguments
 /// * `source_path` - Path to the source history.jsonl file
 /// * `target_path` - Path to the target history.jsonl file (will be overwritten)
 /// * `priority` - Which file's entries take priority when both exist
-///
-/// # Returns
-/// A tuple of (total_entries, entries_added_from_source)
 pub fn merge_history_files(
     source_path: &Path,
     target_path: &Path,
     priority: MergePriority,
-) -> Result<(usize, usize)> {
+) -> Result<HistoryMergeSummary> {
+    let target_keys = read_keys(target_path)?;
     let mut seen: HashSet<(String, i64)> = HashSet::new();
     let mut entries: Vec<HistoryEntry> = Vec::new();
 
@@ -94,7 +148,6 @@ pub fn merge_history_files(
     };
 
     // Read first file (priority)
-    let mut first_count = 0;
     if first_path.exists() {
         let file = fs::File::open(first_path)?;
         for line in BufReader::new(file).lines() {
@@ -107,7 +160,6 @@ pub fn merge_history_files(
                 if !seen.contains(&key) {
                     seen.insert(key);
                     entries.push(entry);
-                    first_count += 1;
                 }
             } else {
                 log::debug!("Skipping invalid history entry: {}", &line[..line.len().min(100)]);
@@ -116,7 +168,6 @@ pub fn merge_history_files(
     }
 
     // Read second file (add entries not in first)
-    let mut second_added = 0;
     if second_path.exists() {
         let file = fs::File::open(second_path)?;
         for line in BufReader::new(file).lines() {
@@ -129,7 +180,6 @@ pub fn merge_history_files(
                 if !seen.contains(&key) {
                     seen.insert(key);
                     entries.push(entry);
-                    second_added += 1;
                 }
             }
         }
@@ -138,20 +188,13 @@ pub fn merge_history_files(
     // Sort by timestamp (entries already have parsed timestamps - no re-parsing needed)
     entries.sort_by_key(|e| e.timestamp);
 
-    // Write merged result
-    if let Some(parent) = target_path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    let mut file = fs::File::create(target_path)?;
-    for entry in &entries {
-        writeln!(file, "{}", entry.line)?;
-    }
+    write_entries(target_path, &entries)?;
 
     let total = entries.len();
-    let added_from_source = match priority {
-        MergePriority::SourceFirst => first_count,
-        MergePriority::TargetFirst => second_added,
-    };
+    let added_from_source = entries
+        .iter()
+        .filter(|e| !target_keys.contains(&e.dedup_key()))
+        .count();
 
     log::info!(
         "Merged history.jsonl: {} total entries, {} from source",
@@ -159,7 +202,155 @@ pub fn merge_history_files(
         added_from_source
     );
 
-    Ok((total, added_from_source))
+    Ok(HistoryMergeSummary {
+        total,
+        added: added_from_source,
+        sessions: session_changes(&entries, &target_keys),
+    })
+}
+
+/// What [`fix_history_file`] found in a history file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryFixSummary {
+    /// Entries kept
+    pub kept: usize,
+    /// Lines that aren't valid entries (no sessionId or timestamp)
+    pub invalid: usize,
+    /// Repeated (sessionId, timestamp) entries
+    pub duplicates: usize,
+    /// Entries for sessions that no longer exist
+    pub orphaned: usize,
+    /// Whether entries were out of timestamp order
+    pub unsorted: bool,
+}
+
+impl HistoryFixSummary {
+    /// Whether the file differs from its repaired form
+    pub fn needs_rewrite(&self) -> bool {
+        self.invalid + self.duplicates + self.orphaned > 0 || self.unsorted
+    }
+}
+
+/// Dedupe a history.jsonl file by (sessionId, timestamp) and sort it
+///
+/// The first of duplicate entries is kept. When `existing_sessions` is given,
+/// entries for other sessions are dropped. The file is only rewritten when
+/// something changes and `dry_run` is false.
+pub fn fix_history_file(
+    path: &Path,
+    existing_sessions: Option<&HashSet<String>>,
+    dry_run: bool,
+) -> Result<HistoryFixSummary> {
+    let mut summary = HistoryFixSummary::default();
+    let mut seen: HashSet<(String, i64)> = HashSet::new();
+    let mut entries: Vec<HistoryEntry> = Vec::new();
+
+    let file = fs::File::open(path)?;
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(entry) = HistoryEntry::parse(&line) else {
+            summary.invalid += 1;
+            continue;
+        };
+        if !seen.insert(entry.dedup_key()) {
+            summary.duplicates += 1;
+        } else if existing_sessions.is_some_and(|s| !s.contains(&entry.session_id)) {
+            summary.orphaned += 1;
+        } else {
+            entries.push(entry);
+        }
+    }
+
+    summary.unsorted = !entries.is_sorted_by_key(|e| e.timestamp);
+    summary.kept = entries.len();
+    if summary.needs_rewrite() && !dry_run {
+        entries.sort_by_key(|e| e.timestamp);
+        write_entries(path, &entries)?;
+    }
+    Ok(summary)
+}
+
+/// Drop the entries of sessions matching `is_removed` from a history file
+///
+/// Other lines, including ones that don't parse, are kept as they are. The
+/// file is only rewritten when something is dropped. Returns the number of
+/// entries dropped.
+pub fn remove_history_sessions(path: &Path, is_removed: impl Fn(&str) -> bool) -> Result<usize> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let content = fs::read_to_string(path)?;
+    let mut kept = String::with_capacity(content.len());
+    let mut removed = 0;
+    for line in content.lines() {
+        if HistoryEntry::parse(line).is_some_and(|e| is_removed(&e.session_id)) {
+            removed += 1;
+        } else {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+    if removed > 0 {
+        fs::write(path, kept)?;
+    }
+    Ok(removed)
+}
+
+fn write_entries(path: &Path, entries: &[HistoryEntry]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::File::create(path)?;
+    for entry in entries {
+        writeln!(file, "{}", entry.line)?;
+    }
+    Ok(())
+}
+
+/// Deduplication keys of the valid entries in a history file
+fn read_keys(path: &Path) -> Result<HashSet<(String, i64)>> {
+    let mut keys = HashSet::new();
+    if path.exists() {
+        let file = fs::File::open(path)?;
+        for line in BufReader::new(file).lines() {
+            if let Some(entry) = HistoryEntry::parse(&line?) {
+                keys.insert(entry.dedup_key());
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Per-session summary of the merged entries missing from `target_keys`
+///
+/// `entries` must be sorted by timestamp.
+fn session_changes(
+    entries: &[HistoryEntry],
+    target_keys: &HashSet<(String, i64)>,
+) -> Vec<SessionHistoryChange> {
+    let target_sessions: HashSet<&str> = target_keys.iter().map(|(s, _)| s.as_str()).collect();
+    let mut changes: Vec<SessionHistoryChange> = Vec::new();
+    let mut index: BTreeMap<&str, usize> = BTreeMap::new();
+    for entry in entries {
+        if target_keys.contains(&entry.dedup_key()) {
+            continue;
+        }
+        let i = *index.entry(&entry.session_id).or_insert_with(|| {
+            changes.push(SessionHistoryChange {
+                session_id: entry.session_id.clone(),
+                entries_added: 0,
+                is_new: !target_sessions.contains(entry.session_id.as_str()),
+                display: entry.display.clone(),
+                project: entry.project.clone(),
+            });
+            changes.len() - 1
+        });
+        changes[i].entries_added += 1;
+    }
+    changes
 }
 
 #[cfg(test)]
@@ -211,15 +402,28 @@ mod tests {
         ]);
 
         // Target first - target's version of duplicate should win
-        let (total, added) = merge_history_files(&source, &target, MergePriority::TargetFirst).unwrap();
-        assert_eq!(total, 3); // a@1000, a@2000, b@3000
-        assert_eq!(added, 1); // Only a@2000 added from source
+        let summary = merge_history_files(&source, &target, MergePriority::TargetFirst).unwrap();
+        assert_eq!(summary.total, 3); // a@1000, a@2000, b@3000
+        assert_eq!(summary.added, 1); // Only a@2000 added from source
 
         // Read back and verify
         let content = fs::read_to_string(&target).unwrap();
         assert!(content.contains("target1")); // Target's version kept
         assert!(content.contains("source2")); // Unique from source
         assert!(content.contains("target3")); // Unique from target
+
+        // Source first - source's version of the duplicate replaces target's,
+        // and only entries new to the target count as added
+        write_history_file(&target, &[
+            r#"{"sessionId":"a","timestamp":1000,"display":"target1"}"#,
+            r#"{"sessionId":"b","timestamp":3000,"display":"target3"}"#,
+        ]);
+        let summary = merge_history_files(&source, &target, MergePriority::SourceFirst).unwrap();
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.added, 1);
+        let content = fs::read_to_string(&target).unwrap();
+        assert!(content.contains("source1"));
+        assert!(!content.contains("target1"));
     }
 
     #[test]
@@ -245,4 +449,100 @@ mod tests {
         assert!(lines[1].contains("second"));
         assert!(lines[2].contains("third"));
     }
+
+    #[test]
+    fn test_remove_history_sessions() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        write_history_file(&path, &[
+            r#"{"sessionId":"a","timestamp":1000,"display":"keep"}"#,
+            r#"{"sessionId":"secret","timestamp":2000,"display":"drop"}"#,
+            r#"not json"#,
+        ]);
+
+        assert_eq!(remove_history_sessions(&path, |id| id == "secret").unwrap(), 1);
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("keep") && content.contains("not json"));
+        assert!(!content.contains("drop"));
+        assert_eq!(remove_history_sessions(&path, |id| id == "secret").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_fix_history_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.jsonl");
+        write_history_file(&path, &[
+            r#"{"sessionId":"a","timestamp":2000,"display":"second"}"#,
+            r#"{"sessionId":"a","timestamp":1000,"display":"first"}"#,
+            r#"{"sessionId":"a","timestamp":2000,"display":"again"}"#,
+            r#"{"sessionId":"gone","timestamp":1500,"display":"orphan"}"#,
+            r#"not json"#,
+        ]);
+
+        // Dry run reports without touching the file
+        let existing: HashSet<String> = ["a".to_string()].into();
+        let summary = fix_history_file(&path, Some(&existing), true).unwrap();
+        assert_eq!(
+            summary,
+            HistoryFixSummary {
+                kept: 2,
+                invalid: 1,
+                duplicates: 1,
+                orphaned: 1,
+                unsorted: true,
+            }
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 5);
+
+        // Without pruning, the orphan stays
+        let summary = fix_history_file(&path, None, false).unwrap();
+        assert_eq!(summary.kept, 3);
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains("first"));
+        assert!(lines[1].contains("orphan"));
+        assert!(lines[2].contains("second"));
+
+        assert!(!fix_history_file(&path, None, false).unwrap().needs_rewrite());
+    }
+
+    #[test]
+    fn test_merge_reports_new_sessions() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("source.jsonl");
+        let target = dir.path().join("target.jsonl");
+
+        write_history_file(&source, &[
+            r#"{"sessionId":"a","timestamp":1000,"display":"old"}"#,
+            r#"{"sessionId":"a","timestamp":2000,"display":"more"}"#,
+            r#"{"sessionId":"c","timestamp":4000,"display":"fix login","project":"/w/app"}"#,
+            r#"{"sessionId":"c","timestamp":5000,"display":"and tests"}"#,
+        ]);
+        write_history_file(&target, &[
+            r#"{"sessionId":"a","timestamp":1000,"display":"old"}"#,
+        ]);
+
+        let summary = merge_history_files(&source, &target, MergePriority::TargetFirst).unwrap();
+        assert_eq!(
+            summary.sessions,
+            vec![
+                SessionHistoryChange {
+                    session_id: "a".to_string(),
+                    entries_added: 1,
+                    is_new: false,
+                    display: "more".to_string(),
+                    project: None,
+                },
+                SessionHistoryChange {
+                    session_id: "c".to_string(),
+                    entries_added: 2,
+                    is_new: true,
+                    display: "fix login".to_string(),
+                    project: Some("/w/app".to_string()),
+                },
+            ]
+        );
+        assert_eq!(summary.new_sessions().count(), 1);
+    }
 }
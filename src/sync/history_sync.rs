@@ -0,0 +1,132 @@
+//! Syncing only `history.jsonl`, the session index behind the `--resume`
+//! picker
+//!
+//! For users who keep session files in sync some other way but still want
+//! every machine's picker to list every session. Nothing under `projects/`
+//! is read or written.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use std::path::PathBuf;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::lock::SyncLock;
+use crate::scm;
+
+use super::history_merge::{merge_history_files, MergePriority};
+use super::state::SyncState;
+use super::temp_branch::TEMP_BRANCH_PREFIX;
+
+/// Sync `history.jsonl` with the sync repository and its remote
+///
+/// Pulls the current branch, merges the local file into the repository's
+/// copy and the result back into the local file (entries both hold are
+/// resolved by the configured pull precedence), then commits and, if
+/// `push_remote` is set, pushes the repository's copy.
+pub fn sync_history_index(push_remote: bool, verbosity: crate::VerbosityLevel) -> Result<()> {
+    use crate::VerbosityLevel;
+
+    let _lock = SyncLock::acquire()?;
+
+    if verbosity >= VerbosityLevel::Normal {
+        println!("{}", "Syncing history.jsonl...".cyan().bold());
+    }
+
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let filter = FilterConfig::load()?;
+    let precedence = filter.pull_precedence()?;
+
+    let branch = repo.current_branch()?;
+    if branch.starts_with(TEMP_BRANCH_PREFIX) {
+        bail!(
+            "The sync repository is on temp branch '{}' left by an interrupted pull. \
+             Run 'claude-code-sync pull' first.",
+            branch
+        );
+    }
+    super::worktree::confirm_unexpected_changes(repo.as_ref(), &filter, verbosity)?;
+
+    if state.has_remote {
+        super::purge::recover_from_purge(repo.as_ref(), &state.sync_repo_path, &branch, verbosity)?;
+        match repo.pull("origin", &branch) {
+            Ok(()) => {
+                if verbosity >= VerbosityLevel::Normal {
+                    println!("  {} Pulled origin/{}", glyphs::ok().green(), branch);
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to pull: {}", e);
+                if verbosity >= VerbosityLevel::Normal {
+                    println!(
+                        "  {} Failed to pull from origin/{}: {}",
+                        "!".yellow().bold(),
+                        branch,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    let projects_dir = super::claude_projects_dir()?;
+    let claude_base_dir = projects_dir.parent().unwrap_or(&projects_dir);
+    let local_history = claude_base_dir.join("history.jsonl");
+    let sync_history = state.sync_repo_path.join("history.jsonl");
+
+    let to_repo = merge_history_files(
+        &local_history,
+        &sync_history,
+        MergePriority::from_precedence(precedence, true),
+    )?;
+    let to_local = merge_history_files(
+        &sync_history,
+        &local_history,
+        MergePriority::from_precedence(precedence, false),
+    )?;
+    if verbosity >= VerbosityLevel::Normal {
+        println!(
+            "  {} history.jsonl merged ({} entries, {} new locally, {} new in the sync repo)",
+            glyphs::ok().green(),
+            to_local.total,
+            to_local.added,
+            to_repo.added
+        );
+    }
+
+    if filter.checksum_manifest {
+        crate::manifest::write_manifest(&state.sync_repo_path)?;
+    }
+    repo.stage_paths(&[
+        PathBuf::from("history.jsonl"),
+        PathBuf::from(crate::manifest::MANIFEST_FILE),
+    ])?;
+    if repo.has_staged_changes()? {
+        repo.commit(&format!(
+            "Sync history.jsonl ({})",
+            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+        ))?;
+        if verbosity >= VerbosityLevel::Normal {
+            println!("  {} Committed history.jsonl", glyphs::ok().green());
+        }
+    }
+
+    if push_remote && state.has_remote {
+        crate::audit::push_audited(repo.as_ref(), "origin", &branch, &branch).map_err(|e| {
+            e.context("Failed to push history.jsonl (run 'claude-code-sync history-sync' again)")
+        })?;
+        if verbosity >= VerbosityLevel::Normal {
+            println!("  {} Pushed to origin/{}", glyphs::ok().green(), branch);
+        }
+    }
+
+    if verbosity == VerbosityLevel::Summary {
+        println!(
+            "history-sync: {} entries, {} new locally, {} new in the sync repo",
+            to_local.total, to_local.added, to_repo.added
+        );
+    }
+
+    Ok(())
+}
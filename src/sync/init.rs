@@ -37,6 +37,7 @@ pub fn init_from_onboarding(
         sync_repo_path: repo_path.to_path_buf(),
         has_remote,
         is_cloned_repo: is_cloned,
+        rsync_remote: None,
     };
     state.save()?;
 
@@ -85,6 +86,7 @@ pub fn init_sync_repo(repo_path: &Path, remote_url: Option<&str>) -> Result<()>
         sync_repo_path: repo_path.to_path_buf(),
         has_remote,
         is_cloned_repo: false,
+        rsync_remote: None,
     };
     state.save()?;
 
@@ -1,66 +1,352 @@
 // Module declarations
+mod active_sessions;
+mod artifacts;
+mod batching;
+mod bundle;
+mod conflict_copies;
 mod discovery;
+mod fail_point;
 mod history_merge;
+mod history_sync;
 mod init;
+mod namespaces;
+mod nested_paths;
+mod oplog;
+mod project_index;
 mod pull;
+mod pull_at;
+mod purge;
 mod push;
+mod reconcile;
+mod rel_path;
+mod relocation;
 mod remote;
+mod remote_lease;
+mod rsync;
+mod snapshot;
 mod state;
 mod status;
+mod temp_branch;
+mod throttle;
+mod timing;
+mod todos;
+mod worktree;
 
 // Re-export public types and functions
+pub use bundle::{export_bundle, import_bundle};
+pub use discovery::claude_projects_dir;
+pub use fail_point::{FAIL_AT_ENV_VAR, PHASES as FAIL_POINT_PHASES};
+pub use history_merge::{fix_history_file, remove_history_sessions};
+pub use history_sync::sync_history_index;
+pub(crate) use discovery::discover_sessions;
 pub use init::{init_from_onboarding, init_sync_repo};
+pub use namespaces::{mirror_sessions, user_projects_subdir, validate_user_name};
+pub use nested_paths::{find_nested_sessions, fix_nested_paths};
 pub use pull::pull_history;
+pub use pull_at::pull_at_revision;
+pub use purge::{recover_from_purge, remove_temp_branches};
 pub use push::push_history;
-pub use remote::{remove_remote, set_remote, show_remote};
+pub use remote::{remove_remote, remove_rsync_remote, set_remote, set_rsync_remote, show_remote};
+pub use snapshot::{
+    create_snapshot, delete_snapshot, list_snapshots, remove_snapshots, restore_snapshot,
+};
 pub use state::SyncState;
 pub use status::show_status;
+pub use temp_branch::{
+    cleanup_old_temp_branches, stale_own_temp_branches, stale_remote_temp_branches,
+    temp_branch_machine, TEMP_BRANCH_PREFIX,
+};
+pub use throttle::automatic_sync_wait;
+pub use todos::TodoMergeMode;
+pub use worktree::managed_paths;
 
 use anyhow::Result;
 use colored::Colorize;
 
-/// Maximum number of conversations to display per project in summary
-const MAX_CONVERSATIONS_TO_DISPLAY: usize = 10;
+use crate::glyphs;
+use crate::history::{OperationHistory, OperationRecord, OperationType, SyncOperation};
+use crate::lock::SyncLock;
+
+/// How far a pull or sync got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncOutcome {
+    /// Remote changes were merged (or there is no remote to merge)
+    Complete,
+    /// Local sessions were saved to the sync repo, but the remote couldn't
+    /// be fetched from or pulled
+    LocalOnly,
+}
+
+impl SyncOutcome {
+    /// Exit code of a run that only got as far as [`SyncOutcome::LocalOnly`]
+    pub const LOCAL_ONLY_EXIT_CODE: i32 = 3;
+
+    fn of(record: Option<&OperationRecord>) -> Self {
+        if record.is_some_and(|r| r.remote_unreachable) {
+            SyncOutcome::LocalOnly
+        } else {
+            SyncOutcome::Complete
+        }
+    }
+}
+
+/// Save an operation record to history, logging (not failing) on error
+fn save_operation_record(record: OperationRecord) {
+    let mut history = match OperationHistory::load() {
+        Ok(h) => h,
+        Err(e) => {
+            log::warn!("Failed to load operation history: {}", e);
+            OperationHistory::default()
+        }
+    };
+
+    match history.add_operation(record) {
+        Ok(expired) => expire_operation_artifacts(&expired),
+        Err(e) => log::warn!("Failed to save operation to history: {}", e),
+    }
+}
+
+/// Fail a `--strict` operation, once its record is saved, if it skipped
+/// sessions for being too large or unparsable or hit network failures
+fn fail_if_incomplete(failures: &[String]) -> Result<()> {
+    if failures.is_empty() {
+        return Ok(());
+    }
+    eprintln!(
+        "\n{} {}",
+        glyphs::fail().red().bold(),
+        format!("Strict mode: the operation was incomplete ({} problems)", failures.len())
+            .red()
+            .bold()
+    );
+    for failure in failures {
+        eprintln!("    {} {}", glyphs::detail().dimmed(), failure);
+    }
+    anyhow::bail!(
+        "{} sessions skipped or network steps failed (--strict)",
+        failures.len()
+    )
+}
+
+/// Remove what operation records rotated out of history point at: backups,
+/// snapshots, conflict reports, and retained temp branches
+fn expire_operation_artifacts(expired: &[OperationRecord]) {
+    if expired.is_empty() {
+        return;
+    }
+    let reports_dir = match crate::config::ConfigManager::conflict_reports_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::warn!("Failed to locate conflict reports: {}", e);
+            return;
+        }
+    };
+    let state = SyncState::load().ok();
+    let repo = state
+        .as_ref()
+        .and_then(|state| crate::scm::open(&state.sync_repo_path).ok());
+    let removed = crate::history::expire_artifacts(
+        expired,
+        repo.as_deref(),
+        state.as_ref().is_some_and(|state| state.has_remote),
+        &reports_dir,
+    );
+    log::info!(
+        "Removed {} artifacts of {} expired operation records",
+        removed,
+        expired.len()
+    );
+}
+
+/// Lay out session files in the sync repo according to
+/// `max_entries_per_file`, splitting oversized sessions into continuation
+/// files (or folding them back in once splitting is off)
+fn split_oversized_sessions(
+    projects_dir: &std::path::Path,
+    filter: &crate::filter::FilterConfig,
+    verbosity: crate::VerbosityLevel,
+) -> Result<()> {
+    let split = crate::parts::split_oversized(projects_dir, filter.max_entries_per_file)?;
+    if split > 0 && verbosity == crate::VerbosityLevel::Verbose {
+        println!(
+            "    {} rearranged continuation files of {} sessions",
+            glyphs::detail().dimmed(),
+            split
+        );
+    }
+    Ok(())
+}
+
+/// Combine the records of a sync's pull and push halves into one
+fn combine_sync_records(
+    pull: Option<OperationRecord>,
+    push: Option<OperationRecord>,
+) -> Option<OperationRecord> {
+    match (pull, push) {
+        (Some(pull), Some(push)) => {
+            let mut record = OperationRecord::new(
+                OperationType::Sync,
+                pull.branch.or(push.branch),
+                pull.affected_conversations,
+            );
+            record.snapshot_path = pull.snapshot_path;
+            record.backup_path = pull.backup_path;
+            record.conflict_report = pull.conflict_report;
+            record.inconsistent_sessions = pull.inconsistent_sessions;
+            record.skipped_sessions = pull.skipped_sessions;
+            record.network_failures = pull.network_failures;
+            record.network_failures.extend(push.network_failures);
+            record.remote_unreachable = pull.remote_unreachable;
+            record.temp_branch = pull.temp_branch;
+            record.phase_timings = pull.phase_timings;
+            record.commit_hash = push.commit_hash;
+            Some(record)
+        }
+        (pull, push) => pull.or(push),
+    }
+}
 
 /// Bidirectional sync: pull remote changes, then push local changes
+///
+/// Both halves run under a single lock and produce a single operation
+/// record. Either half can be skipped with `skip_pull` / `skip_push`, and
+/// the pull's history.jsonl steps with `skip_history`. With `strict`, a sync
+/// that skipped sessions for being too large or unparsable, or hit network
+/// failures, fails instead of only warning.
+///
+/// Returns [`SyncOutcome::LocalOnly`] if the pull couldn't reach the remote.
+#[allow(clippy::too_many_arguments)]
 pub fn sync_bidirectional(
     commit_message: Option<&str>,
     branch: Option<&str>,
     exclude_attachments: bool,
     interactive: bool,
+    include_users: &[String],
+    skip_history: bool,
+    skip_pull: bool,
+    skip_push: bool,
+    strict: bool,
     verbosity: crate::VerbosityLevel,
-) -> Result<()> {
+) -> Result<SyncOutcome> {
     use crate::VerbosityLevel;
 
-    if verbosity != VerbosityLevel::Quiet {
+    // One lock for the whole round trip, so nothing can run in between
+    let _lock = SyncLock::acquire()?;
+    // and, if configured, one lease on the remote so other machines wait
+    let state = SyncState::load()?;
+    let repo = crate::scm::open(&state.sync_repo_path)?;
+    let _lease = if skip_push {
+        None
+    } else {
+        remote_lease::RemoteLease::acquire_configured(repo.as_ref(), &state, verbosity)?
+    };
+
+    if verbosity >= VerbosityLevel::Normal {
         println!("{}", "=== Bidirectional Sync ===".bold().cyan());
-        println!();
-        println!("{}", "Step 1: Pulling remote changes...".bold());
     }
 
-    // First, pull remote changes
-    pull_history(true, branch, interactive, verbosity)?;
+    let pull_record = if skip_pull {
+        None
+    } else {
+        if verbosity >= VerbosityLevel::Normal {
+            println!();
+            println!("{}", "Step 1: Pulling remote changes...".bold());
+        }
+        pull::pull_locked(
+            true,
+            branch,
+            interactive,
+            include_users,
+            skip_history,
+            "origin",
+            verbosity,
+        )?
+    };
 
-    if verbosity != VerbosityLevel::Quiet {
-        println!();
-        println!("{}", "Step 2: Pushing local changes...".bold());
-    }
+    let push_record = if skip_push {
+        None
+    } else {
+        if verbosity >= VerbosityLevel::Normal {
+            println!();
+            println!("{}", "Step 2: Pushing local changes...".bold());
+        }
+        push::push_locked(
+            commit_message,
+            true,
+            branch,
+            exclude_attachments,
+            interactive,
+            verbosity,
+        )?
+    };
+    let pushed = push_record.is_some();
+    let skipped = pull_record
+        .as_ref()
+        .map_or(0, |r| r.skipped_sessions.len());
 
-    // Then, push local changes
-    push_history(commit_message, true, branch, exclude_attachments, interactive, verbosity)?;
+    let record = combine_sync_records(pull_record, push_record);
+    let changed = record.as_ref().map_or(0, |r| {
+        r.affected_conversations
+            .iter()
+            .filter(|c| c.operation != SyncOperation::Unchanged)
+            .count()
+    });
+    let outcome = SyncOutcome::of(record.as_ref());
+    if let Some(record) = record {
+        let failures = if strict {
+            record.strict_failures()
+        } else {
+            Vec::new()
+        };
+        save_operation_record(record);
+        fail_if_incomplete(&failures)?;
+    }
 
-    if verbosity == VerbosityLevel::Quiet {
-        println!("Sync complete");
-    } else {
+    // At Summary level each half has already printed its one-line summary
+    if verbosity >= VerbosityLevel::Normal {
         println!();
-        println!("{}", "=== Sync Complete ===".green().bold());
-        println!(
-            "  {} Your local and remote histories are now in sync",
-            "✓".green()
-        );
+        if outcome == SyncOutcome::LocalOnly {
+            println!(
+                "{}",
+                "=== Sync Complete (LOCAL ONLY — remote unreachable) ==="
+                    .yellow()
+                    .bold()
+            );
+        } else {
+            println!("{}", "=== Sync Complete ===".green().bold());
+        }
+        if !skip_pull {
+            println!("  {} Pulled: {} conversations changed", glyphs::ok().green(), changed);
+        }
+        if !skip_push {
+            if pushed {
+                println!("  {} Pushed local changes", glyphs::ok().green());
+            } else {
+                println!("  {} Nothing to push", glyphs::ok().green());
+            }
+        }
+        if outcome == SyncOutcome::LocalOnly {
+            println!(
+                "  {} Remote changes were not pulled; sync again once the remote is reachable",
+                "!".yellow().bold()
+            );
+        } else if !skip_pull && !skip_push {
+            println!(
+                "  {} Your local and remote histories are now in sync",
+                glyphs::ok().green()
+            );
+        }
+        if skipped > 0 {
+            println!(
+                "  {} {} session files were skipped (see {})",
+                "!".yellow().bold(),
+                skipped,
+                "claude-code-sync history last".bold()
+            );
+        }
     }
 
-    Ok(())
+    Ok(outcome)
 }
 
 #[cfg(test)]
@@ -84,10 +370,11 @@ mod tests {
             sync_repo_path: repo_path.clone(),
             has_remote: false,
             is_cloned_repo: false,
+            rsync_remote: None,
         };
 
         // Create state directory using ConfigManager
-        let _state_path = crate::config::ConfigManager::ensure_config_dir().unwrap();
+        let _state_path = crate::config::ConfigManager::ensure_state_dir().unwrap();
         let state_file = crate::config::ConfigManager::state_file_path().unwrap();
         std::fs::write(&state_file, serde_json::to_string(&state).unwrap()).unwrap();
 
@@ -143,4 +430,24 @@ mod tests {
         assert!(filter.should_include(Path::new("image.png")));
         assert!(filter.should_include(Path::new("document.pdf")));
     }
+
+    #[test]
+    fn test_combine_sync_records() {
+        let mut pull = OperationRecord::new(OperationType::Pull, Some("main".to_string()), vec![]);
+        pull.snapshot_path = Some("/tmp/snapshot".into());
+        pull.temp_branch = Some("sync-local-laptop-20250101-000000-abc123".to_string());
+        let mut push = OperationRecord::new(OperationType::Push, Some("main".to_string()), vec![]);
+        push.commit_hash = Some("abc123".to_string());
+
+        let combined = combine_sync_records(Some(pull.clone()), Some(push)).unwrap();
+        assert_eq!(combined.operation_type, OperationType::Sync);
+        assert_eq!(combined.snapshot_path, pull.snapshot_path);
+        assert_eq!(combined.temp_branch, pull.temp_branch);
+        assert_eq!(combined.commit_hash.as_deref(), Some("abc123"));
+
+        // With one half skipped, that half's record is kept as-is
+        let only_pull = combine_sync_records(Some(pull), None).unwrap();
+        assert_eq!(only_pull.operation_type, OperationType::Pull);
+        assert!(combine_sync_records(None, None).is_none());
+    }
 }
@@ -0,0 +1,161 @@
+//! Per-user namespaces in a team-shared sync repo
+//!
+//! When `user_namespace` is configured, a user's sessions live under
+//! `users/<name>/<sync_subdirectory>/` instead of directly under
+//! `<sync_subdirectory>/`, so several people can share one repo without
+//! merging each other's conversations. A pull only merges the user's own
+//! namespace; teammates' namespaces can be pulled read-only on request.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Directory (relative to the sync repo root) holding per-user namespaces
+pub const USERS_DIR: &str = "users";
+
+/// Validate a user namespace name
+///
+/// Names become a single path component, so separators and relative
+/// components are rejected.
+pub fn validate_user_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\'])
+        || name.starts_with('.')
+    {
+        bail!("Invalid user namespace: '{}'", name);
+    }
+    Ok(())
+}
+
+/// Path (relative to the sync repo root) of a user's projects directory
+pub fn user_projects_subdir(user: &str, sync_subdirectory: &str) -> PathBuf {
+    Path::new(USERS_DIR).join(user).join(sync_subdirectory)
+}
+
+/// Session IDs stored in other users' namespaces
+///
+/// These are never saved into the current user's namespace, which keeps
+/// sessions pulled with `--include-user` read-only.
+pub fn foreign_session_ids(
+    sync_repo: &Path,
+    sync_subdirectory: &str,
+    own_user: Option<&str>,
+) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    let Ok(users) = fs::read_dir(sync_repo.join(USERS_DIR)) else {
+        return ids;
+    };
+
+    for user in users.filter_map(|e| e.ok()) {
+        let name = user.file_name().to_string_lossy().to_string();
+        if Some(name.as_str()) == own_user {
+            continue;
+        }
+        let projects = user.path().join(sync_subdirectory);
+        ids.extend(
+            WalkDir::new(projects)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+                .filter_map(|e| e.path().file_stem().map(|s| s.to_string_lossy().to_string())),
+        );
+    }
+
+    ids
+}
+
+/// Copy read-only sessions (a teammate's or a subscription's) into a local directory
+///
+/// The source copy is authoritative: local files that differ are
+/// overwritten, after being copied into `backup_dir` when one is given.
+/// Returns the number of session files written.
+pub fn mirror_sessions(
+    source_dir: &Path,
+    dest_dir: &Path,
+    backup_dir: Option<&Path>,
+) -> Result<usize> {
+    if !source_dir.exists() {
+        bail!("No shared sessions found at {}", source_dir.display());
+    }
+
+    let mut written = 0;
+    for entry in WalkDir::new(source_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+    {
+        let relative = entry
+            .path()
+            .strip_prefix(source_dir)
+            .unwrap_or(entry.path());
+        let dest = dest_dir.join(relative);
+
+        let source_bytes = fs::read(entry.path())
+            .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+        if fs::read(&dest).is_ok_and(|existing| existing == source_bytes) {
+            continue;
+        }
+        let new_entries = String::from_utf8_lossy(&source_bytes)
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .count();
+        if crate::quarantine::quarantine_if_shrinking(&dest, relative, new_entries)?.is_some() {
+            continue;
+        }
+        if let Some(backup_dir) = backup_dir {
+            crate::backups::backup_file(backup_dir, &dest, relative)?;
+        }
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        fs::write(&dest, source_bytes)
+            .with_context(|| format!("Failed to write {}", dest.display()))?;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_validate_user_name() {
+        assert!(validate_user_name("alice").is_ok());
+        assert!(validate_user_name("bob.smith").is_ok());
+        assert!(validate_user_name("").is_err());
+        assert!(validate_user_name("..").is_err());
+        assert!(validate_user_name("a/b").is_err());
+    }
+
+    #[test]
+    fn test_foreign_session_ids_and_pull() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        for (user, id) in [("alice", "s-alice"), ("bob", "s-bob")] {
+            let dir = repo.join(user_projects_subdir(user, "projects")).join("-proj");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join(format!("{id}.jsonl")), "{}\n").unwrap();
+        }
+
+        let foreign = foreign_session_ids(&repo, "projects", Some("alice"));
+        assert_eq!(foreign, HashSet::from(["s-bob".to_string()]));
+
+        let local = temp.path().join("local");
+        let bob = repo.join(user_projects_subdir("bob", "projects"));
+        assert_eq!(mirror_sessions(&bob, &local, None).unwrap(), 1);
+        assert!(local.join("-proj/s-bob.jsonl").exists());
+        // Unchanged files are not rewritten
+        assert_eq!(mirror_sessions(&bob, &local, None).unwrap(), 0);
+
+        assert!(mirror_sessions(&repo.join("users/carol/projects"), &local, None).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! Repairing sessions stored under nested paths in the sync repo
+//!
+//! Sessions belong at `<project>/<session>.jsonl` in the projects directory
+//! (subagent transcripts at `<project>/<session>/subagents/<agent>.jsonl`).
+//! Older versions fell back to the whole local path when they couldn't make
+//! a session's path relative, so some repos hold sessions under
+//! absolute-looking directories such as
+//! `home/me/.claude/projects/-home-me-app/<session>.jsonl`. `doctor` reports
+//! them, and `doctor --fix-paths` moves each one to its canonical path,
+//! merging it into the session already there.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::lock::SyncLock;
+use crate::parser::{ConversationSession, SessionIdSource};
+use crate::parts::{part_path, split_part};
+use crate::scm;
+
+use super::rel_path::RepoRelPath;
+use super::relocation::merge_copies;
+use super::state::SyncState;
+
+/// A session file stored under a nested path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NestedSession {
+    /// Where the session is now
+    pub path: RepoRelPath,
+    /// Where it belongs
+    pub canonical: RepoRelPath,
+}
+
+/// Session files under `projects_dir` stored under nested paths
+///
+/// Continuation files aren't listed; they move with their base file.
+pub fn find_nested_sessions(projects_dir: &Path) -> Vec<NestedSession> {
+    WalkDir::new(projects_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && split_part(e.path()).is_none())
+        .filter_map(|entry| {
+            let path = RepoRelPath::new(projects_dir, entry.path()).ok()?;
+            let canonical = canonical_path(path.as_path())?;
+            Some(NestedSession {
+                canonical: RepoRelPath::from_relative(canonical).ok()?,
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Where a session file at `relative` belongs, or `None` if it's already
+/// there or doesn't look like a nested session
+fn canonical_path(relative: &Path) -> Option<PathBuf> {
+    if relative.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+        return None;
+    }
+    let components: Vec<&OsStr> = relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect();
+    let count = components.len();
+    let is_subagent = count >= 4 && components[count - 2] == "subagents";
+    let depth = if is_subagent { 4 } else { 2 };
+    if count <= depth {
+        return None;
+    }
+    let canonical = &components[count - depth..];
+    if !is_project_dir(canonical[0]) {
+        return None;
+    }
+    Some(canonical.iter().collect())
+}
+
+/// Whether `name` looks like an encoded project path (see
+/// [`crate::paths::encode_project_path`])
+fn is_project_dir(name: &OsStr) -> bool {
+    name.to_str().is_some_and(|name| {
+        name.contains('-') && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// Move every nested session in the sync repo to its canonical path and
+/// commit the result, returning how many were moved
+pub fn fix_nested_paths(verbosity: crate::VerbosityLevel) -> Result<usize> {
+    use crate::VerbosityLevel;
+
+    let _lock = SyncLock::acquire()?;
+    let state = SyncState::load()?;
+    let filter = FilterConfig::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let projects_dir = state.sync_repo_path.join(filter.projects_subdir());
+
+    let nested = find_nested_sessions(&projects_dir);
+    if nested.is_empty() {
+        if verbosity >= VerbosityLevel::Normal {
+            println!("  {} No sessions stored under nested paths", glyphs::ok().green());
+        }
+        return Ok(0);
+    }
+
+    let id_source = filter.session_id_source()?;
+    let strategies = filter.content_key_strategies()?;
+    for session in &nested {
+        let from = session.path.under(&projects_dir);
+        let to = session.canonical.under(&projects_dir);
+        let merged = to.exists();
+        if merged {
+            let existing = read_whole(&to, id_source)?;
+            let stray = read_whole(&from, id_source)?;
+            merge_copies(&[&existing, &stray], 0, &to, &strategies)
+                .write_to_file_with(&to, filter.preserve_raw_lines)?;
+            remove_with_parts(&from)?;
+        } else {
+            move_with_parts(&from, &to)?;
+        }
+        remove_empty_parents(&from, &projects_dir);
+        if verbosity >= VerbosityLevel::Normal {
+            println!(
+                "    {} {} {} {}{}",
+                glyphs::detail().dimmed(),
+                session.path,
+                glyphs::arrow().dimmed(),
+                session.canonical,
+                if merged { " (merged)" } else { "" }
+            );
+        }
+    }
+
+    super::split_oversized_sessions(&projects_dir, &filter, verbosity)?;
+    repo.stage_paths(&super::managed_paths(&filter))?;
+    if repo.has_staged_changes()? {
+        repo.commit(&format!(
+            "Move {} sessions stored under nested paths to their canonical paths",
+            nested.len()
+        ))?;
+    }
+    if verbosity >= VerbosityLevel::Normal {
+        println!(
+            "  {} Moved {} sessions to their canonical paths; the next push shares the fix",
+            glyphs::ok().green(),
+            nested.len()
+        );
+    }
+    Ok(nested.len())
+}
+
+/// The session in `base` with the entries of its continuation files
+fn read_whole(base: &Path, id_source: SessionIdSource) -> Result<ConversationSession> {
+    let mut session = ConversationSession::from_file_with(base, id_source)?;
+    let mut number = 1;
+    while part_path(base, number).exists() {
+        let part = ConversationSession::from_file_with(part_path(base, number), id_source)?;
+        session.entries.extend(part.entries);
+        number += 1;
+    }
+    Ok(session)
+}
+
+fn move_with_parts(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    let mut number = 0;
+    loop {
+        let (source, dest) = match number {
+            0 => (from.to_path_buf(), to.to_path_buf()),
+            n => (part_path(from, n), part_path(to, n)),
+        };
+        if number > 0 && !source.exists() {
+            return Ok(());
+        }
+        fs::rename(&source, &dest).with_context(|| {
+            format!("Failed to move {} to {}", source.display(), dest.display())
+        })?;
+        number += 1;
+    }
+}
+
+fn remove_with_parts(base: &Path) -> Result<()> {
+    fs::remove_file(base).with_context(|| format!("Failed to remove {}", base.display()))?;
+    let mut number = 1;
+    while part_path(base, number).exists() {
+        fs::remove_file(part_path(base, number))?;
+        number += 1;
+    }
+    Ok(())
+}
+
+/// Remove the directories left empty above `path`, up to `root`
+fn remove_empty_parents(path: &Path, root: &Path) {
+    for dir in path.ancestors().skip(1) {
+        if dir == root || !dir.starts_with(root) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_path() {
+        let canonical = |path: &str| canonical_path(Path::new(path));
+        assert_eq!(
+            canonical("home/me/.claude/projects/-home-me-app/s1.jsonl"),
+            Some(PathBuf::from("-home-me-app/s1.jsonl"))
+        );
+        assert_eq!(
+            canonical("Users/me/.claude/projects/-Users-me-app/s1/subagents/agent-1.jsonl"),
+            Some(PathBuf::from("-Users-me-app/s1/subagents/agent-1.jsonl"))
+        );
+        for path in [
+            "-home-me-app/s1.jsonl",
+            "-home-me-app/s1/subagents/agent-1.jsonl",
+            "s1.jsonl",
+            "home/me/notes/s1.jsonl",
+            "home/me/.claude/projects/-home-me-app/s1.txt",
+        ] {
+            assert_eq!(canonical(path), None, "{path}");
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! Operation-log sync mode
+//!
+//! Instead of storing session files in the sync repo and merging them, each
+//! machine appends one record per conversation entry to its own log file,
+//! `oplog/<machine-id>.jsonl`. No two machines ever write the same file, so
+//! git merges of the sync repo cannot conflict. Local session files are
+//! materialized from the union of all logs, appending only entries that are
+//! missing locally (the same append-only rule as the file-based pull).
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::ConfigManager;
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::history::{OperationRecord, OperationType};
+use crate::parser::{
+    append_entries_to_file_with, make_content_key, ConversationEntry, ConversationSession,
+};
+use crate::scm::Scm;
+
+use super::discovery::discover_sessions;
+use super::rel_path::ClaudeRelPath;
+use super::state::SyncState;
+
+/// Directory (relative to the sync repo root) holding per-machine logs
+pub const OPLOG_DIR: &str = "oplog";
+
+/// One logged conversation entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpRecord {
+    /// Session file path relative to the projects directory (always `/`-separated)
+    pub path: String,
+    /// The entry as a JSONL line
+    pub line: String,
+}
+
+/// Counts from materializing the logs into local session files
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MaterializeStats {
+    pub sessions_added: usize,
+    pub sessions_appended: usize,
+    pub entries_appended: usize,
+}
+
+/// Identity of an entry within a session (UUID, or content key without one)
+fn entry_key(entry: &ConversationEntry) -> String {
+    entry
+        .uuid
+        .clone()
+        .unwrap_or_else(|| make_content_key(entry))
+}
+
+/// Read every record from every machine log, in file name then line order
+pub fn read_all_records(oplog_dir: &Path) -> Result<Vec<OpRecord>> {
+    let Ok(dir) = fs::read_dir(oplog_dir) else {
+        return Ok(Vec::new());
+    };
+    let mut logs: Vec<_> = dir
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    logs.sort();
+
+    let mut records = Vec::new();
+    for log in logs {
+        let file = fs::File::open(&log)
+            .with_context(|| format!("Failed to open op-log: {}", log.display()))?;
+        for (line_num, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<OpRecord>(&line) {
+                Ok(record) => records.push(record),
+                // A torn write at the end of a log must not block the sync
+                Err(e) => log::warn!(
+                    "Skipping malformed op-log record {}:{}: {}",
+                    log.display(),
+                    line_num + 1,
+                    e
+                ),
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Append records for local entries that no machine has logged yet
+///
+/// Returns the number of records appended to this machine's log.
+pub fn export_local(
+    sessions: &[ConversationSession],
+    claude_dir: &Path,
+    oplog_dir: &Path,
+    machine_id: &str,
+    preserve_raw: bool,
+) -> Result<usize> {
+    let mut logged: HashSet<(String, String)> = HashSet::new();
+    for record in read_all_records(oplog_dir)? {
+        if let Ok(entry) = serde_json::from_str::<ConversationEntry>(&record.line) {
+            logged.insert((record.path, entry_key(&entry)));
+        }
+    }
+
+    let mut lines = Vec::new();
+    for session in sessions {
+        let path = ClaudeRelPath::new(claude_dir, Path::new(&session.file_path))?.to_slash_string();
+
+        for entry in &session.entries {
+            if !logged.insert((path.clone(), entry_key(entry))) {
+                continue;
+            }
+            let record = OpRecord {
+                path: path.clone(),
+                line: entry.to_json_line(preserve_raw)?,
+            };
+            lines.push(serde_json::to_string(&record).context("Failed to serialize op-log record")?);
+        }
+    }
+
+    if lines.is_empty() {
+        return Ok(0);
+    }
+
+    fs::create_dir_all(oplog_dir)
+        .with_context(|| format!("Failed to create directory: {}", oplog_dir.display()))?;
+    let log_path = oplog_dir.join(format!("{machine_id}.jsonl"));
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .with_context(|| format!("Failed to open op-log: {}", log_path.display()))?;
+    for line in &lines {
+        writeln!(file, "{line}")
+            .with_context(|| format!("Failed to append to op-log: {}", log_path.display()))?;
+    }
+    file.sync_all()?;
+
+    Ok(lines.len())
+}
+
+/// Materialize the union of all logs into the local projects directory
+///
+/// Entries are ordered by timestamp; entries without one keep the position
+/// of the entry logged before them.
+pub fn materialize(
+    oplog_dir: &Path,
+    claude_dir: &Path,
+    preserve_raw: bool,
+) -> Result<MaterializeStats> {
+    // path -> (effective timestamp, entry), deduplicated by entry key
+    let mut by_path: BTreeMap<String, Vec<(String, ConversationEntry)>> = BTreeMap::new();
+    let mut seen: HashSet<(String, String)> = HashSet::new();
+    let mut last_timestamp: HashMap<String, String> = HashMap::new();
+
+    for record in read_all_records(oplog_dir)? {
+        // Never let a log write outside the projects directory
+        if ClaudeRelPath::from_relative(&record.path).is_err() {
+            log::warn!("Skipping op-log record with invalid path: {}", record.path);
+            continue;
+        }
+        let Ok(mut entry) = serde_json::from_str::<ConversationEntry>(&record.line) else {
+            continue;
+        };
+        let effective = match &entry.timestamp {
+            Some(ts) => {
+                last_timestamp.insert(record.path.clone(), ts.clone());
+                ts.clone()
+            }
+            None => last_timestamp.get(&record.path).cloned().unwrap_or_default(),
+        };
+        if !seen.insert((record.path.clone(), entry_key(&entry))) {
+            continue;
+        }
+        entry.raw_line = Some(record.line);
+        by_path.entry(record.path).or_default().push((effective, entry));
+    }
+
+    let mut stats = MaterializeStats::default();
+    for (path, mut entries) in by_path {
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        let local_path = claude_dir.join(&path);
+        let exists = local_path.exists();
+
+        let local_keys: HashSet<String> = if exists {
+            ConversationSession::from_file(&local_path)?
+                .entries
+                .iter()
+                .map(entry_key)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        let missing: Vec<ConversationEntry> = entries
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .filter(|entry| !local_keys.contains(&entry_key(entry)))
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+
+        if !exists {
+            stats.sessions_added += 1;
+        } else {
+            stats.sessions_appended += 1;
+        }
+        stats.entries_appended += missing.len();
+        append_entries_to_file_with(&local_path, &missing, preserve_raw)?;
+    }
+
+    Ok(stats)
+}
+
+/// Pull in op-log mode
+///
+/// 1. Append local entries not yet logged to this machine's log and commit
+/// 2. Pull from remote (logs are per-machine, so this merges cleanly)
+/// 3. Materialize the union of logs into .claude (append-only)
+pub(crate) fn pull_oplog(
+    state: &SyncState,
+    repo: &dyn Scm,
+    filter: &FilterConfig,
+    claude_dir: &Path,
+    fetch_remote: bool,
+    branch: Option<&str>,
+    verbosity: crate::VerbosityLevel,
+) -> Result<Option<OperationRecord>> {
+    use crate::VerbosityLevel;
+    use colored::Colorize;
+
+    let machine_id = ConfigManager::machine_id()?;
+    let oplog_dir = state.sync_repo_path.join(OPLOG_DIR);
+    let main_branch = branch
+        .map(|s| s.to_string())
+        .or_else(|| repo.current_branch().ok())
+        .unwrap_or_else(|| "main".to_string());
+
+    // Log local entries (scrubbed of PII, like session files)
+    let scrubber = filter.scrubber()?;
+    let mut local_sessions = discover_sessions(claude_dir, filter)?;
+    local_sessions.retain(|session| !filter.is_local_only(Path::new(&session.file_path)));
+    if !scrubber.is_empty() {
+        for session in &mut local_sessions {
+            *session = scrubber.scrub_session(session).0;
+        }
+    }
+    let logged = export_local(
+        &local_sessions,
+        claude_dir,
+        &oplog_dir,
+        &machine_id,
+        filter.preserve_raw_lines,
+    )?;
+    repo.stage_paths(&[PathBuf::from(OPLOG_DIR)])?;
+    if repo.has_staged_changes()? {
+        repo.commit(&format!(
+            "Append {} entries to op-log for {} ({})",
+            logged,
+            machine_id,
+            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+        ))?;
+    }
+    if verbosity >= VerbosityLevel::Normal {
+        println!("  {} Logged {} new local entries", glyphs::ok().green(), logged);
+    }
+
+    // Pull other machines' logs
+    let mut network_failures = Vec::new();
+    if fetch_remote && state.has_remote {
+        match repo.pull("origin", &main_branch) {
+            Ok(_) => {
+                if verbosity >= VerbosityLevel::Normal {
+                    println!("  {} Pulled origin/{}", glyphs::ok().green(), main_branch);
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to pull: {}", e);
+                network_failures.push(format!("pull from origin/{main_branch}: {e}"));
+                if verbosity >= VerbosityLevel::Normal {
+                    println!(
+                        "  {} Failed to pull from origin/{}: {}",
+                        "!".yellow().bold(),
+                        main_branch,
+                        e
+                    );
+                    println!(
+                        "  {} Continuing with local state (remote changes may not be included)",
+                        glyphs::info().cyan()
+                    );
+                }
+            }
+        }
+    }
+
+    // Materialize
+    let stats = materialize(&oplog_dir, claude_dir, filter.preserve_raw_lines)?;
+    if verbosity >= VerbosityLevel::Normal {
+        if stats.sessions_added > 0 || stats.sessions_appended > 0 {
+            println!(
+                "  {} Added {} new sessions, appended {} entries to {} sessions",
+                glyphs::ok().green(),
+                stats.sessions_added,
+                stats.entries_appended,
+                stats.sessions_appended
+            );
+        } else {
+            println!("  {} No changes needed in .claude", glyphs::ok().green());
+        }
+    }
+
+    if verbosity == VerbosityLevel::Summary {
+        println!(
+            "{}{}",
+            super::pull::pull_summary_line(stats.sessions_added, stats.sessions_appended, 0, 0),
+            super::pull::local_only_suffix(!network_failures.is_empty())
+        );
+    } else if verbosity >= VerbosityLevel::Normal {
+        super::pull::print_pull_complete(!network_failures.is_empty());
+    }
+
+    let mut record = OperationRecord::new(OperationType::Pull, Some(main_branch), Vec::new());
+    record.remote_unreachable = !network_failures.is_empty();
+    record.network_failures = network_failures;
+    Ok(Some(record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_session(dir: &Path, relative: &str, lines: &[&str]) {
+        let path = dir.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, lines.join("\n") + "\n").unwrap();
+    }
+
+    fn load(dir: &Path) -> Vec<ConversationSession> {
+        crate::sync::discover_sessions(dir, &crate::filter::FilterConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_two_machines_converge_without_conflicts() {
+        let temp = TempDir::new().unwrap();
+        let oplog = temp.path().join("repo/oplog");
+        let machine_a = temp.path().join("a");
+        let machine_b = temp.path().join("b");
+
+        let first = r#"{"type":"user","uuid":"1","sessionId":"s","timestamp":"2025-01-01T00:00:00Z"}"#;
+        let from_a = r#"{"type":"assistant","uuid":"2a","sessionId":"s","timestamp":"2025-01-01T00:01:00Z"}"#;
+        let from_b = r#"{"type":"assistant","uuid":"2b","sessionId":"s","timestamp":"2025-01-01T00:02:00Z"}"#;
+        write_session(&machine_a, "-proj/s.jsonl", &[first, from_a]);
+        write_session(&machine_b, "-proj/s.jsonl", &[first, from_b]);
+
+        assert_eq!(export_local(&load(&machine_a), &machine_a, &oplog, "a", true).unwrap(), 2);
+        // The shared first entry is already logged by machine A
+        assert_eq!(export_local(&load(&machine_b), &machine_b, &oplog, "b", true).unwrap(), 1);
+
+        let stats = materialize(&oplog, &machine_a, true).unwrap();
+        assert_eq!(stats.entries_appended, 1);
+        let stats = materialize(&oplog, &machine_b, true).unwrap();
+        assert_eq!(stats.entries_appended, 1);
+
+        let a = fs::read_to_string(machine_a.join("-proj/s.jsonl")).unwrap();
+        assert_eq!(a, [first, from_a, from_b].join("\n") + "\n");
+        // Materializing again is a no-op
+        assert_eq!(materialize(&oplog, &machine_a, true).unwrap(), MaterializeStats::default());
+    }
+
+    #[test]
+    fn test_materialize_creates_missing_sessions() {
+        let temp = TempDir::new().unwrap();
+        let oplog = temp.path().join("oplog");
+        let source = temp.path().join("src");
+        write_session(&source, "-proj/new.jsonl", &[r#"{"type":"user","uuid":"1"}"#]);
+        export_local(&load(&source), &source, &oplog, "m", false).unwrap();
+
+        let dest = temp.path().join("dest");
+        let stats = materialize(&oplog, &dest, false).unwrap();
+        assert_eq!(stats.sessions_added, 1);
+        assert!(dest.join("-proj/new.jsonl").exists());
+    }
+}
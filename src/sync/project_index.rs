@@ -0,0 +1,190 @@
+//! Per-project session summaries in the sync repo
+//!
+//! With `project_index` enabled, every push writes `INDEX.md` to each project
+//! directory of the sync repo: one row per session with its start date,
+//! message count, and first user message, linking to the session file.
+//! Browsing the repo on a git host then shows what each project's sessions
+//! were about without opening raw JSONL. The file only depends on the
+//! sessions, so pushes from different machines write the same index.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::filter::FilterConfig;
+use crate::parser::ConversationSession;
+use crate::paths::{decode_project_dir, project_dir_name};
+use crate::text::{short_id, truncate_to_width, Keep};
+
+/// File name of the summary in each project directory
+pub(crate) const PROJECT_INDEX_FILE: &str = "INDEX.md";
+
+/// Width of first-message previews
+const PREVIEW_WIDTH: usize = 100;
+
+/// Write `INDEX.md` to each project directory under `projects_dir` that
+/// holds sessions, and remove it from those that no longer do
+///
+/// Returns how many indexes were written or removed; unchanged ones aren't
+/// rewritten.
+pub(crate) fn write_project_indexes(projects_dir: &Path) -> Result<usize> {
+    if !projects_dir.exists() {
+        return Ok(0);
+    }
+    let sessions = super::discover_sessions(projects_dir, &FilterConfig::default())?;
+    let mut by_project: BTreeMap<String, Vec<&ConversationSession>> = BTreeMap::new();
+    for session in sessions.iter().filter(|s| !s.is_agent_file()) {
+        let project = Path::new(&session.file_path)
+            .strip_prefix(projects_dir)
+            .ok()
+            .and_then(project_dir_name);
+        if let Some(project) = project {
+            by_project.entry(project).or_default().push(session);
+        }
+    }
+
+    let mut changed = 0;
+    for entry in fs::read_dir(projects_dir)
+        .with_context(|| format!("Failed to read {}", projects_dir.display()))?
+    {
+        let dir = entry?.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let path = dir.join(PROJECT_INDEX_FILE);
+        let name = dir.file_name().unwrap_or_default().to_string_lossy();
+        match by_project.get(name.as_ref()) {
+            Some(sessions) => {
+                let content = render_index(&name, sessions, &dir);
+                if fs::read_to_string(&path).ok().as_deref() != Some(content.as_str()) {
+                    fs::write(&path, content)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    changed += 1;
+                }
+            }
+            None if path.exists() => {
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+                changed += 1;
+            }
+            None => {}
+        }
+    }
+    Ok(changed)
+}
+
+/// The `INDEX.md` of project directory `name`, most recent session first
+fn render_index(name: &str, sessions: &[&ConversationSession], project_dir: &Path) -> String {
+    let mut rows: Vec<(String, String)> = sessions
+        .iter()
+        .map(|session| {
+            let started = session
+                .entries
+                .iter()
+                .find_map(|e| e.timestamp.as_deref())
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&chrono::Utc));
+            let date = started.map_or_else(
+                || "-".to_string(),
+                |t| t.format("%Y-%m-%d %H:%M").to_string(),
+            );
+            let file = Path::new(&session.file_path)
+                .strip_prefix(project_dir)
+                .unwrap_or(Path::new(&session.file_path))
+                .to_string_lossy()
+                .replace('\\', "/");
+            let row = format!(
+                "| {} | [{}]({}) | {} | {} |",
+                date,
+                short_id(&session.session_id, 8),
+                file,
+                session.message_count(),
+                escape_cell(&first_user_message(session).unwrap_or_default())
+            );
+            (started.map(|t| t.to_rfc3339()).unwrap_or_default(), row)
+        })
+        .collect();
+    rows.sort_by(|a, b| b.cmp(a));
+
+    let mut content = format!(
+        "# {}\n\n{} sessions, most recent first (times in UTC). Written by claude-code-sync on push; edits are overwritten.\n\n",
+        decode_project_dir(name).display(),
+        sessions.len()
+    );
+    content.push_str("| Started | Session | Messages | First message |\n");
+    content.push_str("|---------|---------|----------|---------------|\n");
+    for (_, row) in rows {
+        content.push_str(&row);
+        content.push('\n');
+    }
+    content
+}
+
+/// Preview of the first user message with text (not a tool result)
+fn first_user_message(session: &ConversationSession) -> Option<String> {
+    session
+        .entries
+        .iter()
+        .filter(|e| e.entry_type == "user" && !e.is_sidechain())
+        .find_map(crate::timeline::message_preview)
+        .map(|text| truncate_to_width(&text, PREVIEW_WIDTH, Keep::Start))
+}
+
+/// `text` safe to put in a Markdown table cell
+fn escape_cell(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('<', "&lt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, lines: &[&str]) -> ConversationSession {
+        ConversationSession {
+            session_id: id.to_string(),
+            entries: lines
+                .iter()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect(),
+            file_path: format!("/repo/projects/-home-me-app/{id}.jsonl"),
+        }
+    }
+
+    #[test]
+    fn test_render_index() {
+        let older = session(
+            "11111111-aaaa",
+            &[
+                r#"{"type":"user","timestamp":"2025-01-01T10:00:00Z","message":{"content":[{"type":"tool_result","content":"x"}]}}"#,
+                r#"{"type":"user","timestamp":"2025-01-01T10:01:00Z","message":{"content":"fix a | b <now>"}}"#,
+                r#"{"type":"assistant","timestamp":"2025-01-01T10:02:00Z","message":{"content":"Done."}}"#,
+            ],
+        );
+        let newer = session(
+            "22222222-bbbb",
+            &[
+                r#"{"type":"user","timestamp":"2025-02-01T09:00:00Z","message":{"content":"hello"}}"#,
+            ],
+        );
+
+        let index = render_index(
+            "-home-me-app",
+            &[&older, &newer],
+            Path::new("/repo/projects/-home-me-app"),
+        );
+        let lines: Vec<&str> = index.lines().collect();
+        assert_eq!(lines[0], "# /home/me/app");
+        assert_eq!(
+            lines[4..],
+            [
+                "| Started | Session | Messages | First message |",
+                "|---------|---------|----------|---------------|",
+                "| 2025-02-01 09:00 | [22222222](22222222-bbbb.jsonl) | 1 | hello |",
+                "| 2025-01-01 10:00 | [11111111](11111111-aaaa.jsonl) | 3 | fix a \\| b &lt;now> |",
+            ]
+        );
+    }
+}
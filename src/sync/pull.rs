@@ -1,29 +1,48 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 use inquire::Confirm;
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-use crate::conflict::{analyze_session_relationship, ConflictDetector, SessionRelationship};
+use crate::compaction;
+use crate::conflict::{
+    analyze_session_relationship, reconcile_modified_entries, ConflictDetector,
+    ConflictResolution, SessionRelationship,
+};
+use crate::glyphs;
 use crate::lock::SyncLock;
-use crate::filter::FilterConfig;
+use crate::machines::MachineFilter;
+use crate::paths::{project_dir_name, project_display_name};
+use crate::merge::MergeStats;
+use crate::filter::{
+    ConflictPolicy, FilterConfig, LargeFileAction, ModifiedEntryPolicy, PullPrecedence, SyncMode,
+};
 use crate::history::{
-    ConversationSummary, OperationHistory, OperationRecord, OperationType, SyncOperation,
+    ConversationSummary, OperationRecord, OperationType, SyncOperation,
 };
 use crate::interactive_conflict;
-use crate::parser::{append_entries_to_file, make_content_key, ConversationSession};
+use crate::parser::{append_entries_to_file_with, content_keys, missing_entries, ConversationSession};
+use crate::i18n::{count, tr};
 use crate::report::{save_conflict_report, ConflictReport};
+use crate::resolutions::{Kept, ResolutionIndex};
 use crate::scm;
+use crate::tags::TagIndex;
+use crate::tombstones::{self, TombstoneIndex};
 
-use super::discovery::{claude_projects_dir, discover_sessions};
+use super::active_sessions::ActiveSessionGuard;
+use super::discovery::{
+    claude_projects_dir, discover_sessions, discover_sessions_with_skips, report_skipped,
+    warn_large_file,
+};
+use super::history_merge::MergePriority;
+use super::rel_path::{ClaudeRelPath, RepoRelPath};
+use super::relocation::{canonical_copy, consolidate_relocated, merge_copies};
 use super::state::SyncState;
-use super::MAX_CONVERSATIONS_TO_DISPLAY;
-
-/// Generate a unique temp branch name with timestamp
-fn generate_temp_branch_name() -> String {
-    let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
-    format!("sync-local-{}", timestamp)
-}
+use super::temp_branch::{cleanup_old_temp_branches, generate_temp_branch_name, TEMP_BRANCH_PREFIX};
+use super::timing::PhaseTimer;
+use crate::table::{Column, Keep, Table};
+use crate::text::truncate_to_width;
 
 /// Pull and merge history from sync repository
 ///
@@ -35,18 +54,68 @@ fn generate_temp_branch_name() -> String {
 /// 5. Merge temp branch into main (smart conflict resolution)
 /// 6. Copy merged result to .claude
 /// 7. Delete temp branch (local + remote)
+///
+/// Sessions from teammates' namespaces listed in `include_users` are copied
+/// into .claude read-only: they are never saved back to the sync repo.
+/// `skip_history` leaves history.jsonl alone on both sides. With `strict`,
+/// a pull that skipped sessions for being too large or unparsable, or hit
+/// network failures, fails once its record is saved. Returns
+/// [`super::SyncOutcome::LocalOnly`] if the remote couldn't be reached.
 pub fn pull_history(
     fetch_remote: bool,
     branch: Option<&str>,
     interactive: bool,
+    include_users: &[String],
+    skip_history: bool,
+    strict: bool,
     verbosity: crate::VerbosityLevel,
-) -> Result<()> {
-    use crate::VerbosityLevel;
-
+) -> Result<super::SyncOutcome> {
     // Acquire exclusive lock to prevent concurrent sync operations
     let _lock = SyncLock::acquire()?;
 
-    if verbosity != VerbosityLevel::Quiet {
+    let record = pull_locked(
+        fetch_remote,
+        branch,
+        interactive,
+        include_users,
+        skip_history,
+        "origin",
+        verbosity,
+    )?;
+    let outcome = super::SyncOutcome::of(record.as_ref());
+    if let Some(record) = record {
+        let failures = if strict {
+            record.strict_failures()
+        } else {
+            Vec::new()
+        };
+        super::save_operation_record(record);
+        super::fail_if_incomplete(&failures)?;
+    }
+    Ok(outcome)
+}
+
+/// Pull while the caller holds the sync lock
+///
+/// Remote changes are merged from `upstream`: `origin`, or another remote
+/// such as a bundle being imported. The temp branch safety net always goes
+/// to origin.
+///
+/// Returns the operation record for the caller to save, or `None` if the
+/// pull was cancelled. A pull that fails verification saves its own record
+/// before returning the error.
+pub(crate) fn pull_locked(
+    fetch_remote: bool,
+    branch: Option<&str>,
+    interactive: bool,
+    include_users: &[String],
+    skip_history: bool,
+    upstream: &str,
+    verbosity: crate::VerbosityLevel,
+) -> Result<Option<OperationRecord>> {
+    use crate::VerbosityLevel;
+
+    if verbosity >= VerbosityLevel::Normal {
         println!("{}", "Pulling Claude Code history...".cyan().bold());
     }
 
@@ -54,27 +123,105 @@ pub fn pull_history(
     let repo = scm::open(&state.sync_repo_path)?;
     let filter = FilterConfig::load()?;
     let claude_dir = claude_projects_dir()?;
+    if filter.sync_mode()? == SyncMode::OpLog {
+        return super::oplog::pull_oplog(
+            &state,
+            repo.as_ref(),
+            &filter,
+            &claude_dir,
+            fetch_remote,
+            branch,
+            verbosity,
+        );
+    }
+    let tag_index = TagIndex::load(&state.sync_repo_path)?;
+    let passes_tags = |session_id: &str| {
+        tag_index.passes(session_id, &filter.include_tags, &filter.exclude_tags)
+    };
+    for user in include_users {
+        super::namespaces::validate_user_name(user)?;
+    }
+    // Sessions owned by other users are read-only and never saved to our namespace
+    let foreign_sessions = super::namespaces::foreign_session_ids(
+        &state.sync_repo_path,
+        &filter.sync_subdirectory,
+        filter.user_namespace.as_deref(),
+    );
+
+    // Local files overwritten by this pull are copied here first
+    let backup_dir = crate::backups::new_run_dir()?;
 
     // Clean up old temp branches that have exceeded retention period
+    let machine_id = crate::config::ConfigManager::machine_id()?;
     cleanup_old_temp_branches(
         repo.as_ref(),
+        &machine_id,
         fetch_remote && state.has_remote,
         filter.temp_branch_retention_hours,
         verbosity,
     )?;
 
+    // A pull that died part-way leaves the repo on its temp branch
+    return_from_temp_branch(repo.as_ref(), &filter, verbosity)?;
+
+    // The temp branch commit below stages the whole working tree
+    super::worktree::confirm_unexpected_changes(repo.as_ref(), &filter, verbosity)?;
+
     // Get the main branch name
     let main_branch = branch
         .map(|s| s.to_string())
         .or_else(|| repo.current_branch().ok())
         .unwrap_or_else(|| "main".to_string());
 
+    // A purge elsewhere rewrote the remote's history; merging with it would
+    // bring the purged commits back
+    if fetch_remote && state.has_remote {
+        super::purge::recover_from_purge(
+            repo.as_ref(),
+            &state.sync_repo_path,
+            &main_branch,
+            verbosity,
+        )?;
+    }
+
+    // Sessions Claude Code is writing to are only appended to below; found
+    // before this pull writes to any
+    let active_sessions = ActiveSessionGuard::detect(&claude_dir, &filter)?;
+
+    // A plain-directory remote is folded into .claude before local sessions
+    // are saved, so the steps below commit it like local work
+    if fetch_remote && upstream == "origin" {
+        if let Some(url) = &state.rsync_remote {
+            super::rsync::merge_remote_sessions(url, &filter, &claude_dir, &backup_dir, verbosity)?;
+        }
+    }
+
+    // Conflict copies a folder-sync tool left next to sessions, in .claude
+    // or in the sync repo, count as more remote versions of those sessions
+    super::conflict_copies::fold_conflict_copies(
+        &claude_dir,
+        &claude_dir,
+        &filter,
+        &backup_dir,
+        &active_sessions,
+        verbosity,
+    )?;
+    super::conflict_copies::fold_conflict_copies(
+        &state.sync_repo_path.join(filter.projects_subdir()),
+        &claude_dir,
+        &filter,
+        &backup_dir,
+        &active_sessions,
+        verbosity,
+    )?;
+
     // ============================================================================
     // STEP 1: Create temp branch and save local state
     // ============================================================================
-    let temp_branch = generate_temp_branch_name();
+    let mut timer = PhaseTimer::new();
+    let temp_branch = generate_temp_branch_name(&machine_id);
 
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!("  {} temp branch '{}'...", "Creating".cyan(), temp_branch);
     }
 
@@ -83,107 +230,322 @@ pub fn pull_history(
         .context("Failed to create temp branch")?;
     repo.checkout(&temp_branch)
         .context("Failed to checkout temp branch")?;
+    timer.mark("temp branch")?;
 
     // ============================================================================
     // STEP 2: Copy local .claude sessions to sync repo on temp branch
     // ============================================================================
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!("  {} local sessions to temp branch...", "Saving".cyan());
     }
 
-    let local_sessions = discover_sessions(&claude_dir, &filter)?;
-    let projects_dir = state.sync_repo_path.join(&filter.sync_subdirectory);
+    let mut skipped_sessions = Vec::new();
+    let mut network_failures = Vec::new();
+    // Sessions the pull leaves on one side on purpose (see STEP 6d)
+    let mut left_out: HashSet<String> = foreign_sessions.clone();
+    let discovery_started = std::time::SystemTime::now();
+    let local_sessions = discover_sessions_with_skips(&claude_dir, &filter, &mut skipped_sessions)?;
+    // Session files deleted from .claude since the last pull
+    let locally_deleted: Vec<String> = if filter.detect_deletions {
+        let present = tombstones::session_file_ids(&claude_dir);
+        tombstones::load_seen_sessions()?
+            .map(|seen| seen.difference(&present).cloned().collect())
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    timer.mark("discovery (local)")?;
+    let projects_dir = state.sync_repo_path.join(filter.projects_subdir());
     std::fs::create_dir_all(&projects_dir)?;
 
+    let sidechain_mode = filter.sidechain_mode()?;
     let mut local_session_count = 0;
+    let mut sidechain_skipped = 0;
+    let mut local_only_skipped = 0;
+    let mut compacted_count = 0;
+    let mut compacted_bytes = 0;
+    let mut corpus_bytes = 0;
+    let mut large_skipped = 0;
+    let mut lfs_patterns = Vec::new();
+    let scrubber = filter.scrubber()?;
+    let mut scrubbed_sessions = Vec::new();
     for session in &local_sessions {
-        let relative_path = Path::new(&session.file_path)
-            .strip_prefix(&claude_dir)
-            .unwrap_or(Path::new(&session.file_path));
-        let dest_path = projects_dir.join(relative_path);
-        session.write_to_file(&dest_path)?;
+        if !passes_tags(&session.session_id) || foreign_sessions.contains(&session.session_id) {
+            left_out.insert(session.session_id.clone());
+            left_out.extend(file_stem(&session.file_path));
+            continue;
+        }
+        // Local-only sessions never reach the sync repo, whatever else matches
+        if filter.is_local_only(Path::new(&session.file_path)) {
+            local_only_skipped += 1;
+            continue;
+        }
+        let Some(session) = sidechain_mode.apply(session) else {
+            left_out.extend(file_stem(&session.file_path));
+            sidechain_skipped += 1;
+            continue;
+        };
+        let relative_path = ClaudeRelPath::new(&claude_dir, Path::new(&session.file_path))?;
+        let dest_path = relative_path.to_repo().under(&projects_dir);
+
+        let size = std::fs::metadata(&session.file_path).map_or(0, |m| m.len());
+        corpus_bytes += size;
+        let large_file_action = filter.large_file_action(size)?;
+        match large_file_action {
+            Some(LargeFileAction::Skip) => {
+                log::info!("Skipping large session file {} ({} bytes)", session.file_path, size);
+                left_out.extend(file_stem(&session.file_path));
+                skipped_sessions.push(crate::history::SkippedSession {
+                    path: session.file_path.clone(),
+                    reason: format!("large file ({size} bytes) skipped by large_file_bands"),
+                    kind: crate::history::SkipKind::TooLarge,
+                });
+                large_skipped += 1;
+                continue;
+            }
+            Some(LargeFileAction::Warn) if verbosity >= VerbosityLevel::Normal => {
+                warn_large_file(Path::new(&session.file_path), size);
+            }
+            Some(LargeFileAction::Lfs) => {
+                // .gitattributes patterns are relative to the repo root
+                let repo_relative: Vec<_> = filter
+                    .projects_subdir()
+                    .join(relative_path.as_path())
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                lfs_patterns.push(format!("/{}", repo_relative.join("/")));
+            }
+            _ => {}
+        }
+
+        // Compact large payloads in old (or large) sessions (sync repo copy only)
+        let compact = large_file_action == Some(LargeFileAction::Compress)
+            || filter
+                .compact_older_than_days
+                .is_some_and(|days| compaction::is_older_than(&session, days));
+        let session = if compact {
+            let (compacted, stats) = compaction::compact_session(
+                &session,
+                filter.compact_min_payload_bytes as usize,
+            );
+            if stats.entries_compacted == 0 {
+                session
+            } else {
+                if filter.compaction_archive {
+                    compaction::archive_original(Path::new(&session.file_path), relative_path.as_path())?;
+                }
+                compacted_count += 1;
+                compacted_bytes += stats.bytes_saved;
+                Cow::Owned(compacted)
+            }
+        } else {
+            session
+        };
+
+        // Scrub PII (sync repo copy only)
+        let session = if scrubber.is_empty() {
+            session
+        } else {
+            let (scrubbed, counts) = scrubber.scrub_session(&session);
+            if counts.is_empty() {
+                session
+            } else {
+                scrubbed_sessions.push((session.session_id.clone(), counts));
+                Cow::Owned(scrubbed)
+            }
+        };
+
+        session.write_to_file_with(&dest_path, filter.preserve_raw_lines)?;
         local_session_count += 1;
     }
 
+    if !scrubbed_sessions.is_empty() && verbosity >= VerbosityLevel::Normal {
+        let mut totals = crate::scrubbing::ScrubCounts::new();
+        for (_, counts) in &scrubbed_sessions {
+            for (rule, count) in counts {
+                *totals.entry(rule.clone()).or_default() += count;
+            }
+        }
+        println!(
+            "  {} Scrubbed {} sessions ({})",
+            glyphs::ok().green(),
+            scrubbed_sessions.len(),
+            crate::scrubbing::describe_counts(&totals)
+        );
+        if verbosity == VerbosityLevel::Verbose {
+            for (session_id, counts) in &scrubbed_sessions {
+                println!(
+                    "    {} {}: {}",
+                    glyphs::detail().dimmed(),
+                    session_id,
+                    crate::scrubbing::describe_counts(counts)
+                );
+            }
+        }
+    }
+
+    if compacted_count > 0 && verbosity >= VerbosityLevel::Normal {
+        println!(
+            "  {} Compacted {} sessions ({:.1} MB saved)",
+            glyphs::ok().green(),
+            compacted_count,
+            compacted_bytes as f64 / (1024.0 * 1024.0)
+        );
+    }
+
+    if !lfs_patterns.is_empty() {
+        match crate::scm::lfs::setup(&state.sync_repo_path, &lfs_patterns) {
+            Ok(()) => log::info!("Tracking {} large session files with Git LFS", lfs_patterns.len()),
+            Err(e) => {
+                log::warn!("Failed to track large session files with Git LFS: {}", e);
+                if verbosity >= VerbosityLevel::Normal {
+                    println!(
+                        "  {} Could not track {} large files with Git LFS: {}",
+                        "!".yellow().bold(),
+                        lfs_patterns.len(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    if verbosity >= VerbosityLevel::Normal {
+        println!(
+            "  {} Local corpus: {} sessions, {:.1} MB{}",
+            glyphs::ok().green(),
+            local_session_count,
+            corpus_bytes as f64 / (1024.0 * 1024.0),
+            if large_skipped > 0 {
+                format!(" ({large_skipped} large files skipped)")
+            } else {
+                String::new()
+            }
+        );
+    }
+
+    if sidechain_skipped > 0 {
+        log::info!("Skipped {} subagent sessions ({:?})", sidechain_skipped, sidechain_mode);
+        if verbosity == VerbosityLevel::Verbose {
+            println!(
+                "    {} skipped {} subagent sessions",
+                glyphs::detail().dimmed(),
+                sidechain_skipped
+            );
+        }
+    }
+
+    if local_only_skipped > 0 && verbosity == VerbosityLevel::Verbose {
+        println!(
+            "    {} kept {} local-only sessions out of the sync repo",
+            glyphs::detail().dimmed(),
+            local_only_skipped
+        );
+    }
+
     // Also copy history.jsonl to sync repo (session index for --resume picker)
     let claude_base_dir = claude_dir.parent().unwrap_or(&claude_dir);
     let local_history = claude_base_dir.join("history.jsonl");
     let sync_history = state.sync_repo_path.join("history.jsonl");
-    if local_history.exists() {
-        // Merge local history into sync repo history (preserving remote entries)
-        let (total, added) = super::history_merge::merge_history_files(
-            &local_history,
-            &sync_history,
-            super::history_merge::MergePriority::TargetFirst,
-        )?;
-        log::debug!("Saved history.jsonl to sync repo: {} total, {} added", total, added);
+    if local_history.exists() && !skip_history {
+        // Merge local history into sync repo history; the pull precedence
+        // decides whose version of an entry both sides hold is kept
+        let priority = MergePriority::from_precedence(filter.pull_precedence()?, true);
+        let merged =
+            super::history_merge::merge_history_files(&local_history, &sync_history, priority)?;
+        log::debug!(
+            "Saved history.jsonl to sync repo: {} total, {} added",
+            merged.total,
+            merged.added
+        );
     }
 
     // Commit local state to temp branch
-    repo.stage_all()?;
-    if repo.has_changes()? {
+    super::split_oversized_sessions(&projects_dir, &filter, verbosity)?;
+    repo.stage_paths(&super::managed_paths(&filter))?;
+    if repo.has_staged_changes()? {
         let commit_msg = format!(
             "Save local state before pull ({})",
             chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
         );
         repo.commit(&commit_msg)?;
 
-        if verbosity != VerbosityLevel::Quiet {
+        if verbosity >= VerbosityLevel::Normal {
             println!(
                 "  {} Saved {} local sessions to temp branch",
-                "✓".green(),
+                glyphs::ok().green(),
                 local_session_count
             );
         }
-    } else if verbosity != VerbosityLevel::Quiet {
-        println!("  {} No local changes to save", "✓".green());
+    } else if verbosity >= VerbosityLevel::Normal {
+        println!("  {} No local changes to save", glyphs::ok().green());
     }
+    timer.mark("save local")?;
 
     // ============================================================================
     // STEP 3: Push temp branch to remote (SAFETY NET - never lose work)
     // ============================================================================
-    if fetch_remote && state.has_remote {
-        if verbosity != VerbosityLevel::Quiet {
-            println!("  {} temp branch to remote...", "Pushing".cyan());
-        }
+    // The temp branch is checked out, so its sessions are read from the
+    // working tree while the push runs rather than after checking it out
+    // again once the remote is fetched
+    let mut temp_branch_sessions = std::thread::scope(|scope| {
+        let discovery = scope.spawn(|| discover_sessions(&projects_dir, &filter));
+
+        if fetch_remote && state.has_remote {
+            if verbosity >= VerbosityLevel::Normal {
+                println!("  {} temp branch to remote...", "Pushing".cyan());
+            }
 
-        match repo.push("origin", &temp_branch) {
-            Ok(_) => {
-                if verbosity != VerbosityLevel::Quiet {
-                    println!("  {} Pushed temp branch to origin/{}", "✓".green(), temp_branch);
+            match crate::audit::push_audited(repo.as_ref(), "origin", &temp_branch, &main_branch) {
+                Ok(_) => {
+                    if verbosity >= VerbosityLevel::Normal {
+                        println!("  {} Pushed temp branch to origin/{}", glyphs::ok().green(), temp_branch);
+                    }
                 }
-            }
-            Err(e) => {
-                log::warn!("Failed to push temp branch: {}", e);
-                log::info!("Continuing - local temp branch still preserves your work");
-                if verbosity != VerbosityLevel::Quiet {
-                    println!(
-                        "  {} Could not push temp branch: {}",
-                        "!".yellow().bold(),
-                        e
-                    );
-                    println!(
-                        "  {} Local temp branch {} still preserves your work",
-                        "ℹ".cyan(),
-                        temp_branch
-                    );
+                Err(e) => {
+                    log::warn!("Failed to push temp branch: {}", e);
+                    network_failures.push(format!("push of temp branch {temp_branch}: {e}"));
+                    log::info!("Continuing - local temp branch still preserves your work");
+                    if verbosity >= VerbosityLevel::Normal {
+                        println!(
+                            "  {} Could not push temp branch: {}",
+                            "!".yellow().bold(),
+                            e
+                        );
+                        println!(
+                            "  {} Local temp branch {} still preserves your work",
+                            glyphs::info().cyan(),
+                            temp_branch
+                        );
+                    }
                 }
             }
         }
-    }
+
+        discovery
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })?;
 
     // ============================================================================
     // STEP 4: Checkout main and pull from remote
     // ============================================================================
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!("  {} to main branch...", "Switching".cyan());
     }
 
     repo.checkout(&main_branch)
         .context("Failed to checkout main branch")?;
 
-    if fetch_remote && state.has_remote {
-        if verbosity != VerbosityLevel::Quiet {
+    let has_upstream = if upstream == "origin" {
+        state.has_remote
+    } else {
+        repo.has_remote(upstream)
+    };
+    let mut remote_unreachable = false;
+    if fetch_remote && has_upstream {
+        if verbosity >= VerbosityLevel::Normal {
             println!("  {} from remote...", "Pulling".cyan());
         }
 
@@ -191,40 +553,49 @@ pub fn pull_history(
         let mut pull_failed = false;
 
         // First fetch to see what's on remote
-        match repo.fetch("origin") {
+        match repo.fetch(upstream) {
             Ok(_) => {
-                if verbosity != VerbosityLevel::Quiet {
-                    println!("  {} Fetched from origin", "✓".green());
+                if verbosity >= VerbosityLevel::Normal {
+                    println!("  {} Fetched from {}", glyphs::ok().green(), upstream);
                 }
             }
             Err(e) => {
                 log::warn!("Failed to fetch: {}", e);
+                network_failures.push(format!("fetch from {upstream}: {e}"));
                 fetch_failed = true;
-                if verbosity != VerbosityLevel::Quiet {
+                if verbosity >= VerbosityLevel::Normal {
                     println!(
-                        "  {} Failed to fetch from origin: {}",
+                        "  {} Failed to fetch from {}: {}",
                         "!".yellow().bold(),
+                        upstream,
                         e
                     );
                 }
             }
         }
 
+        // Check what was fetched before any of it is merged
+        if filter.checksum_manifest && !fetch_failed {
+            verify_fetched_manifest(repo.as_ref(), upstream, &main_branch, verbosity)?;
+        }
+
         // Now pull (which will fast-forward if possible)
-        match repo.pull("origin", &main_branch) {
+        match repo.pull(upstream, &main_branch) {
             Ok(_) => {
-                if verbosity != VerbosityLevel::Quiet {
-                    println!("  {} Pulled origin/{}", "✓".green(), main_branch);
+                if verbosity >= VerbosityLevel::Normal {
+                    println!("  {} Pulled {}/{}", glyphs::ok().green(), upstream, main_branch);
                 }
             }
             Err(e) => {
                 log::warn!("Failed to pull: {}", e);
                 log::info!("Continuing with local state...");
+                network_failures.push(format!("pull from {upstream}/{main_branch}: {e}"));
                 pull_failed = true;
-                if verbosity != VerbosityLevel::Quiet {
+                if verbosity >= VerbosityLevel::Normal {
                     println!(
-                        "  {} Failed to pull from origin/{}: {}",
+                        "  {} Failed to pull from {}/{}: {}",
                         "!".yellow().bold(),
+                        upstream,
                         main_branch,
                         e
                     );
@@ -233,33 +604,42 @@ pub fn pull_history(
         }
 
         // Inform user if network operations failed
-        if (fetch_failed || pull_failed) && verbosity != VerbosityLevel::Quiet {
+        if (fetch_failed || pull_failed) && verbosity >= VerbosityLevel::Normal {
             println!(
                 "  {} Continuing with local state (remote changes may not be included)",
-                "ℹ".cyan()
+                glyphs::info().cyan()
             );
         }
+        remote_unreachable = fetch_failed || pull_failed;
     }
 
+    timer.mark("fetch remote")?;
+
     // ============================================================================
     // STEP 5: Merge temp branch into main (smart merge)
     // ============================================================================
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!("  {} temp branch into main...", "Merging".cyan());
     }
 
-    // Discover sessions from both branches
-    // - main branch now has remote changes
-    // - temp branch has our local changes
-    let remote_sessions = discover_sessions(&projects_dir, &filter)?;
-
-    // We need to get the local sessions from the temp branch
-    // Switch to temp branch, read sessions, switch back
-    repo.checkout(&temp_branch)?;
-    let temp_branch_sessions = discover_sessions(&projects_dir, &filter)?;
-    repo.checkout(&main_branch)?;
+    // Main now has the remote changes; the temp branch's sessions (our
+    // local changes) were read in STEP 3
+    let mut remote_sessions =
+        discover_sessions_with_skips(&projects_dir, &filter, &mut skipped_sessions)?;
+
+    // Sessions deleted on any machine stay deleted
+    let trash_after_merge = apply_tombstones(
+        &state.sync_repo_path,
+        &machine_id,
+        &locally_deleted,
+        &mut remote_sessions,
+        &mut temp_branch_sessions,
+        &local_sessions,
+        verbosity,
+    )?;
+    timer.mark("discovery (remote)")?;
 
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!(
             "  {} {} sessions from remote, {} from local",
             "Found".green(),
@@ -271,7 +651,7 @@ pub fn pull_history(
     // ============================================================================
     // CONFLICT DETECTION
     // ============================================================================
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!("  {} conflicts...", "Detecting".cyan());
     }
 
@@ -287,18 +667,23 @@ pub fn pull_history(
         .collect();
 
     // Find sessions that exist in both and may have conflicts
-    let mut detector = ConflictDetector::new();
+    let modified_entry_policy = filter.modified_entry_policy()?;
+    let precedence = filter.pull_precedence()?;
+    let key_strategies = filter.content_key_strategies()?;
+    let mut detector = ConflictDetector::new()
+        .fork_modified_entries(modified_entry_policy == ModifiedEntryPolicy::Fork);
     detector.detect(&temp_branch_sessions, &remote_sessions);
+    timer.mark("conflict detection")?;
 
     // ============================================================================
     // INTERACTIVE CONFIRMATION
     // ============================================================================
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!();
         println!("{}", "Pull Summary:".bold().cyan());
-        println!("  {} Local sessions: {}", "•".cyan(), temp_branch_sessions.len());
-        println!("  {} Remote sessions: {}", "•".cyan(), remote_sessions.len());
-        println!("  {} Conflicts: {}", "•".yellow(), detector.conflict_count());
+        println!("  {} Local sessions: {}", glyphs::bullet().cyan(), temp_branch_sessions.len());
+        println!("  {} Remote sessions: {}", glyphs::bullet().cyan(), remote_sessions.len());
+        println!("  {} Conflicts: {}", glyphs::bullet().yellow(), detector.conflict_count());
         println!();
     }
 
@@ -313,8 +698,9 @@ pub fn pull_history(
             // Clean up temp branch before exiting (force=true to delete even with retention)
             cleanup_temp_branch(repo.as_ref(), &temp_branch, fetch_remote && state.has_remote, verbosity, 0, true)?;
             println!("\n{}", "Pull cancelled.".yellow());
-            return Ok(());
+            return Ok(None);
         }
+        timer.mark("confirmation")?;
     }
 
     // ============================================================================
@@ -326,10 +712,16 @@ pub fn pull_history(
     let mut modified_count = 0;
     let mut unchanged_count = 0;
     let mut skipped_local_newer = 0;
+    let mut error_count = 0;
+    let mut conflict_report_id = None;
+    let mut fork_stats: Vec<(String, MergeStats)> = Vec::new();
+    // Remote versions of entries edited in place that must replace local ones
+    let mut local_replacements: HashMap<String, Vec<crate::parser::ConversationEntry>> =
+        HashMap::new();
 
     // Handle conflicts with smart merge
     if detector.has_conflicts() {
-        if verbosity != VerbosityLevel::Quiet {
+        if verbosity >= VerbosityLevel::Normal {
             println!(
                 "  {} {} diverged sessions detected (will create forks)",
                 "!".yellow(),
@@ -339,6 +731,7 @@ pub fn pull_history(
         }
 
         let mut smart_merge_success_count = 0;
+        let mut policy_resolved_count = 0;
         let mut smart_merge_failed_conflicts = Vec::new();
 
         for conflict in detector.conflicts_mut() {
@@ -346,6 +739,33 @@ pub fn pull_history(
                 local_map.get(&conflict.session_id),
                 remote_map.get(&conflict.session_id),
             ) {
+                let policy = filter.conflict_policy_for(&conflict.local_file)?;
+                if policy != ConflictPolicy::SmartMerge {
+                    let dest_path = repo_path(&projects_dir, local_session)?;
+                    apply_conflict_policy(
+                        conflict,
+                        policy,
+                        local_session,
+                        remote_session,
+                        &dest_path,
+                        &claude_dir,
+                        &projects_dir,
+                        &backup_dir,
+                        &active_sessions,
+                        filter.preserve_raw_lines,
+                    )?;
+                    policy_resolved_count += 1;
+                    if verbosity >= VerbosityLevel::Normal {
+                        println!(
+                            "  {} Resolved {} by project policy ({})",
+                            glyphs::ok().green(),
+                            conflict.session_id,
+                            policy.as_str()
+                        );
+                    }
+                    continue;
+                }
+
                 match conflict.try_smart_merge(local_session, remote_session) {
                     Ok(()) => {
                         smart_merge_success_count += 1;
@@ -361,23 +781,22 @@ pub fn pull_history(
                             };
 
                             // Write to sync repo (main branch)
-                            let dest_path = projects_dir.join(
-                                Path::new(&local_session.file_path)
-                                    .strip_prefix(&claude_dir)
-                                    .unwrap_or(Path::new(&local_session.file_path))
-                            );
-                            if let Err(e) = merged_session.write_to_file(&dest_path) {
+                            let dest_path = repo_path(&projects_dir, local_session)?;
+                            if let Err(e) = merged_session.write_to_file_with(&dest_path, filter.preserve_raw_lines) {
                                 log::warn!("Failed to write merged session: {}", e);
                                 smart_merge_failed_conflicts.push(conflict.clone());
-                            } else if verbosity != VerbosityLevel::Quiet {
-                                println!(
-                                    "  {} Forked {} ({} local + {} remote = {} combined)",
-                                    "✓".green(),
-                                    conflict.session_id,
-                                    stats.local_messages,
-                                    stats.remote_messages,
-                                    stats.merged_messages,
-                                );
+                            } else {
+                                fork_stats.push((conflict.session_id.clone(), stats.clone()));
+                                if verbosity >= VerbosityLevel::Normal {
+                                    println!(
+                                        "  {} Forked {} ({} local + {} remote = {} combined)",
+                                        glyphs::ok().green(),
+                                        conflict.session_id,
+                                        stats.local_messages,
+                                        stats.remote_messages,
+                                        stats.merged_messages,
+                                    );
+                                }
                             }
                         }
                     }
@@ -389,18 +808,59 @@ pub fn pull_history(
             }
         }
 
-        if verbosity != VerbosityLevel::Quiet {
+        if verbosity >= VerbosityLevel::Normal {
             println!(
                 "  {} Successfully merged {}/{} diverged sessions",
-                "✓".green(),
+                glyphs::ok().green(),
                 smart_merge_success_count,
-                detector.conflict_count()
+                detector.conflict_count() - policy_resolved_count
             );
         }
 
+        // Replay decisions already made for the same forks (on any machine)
+        let mut resolution_index = ResolutionIndex::load(&state.sync_repo_path)?;
+        let mut unresolved = Vec::new();
+        for mut conflict in smart_merge_failed_conflicts {
+            let sessions = local_map
+                .get(&conflict.session_id)
+                .zip(remote_map.get(&conflict.session_id));
+            let replay = sessions.and_then(|(local, remote)| {
+                resolution_index
+                    .replay(local, remote)
+                    .map(|policy| (policy, local, remote))
+            });
+            let Some((policy, local_session, remote_session)) = replay else {
+                unresolved.push(conflict);
+                continue;
+            };
+
+            let dest_path = repo_path(&projects_dir, local_session)?;
+            apply_conflict_policy(
+                &mut conflict,
+                policy,
+                local_session,
+                remote_session,
+                &dest_path,
+                &claude_dir,
+                &projects_dir,
+                &backup_dir,
+                &active_sessions,
+                filter.preserve_raw_lines,
+            )?;
+            if verbosity >= VerbosityLevel::Normal {
+                println!(
+                    "  {} Replayed recorded resolution for {} ({})",
+                    glyphs::ok().green(),
+                    conflict.session_id,
+                    policy.as_str()
+                );
+            }
+        }
+        let mut smart_merge_failed_conflicts = unresolved;
+
         // Handle failed smart merges
         if !smart_merge_failed_conflicts.is_empty() {
-            if verbosity != VerbosityLevel::Quiet {
+            if verbosity >= VerbosityLevel::Normal {
                 println!(
                     "  {} {} conflicts require manual resolution",
                     "!".yellow(),
@@ -418,7 +878,29 @@ pub fn pull_history(
                     &remote_sessions,
                     &claude_dir,
                     &projects_dir,
+                    filter.preserve_raw_lines,
+                    &backup_dir,
                 )?;
+
+                // Record the decisions so other machines replay them
+                let decisions = [
+                    (Kept::Local, &resolution_result.keep_local),
+                    (Kept::Remote, &resolution_result.keep_remote),
+                    (Kept::Both, &resolution_result.keep_both),
+                ];
+                for (kept, conflicts) in decisions {
+                    for conflict in conflicts {
+                        if let (Some(local), Some(remote)) = (
+                            local_map.get(&conflict.session_id),
+                            remote_map.get(&conflict.session_id),
+                        ) {
+                            resolution_index.record(kept, local, remote, &machine_id);
+                        }
+                    }
+                }
+                if resolution_result.total() > resolution_result.smart_merge.len() {
+                    resolution_index.save(&state.sync_repo_path)?;
+                }
             } else {
                 // Non-interactive: keep both versions
                 for conflict in &smart_merge_failed_conflicts {
@@ -430,21 +912,21 @@ pub fn pull_history(
                             .iter()
                             .find(|s| s.session_id == conflict.session_id)
                         {
-                            session.write_to_file(&renamed_path)?;
+                            session.write_to_file_with(&renamed_path, filter.preserve_raw_lines)?;
                         }
                     }
                 }
             }
 
             let report = ConflictReport::from_conflicts(detector.conflicts());
-            save_conflict_report(&report)?;
+            conflict_report_id = Some(save_conflict_report(&report, "pull")?);
         }
     }
 
     // ============================================================================
     // MERGE NON-CONFLICTING SESSIONS
     // ============================================================================
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!("  {} non-conflicting sessions...", "Merging".cyan());
     }
 
@@ -458,12 +940,9 @@ pub fn pull_history(
             continue; // Already handled above
         }
 
-        let relative_path = Path::new(&local_session.file_path)
-            .strip_prefix(&claude_dir)
-            .ok()
-            .unwrap_or_else(|| Path::new(&local_session.file_path));
-
-        let dest_path = projects_dir.join(relative_path);
+        // Read from the temp branch, so already in the sync repo
+        let relative_path = RepoRelPath::new(&projects_dir, Path::new(&local_session.file_path))?;
+        let dest_path = relative_path.under(&projects_dir);
 
         let (operation, should_copy) = if let Some(remote) = remote_map.get(&local_session.session_id) {
             let relationship = analyze_session_relationship(local_session, remote);
@@ -484,45 +963,80 @@ pub fn pull_history(
                     skipped_local_newer += 1;
                     (SyncOperation::Modified, true)
                 }
+                SessionRelationship::EntriesModified => {
+                    // Entries edited in place - reconcile each one instead of forking
+                    let reconciliation = reconcile_modified_entries(
+                        local_session,
+                        remote,
+                        modified_entry_policy,
+                        precedence,
+                        &key_strategies,
+                    );
+                    let reconciled_session = ConversationSession {
+                        session_id: local_session.session_id.clone(),
+                        entries: reconciliation.entries,
+                        file_path: local_session.file_path.clone(),
+                    };
+                    if let Err(e) = reconciled_session.write_to_file_with(&dest_path, filter.preserve_raw_lines) {
+                        log::warn!("Failed to write reconciled session: {}", e);
+                        error_count += 1;
+                    } else if !reconciliation.remote_wins.is_empty() {
+                        local_replacements
+                            .insert(local_session.session_id.clone(), reconciliation.remote_wins);
+                    }
+                    if reconciliation.modified == 0
+                        && local_session.entries.len() == remote.entries.len()
+                    {
+                        // Only compacted or scrubbed copies differ, which
+                        // isn't a change
+                        unchanged_count += 1;
+                        (SyncOperation::Unchanged, false)
+                    } else {
+                        if verbosity == VerbosityLevel::Verbose {
+                            println!(
+                                "    {} {} entries modified in place in {} ({})",
+                                glyphs::detail().dimmed(),
+                                reconciliation.modified,
+                                local_session.session_id,
+                                modified_entry_policy.as_str()
+                            );
+                        }
+
+                        modified_count += 1;
+                        (SyncOperation::Modified, false) // Already written above
+                    }
+                }
                 SessionRelationship::Diverged => {
                     // Diverged session not caught by ConflictDetector - do inline merge
                     // Combine entries from both versions using UUID-based deduplication
-                    // For entries without UUIDs, use (type, timestamp, content_hash) as key
+                    // For entries without UUIDs, use their content key (see content_keys)
                     let mut seen_uuids = std::collections::HashSet::new();
                     let mut seen_non_uuid = std::collections::HashSet::new();
                     let mut combined_entries = Vec::new();
-
-                    // Helper to create a dedup key for entries without UUIDs
-                    // Uses xxhash for cross-platform stability (same result on ARM and x86)
-                    let make_non_uuid_key = |entry: &crate::parser::ConversationEntry| -> String {
-                        let ts = entry.timestamp.as_deref().unwrap_or("");
-                        let content_hash = entry.message.as_ref()
-                            .map(|m| {
-                                let json = serde_json::to_string(m).unwrap_or_default();
-                                xxhash_rust::xxh3::xxh3_64(json.as_bytes())
-                            })
-                            .unwrap_or(0);
-                        format!("{}:{}:{:016x}", entry.entry_type, ts, content_hash)
+                    let (first, second) = match precedence {
+                        PullPrecedence::LocalFirst => (local_session, *remote),
+                        PullPrecedence::RemoteFirst => (*remote, local_session),
                     };
 
-                    // Add all local entries first
-                    for entry in &local_session.entries {
+                    // Add all entries of the side that takes precedence first
+                    let first_keys = content_keys(&first.entries, &key_strategies);
+                    for (entry, key) in first.entries.iter().zip(first_keys) {
                         if let Some(ref uuid) = entry.uuid {
                             seen_uuids.insert(uuid.clone());
-                        } else {
-                            seen_non_uuid.insert(make_non_uuid_key(entry));
+                        } else if let Some(key) = key {
+                            seen_non_uuid.insert(key);
                         }
                         combined_entries.push(entry.clone());
                     }
 
-                    // Add remote entries that aren't already present
-                    for entry in &remote.entries {
-                        let dominated_by_local = if let Some(ref uuid) = entry.uuid {
-                            seen_uuids.contains(uuid)
-                        } else {
-                            seen_non_uuid.contains(&make_non_uuid_key(entry))
+                    // Add entries from the other side that aren't already present
+                    let second_keys = content_keys(&second.entries, &key_strategies);
+                    for (entry, key) in second.entries.iter().zip(second_keys) {
+                        let already_present = match (&entry.uuid, key) {
+                            (Some(uuid), _) => seen_uuids.contains(uuid),
+                            (None, key) => key.is_some_and(|k| seen_non_uuid.contains(&k)),
                         };
-                        if !dominated_by_local {
+                        if !already_present {
                             combined_entries.push(entry.clone());
                         }
                     }
@@ -538,8 +1052,9 @@ pub fn pull_history(
                         entries: combined_entries,
                         file_path: local_session.file_path.clone(),
                     };
-                    if let Err(e) = merged_session.write_to_file(&dest_path) {
+                    if let Err(e) = merged_session.write_to_file_with(&dest_path, filter.preserve_raw_lines) {
                         log::warn!("Failed to write merged diverged session: {}", e);
+                        error_count += 1;
                     }
 
                     modified_count += 1;
@@ -553,11 +1068,11 @@ pub fn pull_history(
         };
 
         if should_copy {
-            local_session.write_to_file(&dest_path)?;
+            local_session.write_to_file_with(&dest_path, filter.preserve_raw_lines)?;
             merged_count += 1;
         }
 
-        let relative_path_str = relative_path.to_string_lossy().to_string();
+        let relative_path_str = relative_path.to_string();
         if let Ok(summary) = ConversationSummary::new(
             local_session.session_id.clone(),
             relative_path_str,
@@ -575,14 +1090,11 @@ pub fn pull_history(
             continue; // Already handled above
         }
 
-        let relative_path = Path::new(&remote_session.file_path)
-            .strip_prefix(&projects_dir)
-            .ok()
-            .unwrap_or_else(|| Path::new(&remote_session.file_path));
+        let relative_path = RepoRelPath::new(&projects_dir, Path::new(&remote_session.file_path))?;
 
         added_count += 1;
 
-        let relative_path_str = relative_path.to_string_lossy().to_string();
+        let relative_path_str = relative_path.to_string();
         if let Ok(summary) = ConversationSummary::new(
             remote_session.session_id.clone(),
             relative_path_str,
@@ -594,266 +1106,1145 @@ pub fn pull_history(
         }
     }
 
+    // Merge todo lists (both directions) so they land in the merge commit
+    if filter.sync_todos {
+        let stats = super::todos::merge_todos(
+            &claude_base_dir.join("todos"),
+            &state.sync_repo_path.join("todos"),
+            filter.todo_merge_mode()?,
+        )?;
+        if verbosity >= VerbosityLevel::Normal && (stats.local_updated > 0 || stats.repo_updated > 0) {
+            println!(
+                "  {} Todos merged ({} updated locally, {} in sync repo)",
+                glyphs::ok().green(),
+                stats.local_updated,
+                stats.repo_updated
+            );
+        }
+    }
+
+    // Union-sync auxiliary artifact directories that are enabled in config
+    for (enabled, dir_name) in [
+        (filter.sync_file_history, super::artifacts::FILE_HISTORY_DIR),
+        (filter.sync_shell_snapshots, super::artifacts::SHELL_SNAPSHOTS_DIR),
+    ] {
+        if !enabled {
+            continue;
+        }
+        let stats = super::artifacts::sync_artifact_dir(
+            &claude_base_dir.join(dir_name),
+            &state.sync_repo_path.join(dir_name),
+            filter.artifact_max_file_size_bytes,
+        )?;
+        if verbosity >= VerbosityLevel::Normal
+            && (stats.copied_to_local > 0 || stats.copied_to_repo > 0 || stats.skipped_too_large > 0)
+        {
+            println!(
+                "  {} {}: {} copied locally, {} to sync repo, {} skipped (too large)",
+                glyphs::ok().green(),
+                dir_name,
+                stats.copied_to_local,
+                stats.copied_to_repo,
+                stats.skipped_too_large
+            );
+        }
+    }
+
+    // Local copies of deleted sessions go to the trash (restorable)
+    if !trash_after_merge.is_empty() {
+        let trash = crate::trash::Trash::open()?;
+        for path in &trash_after_merge {
+            if active_sessions.defers(path) {
+                continue;
+            }
+            trash.put(path, "pull (session deleted on another machine)")?;
+        }
+    }
+
+    // Link sessions forked from one another into thread families
+    let mut families = crate::families::FamilyIndex::load(&state.sync_repo_path)?;
+    let linked = crate::families::detect_forks(temp_branch_sessions.iter().chain(&remote_sessions))
+        .into_iter()
+        .filter(|(fork, link)| families.link(fork, link.clone()))
+        .count();
+    if linked > 0 {
+        families.save(&state.sync_repo_path)?;
+        if verbosity >= VerbosityLevel::Normal {
+            println!(
+                "  {} Linked {} forked sessions to the sessions they were forked from",
+                glyphs::ok().green(),
+                linked
+            );
+        }
+    }
+
     // Commit the merged result to main branch
-    repo.stage_all()?;
-    if repo.has_changes()? {
-        let commit_msg = format!(
-            "Merge local changes from {} ({})",
-            temp_branch,
-            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-        );
-        repo.commit(&commit_msg)?;
+    super::split_oversized_sessions(&projects_dir, &filter, verbosity)?;
+    repo.stage_paths(&super::managed_paths(&filter))?;
+    if repo.has_staged_changes()? {
+        let commit_msg = merge_commit_message(&temp_branch, &fork_stats);
+        super::batching::commit_staged(repo.as_ref(), &state.sync_repo_path, &filter, &commit_msg)?;
     }
 
-    if verbosity != VerbosityLevel::Quiet {
-        println!("  {} Merged {} sessions", "✓".green(), merged_count);
+    if verbosity >= VerbosityLevel::Normal {
+        println!("  {} Merged {} sessions", glyphs::ok().green(), merged_count);
         if skipped_local_newer > 0 {
             println!(
                 "  {} Kept {} local sessions (already ahead of remote)",
-                "✓".green(),
+                glyphs::ok().green(),
                 skipped_local_newer
             );
         }
     }
 
+    timer.mark("merge")?;
+
     // ============================================================================
     // STEP 6: Append-only merge to .claude
     // ============================================================================
     // Key insight: Instead of rewriting files, we APPEND missing entries.
     // This avoids race conditions with concurrent Claude Code writes.
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!("  {} to .claude (append-only)...", "Syncing".cyan());
     }
 
+    // A filter committed centrally for this machine narrows what it pulls;
+    // read it now so a change fetched by this pull applies right away
+    let machine_filter = MachineFilter::load(&state.sync_repo_path, &machine_id)?;
+    if machine_filter.is_some() && verbosity == VerbosityLevel::Verbose {
+        println!(
+            "  {} Pulling only what {}/{}.toml allows",
+            glyphs::info().cyan(),
+            crate::machines::MACHINES_DIR,
+            machine_id
+        );
+    }
+    let passes_machine = |session: &ConversationSession| {
+        machine_filter.as_ref().is_none_or(|machine| {
+            let path = Path::new(&session.file_path);
+            let relative = path
+                .strip_prefix(&projects_dir)
+                .or_else(|_| path.strip_prefix(&claude_dir))
+                .unwrap_or(path);
+            machine.passes(session, relative)
+        })
+    };
+    let mut machine_skipped = 0;
+
     // Re-read current local state (may have changed since step 2)
     let current_local_sessions = discover_sessions(&claude_dir, &filter)?;
-    let current_local_map: HashMap<_, _> = current_local_sessions
-        .iter()
-        .map(|s| (s.session_id.clone(), s))
-        .collect();
+    // Subagent transcripts carry their parent's session ID, so they are
+    // matched by path; everything else by ID, wherever the file is
+    let mut current_local_by_id: HashMap<&str, Vec<&ConversationSession>> = HashMap::new();
+    let mut current_local_agents: HashMap<&str, &ConversationSession> = HashMap::new();
+    for session in &current_local_sessions {
+        if session.is_agent_file() {
+            current_local_agents.insert(&session.file_path, session);
+        } else {
+            current_local_by_id
+                .entry(&session.session_id)
+                .or_default()
+                .push(session);
+        }
+    }
 
     // Read sync repo sessions (contains merged state)
-    let sync_repo_sessions = discover_sessions(&projects_dir, &filter)?;
+    let mut sync_repo_sessions = discover_sessions(&projects_dir, &filter)?;
+
+    // A session renamed on one machine is now in the repo under both names;
+    // fold the copies into one file
+    let relocations =
+        consolidate_relocated(&sync_repo_sessions, &key_strategies, filter.preserve_raw_lines)?;
+    if !relocations.is_empty() {
+        super::split_oversized_sessions(&projects_dir, &filter, verbosity)?;
+        repo.stage_paths(&super::managed_paths(&filter))?;
+        repo.commit(&format!(
+            "Consolidate {} sessions stored under more than one file name",
+            relocations.len()
+        ))?;
+        sync_repo_sessions = discover_sessions(&projects_dir, &filter)?;
+        if verbosity >= VerbosityLevel::Normal {
+            println!(
+                "  {} Consolidated {} sessions stored under more than one file name",
+                glyphs::ok().green(),
+                relocations.len()
+            );
+        }
+        if verbosity == VerbosityLevel::Verbose {
+            let relative = |path: &Path| {
+                let path = path.strip_prefix(&projects_dir).unwrap_or(path);
+                path.display().to_string()
+            };
+            for relocation in &relocations {
+                let removed: Vec<_> = relocation.removed.iter().map(|p| relative(p)).collect();
+                println!(
+                    "    {} {}: kept {}, removed {}",
+                    glyphs::detail().dimmed(),
+                    relocation.session_id,
+                    relative(&relocation.kept),
+                    removed.join(", ")
+                );
+            }
+        }
+    }
 
     let mut sessions_added = 0;
     let mut sessions_appended = 0;
     let mut entries_appended = 0;
+    let mut sessions_quarantined = 0;
+    let mut entries_replaced = 0;
+    let mut sessions_relocated = 0;
 
     for sync_session in &sync_repo_sessions {
-        let relative_path = Path::new(&sync_session.file_path)
-            .strip_prefix(&projects_dir)
-            .unwrap_or(Path::new(&sync_session.file_path));
-        let local_path = claude_dir.join(relative_path);
+        if !passes_tags(&sync_session.session_id) {
+            left_out.insert(sync_session.session_id.clone());
+            left_out.extend(file_stem(&sync_session.file_path));
+            continue;
+        }
+        if !passes_machine(sync_session) {
+            left_out.extend(file_stem(&sync_session.file_path));
+            machine_skipped += 1;
+            continue;
+        }
+        let relative_path = RepoRelPath::new(&projects_dir, Path::new(&sync_session.file_path))?.to_claude();
+        let local_path = relative_path.under(&claude_dir);
+
+        let local_copies: Vec<&ConversationSession> = if sync_session.is_agent_file() {
+            current_local_agents
+                .get(local_path.to_string_lossy().as_ref())
+                .into_iter()
+                .copied()
+                .collect()
+        } else {
+            current_local_by_id
+                .get(sync_session.session_id.as_str())
+                .cloned()
+                .unwrap_or_default()
+        };
+        // Local copies under another file name are moved into the synced one
+        let moved: Vec<&ConversationSession> = local_copies
+            .iter()
+            .copied()
+            .filter(|s| Path::new(&s.file_path) != local_path)
+            .collect();
+        let merged_local;
+        let local_session = match local_copies.as_slice() {
+            [] => None,
+            [only] if moved.is_empty() => Some(*only),
+            copies => {
+                let base = copies
+                    .iter()
+                    .position(|s| Path::new(&s.file_path) == local_path)
+                    .unwrap_or_else(|| canonical_copy(copies));
+                merged_local = merge_copies(copies, base, &local_path, &key_strategies);
+                Some(&merged_local)
+            }
+        };
 
-        if let Some(local_session) = current_local_map.get(&sync_session.session_id) {
+        if let Some(local_session) = local_session {
             // Session exists locally - append only missing entries
+            let entries_to_append =
+                missing_entries(&local_session.entries, &sync_session.entries, &key_strategies);
+            let replacements = local_replacements.get(&sync_session.session_id);
+            let rewrite = replacements.is_some() || !moved.is_empty();
+            // Checked for every copy, so each deferred file is reported
+            let deferred = rewrite
+                && local_copies
+                    .iter()
+                    .filter(|s| active_sessions.defers(Path::new(&s.file_path)))
+                    .count()
+                    > 0;
+
+            if rewrite && !deferred {
+                // Entries edited in place can't be appended, and moved copies
+                // need a new file; rewrite it with the winning versions (after
+                // a backup)
+                let replacements = replacements.map_or(&[][..], Vec::as_slice);
+                let by_uuid: HashMap<_, _> = replacements
+                    .iter()
+                    .filter_map(|e| e.uuid.as_deref().map(|uuid| (uuid, e)))
+                    .collect();
+                let mut entries: Vec<_> = local_session
+                    .entries
+                    .iter()
+                    .map(|e| {
+                        let replacement = e.uuid.as_deref().and_then(|uuid| by_uuid.get(uuid));
+                        replacement.map_or_else(|| e.clone(), |r| (*r).clone())
+                    })
+                    .collect();
+                entries.extend(entries_to_append.iter().cloned());
+                let updated = ConversationSession {
+                    session_id: local_session.session_id.clone(),
+                    entries,
+                    file_path: local_session.file_path.clone(),
+                };
+                crate::backups::backup_file(&backup_dir, &local_path, relative_path.as_path())?;
+                updated.write_to_file_with(&local_path, filter.preserve_raw_lines)?;
+                for copy in &moved {
+                    let path = Path::new(&copy.file_path);
+                    let relative = ClaudeRelPath::new(&claude_dir, path)?;
+                    crate::backups::backup_file(&backup_dir, path, relative.as_path())?;
+                    std::fs::remove_file(path)
+                        .with_context(|| format!("Failed to remove {}", path.display()))?;
+                }
+                entries_replaced += replacements.len();
+                entries_appended += entries_to_append.len();
+                if !replacements.is_empty() || !entries_to_append.is_empty() {
+                    sessions_appended += 1;
+                }
+                if !moved.is_empty() {
+                    sessions_relocated += 1;
+                }
 
-            // Build sets of what's already in local
-            let local_uuids: HashSet<String> = local_session
-                .entries
-                .iter()
-                .filter_map(|e| e.uuid.clone())
-                .collect();
-
-            let local_non_uuid_keys: HashSet<String> = local_session
-                .entries
-                .iter()
-                .filter(|e| e.uuid.is_none())
-                .map(make_content_key)
-                .collect();
-
-            // Find entries in sync_repo that aren't in local
-            let entries_to_append: Vec<_> = sync_session
-                .entries
-                .iter()
-                .filter(|entry| {
-                    if let Some(ref uuid) = entry.uuid {
-                        !local_uuids.contains(uuid)
-                    } else {
-                        !local_non_uuid_keys.contains(&make_content_key(entry))
+                if verbosity == crate::VerbosityLevel::Verbose {
+                    if !replacements.is_empty() {
+                        println!(
+                            "    {} {} modified entries updated in {}",
+                            glyphs::detail().dimmed(),
+                            replacements.len(),
+                            sync_session.session_id
+                        );
                     }
-                })
-                .cloned()
-                .collect();
-
-            if !entries_to_append.is_empty() {
-                append_entries_to_file(&local_path, &entries_to_append)?;
+                    if !moved.is_empty() {
+                        println!(
+                            "    {} {} moved to {}",
+                            glyphs::detail().dimmed(),
+                            sync_session.session_id,
+                            relative_path
+                        );
+                    }
+                }
+            } else if !entries_to_append.is_empty() && moved.is_empty() {
+                append_entries_to_file_with(&local_path, &entries_to_append, filter.preserve_raw_lines)?;
                 entries_appended += entries_to_append.len();
                 sessions_appended += 1;
 
                 if verbosity == crate::VerbosityLevel::Verbose {
                     println!(
                         "    {} +{} entries to {}",
-                        "↳".dimmed(),
+                        glyphs::detail().dimmed(),
                         entries_to_append.len(),
                         sync_session.session_id
                     );
                 }
             }
         } else {
-            // Session doesn't exist locally - copy entire file
-            sync_session.write_to_file(&local_path)?;
+            // Session doesn't exist locally - copy entire file, unless a file
+            // already at that path has more entries (never shrink local data)
+            if crate::quarantine::quarantine_if_shrinking(
+                &local_path,
+                relative_path.as_path(),
+                sync_session.entries.len(),
+            )?
+            .is_some()
+            {
+                sessions_quarantined += 1;
+                continue;
+            }
+            crate::backups::backup_file(&backup_dir, &local_path, relative_path.as_path())?;
+            sync_session.write_to_file_with(&local_path, filter.preserve_raw_lines)?;
             sessions_added += 1;
 
             if verbosity == crate::VerbosityLevel::Verbose {
                 println!(
                     "    {} new session {}",
-                    "↳".dimmed(),
+                    glyphs::detail().dimmed(),
                     sync_session.session_id
                 );
             }
         }
     }
 
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
+        if sessions_relocated > 0 {
+            println!(
+                "  {} Moved {} sessions to the file name used in the sync repo",
+                glyphs::ok().green(),
+                sessions_relocated
+            );
+        }
         if sessions_added > 0 || sessions_appended > 0 {
             println!(
                 "  {} Added {} new sessions, appended {} entries to {} sessions",
-                "✓".green(),
+                glyphs::ok().green(),
                 sessions_added,
                 entries_appended,
                 sessions_appended
             );
-        } else {
-            println!("  {} No changes needed in .claude", "✓".green());
+            if entries_replaced > 0 {
+                println!(
+                    "  {} Updated {} entries edited in place on another machine",
+                    glyphs::ok().green(),
+                    entries_replaced
+                );
+            }
+        } else if sessions_relocated == 0 {
+            println!("  {} No changes needed in .claude", glyphs::ok().green());
+        }
+        if machine_skipped > 0 {
+            println!(
+                "  {} Skipped {} sessions outside this machine's filter ({}/{}.toml)",
+                glyphs::info().cyan(),
+                machine_skipped,
+                crate::machines::MACHINES_DIR,
+                machine_id
+            );
+        }
+    }
+    active_sessions.report(&claude_dir, verbosity);
+    error_count += sessions_quarantined;
+    if sessions_quarantined > 0 {
+        // Shown even in quiet mode: this means a bug, and local data was protected
+        eprintln!(
+            "  {} {}",
+            "!".red().bold(),
+            format!(
+                "{} sessions were not updated because the pull would have shrunk them (see quarantine dir)",
+                sessions_quarantined
+            )
+            .red()
+            .bold()
+        );
+    }
+
+    // Copy teammates' shared sessions (read-only)
+    for user in include_users {
+        let user_projects_dir = state
+            .sync_repo_path
+            .join(super::namespaces::user_projects_subdir(user, &filter.sync_subdirectory));
+        let written = super::namespaces::mirror_sessions(&user_projects_dir, &claude_dir, Some(&backup_dir))?;
+        left_out.extend(crate::tombstones::session_file_ids(&user_projects_dir));
+        if verbosity >= VerbosityLevel::Normal {
+            println!(
+                "  {} Pulled {} shared sessions from {} (read-only)",
+                glyphs::ok().green(),
+                written,
+                user
+            );
         }
     }
 
+    // Refresh read-only subscriptions (never part of the sync repo)
+    if fetch_remote && upstream == "origin" {
+        let subscriptions = crate::subscriptions::Subscriptions::load()?;
+        for subscription in &subscriptions.subscriptions {
+            match subscription.refresh(claude_base_dir) {
+                Ok(written) => {
+                    if verbosity >= VerbosityLevel::Normal && written > 0 {
+                        println!(
+                            "  {} Subscription {}: {} sessions updated",
+                            glyphs::ok().green(),
+                            subscription.name,
+                            written
+                        );
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Failed to refresh subscription {}: {}", subscription.name, e);
+                    network_failures.push(format!(
+                        "refresh of subscription {}: {e}",
+                        subscription.name
+                    ));
+                    if verbosity >= VerbosityLevel::Normal {
+                        println!(
+                            "  {} Could not refresh subscription {}: {}",
+                            "!".yellow().bold(),
+                            subscription.name,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    timer.mark("append")?;
+
     // ============================================================================
     // STEP 6b: Merge history.jsonl (session index for --resume picker)
     // ============================================================================
-    let claude_base_dir = claude_dir.parent().unwrap_or(&claude_dir);
     let local_history = claude_base_dir.join("history.jsonl");
     let sync_history = state.sync_repo_path.join("history.jsonl");
 
-    if sync_history.exists() {
-        println!("  {} history.jsonl...", "Merging".cyan());
-        // Merge sync repo entries into local, with local entries taking priority
-        let (total, added) = super::history_merge::merge_history_files(
-            &sync_history,
+    if (sync_history.exists() || local_history.exists()) && !skip_history {
+        if verbosity >= VerbosityLevel::Normal {
+            println!("  {} history.jsonl...", "Merging".cyan());
+        }
+        // Merge local entries into the main branch's copy so the next push
+        // carries them, then the result into local, keeping the version of
+        // entries both sides hold that the pull precedence picks
+        super::history_merge::merge_history_files(
             &local_history,
-            super::history_merge::MergePriority::TargetFirst,
+            &sync_history,
+            MergePriority::from_precedence(precedence, true),
         )?;
-        println!("  {} history.jsonl merged ({} entries, {} new)", "✓".green(), total, added);
+        let priority = MergePriority::from_precedence(precedence, false);
+        let merged =
+            super::history_merge::merge_history_files(&sync_history, &local_history, priority)?;
+        if verbosity >= VerbosityLevel::Normal {
+            println!(
+                "  {} history.jsonl merged ({} entries, {} new)",
+                glyphs::ok().green(),
+                merged.total,
+                merged.added
+            );
+            print_history_changes(&merged);
+        }
+
+        // Deleted sessions must not come back through another machine's index
+        let tombstones = TombstoneIndex::load(&state.sync_repo_path)?;
+        if !tombstones.sessions.is_empty() {
+            for path in [&sync_history, &local_history] {
+                super::history_merge::remove_history_sessions(path, |id| {
+                    tombstones.is_deleted(id)
+                })?;
+            }
+        }
+    }
+
+    timer.mark("history merge")?;
+
+    // ============================================================================
+    // STEP 6c: Verify .claude against the sync repo (safety net)
+    // ============================================================================
+    let inconsistent_sessions = if filter.verify_after_pull {
+        let local_after: Vec<_> = discover_sessions(&claude_dir, &filter)?
+            .into_iter()
+            .filter(|s| passes_tags(&s.session_id) && passes_machine(s))
+            .collect();
+        let diverged = crate::conflict::find_diverged_sessions(&local_after, &sync_repo_sessions);
+        if diverged.is_empty() && verbosity >= VerbosityLevel::Normal {
+            println!("  {} Verified .claude is consistent with the sync repo", glyphs::ok().green());
+        }
+        timer.mark("verification")?;
+        diverged
+    } else {
+        Vec::new()
+    };
+
+    // ============================================================================
+    // STEP 6d: Check every session is on both sides
+    // ============================================================================
+    left_out.extend(
+        skipped_sessions
+            .iter()
+            .filter_map(|skip| file_stem(&skip.path)),
+    );
+    super::reconcile::find_one_sided(
+        &claude_dir,
+        &projects_dir,
+        &filter,
+        &left_out,
+        discovery_started,
+    )
+    .report(&claude_dir, &projects_dir, verbosity);
+
+    if filter.detect_deletions {
+        tombstones::save_seen_sessions(&tombstones::session_file_ids(&claude_dir))?;
     }
 
     // ============================================================================
     // STEP 7: Clean up temp branch (respects retention config)
     // ============================================================================
-    cleanup_temp_branch(
-        repo.as_ref(),
-        &temp_branch,
-        fetch_remote && state.has_remote,
-        verbosity,
-        filter.temp_branch_retention_hours,
-        false, // don't force delete
-    )?;
+    // An inconsistent pull keeps its temp branch for inspection
+    if inconsistent_sessions.is_empty() {
+        cleanup_temp_branch(
+            repo.as_ref(),
+            &temp_branch,
+            fetch_remote && state.has_remote,
+            verbosity,
+            filter.temp_branch_retention_hours,
+            false, // don't force delete
+        )?;
+    }
+    timer.mark("cleanup")?;
 
     // ============================================================================
     // CREATE AND SAVE OPERATION RECORD
     // ============================================================================
-    let operation_record = OperationRecord::new(
+    let mut operation_record = OperationRecord::new(
         OperationType::Pull,
         Some(main_branch.clone()),
         affected_conversations.clone(),
     );
-
-    let mut history = match OperationHistory::load() {
-        Ok(h) => h,
-        Err(e) => {
-            log::warn!("Failed to load operation history: {}", e);
-            OperationHistory::default()
+    operation_record.inconsistent_sessions = inconsistent_sessions.clone();
+    operation_record.skipped_sessions = skipped_sessions;
+    operation_record.network_failures = network_failures;
+    operation_record.remote_unreachable = remote_unreachable;
+    if verbosity == VerbosityLevel::Verbose {
+        timer.print();
+    }
+    operation_record.phase_timings = timer.into_phases();
+    operation_record.conflict_report = conflict_report_id;
+    operation_record.temp_branch = repo.branch_exists(&temp_branch).then(|| temp_branch.clone());
+    operation_record.backup_path = crate::backups::finish_run(&backup_dir);
+    if let Some(path) = &operation_record.backup_path {
+        if verbosity >= VerbosityLevel::Normal {
+            println!(
+                "  {} Overwritten local files backed up to {}",
+                glyphs::info().cyan(),
+                path.display()
+            );
         }
-    };
+    }
 
-    if let Err(e) = history.add_operation(operation_record) {
-        log::warn!("Failed to save operation to history: {}", e);
+    if !inconsistent_sessions.is_empty() {
+        super::save_operation_record(operation_record);
+        eprintln!(
+            "\n{} {}",
+            glyphs::fail().red().bold(),
+            format!(
+                "Verification failed: {} sessions diverged between .claude and the sync repo",
+                inconsistent_sessions.len()
+            )
+            .red()
+            .bold()
+        );
+        for session_id in &inconsistent_sessions {
+            eprintln!("    {} {}", glyphs::detail().dimmed(), session_id);
+        }
+        eprintln!(
+            "  {} Temp branch {} was kept for inspection",
+            glyphs::info().cyan(),
+            temp_branch
+        );
+        anyhow::bail!(
+            "Pull left {} sessions inconsistent with the sync repo",
+            inconsistent_sessions.len()
+        );
     }
 
     // ============================================================================
     // DISPLAY SUMMARY
     // ============================================================================
-    if verbosity != VerbosityLevel::Quiet {
-        println!("\n{}", "=== Pull Summary ===".bold().cyan());
+    if verbosity == VerbosityLevel::Summary {
+        println!(
+            "{}{}",
+            pull_summary_line(
+                added_count,
+                modified_count,
+                detector.conflict_count(),
+                error_count
+            ),
+            local_only_suffix(remote_unreachable)
+        );
+    }
+    if verbosity >= VerbosityLevel::Normal {
+        println!("\n{}", tr("pull.heading", &[]).bold().cyan());
 
         let fork_count = detector.conflict_count();
         println!(
-            "  {} Added    {} Modified    {} Forked    {} Unchanged",
-            format!("{added_count}").green(),
-            format!("{modified_count}").cyan(),
-            format!("{fork_count}").yellow(),
-            format!("{unchanged_count}").dimmed(),
+            "  {}",
+            tr(
+                "pull.counts",
+                &[
+                    ("added", &format!("{added_count}").green()),
+                    ("modified", &format!("{modified_count}").cyan()),
+                    ("forks", &format!("{fork_count}").yellow()),
+                    ("unchanged", &format!("{unchanged_count}").dimmed()),
+                ]
+            )
         );
 
         if skipped_local_newer > 0 {
             println!(
-                "  (Kept {} sessions where local was ahead of remote)",
-                skipped_local_newer
+                "  {}",
+                tr("pull.kept_local_newer", &[("n", &skipped_local_newer)])
             );
         }
         println!();
 
-        // Group by project
-        let mut by_project: HashMap<String, Vec<&ConversationSummary>> = HashMap::new();
-        for conv in &affected_conversations {
-            if conv.operation == SyncOperation::Unchanged {
-                continue;
-            }
-            let project = conv
-                .project_path
-                .split('/')
-                .next()
-                .unwrap_or("unknown")
-                .to_string();
-            by_project.entry(project).or_default().push(conv);
+        print_affected_conversations(&affected_conversations, filter.max_conversations_display);
+        report_skipped(&operation_record.skipped_sessions, verbosity);
+
+        print_pull_complete(remote_unreachable);
+    }
+
+    Ok(Some(operation_record))
+}
+
+/// Resolve a diverged session by a configured (non-smart-merge) policy
+///
+/// `dest_path` is the session's path in the sync repo, which holds the remote
+/// version at this point; the local copy is only touched for
+/// `PreferRemote`, after a backup, and not at all while Claude Code may be
+/// writing to it.
+#[allow(clippy::too_many_arguments)]
+fn apply_conflict_policy(
+    conflict: &mut crate::conflict::Conflict,
+    policy: ConflictPolicy,
+    local_session: &ConversationSession,
+    remote_session: &ConversationSession,
+    dest_path: &Path,
+    claude_dir: &Path,
+    projects_dir: &Path,
+    backup_dir: &Path,
+    active_sessions: &ActiveSessionGuard,
+    preserve_raw: bool,
+) -> Result<()> {
+    // The conflict is between the temp branch and main, so its local file is
+    // the sync repo copy; the session in .claude is at the same relative path
+    let relative = RepoRelPath::new(projects_dir, &conflict.local_file)?.to_claude();
+    let local_path = relative.under(claude_dir);
+    match policy {
+        ConflictPolicy::SmartMerge => {}
+        ConflictPolicy::PreferLocal => {
+            local_session.write_to_file_with(dest_path, preserve_raw)?;
+            conflict.resolution = ConflictResolution::KeepLocal;
+        }
+        ConflictPolicy::PreferRemote if active_sessions.defers(&local_path) => {
+            // Left for a later pull; step 6 still appends the remote's entries
+            conflict.resolution = ConflictResolution::KeepLocal;
+        }
+        ConflictPolicy::PreferRemote => {
+            crate::backups::backup_file(backup_dir, &local_path, relative.as_path())?;
+            remote_session
+                .write_to_file_with(&conflict.local_file, preserve_raw)
+                .with_context(|| {
+                    format!(
+                        "Failed to overwrite local file with remote: {}",
+                        conflict.local_file.display()
+                    )
+                })?;
+            conflict.resolution = ConflictResolution::KeepRemote;
         }
+        ConflictPolicy::KeepBoth => {
+            let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+            let renamed_path = conflict.resolve_keep_both(&format!("conflict-{timestamp}"))?;
+            remote_session.write_to_file_with(&renamed_path, preserve_raw)?;
+            local_session.write_to_file_with(dest_path, preserve_raw)?;
+        }
+    }
+    Ok(())
+}
 
-        if !by_project.is_empty() {
-            println!("{}", "Affected Conversations:".bold());
+/// Print the changed conversations grouped by real project path
+///
+/// At most `max_per_project` conversations are listed per project (0 = no
+/// limit). When that cuts anything off on an interactive terminal, the full
+/// listing is shown in the pager instead.
+fn print_affected_conversations(conversations: &[ConversationSummary], max_per_project: usize) {
+    let mut by_project: HashMap<String, Vec<&ConversationSummary>> = HashMap::new();
+    for conv in conversations {
+        if conv.operation == SyncOperation::Unchanged {
+            continue;
+        }
+        let project = project_dir_name(Path::new(&conv.project_path))
+            .map_or_else(|| "unknown".to_string(), |dir| project_display_name(&dir));
+        by_project.entry(project).or_default().push(conv);
+    }
+    if by_project.is_empty() {
+        return;
+    }
+
+    let truncated = max_per_project > 0 && by_project.values().any(|c| c.len() > max_per_project);
+    if truncated && crate::pager::is_interactive() {
+        let full = render_affected_conversations(&by_project, 0);
+        if crate::pager::page(&full) {
+            return;
+        }
+    }
+    print!("{}", render_affected_conversations(&by_project, max_per_project));
+}
 
-            let mut projects: Vec<_> = by_project.keys().collect();
-            projects.sort();
+/// New conversations listed after the history.jsonl merge; the rest are
+/// summarized in a count
+const MAX_LISTED_NEW_CONVERSATIONS: usize = 10;
+
+/// Show which sessions the history.jsonl merge made resumable or extended
+fn print_history_changes(merged: &super::history_merge::HistoryMergeSummary) {
+    for session in &merged.sessions {
+        log::debug!(
+            "history.jsonl: session {} gained {} entries{}",
+            session.session_id,
+            session.entries_added,
+            if session.is_new { " (new)" } else { "" }
+        );
+    }
 
-            for project in projects {
-                let conversations = &by_project[project];
-                println!("\n  {} {}/", "Project:".bold(), project.cyan());
+    let new: Vec<_> = merged.new_sessions().collect();
+    if !new.is_empty() {
+        println!(
+            "    {} {} new conversation{} to resume:",
+            glyphs::detail().dimmed(),
+            new.len(),
+            if new.len() == 1 { "" } else { "s" }
+        );
+        for session in new.iter().take(MAX_LISTED_NEW_CONVERSATIONS) {
+            let display = truncate_to_width(session.display.trim(), 60, Keep::Start);
+            match &session.project {
+                Some(project) => println!("      \"{}\" {}", display, project.dimmed()),
+                None => println!("      \"{}\"", display),
+            }
+        }
+        if new.len() > MAX_LISTED_NEW_CONVERSATIONS {
+            println!(
+                "      {}",
+                format!("... and {} more", new.len() - MAX_LISTED_NEW_CONVERSATIONS).dimmed()
+            );
+        }
+    }
 
-                for conv in conversations.iter().take(MAX_CONVERSATIONS_TO_DISPLAY) {
-                    let operation_str = match conv.operation {
-                        SyncOperation::Added => "ADD".green(),
-                        SyncOperation::Modified => "MOD".cyan(),
-                        SyncOperation::Conflict => "FORK".yellow(),
-                        SyncOperation::Unchanged => "---".dimmed(),
-                    };
+    let extended: Vec<_> = merged.sessions.iter().filter(|s| !s.is_new).collect();
+    if !extended.is_empty() {
+        println!(
+            "    {} {} existing session{} gained {} entries",
+            glyphs::detail().dimmed(),
+            extended.len(),
+            if extended.len() == 1 { "" } else { "s" },
+            extended.iter().map(|s| s.entries_added).sum::<usize>()
+        );
+    }
+}
 
-                    let timestamp_str = conv
-                        .timestamp
-                        .as_ref()
-                        .and_then(|t| t.split('T').next())
-                        .unwrap_or("unknown");
+/// Per-operation counts for a project heading, e.g. "2 added, 1 forked"
+fn operation_counts(conversations: &[&ConversationSummary]) -> String {
+    let count = |op: SyncOperation| conversations.iter().filter(|c| c.operation == op).count();
+    [
+        (count(SyncOperation::Added), "added"),
+        (count(SyncOperation::Modified), "modified"),
+        (count(SyncOperation::Conflict), "forked"),
+    ]
+    .iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, label)| format!("{n} {label}"))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
 
-                    println!(
-                        "    {} {} ({}msg, {})",
-                        operation_str,
-                        conv.project_path,
-                        conv.message_count,
-                        timestamp_str.dimmed()
-                    );
-                }
+fn render_affected_conversations(
+    by_project: &HashMap<String, Vec<&ConversationSummary>>,
+    max_per_project: usize,
+) -> String {
+    let mut out = format!("{}\n", "Affected Conversations:".bold());
+
+    let mut projects: Vec<_> = by_project.keys().collect();
+    projects.sort();
+
+    for project in projects {
+        let conversations = &by_project[project];
+        out.push_str(&format!(
+            "\n  {} {}  {}\n",
+            "Project:".bold(),
+            project.cyan(),
+            operation_counts(conversations).dimmed()
+        ));
+
+        let shown = if max_per_project == 0 {
+            conversations.len()
+        } else {
+            conversations.len().min(max_per_project)
+        };
+        let mut table = Table::new(vec![
+            Column::new("", "operation"),
+            Column::new("Conversation", "conversation").max_width(60, Keep::End),
+            Column::new("Messages", "messages").right(),
+            Column::new("Date", "date"),
+        ]);
+        for conv in &conversations[..shown] {
+            let operation_str = match conv.operation {
+                SyncOperation::Added => "ADD".green(),
+                SyncOperation::Modified => "MOD".cyan(),
+                SyncOperation::Conflict => "FORK".yellow(),
+                SyncOperation::Unchanged => "---".dimmed(),
+            };
+
+            let timestamp_str = conv
+                .timestamp
+                .as_ref()
+                .and_then(|t| t.split('T').next())
+                .unwrap_or("unknown");
+
+            // The project is in the heading; show the path within it
+            let path = Path::new(&conv.project_path);
+            let within_project = project_dir_name(path)
+                .and_then(|dir| path.strip_prefix(dir).ok())
+                .unwrap_or(path);
+            table.push_row(vec![
+                operation_str.to_string(),
+                within_project.display().to_string(),
+                conv.message_count.to_string(),
+                timestamp_str.dimmed().to_string(),
+            ]);
+        }
+        for line in table.to_text().lines() {
+            out.push_str(&format!("  {line}\n"));
+        }
 
-                if conversations.len() > MAX_CONVERSATIONS_TO_DISPLAY {
-                    println!(
-                        "    {} ... and {} more conversations",
-                        "...".dimmed(),
-                        conversations.len() - MAX_CONVERSATIONS_TO_DISPLAY
-                    );
-                }
+        if conversations.len() > shown {
+            out.push_str(&format!(
+                "    {} ... and {} more conversations (see `config --max-conversations-display`)\n",
+                "...".dimmed(),
+                conversations.len() - shown
+            ));
+        }
+    }
+    out
+}
+
+/// Message for the commit merging a pull's temp branch into main
+///
+/// The body lists the message counts of each forked session, so the repo
+/// history explains how a diverged session file came to look the way it does.
+fn merge_commit_message(temp_branch: &str, fork_stats: &[(String, MergeStats)]) -> String {
+    let mut message = format!(
+        "Merge local changes from {} ({})",
+        temp_branch,
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    if !fork_stats.is_empty() {
+        message.push_str("\n\nForked sessions:\n");
+        for (session_id, stats) in fork_stats {
+            message.push_str(&format!(
+                "- {}: {} local + {} remote = {} merged messages",
+                session_id, stats.local_messages, stats.remote_messages, stats.merged_messages
+            ));
+            if stats.branches_detected > 0 {
+                message.push_str(&format!(", {} branches", stats.branches_detected));
             }
+            message.push('\n');
+        }
+    }
+    message
+}
+
+/// One-line pull summary printed at `VerbosityLevel::Summary`
+pub(crate) fn pull_summary_line(added: usize, modified: usize, forks: usize, errors: usize) -> String {
+    tr(
+        "pull.summary_line",
+        &[
+            ("added", &added),
+            ("modified", &modified),
+            ("forks", &count("count.forks", forks)),
+            ("errors", &count("count.errors", errors)),
+        ],
+    )
+}
+
+/// Where a session read from the temp branch goes in the sync repo
+fn repo_path(projects_dir: &Path, local_session: &ConversationSession) -> Result<std::path::PathBuf> {
+    let relative = RepoRelPath::new(projects_dir, Path::new(&local_session.file_path))?;
+    Ok(relative.under(projects_dir))
+}
+
+/// The file stem of a session file path, which is its ID unless the entries
+/// say otherwise
+fn file_stem(path: &str) -> Option<String> {
+    let path = Path::new(path);
+    let base = crate::parts::split_part(path).map_or_else(|| path.to_path_buf(), |(base, _)| base);
+    base.file_stem().map(|s| s.to_string_lossy().to_string())
+}
+
+/// What the one-line summary of a pull that couldn't reach the remote ends with
+pub(crate) fn local_only_suffix(remote_unreachable: bool) -> String {
+    if remote_unreachable {
+        tr("pull.local_only", &[])
+    } else {
+        String::new()
+    }
+}
+
+/// Print the final status of a pull, which only covered local state if the
+/// remote couldn't be reached
+pub(crate) fn print_pull_complete(remote_unreachable: bool) {
+    if remote_unreachable {
+        println!(
+            "\n{}",
+            tr("pull.complete_local_only", &[]).yellow().bold()
+        );
+    } else {
+        println!("\n{}", tr("pull.complete", &[]).green().bold());
+    }
+}
+
+/// Drop tombstoned sessions from both sides of the merge
+///
+/// Tombstones are added for `locally_deleted` first. A session continued
+/// after its tombstone is kept and the tombstone dropped; the sync repo
+/// copies of the others are removed from the main branch's working tree.
+/// Returns the local files of deleted sessions, to be trashed once the merge
+/// has gone through.
+#[allow(clippy::too_many_arguments)]
+fn apply_tombstones(
+    sync_repo: &Path,
+    machine_id: &str,
+    locally_deleted: &[String],
+    remote_sessions: &mut Vec<ConversationSession>,
+    temp_branch_sessions: &mut Vec<ConversationSession>,
+    local_sessions: &[ConversationSession],
+    verbosity: crate::VerbosityLevel,
+) -> Result<Vec<std::path::PathBuf>> {
+    use crate::VerbosityLevel;
+
+    let mut index = TombstoneIndex::load(sync_repo)?;
+    let mut changed = false;
+    for session_id in locally_deleted {
+        changed |= index.add(session_id, machine_id);
+    }
+    if index.sessions.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let revived: std::collections::BTreeSet<String> = remote_sessions
+        .iter()
+        .chain(temp_branch_sessions.iter())
+        .chain(local_sessions)
+        .filter(|s| index.outlived_by(s))
+        .map(|s| s.session_id.clone())
+        .collect();
+    for session_id in &revived {
+        index.remove(session_id);
+        changed = true;
+        if verbosity >= VerbosityLevel::Normal {
+            println!(
+                "  {} Keeping {}: it was continued after being deleted",
+                glyphs::info().cyan(),
+                session_id
+            );
+        }
+    }
+
+    let mut removed = std::collections::BTreeSet::new();
+    for session in remote_sessions.iter().filter(|s| index.is_deleted(&s.session_id)) {
+        let base = Path::new(&session.file_path);
+        let files = std::iter::once(base.to_path_buf())
+            .chain((1..).map(|n| crate::parts::part_path(base, n)))
+            .take_while(|p| p.exists());
+        for file in files.collect::<Vec<_>>() {
+            std::fs::remove_file(&file)
+                .with_context(|| format!("Failed to remove {}", file.display()))?;
         }
+        removed.insert(session.session_id.clone());
+    }
+    remote_sessions.retain(|s| !index.is_deleted(&s.session_id));
+    temp_branch_sessions.retain(|s| !index.is_deleted(&s.session_id));
 
-        println!("\n{}", "Pull complete!".green().bold());
+    let local_files: Vec<_> = local_sessions
+        .iter()
+        .filter(|s| index.is_deleted(&s.session_id))
+        .map(|s| std::path::PathBuf::from(&s.file_path))
+        .filter(|p| p.exists())
+        .collect();
+    removed.extend(
+        local_sessions
+            .iter()
+            .filter(|s| index.is_deleted(&s.session_id))
+            .map(|s| s.session_id.clone()),
+    );
+
+    if changed {
+        index.save(sync_repo)?;
+    }
+    if !removed.is_empty() && verbosity >= VerbosityLevel::Normal {
+        println!(
+            "  {} Removing {} sessions deleted on some machine",
+            glyphs::ok().green(),
+            removed.len()
+        );
     }
+    Ok(local_files)
+}
+
+/// Fail if files fetched for `<remote>/<branch>` don't match its checksum
+/// manifest
+///
+/// Only files changed since the common ancestor with the local branch are
+/// hashed; the local temp branch keeps this machine's sessions meanwhile.
+fn verify_fetched_manifest(
+    repo: &dyn scm::Scm,
+    remote: &str,
+    branch: &str,
+    verbosity: crate::VerbosityLevel,
+) -> Result<()> {
+    use crate::VerbosityLevel;
 
+    let remote_rev = format!("{remote}/{branch}");
+    let since = repo.merge_base("HEAD", &remote_rev).ok();
+    let Some(report) = crate::manifest::verify_at(repo, &remote_rev, since.as_deref())? else {
+        return Ok(());
+    };
+    if !report.unlisted.is_empty() {
+        log::warn!(
+            "{} files in {} are not in its checksum manifest: {:?}",
+            report.unlisted.len(),
+            remote_rev,
+            report.unlisted
+        );
+    }
+    if report.is_ok() {
+        if verbosity >= VerbosityLevel::Normal {
+            println!(
+                "  {} Verified {} fetched files against the checksum manifest",
+                glyphs::ok().green(),
+                report.checked
+            );
+        }
+        return Ok(());
+    }
+
+    for path in &report.mismatched {
+        println!("  {} {} does not match its checksum", glyphs::fail().red(), path);
+    }
+    for path in &report.missing {
+        println!("  {} {} is in the manifest but was not fetched", glyphs::fail().red(), path);
+    }
+    anyhow::bail!(
+        "{} fetched files failed the checksum manifest check; nothing was merged. \
+         Run 'verify-sync --manifest --against {}' to inspect them",
+        report.mismatched.len() + report.missing.len(),
+        remote_rev
+    );
+}
+
+/// Check out the main branch again if an interrupted pull left the repo on
+/// one of its temp branches
+///
+/// Uncommitted changes are committed to the temp branch first, and the temp
+/// branch is kept like any other, so nothing the interrupted pull saved is
+/// lost. The main branch is `main` or `master`, or the only other branch.
+fn return_from_temp_branch(
+    repo: &dyn scm::Scm,
+    filter: &FilterConfig,
+    verbosity: crate::VerbosityLevel,
+) -> Result<()> {
+    use crate::VerbosityLevel;
+
+    let current = repo.current_branch()?;
+    if !current.starts_with(TEMP_BRANCH_PREFIX) {
+        return Ok(());
+    }
+
+    let branches = repo.list_branches()?;
+    let others: Vec<&String> = branches
+        .iter()
+        .filter(|b| !b.starts_with(TEMP_BRANCH_PREFIX))
+        .collect();
+    let main = ["main", "master"]
+        .iter()
+        .find_map(|name| others.iter().find(|b| b.as_str() == *name))
+        .or_else(|| others.first().filter(|_| others.len() == 1));
+    let Some(main) = main else {
+        anyhow::bail!(
+            "The sync repository is on temp branch '{}', left by an interrupted pull, \
+             and its main branch can't be determined. Check out the main branch and retry.",
+            current
+        );
+    };
+
+    repo.stage_paths(&super::managed_paths(filter))?;
+    if repo.has_staged_changes()? {
+        repo.commit("Save state of interrupted pull")?;
+    }
+    repo.checkout(main)
+        .with_context(|| format!("Failed to check out '{main}' after an interrupted pull"))?;
+    if verbosity >= VerbosityLevel::Normal {
+        println!(
+            "  {} A previous pull was interrupted; returned to '{}' (its state is kept on '{}')",
+            "!".yellow().bold(),
+            main,
+            current
+        );
+    }
     Ok(())
 }
 
@@ -873,10 +2264,10 @@ fn cleanup_temp_branch(
 
     // Skip cleanup if retention is enabled and this isn't a forced cleanup
     if retention_hours > 0 && !force {
-        if verbosity != VerbosityLevel::Quiet {
+        if verbosity >= VerbosityLevel::Normal {
             println!(
                 "  {} Temp branch {} retained for {} hours",
-                "ℹ".cyan(),
+                glyphs::info().cyan(),
                 temp_branch,
                 retention_hours
             );
@@ -884,7 +2275,7 @@ fn cleanup_temp_branch(
         return Ok(());
     }
 
-    if verbosity != VerbosityLevel::Quiet {
+    if verbosity >= VerbosityLevel::Normal {
         println!("  {} temp branch...", "Cleaning up".cyan());
     }
 
@@ -892,8 +2283,8 @@ fn cleanup_temp_branch(
     if has_remote {
         match repo.delete_remote_branch("origin", temp_branch) {
             Ok(_) => {
-                if verbosity != VerbosityLevel::Quiet {
-                    println!("  {} Deleted origin/{}", "✓".green(), temp_branch);
+                if verbosity >= VerbosityLevel::Normal {
+                    println!("  {} Deleted origin/{}", glyphs::ok().green(), temp_branch);
                 }
             }
             Err(e) => {
@@ -905,8 +2296,8 @@ fn cleanup_temp_branch(
     // Delete local branch
     match repo.delete_branch(temp_branch) {
         Ok(_) => {
-            if verbosity != VerbosityLevel::Quiet {
-                println!("  {} Deleted local branch {}", "✓".green(), temp_branch);
+            if verbosity >= VerbosityLevel::Normal {
+                println!("  {} Deleted local branch {}", glyphs::ok().green(), temp_branch);
             }
         }
         Err(e) => {
@@ -917,78 +2308,103 @@ fn cleanup_temp_branch(
     Ok(())
 }
 
-/// Clean up old temporary branches that have exceeded their retention period
-fn cleanup_old_temp_branches(
-    repo: &dyn scm::Scm,
-    has_remote: bool,
-    retention_hours: u32,
-    verbosity: crate::VerbosityLevel,
-) -> Result<()> {
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::{MockScm, Scm};
     use crate::VerbosityLevel;
 
-    // If retention is 0, branches are deleted immediately so nothing to clean up
-    if retention_hours == 0 {
-        return Ok(());
+    fn repo_with_temp_branches(branches: &[&str]) -> MockScm {
+        let repo = MockScm::new();
+        repo.write_file("README.md", "# sync\n");
+        repo.stage_all().unwrap();
+        repo.commit("Initial commit").unwrap();
+        repo.add_remote("origin", "mock://origin").unwrap();
+        for branch in branches {
+            repo.create_branch(branch).unwrap();
+            repo.push("origin", branch).unwrap();
+        }
+        repo
     }
 
-    // Get list of local branches matching our temp branch pattern
-    let branches = match repo.list_branches() {
-        Ok(b) => b,
-        Err(e) => {
-            log::debug!("Failed to list branches for cleanup: {}", e);
-            return Ok(());
-        }
-    };
+    #[test]
+    fn test_cleanup_temp_branch_survives_remote_failure() {
+        let branch = "sync-local-laptop-20250101-120000-abc123";
+        let repo = repo_with_temp_branches(&[branch]);
 
-    let now = chrono::Utc::now();
-    let retention_duration = chrono::Duration::hours(retention_hours as i64);
-    let mut cleaned = 0;
+        // Retained unless forced
+        cleanup_temp_branch(&repo, branch, true, VerbosityLevel::Quiet, 24, false).unwrap();
+        assert!(repo.branch_exists(branch));
 
-    for branch in branches {
-        // Only process our temp branches (format: sync-local-YYYYMMDD-HHMMSS)
-        if !branch.starts_with("sync-local-") {
-            continue;
-        }
-
-        // Parse timestamp from branch name
-        let timestamp_part = branch.strip_prefix("sync-local-").unwrap_or(&branch);
-        if let Ok(branch_time) = chrono::NaiveDateTime::parse_from_str(timestamp_part, "%Y%m%d-%H%M%S")
-        {
-            let branch_time_utc = chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(
-                branch_time,
-                chrono::Utc,
-            );
+        // A failed remote delete still removes the local branch
+        repo.fail_next_pushes(1);
+        cleanup_temp_branch(&repo, branch, true, VerbosityLevel::Quiet, 24, true).unwrap();
+        assert!(!repo.list_branches().unwrap().iter().any(|b| b == branch));
+        assert!(repo.remote_branches("origin").iter().any(|b| b == branch));
+    }
 
-            // Check if branch has exceeded retention period
-            if now.signed_duration_since(branch_time_utc) > retention_duration {
-                log::debug!("Cleaning up old temp branch: {}", branch);
+    #[test]
+    fn test_return_from_interrupted_pull() {
+        let temp_branch = "sync-local-laptop-20250101-120000-abc123";
+        let repo = repo_with_temp_branches(&[temp_branch]);
+        repo.checkout(temp_branch).unwrap();
+        repo.write_file("projects/p/half-merged.jsonl", "x\n");
+
+        return_from_temp_branch(&repo, &FilterConfig::default(), VerbosityLevel::Quiet).unwrap();
+        assert_eq!(repo.current_branch().unwrap(), "main");
+        assert_eq!(repo.file("projects/p/half-merged.jsonl"), None);
+        assert_eq!(
+            repo.log(temp_branch),
+            ["Save state of interrupted pull", "Initial commit"]
+        );
 
-                // Delete remote branch first
-                if has_remote {
-                    if let Err(e) = repo.delete_remote_branch("origin", &branch) {
-                        log::debug!("Failed to delete remote branch {}: {}", branch, e);
-                    }
-                }
+        // Nothing to do when already on the main branch
+        return_from_temp_branch(&repo, &FilterConfig::default(), VerbosityLevel::Quiet).unwrap();
+        assert_eq!(repo.current_branch().unwrap(), "main");
+    }
 
-                // Delete local branch
-                if let Err(e) = repo.delete_branch(&branch) {
-                    log::debug!("Failed to delete local branch {}: {}", branch, e);
-                } else {
-                    cleaned += 1;
-                }
-            }
-        }
+    #[test]
+    fn test_pull_summary_line() {
+        assert_eq!(
+            pull_summary_line(3, 2, 1, 0),
+            "pull: +3 added, 2 modified, 1 fork, 0 errors"
+        );
     }
 
-    if cleaned > 0 && verbosity != VerbosityLevel::Quiet {
-        println!(
-            "  {} Cleaned up {} old temp branch{}",
-            "✓".green(),
-            cleaned,
-            if cleaned == 1 { "" } else { "es" }
+    #[test]
+    fn test_project_heading_counts() {
+        let summary = |operation| ConversationSummary {
+            session_id: "s1".to_string(),
+            project_path: "-home-me-app/s1.jsonl".to_string(),
+            timestamp: None,
+            message_count: 1,
+            operation,
+        };
+        let added = summary(SyncOperation::Added);
+        let forked = summary(SyncOperation::Conflict);
+        assert_eq!(
+            operation_counts(&[&added, &forked, &added]),
+            "2 added, 1 forked"
         );
     }
 
-    Ok(())
-}
+    #[test]
+    fn test_merge_commit_message_lists_forks() {
+        assert!(!merge_commit_message("temp", &[]).contains('\n'));
 
+        let stats = MergeStats {
+            local_messages: 4,
+            remote_messages: 5,
+            merged_messages: 7,
+            branches_detected: 2,
+            ..Default::default()
+        };
+        let message = merge_commit_message("temp", &[("abc".to_string(), stats)]);
+        let (subject, body) = message.split_once("\n\n").unwrap();
+        assert!(subject.starts_with("Merge local changes from temp"));
+        assert_eq!(
+            body,
+            "Forked sessions:\n- abc: 4 local + 5 remote = 7 merged messages, 2 branches\n"
+        );
+    }
+}
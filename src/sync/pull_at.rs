@@ -0,0 +1,309 @@
+//! Pulling the sync repo's state as of an earlier commit or tag
+//!
+//! `pull --at <ref>` merges the sessions the sync repo held at `ref` into
+//! `.claude` without touching the sync repo's branch. It only ever adds:
+//! sessions missing locally are written, and entries missing from a local
+//! session are appended. That makes it safe for recovering the corpus as it
+//! was before a bad sync, whatever has happened locally since.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::lock::SyncLock;
+use crate::parser::{append_entries_to_file_with, missing_entries, ConversationSession};
+use crate::scm::{self, Scm};
+
+use super::discovery::{claude_projects_dir, discover_sessions};
+use super::rel_path::{ClaudeRelPath, RepoRelPath};
+use super::state::SyncState;
+
+/// What a pull at a revision brought back
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct RestoreStats {
+    pub sessions_added: usize,
+    pub sessions_appended: usize,
+    pub entries_appended: usize,
+}
+
+/// Write the session files under `projects_subdir` at `rev` into `dest`
+fn materialize_projects(
+    repo: &dyn Scm,
+    rev: &str,
+    projects_subdir: &str,
+    dest: &Path,
+) -> Result<usize> {
+    let prefix = format!("{}/", projects_subdir.trim_end_matches('/'));
+    let mut files = 0;
+    for file in repo.list_files_at(rev, projects_subdir)? {
+        let Some(relative) = file.strip_prefix(&prefix) else {
+            continue;
+        };
+        let path = RepoRelPath::from_relative(relative)?.under(dest);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        fs::write(&path, repo.read_file_at(rev, &file)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        files += 1;
+    }
+    Ok(files)
+}
+
+/// Merge `sessions` (found under `source_dir`) into `claude_dir`, adding
+/// only what is missing locally
+///
+/// A session already present locally under another project directory is
+/// appended to in place rather than copied next to it. Local files are
+/// copied to `backup_dir` before being appended to.
+pub(crate) fn restore_sessions(
+    sessions: &[ConversationSession],
+    source_dir: &Path,
+    claude_dir: &Path,
+    filter: &FilterConfig,
+    backup_dir: &Path,
+    verbosity: crate::VerbosityLevel,
+) -> Result<RestoreStats> {
+    let key_strategies = filter.content_key_strategies()?;
+    let local_sessions = discover_sessions(claude_dir, filter)?;
+    let mut local_by_id: HashMap<&str, &ConversationSession> = HashMap::new();
+    let mut local_by_path: HashMap<&Path, &ConversationSession> = HashMap::new();
+    for session in &local_sessions {
+        local_by_path.insert(Path::new(&session.file_path), session);
+        if !session.is_agent_file() {
+            local_by_id
+                .entry(session.session_id.as_str())
+                .or_insert(session);
+        }
+    }
+
+    let mut stats = RestoreStats::default();
+    for session in sessions {
+        let target = RepoRelPath::new(source_dir, Path::new(&session.file_path))?
+            .to_claude()
+            .under(claude_dir);
+        let local = local_by_path.get(target.as_path()).copied().or_else(|| {
+            (!session.is_agent_file())
+                .then(|| local_by_id.get(session.session_id.as_str()).copied())
+                .flatten()
+        });
+
+        let Some(local) = local else {
+            if target.exists() {
+                // Unparseable local file: leave it for the user to inspect
+                log::warn!("Not restoring over unreadable {}", target.display());
+                continue;
+            }
+            session.write_to_file_with(&target, filter.preserve_raw_lines)?;
+            stats.sessions_added += 1;
+            if verbosity == crate::VerbosityLevel::Verbose {
+                println!(
+                    "    {} restored session {}",
+                    glyphs::detail().dimmed(),
+                    session.session_id
+                );
+            }
+            continue;
+        };
+
+        let entries = missing_entries(&local.entries, &session.entries, &key_strategies);
+        if entries.is_empty() {
+            continue;
+        }
+        let local_path = Path::new(&local.file_path);
+        let local_relative = ClaudeRelPath::new(claude_dir, local_path)?;
+        crate::backups::backup_file(backup_dir, local_path, local_relative.as_path())?;
+        append_entries_to_file_with(local_path, &entries, filter.preserve_raw_lines)?;
+        stats.entries_appended += entries.len();
+        stats.sessions_appended += 1;
+        if verbosity == crate::VerbosityLevel::Verbose {
+            println!(
+                "    {} +{} entries to {}",
+                glyphs::detail().dimmed(),
+                entries.len(),
+                session.session_id
+            );
+        }
+    }
+    Ok(stats)
+}
+
+/// Read the sessions at `rev` into `tree` and restore them into `claude_dir`,
+/// returning the stats and the backup directory used
+fn restore_revision(
+    repo: &dyn Scm,
+    rev: &str,
+    projects_subdir: &str,
+    tree: &Path,
+    claude_dir: &Path,
+    filter: &FilterConfig,
+    verbosity: crate::VerbosityLevel,
+) -> Result<(RestoreStats, PathBuf)> {
+    let files = materialize_projects(repo, rev, projects_subdir, tree)
+        .with_context(|| format!("Failed to read the sync repo at {rev}"))?;
+    let sessions = discover_sessions(tree, filter)?;
+    if verbosity >= crate::VerbosityLevel::Normal {
+        println!(
+            "  {} Found {} sessions ({} files) at {}",
+            glyphs::info().cyan(),
+            sessions.len(),
+            files,
+            rev
+        );
+    }
+
+    let backup_dir = crate::backups::new_run_dir()?;
+    let stats = restore_sessions(&sessions, tree, claude_dir, filter, &backup_dir, verbosity)?;
+
+    Ok((stats, backup_dir))
+}
+
+/// Merge the sessions the sync repo held at `rev` into `.claude`
+///
+/// The sync repo itself is left as it is; `rev` may be any commit, tag, or
+/// `origin/<branch>` (fetched first).
+pub fn pull_at_revision(rev: &str, verbosity: crate::VerbosityLevel) -> Result<()> {
+    let _lock = SyncLock::acquire()?;
+    pull_at_locked(rev, verbosity)
+}
+
+/// [`pull_at_revision`] while the caller holds the sync lock
+pub(crate) fn pull_at_locked(rev: &str, verbosity: crate::VerbosityLevel) -> Result<()> {
+    use crate::VerbosityLevel;
+
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let filter = FilterConfig::load()?;
+    let claude_dir = claude_projects_dir()?;
+
+    if let Some((remote, _)) = rev.split_once('/') {
+        if repo.has_remote(remote) {
+            repo.fetch(remote)
+                .with_context(|| format!("Failed to fetch from {remote}"))?;
+        }
+    }
+
+    if verbosity >= VerbosityLevel::Normal {
+        println!(
+            "{} {}",
+            "Pulling Claude Code history as of".cyan().bold(),
+            rev.bold()
+        );
+    }
+
+    let projects_subdir = filter
+        .projects_subdir()
+        .to_string_lossy()
+        .replace('\\', "/");
+    let tree = std::env::temp_dir().join(format!("claude-code-sync-at-{}", std::process::id()));
+    let result = restore_revision(
+        repo.as_ref(),
+        rev,
+        &projects_subdir,
+        &tree,
+        &claude_dir,
+        &filter,
+        verbosity,
+    );
+    if let Err(e) = fs::remove_dir_all(&tree) {
+        log::debug!("Failed to remove {}: {}", tree.display(), e);
+    }
+    let (stats, backup_dir) = result?;
+
+    if verbosity >= VerbosityLevel::Normal {
+        if stats == RestoreStats::default() {
+            println!("  {} Nothing missing locally", glyphs::ok().green());
+        } else {
+            println!(
+                "  {} Restored {} sessions, appended {} entries to {} sessions",
+                glyphs::ok().green(),
+                stats.sessions_added,
+                stats.entries_appended,
+                stats.sessions_appended
+            );
+            if backup_dir.exists() {
+                println!(
+                    "  {} Previous versions backed up to {}",
+                    glyphs::info().cyan(),
+                    backup_dir.display()
+                );
+            }
+        }
+    } else if verbosity == VerbosityLevel::Summary {
+        println!(
+            "pull --at {}: {} restored, +{} entries",
+            rev, stats.sessions_added, stats.entries_appended
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::MockScm;
+
+    const A: &str =
+        r#"{"type":"user","uuid":"a","sessionId":"s1","timestamp":"2025-01-01T00:00:00Z"}"#;
+    const B: &str =
+        r#"{"type":"user","uuid":"b","sessionId":"s1","timestamp":"2025-01-01T00:01:00Z"}"#;
+    const C: &str =
+        r#"{"type":"user","uuid":"c","sessionId":"s2","timestamp":"2025-01-01T00:02:00Z"}"#;
+
+    #[test]
+    fn test_restore_sessions_only_adds() {
+        let repo = MockScm::new();
+        repo.write_file("projects/-p/s1.jsonl", format!("{A}\n{B}\n"));
+        repo.write_file("projects/-p/s2.jsonl", format!("{C}\n"));
+        repo.stage_all().unwrap();
+        repo.commit("Good state").unwrap();
+
+        let temp = tempfile::tempdir().unwrap();
+        let tree = temp.path().join("tree");
+        let claude = temp.path().join("claude");
+        assert_eq!(
+            materialize_projects(&repo, "HEAD", "projects", &tree).unwrap(),
+            2
+        );
+        let sessions = discover_sessions(&tree, &FilterConfig::default()).unwrap();
+
+        // Locally s1 lost an entry but gained one, and s2 is gone
+        let local = claude.join("-p/s1.jsonl");
+        fs::create_dir_all(local.parent().unwrap()).unwrap();
+        let newer =
+            r#"{"type":"user","uuid":"d","sessionId":"s1","timestamp":"2025-01-02T00:00:00Z"}"#;
+        fs::write(&local, format!("{A}\n{newer}\n")).unwrap();
+
+        let stats = restore_sessions(
+            &sessions,
+            &tree,
+            &claude,
+            &FilterConfig::default(),
+            &temp.path().join("backup"),
+            crate::VerbosityLevel::Quiet,
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            RestoreStats {
+                sessions_added: 1,
+                sessions_appended: 1,
+                entries_appended: 1,
+            }
+        );
+        let uuids: Vec<_> = ConversationSession::from_file(&local)
+            .unwrap()
+            .entries
+            .iter()
+            .filter_map(|e| e.uuid.clone())
+            .collect();
+        assert_eq!(uuids, ["a", "d", "b"]);
+        assert!(claude.join("-p/s2.jsonl").exists());
+        assert!(temp.path().join("backup/-p/s1.jsonl").exists());
+    }
+}
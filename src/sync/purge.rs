@@ -0,0 +1,107 @@
+//! Catching up with history rewritten by `purge` on another machine
+
+use anyhow::Result;
+use colored::Colorize;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::purge::{PurgeIndex, PURGE_INDEX_FILE};
+use crate::scm::Scm;
+use crate::tombstones::session_paths;
+use crate::trash::Trash;
+
+use super::history_merge::remove_history_sessions;
+use super::temp_branch::TEMP_BRANCH_PREFIX;
+
+/// Delete this machine's temp branches, and with `remote` set every
+/// machine's temp branches on origin, returning how many were deleted
+///
+/// Temp branches are cut from the sync branch, so after a purge they still
+/// hold the purged history.
+pub fn remove_temp_branches(repo: &dyn Scm, remote: bool) -> Result<usize> {
+    let current = repo.current_branch()?;
+    let mut removed = 0;
+    for branch in repo.list_branches()? {
+        if branch.starts_with(TEMP_BRANCH_PREFIX) && branch != current {
+            repo.delete_branch(&branch)?;
+            removed += 1;
+        }
+    }
+    if remote {
+        for branch in repo.list_remote_branches("origin")? {
+            if branch.starts_with(TEMP_BRANCH_PREFIX) {
+                repo.delete_remote_branch("origin", &branch)?;
+                removed += 1;
+            }
+        }
+    }
+    Ok(removed)
+}
+
+/// Reset `branch` to origin's if origin's history was rewritten by a purge
+/// this clone hasn't seen, returning whether it was reset
+///
+/// Merging or rebasing onto the rewritten history would bring the purged
+/// commits back. Local copies of the purged sessions are moved to the trash
+/// first, so the pull that follows doesn't push them again; anything else
+/// only in the old history is rebuilt from `.claude` by that pull. Temp
+/// branches and snapshots still pointing at the old history are deleted.
+pub fn recover_from_purge(
+    repo: &dyn Scm,
+    sync_repo: &Path,
+    branch: &str,
+    verbosity: crate::VerbosityLevel,
+) -> Result<bool> {
+    use crate::VerbosityLevel;
+
+    if repo.current_branch()? != branch {
+        return Ok(false);
+    }
+    if let Err(e) = repo.fetch("origin") {
+        log::warn!("Failed to fetch while checking for purges: {}", e);
+        return Ok(false);
+    }
+    let remote_rev = format!("origin/{branch}");
+    let Ok(content) = repo.read_file_at(&remote_rev, PURGE_INDEX_FILE) else {
+        return Ok(false);
+    };
+    let remote = PurgeIndex::parse(&String::from_utf8_lossy(&content))?;
+    let local = PurgeIndex::load(sync_repo)?;
+    let missing = local.missing_from(&remote);
+    if missing.is_empty() {
+        return Ok(false);
+    }
+
+    let mut sessions = BTreeSet::new();
+    for (_, purge) in &missing {
+        if verbosity >= VerbosityLevel::Normal {
+            println!(
+                "  {} {} rewrote the sync repo history on {} to purge {} session(s); resetting to {}",
+                "!".yellow().bold(),
+                purge.machine_id,
+                purge.purged_at.format("%Y-%m-%d %H:%M UTC"),
+                purge.sessions.len(),
+                remote_rev
+            );
+        }
+        sessions.extend(purge.sessions.iter().cloned());
+    }
+
+    let projects_dir = super::claude_projects_dir()?;
+    let trash = Trash::open()?;
+    for session_id in &sessions {
+        for path in session_paths(&projects_dir, session_id) {
+            trash.put(&path, "pull (session purged on another machine)")?;
+        }
+    }
+    let claude_dir = projects_dir.parent().unwrap_or(&projects_dir);
+    remove_history_sessions(&claude_dir.join("history.jsonl"), |id| {
+        sessions.contains(id)
+    })?;
+
+    remove_temp_branches(repo, false)?;
+    super::snapshot::remove_snapshots(repo, false)?;
+    repo.reset_hard(&remote_rev)?;
+    repo.prune_unreachable()?;
+    Ok(true)
+}
@@ -3,7 +3,9 @@ use colored::Colorize;
 use inquire::Confirm;
 
 use crate::filter::FilterConfig;
-use crate::history::{OperationHistory, OperationRecord, OperationType};
+use crate::glyphs;
+use crate::history::{OperationRecord, OperationType};
+use crate::i18n::{count, tr};
 use crate::interactive_conflict;
 use crate::lock::SyncLock;
 use crate::scm;
@@ -18,21 +20,71 @@ use super::state::SyncState;
 /// 3. Push to remote (fail on conflict - user must pull first)
 ///
 /// Note: Local ~/.claude sessions are captured during `pull`, not here.
-/// Push just pushes whatever is already in the sync repo.
+/// Push just pushes whatever is already in the sync repo. With `strict`, a
+/// failed mirror push fails the push.
 pub fn push_history(
     commit_message: Option<&str>,
     push_remote: bool,
     branch: Option<&str>,
-    _exclude_attachments: bool,
+    exclude_attachments: bool,
     interactive: bool,
+    strict: bool,
     verbosity: crate::VerbosityLevel,
 ) -> Result<()> {
     use crate::VerbosityLevel;
 
     // Acquire exclusive lock to prevent concurrent sync operations
     let _lock = SyncLock::acquire()?;
+    // A lease on the remote makes other machines' syncs wait for this push
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let _lease = if push_remote {
+        super::remote_lease::RemoteLease::acquire_configured(repo.as_ref(), &state, verbosity)?
+    } else {
+        None
+    };
+
+    let Some(record) = push_locked(
+        commit_message,
+        push_remote,
+        branch,
+        exclude_attachments,
+        interactive,
+        verbosity,
+    )?
+    else {
+        return Ok(());
+    };
+    let failures = if strict {
+        record.strict_failures()
+    } else {
+        Vec::new()
+    };
+    super::save_operation_record(record);
+    super::fail_if_incomplete(&failures)?;
+
+    if verbosity >= VerbosityLevel::Normal {
+        println!("\n{}", tr("push.complete", &[]).green().bold());
+    }
+
+    Ok(())
+}
 
-    if verbosity != VerbosityLevel::Quiet {
+/// Push while the caller holds the sync lock
+///
+/// Returns the operation record for the caller to save, or `None` if nothing
+/// was pushed (no changes, or cancelled).
+pub(crate) fn push_locked(
+    commit_message: Option<&str>,
+    push_remote: bool,
+    branch: Option<&str>,
+    _exclude_attachments: bool,
+    interactive: bool,
+    verbosity: crate::VerbosityLevel,
+) -> Result<Option<OperationRecord>> {
+    use crate::VerbosityLevel;
+
+    if verbosity >= VerbosityLevel::Normal {
         println!("{}", "Pushing Claude Code history...".cyan().bold());
     }
 
@@ -40,9 +92,18 @@ pub fn push_history(
     let repo = scm::open(&state.sync_repo_path)?;
     let filter = FilterConfig::load()?;
 
+    // Machines that only push would otherwise never clean up their temp branches
+    super::cleanup_old_temp_branches(
+        repo.as_ref(),
+        &crate::config::ConfigManager::machine_id()?,
+        push_remote && state.has_remote,
+        filter.temp_branch_retention_hours,
+        verbosity,
+    )?;
+
     // Set up LFS if enabled
     if filter.enable_lfs {
-        if verbosity != VerbosityLevel::Quiet {
+        if verbosity >= VerbosityLevel::Normal {
             println!("  {} Git LFS...", "Configuring".cyan());
         }
         scm::lfs::setup(&state.sync_repo_path, &filter.lfs_patterns)
@@ -55,16 +116,44 @@ pub fn push_history(
         .or_else(|| repo.current_branch().ok())
         .unwrap_or_else(|| "main".to_string());
 
-    // Stage any uncommitted changes
-    repo.stage_all()?;
+    // Stage any uncommitted changes, once hand edits elsewhere are confirmed
+    super::worktree::confirm_unexpected_changes(repo.as_ref(), &filter, verbosity)?;
+    let projects_dir = state.sync_repo_path.join(filter.projects_subdir());
+    // Entries other machines uploaded to a plain-directory remote are merged
+    // in first, so the upload below doesn't overwrite them
+    if let (true, Some(url)) = (push_remote, &state.rsync_remote) {
+        let backup_dir = crate::backups::new_run_dir()?;
+        super::rsync::merge_remote_sessions(url, &filter, &projects_dir, &backup_dir, verbosity)?;
+    }
+    // Conflict copies a folder-sync tool left in the sync repo
+    super::conflict_copies::fold_conflict_copies(
+        &projects_dir,
+        &projects_dir,
+        &filter,
+        &crate::backups::new_run_dir()?,
+        &super::active_sessions::ActiveSessionGuard::default(),
+        verbosity,
+    )?;
+    super::split_oversized_sessions(&projects_dir, &filter, verbosity)?;
+    if filter.project_index {
+        let changed = super::project_index::write_project_indexes(&projects_dir)?;
+        if changed > 0 && verbosity >= VerbosityLevel::Verbose {
+            println!("  {} Updated {} project indexes", glyphs::ok().green(), changed);
+        }
+    }
+    if filter.checksum_manifest {
+        crate::manifest::write_manifest(&state.sync_repo_path)?;
+    }
+    repo.stage_paths(&super::managed_paths(&filter))?;
+    super::fail_point::check("push stage")?;
 
-    let has_changes = repo.has_changes()?;
+    let has_changes = repo.has_staged_changes()?;
     let commit_before_push = repo.current_commit_hash().ok();
 
     if has_changes {
         // Show what will be committed
-        if verbosity != VerbosityLevel::Quiet {
-            println!("  {} Changes staged for commit", "✓".green());
+        if verbosity >= VerbosityLevel::Normal {
+            println!("  {} Changes staged for commit", glyphs::ok().green());
         }
 
         // Interactive confirmation
@@ -77,7 +166,7 @@ pub fn push_history(
 
             if !confirm {
                 println!("\n{}", "Push cancelled.".yellow());
-                return Ok(());
+                return Ok(None);
             }
         }
 
@@ -88,59 +177,141 @@ pub fn push_history(
         );
         let message = commit_message.unwrap_or(&default_message);
 
-        if verbosity != VerbosityLevel::Quiet {
+        if verbosity >= VerbosityLevel::Normal {
             println!("  {} changes...", "Committing".cyan());
         }
-        repo.commit(message)?;
-        if verbosity != VerbosityLevel::Quiet {
-            println!("  {} Committed: {}", "✓".green(), message);
+        let commits =
+            super::batching::commit_staged(repo.as_ref(), &state.sync_repo_path, &filter, message)?;
+        if verbosity >= VerbosityLevel::Normal {
+            if commits > 1 {
+                println!("  {} Committed {} commits: {}", glyphs::ok().green(), commits, message);
+            } else {
+                println!("  {} Committed: {}", glyphs::ok().green(), message);
+            }
         }
-    } else if verbosity != VerbosityLevel::Quiet {
-        println!("  {} No new changes to commit", "✓".green());
+    } else if verbosity >= VerbosityLevel::Normal {
+        println!("  {} No new changes to commit", glyphs::ok().green());
     }
+    super::fail_point::check("push commit")?;
+
+    // Upload the session files to a plain-directory remote if configured
+    let uploaded = match (&state.rsync_remote, push_remote) {
+        (Some(url), true) => {
+            super::rsync::upload_sessions(url, &filter, &state.sync_repo_path, verbosity)?;
+            true
+        }
+        _ => false,
+    };
 
     // Push to remote if configured
-    if push_remote && state.has_remote {
-        if verbosity != VerbosityLevel::Quiet {
+    let mut mirror_failures = Vec::new();
+    let pushed = push_remote && state.has_remote;
+    if pushed {
+        if verbosity >= VerbosityLevel::Normal {
             println!("  {} to remote...", "Pushing".cyan());
         }
 
-        match repo.push("origin", &branch_name) {
-            Ok(_) => {
-                if verbosity != VerbosityLevel::Quiet {
-                    println!("  {} Pushed to origin/{}", "✓".green(), branch_name);
+        let mut attempt = 0;
+        loop {
+            match crate::audit::push_audited(repo.as_ref(), "origin", &branch_name, &branch_name) {
+                Ok(_) => {
+                    if verbosity >= VerbosityLevel::Normal {
+                        println!("  {} Pushed to origin/{}", glyphs::ok().green(), branch_name);
+                    }
+                    mirror_failures =
+                        push_to_mirrors(repo.as_ref(), &filter.mirror_remotes, &branch_name, verbosity);
+                    super::fail_point::check("push remote")?;
+                    break;
                 }
-            }
-            Err(e) => {
-                let error_msg = e.to_string();
-                if error_msg.contains("non-fast-forward")
-                    || error_msg.contains("fetch first")
-                    || error_msg.contains("rejected")
-                    || error_msg.contains("failed to push")
-                {
-                    println!(
-                        "\n{} Remote has changes that aren't in your local repository.",
-                        "!".yellow().bold()
-                    );
-                    println!(
-                        "{} Run {} first to merge remote changes, then push again.",
-                        "→".cyan(),
-                        "claude-code-sync pull".bold()
-                    );
-                    return Err(anyhow::anyhow!(
-                        "Push rejected: remote has new commits. Run 'claude-code-sync pull' first."
-                    ));
-                } else {
-                    return Err(e.context("Failed to push to remote"));
+                Err(e) if is_rejection(&e) && attempt < filter.push_retries => {
+                    // Another machine pushed first: merge its commits and try again,
+                    // backing off so machines retrying together drift apart
+                    attempt += 1;
+                    let delay = retry_delay(attempt);
+                    if verbosity >= VerbosityLevel::Normal {
+                        println!(
+                            "  {} Remote has new commits; merging them and retrying in {:.1}s ({}/{})",
+                            "!".yellow().bold(),
+                            delay.as_secs_f64(),
+                            attempt,
+                            filter.push_retries
+                        );
+                    }
+                    std::thread::sleep(delay);
+                    let pull_verbosity = if verbosity == VerbosityLevel::Verbose {
+                        verbosity
+                    } else {
+                        VerbosityLevel::Quiet
+                    };
+                    if let Some(record) = super::pull::pull_locked(
+                        true,
+                        Some(&branch_name),
+                        false,
+                        &[],
+                        false,
+                        "origin",
+                        pull_verbosity,
+                    )? {
+                        super::save_operation_record(record);
+                    }
+                }
+                Err(e) => {
+                    if is_rejection(&e) {
+                        println!(
+                            "\n{} Remote has changes that aren't in your local repository.",
+                            "!".yellow().bold()
+                        );
+                        println!(
+                            "{} Run {} first to merge remote changes, then push again.",
+                            glyphs::arrow().cyan(),
+                            "claude-code-sync pull".bold()
+                        );
+                        return Err(anyhow::anyhow!(
+                            "Push rejected: remote has new commits. Run 'claude-code-sync pull' first."
+                        ));
+                    } else {
+                        return Err(e.context("Failed to push to remote"));
+                    }
                 }
             }
         }
-    } else if !has_changes {
+    } else if !has_changes && !uploaded {
         // No remote and no local changes - nothing to do
-        if verbosity != VerbosityLevel::Quiet {
-            println!("  {} No changes to push", "✓".green());
+        if verbosity >= VerbosityLevel::Normal {
+            println!("  {} {}", glyphs::ok().green(), tr("push.no_changes", &[]));
+        } else if verbosity == VerbosityLevel::Summary {
+            println!("{}", tr("push.nothing_line", &[]));
         }
-        return Ok(());
+        return Ok(None);
+    }
+
+    if verbosity == VerbosityLevel::Summary {
+        let committed = tr(
+            if has_changes {
+                "push.committed"
+            } else {
+                "push.no_new_changes"
+            },
+            &[],
+        );
+        let remote = if pushed {
+            tr("push.pushed_to", &[("branch", &branch_name)])
+        } else if uploaded {
+            tr("push.uploaded", &[])
+        } else {
+            tr("push.not_pushed", &[])
+        };
+        println!(
+            "{}",
+            tr(
+                "push.summary_line",
+                &[
+                    ("committed", &committed),
+                    ("pushed", &remote),
+                    ("errors", &count("count.errors", mirror_failures.len())),
+                ]
+            )
+        );
     }
 
     // Record operation in history
@@ -150,24 +321,65 @@ pub fn push_history(
         Vec::new(), // No detailed conversation tracking in simplified push
     );
     operation_record.commit_hash = commit_before_push;
+    operation_record.network_failures = mirror_failures;
 
-    let mut history = match OperationHistory::load() {
-        Ok(h) => h,
-        Err(e) => {
-            log::warn!("Failed to load operation history: {}", e);
-            OperationHistory::default()
-        }
-    };
+    Ok(Some(operation_record))
+}
 
-    if let Err(e) = history.add_operation(operation_record) {
-        log::warn!("Failed to save operation to history: {}", e);
-    }
+/// Whether a push failed because the remote has commits this repo lacks
+fn is_rejection(error: &anyhow::Error) -> bool {
+    let error_msg = error.to_string();
+    error_msg.contains("non-fast-forward")
+        || error_msg.contains("fetch first")
+        || error_msg.contains("rejected")
+        || error_msg.contains("failed to push")
+}
 
-    if verbosity == VerbosityLevel::Quiet {
-        println!("Push complete");
-    } else {
-        println!("\n{}", "Push complete!".green().bold());
-    }
+/// Wait before push retry `attempt` (from 1): doubling from a second, plus
+/// up to a second of jitter
+fn retry_delay(attempt: u32) -> std::time::Duration {
+    let base = 1000u64 << (attempt - 1).min(6);
+    let jitter = (uuid::Uuid::new_v4().as_u128() % 1000) as u64;
+    std::time::Duration::from_millis(base + jitter)
+}
 
-    Ok(())
+/// Push `branch` to each mirror remote after a successful push to origin
+///
+/// Mirrors are independent: a failure is reported and the remaining mirrors
+/// are still pushed, and the push as a whole still succeeds. Returns a
+/// description of each failure.
+fn push_to_mirrors(
+    repo: &dyn scm::Scm,
+    mirrors: &[String],
+    branch: &str,
+    verbosity: crate::VerbosityLevel,
+) -> Vec<String> {
+    use crate::VerbosityLevel;
+
+    let mut failed = Vec::new();
+    for mirror in mirrors {
+        let result = if repo.has_remote(mirror) {
+            crate::audit::push_audited(repo, mirror, branch, branch)
+        } else {
+            Err(anyhow::anyhow!("remote '{}' is not configured", mirror))
+        };
+        match result {
+            Ok(()) => {
+                if verbosity >= VerbosityLevel::Normal {
+                    println!("  {} Mirrored to {}/{}", glyphs::ok().green(), mirror, branch);
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to push to mirror {}: {}", mirror, e);
+                println!(
+                    "  {} Could not push to mirror {}: {}",
+                    "!".yellow().bold(),
+                    mirror,
+                    e
+                );
+                failed.push(format!("push to mirror {mirror}: {e}"));
+            }
+        }
+    }
+    failed
 }
@@ -0,0 +1,198 @@
+//! Checking that a pull left every session on both sides
+//!
+//! Once a pull has gone through, each session in `.claude` should also be in
+//! the sync repo and the other way around, except those left out on purpose:
+//! filtered, marked local-only, excluded by tags or a machine filter, or a
+//! teammate's read-only copy. A session on only one side means discovery or
+//! the mapping between the two directories went wrong somewhere, which
+//! would otherwise go unnoticed until the session is missed on another
+//! machine. Sessions are compared by file name, so the check only walks the
+//! two directories.
+
+use colored::Colorize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+
+/// Sessions found on only one side after a pull
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct OneSidedSessions {
+    /// Session files in `.claude` with no copy in the sync repo
+    pub local_only: Vec<PathBuf>,
+    /// Session files in the sync repo with no copy in `.claude`
+    pub repo_only: Vec<PathBuf>,
+}
+
+impl OneSidedSessions {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.local_only.is_empty() && self.repo_only.is_empty()
+    }
+
+    /// Warn about each session, relative to its side's directory
+    pub(crate) fn report(
+        &self,
+        claude_dir: &Path,
+        projects_dir: &Path,
+        verbosity: crate::VerbosityLevel,
+    ) {
+        for path in &self.local_only {
+            log::warn!(
+                "Session in .claude but not in the sync repo: {}",
+                path.display()
+            );
+        }
+        for path in &self.repo_only {
+            log::warn!(
+                "Session in the sync repo but not in .claude: {}",
+                path.display()
+            );
+        }
+        if self.is_empty() || verbosity < crate::VerbosityLevel::Normal {
+            return;
+        }
+        println!(
+            "  {} {} sessions are on only one side after the pull:",
+            "!".yellow().bold(),
+            self.local_only.len() + self.repo_only.len()
+        );
+        for path in &self.local_only {
+            let relative = path.strip_prefix(claude_dir).unwrap_or(path);
+            println!(
+                "    {} {} {}",
+                glyphs::detail().dimmed(),
+                relative.display(),
+                "(only in .claude)".dimmed()
+            );
+        }
+        for path in &self.repo_only {
+            let relative = path.strip_prefix(projects_dir).unwrap_or(path);
+            println!(
+                "    {} {} {}",
+                glyphs::detail().dimmed(),
+                relative.display(),
+                "(only in the sync repo)".dimmed()
+            );
+        }
+    }
+}
+
+/// Compare the sessions in `claude_dir` and `projects_dir` by ID
+///
+/// Sessions in `ignored` were left out on purpose by the pull. Local files
+/// written after `since` are new since the pull looked at `.claude`, and are
+/// picked up by the next one.
+pub(crate) fn find_one_sided(
+    claude_dir: &Path,
+    projects_dir: &Path,
+    filter: &FilterConfig,
+    ignored: &HashSet<String>,
+    since: SystemTime,
+) -> OneSidedSessions {
+    let local = session_files(claude_dir, filter, ignored, Some(since));
+    let repo = session_files(projects_dir, filter, ignored, None);
+    let only_in = |side: &BTreeMap<String, PathBuf>, other: &BTreeMap<String, PathBuf>| {
+        side.iter()
+            .filter(|(id, _)| !other.contains_key(*id))
+            .map(|(_, path)| path.clone())
+            .collect()
+    };
+    OneSidedSessions {
+        local_only: only_in(&local, &repo),
+        repo_only: only_in(&repo, &local),
+    }
+}
+
+/// Session files under `dir` the pull is expected to have on both sides,
+/// by the ID their file name gives
+fn session_files(
+    dir: &Path,
+    filter: &FilterConfig,
+    ignored: &HashSet<String>,
+    written_before: Option<SystemTime>,
+) -> BTreeMap<String, PathBuf> {
+    let mut files = BTreeMap::new();
+    for entry in WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("jsonl")
+            || super::conflict_copies::conflict_copy_base(path).is_some()
+        {
+            continue;
+        }
+        // Continuation files stand for their session's base file
+        let base =
+            crate::parts::split_part(path).map_or_else(|| path.to_path_buf(), |(base, _)| base);
+        let Some(id) = base.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        // Subagent transcripts go wherever their parent session goes
+        if id.starts_with("agent-")
+            || ignored.contains(&id)
+            || !filter.should_include(&base)
+            || filter.is_local_only(&base)
+        {
+            continue;
+        }
+        let modified = entry.metadata().ok().and_then(|m| m.modified().ok());
+        if written_before.is_some_and(|since| modified.is_some_and(|m| m > since)) {
+            continue;
+        }
+        files.entry(id).or_insert(base);
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_find_one_sided() {
+        let temp = tempfile::tempdir().unwrap();
+        let claude = temp.path().join("claude");
+        let repo = temp.path().join("repo");
+        let write = |path: PathBuf| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "{}\n").unwrap();
+        };
+        for dir in [&claude, &repo] {
+            write(dir.join("-p/both.jsonl"));
+            write(dir.join("-p/split.jsonl"));
+        }
+        write(repo.join("-p/split.part02.jsonl"));
+        write(claude.join("-p/local.jsonl"));
+        write(claude.join("-p/ignored.jsonl"));
+        write(claude.join("-p/both/subagents/agent-1.jsonl"));
+        write(claude.join("-p/s1.sync-conflict-20250101-120000-ABCDEFG.jsonl"));
+        write(repo.join("-q/remote.jsonl"));
+
+        let ignored = HashSet::from(["ignored".to_string()]);
+        let found = find_one_sided(
+            &claude,
+            &repo,
+            &FilterConfig::default(),
+            &ignored,
+            SystemTime::now(),
+        );
+        assert_eq!(found.local_only, [claude.join("-p/local.jsonl")]);
+        assert_eq!(found.repo_only, [repo.join("-q/remote.jsonl")]);
+
+        // Sessions written since the pull looked at .claude aren't flagged
+        let found = find_one_sided(
+            &claude,
+            &repo,
+            &FilterConfig::default(),
+            &ignored,
+            SystemTime::UNIX_EPOCH,
+        );
+        assert!(found.local_only.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+//! Session paths relative to the two projects directories
+//!
+//! A session lives at the same relative path under `~/.claude/projects` and
+//! under the sync repo's projects directory, so pull and push map one to the
+//! other by stripping one directory and joining the other. Joining a
+//! directory with an absolute path replaces the directory, though: a path
+//! that failed to strip and fell back to the full path used to write the
+//! "copy" wherever that path pointed, or to store it under a nested path in
+//! the repo. [`ClaudeRelPath`] and [`RepoRelPath`] can only hold relative
+//! paths with no `..`, and are built by stripping a directory that must
+//! contain the path, so that fallback can't happen.
+
+use anyhow::{bail, Result};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+macro_rules! rel_path {
+    ($(#[$doc:meta])* $name:ident, $side:literal) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(PathBuf);
+
+        impl $name {
+            /// `path` relative to `dir`, failing if it isn't inside `dir`
+            pub fn new(dir: &Path, path: &Path) -> Result<Self> {
+                match path.strip_prefix(dir) {
+                    Ok(relative) => Self::from_relative(relative),
+                    Err(_) => bail!(
+                        "{} is not inside the {} ({})",
+                        path.display(),
+                        $side,
+                        dir.display()
+                    ),
+                }
+            }
+
+            /// Check a path that is already relative to the directory
+            pub fn from_relative(path: impl AsRef<Path>) -> Result<Self> {
+                Ok(Self(checked_relative(path.as_ref())?))
+            }
+
+            /// The full path under `dir`
+            pub fn under(&self, dir: &Path) -> PathBuf {
+                dir.join(&self.0)
+            }
+
+            pub fn as_path(&self) -> &Path {
+                &self.0
+            }
+        }
+
+        impl AsRef<Path> for $name {
+            fn as_ref(&self) -> &Path {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.display().fmt(f)
+            }
+        }
+    };
+}
+
+rel_path!(
+    /// A session's path relative to the local projects directory
+    /// (`~/.claude/projects`)
+    ClaudeRelPath,
+    "Claude projects directory"
+);
+
+rel_path!(
+    /// A session's path relative to the sync repo's projects directory
+    RepoRelPath,
+    "sync repo's projects directory"
+);
+
+impl ClaudeRelPath {
+    /// The same session's path in the sync repo
+    pub fn to_repo(&self) -> RepoRelPath {
+        RepoRelPath(self.0.clone())
+    }
+
+    /// The path with `/` separators on every platform, as the op-log
+    /// records it
+    pub fn to_slash_string(&self) -> String {
+        self.0
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+impl RepoRelPath {
+    /// The same session's path in `~/.claude/projects`
+    pub fn to_claude(&self) -> ClaudeRelPath {
+        ClaudeRelPath(self.0.clone())
+    }
+}
+
+/// `path` with `.` components dropped, or an error if it is empty,
+/// absolute, or climbs out with `..`
+fn checked_relative(path: &Path) -> Result<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => relative.push(name),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                bail!("{} is not a relative session path", path.display())
+            }
+        }
+    }
+    if relative.as_os_str().is_empty() {
+        bail!("Empty session path");
+    }
+    Ok(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_requires_path_inside_dir() {
+        let claude = Path::new("/home/me/.claude/projects");
+        let relative = ClaudeRelPath::new(claude, &claude.join("-home-me-app/s1.jsonl")).unwrap();
+        assert_eq!(relative.as_path(), Path::new("-home-me-app/s1.jsonl"));
+        assert_eq!(
+            relative.to_repo().under(Path::new("/repo/projects")),
+            Path::new("/repo/projects/-home-me-app/s1.jsonl")
+        );
+
+        let err = ClaudeRelPath::new(claude, Path::new("/tmp/-p/s1.jsonl")).unwrap_err();
+        assert!(err.to_string().contains("not inside"), "{err}");
+        assert!(ClaudeRelPath::new(claude, claude).is_err());
+    }
+
+    #[test]
+    fn test_from_relative_rejects_escapes() {
+        assert_eq!(
+            ClaudeRelPath::from_relative("./-p/s1.jsonl")
+                .unwrap()
+                .to_slash_string(),
+            "-p/s1.jsonl"
+        );
+        for path in ["/-p/s1.jsonl", "../-p/s1.jsonl", "-p/../../s1.jsonl", ""] {
+            assert!(RepoRelPath::from_relative(path).is_err(), "{path}");
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Sessions whose file moved
+//!
+//! Claude Code can rewrite a session under a new file name (for example
+//! after compaction), so the same session ID turns up in two files. A pull
+//! would carry both forward: the new file as an added session and the old
+//! one as an orphan. The helpers here find such copies and fold them into a
+//! single file, keeping every entry from each copy.
+//!
+//! Subagent transcripts (`agent-*.jsonl`) carry their parent's session ID by
+//! design and are never treated as copies.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::parser::{missing_entries, ContentKeyStrategies, ConversationSession};
+
+/// A session whose copies were folded into one file
+#[derive(Debug, Clone)]
+pub struct Relocation {
+    pub session_id: String,
+    /// File that now holds every entry
+    pub kept: PathBuf,
+    /// Other copies, now removed
+    pub removed: Vec<PathBuf>,
+}
+
+/// Sessions stored in more than one file, grouped by session ID
+pub fn find_relocated(sessions: &[ConversationSession]) -> Vec<Vec<&ConversationSession>> {
+    let mut by_id: BTreeMap<&str, Vec<&ConversationSession>> = BTreeMap::new();
+    for session in sessions.iter().filter(|s| !s.is_agent_file()) {
+        by_id.entry(&session.session_id).or_default().push(session);
+    }
+    by_id.into_values().filter(|copies| copies.len() > 1).collect()
+}
+
+/// Index of the copy to keep: the one with the newest entry, then the most
+/// entries, then one named after the session ID
+pub fn canonical_copy(copies: &[&ConversationSession]) -> usize {
+    let named_after_id = |s: &ConversationSession| {
+        Path::new(&s.file_path)
+            .file_stem()
+            .is_some_and(|stem| stem.to_string_lossy() == s.session_id)
+    };
+    (0..copies.len())
+        .max_by(|&a, &b| {
+            let (a, b) = (copies[a], copies[b]);
+            a.latest_timestamp()
+                .cmp(&b.latest_timestamp())
+                .then(a.entries.len().cmp(&b.entries.len()))
+                .then(named_after_id(a).cmp(&named_after_id(b)))
+                .then(b.file_path.cmp(&a.file_path))
+        })
+        .unwrap_or(0)
+}
+
+/// The copy at `base` extended with the entries only the other copies have,
+/// written as `file_path`
+pub fn merge_copies(
+    copies: &[&ConversationSession],
+    base: usize,
+    file_path: &Path,
+    strategies: &ContentKeyStrategies,
+) -> ConversationSession {
+    let mut entries = copies[base].entries.clone();
+    for (i, copy) in copies.iter().enumerate() {
+        if i != base {
+            let missing = missing_entries(&entries, &copy.entries, strategies);
+            entries.extend(missing);
+        }
+    }
+    ConversationSession {
+        session_id: copies[base].session_id.clone(),
+        entries,
+        file_path: file_path.to_string_lossy().to_string(),
+    }
+}
+
+/// Fold every session stored in more than one file into its canonical copy
+/// and delete the others
+///
+/// Meant for the sync repo, where git keeps the removed files.
+pub fn consolidate_relocated(
+    sessions: &[ConversationSession],
+    strategies: &ContentKeyStrategies,
+    preserve_raw: bool,
+) -> Result<Vec<Relocation>> {
+    let mut relocations = Vec::new();
+    for copies in find_relocated(sessions) {
+        let base = canonical_copy(&copies);
+        let kept = PathBuf::from(&copies[base].file_path);
+        let merged = merge_copies(&copies, base, &kept, strategies);
+        merged.write_to_file_with(&kept, preserve_raw)?;
+
+        let mut removed = Vec::new();
+        for (i, copy) in copies.iter().enumerate() {
+            if i != base {
+                let path = PathBuf::from(&copy.file_path);
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+                let mut number = 1;
+                while crate::parts::part_path(&path, number).exists() {
+                    fs::remove_file(crate::parts::part_path(&path, number))?;
+                    number += 1;
+                }
+                removed.push(path);
+            }
+        }
+        log::info!(
+            "Session {} was in {} files; kept {}",
+            merged.session_id,
+            copies.len(),
+            kept.display()
+        );
+        relocations.push(Relocation {
+            session_id: merged.session_id,
+            kept,
+            removed,
+        });
+    }
+    Ok(relocations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn session(dir: &Path, name: &str, uuids: &[(&str, &str)]) -> ConversationSession {
+        let session = ConversationSession {
+            session_id: "s1".to_string(),
+            entries: uuids
+                .iter()
+                .map(|(uuid, ts)| {
+                    serde_json::from_str(&format!(
+                        r#"{{"type":"user","uuid":"{uuid}","sessionId":"s1","timestamp":"{ts}"}}"#
+                    ))
+                    .unwrap()
+                })
+                .collect(),
+            file_path: dir.join(name).to_string_lossy().to_string(),
+        };
+        session.write_to_file(&session.file_path).unwrap();
+        session
+    }
+
+    #[test]
+    fn test_consolidate_keeps_newest_copy_with_all_entries() {
+        let temp = TempDir::new().unwrap();
+        let old = session(temp.path(), "s1.jsonl", &[("a", "1"), ("b", "2")]);
+        let new = session(temp.path(), "renamed.jsonl", &[("a", "1"), ("c", "3")]);
+        let agent = session(temp.path(), "agent-x.jsonl", &[("z", "9")]);
+        let sessions = vec![old, new, agent];
+
+        let relocations =
+            consolidate_relocated(&sessions, &ContentKeyStrategies::default(), false).unwrap();
+        assert_eq!(relocations.len(), 1);
+        assert_eq!(relocations[0].kept, temp.path().join("renamed.jsonl"));
+        assert!(!temp.path().join("s1.jsonl").exists());
+        assert!(temp.path().join("agent-x.jsonl").exists());
+
+        let merged = ConversationSession::from_file(temp.path().join("renamed.jsonl")).unwrap();
+        let uuids: Vec<_> = merged.entries.iter().filter_map(|e| e.uuid.clone()).collect();
+        assert_eq!(uuids, ["a", "c", "b"]);
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Context, Result};
 use colored::Colorize;
 
+use crate::glyphs;
 use crate::scm;
 
 use super::state::SyncState;
@@ -30,6 +31,11 @@ pub fn show_remote() -> Result<()> {
 
     println!();
 
+    if let Some(url) = &state.rsync_remote {
+        println!("{} {}", "Rsync Remote:".bold(), url.cyan());
+        println!();
+    }
+
     // List all remotes
     let remotes = repo.list_remotes()?;
 
@@ -85,7 +91,7 @@ pub fn set_remote(name: &str, url: &str) -> Result<()> {
 
         println!(
             "{} Updated remote '{}' to: {}",
-            "✓".green().bold(),
+            glyphs::ok().green().bold(),
             name.cyan(),
             url
         );
@@ -96,7 +102,7 @@ pub fn set_remote(name: &str, url: &str) -> Result<()> {
 
         println!(
             "{} Created remote '{}': {}",
-            "✓".green().bold(),
+            glyphs::ok().green().bold(),
             name.cyan(),
             url
         );
@@ -128,7 +134,7 @@ pub fn remove_remote(name: &str) -> Result<()> {
     repo.remove_remote(name)
         .with_context(|| format!("Failed to remove remote '{name}'"))?;
 
-    println!("{} Removed remote '{}'", "✓".green().bold(), name.cyan());
+    println!("{} Removed remote '{}'", glyphs::ok().green().bold(), name.cyan());
 
     // Update state if this was the origin remote
     if name == "origin" {
@@ -139,3 +145,30 @@ pub fn remove_remote(name: &str) -> Result<()> {
 
     Ok(())
 }
+
+/// Set the rsync remote, a plain directory sessions are replicated to
+pub fn set_rsync_remote(url: &str) -> Result<()> {
+    let mut state = SyncState::load()?;
+    super::rsync::RsyncRemote::parse(url)?;
+
+    state.rsync_remote = Some(url.to_string());
+    state.save()?;
+
+    println!("{} Set rsync remote: {}", glyphs::ok().green().bold(), url.cyan());
+    println!("\n{} claude-code-sync sync", "Next:".cyan());
+
+    Ok(())
+}
+
+/// Stop replicating to the rsync remote
+pub fn remove_rsync_remote() -> Result<()> {
+    let mut state = SyncState::load()?;
+    let Some(url) = state.rsync_remote.take() else {
+        return Err(anyhow!("No rsync remote configured"));
+    };
+    state.save()?;
+
+    println!("{} Removed rsync remote {}", glyphs::ok().green().bold(), url.cyan());
+
+    Ok(())
+}
@@ -0,0 +1,305 @@
+//! A lease on the remote, so machines take turns pushing
+//!
+//! Two machines that sync on the same schedule both pull, both push, and
+//! one push fails as a non-fast-forward, often again on the retry. With
+//! `remote_lease_secs` set, `sync` and `push` first take a lease: a
+//! `sync-lease/<expiry>/<machine>` branch pushed to origin and deleted once
+//! done. A machine that finds another machine's live lease waits for it to
+//! go away. Leases carry their expiry, so one left by a crashed sync stops
+//! counting after `remote_lease_secs` and is deleted by the next machine.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::time::{Duration, Instant};
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::scm::Scm;
+
+use super::state::SyncState;
+
+/// Prefix of the branches that hold leases
+pub const LEASE_BRANCH_PREFIX: &str = "sync-lease/";
+
+/// How often a waiting machine checks whether the lease is free
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A lease found on the remote
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Lease {
+    branch: String,
+    /// Unix time the lease stops counting
+    expires: i64,
+    machine: String,
+}
+
+impl Lease {
+    fn new(expires: i64, machine: &str) -> Self {
+        Lease {
+            branch: format!("{LEASE_BRANCH_PREFIX}{expires}/{machine}"),
+            expires,
+            machine: machine.to_string(),
+        }
+    }
+
+    fn parse(branch: &str) -> Option<Self> {
+        let (expires, machine) = branch.strip_prefix(LEASE_BRANCH_PREFIX)?.split_once('/')?;
+        if machine.is_empty() {
+            return None;
+        }
+        Some(Lease {
+            branch: branch.to_string(),
+            expires: expires.parse().ok()?,
+            machine: machine.to_string(),
+        })
+    }
+
+    fn expiry_time(&self) -> String {
+        chrono::DateTime::from_timestamp(self.expires, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|| self.expires.to_string())
+    }
+}
+
+/// A lease this machine holds on a remote, released when dropped
+pub(crate) struct RemoteLease<'a> {
+    repo: &'a dyn Scm,
+    remote: String,
+    branch: String,
+}
+
+impl<'a> RemoteLease<'a> {
+    /// Take a lease on origin if `remote_lease_secs` is set and there is a
+    /// remote, waiting up to one lease period for another machine's lease
+    pub(crate) fn acquire_configured(
+        repo: &'a dyn Scm,
+        state: &SyncState,
+        verbosity: crate::VerbosityLevel,
+    ) -> Result<Option<Self>> {
+        let ttl = FilterConfig::load()?.remote_lease_secs;
+        if ttl == 0 || !state.has_remote {
+            return Ok(None);
+        }
+        let machine_id = crate::config::ConfigManager::machine_id()?;
+        Self::acquire(
+            repo,
+            "origin",
+            &machine_id,
+            ttl,
+            Duration::from_secs(ttl),
+            verbosity,
+        )
+        .map(Some)
+    }
+
+    /// Take a lease on `remote` lasting `ttl` seconds, waiting up to `wait`
+    /// for leases held by other machines
+    fn acquire(
+        repo: &'a dyn Scm,
+        remote: &str,
+        machine_id: &str,
+        ttl: u64,
+        wait: Duration,
+        verbosity: crate::VerbosityLevel,
+    ) -> Result<Self> {
+        let deadline = Instant::now() + wait;
+        // This machine holds the sync lock, so its own leases are left over
+        // from a sync that died
+        for lease in remote_leases(repo, remote)? {
+            if lease.machine == machine_id {
+                delete_lease(repo, remote, &lease);
+            }
+        }
+
+        let mut announced = false;
+        loop {
+            let now = chrono::Utc::now().timestamp();
+            let held = live_leases(repo, remote, now)?.into_iter().next();
+            let holder = match held {
+                Some(holder) => holder,
+                None => {
+                    let lease = Lease::new(now + ttl as i64, machine_id);
+                    repo.create_branch(&lease.branch)?;
+                    let pushed = repo.push(remote, &lease.branch);
+                    if let Err(e) = repo.delete_branch(&lease.branch) {
+                        log::debug!("Failed to delete local branch {}: {}", lease.branch, e);
+                    }
+                    pushed.context("Failed to take a lease on the remote")?;
+
+                    // Machines that took a lease at the same time all see the
+                    // same leases; the first one wins and the others back off
+                    let leases = live_leases(repo, remote, now)?;
+                    match leases.into_iter().next() {
+                        Some(first) if first.branch == lease.branch => {
+                            log::info!("Took remote lease {}", lease.branch);
+                            return Ok(RemoteLease {
+                                repo,
+                                remote: remote.to_string(),
+                                branch: lease.branch,
+                            });
+                        }
+                        Some(first) => {
+                            delete_lease(repo, remote, &lease);
+                            first
+                        }
+                        None => bail!("Lease {} disappeared from the remote", lease.branch),
+                    }
+                }
+            };
+
+            if Instant::now() >= deadline {
+                bail!(
+                    "{} is syncing with the remote (lease until {}); try again later",
+                    holder.machine,
+                    holder.expiry_time()
+                );
+            }
+            if !announced && verbosity >= crate::VerbosityLevel::Normal {
+                println!(
+                    "  {} Waiting for {} to finish syncing (lease until {})...",
+                    glyphs::info().cyan(),
+                    holder.machine,
+                    holder.expiry_time()
+                );
+                announced = true;
+            }
+            std::thread::sleep(
+                POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now())),
+            );
+        }
+    }
+}
+
+impl Drop for RemoteLease<'_> {
+    fn drop(&mut self) {
+        // A lease that can't be deleted still expires
+        if let Err(e) = self.repo.delete_remote_branch(&self.remote, &self.branch) {
+            log::warn!("Failed to release remote lease {}: {}", self.branch, e);
+        }
+    }
+}
+
+/// Every lease on `remote`, in the order they were taken
+fn remote_leases(repo: &dyn Scm, remote: &str) -> Result<Vec<Lease>> {
+    let mut leases: Vec<Lease> = repo
+        .list_remote_branches(remote)?
+        .iter()
+        .filter_map(|branch| Lease::parse(branch))
+        .collect();
+    leases.sort_by(|a, b| (a.expires, &a.branch).cmp(&(b.expires, &b.branch)));
+    Ok(leases)
+}
+
+/// The leases on `remote` still counting at `now`, deleting expired ones
+fn live_leases(repo: &dyn Scm, remote: &str, now: i64) -> Result<Vec<Lease>> {
+    let (live, expired): (Vec<_>, Vec<_>) = remote_leases(repo, remote)?
+        .into_iter()
+        .partition(|lease| lease.expires > now);
+    for lease in &expired {
+        delete_lease(repo, remote, lease);
+    }
+    Ok(live)
+}
+
+fn delete_lease(repo: &dyn Scm, remote: &str, lease: &Lease) {
+    // Another machine may have deleted it first
+    if let Err(e) = repo.delete_remote_branch(remote, &lease.branch) {
+        log::debug!("Failed to delete lease {}: {}", lease.branch, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::MockScm;
+    use crate::VerbosityLevel;
+
+    fn repo_with_remote() -> MockScm {
+        let repo = MockScm::new();
+        repo.write_file("README.md", "# sync\n");
+        repo.stage_all().unwrap();
+        repo.commit("Initial commit").unwrap();
+        repo.add_remote("origin", "mock://origin").unwrap();
+        repo.push("origin", "main").unwrap();
+        repo
+    }
+
+    fn push_lease(repo: &MockScm, lease: &Lease) {
+        repo.create_branch(&lease.branch).unwrap();
+        repo.push("origin", &lease.branch).unwrap();
+        repo.delete_branch(&lease.branch).unwrap();
+    }
+
+    fn leases(repo: &MockScm) -> Vec<String> {
+        remote_leases(repo, "origin")
+            .unwrap()
+            .into_iter()
+            .map(|l| l.branch)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_lease() {
+        let lease = Lease::new(1700000000, "old-box-9f8e");
+        assert_eq!(lease.branch, "sync-lease/1700000000/old-box-9f8e");
+        assert_eq!(Lease::parse(&lease.branch), Some(lease));
+        for branch in [
+            "main",
+            "sync-lease/x/laptop",
+            "sync-lease/1700000000/",
+            "sync-lease/1",
+        ] {
+            assert_eq!(Lease::parse(branch), None, "{branch}");
+        }
+    }
+
+    #[test]
+    fn test_lease_is_released_on_drop() {
+        let repo = repo_with_remote();
+        // Expired, and left over from this machine
+        push_lease(&repo, &Lease::new(1, "desktop"));
+        push_lease(&repo, &Lease::new(i64::MAX / 2, "laptop"));
+
+        let lease = RemoteLease::acquire(
+            &repo,
+            "origin",
+            "laptop",
+            60,
+            Duration::ZERO,
+            VerbosityLevel::Quiet,
+        )
+        .unwrap();
+        let held = leases(&repo);
+        assert_eq!(held.len(), 1);
+        assert!(held[0].ends_with("/laptop"));
+        assert!(!repo.list_branches().unwrap().contains(&held[0]));
+
+        drop(lease);
+        assert!(leases(&repo).is_empty());
+        assert!(repo.remote_branches("origin").contains(&"main".to_string()));
+    }
+
+    #[test]
+    fn test_live_lease_of_another_machine_blocks() {
+        let repo = repo_with_remote();
+        let theirs = Lease::new(chrono::Utc::now().timestamp() + 600, "desktop");
+        push_lease(&repo, &theirs);
+
+        let err = RemoteLease::acquire(
+            &repo,
+            "origin",
+            "laptop",
+            60,
+            Duration::ZERO,
+            VerbosityLevel::Quiet,
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("desktop is syncing"), "{err}");
+        assert_eq!(leases(&repo), [theirs.branch]);
+    }
+}
@@ -0,0 +1,249 @@
+//! Replicating the sync repo's session files with rsync
+//!
+//! For homelab setups with no git server, `SyncState::rsync_remote` names a
+//! plain directory, `ssh://user@host/path` or a local path such as a mounted
+//! share. Push uploads the sync repo's projects directory there; pull
+//! downloads it and folds what other machines uploaded into `.claude` with
+//! the append-only merge of `pull --at`, after which the usual pipeline
+//! commits it to the local sync repo. Push merges the remote copy into the
+//! sync repo before uploading, so an upload never drops entries another
+//! machine uploaded first.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+
+use super::discovery::discover_sessions;
+use super::pull_at::{restore_sessions, RestoreStats};
+
+/// Where an rsync remote lives
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsyncRemote {
+    /// `user@host` (or `host`) for SSH remotes, `None` for local paths
+    pub host: Option<String>,
+    /// SSH port, when not the default
+    pub port: Option<u16>,
+    /// Directory on the host
+    pub path: String,
+}
+
+impl RsyncRemote {
+    /// Parse `ssh://[user@]host[:port]/path` or an absolute local path
+    pub fn parse(url: &str) -> Result<Self> {
+        if let Some(rest) = url.strip_prefix("ssh://") {
+            let Some((authority, path)) = rest.split_once('/') else {
+                bail!(
+                    "Invalid rsync remote '{}': missing path after the host",
+                    url
+                );
+            };
+            let (host, port) = match authority.rsplit_once(':') {
+                Some((host, port)) => {
+                    let port = port
+                        .parse()
+                        .with_context(|| format!("Invalid port in rsync remote '{url}'"))?;
+                    (host, Some(port))
+                }
+                None => (authority, None),
+            };
+            if host.is_empty() || host.ends_with('@') || path.is_empty() {
+                bail!("Invalid rsync remote '{}'", url);
+            }
+            // ssh and rsync would take the host for an option
+            if host.starts_with('-') {
+                bail!("Invalid rsync remote '{}': the host can't start with '-'", url);
+            }
+            return Ok(Self {
+                host: Some(host.to_string()),
+                port,
+                path: format!("/{}", path.trim_end_matches('/')),
+            });
+        }
+        if Path::new(url).is_absolute() {
+            return Ok(Self {
+                host: None,
+                port: None,
+                path: url.trim_end_matches('/').to_string(),
+            });
+        }
+        bail!(
+            "Invalid rsync remote '{}': expected ssh://user@host/path or an absolute path",
+            url
+        );
+    }
+
+    /// rsync's spelling of `subdir` on the remote, with a trailing slash so
+    /// directory contents are copied rather than the directory itself
+    fn location(&self, subdir: &str) -> String {
+        let path = format!("{}/{}/", self.path, subdir.trim_matches('/'));
+        match &self.host {
+            Some(host) => format!("{host}:{path}"),
+            None => path,
+        }
+    }
+
+    /// An `rsync -a` command, tunnelled over SSH on a non-default port
+    fn rsync(&self) -> Command {
+        let mut command = Command::new("rsync");
+        command.arg("-a");
+        if let Some(port) = self.port {
+            command.args(["-e", &format!("ssh -p {port}")]);
+        }
+        command
+    }
+
+    /// Create `subdir` on the remote if it doesn't exist yet
+    fn create_dir(&self, subdir: &str) -> Result<()> {
+        let path = format!("{}/{}", self.path, subdir.trim_matches('/'));
+        let Some(host) = &self.host else {
+            return fs::create_dir_all(&path).with_context(|| format!("Failed to create {path}"));
+        };
+        let mut command = Command::new("ssh");
+        if let Some(port) = self.port {
+            command.args(["-p", &port.to_string()]);
+        }
+        command.args(["--", host.as_str(), "mkdir", "-p", "--", &shell_quote(&path)]);
+        run(command, "ssh")
+    }
+
+    /// Copy the remote's `subdir` into `dest`, which stays empty if the
+    /// remote has nothing there yet
+    pub fn download(&self, subdir: &str, dest: &Path) -> Result<()> {
+        fs::create_dir_all(dest)
+            .with_context(|| format!("Failed to create directory: {}", dest.display()))?;
+        self.create_dir(subdir)?;
+        let mut command = self.rsync();
+        command.arg(self.location(subdir)).arg(with_slash(dest));
+        run(command, "rsync")
+    }
+
+    /// Copy `src` into the remote's `subdir`, never deleting remote files
+    pub fn upload(&self, src: &Path, subdir: &str) -> Result<()> {
+        self.create_dir(subdir)?;
+        let mut command = self.rsync();
+        command.arg(with_slash(src)).arg(self.location(subdir));
+        run(command, "rsync")
+    }
+}
+
+/// `path` as a string ending in `/`
+fn with_slash(path: &Path) -> String {
+    format!("{}/", path.to_string_lossy().trim_end_matches('/'))
+}
+
+/// Quote `arg` for the remote shell ssh runs commands in
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+fn run(mut command: Command, program: &str) -> Result<()> {
+    let output = command
+        .output()
+        .with_context(|| format!("Failed to run {program}; is it installed?"))?;
+    if !output.status.success() {
+        bail!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Download the remote's sessions and add whatever `dest_dir` is missing,
+/// backing up appended-to files in `backup_dir`
+pub(crate) fn merge_remote_sessions(
+    url: &str,
+    filter: &FilterConfig,
+    dest_dir: &Path,
+    backup_dir: &Path,
+    verbosity: crate::VerbosityLevel,
+) -> Result<RestoreStats> {
+    let remote = RsyncRemote::parse(url)?;
+    let stats = (|| {
+        // Removed when dropped
+        let staging = tempfile::tempdir().context("Failed to create a staging directory")?;
+        remote.download(&projects_subdir(filter), staging.path())?;
+        let sessions = discover_sessions(staging.path(), filter)?;
+        restore_sessions(&sessions, staging.path(), dest_dir, filter, backup_dir, verbosity)
+    })()
+    .with_context(|| format!("Failed to pull from rsync remote {url}"))?;
+
+    if verbosity >= crate::VerbosityLevel::Normal {
+        println!(
+            "  {} Fetched from {}: {} new sessions, {} entries appended to {} sessions",
+            glyphs::ok().green(),
+            url,
+            stats.sessions_added,
+            stats.entries_appended,
+            stats.sessions_appended
+        );
+    }
+    Ok(stats)
+}
+
+/// Upload the sync repo's projects directory to the remote
+pub(crate) fn upload_sessions(
+    url: &str,
+    filter: &FilterConfig,
+    sync_repo: &Path,
+    verbosity: crate::VerbosityLevel,
+) -> Result<()> {
+    let remote = RsyncRemote::parse(url)?;
+    let subdir = projects_subdir(filter);
+    remote
+        .upload(&sync_repo.join(&subdir), &subdir)
+        .with_context(|| format!("Failed to push to rsync remote {url}"))?;
+    if verbosity >= crate::VerbosityLevel::Normal {
+        println!("  {} Uploaded sessions to {}", glyphs::ok().green(), url);
+    }
+    Ok(())
+}
+
+/// The projects directory, relative to the sync repo and the remote
+fn projects_subdir(filter: &FilterConfig) -> String {
+    filter
+        .projects_subdir()
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rsync_remote() {
+        assert_eq!(
+            RsyncRemote::parse("ssh://me@nas.lan:2222/srv/claude/").unwrap(),
+            RsyncRemote {
+                host: Some("me@nas.lan".to_string()),
+                port: Some(2222),
+                path: "/srv/claude".to_string(),
+            }
+        );
+        let remote = RsyncRemote::parse("ssh://nas/backup").unwrap();
+        assert_eq!(remote.location("projects"), "nas:/backup/projects/");
+        let local = RsyncRemote::parse("/mnt/share/claude").unwrap();
+        assert_eq!(local.host, None);
+        assert_eq!(local.location("/projects/"), "/mnt/share/claude/projects/");
+
+        for url in [
+            "ssh://host",
+            "ssh:///path",
+            "ssh://me@/path",
+            "ssh://h:x/p",
+            "ssh://-oProxyCommand=touch%20pwned/path",
+            "ssh://-oProxyCommand=x:22/path",
+            "relative/dir",
+            "https://h/p",
+        ] {
+            assert!(RsyncRemote::parse(url).is_err(), "{url}");
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Named recovery points in the sync repo
+//!
+//! `snapshot <name>` pulls, checks that `.claude` and the sync repo agree,
+//! and tags the resulting commit `snapshot/<name>`. `restore --snapshot
+//! <name>` brings that state back into `.claude` with `pull --at`, which only
+//! ever adds what is missing.
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::lock::SyncLock;
+use crate::scm::{self, Scm, TagInfo};
+
+use super::discovery::{claude_projects_dir, discover_sessions};
+use super::state::SyncState;
+
+/// Prefix of the tags that hold snapshots
+pub const SNAPSHOT_TAG_PREFIX: &str = "snapshot/";
+
+/// Reject names git can't use as a single tag path component
+pub fn validate_snapshot_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || name.starts_with(['.', '-'])
+        || name.ends_with(".lock")
+        || name.contains("..")
+        || name
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || "/\\~^:?*[@{".contains(c))
+    {
+        bail!("Invalid snapshot name: '{}'", name);
+    }
+    Ok(())
+}
+
+/// Snapshots in the sync repo, oldest first, named without the tag prefix
+pub fn list_snapshots(repo: &dyn Scm) -> Result<Vec<TagInfo>> {
+    Ok(repo
+        .list_tags()?
+        .into_iter()
+        .filter_map(|tag| {
+            let name = tag.name.strip_prefix(SNAPSHOT_TAG_PREFIX)?.to_string();
+            Some(TagInfo { name, ..tag })
+        })
+        .collect())
+}
+
+/// Delete every snapshot, and with `remote` set their tags on origin too,
+/// returning how many were deleted
+///
+/// Snapshots keep the commits they point at reachable, so history rewritten
+/// by a purge would otherwise survive in them.
+pub fn remove_snapshots(repo: &dyn Scm, remote: bool) -> Result<usize> {
+    let snapshots = list_snapshots(repo)?;
+    for snapshot in &snapshots {
+        let tag = format!("{SNAPSHOT_TAG_PREFIX}{}", snapshot.name);
+        if remote {
+            if let Err(e) = repo.delete_remote_tag("origin", &tag) {
+                log::debug!("Failed to delete remote tag {}: {}", tag, e);
+            }
+        }
+        repo.delete_tag(&tag)?;
+    }
+    Ok(snapshots.len())
+}
+
+/// Pull, verify, and tag the result as snapshot `name`
+///
+/// The tag is pushed to origin when there is a remote, so the snapshot can
+/// be restored on other machines.
+pub fn create_snapshot(
+    name: &str,
+    message: Option<&str>,
+    verbosity: crate::VerbosityLevel,
+) -> Result<()> {
+    use crate::VerbosityLevel;
+
+    validate_snapshot_name(name)?;
+    let _lock = SyncLock::acquire()?;
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let tag = format!("{SNAPSHOT_TAG_PREFIX}{name}");
+    if repo.list_tags()?.iter().any(|t| t.name == tag) {
+        bail!("Snapshot '{}' already exists", name);
+    }
+
+    let Some(record) =
+        super::pull::pull_locked(true, None, false, &[], false, "origin", verbosity)?
+    else {
+        bail!("Pull was cancelled; no snapshot taken");
+    };
+    super::save_operation_record(record);
+
+    // A pull with verify_after_pull enabled has checked this already
+    let filter = FilterConfig::load()?;
+    if !filter.verify_after_pull {
+        let local = discover_sessions(&claude_projects_dir()?, &filter)?;
+        let synced = discover_sessions(
+            &state.sync_repo_path.join(filter.projects_subdir()),
+            &filter,
+        )?;
+        let diverged = crate::conflict::find_diverged_sessions(&local, &synced);
+        if !diverged.is_empty() {
+            bail!(
+                "{} sessions differ between .claude and the sync repo ({}); no snapshot taken",
+                diverged.len(),
+                diverged.join(", ")
+            );
+        }
+    }
+
+    let machine_id = crate::config::ConfigManager::machine_id()?;
+    let message = match message {
+        Some(message) => message.to_string(),
+        None => format!("Snapshot {name} from {machine_id}"),
+    };
+    repo.create_tag(&tag, &message)?;
+    let commit = repo.current_commit_hash()?;
+    if verbosity >= VerbosityLevel::Normal {
+        println!(
+            "  {} Snapshot {} taken at {}",
+            glyphs::ok().green(),
+            name.bold(),
+            commit.chars().take(12).collect::<String>()
+        );
+    }
+
+    if state.has_remote {
+        match repo.push_tag("origin", &tag) {
+            Ok(()) => {
+                if verbosity >= VerbosityLevel::Normal {
+                    println!("  {} Pushed snapshot to origin", glyphs::ok().green());
+                }
+            }
+            Err(e) => {
+                log::warn!("Failed to push snapshot tag {}: {}", tag, e);
+                if verbosity >= VerbosityLevel::Normal {
+                    println!(
+                        "  {} Snapshot is local only: failed to push it to origin",
+                        "!".yellow().bold()
+                    );
+                }
+            }
+        }
+    }
+    if verbosity == VerbosityLevel::Summary {
+        println!("snapshot {name}: {commit}");
+    }
+    Ok(())
+}
+
+/// Delete snapshot `name`, locally and on origin
+pub fn delete_snapshot(name: &str) -> Result<()> {
+    validate_snapshot_name(name)?;
+    let _lock = SyncLock::acquire()?;
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+    let tag = format!("{SNAPSHOT_TAG_PREFIX}{name}");
+    if !repo.list_tags()?.iter().any(|t| t.name == tag) {
+        bail!("No snapshot named '{}'", name);
+    }
+    if state.has_remote {
+        if let Err(e) = repo.delete_remote_tag("origin", &tag) {
+            log::debug!("Failed to delete remote tag {}: {}", tag, e);
+        }
+    }
+    repo.delete_tag(&tag)
+}
+
+/// Merge the sessions of snapshot `name` into `.claude`, adding only what
+/// is missing locally
+pub fn restore_snapshot(name: &str, verbosity: crate::VerbosityLevel) -> Result<()> {
+    validate_snapshot_name(name)?;
+    let _lock = SyncLock::acquire()?;
+    let state = SyncState::load()?;
+    let repo = scm::open(&state.sync_repo_path)?;
+
+    // Snapshots taken on other machines arrive with a fetch
+    if state.has_remote {
+        if let Err(e) = repo.fetch("origin") {
+            log::warn!("Failed to fetch snapshots from origin: {}", e);
+        }
+    }
+    let tag = format!("{SNAPSHOT_TAG_PREFIX}{name}");
+    if !repo.list_tags()?.iter().any(|t| t.name == tag) {
+        bail!(
+            "No snapshot named '{}'; run 'claude-code-sync snapshot' to list them",
+            name
+        );
+    }
+    super::pull_at::pull_at_locked(&tag, verbosity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::MockScm;
+
+    #[test]
+    fn test_validate_snapshot_name() {
+        assert!(validate_snapshot_name("before-refactor").is_ok());
+        assert!(validate_snapshot_name("v1.2_ok").is_ok());
+        for name in ["", "a b", "a/b", "..", "-x", ".x", "a..b", "x.lock", "a:b"] {
+            assert!(validate_snapshot_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_list_and_remove_snapshots() {
+        let repo = MockScm::new();
+        repo.write_file("README.md", "# sync\n");
+        repo.stage_all().unwrap();
+        repo.commit("Initial commit").unwrap();
+        repo.add_remote("origin", "mock://origin").unwrap();
+        repo.create_tag("v1", "Release").unwrap();
+        repo.create_tag("snapshot/good", "Snapshot good").unwrap();
+        repo.push_tag("origin", "snapshot/good").unwrap();
+
+        let snapshots = list_snapshots(&repo).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].name, "good");
+        assert_eq!(snapshots[0].commit, repo.current_commit_hash().unwrap());
+
+        assert_eq!(remove_snapshots(&repo, true).unwrap(), 1);
+        let tags: Vec<_> = repo
+            .list_tags()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(tags, ["v1"]);
+        assert!(repo.remote_tags("origin").is_empty());
+    }
+}
@@ -1,5 +1,4 @@
 use anyhow::{anyhow, Context, Result};
-use std::fs;
 use std::path::PathBuf;
 
 /// Sync state and configuration
@@ -39,6 +38,15 @@ pub struct SyncState {
     /// may already have existing content and history.
     #[serde(default)]
     pub is_cloned_repo: bool,
+
+    /// Plain directory the sync repo's files are replicated to with rsync
+    ///
+    /// Set for homelab setups without git hosting: `ssh://user@host/path`,
+    /// or a local path such as a mounted share. Push uploads the session
+    /// files there and pull folds in what other machines uploaded, instead
+    /// of (or alongside) pushing to and pulling from `origin`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rsync_remote: Option<String>,
 }
 
 impl SyncState {
@@ -58,7 +66,8 @@ impl SyncState {
     /// This function returns an error if:
     /// - The sync system has not been initialized (state file doesn't exist)
     /// - The state file cannot be read (permission errors, I/O errors)
-    /// - The state file contains invalid JSON or cannot be deserialized
+    /// - The state file contains invalid JSON or cannot be deserialized, and
+    ///   there is no usable backup of the last good version to restore
     ///
     /// If the sync is not initialized, the error message will instruct the user
     /// to run `claude-code-sync init` first.
@@ -87,27 +96,21 @@ impl SyncState {
             ));
         }
 
-        let content = fs::read_to_string(&state_path).context("Failed to read sync state")?;
-
-        let state: SyncState =
-            serde_json::from_str(&content).context("Failed to parse sync state")?;
-
-        Ok(state)
+        crate::state_file::read_with_recovery(&state_path, "sync state", |content| {
+            serde_json::from_str(content).context("Failed to parse sync state")
+        })
     }
 
     pub(crate) fn save(&self) -> Result<()> {
         let state_path = Self::state_file_path()?;
 
-        if let Some(parent) = state_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
         let content =
             serde_json::to_string_pretty(self).context("Failed to serialize sync state")?;
 
-        fs::write(&state_path, content).context("Failed to write sync state")?;
-
-        Ok(())
+        crate::state_file::write_atomic(&state_path, &content, |current| {
+            serde_json::from_str::<SyncState>(current).is_ok()
+        })
+        .context("Failed to write sync state")
     }
 
     fn state_file_path() -> Result<PathBuf> {
@@ -2,8 +2,11 @@ use anyhow::Result;
 use colored::Colorize;
 use std::path::Path;
 
+use crate::config::ConfigManager;
 use crate::filter::FilterConfig;
+use crate::machines::{MachineFilter, MACHINES_DIR};
 use crate::scm;
+use crate::text::short_id;
 
 use super::discovery::{claude_projects_dir, discover_sessions};
 use super::state::SyncState;
@@ -38,6 +41,18 @@ pub fn show_status(show_conflicts: bool, show_files: bool) -> Result<()> {
         println!("  Branch: {}", branch.cyan());
     }
 
+    let machine_id = ConfigManager::machine_id()?;
+    if MachineFilter::path(&state.sync_repo_path, &machine_id).exists() {
+        println!(
+            "  Machine ID: {} (pulls filtered by {}/{}.toml)",
+            machine_id.cyan(),
+            MACHINES_DIR,
+            machine_id
+        );
+    } else {
+        println!("  Machine ID: {}", machine_id.cyan());
+    }
+
     if let Ok(has_changes) = repo.has_changes() {
         println!(
             "  Uncommitted changes: {}",
@@ -55,7 +70,7 @@ pub fn show_status(show_conflicts: bool, show_files: bool) -> Result<()> {
     let local_sessions = discover_sessions(&claude_dir, &filter)?;
     println!("  Local: {}", local_sessions.len().to_string().cyan());
 
-    let remote_projects_dir = state.sync_repo_path.join(&filter.sync_subdirectory);
+    let remote_projects_dir = state.sync_repo_path.join(filter.projects_subdir());
     if remote_projects_dir.exists() {
         let remote_sessions = discover_sessions(&remote_projects_dir, &filter)?;
         println!("  Sync repo: {}", remote_sessions.len().to_string().cyan());
@@ -65,14 +80,20 @@ pub fn show_status(show_conflicts: bool, show_files: bool) -> Result<()> {
     if show_files {
         println!();
         println!("{}", "Local session files:".bold());
+        let families = crate::families::FamilyIndex::load(&state.sync_repo_path)?;
         for session in local_sessions.iter().take(20) {
             let relative = Path::new(&session.file_path)
                 .strip_prefix(&claude_dir)
                 .unwrap_or(Path::new(&session.file_path));
+            let fork_of = match families.parent_of(&session.session_id) {
+                Some(parent) => format!(" (fork of {})", short_id(parent, 8)),
+                None => String::new(),
+            };
             println!(
-                "  {} ({} messages)",
+                "  {} ({} messages){}",
                 relative.display(),
-                session.message_count()
+                session.message_count(),
+                fork_of.dimmed()
             );
         }
         if local_sessions.len() > 20 {
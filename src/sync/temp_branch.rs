@@ -0,0 +1,240 @@
+//! Temp branch naming and ownership
+//!
+//! Every pull saves local state on a `sync-local-<machine>-<timestamp>-<random>`
+//! branch before merging. The machine ID says which machine owns a branch; the
+//! timestamp says when it can be cleaned up.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::glyphs;
+use crate::scm::Scm;
+use crate::VerbosityLevel;
+
+/// Prefix shared by all temp branches
+pub const TEMP_BRANCH_PREFIX: &str = "sync-local-";
+
+/// Timestamp format embedded in temp branch names
+const TEMP_BRANCH_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Length of a formatted `TEMP_BRANCH_TIME_FORMAT` timestamp
+const TEMP_BRANCH_TIME_LEN: usize = 15;
+
+/// Generate a unique temp branch name: `sync-local-<machine>-<timestamp>-<random>`
+///
+/// The machine ID keeps machines that pull in the same second from pushing
+/// the same safety branch, and marks which machine owns the branch.
+pub(crate) fn generate_temp_branch_name(machine_id: &str) -> String {
+    let timestamp = chrono::Utc::now().format(TEMP_BRANCH_TIME_FORMAT);
+    let suffix = &uuid::Uuid::new_v4().simple().to_string()[..6];
+    format!("{TEMP_BRANCH_PREFIX}{machine_id}-{timestamp}-{suffix}")
+}
+
+fn parse_time(timestamp: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let time = chrono::NaiveDateTime::parse_from_str(timestamp, TEMP_BRANCH_TIME_FORMAT).ok()?;
+    Some(chrono::DateTime::from_naive_utc_and_offset(
+        time,
+        chrono::Utc,
+    ))
+}
+
+/// Creation time of any machine's temp branch
+pub(crate) fn temp_branch_time(branch: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let rest = branch.strip_prefix(TEMP_BRANCH_PREFIX)?;
+    // Old-style "<timestamp>"
+    if let Some(time) = parse_time(rest) {
+        return Some(time);
+    }
+    // "<machine>-<timestamp>-<random>"
+    let (head, _random) = rest.rsplit_once('-')?;
+    let start = head.len().checked_sub(TEMP_BRANCH_TIME_LEN)?;
+    parse_time(head.get(start..)?)
+}
+
+/// Machine that created a temp branch (`None` for old-style branches,
+/// which predate machine IDs, and anything that isn't a temp branch)
+pub fn temp_branch_machine(branch: &str) -> Option<&str> {
+    let rest = branch.strip_prefix(TEMP_BRANCH_PREFIX)?;
+    let (head, _random) = rest.rsplit_once('-')?;
+    let start = head.len().checked_sub(TEMP_BRANCH_TIME_LEN)?;
+    parse_time(head.get(start..)?)?;
+    head.get(..start)?.strip_suffix('-').filter(|m| !m.is_empty())
+}
+
+/// Creation time of a temp branch owned by this machine
+///
+/// Returns `None` for other machines' branches and anything that isn't a temp
+/// branch. Old-style `sync-local-<timestamp>` branches predate machine IDs;
+/// they are only ever listed locally, so they are treated as ours.
+pub(crate) fn owned_temp_branch_time(
+    branch: &str,
+    machine_id: &str,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    let rest = branch.strip_prefix(TEMP_BRANCH_PREFIX)?;
+    let timestamp = match rest
+        .strip_prefix(machine_id)
+        .and_then(|r| r.strip_prefix('-'))
+    {
+        // "<timestamp>-<random>"
+        Some(owned) => owned.rsplit_once('-')?.0,
+        None => rest,
+    };
+    parse_time(timestamp)
+}
+
+/// This machine's local temp branches older than the retention window
+///
+/// With a retention of 0, pulls delete their temp branch right away, so
+/// there is never anything to clean up.
+pub fn stale_own_temp_branches(
+    repo: &dyn Scm,
+    machine_id: &str,
+    retention_hours: u32,
+) -> Result<Vec<String>> {
+    if retention_hours == 0 {
+        return Ok(Vec::new());
+    }
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(retention_hours as i64);
+    Ok(repo
+        .list_branches()?
+        .into_iter()
+        .filter(|branch| {
+            owned_temp_branch_time(branch, machine_id).is_some_and(|time| time < cutoff)
+        })
+        .collect())
+}
+
+/// Clean up this machine's temporary branches that have exceeded their retention period
+///
+/// Branches created by other machines are left alone, even if they were
+/// fetched locally: their owner may still need them. Runs at the start of
+/// every pull and push, so a machine that only pushes cleans up too.
+/// Returns the number of branches deleted.
+pub fn cleanup_old_temp_branches(
+    repo: &dyn Scm,
+    machine_id: &str,
+    has_remote: bool,
+    retention_hours: u32,
+    verbosity: VerbosityLevel,
+) -> Result<usize> {
+    let stale = match stale_own_temp_branches(repo, machine_id, retention_hours) {
+        Ok(stale) => stale,
+        Err(e) => {
+            log::debug!("Failed to list branches for cleanup: {}", e);
+            return Ok(0);
+        }
+    };
+
+    let mut cleaned = 0;
+    for branch in stale {
+        log::debug!("Cleaning up old temp branch: {}", branch);
+
+        // Delete remote branch first
+        if has_remote {
+            if let Err(e) = repo.delete_remote_branch("origin", &branch) {
+                log::debug!("Failed to delete remote branch {}: {}", branch, e);
+            }
+        }
+
+        // Delete local branch
+        if let Err(e) = repo.delete_branch(&branch) {
+            log::debug!("Failed to delete local branch {}: {}", branch, e);
+        } else {
+            cleaned += 1;
+        }
+    }
+
+    if cleaned > 0 && verbosity >= VerbosityLevel::Normal {
+        println!(
+            "  {} Cleaned up {} old temp branch{}",
+            glyphs::ok().green(),
+            cleaned,
+            if cleaned == 1 { "" } else { "es" }
+        );
+    }
+
+    Ok(cleaned)
+}
+
+/// Temp branches on `remote`, from any machine, older than `max_age_hours`
+pub fn stale_remote_temp_branches(
+    repo: &dyn Scm,
+    remote: &str,
+    max_age_hours: u32,
+) -> Result<Vec<String>> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(max_age_hours as i64);
+    let mut stale: Vec<String> = repo
+        .list_remote_branches(remote)?
+        .into_iter()
+        .filter(|branch| temp_branch_time(branch).is_some_and(|time| time < cutoff))
+        .collect();
+    stale.sort();
+    Ok(stale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::MockScm;
+
+    #[test]
+    fn test_temp_branch_names_are_owned_per_machine() {
+        let ours = generate_temp_branch_name("laptop-1a2b3c4d");
+        assert!(ours.starts_with("sync-local-laptop-1a2b3c4d-"));
+        assert_ne!(ours, generate_temp_branch_name("laptop-1a2b3c4d"));
+        assert!(owned_temp_branch_time(&ours, "laptop-1a2b3c4d").is_some());
+        assert!(owned_temp_branch_time(&ours, "desktop-9f8e7d6c").is_none());
+        assert!(owned_temp_branch_time(&ours, "laptop").is_none());
+
+        // Branches from before machine IDs were added
+        assert!(owned_temp_branch_time("sync-local-20250101-120000", "laptop-1a2b3c4d").is_some());
+        assert!(owned_temp_branch_time("main", "laptop-1a2b3c4d").is_none());
+    }
+
+    #[test]
+    fn test_temp_branch_time_for_any_machine() {
+        let expected = parse_time("20250101-120000");
+        assert_eq!(temp_branch_time("sync-local-20250101-120000"), expected);
+        assert_eq!(
+            temp_branch_time("sync-local-old-box-9f8e7d6c-20250101-120000-a1b2c3"),
+            expected
+        );
+        assert!(temp_branch_time("sync-local-garbage").is_none());
+        assert!(temp_branch_time("feature-20250101-120000-a1b2c3").is_none());
+    }
+
+    #[test]
+    fn test_cleanup_old_temp_branches_only_ours() {
+        let old_ours = "sync-local-laptop-20200101-120000-abc123";
+        let old_theirs = "sync-local-desktop-20200101-120000-def456";
+        let new_ours = generate_temp_branch_name("laptop");
+        let repo = MockScm::new();
+        repo.write_file("README.md", "# sync\n");
+        repo.stage_all().unwrap();
+        repo.commit("Initial commit").unwrap();
+        repo.add_remote("origin", "mock://origin").unwrap();
+        for branch in [old_ours, old_theirs, &new_ours] {
+            repo.create_branch(branch).unwrap();
+            repo.push("origin", branch).unwrap();
+        }
+
+        let cleaned =
+            cleanup_old_temp_branches(&repo, "laptop", true, 24, VerbosityLevel::Quiet).unwrap();
+
+        assert_eq!(cleaned, 1);
+        let local = repo.list_branches().unwrap();
+        assert!(!local.iter().any(|b| b == old_ours));
+        assert!(local.contains(&old_theirs.to_string()));
+        assert!(local.contains(&new_ours));
+        assert!(!repo.remote_branches("origin").iter().any(|b| b == old_ours));
+        assert!(stale_own_temp_branches(&repo, "laptop", 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_temp_branch_machine() {
+        let branch = generate_temp_branch_name("old-box-9f8e7d6c");
+        assert_eq!(temp_branch_machine(&branch), Some("old-box-9f8e7d6c"));
+        assert_eq!(temp_branch_machine("sync-local-20250101-120000"), None);
+        assert_eq!(temp_branch_machine("main"), None);
+    }
+}
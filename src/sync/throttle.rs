@@ -0,0 +1,59 @@
+//! A minimum interval between automatic syncs
+//!
+//! Syncs nobody is waiting on, started by a schedule (`sync --scheduled`) or
+//! over the API (`serve`'s `POST /sync`), are skipped while the last
+//! operation in the history ran less than `min_sync_interval_secs` ago. A
+//! tight cron schedule or a chatty editor plugin then can't keep the disk,
+//! network, and fans busy. Syncs run by hand are never held back.
+
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+use crate::filter::FilterConfig;
+use crate::history::OperationHistory;
+
+/// Time left before an automatic sync may run, or `None` if it may run now
+pub fn automatic_sync_wait() -> Option<Duration> {
+    let min_interval = FilterConfig::load()
+        .map(|f| f.min_sync_interval_secs)
+        .unwrap_or_default();
+    if min_interval == 0 {
+        return None;
+    }
+    let history = OperationHistory::load().ok()?;
+    let last = history.get_last_operation()?.timestamp;
+    wait_after(last, min_interval, Utc::now())
+}
+
+/// Time left until `min_interval` seconds have passed since `last`
+fn wait_after(last: DateTime<Utc>, min_interval: u64, now: DateTime<Utc>) -> Option<Duration> {
+    let elapsed = (now - last).to_std().unwrap_or_default();
+    Duration::from_secs(min_interval)
+        .checked_sub(elapsed)
+        .filter(|wait| !wait.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_after() {
+        let last = DateTime::parse_from_rfc3339("2025-01-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let at = |secs| last + chrono::Duration::seconds(secs);
+
+        assert_eq!(
+            wait_after(last, 600, at(60)),
+            Some(Duration::from_secs(540))
+        );
+        assert_eq!(wait_after(last, 600, at(600)), None);
+        assert_eq!(wait_after(last, 600, at(3600)), None);
+        // A clock that went backwards doesn't hold syncs back for longer
+        assert_eq!(
+            wait_after(last, 600, at(-60)),
+            Some(Duration::from_secs(600))
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Per-phase timing for sync operations
+
+use anyhow::Result;
+use colored::Colorize;
+use std::time::Instant;
+
+use crate::history::PhaseTiming;
+
+/// Measures consecutive phases: each `mark` closes the phase that started at
+/// the previous mark (or at creation)
+pub(crate) struct PhaseTimer {
+    last: Instant,
+    phases: Vec<PhaseTiming>,
+}
+
+impl PhaseTimer {
+    pub(crate) fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// End the current phase under `name` and start the next one
+    ///
+    /// Phase boundaries double as fail points (see [`super::fail_point`]).
+    pub(crate) fn mark(&mut self, name: &str) -> Result<()> {
+        let now = Instant::now();
+        self.phases.push(PhaseTiming {
+            phase: name.to_string(),
+            millis: now.duration_since(self.last).as_millis() as u64,
+        });
+        self.last = now;
+        super::fail_point::check(name)
+    }
+
+    /// Print the phases recorded so far, slowest highlighted
+    pub(crate) fn print(&self) {
+        let total: u64 = self.phases.iter().map(|p| p.millis).sum();
+        let slowest = self.phases.iter().map(|p| p.millis).max().unwrap_or(0);
+        println!("\n{}", "Timings:".bold());
+        for phase in &self.phases {
+            let line = format!("  {:<20} {:>8.2}s", phase.phase, phase.millis as f64 / 1000.0);
+            if phase.millis == slowest && total > 0 {
+                println!("{}", line.yellow());
+            } else {
+                println!("{line}");
+            }
+        }
+        println!("  {:<20} {:>8.2}s", "total", total as f64 / 1000.0);
+    }
+
+    pub(crate) fn into_phases(self) -> Vec<PhaseTiming> {
+        self.phases
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marks_consecutive_phases() {
+        let mut timer = PhaseTimer::new();
+        timer.mark("temp branch").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        timer.mark("save local").unwrap();
+
+        let phases = timer.into_phases();
+        let names: Vec<_> = phases.iter().map(|p| p.phase.as_str()).collect();
+        assert_eq!(names, ["temp branch", "save local"]);
+        assert!(phases[1].millis >= 5);
+    }
+}
@@ -0,0 +1,261 @@
+//! Todo list sync
+//!
+//! Claude Code keeps per-session task lists in `~/.claude/todos/*.json`, each a
+//! JSON array of items like `{"content": ..., "status": ..., "activeForm": ...}`.
+//! These are mirrored to a `todos/` directory in the sync repo and merged in
+//! both directions during pull.
+//!
+//! Git checkouts reset file modification times, so the time each todo file was
+//! last written on its origin machine is tracked in a small manifest
+//! (`todos/.mtimes.json`) inside the sync repo.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// Name of the manifest recording origin modification times
+const MTIME_MANIFEST: &str = ".mtimes.json";
+
+/// How to combine a todo file that changed on both sides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TodoMergeMode {
+    /// Keep whichever file was written most recently
+    LastWriterWins,
+    /// Union the items of both files, preferring the newer file's version of an item
+    PerItem,
+}
+
+impl TodoMergeMode {
+    /// Parse a mode name from config ("last-writer-wins" or "per-item")
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "last-writer-wins" | "lww" => Some(TodoMergeMode::LastWriterWins),
+            "per-item" | "merge" => Some(TodoMergeMode::PerItem),
+            _ => None,
+        }
+    }
+}
+
+/// Counts of todo files written during a merge
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TodoSyncStats {
+    /// Files written to the local todos directory
+    pub local_updated: usize,
+    /// Files written to the sync repo todos directory
+    pub repo_updated: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MtimeManifest {
+    #[serde(flatten)]
+    files: BTreeMap<String, u64>,
+}
+
+impl MtimeManifest {
+    fn load(repo_todos: &Path) -> Self {
+        fs::read_to_string(repo_todos.join(MTIME_MANIFEST))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, repo_todos: &Path) -> Result<()> {
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize todo manifest")?;
+        fs::write(repo_todos.join(MTIME_MANIFEST), content)
+            .context("Failed to write todo manifest")?;
+        Ok(())
+    }
+}
+
+/// Merge todo files between the local todos directory and the sync repo
+///
+/// Files present on only one side are copied to the other. Files present on
+/// both sides with different content are combined according to `mode`.
+pub fn merge_todos(local_todos: &Path, repo_todos: &Path, mode: TodoMergeMode) -> Result<TodoSyncStats> {
+    let mut stats = TodoSyncStats::default();
+
+    if !local_todos.exists() && !repo_todos.exists() {
+        return Ok(stats);
+    }
+
+    fs::create_dir_all(local_todos)
+        .with_context(|| format!("Failed to create directory: {}", local_todos.display()))?;
+    fs::create_dir_all(repo_todos)
+        .with_context(|| format!("Failed to create directory: {}", repo_todos.display()))?;
+
+    let mut manifest = MtimeManifest::load(repo_todos);
+    let names: HashSet<String> = list_todo_files(local_todos)?
+        .into_iter()
+        .chain(list_todo_files(repo_todos)?)
+        .collect();
+
+    for name in names {
+        let local_path = local_todos.join(&name);
+        let repo_path = repo_todos.join(&name);
+        let local_content = fs::read_to_string(&local_path).ok();
+        let repo_content = fs::read_to_string(&repo_path).ok();
+        let local_mtime = file_mtime(&local_path);
+        let repo_mtime = manifest.files.get(&name).copied().unwrap_or(0);
+
+        let merged = match (&local_content, &repo_content) {
+            (Some(local), Some(remote)) if local == remote => None,
+            (Some(local), Some(remote)) => Some(match mode {
+                TodoMergeMode::LastWriterWins => {
+                    if local_mtime >= repo_mtime {
+                        local.clone()
+                    } else {
+                        remote.clone()
+                    }
+                }
+                TodoMergeMode::PerItem => merge_items(local, remote, local_mtime >= repo_mtime),
+            }),
+            (Some(local), None) => Some(local.clone()),
+            (None, Some(remote)) => Some(remote.clone()),
+            (None, None) => None,
+        };
+
+        let Some(merged) = merged else {
+            if local_content.is_some() {
+                manifest.files.insert(name, local_mtime.max(repo_mtime));
+            }
+            continue;
+        };
+
+        if local_content.as_deref() != Some(merged.as_str()) {
+            fs::write(&local_path, &merged)
+                .with_context(|| format!("Failed to write todo file: {}", local_path.display()))?;
+            stats.local_updated += 1;
+        }
+        if repo_content.as_deref() != Some(merged.as_str()) {
+            fs::write(&repo_path, &merged)
+                .with_context(|| format!("Failed to write todo file: {}", repo_path.display()))?;
+            stats.repo_updated += 1;
+        }
+        manifest.files.insert(name, file_mtime(&local_path).max(repo_mtime));
+    }
+
+    manifest.save(repo_todos)?;
+
+    Ok(stats)
+}
+
+/// Union two todo lists, keyed by item `id` (or `content` when there is no id)
+///
+/// Items from the preferred side keep their position and version; items only
+/// present on the other side are appended. Falls back to the preferred side's
+/// raw content if either file is not a JSON array.
+fn merge_items(local: &str, remote: &str, prefer_local: bool) -> String {
+    let (preferred, other) = if prefer_local { (local, remote) } else { (remote, local) };
+
+    let (Ok(Value::Array(mut items)), Ok(Value::Array(other_items))) = (
+        serde_json::from_str::<Value>(preferred),
+        serde_json::from_str::<Value>(other),
+    ) else {
+        return preferred.to_string();
+    };
+
+    let mut seen: HashSet<String> = items.iter().map(item_key).collect();
+    for item in other_items {
+        if seen.insert(item_key(&item)) {
+            items.push(item);
+        }
+    }
+
+    serde_json::to_string(&Value::Array(items)).unwrap_or_else(|_| preferred.to_string())
+}
+
+fn item_key(item: &Value) -> String {
+    item.get("id")
+        .or_else(|| item.get("content"))
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| item.to_string())
+}
+
+fn list_todo_files(dir: &Path) -> Result<Vec<String>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
+                if name != MTIME_MANIFEST {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    Ok(names)
+}
+
+fn file_mtime(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_merge_items_union_prefers_newer() {
+        let local = r#"[{"content":"a","status":"completed"},{"content":"b","status":"pending"}]"#;
+        let remote = r#"[{"content":"a","status":"pending"},{"content":"c","status":"pending"}]"#;
+
+        let merged: Vec<Value> = serde_json::from_str(&merge_items(local, remote, true)).unwrap();
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0]["status"], "completed");
+        assert_eq!(merged[2]["content"], "c");
+    }
+
+    #[test]
+    fn test_merge_todos_copies_one_sided_files() {
+        let temp = TempDir::new().unwrap();
+        let local = temp.path().join("local");
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(&local).unwrap();
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(local.join("s1.json"), "[]").unwrap();
+        fs::write(repo.join("s2.json"), r#"[{"content":"x"}]"#).unwrap();
+
+        let stats = merge_todos(&local, &repo, TodoMergeMode::LastWriterWins).unwrap();
+        assert_eq!(stats, TodoSyncStats { local_updated: 1, repo_updated: 1 });
+        assert!(repo.join("s1.json").exists());
+        assert!(local.join("s2.json").exists());
+        assert!(repo.join(MTIME_MANIFEST).exists());
+
+        // Second run is a no-op
+        let stats = merge_todos(&local, &repo, TodoMergeMode::LastWriterWins).unwrap();
+        assert_eq!(stats, TodoSyncStats::default());
+    }
+
+    #[test]
+    fn test_last_writer_wins_uses_manifest_time() {
+        let temp = TempDir::new().unwrap();
+        let local = temp.path().join("local");
+        let repo = temp.path().join("repo");
+        fs::create_dir_all(&local).unwrap();
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(local.join("s1.json"), "[1]").unwrap();
+        fs::write(repo.join("s1.json"), "[2]").unwrap();
+
+        // Remote was written far in the future relative to the local file
+        let mut manifest = MtimeManifest::default();
+        manifest.files.insert("s1.json".to_string(), u64::MAX);
+        manifest.save(&repo).unwrap();
+
+        merge_todos(&local, &repo, TodoMergeMode::LastWriterWins).unwrap();
+        assert_eq!(fs::read_to_string(local.join("s1.json")).unwrap(), "[2]");
+    }
+}
@@ -0,0 +1,149 @@
+//! Paths this tool writes in the sync repo
+//!
+//! Push and pull only stage [`managed_paths`], so other content in the sync
+//! repo, such as a hand edit, is never swept into a "Sync at ..." commit.
+//! Uncommitted changes outside those paths are listed before staging; on a
+//! terminal they can be committed along with the sync, otherwise they are
+//! left for the user.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use inquire::Confirm;
+use std::path::PathBuf;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::interactive_conflict;
+use crate::scm::Scm;
+use crate::VerbosityLevel;
+
+/// Top-level directories this tool writes, besides the sync subdirectory
+const MANAGED_DIRS: &[&str] = &[
+    super::namespaces::USERS_DIR,
+    super::oplog::OPLOG_DIR,
+    super::artifacts::FILE_HISTORY_DIR,
+    super::artifacts::SHELL_SNAPSHOTS_DIR,
+    crate::machines::MACHINES_DIR,
+    "todos",
+];
+
+/// Top-level files this tool writes
+const MANAGED_FILES: &[&str] = &[
+    "history.jsonl",
+    ".gitattributes",
+    crate::tags::TAG_INDEX_FILE,
+    crate::tombstones::TOMBSTONE_INDEX_FILE,
+    crate::resolutions::RESOLUTION_INDEX_FILE,
+    crate::purge::PURGE_INDEX_FILE,
+    crate::manifest::MANIFEST_FILE,
+    crate::families::FAMILY_INDEX_FILE,
+];
+
+/// Paths this tool writes, relative to the repo root, for
+/// [`Scm::stage_paths`]
+pub fn managed_paths(filter: &FilterConfig) -> Vec<PathBuf> {
+    let sync_subdirectory = filter.sync_subdirectory.trim_matches('/');
+    MANAGED_DIRS
+        .iter()
+        .chain(MANAGED_FILES)
+        .copied()
+        .chain([sync_subdirectory])
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Whether this tool writes `path` (relative to the repo root)
+fn is_managed(path: &str, filter: &FilterConfig) -> bool {
+    let sync_subdirectory = filter.sync_subdirectory.trim_matches('/');
+    let mut dirs = MANAGED_DIRS.iter().copied().chain([sync_subdirectory]);
+    MANAGED_FILES.contains(&path)
+        || dirs.any(|dir| {
+            path.strip_prefix(dir)
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+}
+
+/// Uncommitted paths in the sync repo outside the ones this tool writes
+pub(crate) fn unexpected_changes(repo: &dyn Scm, filter: &FilterConfig) -> Result<Vec<String>> {
+    Ok(repo
+        .uncommitted_paths()?
+        .into_iter()
+        .filter(|path| !is_managed(path, filter))
+        .collect())
+}
+
+/// List changes in the sync repo this tool didn't make before staging, and
+/// stage them too if the user confirms on a terminal
+///
+/// Otherwise they are left uncommitted, as only [`managed_paths`] are staged.
+pub(crate) fn confirm_unexpected_changes(
+    repo: &dyn Scm,
+    filter: &FilterConfig,
+    verbosity: VerbosityLevel,
+) -> Result<()> {
+    let unexpected = unexpected_changes(repo, filter)?;
+    if unexpected.is_empty() || verbosity < VerbosityLevel::Normal {
+        return Ok(());
+    }
+
+    println!(
+        "  {} The sync repo has uncommitted changes this tool didn't make:",
+        "!".yellow().bold()
+    );
+    for path in &unexpected {
+        println!("    {} {}", glyphs::detail().dimmed(), path);
+    }
+
+    if interactive_conflict::is_interactive() {
+        let confirm = Confirm::new("Commit these changes along with the sync?")
+            .with_default(false)
+            .with_help_message("Answer no to leave them uncommitted")
+            .prompt()
+            .context("Failed to get confirmation")?;
+        if confirm {
+            let paths: Vec<PathBuf> = unexpected.iter().map(PathBuf::from).collect();
+            return repo.stage_paths(&paths);
+        }
+    }
+    println!(
+        "  {} Left {} changes uncommitted; commit or discard them yourself",
+        glyphs::info().cyan(),
+        unexpected.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scm::MockScm;
+
+    #[test]
+    fn test_unexpected_changes_skip_managed_paths() {
+        let repo = MockScm::new();
+        repo.write_file("README.md", "v1");
+        repo.stage_all().unwrap();
+        repo.commit("Init").unwrap();
+
+        repo.write_file("projects/p/s.jsonl", "{}");
+        repo.write_file("history.jsonl", "{}");
+        repo.write_file("tags.json", "{}");
+        repo.write_file("oplog/m.jsonl", "{}");
+        repo.write_file("README.md", "v2");
+        repo.write_file("notes/todo.md", "hand-written");
+        repo.write_file("projects.md", "not the projects dir");
+
+        let filter = FilterConfig::default();
+        assert_eq!(
+            unexpected_changes(&repo, &filter).unwrap(),
+            ["README.md", "notes/todo.md", "projects.md"]
+        );
+
+        repo.stage_paths(&managed_paths(&filter)).unwrap();
+        repo.commit("Sync").unwrap();
+        assert_eq!(
+            repo.uncommitted_paths().unwrap(),
+            ["README.md", "notes/todo.md", "projects.md"]
+        );
+    }
+}
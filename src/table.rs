@@ -0,0 +1,298 @@
+//! Plain-text, JSON, and CSV rendering of tabular summaries
+//!
+//! Column widths are measured in terminal cells rather than bytes or chars
+//! (see [`crate::text`]), so wide (e.g. CJK) project names line up, and
+//! cells over a column's maximum width are truncated with an ellipsis. The
+//! same [`Table`] can be rendered as JSON (an array of objects keyed by
+//! column) or CSV for `--format json|csv`.
+
+use anyhow::{bail, Result};
+
+use crate::text::{display_width, truncate_to_width};
+pub use crate::text::Keep;
+
+/// Output format selected with `--format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    /// Parse a format name ("table", "json", or "csv")
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "table" | "text" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            other => bail!("Unknown format: '{}'. Use 'table', 'json', or 'csv'.", other),
+        }
+    }
+}
+
+/// Horizontal alignment of a column in table output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A column definition
+#[derive(Debug, Clone)]
+pub struct Column {
+    /// Header shown in table and CSV output
+    pub header: String,
+    /// Key used for JSON output
+    pub key: String,
+    pub align: Align,
+    /// Maximum cell width in terminal cells (table output only)
+    pub max_width: Option<usize>,
+    pub keep: Keep,
+}
+
+impl Column {
+    /// A left-aligned column with no width limit
+    pub fn new(header: &str, key: &str) -> Self {
+        Self {
+            header: header.to_string(),
+            key: key.to_string(),
+            align: Align::Left,
+            max_width: None,
+            keep: Keep::Start,
+        }
+    }
+
+    /// Right-align the column (numbers); its cells are emitted as JSON
+    /// numbers when they parse as one
+    pub fn right(mut self) -> Self {
+        self.align = Align::Right;
+        self
+    }
+
+    /// Truncate cells wider than `width`, keeping the given end
+    pub fn max_width(mut self, width: usize, keep: Keep) -> Self {
+        self.max_width = Some(width);
+        self.keep = keep;
+        self
+    }
+}
+
+/// Rows of string cells under a set of columns
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+    /// Rows drawn below a rule in table output (e.g. totals)
+    footer: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self {
+            columns,
+            rows: Vec::new(),
+            footer: Vec::new(),
+        }
+    }
+
+    /// Append a row; missing cells are treated as empty
+    pub fn push_row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    /// Append a footer row, shown below a rule in table output and as a
+    /// regular row in JSON/CSV
+    pub fn push_footer(&mut self, cells: Vec<String>) {
+        self.footer.push(cells);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Render in the given format
+    pub fn render(&self, format: OutputFormat) -> Result<String> {
+        Ok(match format {
+            OutputFormat::Table => self.to_text(),
+            OutputFormat::Json => self.to_json()?,
+            OutputFormat::Csv => self.to_csv(),
+        })
+    }
+
+    /// Aligned plain-text table, each line indented by two spaces
+    pub fn to_text(&self) -> String {
+        let cell = |row: &[String], i: usize| -> String {
+            let value = row.get(i).map(String::as_str).unwrap_or("");
+            match self.columns[i].max_width {
+                Some(max) => truncate_to_width(value, max, self.columns[i].keep),
+                None => value.to_string(),
+            }
+        };
+        let body: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .chain(&self.footer)
+            .map(|row| (0..self.columns.len()).map(|i| cell(row, i)).collect())
+            .collect();
+
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                body.iter()
+                    .map(|row| display_width(&row[i]))
+                    .chain(std::iter::once(display_width(&col.header)))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let format_line = |cells: Vec<&str>| -> String {
+            let parts: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(i, value)| pad(value, widths[i], self.columns[i].align))
+                .collect();
+            format!("  {}", parts.join("  ").trim_end())
+        };
+
+        let mut lines = vec![format_line(
+            self.columns.iter().map(|c| c.header.as_str()).collect(),
+        )];
+        let (rows, footer) = body.split_at(self.rows.len());
+        for row in rows {
+            lines.push(format_line(row.iter().map(String::as_str).collect()));
+        }
+        if !footer.is_empty() {
+            let total_width = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+            lines.push(format!("  {}", "-".repeat(total_width)));
+            for row in footer {
+                lines.push(format_line(row.iter().map(String::as_str).collect()));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// JSON array with one object per row, keyed by column key
+    ///
+    /// Cells of right-aligned columns that parse as numbers are emitted as
+    /// JSON numbers; everything else is a string.
+    pub fn to_json(&self) -> Result<String> {
+        let rows: Vec<serde_json::Value> = self
+            .rows
+            .iter()
+            .chain(&self.footer)
+            .map(|row| {
+                let object = self
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, col)| {
+                        let value = row.get(i).map(String::as_str).unwrap_or("");
+                        let value = match col.align {
+                            Align::Right => json_value(value),
+                            Align::Left => value.into(),
+                        };
+                        (col.key.clone(), value)
+                    })
+                    .collect();
+                serde_json::Value::Object(object)
+            })
+            .collect();
+        Ok(serde_json::to_string_pretty(&rows)?)
+    }
+
+    /// RFC 4180 CSV with a header row
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        let header: Vec<String> = self.columns.iter().map(|c| csv_field(&c.header)).collect();
+        out.push_str(&header.join(","));
+        out.push_str("\r\n");
+        for row in self.rows.iter().chain(&self.footer) {
+            let cells: Vec<String> = (0..self.columns.len())
+                .map(|i| csv_field(row.get(i).map(String::as_str).unwrap_or("")))
+                .collect();
+            out.push_str(&cells.join(","));
+            out.push_str("\r\n");
+        }
+        out
+    }
+}
+
+fn pad(value: &str, width: usize, align: Align) -> String {
+    let fill = " ".repeat(width.saturating_sub(display_width(value)));
+    match align {
+        Align::Left => format!("{value}{fill}"),
+        Align::Right => format!("{fill}{value}"),
+    }
+}
+
+fn json_value(value: &str) -> serde_json::Value {
+    if let Ok(n) = value.parse::<u64>() {
+        return n.into();
+    }
+    if let Ok(n) = value.parse::<f64>() {
+        if n.is_finite() {
+            return n.into();
+        }
+    }
+    value.into()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table {
+        let mut table = Table::new(vec![
+            Column::new("Project", "project").max_width(8, Keep::End),
+            Column::new("Count", "count").right(),
+        ]);
+        table.push_row(vec!["日本語".to_string(), "5".to_string()]);
+        table.push_row(vec!["-home-me-app".to_string(), "12".to_string()]);
+        table.push_footer(vec!["Total".to_string(), "17".to_string()]);
+        table
+    }
+
+    #[test]
+    fn test_text_alignment_with_wide_chars() {
+        let text = sample().to_text();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines[0], "  Project   Count");
+        assert_eq!(lines[1], "  日本語        5");
+        assert_eq!(lines[2], "  …-me-app     12");
+        assert!(lines[3].starts_with("  ---"));
+        assert_eq!(lines[4], "  Total        17");
+        // Every line has the same display width
+        assert!(lines.iter().all(|l| display_width(l) == display_width(lines[0])));
+    }
+
+    #[test]
+    fn test_json_and_csv() {
+        let table = sample();
+        let json: serde_json::Value = serde_json::from_str(&table.to_json().unwrap()).unwrap();
+        assert_eq!(json[1]["project"], "-home-me-app");
+        assert_eq!(json[1]["count"], 12);
+        assert_eq!(json.as_array().unwrap().len(), 3);
+
+        let mut quoted = Table::new(vec![Column::new("Name", "name")]);
+        quoted.push_row(vec!["a, \"b\"".to_string()]);
+        assert_eq!(quoted.to_csv(), "Name\r\n\"a, \"\"b\"\"\"\r\n");
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(OutputFormat::parse("CSV").unwrap(), OutputFormat::Csv);
+        assert!(OutputFormat::parse("xml").is_err());
+    }
+}
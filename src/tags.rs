@@ -0,0 +1,154 @@
+//! Session tags
+//!
+//! Tags are free-form labels such as `work/infra` attached to session IDs.
+//! They live in a sidecar index (`tags.json`) at the root of the sync repo so
+//! every machine sees the same labels. Tags are hierarchical: filtering by
+//! `work` also matches `work/infra`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the tag index within the sync repo
+pub const TAG_INDEX_FILE: &str = "tags.json";
+
+/// Mapping of session ID to its tags
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TagIndex {
+    #[serde(flatten)]
+    pub sessions: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TagIndex {
+    /// Path of the tag index within a sync repo
+    pub fn path(sync_repo: &Path) -> PathBuf {
+        sync_repo.join(TAG_INDEX_FILE)
+    }
+
+    /// Load the tag index from a sync repo (empty if it doesn't exist)
+    pub fn load(sync_repo: &Path) -> Result<Self> {
+        let path = Self::path(sync_repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read tag index: {}", path.display()))?;
+        serde_json::from_str(&content).context("Failed to parse tag index")
+    }
+
+    /// Save the tag index to a sync repo
+    pub fn save(&self, sync_repo: &Path) -> Result<()> {
+        let path = Self::path(sync_repo);
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize tag index")?;
+        fs::write(&path, content + "\n")
+            .with_context(|| format!("Failed to write tag index: {}", path.display()))
+    }
+
+    /// Add a tag to a session, returning false if it was already present
+    pub fn add(&mut self, session_id: &str, tag: &str) -> bool {
+        self.sessions
+            .entry(session_id.to_string())
+            .or_default()
+            .insert(tag.to_string())
+    }
+
+    /// Remove a tag from a session, returning false if it wasn't present
+    pub fn remove(&mut self, session_id: &str, tag: &str) -> bool {
+        let Some(tags) = self.sessions.get_mut(session_id) else {
+            return false;
+        };
+        let removed = tags.remove(tag);
+        if tags.is_empty() {
+            self.sessions.remove(session_id);
+        }
+        removed
+    }
+
+    /// Tags attached to a session
+    pub fn tags_for(&self, session_id: &str) -> Vec<&str> {
+        self.sessions
+            .get(session_id)
+            .map(|tags| tags.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Check if a session has a tag matching `pattern` (exact or as a parent)
+    pub fn has_tag(&self, session_id: &str, pattern: &str) -> bool {
+        self.tags_for(session_id)
+            .iter()
+            .any(|tag| tag_matches(pattern, tag))
+    }
+
+    /// Apply include/exclude tag filters to a session
+    ///
+    /// Excludes win over includes. With no include tags, every session not
+    /// excluded passes.
+    pub fn passes(&self, session_id: &str, include: &[String], exclude: &[String]) -> bool {
+        if exclude.iter().any(|p| self.has_tag(session_id, p)) {
+            return false;
+        }
+        include.is_empty() || include.iter().any(|p| self.has_tag(session_id, p))
+    }
+}
+
+/// A pattern matches a tag if equal, or if the tag is nested under it
+fn tag_matches(pattern: &str, tag: &str) -> bool {
+    let pattern = pattern.trim_end_matches('/');
+    tag == pattern
+        || tag
+            .strip_prefix(pattern)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Normalize a user-supplied tag (trimmed, no leading/trailing slashes)
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_matches('/');
+    (!tag.is_empty()).then(|| tag.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hierarchical_matching() {
+        assert!(tag_matches("work", "work/infra"));
+        assert!(tag_matches("work/infra", "work/infra"));
+        assert!(tag_matches("work/", "work/infra"));
+        assert!(!tag_matches("work", "workshop"));
+        assert!(!tag_matches("work/infra", "work"));
+    }
+
+    #[test]
+    fn test_passes_include_exclude() {
+        let mut index = TagIndex::default();
+        index.add("a", "work/infra");
+        index.add("b", "personal");
+        index.add("c", "work/secret");
+
+        let include = vec!["work".to_string()];
+        let exclude = vec!["work/secret".to_string()];
+        assert!(index.passes("a", &include, &exclude));
+        assert!(!index.passes("b", &include, &exclude));
+        assert!(!index.passes("c", &include, &exclude));
+        assert!(index.passes("untagged", &[], &exclude));
+        assert!(!index.passes("untagged", &include, &[]));
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let mut index = TagIndex::default();
+        assert!(index.add("s1", "work"));
+        assert!(!index.add("s1", "work"));
+        index.save(temp.path()).unwrap();
+
+        let mut loaded = TagIndex::load(temp.path()).unwrap();
+        assert_eq!(loaded.tags_for("s1"), vec!["work"]);
+        assert!(loaded.remove("s1", "work"));
+        assert!(loaded.sessions.is_empty());
+    }
+}
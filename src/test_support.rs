@@ -0,0 +1,329 @@
+//! Throwaway sync environments for integration tests
+//!
+//! Enabled by the `test_support` feature. A [`TestEnv`] lives in a temporary
+//! directory and holds everything a sync needs: a home directory with
+//! `.claude/projects/`, a config directory with `state.json` and
+//! `config.toml`, and a git sync repository with an initial commit,
+//! optionally pushed to a bare remote.
+//!
+//! ```no_run
+//! use claude_code_sync::test_support::TestEnv;
+//!
+//! let env = TestEnv::new().with_sessions(3).with_remote();
+//! let _active = env.activate();
+//! // claude_code_sync::sync::push_history(...) now works on `env`
+//! ```
+//!
+//! In-process calls find the environment through [`TestEnv::activate`], which
+//! points `CLAUDE_CODE_SYNC_CONFIG_DIR` and `HOME` at it; the binary gets the
+//! same variables from [`TestEnv::command`], or [`TestEnv::run`] and
+//! [`TestEnv::run_ok`], which find the built `claude-code-sync` binary
+//! themselves. Setup failures panic, like `unwrap` in a test would.
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+use crate::config::CONFIG_DIR_ENV_VAR;
+use crate::filter::FilterConfig;
+use crate::paths::encode_project_path;
+use crate::scm;
+use crate::sync::SyncState;
+
+/// Entries written to each session created by [`TestEnv::with_sessions`]
+const ENTRIES_PER_SESSION: usize = 4;
+
+/// A temporary home, config directory, and sync repository
+pub struct TestEnv {
+    root: TempDir,
+    filter: FilterConfig,
+    state: SyncState,
+    session_ids: Vec<String>,
+    remote: Option<PathBuf>,
+}
+
+impl TestEnv {
+    /// Create an initialized environment with no sessions and no remote
+    pub fn new() -> Self {
+        let root = TempDir::new().expect("Failed to create test directory");
+        let mut env = Self {
+            filter: FilterConfig::default(),
+            state: SyncState {
+                sync_repo_path: root.path().join("repo"),
+                has_remote: false,
+                is_cloned_repo: false,
+                rsync_remote: None,
+            },
+            root,
+            session_ids: Vec::new(),
+            remote: None,
+        };
+        env.filter.claude_projects_dir = Some(env.projects_dir().to_string_lossy().to_string());
+
+        fs::create_dir_all(env.projects_dir()).expect("Failed to create projects directory");
+        fs::create_dir_all(env.project_path()).expect("Failed to create project directory");
+        fs::create_dir_all(env.config_dir()).expect("Failed to create config directory");
+        let repo = scm::init(env.repo_dir()).expect("Failed to create sync repository");
+        fs::write(env.repo_dir().join("README.md"), "# Test sync repository\n")
+            .expect("Failed to write README.md");
+        repo.stage_all().expect("Failed to stage README.md");
+        repo.commit("Initial commit")
+            .expect("Failed to commit README.md");
+
+        env.write_state();
+        env.write_filter();
+        env
+    }
+
+    /// Add `count` sessions of a few entries each to the local project
+    pub fn with_sessions(mut self, count: usize) -> Self {
+        let project_dir = self
+            .projects_dir()
+            .join(encode_project_path(&self.project_path()));
+        fs::create_dir_all(&project_dir).expect("Failed to create project directory");
+
+        for _ in 0..count {
+            let number = self.session_ids.len() + 1;
+            let session_id = format!("00000000-0000-4000-8000-{number:012x}");
+            let lines: Vec<String> = (0..ENTRIES_PER_SESSION)
+                .map(|i| self.entry_line(&session_id, number, i))
+                .collect();
+            fs::write(
+                project_dir.join(format!("{session_id}.jsonl")),
+                lines.join("\n") + "\n",
+            )
+            .expect("Failed to write session file");
+            self.session_ids.push(session_id);
+        }
+        self
+    }
+
+    /// Push the sync repository to a new bare repository as `origin`
+    pub fn with_remote(mut self) -> Self {
+        let remote = self.root.path().join("remote.git");
+        let output = Command::new("git")
+            .args(["init", "--bare"])
+            .arg(&remote)
+            .output()
+            .expect("Failed to run 'git init --bare'");
+        assert!(
+            output.status.success(),
+            "git init --bare failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let repo = scm::open(self.repo_dir()).expect("Failed to open sync repository");
+        repo.add_remote("origin", &remote.to_string_lossy())
+            .expect("Failed to add remote");
+        let branch = repo.current_branch().expect("Failed to get current branch");
+        repo.push("origin", &branch)
+            .expect("Failed to push to remote");
+
+        self.remote = Some(remote);
+        self.state.has_remote = true;
+        self.write_state();
+        self
+    }
+
+    /// Change the filter configuration written to `config.toml`
+    pub fn with_filter(mut self, change: impl FnOnce(&mut FilterConfig)) -> Self {
+        change(&mut self.filter);
+        self.write_filter();
+        self
+    }
+
+    /// Point `CLAUDE_CODE_SYNC_CONFIG_DIR` and `HOME` at this environment
+    /// until the returned guard is dropped
+    ///
+    /// Environment variables are process-wide, so tests that activate an
+    /// environment must not run in parallel (e.g. use `serial_test`).
+    pub fn activate(&self) -> ActiveEnv {
+        let saved = [CONFIG_DIR_ENV_VAR, "HOME"].map(|name| (name, std::env::var_os(name)));
+        std::env::set_var(CONFIG_DIR_ENV_VAR, self.config_dir());
+        std::env::set_var("HOME", self.home_dir());
+        ActiveEnv { saved }
+    }
+
+    /// A command for `program` (e.g. `env!("CARGO_BIN_EXE_claude-code-sync")`)
+    /// that runs against this environment
+    pub fn command(&self, program: impl AsRef<OsStr>) -> Command {
+        let mut command = Command::new(program);
+        command
+            .env(CONFIG_DIR_ENV_VAR, self.config_dir())
+            .env("HOME", self.home_dir())
+            .current_dir(self.project_path());
+        command
+    }
+
+    /// Run the `claude-code-sync` binary with `args` against this environment
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.command(binary())
+            .args(args)
+            .output()
+            .expect("Failed to run claude-code-sync")
+    }
+
+    /// [`Self::run`], failing the test unless the command succeeds
+    pub fn run_ok(&self, args: &[&str]) -> Output {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "claude-code-sync {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output
+    }
+
+    /// Run git with `args` in the sync repository; see [`git`]
+    pub fn git(&self, args: &[&str]) -> String {
+        git(self.repo_dir(), args)
+    }
+
+    /// Root of the temporary directory
+    pub fn root(&self) -> &Path {
+        self.root.path()
+    }
+
+    /// Home directory (`HOME` while active)
+    pub fn home_dir(&self) -> PathBuf {
+        self.root.path().join("home")
+    }
+
+    /// Claude Code's data directory, `~/.claude`
+    pub fn claude_dir(&self) -> PathBuf {
+        self.home_dir().join(".claude")
+    }
+
+    /// Claude Code's projects directory, `~/.claude/projects`
+    pub fn projects_dir(&self) -> PathBuf {
+        self.claude_dir().join("projects")
+    }
+
+    /// Working directory of the project the sessions belong to
+    pub fn project_path(&self) -> PathBuf {
+        self.home_dir().join("project")
+    }
+
+    /// Directory holding `state.json`, `config.toml`, and all other state
+    pub fn config_dir(&self) -> PathBuf {
+        self.root.path().join("config")
+    }
+
+    /// The sync repository
+    pub fn repo_dir(&self) -> &Path {
+        &self.state.sync_repo_path
+    }
+
+    /// The bare remote, if [`Self::with_remote`] was used
+    pub fn remote_dir(&self) -> Option<&Path> {
+        self.remote.as_deref()
+    }
+
+    /// IDs of the sessions created so far, in creation order
+    pub fn session_ids(&self) -> &[String] {
+        &self.session_ids
+    }
+
+    /// Path of a session created by [`Self::with_sessions`]
+    pub fn session_path(&self, session_id: &str) -> PathBuf {
+        self.projects_dir()
+            .join(encode_project_path(&self.project_path()))
+            .join(format!("{session_id}.jsonl"))
+    }
+
+    fn entry_line(&self, session_id: &str, session: usize, index: usize) -> String {
+        let uuid = |i: usize| format!("00000000-0000-4000-{session:04x}-{i:012x}");
+        let (role, content) = if index.is_multiple_of(2) {
+            (
+                "user",
+                format!("Question {} in session {}", index / 2 + 1, session),
+            )
+        } else {
+            (
+                "assistant",
+                format!("Answer {} in session {}", index / 2 + 1, session),
+            )
+        };
+        serde_json::json!({
+            "parentUuid": index.checked_sub(1).map(uuid),
+            "isSidechain": false,
+            "userType": "external",
+            "cwd": self.project_path(),
+            "sessionId": session_id,
+            "version": "2.0.22",
+            "type": role,
+            "message": { "role": role, "content": content },
+            "uuid": uuid(index),
+            "timestamp": format!("2025-01-{:02}T10:{:02}:00.000Z", session % 28 + 1, index),
+        })
+        .to_string()
+    }
+
+    fn write_state(&self) {
+        let content =
+            serde_json::to_string_pretty(&self.state).expect("Failed to serialize sync state");
+        fs::write(self.config_dir().join("state.json"), content)
+            .expect("Failed to write state.json");
+    }
+
+    fn write_filter(&self) {
+        let content = toml::to_string_pretty(&self.filter).expect("Failed to serialize config");
+        fs::write(self.config_dir().join("config.toml"), content)
+            .expect("Failed to write config.toml");
+    }
+}
+
+/// The `claude-code-sync` binary Cargo built alongside the running test
+///
+/// Integration tests run from `target/<profile>/deps/`, and the binary is
+/// in `target/<profile>/`.
+fn binary() -> PathBuf {
+    let mut dir = std::env::current_exe().expect("Failed to find the test executable");
+    dir.pop();
+    if dir.ends_with("deps") {
+        dir.pop();
+    }
+    dir.join(format!("claude-code-sync{}", std::env::consts::EXE_SUFFIX))
+}
+
+/// Run git with `args` in `dir`, failing the test unless it succeeds, and
+/// return its stdout without the trailing newline
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("Failed to run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim_end().to_string()
+}
+
+impl Default for TestEnv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Restores the environment variables changed by [`TestEnv::activate`]
+pub struct ActiveEnv {
+    saved: [(&'static str, Option<OsString>); 2],
+}
+
+impl Drop for ActiveEnv {
+    fn drop(&mut self) {
+        for (name, value) in &self.saved {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+    }
+}
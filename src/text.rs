@@ -0,0 +1,144 @@
+//! Unicode-safe measuring and truncation of user-facing text
+//!
+//! Session titles and prompts are arbitrary user text: emoji, CJK, combining
+//! marks, and ZWJ sequences. Everything here works on grapheme clusters
+//! measured in terminal cells, so truncation never splits a character (or a
+//! family emoji) and never panics on short input, unlike byte slicing.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Which part of an over-long string is kept when truncating
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    /// Keep the beginning: `long-name…`
+    Start,
+    /// Keep the end: `…ong-name` (for paths, where the tail is most telling)
+    End,
+}
+
+/// Width of `s` in terminal cells
+///
+/// ANSI escape sequences (from `colored`) take no space, so styled text
+/// measures the same as plain text.
+pub fn display_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut rest = s;
+    while let Some(start) = rest.find('\u{1b}') {
+        width += rest[..start].width();
+        // Skip to the end of the CSI sequence (a letter)
+        let after = &rest[start + 1..];
+        rest = match after.find(|c: char| c.is_ascii_alphabetic()) {
+            Some(end) => &after[end + 1..],
+            None => "",
+        };
+    }
+    width + rest.width()
+}
+
+/// Truncate `s` to at most `max` terminal cells, marking the cut with `…`
+/// (`...` in ASCII mode)
+///
+/// Grapheme clusters are kept or dropped whole.
+pub fn truncate_to_width(s: &str, max: usize, keep: Keep) -> String {
+    truncate_with_marker(s, max, keep, crate::glyphs::ellipsis())
+}
+
+fn truncate_with_marker(s: &str, max: usize, keep: Keep, marker: &str) -> String {
+    if display_width(s) <= max {
+        return s.to_string();
+    }
+    // Too narrow for the marker: cut without one
+    let marker = if marker.width() > max { "" } else { marker };
+    let budget = max - marker.width();
+    let mut kept = Vec::new();
+    let mut width = 0;
+    let graphemes: Box<dyn Iterator<Item = &str>> = match keep {
+        Keep::Start => Box::new(s.graphemes(true)),
+        Keep::End => Box::new(s.graphemes(true).rev()),
+    };
+    for g in graphemes {
+        let w = g.width();
+        if width + w > budget {
+            break;
+        }
+        width += w;
+        kept.push(g);
+    }
+
+    match keep {
+        Keep::Start => format!("{}{marker}", kept.concat()),
+        Keep::End => {
+            kept.reverse();
+            format!("{marker}{}", kept.concat())
+        }
+    }
+}
+
+/// The first `len` characters of an identifier (session ID, hash, ...)
+///
+/// Returns the whole string when it is shorter, instead of panicking like
+/// `&id[..len]` would.
+pub fn short_id(id: &str, len: usize) -> &str {
+    match id.char_indices().nth(len) {
+        Some((end, _)) => &id[..end],
+        None => id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("abc", 5, Keep::Start), "abc");
+        assert_eq!(truncate_to_width("abcdef", 4, Keep::Start), "abc…");
+        assert_eq!(truncate_to_width("abcdef", 4, Keep::End), "…def");
+        // Wide characters take two cells and are never split
+        assert_eq!(truncate_to_width("日本語テキスト", 6, Keep::Start), "日本…");
+        assert_eq!(display_width("日本…"), 5);
+        assert_eq!(display_width("\u{1b}[32mADD\u{1b}[0m"), 3);
+
+        // The ASCII marker takes three cells, or is left out when it won't fit
+        assert_eq!(
+            truncate_with_marker("abcdef", 5, Keep::Start, "..."),
+            "ab..."
+        );
+        assert_eq!(truncate_with_marker("abcdef", 2, Keep::End, "..."), "ef");
+    }
+
+    #[test]
+    fn test_emoji_titles_are_not_split() {
+        // Family (ZWJ sequence), flag, and a combining accent
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let title = format!("{family}🇯🇵 cafe\u{301} debugging session");
+        let graphemes: Vec<&str> = title.graphemes(true).collect();
+
+        for max in 0..=display_width(&title) {
+            for keep in [Keep::Start, Keep::End] {
+                let cut = truncate_to_width(&title, max, keep);
+                assert!(display_width(&cut) <= max, "{cut:?} wider than {max}");
+                let body = cut.trim_start_matches('…').trim_end_matches('…');
+                let whole = (0..=graphemes.len()).any(|n| match keep {
+                    Keep::Start => graphemes[..n].concat() == body,
+                    Keep::End => graphemes[n..].concat() == body,
+                });
+                assert!(whole, "{cut:?} split a grapheme");
+            }
+        }
+        assert_eq!(
+            truncate_to_width(&title, 3, Keep::Start),
+            format!("{family}…")
+        );
+        assert!(truncate_to_width("cafe\u{301}!!", 5, Keep::Start).ends_with("e\u{301}…"));
+    }
+
+    #[test]
+    fn test_short_id() {
+        assert_eq!(short_id("0123456789", 8), "01234567");
+        assert_eq!(short_id("abc", 8), "abc");
+        assert_eq!(short_id("", 8), "");
+        assert_eq!(short_id("ééééééééé", 8), "éééééééé");
+    }
+}
@@ -0,0 +1,302 @@
+//! Chronological activity across all synced sessions
+//!
+//! The sync repo holds the sessions of every machine, so interleaving their
+//! messages by timestamp shows what was worked on, and where, regardless of
+//! which machine each conversation ran on.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::families::FamilyIndex;
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::parser::{ConversationEntry, ConversationSession};
+use crate::paths::project_dir_name;
+use crate::table::{Column, Keep, OutputFormat, Table};
+use crate::text::short_id;
+use crate::usage::parse_since;
+
+/// Width of message previews in the table view
+const PREVIEW_WIDTH: usize = 80;
+
+/// One message in the timeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineEvent {
+    pub timestamp: DateTime<Utc>,
+    pub project: String,
+    pub session_id: String,
+    /// The session the message's thread family started from: the session
+    /// itself unless it was forked from another
+    pub thread: String,
+    /// `user` or `assistant`
+    pub role: String,
+    /// The message's text, whitespace collapsed onto one line
+    pub preview: String,
+}
+
+/// Text of a user or assistant message, on one line
+///
+/// Tool calls and tool results carry no text and yield `None`.
+pub(crate) fn message_preview(entry: &ConversationEntry) -> Option<String> {
+    let content = entry.message.as_ref()?.get("content")?;
+    let text = match content {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text")?.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => return None,
+    };
+    let preview = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!preview.is_empty()).then_some(preview)
+}
+
+/// Messages from `sessions` at or after `since`, oldest first
+///
+/// Subagent (sidechain) messages are left out, and an entry that appears in
+/// more than one file is listed once, so the entries a fork copied from its
+/// parent show up under the parent.
+pub fn build_timeline(
+    sessions: &[ConversationSession],
+    projects_dir: &Path,
+    families: &FamilyIndex,
+    since: DateTime<Utc>,
+) -> Vec<TimelineEvent> {
+    let mut events = Vec::new();
+    let mut seen = HashSet::new();
+
+    for session in sessions {
+        let project = Path::new(&session.file_path)
+            .strip_prefix(projects_dir)
+            .ok()
+            .and_then(project_dir_name)
+            .unwrap_or_else(|| "unknown".to_string());
+        let thread = families.root_of(&session.session_id).to_string();
+
+        for entry in &session.entries {
+            if !matches!(entry.entry_type.as_str(), "user" | "assistant") || entry.is_sidechain() {
+                continue;
+            }
+            let Some(timestamp) = entry
+                .timestamp
+                .as_deref()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Utc))
+            else {
+                continue;
+            };
+            if timestamp < since {
+                continue;
+            }
+            if let Some(uuid) = &entry.uuid {
+                if !seen.insert(uuid.clone()) {
+                    continue;
+                }
+            }
+            let Some(preview) = message_preview(entry) else {
+                continue;
+            };
+            events.push(TimelineEvent {
+                timestamp,
+                project: project.clone(),
+                session_id: session.session_id.clone(),
+                thread: thread.clone(),
+                role: entry.entry_type.clone(),
+                preview,
+            });
+        }
+    }
+
+    events.sort_by_key(|event| event.timestamp);
+    events
+}
+
+/// Show the timeline of the sessions in the sync repository
+pub fn show_timeline(since: &str, format: &str) -> Result<()> {
+    let format = OutputFormat::parse(format)?;
+    let cutoff = parse_since(since)?;
+
+    let state = crate::sync::SyncState::load()?;
+    let filter = FilterConfig::load()?;
+    let projects_dir = state.sync_repo_path.join(filter.projects_subdir());
+    let sessions = crate::sync::discover_sessions(&projects_dir, &FilterConfig::default())?;
+    let families = FamilyIndex::load(&state.sync_repo_path)?;
+    let events = build_timeline(&sessions, &projects_dir, &families, cutoff);
+
+    if format != OutputFormat::Table {
+        println!("{}", timeline_table(&events, true).render(format)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Timeline (last {since})").cyan().bold());
+    if events.is_empty() {
+        println!("{}", "No activity found.".yellow());
+        return Ok(());
+    }
+    print!("{}", timeline_table(&events, false).to_text());
+    let sessions: HashSet<&str> = events.iter().map(|e| e.session_id.as_str()).collect();
+    let threads: HashSet<&str> = events.iter().map(|e| e.thread.as_str()).collect();
+    let projects: HashSet<&str> = events.iter().map(|e| e.project.as_str()).collect();
+    println!(
+        "\n  {} {} messages in {} sessions ({} threads) across {} projects",
+        glyphs::info().cyan(),
+        events.len(),
+        sessions.len(),
+        threads.len(),
+        projects.len()
+    );
+
+    Ok(())
+}
+
+/// One row per message; `full` keeps exact times, whole IDs, and whole text
+fn timeline_table(events: &[TimelineEvent], full: bool) -> Table {
+    let mut preview = Column::new("Message", "preview");
+    if !full {
+        preview = preview.max_width(PREVIEW_WIDTH, Keep::Start);
+    }
+    let mut table = Table::new(vec![
+        Column::new("Time", "timestamp"),
+        Column::new("Project", "project").max_width(30, Keep::End),
+        Column::new("Session", "session_id"),
+        Column::new("Thread", "thread"),
+        Column::new("Role", "role"),
+        preview,
+    ]);
+    for event in events {
+        table.push_row(vec![
+            if full {
+                event.timestamp.to_rfc3339()
+            } else {
+                event
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            },
+            event.project.clone(),
+            if full {
+                event.session_id.clone()
+            } else {
+                short_id(&event.session_id, 8).to_string()
+            },
+            if full {
+                event.thread.clone()
+            } else {
+                short_id(&event.thread, 8).to_string()
+            },
+            event.role.clone(),
+            event.preview.clone(),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(json: &str) -> ConversationEntry {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn session(id: &str, project: &str, entries: Vec<ConversationEntry>) -> ConversationSession {
+        ConversationSession {
+            session_id: id.to_string(),
+            entries,
+            file_path: format!("/repo/projects/{project}/{id}.jsonl"),
+        }
+    }
+
+    #[test]
+    fn test_build_timeline_interleaves_sessions() {
+        let api = session(
+            "s1",
+            "-home-me-api",
+            vec![
+                entry(
+                    r#"{"type":"user","uuid":"a","timestamp":"2025-01-01T10:00:00Z","message":{"role":"user","content":"fix the\n  login bug"}}"#,
+                ),
+                entry(
+                    r#"{"type":"assistant","uuid":"b","timestamp":"2025-01-01T12:00:00Z","message":{"content":[{"type":"tool_use","name":"Bash"}]}}"#,
+                ),
+                entry(
+                    r#"{"type":"assistant","uuid":"c","timestamp":"2025-01-01T12:01:00Z","message":{"content":[{"type":"text","text":"Fixed."}]}}"#,
+                ),
+                entry(
+                    r#"{"type":"user","uuid":"old","timestamp":"2024-01-01T00:00:00Z","message":{"content":"too old"}}"#,
+                ),
+            ],
+        );
+        let web = session(
+            "s2",
+            "-home-me-web",
+            vec![entry(
+                r#"{"type":"user","uuid":"d","timestamp":"2025-01-01T11:00:00Z","message":{"content":"restyle the header"}}"#,
+            )],
+        );
+
+        let since = DateTime::parse_from_rfc3339("2024-12-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let events = build_timeline(
+            &[api, web],
+            Path::new("/repo/projects"),
+            &FamilyIndex::default(),
+            since,
+        );
+        let summary: Vec<_> = events
+            .iter()
+            .map(|e| (e.project.as_str(), e.preview.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("-home-me-api", "fix the login bug"),
+                ("-home-me-web", "restyle the header"),
+                ("-home-me-api", "Fixed."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_timeline_groups_forks_into_threads() {
+        let first = r#"{"type":"user","uuid":"a","timestamp":"2025-01-01T10:00:00Z","message":{"content":"fix the login bug"}}"#;
+        let original = session("s1", "-home-me-api", vec![entry(first)]);
+        let fork = session(
+            "s2",
+            "-home-me-api",
+            vec![
+                entry(first),
+                entry(
+                    r#"{"type":"user","uuid":"b","timestamp":"2025-01-01T11:00:00Z","message":{"content":"try another way"}}"#,
+                ),
+            ],
+        );
+        let mut families = FamilyIndex::default();
+        families.link(
+            "s2",
+            crate::families::ForkLink {
+                parent: "s1".to_string(),
+                shared_entries: 1,
+            },
+        );
+
+        let events = build_timeline(
+            &[original, fork],
+            Path::new("/repo/projects"),
+            &families,
+            DateTime::<Utc>::MIN_UTC,
+        );
+        let summary: Vec<_> = events
+            .iter()
+            .map(|e| (e.session_id.as_str(), e.thread.as_str()))
+            .collect();
+        assert_eq!(summary, [("s1", "s1"), ("s2", "s1")]);
+    }
+}
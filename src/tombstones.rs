@@ -0,0 +1,237 @@
+//! Tombstones for deleted sessions
+//!
+//! Deleting a session on one machine used to be undone by the next pull,
+//! which copied it back from the sync repo. A tombstone records that a
+//! session was deleted, and when, in a sidecar index (`tombstones.json`) at
+//! the root of the sync repo next to `tags.json`, so every machine learns
+//! about it. Pull drops tombstoned sessions from the sync repo and moves
+//! local copies to the trash.
+//!
+//! A session with entries newer than its tombstone was continued somewhere
+//! after the deletion; pull keeps it and drops the tombstone instead.
+//!
+//! Deletions can also be detected: with `detect_deletions` enabled, pull
+//! remembers which session files were in `.claude` afterwards (in the state
+//! directory), and a file missing next time is tombstoned.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::config::ConfigManager;
+use crate::parser::ConversationSession;
+
+/// File name of the tombstone index within the sync repo
+pub const TOMBSTONE_INDEX_FILE: &str = "tombstones.json";
+
+/// Record of a deleted session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub deleted_at: DateTime<Utc>,
+    /// Machine the session was deleted on
+    pub machine_id: String,
+}
+
+/// Mapping of session ID to its tombstone
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TombstoneIndex {
+    #[serde(flatten)]
+    pub sessions: BTreeMap<String, Tombstone>,
+}
+
+impl TombstoneIndex {
+    /// Path of the tombstone index within a sync repo
+    pub fn path(sync_repo: &Path) -> PathBuf {
+        sync_repo.join(TOMBSTONE_INDEX_FILE)
+    }
+
+    /// Load the tombstone index from a sync repo (empty if it doesn't exist)
+    pub fn load(sync_repo: &Path) -> Result<Self> {
+        let path = Self::path(sync_repo);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read tombstone index: {}", path.display()))?;
+        serde_json::from_str(&content).context("Failed to parse tombstone index")
+    }
+
+    /// Save the tombstone index to a sync repo
+    pub fn save(&self, sync_repo: &Path) -> Result<()> {
+        let path = Self::path(sync_repo);
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize tombstone index")?;
+        fs::write(&path, content + "\n")
+            .with_context(|| format!("Failed to write tombstone index: {}", path.display()))
+    }
+
+    /// Record that a session was deleted now, returning false if it already
+    /// had a tombstone
+    pub fn add(&mut self, session_id: &str, machine_id: &str) -> bool {
+        if self.sessions.contains_key(session_id) {
+            return false;
+        }
+        self.sessions.insert(
+            session_id.to_string(),
+            Tombstone {
+                deleted_at: Utc::now(),
+                machine_id: machine_id.to_string(),
+            },
+        );
+        true
+    }
+
+    /// Remove a session's tombstone, returning false if it had none
+    pub fn remove(&mut self, session_id: &str) -> bool {
+        self.sessions.remove(session_id).is_some()
+    }
+
+    /// Whether a session has been deleted
+    pub fn is_deleted(&self, session_id: &str) -> bool {
+        self.sessions.contains_key(session_id)
+    }
+
+    /// Whether `session` has a tombstone but also entries written after it,
+    /// i.e. it was continued after being deleted
+    pub fn outlived_by(&self, session: &ConversationSession) -> bool {
+        let Some(tombstone) = self.sessions.get(&session.session_id) else {
+            return false;
+        };
+        session
+            .entries
+            .iter()
+            .filter_map(|e| e.timestamp.as_deref())
+            .filter_map(|t| DateTime::parse_from_rfc3339(t).ok())
+            .any(|t| t > tombstone.deleted_at)
+    }
+}
+
+/// IDs of the session files under a projects directory, by file name
+///
+/// Subagent transcripts (`agent-*.jsonl`) are skipped; they belong to their
+/// parent session.
+pub fn session_file_ids(projects_dir: &Path) -> BTreeSet<String> {
+    WalkDir::new(projects_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+        .filter_map(|e| {
+            e.path()
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+        })
+        .filter(|id| !id.starts_with("agent-"))
+        .collect()
+}
+
+/// Files of a session under a projects directory: its session file, any
+/// continuation files, and a directory named after the session (subagent
+/// transcripts)
+pub fn session_paths(projects_dir: &Path, session_id: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let mut walker = WalkDir::new(projects_dir).into_iter();
+    while let Some(Ok(entry)) = walker.next() {
+        let path = entry.path();
+        if entry.file_type().is_dir() {
+            if entry.depth() > 0 && entry.file_name() == session_id {
+                paths.push(path.to_path_buf());
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+        let base =
+            crate::parts::split_part(path).map_or_else(|| path.to_path_buf(), |(base, _)| base);
+        let is_session = base.extension().is_some_and(|ext| ext == "jsonl")
+            && base.file_stem().is_some_and(|stem| stem == session_id);
+        if is_session {
+            paths.push(path.to_path_buf());
+        }
+    }
+    paths
+}
+
+/// Path of the record of session files seen in `.claude` after the last pull
+fn seen_sessions_path() -> Result<PathBuf> {
+    Ok(ConfigManager::state_dir()?.join("seen-sessions.json"))
+}
+
+/// Session files seen in `.claude` after the last pull, if recorded
+pub fn load_seen_sessions() -> Result<Option<BTreeSet<String>>> {
+    let path = seen_sessions_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(Some(
+        serde_json::from_str(&content).context("Failed to parse seen sessions")?,
+    ))
+}
+
+/// Record the session files in `.claude` for the next pull's deletion check
+pub fn save_seen_sessions(ids: &BTreeSet<String>) -> Result<()> {
+    ConfigManager::ensure_state_dir()?;
+    let path = seen_sessions_path()?;
+    let content = serde_json::to_string(ids).context("Failed to serialize seen sessions")?;
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn session(id: &str, timestamps: &[&str]) -> ConversationSession {
+        ConversationSession {
+            session_id: id.to_string(),
+            entries: timestamps
+                .iter()
+                .map(|t| {
+                    serde_json::from_str(&format!(r#"{{"type":"user","timestamp":"{t}"}}"#))
+                        .unwrap()
+                })
+                .collect(),
+            file_path: format!("{id}.jsonl"),
+        }
+    }
+
+    #[test]
+    fn test_tombstone_outlived_by_newer_entries() {
+        let mut index = TombstoneIndex::default();
+        assert!(index.add("s1", "machine-a"));
+        assert!(!index.add("s1", "machine-b"));
+        assert!(index.is_deleted("s1"));
+
+        assert!(!index.outlived_by(&session("s1", &["2020-01-01T00:00:00.000Z"])));
+        assert!(index.outlived_by(&session(
+            "s1",
+            &["2020-01-01T00:00:00.000Z", "2999-01-01T00:00:00.000Z"]
+        )));
+        assert!(!index.outlived_by(&session("s2", &["2999-01-01T00:00:00.000Z"])));
+
+        assert!(index.remove("s1"));
+        assert!(!index.is_deleted("s1"));
+    }
+
+    #[test]
+    fn test_save_load_and_session_file_ids() {
+        let temp = TempDir::new().unwrap();
+        let mut index = TombstoneIndex::default();
+        index.add("s1", "m");
+        index.save(temp.path()).unwrap();
+        let loaded = TombstoneIndex::load(temp.path()).unwrap();
+        assert_eq!(loaded.sessions["s1"].machine_id, "m");
+
+        let project = temp.path().join("projects").join("-home-project");
+        fs::create_dir_all(&project).unwrap();
+        for name in ["s1.jsonl", "agent-1.jsonl", "notes.txt"] {
+            fs::write(project.join(name), "").unwrap();
+        }
+        let ids = session_file_ids(&temp.path().join("projects"));
+        assert_eq!(ids, BTreeSet::from(["s1".to_string()]));
+    }
+}
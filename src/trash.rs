@@ -0,0 +1,233 @@
+//! Trash area for files removed by cleanup operations
+//!
+//! Delete-like operations never unlink files directly. Instead the file or
+//! directory is moved into `.trash/<id>/` under the config directory, next to
+//! an `entry.toml` recording where it came from, so it can be restored with
+//! `claude-code-sync trash restore <id>`. Entries older than
+//! [`TRASH_RETENTION_DAYS`] are removed for good the next time anything is
+//! trashed, or immediately with `trash empty`.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::config::ConfigManager;
+
+/// Days a trashed item is kept before it is removed permanently
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Name of the moved file or directory inside an entry's directory
+const ITEM_NAME: &str = "item";
+
+/// Name of the metadata file inside an entry's directory
+const ENTRY_FILE: &str = "entry.toml";
+
+/// Metadata about one trashed file or directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// Identifier used by `trash restore`
+    pub id: String,
+    /// Where the item lived before it was trashed
+    pub original_path: PathBuf,
+    /// When it was trashed
+    pub trashed_at: DateTime<Utc>,
+    /// The operation that trashed it (e.g. "subscribe remove")
+    pub reason: String,
+}
+
+/// The trash directory
+pub struct Trash {
+    root: PathBuf,
+}
+
+impl Trash {
+    /// Open the trash under the config directory
+    pub fn open() -> Result<Self> {
+        Ok(Self::at(ConfigManager::trash_dir()?))
+    }
+
+    /// Open a trash rooted at `root`
+    pub fn at(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Move `path` into the trash, returning its entry
+    ///
+    /// Expired entries are purged first.
+    pub fn put(&self, path: &Path, reason: &str) -> Result<TrashEntry> {
+        if !path.exists() {
+            bail!("Cannot trash {}: it does not exist", path.display());
+        }
+        if let Err(e) = self.purge_expired() {
+            log::warn!("Failed to purge expired trash: {}", e);
+        }
+
+        let original_path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(path)
+        };
+        let trashed_at = Utc::now();
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let entry = TrashEntry {
+            id: format!("{}-{}", trashed_at.format("%Y%m%d-%H%M%S"), &suffix[..6]),
+            original_path,
+            trashed_at,
+            reason: reason.to_string(),
+        };
+
+        let entry_dir = self.root.join(&entry.id);
+        fs::create_dir_all(&entry_dir)
+            .with_context(|| format!("Failed to create directory: {}", entry_dir.display()))?;
+        move_path(path, &entry_dir.join(ITEM_NAME))?;
+        let content = toml::to_string_pretty(&entry).context("Failed to serialize trash entry")?;
+        fs::write(entry_dir.join(ENTRY_FILE), content)
+            .with_context(|| format!("Failed to write trash entry for {}", entry.id))?;
+
+        log::info!("Trashed {} as {}", path.display(), entry.id);
+        Ok(entry)
+    }
+
+    /// All trashed entries, oldest first
+    pub fn list(&self) -> Result<Vec<TrashEntry>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for dir in fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read {}", self.root.display()))?
+            .filter_map(|e| e.ok())
+        {
+            let entry_file = dir.path().join(ENTRY_FILE);
+            let Ok(content) = fs::read_to_string(&entry_file) else {
+                continue;
+            };
+            match toml::from_str::<TrashEntry>(&content) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => log::warn!("Skipping unreadable {}: {}", entry_file.display(), e),
+            }
+        }
+        entries.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(entries)
+    }
+
+    /// Move a trashed item back to its original location
+    ///
+    /// Fails rather than overwrite anything that now exists there.
+    pub fn restore(&self, id: &str) -> Result<TrashEntry> {
+        let entry = self
+            .list()?
+            .into_iter()
+            .find(|e| e.id == id)
+            .with_context(|| format!("No trash entry '{id}'"))?;
+        if entry.original_path.exists() {
+            bail!(
+                "Cannot restore {}: {} already exists",
+                id,
+                entry.original_path.display()
+            );
+        }
+        if let Some(parent) = entry.original_path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        let entry_dir = self.root.join(id);
+        move_path(&entry_dir.join(ITEM_NAME), &entry.original_path)?;
+        fs::remove_dir_all(&entry_dir)
+            .with_context(|| format!("Failed to remove {}", entry_dir.display()))?;
+        Ok(entry)
+    }
+
+    /// Permanently remove every entry, returning how many were removed
+    pub fn empty(&self) -> Result<usize> {
+        self.remove_where(|_| true)
+    }
+
+    /// Permanently remove entries older than the retention period
+    pub fn purge_expired(&self) -> Result<usize> {
+        let cutoff = Utc::now() - chrono::Duration::days(TRASH_RETENTION_DAYS);
+        self.remove_where(|entry| entry.trashed_at < cutoff)
+    }
+
+    fn remove_where(&self, predicate: impl Fn(&TrashEntry) -> bool) -> Result<usize> {
+        let mut removed = 0;
+        for entry in self.list()?.into_iter().filter(|e| predicate(e)) {
+            let entry_dir = self.root.join(&entry.id);
+            fs::remove_dir_all(&entry_dir)
+                .with_context(|| format!("Failed to remove {}", entry_dir.display()))?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+/// Rename `from` to `to`, copying when they are on different filesystems
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if from.is_dir() {
+        for entry in WalkDir::new(from).into_iter().filter_map(|e| e.ok()) {
+            let dest = to.join(entry.path().strip_prefix(from).unwrap_or(entry.path()));
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(&dest)
+                    .with_context(|| format!("Failed to create directory: {}", dest.display()))?;
+            } else {
+                fs::copy(entry.path(), &dest)
+                    .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+            }
+        }
+        fs::remove_dir_all(from).with_context(|| format!("Failed to remove {}", from.display()))
+    } else {
+        fs::copy(from, to).with_context(|| {
+            format!("Failed to move {} to {}", from.display(), to.display())
+        })?;
+        fs::remove_file(from).with_context(|| format!("Failed to remove {}", from.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_put_list_restore() {
+        let temp = TempDir::new().unwrap();
+        let trash = Trash::at(temp.path().join(".trash"));
+        let dir = temp.path().join("clone");
+        fs::create_dir_all(dir.join("projects")).unwrap();
+        fs::write(dir.join("projects/s.jsonl"), "{}\n").unwrap();
+
+        let entry = trash.put(&dir, "test").unwrap();
+        assert!(!dir.exists());
+        assert_eq!(trash.list().unwrap(), vec![entry.clone()]);
+
+        trash.restore(&entry.id).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("projects/s.jsonl")).unwrap(), "{}\n");
+        assert!(trash.list().unwrap().is_empty());
+        assert!(trash.restore(&entry.id).is_err());
+    }
+
+    #[test]
+    fn test_restore_refuses_to_overwrite_and_empty() {
+        let temp = TempDir::new().unwrap();
+        let trash = Trash::at(temp.path().join(".trash"));
+        let file = temp.path().join("s.jsonl");
+        fs::write(&file, "old\n").unwrap();
+
+        let entry = trash.put(&file, "test").unwrap();
+        fs::write(&file, "new\n").unwrap();
+        assert!(trash.restore(&entry.id).is_err());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "new\n");
+
+        assert_eq!(trash.purge_expired().unwrap(), 0);
+        assert_eq!(trash.empty().unwrap(), 1);
+        assert!(trash.list().unwrap().is_empty());
+    }
+}
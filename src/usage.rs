@@ -0,0 +1,347 @@
+//! Token usage and cost accounting
+//!
+//! Assistant entries carry the API `usage` block in `message`, so the synced
+//! corpus already holds a complete record of token consumption. This module
+//! aggregates it by project, model, session, or day and attaches a rough cost
+//! estimate based on public per-model list prices.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::filter::FilterConfig;
+use crate::glyphs;
+use crate::parser::{ConversationEntry, ConversationSession};
+use crate::paths::project_dir_name;
+use crate::table::{Column, Keep, OutputFormat, Table};
+
+/// How usage rows are grouped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageGrouping {
+    Project,
+    Model,
+    Session,
+    Day,
+}
+
+impl UsageGrouping {
+    /// Parse a grouping name ("project", "model", "session", or "day")
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "project" => Ok(UsageGrouping::Project),
+            "model" => Ok(UsageGrouping::Model),
+            "session" => Ok(UsageGrouping::Session),
+            "day" => Ok(UsageGrouping::Day),
+            other => bail!(
+                "Unknown grouping: '{}'. Use 'project', 'model', 'session', or 'day'.",
+                other
+            ),
+        }
+    }
+}
+
+/// Token counts and estimated cost for one group
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Estimated cost in USD
+    pub cost_usd: f64,
+    /// Number of API responses counted
+    pub responses: u64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, other: &UsageTotals) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cost_usd += other.cost_usd;
+        self.responses += other.responses;
+    }
+}
+
+/// Per-million-token list prices in USD: (input, output)
+///
+/// Cache writes are billed at 1.25x input and cache reads at 0.1x input.
+fn model_prices(model: &str) -> (f64, f64) {
+    let model = model.to_lowercase();
+    if model.contains("opus") {
+        (15.0, 75.0)
+    } else if model.contains("haiku") {
+        (0.8, 4.0)
+    } else {
+        // Sonnet and unknown models
+        (3.0, 15.0)
+    }
+}
+
+/// Extract usage from an assistant entry, if it has any
+fn entry_usage(entry: &ConversationEntry) -> Option<(String, UsageTotals)> {
+    if entry.entry_type != "assistant" {
+        return None;
+    }
+    let message = entry.message.as_ref()?;
+    let usage = message.get("usage")?;
+    let model = message
+        .get("model")
+        .and_then(|m| m.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let count = |field: &str| usage.get(field).and_then(|v| v.as_u64()).unwrap_or(0);
+    let mut totals = UsageTotals {
+        input_tokens: count("input_tokens"),
+        output_tokens: count("output_tokens"),
+        cache_creation_tokens: count("cache_creation_input_tokens"),
+        cache_read_tokens: count("cache_read_input_tokens"),
+        cost_usd: 0.0,
+        responses: 1,
+    };
+
+    let (input_price, output_price) = model_prices(&model);
+    totals.cost_usd = (totals.input_tokens as f64 * input_price
+        + totals.output_tokens as f64 * output_price
+        + totals.cache_creation_tokens as f64 * input_price * 1.25
+        + totals.cache_read_tokens as f64 * input_price * 0.1)
+        / 1_000_000.0;
+
+    Some((model, totals))
+}
+
+/// Parse a relative time window like "30d", "12h", or "2w" into a cutoff time
+pub fn parse_since(value: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    let value = value.trim();
+    let invalid = || format!("Invalid time window: '{}'. Use e.g. 30d, 12h, 2w", value);
+    // Split off the last character, not byte, so multi-byte input is an error
+    let Some((split, _)) = value.char_indices().last() else {
+        bail!(invalid());
+    };
+    let (number, unit) = value.split_at(split);
+    let amount: i64 = number.parse().with_context(invalid)?;
+    let duration = match unit {
+        "h" => chrono::Duration::hours(amount),
+        "d" => chrono::Duration::days(amount),
+        "w" => chrono::Duration::weeks(amount),
+        _ => bail!(invalid()),
+    };
+    Ok(chrono::Utc::now() - duration)
+}
+
+/// Aggregate usage over sessions
+///
+/// Claude Code writes one entry per content block of a response, each
+/// repeating the same usage block, so responses are deduplicated by
+/// `message.id` (falling back to `requestId`).
+pub fn aggregate_usage(
+    sessions: &[ConversationSession],
+    projects_dir: &Path,
+    grouping: UsageGrouping,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> HashMap<String, UsageTotals> {
+    let mut groups: HashMap<String, UsageTotals> = HashMap::new();
+    let mut seen_responses = HashSet::new();
+
+    for session in sessions {
+        let project = Path::new(&session.file_path)
+            .strip_prefix(projects_dir)
+            .ok()
+            .and_then(project_dir_name)
+            .unwrap_or_else(|| "unknown".to_string());
+
+        for entry in &session.entries {
+            let Some((model, totals)) = entry_usage(entry) else {
+                continue;
+            };
+
+            let timestamp = entry
+                .timestamp
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&chrono::Utc));
+            if let (Some(cutoff), Some(ts)) = (since, timestamp) {
+                if ts < cutoff {
+                    continue;
+                }
+            }
+
+            let response_id = entry
+                .message
+                .as_ref()
+                .and_then(|m| m.get("id"))
+                .and_then(|id| id.as_str())
+                .or_else(|| entry.extra.get("requestId").and_then(|id| id.as_str()));
+            if let Some(id) = response_id {
+                if !seen_responses.insert(id.to_string()) {
+                    continue;
+                }
+            }
+
+            let key = match grouping {
+                UsageGrouping::Project => project.clone(),
+                UsageGrouping::Model => model,
+                UsageGrouping::Session => session.session_id.clone(),
+                UsageGrouping::Day => timestamp
+                    .map(|t| t.format("%Y-%m-%d").to_string())
+                    .unwrap_or_else(|| "unknown".to_string()),
+            };
+            groups.entry(key).or_default().add(&totals);
+        }
+    }
+
+    groups
+}
+
+/// Show a usage report for the sessions in the sync repository
+pub fn show_usage(by: &str, since: Option<&str>, format: &str) -> Result<()> {
+    let grouping = UsageGrouping::parse(by)?;
+    let format = OutputFormat::parse(format)?;
+    let cutoff = since.map(parse_since).transpose()?;
+
+    let state = crate::sync::SyncState::load()?;
+    let filter = FilterConfig::load()?;
+    let projects_dir = state.sync_repo_path.join(filter.projects_subdir());
+    let sessions = crate::sync::discover_sessions(&projects_dir, &FilterConfig::default())?;
+
+    let groups = aggregate_usage(&sessions, &projects_dir, grouping, cutoff);
+    let table = usage_table(by, groups);
+
+    if format != OutputFormat::Table {
+        println!("{}", table.render(format)?);
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        format!(
+            "Token Usage by {}{}",
+            by.to_lowercase(),
+            since.map(|s| format!(" (last {s})")).unwrap_or_default()
+        )
+        .cyan()
+        .bold()
+    );
+
+    if table.is_empty() {
+        println!("{}", "No usage data found.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", table.to_text());
+    println!();
+    println!(
+        "  {} Costs are estimates from list prices and may not match your bill",
+        glyphs::info().cyan()
+    );
+
+    Ok(())
+}
+
+/// Usage groups as a table, most expensive first, with a totals footer
+fn usage_table(by: &str, groups: HashMap<String, UsageTotals>) -> Table {
+    let mut rows: Vec<_> = groups.into_iter().collect();
+    rows.sort_by(|a, b| b.1.cost_usd.total_cmp(&a.1.cost_usd));
+
+    let mut table = Table::new(vec![
+        Column::new(&capitalize(by), &by.to_lowercase()).max_width(40, Keep::End),
+        Column::new("Input", "input_tokens").right(),
+        Column::new("Output", "output_tokens").right(),
+        Column::new("Cache write", "cache_creation_tokens").right(),
+        Column::new("Cache read", "cache_read_tokens").right(),
+        Column::new("Est. cost ($)", "cost_usd").right(),
+    ]);
+    let cells = |label: &str, totals: &UsageTotals| {
+        vec![
+            label.to_string(),
+            totals.input_tokens.to_string(),
+            totals.output_tokens.to_string(),
+            totals.cache_creation_tokens.to_string(),
+            totals.cache_read_tokens.to_string(),
+            format!("{:.2}", totals.cost_usd),
+        ]
+    };
+
+    let mut total = UsageTotals::default();
+    for (key, totals) in &rows {
+        total.add(totals);
+        table.push_row(cells(key, totals));
+    }
+    if !rows.is_empty() {
+        table.push_footer(cells("Total", &total));
+    }
+    table
+}
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assistant(id: &str, model: &str, ts: &str) -> ConversationEntry {
+        serde_json::from_str(&format!(
+            r#"{{"type":"assistant","uuid":"{id}-u","timestamp":"{ts}","message":{{"id":"{id}","model":"{model}","usage":{{"input_tokens":1000000,"output_tokens":1000000,"cache_read_input_tokens":0}}}}}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_aggregate_dedups_and_groups() {
+        let projects = Path::new("/repo/projects");
+        let session = ConversationSession {
+            session_id: "s1".to_string(),
+            entries: vec![
+                assistant("m1", "claude-sonnet-4", "2025-01-01T00:00:00Z"),
+                // Second content block of the same response
+                assistant("m1", "claude-sonnet-4", "2025-01-01T00:00:01Z"),
+                assistant("m2", "claude-opus-4", "2025-01-02T00:00:00Z"),
+            ],
+            file_path: "/repo/projects/-home-me-app/s1.jsonl".to_string(),
+        };
+
+        let by_project =
+            aggregate_usage(std::slice::from_ref(&session), projects, UsageGrouping::Project, None);
+        let app = &by_project["-home-me-app"];
+        assert_eq!(app.responses, 2);
+        assert_eq!(app.input_tokens, 2_000_000);
+        assert!((app.cost_usd - (3.0 + 15.0 + 15.0 + 75.0)).abs() < 1e-9);
+
+        let by_model = aggregate_usage(&[session], projects, UsageGrouping::Model, None);
+        assert_eq!(by_model.len(), 2);
+    }
+
+    #[test]
+    fn test_since_cutoff() {
+        let session = ConversationSession {
+            session_id: "s1".to_string(),
+            entries: vec![assistant("old", "claude-sonnet-4", "2000-01-01T00:00:00Z")],
+            file_path: "s1.jsonl".to_string(),
+        };
+        let cutoff = parse_since("30d").unwrap();
+        let groups = aggregate_usage(&[session], Path::new("/"), UsageGrouping::Day, Some(cutoff));
+        assert!(groups.is_empty());
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert!(parse_since("12h").is_ok());
+        assert!(parse_since("2w").is_ok());
+        assert!(parse_since("30").is_err());
+        assert!(parse_since("xd").is_err());
+        assert!(parse_since("").is_err());
+        for input in ["3é", "é", "2週"] {
+            let err = parse_since(input).unwrap_err().to_string();
+            assert!(err.starts_with("Invalid time window"), "{err}");
+        }
+    }
+}
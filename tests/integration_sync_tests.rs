@@ -56,6 +56,7 @@ fn create_test_sync_state(sync_repo_path: &Path, state_dir: &Path) -> anyhow::Re
         sync_repo_path: sync_repo_path.to_path_buf(),
         has_remote: false,
         is_cloned_repo: false,
+        rsync_remote: None,
     };
 
     let state_file = state_dir.join("state.json");
@@ -633,6 +634,7 @@ fn test_append_only_preserves_concurrent_writes() {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         }
     };
 
@@ -740,6 +742,7 @@ fn test_append_only_non_uuid_entries() {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         }
     };
 
@@ -825,6 +828,7 @@ fn test_append_only_idempotent() {
             version: None,
             git_branch: None,
             extra: serde_json::Value::Null,
+            raw_line: None,
         }
     };
 
@@ -898,3 +902,1477 @@ fn test_append_only_idempotent() {
         );
     }
 }
+
+/// A compacted sync repo copy is not an edit: a machine that doesn't compact
+/// keeps its original, and doesn't put the original back in the sync repo
+mod compacted_copies {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A session with a large tool result and a large file snapshot (which has
+    /// no UUID), synced by a machine that compacts both
+    fn synced_by_compacting_machine() -> (TestEnv, PathBuf, String) {
+        let env = TestEnv::new()
+            .with_sessions(1)
+            .with_remote()
+            .with_filter(|f| {
+                f.compact_older_than_days = Some(1);
+                f.compact_min_payload_bytes = 100;
+            });
+        let local_path = env.session_path(&env.session_ids()[0]);
+        let mut lines: Vec<serde_json::Value> = fs::read_to_string(&local_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        lines[1]["toolUseResult"] = "x".repeat(500).into();
+        lines.push(serde_json::json!({
+            "type": "file-history-snapshot",
+            "messageId": lines[0]["uuid"],
+            "snapshot": { "trackedFileBackups": { "notes.md": "y".repeat(500) } },
+            "isSnapshotUpdate": false,
+        }));
+        let original: String = lines.iter().map(|line| format!("{line}\n")).collect();
+        fs::write(&local_path, &original).unwrap();
+
+        env.run_ok(&["sync"]);
+        assert!(repo_copy(&env).contains("[compacted by claude-code-sync"));
+        (env, local_path, original)
+    }
+
+    fn repo_copy(env: &TestEnv) -> String {
+        let name = format!("{}.jsonl", env.session_ids()[0]);
+        let path = walkdir::WalkDir::new(env.repo_dir().join("projects"))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .find(|e| e.file_name().to_string_lossy() == name)
+            .expect("session saved to the sync repo")
+            .into_path();
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_compacted_copy_never_replaces_local_original() {
+        let (env, local_path, original) = synced_by_compacting_machine();
+
+        // A machine that doesn't compact and prefers the remote side
+        let env = env.with_filter(|f| {
+            f.compact_older_than_days = None;
+            f.modified_entry_policy = "prefer-remote".to_string();
+            f.pull_precedence = "remote-first".to_string();
+        });
+        env.run_ok(&["sync"]);
+
+        assert_eq!(fs::read_to_string(&local_path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_non_compacting_machine_keeps_repo_copy_compacted() {
+        let (env, local_path, original) = synced_by_compacting_machine();
+
+        let env = env.with_filter(|f| f.compact_older_than_days = None);
+        let output = env.run_ok(&["sync", "--verbose"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!stdout.contains("modified in place"), "{stdout}");
+
+        assert!(repo_copy(&env).contains("[compacted by claude-code-sync"));
+        assert_eq!(fs::read_to_string(&local_path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_pull_after_compaction_leaves_local_file_alone() {
+        let (env, local_path, original) = synced_by_compacting_machine();
+
+        env.run_ok(&["pull"]);
+        env.run_ok(&["sync"]);
+
+        assert_eq!(fs::read_to_string(&local_path).unwrap(), original);
+    }
+}
+
+/// PII scrubbing rewrites the sync repo copy of a session, never the local one
+mod scrubbing {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    #[test]
+    fn test_scrubs_sync_repo_copy_only() {
+        let env = TestEnv::new().with_sessions(1).with_filter(|f| {
+            f.scrub_rules = vec!["email".to_string(), "ip".to_string()];
+            f.scrub_allowlist = vec!["^support@".to_string()];
+        });
+        let session_id = env.session_ids()[0].clone();
+        let local_path = env.session_path(&session_id);
+        let original = fs::read_to_string(&local_path).unwrap().replace(
+            "Question 1 in session 1",
+            "Mail jane@corp.example.com or support@corp.example.com from 10.1.2.3",
+        );
+        fs::write(&local_path, &original).unwrap();
+
+        let output = env.run_ok(&["sync", "--verbose"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("Scrubbed 1 sessions (1 email, 1 ip)"),
+            "{stdout}"
+        );
+        assert!(
+            stdout.contains(&format!("{session_id}: 1 email, 1 ip")),
+            "{stdout}"
+        );
+
+        let repo_copy = walkdir::WalkDir::new(env.repo_dir())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .find(|e| e.file_name().to_string_lossy() == format!("{session_id}.jsonl"))
+            .expect("session saved to the sync repo");
+        let synced = fs::read_to_string(repo_copy.path()).unwrap();
+        assert!(synced.contains("Mail [email] or support@corp.example.com from [ip]"));
+        assert!(!synced.contains("jane@"));
+
+        assert_eq!(fs::read_to_string(&local_path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_pull_after_scrubbing_leaves_local_file_alone() {
+        let env = TestEnv::new()
+            .with_sessions(1)
+            .with_remote()
+            .with_filter(|f| f.scrub_rules = vec!["email".to_string()]);
+        let local_path = env.session_path(&env.session_ids()[0]);
+        // A summary has no UUID, so pulls match it by content
+        let original = format!(
+            "{}\n{}",
+            r#"{"type":"summary","summary":"Ask jane@corp.example.com about the release","leafUuid":"x"}"#,
+            fs::read_to_string(&local_path).unwrap()
+        );
+        fs::write(&local_path, &original).unwrap();
+
+        env.run_ok(&["sync"]);
+        env.run_ok(&["pull"]);
+        env.run_ok(&["sync"]);
+
+        assert_eq!(fs::read_to_string(&local_path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_unknown_scrub_rule_is_rejected() {
+        let env = TestEnv::new();
+
+        let output = env.run(&["config", "--scrub-rules", "email,passport"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("Unknown scrubbing rule: 'passport'"),
+            "{stderr}"
+        );
+    }
+}
+
+/// Local-only sessions are never copied into the sync repo
+mod local_only {
+    use claude_code_sync::test_support::TestEnv;
+
+    fn synced_ids(env: &TestEnv) -> Vec<String> {
+        let mut ids: Vec<String> = walkdir::WalkDir::new(env.repo_dir())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+            .filter_map(|e| {
+                e.path()
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+            })
+            .filter(|id| id != "history")
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_local_only_session_stays_out_of_sync_repo() {
+        let env = TestEnv::new()
+            .with_sessions(2)
+            .with_filter(|f| f.include_patterns = vec!["*".to_string()]);
+        let private = env.session_ids()[0].clone();
+        let shared = env.session_ids()[1].clone();
+
+        env.run_ok(&["mark", "local-only", &private]);
+        let output = env.run_ok(&["mark", "local-only"]);
+        assert!(String::from_utf8_lossy(&output.stdout).contains(&private));
+
+        let output = env.run_ok(&["sync"]);
+        assert_eq!(synced_ids(&env), vec![shared.clone()]);
+        // Left out on purpose, so not reported as missing from the sync repo
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!stdout.contains("only one side"), "{stdout}");
+        assert!(env.session_path(&private).exists());
+
+        // Undoing the mark syncs the session again
+        env.run_ok(&["mark", "local-only", "--undo", &private]);
+        env.run_ok(&["sync"]);
+        let mut expected = vec![private, shared];
+        expected.sort();
+        assert_eq!(synced_ids(&env), expected);
+    }
+
+    #[test]
+    fn test_local_only_project_stays_out_of_sync_repo() {
+        let env = TestEnv::new().with_sessions(2);
+        let project = env
+            .session_path(&env.session_ids()[0])
+            .parent()
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+
+        env.run_ok(&["mark", "local-only", &project]);
+        env.run_ok(&["sync"]);
+        assert!(synced_ids(&env).is_empty());
+    }
+}
+
+/// A machine filter committed to the sync repo narrows what one machine pulls
+mod machine_filter {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Commit a machine filter to the sync repo, as if managed centrally
+    fn commit_filter(repo: &Path, path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+        for args in [&["add", "-A"][..], &["commit", "-qm", "Set machine filter"]] {
+            let status = Command::new("git")
+                .current_dir(repo)
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+    }
+
+    fn machine_id(env: &TestEnv) -> String {
+        let output = env.run_ok(&["status"]);
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Machine ID: "))
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_machine_filter_limits_pulled_sessions() {
+        let env = TestEnv::new().with_sessions(2);
+        env.run_ok(&["sync", "--quiet"]);
+        let id = machine_id(&env);
+
+        // Sessions in the test environment are from January 2025
+        let filter_path = env.repo_dir().join("machines").join(format!("{id}.toml"));
+        commit_filter(env.repo_dir(), &filter_path, "newer_than_days = 30\n");
+        for session_id in env.session_ids() {
+            fs::remove_file(env.session_path(session_id)).unwrap();
+        }
+
+        let output = env.run_ok(&["pull"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Skipped 2 sessions"), "{stdout}");
+        for session_id in env.session_ids() {
+            assert!(!env.session_path(session_id).exists());
+        }
+        assert!(String::from_utf8_lossy(&env.run_ok(&["status"]).stdout)
+            .contains(&format!("pulls filtered by machines/{id}.toml")));
+
+        // The filter never limits what is pushed, and a wider one pulls again
+        commit_filter(env.repo_dir(), &filter_path, "include_projects = [\"*\"]\n");
+        env.run_ok(&["pull"]);
+        for session_id in env.session_ids() {
+            assert!(env.session_path(session_id).exists());
+        }
+    }
+}
+
+/// Deleted sessions stay deleted: tombstones pushed by another machine, and
+/// deletions detected locally, remove sessions instead of being undone
+mod tombstones {
+    use claude_code_sync::scm;
+    use claude_code_sync::test_support::TestEnv;
+    use claude_code_sync::tombstones::TombstoneIndex;
+
+    /// Session files on the remote's current branch
+    fn remote_sessions(env: &TestEnv) -> Vec<String> {
+        let clone = tempfile::TempDir::new().unwrap();
+        let repo = scm::clone(&env.remote_dir().unwrap().to_string_lossy(), clone.path()).unwrap();
+        let branch = repo.current_branch().unwrap();
+        repo.list_files_at(&branch, ".")
+            .unwrap()
+            .into_iter()
+            .filter(|f| f.ends_with(".jsonl") && !f.ends_with("history.jsonl"))
+            .collect()
+    }
+
+    #[test]
+    fn test_tombstone_from_another_machine_removes_session() {
+        let env = TestEnv::new().with_sessions(2).with_remote();
+        env.run_ok(&["sync", "--quiet"]);
+        let deleted = env.session_ids()[0].clone();
+
+        // Another machine deletes the session
+        let other = env.root().join("other");
+        let repo = scm::clone(&env.remote_dir().unwrap().to_string_lossy(), &other).unwrap();
+        for (key, value) in [("user.name", "Other"), ("user.email", "other@local")] {
+            std::process::Command::new("git")
+                .args(["config", key, value])
+                .current_dir(&other)
+                .output()
+                .unwrap();
+        }
+        let mut index = TombstoneIndex::load(&other).unwrap();
+        index.add(&deleted, "other-machine");
+        index.save(&other).unwrap();
+        repo.stage_all().unwrap();
+        repo.commit("Delete session").unwrap();
+        repo.push("origin", &repo.current_branch().unwrap())
+            .unwrap();
+
+        env.run_ok(&["sync", "--quiet"]);
+
+        assert!(!env.session_path(&deleted).exists());
+        assert!(env.session_path(&env.session_ids()[1]).exists());
+        let remote = remote_sessions(&env);
+        assert_eq!(remote.len(), 1, "{remote:?}");
+        assert!(!remote.iter().any(|f| f.contains(&deleted)));
+    }
+
+    #[test]
+    fn test_detected_deletion_propagates() {
+        let env = TestEnv::new()
+            .with_sessions(2)
+            .with_remote()
+            .with_filter(|f| f.detect_deletions = true);
+        env.run_ok(&["sync", "--quiet"]);
+        let deleted = env.session_ids()[1].clone();
+
+        std::fs::remove_file(env.session_path(&deleted)).unwrap();
+        env.run_ok(&["sync", "--quiet"]);
+
+        let remote = remote_sessions(&env);
+        assert_eq!(remote.len(), 1, "{remote:?}");
+        assert!(TombstoneIndex::load(env.repo_dir())
+            .unwrap()
+            .is_deleted(&deleted));
+        // And it isn't brought back by later syncs
+        env.run_ok(&["sync", "--quiet"]);
+        assert!(!env.session_path(&deleted).exists());
+    }
+
+    #[test]
+    fn test_deletion_not_detected_by_default() {
+        let env = TestEnv::new().with_sessions(1).with_remote();
+        env.run_ok(&["sync", "--quiet"]);
+        let session = env.session_ids()[0].clone();
+
+        std::fs::remove_file(env.session_path(&session)).unwrap();
+        env.run_ok(&["sync", "--quiet"]);
+
+        assert!(env.session_path(&session).exists());
+    }
+}
+
+/// `delete` removes a session locally, from the sync repo and its remote,
+/// and from history.jsonl, and leaves a tombstone for other machines
+mod delete {
+    use claude_code_sync::scm;
+    use claude_code_sync::test_support::TestEnv;
+    use claude_code_sync::tombstones::TombstoneIndex;
+    use std::fs;
+
+    #[test]
+    fn test_delete_removes_session_everywhere() {
+        let env = TestEnv::new().with_sessions(2).with_remote();
+        let deleted = env.session_ids()[0].clone();
+        let kept = env.session_ids()[1].clone();
+        fs::write(
+            env.claude_dir().join("history.jsonl"),
+            format!(
+                "{{\"sessionId\":\"{deleted}\",\"timestamp\":1000,\"display\":\"secret\"}}\n\
+             {{\"sessionId\":\"{kept}\",\"timestamp\":2000,\"display\":\"fine\"}}\n"
+            ),
+        )
+        .unwrap();
+        env.run_ok(&["sync", "--quiet"]);
+
+        env.run_ok(&["delete", &deleted, "--yes"]);
+
+        assert!(!env.session_path(&deleted).exists());
+        assert!(env.session_path(&kept).exists());
+        let local_history = fs::read_to_string(env.claude_dir().join("history.jsonl")).unwrap();
+        assert!(!local_history.contains(&deleted));
+        assert!(local_history.contains(&kept));
+
+        let clone = tempfile::TempDir::new().unwrap();
+        let repo = scm::clone(&env.remote_dir().unwrap().to_string_lossy(), clone.path()).unwrap();
+        let files = repo
+            .list_files_at(&repo.current_branch().unwrap(), ".")
+            .unwrap();
+        assert!(!files.iter().any(|f| f.contains(&deleted)), "{files:?}");
+        assert!(files.iter().any(|f| f.contains(&kept)), "{files:?}");
+        assert!(TombstoneIndex::load(clone.path())
+            .unwrap()
+            .is_deleted(&deleted));
+        let remote_history = fs::read_to_string(clone.path().join("history.jsonl")).unwrap();
+        assert!(!remote_history.contains(&deleted));
+        assert!(remote_history.contains(&kept));
+
+        // The session stays deleted through the next sync
+        env.run_ok(&["sync", "--quiet"]);
+        assert!(!env.session_path(&deleted).exists());
+    }
+
+    #[test]
+    fn test_delete_needs_the_session_id() {
+        let env = TestEnv::new().with_sessions(1).with_remote();
+        let session = env.session_ids()[0].clone();
+        env.run_ok(&["sync", "--quiet"]);
+
+        // A reference only a fuzzy match would resolve deletes nothing
+        let fuzzy: String = session.chars().filter(|c| *c != '-').skip(2).collect();
+        let output = env.run(&["delete", &fuzzy, "--yes"]);
+        assert!(!output.status.success());
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("No session ID starts with"),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(env.session_path(&session).exists());
+    }
+}
+
+/// Syncs can commit each touched project directory separately
+mod commit_per_project {
+    use claude_code_sync::test_support::TestEnv;
+    use std::process::Command;
+
+    #[test]
+    fn test_sync_commits_each_project_separately() {
+        let env = TestEnv::new().with_sessions(2);
+        env.run_ok(&["config", "--commit-per-project", "true"]);
+        env.run_ok(&["sync", "--quiet"]);
+
+        let output = Command::new("git")
+            .current_dir(env.repo_dir())
+            .args(["log", "--format=%s"])
+            .output()
+            .unwrap();
+        let subjects = String::from_utf8_lossy(&output.stdout);
+        // Both sessions live in the one test project
+        assert!(
+            subjects
+                .lines()
+                .any(|subject| subject.ends_with("-project: 2 new sessions (+8 entries)")),
+            "{subjects}"
+        );
+    }
+}
+
+/// Push can write an INDEX.md summarizing each project's sessions
+mod project_index {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    #[test]
+    fn test_push_writes_project_index() {
+        let env = TestEnv::new().with_sessions(2);
+        env.run_ok(&["config", "--project-index", "true"]);
+        env.run_ok(&["sync", "--quiet"]);
+
+        let ids = env.session_ids().to_vec();
+        let project = env.session_path(&ids[0]);
+        let project = project.parent().unwrap().file_name().unwrap();
+        let index_path = env
+            .repo_dir()
+            .join("projects")
+            .join(project)
+            .join("INDEX.md");
+        let index = fs::read_to_string(&index_path).unwrap();
+        let rows: Vec<&str> = index.lines().filter(|l| l.starts_with("| 2025")).collect();
+        assert_eq!(
+            rows,
+            [
+                format!(
+                    "| 2025-01-03 10:00 | [00000000]({}.jsonl) | 4 | Question 1 in session 2 |",
+                    ids[1]
+                ),
+                format!(
+                    "| 2025-01-02 10:00 | [00000000]({}.jsonl) | 4 | Question 1 in session 1 |",
+                    ids[0]
+                ),
+            ]
+        );
+
+        // Committed, and left alone by a push with nothing new
+        let output = std::process::Command::new("git")
+            .current_dir(env.repo_dir())
+            .args(["status", "--porcelain"])
+            .output()
+            .unwrap();
+        assert!(output.stdout.is_empty());
+        env.run_ok(&["push"]);
+        assert_eq!(fs::read_to_string(&index_path).unwrap(), index);
+    }
+}
+
+/// With `checksum_manifest` enabled, pushes write a manifest of file hashes
+/// and pulls refuse to merge fetched files that don't match it
+mod manifest {
+    use claude_code_sync::test_support::{git, TestEnv};
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+    use walkdir::WalkDir;
+
+    /// Path of a session's file under `root`
+    fn find_session(root: &Path, session_id: &str) -> std::path::PathBuf {
+        let file_name = format!("{session_id}.jsonl");
+        WalkDir::new(root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .find(|e| e.file_name().to_string_lossy() == file_name)
+            .unwrap()
+            .into_path()
+    }
+
+    #[test]
+    fn test_pull_rejects_files_not_matching_manifest() {
+        let env = TestEnv::new()
+            .with_sessions(1)
+            .with_remote()
+            .with_filter(|f| f.checksum_manifest = true);
+        let session_id = env.session_ids()[0].clone();
+
+        let output = env.run(&["sync", "--quiet"]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let manifest = fs::read_to_string(env.repo_dir().join("manifest.json")).unwrap();
+        assert!(manifest.contains(&format!("{session_id}.jsonl")));
+
+        let verify = env
+            .command(env!("CARGO_BIN_EXE_verify-sync"))
+            .args(["--manifest", "--repo"])
+            .arg(env.repo_dir())
+            .output()
+            .unwrap();
+        assert!(
+            verify.status.success(),
+            "{}",
+            String::from_utf8_lossy(&verify.stdout)
+        );
+
+        // Edit a session on the remote without updating the manifest
+        let clone = env.root().join("clone");
+        git(
+            env.root(),
+            &[
+                "clone",
+                &env.remote_dir().unwrap().to_string_lossy(),
+                "clone",
+            ],
+        );
+        let remote_session = find_session(&clone, &session_id);
+        let mut content = fs::read_to_string(&remote_session).unwrap();
+        content = content.replacen("\"type\"", "\"tampered\":true,\"type\"", 1);
+        fs::write(&remote_session, content).unwrap();
+        git(
+            &clone,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-qam",
+                "Edit",
+            ],
+        );
+        git(&clone, &["push", "-q", "origin", "HEAD"]);
+
+        let branch = String::from_utf8(
+            Command::new("git")
+                .current_dir(env.repo_dir())
+                .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                .output()
+                .unwrap()
+                .stdout,
+        )
+        .unwrap();
+        let branch = branch.trim();
+
+        let local_before = fs::read_to_string(env.session_path(&session_id)).unwrap();
+        let output = env.run(&["pull"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("checksum manifest"), "{stderr}");
+        assert_eq!(
+            fs::read_to_string(env.session_path(&session_id)).unwrap(),
+            local_before
+        );
+
+        let verify = env
+            .command(env!("CARGO_BIN_EXE_verify-sync"))
+            .args([
+                "--manifest",
+                "--against",
+                &format!("origin/{branch}"),
+                "--repo",
+            ])
+            .arg(env.repo_dir())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&verify.stdout);
+        assert!(!verify.status.success(), "{stdout}");
+        assert!(stdout.contains("mismatched: "), "{stdout}");
+    }
+}
+
+/// Pushes are mirrored to every mirror remote, and one that can't be
+/// reached doesn't stop the others
+mod mirrors {
+    use claude_code_sync::test_support::{git, TestEnv};
+
+    #[test]
+    fn test_unreachable_mirror_is_reported_and_others_still_pushed() {
+        let env = TestEnv::new()
+            .with_sessions(1)
+            .with_remote()
+            .with_filter(|f| f.mirror_remotes = vec!["bad".to_string(), "good".to_string()]);
+        let good = env.root().join("good.git");
+        git(
+            env.root(),
+            &["init", "-q", "--bare", &good.to_string_lossy()],
+        );
+        env.git(&["remote", "add", "good", &good.to_string_lossy()]);
+        let missing = env.root().join("missing.git");
+        env.git(&["remote", "add", "bad", &missing.to_string_lossy()]);
+
+        let output = env.run_ok(&["push"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let branch = env.git(&["rev-parse", "--abbrev-ref", "HEAD"]);
+        assert!(stdout.contains("Could not push to mirror bad"), "{stdout}");
+        assert!(
+            stdout.contains(&format!("Mirrored to good/{branch}")),
+            "{stdout}"
+        );
+
+        // The good mirror has the pushed commit despite the bad one before it
+        assert_eq!(
+            git(&good, &["rev-parse", &branch]),
+            env.git(&["rev-parse", "HEAD"])
+        );
+
+        let output = env.run_ok(&["history", "last", "--format", "json"]);
+        let record: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let failures = record["network_failures"].as_array().unwrap();
+        assert_eq!(failures.len(), 1, "{failures:?}");
+        assert!(
+            failures[0]
+                .as_str()
+                .unwrap()
+                .starts_with("push to mirror bad:"),
+            "{failures:?}"
+        );
+    }
+}
+
+/// A push rejected because another machine pushed first merges the remote's
+/// new commits and retries
+mod push_retry {
+    use claude_code_sync::test_support::{git, TestEnv};
+    use std::fs;
+
+    /// Push a new session to the remote from a separate clone, as another
+    /// machine would
+    fn push_from_other_machine(env: &TestEnv, session_id: &str) {
+        let clone = env.root().join(format!("clone-{session_id}"));
+        git(
+            env.root(),
+            &[
+                "clone",
+                "-q",
+                &env.remote_dir().unwrap().to_string_lossy(),
+                &clone.to_string_lossy(),
+            ],
+        );
+        let project = fs::read_dir(clone.join("projects"))
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        fs::write(
+        project.join(format!("{session_id}.jsonl")),
+        format!(
+            r#"{{"type":"user","uuid":"{session_id}-1","sessionId":"{session_id}","timestamp":"2025-01-01T00:00:00Z"}}"#
+        ) + "\n",
+    )
+    .unwrap();
+        git(&clone, &["add", "-A"]);
+        git(
+            &clone,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-qm",
+                "Other machine",
+            ],
+        );
+        git(&clone, &["push", "-q", "origin", "HEAD"]);
+    }
+
+    #[test]
+    fn test_rejected_push_merges_and_retries() {
+        let env = TestEnv::new().with_sessions(1).with_remote();
+        let output = env.run(&["sync", "--quiet"]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        push_from_other_machine(&env, "other-session");
+        let output = env.run(&["push"]);
+        assert!(
+            output.status.success(),
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("retrying"), "{stdout}");
+        assert!(env.session_path("other-session").exists());
+    }
+
+    #[test]
+    fn test_rejected_push_fails_without_retries() {
+        let env = TestEnv::new()
+            .with_sessions(1)
+            .with_remote()
+            .with_filter(|f| f.push_retries = 0);
+        let output = env.run(&["sync", "--quiet"]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        push_from_other_machine(&env, "other-session");
+        let output = env.run(&["push"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Push rejected"));
+        assert!(!env.session_path("other-session").exists());
+    }
+}
+
+/// A pull that can't reach the remote says so and exits with its own code
+mod remote_unreachable {
+    use claude_code_sync::sync::SyncOutcome;
+    use claude_code_sync::test_support::TestEnv;
+
+    #[test]
+    fn test_pull_with_unreachable_remote_is_local_only() {
+        let env = TestEnv::new().with_sessions(1).with_remote();
+        std::fs::remove_dir_all(env.remote_dir().unwrap()).unwrap();
+
+        for command in ["pull", "sync"] {
+            let mut args = vec![command];
+            if command == "sync" {
+                args.push("--no-push");
+            }
+            let output = env
+                .command(env!("CARGO_BIN_EXE_claude-code-sync"))
+                .args(&args)
+                .output()
+                .unwrap();
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert_eq!(
+                output.status.code(),
+                Some(SyncOutcome::LOCAL_ONLY_EXIT_CODE),
+                "{stdout}"
+            );
+            assert!(stdout.contains("LOCAL ONLY"), "{stdout}");
+            assert!(!stdout.contains("Pull complete!"), "{stdout}");
+        }
+
+        let output = env.run(&["history", "last", "--format", "json"]);
+        let record: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(record["remote_unreachable"], true);
+
+        // Local sessions still reached the sync repo
+        let synced = walkdir::WalkDir::new(env.repo_dir())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .any(|e| e.file_name().to_string_lossy() == format!("{}.jsonl", env.session_ids()[0]));
+        assert!(synced);
+    }
+
+    #[test]
+    fn test_quiet_pull_summary_marks_local_only() {
+        let env = TestEnv::new().with_sessions(1).with_remote();
+        std::fs::remove_dir_all(env.remote_dir().unwrap()).unwrap();
+
+        let output = env.run(&["pull", "-q"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout
+                .trim_end()
+                .ends_with(", local only (remote unreachable)"),
+            "{stdout}"
+        );
+    }
+}
+
+/// Pulls, pushes, and syncs ping the configured healthcheck URL
+mod healthcheck {
+    use claude_code_sync::test_support::TestEnv;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// A server answering every request with 200, sending each request line
+    /// and body down the returned channel
+    fn healthcheck_server() -> (String, mpsc::Receiver<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/ping/abc", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK",
+                    )
+                    .unwrap();
+                let _ = sender.send((
+                    request_line.trim().to_string(),
+                    String::from_utf8(body).unwrap(),
+                ));
+            }
+        });
+        (url, receiver)
+    }
+
+    #[test]
+    fn test_healthcheck_pinged_on_success_and_failure() {
+        let env = TestEnv::new().with_sessions(1);
+        let (url, pings) = healthcheck_server();
+        let output = env.run(&["config", "--healthcheck-url", &url]);
+        assert!(output.status.success());
+
+        let output = env.run(&["sync", "--quiet"]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let (request, _) = pings
+            .recv_timeout(Duration::from_secs(10))
+            .expect("no ping after sync");
+        assert_eq!(request, "GET /ping/abc HTTP/1.1");
+
+        let output = env.run(&["push", "--quiet", "--fail-at", "push commit"]);
+        assert!(!output.status.success());
+        let (request, body) = pings
+            .recv_timeout(Duration::from_secs(10))
+            .expect("no ping after failed push");
+        assert_eq!(request, "POST /ping/abc/fail HTTP/1.1");
+        assert!(body.contains("push commit"), "{body}");
+
+        // Without a URL, nothing is pinged
+        let output = env.run(&["config", "--healthcheck-url", ""]);
+        assert!(output.status.success());
+        assert!(env.run(&["sync", "--quiet"]).status.success());
+        assert!(pings.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_healthcheck_url_must_be_http() {
+        let env = TestEnv::new();
+        let output = env.run(&["config", "--healthcheck-url", "hc-ping.com/abc"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid healthcheck URL"));
+    }
+}
+
+/// Crash recovery: syncs aborted at a phase with `--fail-at` must release the
+/// lock, keep their temp branch, and be recovered by the next sync
+mod fail_points {
+    use claude_code_sync::scm;
+    use claude_code_sync::test_support::TestEnv;
+
+    fn remote_sessions(env: &TestEnv) -> Vec<String> {
+        let clone = tempfile::TempDir::new().unwrap();
+        let repo = scm::clone(&env.remote_dir().unwrap().to_string_lossy(), clone.path()).unwrap();
+        let branch = repo.current_branch().unwrap();
+        repo.list_files_at(&branch, ".")
+            .unwrap()
+            .into_iter()
+            .filter(|f| f.ends_with(".jsonl") && !f.ends_with("history.jsonl"))
+            .collect()
+    }
+
+    #[test]
+    fn test_pull_aborted_after_saving_local_state_recovers() {
+        let env = TestEnv::new().with_sessions(2).with_remote();
+
+        let output = env.run(&["sync", "--quiet", "--fail-at", "save local"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Simulated failure"));
+
+        // The temp branch holding the saved local state is kept
+        let repo = scm::open(env.repo_dir()).unwrap();
+        let branches = repo.list_branches().unwrap();
+        assert!(
+            branches.iter().any(|b| b.starts_with("sync-local-")),
+            "{branches:?}"
+        );
+
+        // The lock was released and the next sync finishes the job
+        let output = env.run(&["sync", "--quiet"]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(remote_sessions(&env).len(), 2);
+        assert!(!repo.current_branch().unwrap().starts_with("sync-local-"));
+    }
+
+    #[test]
+    fn test_push_aborted_before_pushing_recovers() {
+        let env = TestEnv::new().with_sessions(1).with_remote();
+
+        let output = env.run(&["sync", "--quiet", "--fail-at", "push commit"]);
+        assert!(!output.status.success());
+        assert!(remote_sessions(&env).is_empty());
+
+        let output = env.run(&["push", "--quiet"]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(remote_sessions(&env).len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_phase_is_rejected() {
+        let env = TestEnv::new();
+        let output = env.run(&["sync", "--fail-at", "nowhere"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("nowhere"));
+    }
+}
+
+/// Session files a pull leaves out are reported with the reason, and fail
+/// it under `--strict`
+mod skip_report {
+    use claude_code_sync::test_support::TestEnv;
+
+    #[test]
+    fn test_pull_reports_skipped_sessions() {
+        let env = TestEnv::new().with_sessions(2);
+        let excluded = env.session_ids()[0].clone();
+        let env = env.with_filter(|f| f.exclude_patterns = vec![format!("*{excluded}*")]);
+        let broken = env.session_path(&excluded).with_file_name("broken.jsonl");
+        std::fs::write(&broken, "not json\n").unwrap();
+
+        let output = env.run_ok(&["pull"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Skipped 2 session files"), "{stdout}");
+        assert!(stdout.contains("failed to parse"), "{stdout}");
+
+        let output = env.run_ok(&["history", "last", "--format", "json"]);
+        let record: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let skipped: Vec<(String, String)> = record["skipped_sessions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| {
+                (
+                    s["path"].as_str().unwrap().to_string(),
+                    s["reason"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(skipped.len(), 2, "{skipped:?}");
+        let (path, reason) = skipped
+            .iter()
+            .find(|(path, _)| path.contains(&excluded))
+            .unwrap();
+        assert!(path.ends_with(&format!("{excluded}.jsonl")));
+        assert_eq!(reason, &format!("matches exclude pattern '*{excluded}*'"));
+        assert!(skipped
+            .iter()
+            .any(|(path, reason)| path == &broken.display().to_string()
+                && reason.starts_with("failed to parse")));
+    }
+
+    #[test]
+    fn test_strict_pull_fails_on_unparsable_session() {
+        let env = TestEnv::new().with_sessions(2);
+        let excluded = env.session_ids()[0].clone();
+        let env = env.with_filter(|f| f.exclude_patterns = vec![format!("*{excluded}*")]);
+
+        // Sessions excluded on purpose don't count
+        env.run_ok(&["pull", "--strict"]);
+
+        let broken = env.session_path(&excluded).with_file_name("broken.jsonl");
+        std::fs::write(&broken, "not json\n").unwrap();
+        let output = env.run(&["pull", "--strict"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Strict mode"), "{stderr}");
+        assert!(stderr.contains("broken.jsonl"), "{stderr}");
+
+        // The pull itself went through and was recorded
+        let output = env.run_ok(&["history", "last", "--format", "json"]);
+        let record: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert!(record["skipped_sessions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|s| s["kind"] == "unparsable"));
+    }
+}
+
+/// Hand edits in the sync repo are not swept into sync commits
+mod unexpected_changes {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    #[test]
+    fn test_sync_leaves_hand_edits_outside_synced_paths_uncommitted() {
+        let env = TestEnv::new().with_sessions(1);
+        let session_id = env.session_ids()[0].clone();
+        fs::write(env.repo_dir().join("NOTES.md"), "hand-written\n").unwrap();
+        fs::write(env.repo_dir().join("README.md"), "edited by hand\n").unwrap();
+
+        for command in [&["sync"][..], &["push"]] {
+            let output = env.run_ok(command);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(stdout.contains("NOTES.md"), "{stdout}");
+            assert!(stdout.contains("Left 2 changes uncommitted"), "{stdout}");
+        }
+
+        // The session was committed, the hand edits were not
+        let committed = env.git(&["ls-files"]);
+        assert!(committed.contains(&format!("{session_id}.jsonl")));
+        assert!(!committed.contains("NOTES.md"));
+        let status = env.git(&["status", "--porcelain"]);
+        assert!(status.contains("?? NOTES.md"), "{status}");
+        assert!(status.contains(" M README.md"), "{status}");
+    }
+}
+
+/// `history-sync` syncs only history.jsonl; `--no-history` leaves it out of
+/// a normal sync, which otherwise carries it
+mod history_sync {
+    use claude_code_sync::scm;
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    /// Files on the remote's current branch
+    fn remote_files(env: &TestEnv) -> Vec<String> {
+        let clone = tempfile::TempDir::new().unwrap();
+        let repo = scm::clone(&env.remote_dir().unwrap().to_string_lossy(), clone.path()).unwrap();
+        let branch = repo.current_branch().unwrap();
+        repo.list_files_at(&branch, ".").unwrap()
+    }
+
+    fn write_history(env: &TestEnv, lines: &[&str]) {
+        fs::write(
+            env.claude_dir().join("history.jsonl"),
+            lines.join("\n") + "\n",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_history_sync_pushes_only_history() {
+        let env = TestEnv::new().with_sessions(2).with_remote();
+        write_history(
+            &env,
+            &[r#"{"sessionId":"a","timestamp":1000,"display":"first"}"#],
+        );
+
+        env.run_ok(&["history-sync", "--quiet"]);
+
+        let files = remote_files(&env);
+        assert!(files.contains(&"history.jsonl".to_string()), "{files:?}");
+        assert!(!files
+            .iter()
+            .any(|f| f.ends_with(".jsonl") && f != "history.jsonl"));
+    }
+
+    #[test]
+    fn test_history_sync_merges_both_ways() {
+        let env = TestEnv::new().with_remote();
+        write_history(
+            &env,
+            &[r#"{"sessionId":"a","timestamp":1000,"display":"local"}"#],
+        );
+        // Another machine already pushed an entry
+        let repo_history = env.repo_dir().join("history.jsonl");
+        fs::write(
+            &repo_history,
+            r#"{"sessionId":"b","timestamp":2000,"display":"remote"}"#.to_string() + "\n",
+        )
+        .unwrap();
+
+        env.run_ok(&["history-sync", "--quiet", "--no-push"]);
+
+        let local = fs::read_to_string(env.claude_dir().join("history.jsonl")).unwrap();
+        assert!(
+            local.contains("local") && local.contains("remote"),
+            "{local}"
+        );
+        assert_eq!(fs::read_to_string(&repo_history).unwrap(), local);
+        assert!(!remote_files(&env).contains(&"history.jsonl".to_string()));
+    }
+
+    #[test]
+    fn test_sync_no_history_leaves_history_alone() {
+        let env = TestEnv::new().with_sessions(1).with_remote();
+        write_history(
+            &env,
+            &[r#"{"sessionId":"a","timestamp":1000,"display":"first"}"#],
+        );
+
+        env.run_ok(&["sync", "--quiet", "--no-history"]);
+
+        let files = remote_files(&env);
+        assert!(!files.contains(&"history.jsonl".to_string()), "{files:?}");
+        assert!(files
+            .iter()
+            .any(|f| f.ends_with(&format!("{}.jsonl", env.session_ids()[0]))));
+
+        // A normal sync carries it
+        env.run_ok(&["sync", "--quiet"]);
+        assert!(remote_files(&env).contains(&"history.jsonl".to_string()));
+    }
+}
+
+/// `sync --scheduled` waits out the minimum interval between automatic syncs
+mod scheduled_sync {
+    use claude_code_sync::test_support::TestEnv;
+
+    fn sync_count(env: &TestEnv) -> usize {
+        let output = env.run_ok(&["history", "list", "--format", "json"]);
+        let records: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+        records.len()
+    }
+
+    #[test]
+    fn test_scheduled_sync_skipped_within_minimum_interval() {
+        let env = TestEnv::new()
+            .with_sessions(1)
+            .with_filter(|f| f.min_sync_interval_secs = 3600);
+        env.run_ok(&["sync", "--quiet"]);
+
+        let output = env.run_ok(&["sync", "--scheduled", "--quiet"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Skipping scheduled sync"), "{stdout}");
+        assert_eq!(sync_count(&env), 1);
+
+        // Syncs run by hand are never held back
+        env.run_ok(&["sync", "--quiet"]);
+        assert_eq!(sync_count(&env), 2);
+    }
+
+    #[test]
+    fn test_scheduled_sync_runs_without_minimum_interval() {
+        let env = TestEnv::new().with_sessions(1);
+        env.run_ok(&["sync", "--quiet"]);
+
+        env.run_ok(&["sync", "--scheduled", "--quiet"]);
+        assert_eq!(sync_count(&env), 2);
+    }
+}
+
+/// `pull --at` restores the sync repo state of an earlier commit
+mod pull_at {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    #[test]
+    fn test_pull_at_restores_without_removing() {
+        let env = TestEnv::new().with_sessions(2);
+        env.run_ok(&["sync", "--quiet"]);
+        let good = env.git(&["rev-parse", "HEAD"]);
+
+        // A bad sync drops a session from the repo, and it is lost locally too
+        let (lost, truncated) = (&env.session_ids()[0], &env.session_ids()[1]);
+        let lost_path = env.session_path(lost);
+        let lost_content = fs::read_to_string(&lost_path).unwrap();
+        let repo_file = env.git(&["ls-files", &format!("*{lost}.jsonl")]);
+        env.git(&["rm", "-q", &repo_file]);
+        env.git(&["commit", "-qm", "Bad sync"]);
+        let bad = env.git(&["rev-parse", "HEAD"]);
+        fs::remove_file(&lost_path).unwrap();
+
+        // Another session lost its tail but gained a new entry since
+        let truncated_path = env.session_path(truncated);
+        let content = fs::read_to_string(&truncated_path).unwrap();
+        let first = content.lines().next().unwrap();
+        let newer = first.replace("\"uuid\":\"", "\"uuid\":\"new-");
+        fs::write(&truncated_path, format!("{first}\n{newer}\n")).unwrap();
+
+        env.run_ok(&["pull", "--at", &good, "--quiet"]);
+
+        let lost_restored = fs::read_to_string(&lost_path).unwrap();
+        assert_eq!(lost_restored.lines().count(), lost_content.lines().count());
+        let restored = fs::read_to_string(&truncated_path).unwrap();
+        assert_eq!(restored.lines().count(), content.lines().count() + 1);
+        assert!(restored.contains(&newer));
+        // The sync repo is left where it was
+        assert_eq!(env.git(&["rev-parse", "HEAD"]), bad);
+    }
+
+    #[test]
+    fn test_pull_at_unknown_revision_fails() {
+        let env = TestEnv::new().with_sessions(1);
+        env.run_ok(&["sync", "--quiet"]);
+        let path = env.session_path(&env.session_ids()[0]);
+        let content = fs::read_to_string(&path).unwrap();
+
+        let output = env.run(&["pull", "--at", "no-such-revision"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Not a valid object name"), "{stderr}");
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+}
+
+/// Snapshots tag a verified sync state that `restore` can bring back
+mod snapshot {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+    use std::process::Command;
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let env = TestEnv::new().with_sessions(2);
+        env.run_ok(&["snapshot", "before-experiment", "--quiet"]);
+
+        let tags = Command::new("git")
+            .current_dir(env.repo_dir())
+            .args(["tag", "--list"])
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&tags.stdout).trim(),
+            "snapshot/before-experiment"
+        );
+        let listed = env.run_ok(&["snapshot", "--format", "csv"]);
+        assert!(String::from_utf8_lossy(&listed.stdout).contains("before-experiment"));
+
+        // Taking the same snapshot twice is refused
+        let again = env.run(&["snapshot", "before-experiment", "--quiet"]);
+        assert!(!again.status.success());
+
+        let session = env.session_path(&env.session_ids()[0]);
+        let content = fs::read_to_string(&session).unwrap();
+        fs::remove_file(&session).unwrap();
+
+        env.run_ok(&["restore", "--snapshot", "before-experiment", "--quiet"]);
+        let restored = fs::read_to_string(&session).unwrap();
+        assert_eq!(restored.lines().count(), content.lines().count());
+
+        env.run_ok(&["snapshot", "before-experiment", "--delete"]);
+        let missing = env.run(&["restore", "--snapshot", "before-experiment"]);
+        assert!(!missing.status.success());
+    }
+}
+
+/// `purge` removes a session from every commit and force-pushes; other
+/// clones reset to the rewritten history instead of merging the old one back
+mod purge {
+    use claude_code_sync::purge::PurgeIndex;
+    use claude_code_sync::scm::{self, HistoryRewrite};
+    use claude_code_sync::test_support::TestEnv;
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::process::Command;
+
+    fn write_history(env: &TestEnv) {
+        let lines: Vec<String> = env
+            .session_ids()
+            .iter()
+            .map(|id| format!(r#"{{"sessionId":"{id}","timestamp":1000,"display":"hi"}}"#))
+            .collect();
+        fs::write(
+            env.claude_dir().join("history.jsonl"),
+            lines.join("\n") + "\n",
+        )
+        .unwrap();
+    }
+
+    /// Every file path in the remote branch's history
+    fn remote_history_files(env: &TestEnv) -> Vec<String> {
+        let clone = tempfile::TempDir::new().unwrap();
+        let repo = scm::clone(&env.remote_dir().unwrap().to_string_lossy(), clone.path()).unwrap();
+        repo.list_files_in_history().unwrap()
+    }
+
+    /// Whether any commit on the remote branch mentions `text` in history.jsonl
+    fn remote_history_mentions(env: &TestEnv, text: &str) -> bool {
+        let output = Command::new("git")
+            .args(["log", "-p", "--all", "--", "history.jsonl"])
+            .current_dir(env.remote_dir().unwrap())
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).contains(text)
+    }
+
+    #[test]
+    fn test_purge_rewrites_remote_history() {
+        let env = TestEnv::new().with_sessions(2).with_remote();
+        write_history(&env);
+        env.run_ok(&["sync", "--quiet"]);
+        let purged = env.session_ids()[0].clone();
+        let kept = env.session_ids()[1].clone();
+        assert!(remote_history_files(&env)
+            .iter()
+            .any(|f| f.contains(&purged)));
+
+        env.run_ok(&["purge", &purged, "--yes"]);
+
+        let files = remote_history_files(&env);
+        assert!(!files.iter().any(|f| f.contains(&purged)), "{files:?}");
+        assert!(files.iter().any(|f| f.contains(&kept)), "{files:?}");
+        assert!(!remote_history_mentions(&env, &purged));
+        assert!(remote_history_mentions(&env, &kept));
+        assert!(!env.session_path(&purged).exists());
+
+        let purges = PurgeIndex::load(env.repo_dir()).unwrap();
+        assert_eq!(purges.purges.len(), 1);
+    }
+
+    #[test]
+    fn test_purge_needs_the_session_id() {
+        let env = TestEnv::new().with_sessions(1).with_remote();
+        env.run_ok(&["sync", "--quiet"]);
+        let session = env.session_ids()[0].clone();
+
+        // A reference only a fuzzy match would resolve rewrites nothing
+        let fuzzy: String = session.chars().filter(|c| *c != '-').skip(2).collect();
+        let output = env.run(&["purge", &fuzzy, "--yes"]);
+        assert!(!output.status.success());
+        assert!(remote_history_files(&env)
+            .iter()
+            .any(|f| f.contains(&session)));
+        assert!(PurgeIndex::load(env.repo_dir()).unwrap().purges.is_empty());
+    }
+
+    #[test]
+    fn test_pull_resets_to_history_purged_elsewhere() {
+        let env = TestEnv::new().with_sessions(2).with_remote();
+        env.run_ok(&["sync", "--quiet"]);
+        let purged = env.session_ids()[0].clone();
+
+        // Another machine purges the session
+        let other = env.root().join("other");
+        let repo = scm::clone(&env.remote_dir().unwrap().to_string_lossy(), &other).unwrap();
+        for (key, value) in [("user.name", "Other"), ("user.email", "other@local")] {
+            Command::new("git")
+                .args(["config", key, value])
+                .current_dir(&other)
+                .output()
+                .unwrap();
+        }
+        let paths: Vec<String> = repo
+            .list_files_in_history()
+            .unwrap()
+            .into_iter()
+            .filter(|f| f.contains(&purged))
+            .collect();
+        repo.rewrite_history(&HistoryRewrite {
+            remove_paths: paths,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut purges = PurgeIndex::default();
+        purges.add("other-machine", BTreeSet::from([purged.clone()]));
+        purges.save(&other).unwrap();
+        repo.stage_all().unwrap();
+        repo.commit("Purge").unwrap();
+        let branch = repo.current_branch().unwrap();
+        repo.force_push("origin", &branch).unwrap();
+
+        env.run_ok(&["sync", "--quiet"]);
+
+        assert!(!env.session_path(&purged).exists());
+        assert!(env.session_path(&env.session_ids()[1]).exists());
+        let local = scm::open(env.repo_dir()).unwrap();
+        assert!(!local
+            .list_files_in_history()
+            .unwrap()
+            .iter()
+            .any(|f| f.contains(&purged)));
+        assert!(!remote_history_files(&env)
+            .iter()
+            .any(|f| f.contains(&purged)));
+    }
+}
+
+/// The hidden `stress` command: concurrent syncs from several simulated
+/// machines must converge without losing entries
+mod stress {
+    use claude_code_sync::test_support::TestEnv;
+
+    #[test]
+    fn test_stress_small_run_loses_nothing() {
+        let env = TestEnv::new();
+        let output = env
+            .command(env!("CARGO_BIN_EXE_claude-code-sync"))
+            .args(["stress", "--machines", "2", "--rounds", "2", "--seed", "3"])
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success(),
+            "{stdout}\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(stdout.contains("No entries lost"), "{stdout}");
+    }
+}
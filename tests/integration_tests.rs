@@ -235,3 +235,858 @@ fn test_concurrent_file_access() {
         handle.join().unwrap();
     }
 }
+
+/// Tests for the `test_support` environment builders, which also exercise a
+/// full sync against a bare remote
+mod support_env {
+    use claude_code_sync::scm;
+    use claude_code_sync::sync::sync_bidirectional;
+    use claude_code_sync::test_support::TestEnv;
+    use claude_code_sync::VerbosityLevel;
+    use serial_test::file_serial;
+    use std::path::Path;
+
+    /// Session files in the remote's current branch
+    fn remote_session_files(env: &TestEnv, remote: &Path) -> Vec<String> {
+        let clone = env.root().join("check");
+        let repo = scm::clone(&remote.to_string_lossy(), &clone).unwrap();
+        let branch = repo.current_branch().unwrap();
+        repo.list_files_at(&branch, ".")
+            .unwrap()
+            .into_iter()
+            .filter(|f| f.ends_with(".jsonl"))
+            .collect()
+    }
+
+    #[test]
+    fn test_env_layout() {
+        let env = TestEnv::new().with_sessions(3);
+
+        assert_eq!(env.session_ids().len(), 3);
+        for id in env.session_ids() {
+            let content = std::fs::read_to_string(env.session_path(id)).unwrap();
+            assert_eq!(content.lines().count(), 4);
+        }
+        assert!(env.config_dir().join("state.json").exists());
+        assert!(env.config_dir().join("config.toml").exists());
+        assert!(scm::is_repo(env.repo_dir()));
+        assert!(env.remote_dir().is_none());
+    }
+
+    #[test]
+    #[file_serial]
+    fn test_sync_to_remote_in_process() {
+        let env = TestEnv::new().with_sessions(2).with_remote();
+        {
+            let _active = env.activate();
+            sync_bidirectional(
+                None,
+                None,
+                false,
+                false,
+                &[],
+                false,
+                false,
+                false,
+                false,
+                VerbosityLevel::Quiet,
+            )
+            .unwrap();
+        }
+
+        let files = remote_session_files(&env, env.remote_dir().unwrap());
+        assert_eq!(files.len(), 2, "{files:?}");
+        for id in env.session_ids() {
+            assert!(files.iter().any(|f| f.ends_with(&format!("{id}.jsonl"))));
+        }
+    }
+
+    #[test]
+    fn test_sync_to_remote_with_binary() {
+        let env = TestEnv::new().with_sessions(1).with_remote();
+        let output = env
+            .command(env!("CARGO_BIN_EXE_claude-code-sync"))
+            .args(["sync", "--quiet"])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let files = remote_session_files(&env, env.remote_dir().unwrap());
+        assert_eq!(files.len(), 1, "{files:?}");
+    }
+}
+
+/// The verbosity, color, locale, and ASCII settings apply unless flags
+/// override them
+mod config_defaults {
+    use claude_code_sync::test_support::TestEnv;
+
+    fn stdout(env: &TestEnv, args: &[&str]) -> String {
+        String::from_utf8(env.run_ok(args).stdout).unwrap()
+    }
+
+    #[test]
+    fn test_configured_verbosity_is_the_default() {
+        let env = TestEnv::new()
+            .with_sessions(1)
+            .with_filter(|f| f.verbosity = "summary".to_string());
+
+        let summary = stdout(&env, &["sync"]);
+        assert!(
+            summary.lines().any(|l| l.starts_with("pull: ")),
+            "{summary}"
+        );
+        assert!(!summary.contains("Pulling"), "{summary}");
+
+        // A flag still wins
+        let verbose = stdout(&env, &["sync", "--verbose"]);
+        assert!(
+            !verbose.lines().any(|l| l.starts_with("pull: ")),
+            "{verbose}"
+        );
+    }
+
+    #[test]
+    fn test_color_always_overrides_no_color() {
+        let env = TestEnv::new().with_filter(|f| f.color = "always".to_string());
+        let colored = stdout(&env, &["config", "--show"]);
+        assert!(colored.contains('\u{1b}'), "{colored}");
+
+        let mut command = env.command(env!("CARGO_BIN_EXE_claude-code-sync"));
+        let plain = command
+            .args(["--no-color", "config", "--show"])
+            .output()
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&plain.stdout).contains('\u{1b}'));
+    }
+
+    #[test]
+    fn test_summary_in_configured_locale() {
+        let env = TestEnv::new()
+            .with_sessions(1)
+            .with_filter(|f| f.locale = "de".to_string());
+
+        let summary = stdout(&env, &["sync", "-q"]);
+        assert!(
+            summary.lines().any(|l| l.starts_with("pull: +")
+                && l.contains(" hinzugefügt, ")
+                && l.ends_with(", 0 Fehler")),
+            "{summary}"
+        );
+    }
+
+    #[test]
+    fn test_ascii_output() {
+        let env = TestEnv::new().with_sessions(1);
+        let glyphs = ['✓', '✗', 'ℹ', '↳', '→', '•', '…', '⚠'];
+
+        let ascii = stdout(&env, &["--ascii", "sync"]);
+        assert!(ascii.contains("[ok] "), "{ascii}");
+        assert!(!ascii.contains(glyphs), "{ascii}");
+
+        let env = env.with_filter(|f| f.ascii = true);
+        let configured = stdout(&env, &["sync", "--verbose"]);
+        assert!(!configured.contains(glyphs), "{configured}");
+    }
+}
+
+/// Pull links sessions forked from one another into thread families
+mod families {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    #[test]
+    fn test_pull_links_forked_sessions() {
+        let env = TestEnv::new().with_sessions(2);
+        let original = env.session_ids()[0].clone();
+        let fork = "00000000-0000-4000-8000-0000000000ff";
+
+        // The first exchange of the original, then a different second question
+        let copied: Vec<String> = fs::read_to_string(env.session_path(&original))
+            .unwrap()
+            .lines()
+            .take(2)
+            .map(|line| line.replace(&original, fork))
+            .collect();
+        let retry = serde_json::json!({
+            "type": "user",
+            "uuid": "00000000-0000-4000-00ff-000000000002",
+            "parentUuid": "00000000-0000-4000-0001-000000000001",
+            "sessionId": fork,
+            "timestamp": "2025-01-02T11:00:00.000Z",
+            "message": {"role": "user", "content": "Ask it differently"},
+        });
+        fs::write(
+            env.session_path(fork),
+            format!("{}\n{retry}\n", copied.join("\n")),
+        )
+        .unwrap();
+
+        let output = env.run_ok(&["pull"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Linked 1 forked sessions"), "{stdout}");
+
+        let index: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(env.repo_dir().join("families.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            index,
+            serde_json::json!({fork: {"parent": original, "shared_entries": 2}})
+        );
+
+        // Already linked, so the next pull leaves the index alone
+        let output = env.run_ok(&["pull"]);
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("Linked"));
+
+        let output = env.run_ok(&["timeline", "--since", "1000w", "--format", "json"]);
+        // Log lines may come before the JSON
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let events: serde_json::Value =
+            serde_json::from_str(&stdout[stdout.find("\n[").unwrap()..]).unwrap();
+        let threads: Vec<(&str, &str)> = events
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                (
+                    e["session_id"].as_str().unwrap(),
+                    e["thread"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert!(threads.contains(&(fork, original.as_str())), "{threads:?}");
+        let other = env.session_ids()[1].as_str();
+        assert!(threads.contains(&(other, other)), "{threads:?}");
+
+        let output = env.run_ok(&["status", "--show-files"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("(fork of 00000000)"), "{stdout}");
+    }
+}
+
+/// `doctor --fix-paths` moves sessions stored under nested paths in the
+/// sync repo to their canonical paths
+mod fix_paths {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    #[test]
+    fn test_fix_paths_moves_and_merges_nested_sessions() {
+        let env = TestEnv::new().with_sessions(2);
+        env.run_ok(&["pull"]);
+
+        let ids = env.session_ids().to_vec();
+        let projects = env.repo_dir().join("projects");
+        let local = env.session_path(&ids[0]);
+        let project = local.parent().unwrap().file_name().unwrap();
+        let canonical = |id: &str| projects.join(project).join(format!("{id}.jsonl"));
+        let nested_dir = projects.join("home/me/.claude/projects").join(project);
+        fs::create_dir_all(&nested_dir).unwrap();
+
+        // A copy of a synced session with one more entry, and a session only
+        // stored under the nested path
+        let mut extended = fs::read_to_string(canonical(&ids[0])).unwrap();
+        let extra = extended
+            .lines()
+            .last()
+            .unwrap()
+            .replace("-000000000003\"", "-0000000000ff\"");
+        extended.push_str(&extra);
+        extended.push('\n');
+        fs::write(nested_dir.join(format!("{}.jsonl", ids[0])), extended).unwrap();
+        fs::rename(
+            canonical(&ids[1]),
+            nested_dir.join(format!("{}.jsonl", ids[1])),
+        )
+        .unwrap();
+        env.git(&["add", "-A"]);
+        env.git(&["commit", "-qm", "Nested sessions"]);
+
+        let output = env.run_ok(&["doctor"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("2 sessions are stored under nested paths"),
+            "{stdout}"
+        );
+
+        env.run_ok(&["doctor", "--fix-paths"]);
+        assert!(!projects.join("home").exists());
+        let merged = fs::read_to_string(canonical(&ids[0])).unwrap();
+        assert!(merged.contains("-0000000000ff"), "{merged}");
+        assert_eq!(
+            merged.lines().count(),
+            fs::read_to_string(&local).unwrap().lines().count() + 1
+        );
+        assert!(canonical(&ids[1]).exists());
+        assert!(env.git(&["status", "--porcelain"]).is_empty());
+
+        let output = env.run_ok(&["doctor"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!stdout.contains("nested paths"), "{stdout}");
+    }
+}
+
+/// Every push is recorded in the audit log with the files and entry UUIDs
+/// the remote didn't have yet
+mod audit {
+    use claude_code_sync::test_support::TestEnv;
+    use std::collections::BTreeSet;
+    use std::fs;
+
+    /// Records for a session from `audit list --format json`
+    fn audit_records(env: &TestEnv, session_id: &str) -> Vec<serde_json::Value> {
+        let output = env.run_ok(&["audit", "list", "--session", session_id, "--format", "json"]);
+        serde_json::from_slice(&output.stdout).unwrap()
+    }
+
+    fn pushed_uuids(records: &[serde_json::Value]) -> BTreeSet<String> {
+        records
+            .iter()
+            .flat_map(|r| r["uuids"].as_str().unwrap().split_whitespace())
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_audit_log_records_pushed_entries() {
+        let env = TestEnv::new().with_sessions(2).with_remote();
+        let session_id = env.session_ids()[0].clone();
+        let local_path = env.session_path(&session_id);
+        let uuids: BTreeSet<String> = fs::read_to_string(&local_path)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+                entry["uuid"].as_str().unwrap().to_string()
+            })
+            .collect();
+
+        env.run_ok(&["sync", "--quiet"]);
+        let first = audit_records(&env, &session_id);
+        assert!(!first.is_empty());
+        assert_eq!(pushed_uuids(&first), uuids);
+        assert!(first
+            .iter()
+            .all(|r| r["session_id"] == session_id.as_str() && r["remote"] == "origin"));
+
+        // Only the new entry is recorded by the next push
+        let mut content = fs::read_to_string(&local_path).unwrap();
+        let mut entry: serde_json::Value =
+            serde_json::from_str(content.lines().last().unwrap()).unwrap();
+        entry["parentUuid"] = entry["uuid"].clone();
+        entry["uuid"] = "00000000-0000-4000-0000-0000000000ff".into();
+        content.push_str(&format!("{entry}\n"));
+        fs::write(&local_path, content).unwrap();
+
+        env.run_ok(&["sync", "--quiet"]);
+        let second = audit_records(&env, &session_id);
+        let new: Vec<_> = second[first.len()..].to_vec();
+        assert!(!new.is_empty());
+        assert_eq!(
+            pushed_uuids(&new),
+            BTreeSet::from(["00000000-0000-4000-0000-0000000000ff".to_string()])
+        );
+
+        let table = env.run_ok(&["audit", "list"]);
+        assert!(String::from_utf8_lossy(&table.stdout).contains("Audit Log"));
+    }
+}
+
+/// `blame` reports which commit and machine introduced each session entry
+mod blame {
+    use claude_code_sync::test_support::TestEnv;
+    use std::collections::BTreeSet;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    #[test]
+    fn test_blame_attributes_entries_to_commits_and_machines() {
+        let env = TestEnv::new().with_sessions(1);
+        let session_id = env.session_ids()[0].clone();
+        env.run_ok(&["sync", "--quiet"]);
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(env.session_path(&session_id))
+            .unwrap();
+        writeln!(
+            file,
+            "{{\"type\":\"user\",\"sessionId\":\"{session_id}\",\"uuid\":\"late-entry\",\"timestamp\":\"2025-01-02T00:00:00Z\"}}"
+        )
+        .unwrap();
+        env.run_ok(&["sync", "--quiet"]);
+
+        let output = env.run_ok(&["blame", &session_id[..8], "--format", "json"]);
+        let rows: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+        let last = rows.last().unwrap();
+        assert_eq!(last["uuid"], "late-entry");
+        assert_eq!(last["state"], "present");
+        let commits: BTreeSet<&str> = rows.iter().map(|r| r["commit"].as_str().unwrap()).collect();
+        assert_eq!(commits.len(), 2);
+        assert!(rows
+            .iter()
+            .all(|r| !r["machine"].as_str().unwrap().is_empty()));
+
+        let table =
+            String::from_utf8_lossy(&env.run_ok(&["blame", &session_id]).stdout).to_string();
+        assert!(table.contains("entries from 2 commits"), "{table}");
+    }
+
+    #[test]
+    fn test_blame_unsynced_session_fails() {
+        let env = TestEnv::new().with_sessions(1);
+        let session_id = env.session_ids()[0].clone();
+
+        let output = env.run(&["blame", &session_id]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("has no committed entries"), "{stderr}");
+    }
+}
+
+/// Bundles move sync repo history between machines without a shared remote
+mod bundle {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    /// Session files under `env`'s `.claude/projects`, by file name
+    fn session_files(env: &TestEnv) -> Vec<String> {
+        let mut files: Vec<String> = walkdir::WalkDir::new(env.projects_dir())
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "jsonl"))
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_export_and_import_bundles() {
+        let laptop = TestEnv::new().with_sessions(1);
+        let desktop = TestEnv::new();
+        let bundle = laptop.root().join("first.bundle");
+        let bundle_arg = bundle.to_str().unwrap();
+
+        laptop.run_ok(&["export", "--git-bundle", bundle_arg, "--quiet"]);
+        desktop.run_ok(&["import", "--git-bundle", bundle_arg, "--quiet"]);
+        assert_eq!(session_files(&desktop), session_files(&laptop));
+
+        // The next export only carries what changed since
+        let session = laptop.session_path(&laptop.session_ids()[0]);
+        let content = fs::read_to_string(&session).unwrap();
+        let first = content.lines().next().unwrap();
+        let newer = first.replace("\"uuid\":\"", "\"uuid\":\"new-");
+        fs::write(&session, format!("{content}{newer}\n")).unwrap();
+        let second = laptop.root().join("second.bundle");
+        let second_arg = second.to_str().unwrap();
+        laptop.run_ok(&["export", "--git-bundle", second_arg, "--quiet"]);
+        desktop.run_ok(&["import", "--git-bundle", second_arg, "--quiet"]);
+
+        let imported = desktop
+            .projects_dir()
+            .join(session.strip_prefix(laptop.projects_dir()).unwrap());
+        let imported = fs::read_to_string(imported).unwrap();
+        assert!(imported.contains("\"uuid\":\"new-"), "{imported}");
+
+        // Nothing new to export
+        let again = laptop.run(&["export", "--git-bundle", second_arg]);
+        assert!(!again.status.success());
+    }
+
+    #[test]
+    fn test_import_of_missing_bundle_fails() {
+        let env = TestEnv::new().with_sessions(1);
+        let missing = env.root().join("missing.bundle");
+
+        let output = env.run(&["import", "--git-bundle", missing.to_str().unwrap()]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Failed to open bundle"), "{stderr}");
+        assert_eq!(session_files(&env).len(), 1);
+    }
+}
+
+/// `export --graph` writes the parentUuid graph of a session
+mod export_graph {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    #[test]
+    fn test_export_graph_shows_forks() {
+        let env = TestEnv::new().with_sessions(1);
+        let session_id = env.session_ids()[0].clone();
+        let path = env.session_path(&session_id);
+
+        // A second reply to the first answer, as a rewind in Claude Code leaves
+        let mut content = fs::read_to_string(&path).unwrap();
+        let first_answer = "00000000-0000-4000-0001-000000000001";
+        let retry = serde_json::json!({
+            "type": "user",
+            "uuid": "00000000-0000-4000-0001-0000000000ff",
+            "parentUuid": first_answer,
+            "sessionId": session_id,
+            "timestamp": "2025-01-02T11:00:00.000Z",
+            "message": {"role": "user", "content": "Try \"again\""},
+        });
+        content.push_str(&format!("{retry}\n"));
+        fs::write(&path, content).unwrap();
+
+        let output = env.run_ok(&["export", "--graph", &session_id[..13], "--format", "json"]);
+        let graph: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(graph["session_id"], session_id.as_str());
+        assert_eq!(graph["nodes"].as_array().unwrap().len(), 5);
+        assert_eq!(graph["edges"].as_array().unwrap().len(), 4);
+        assert_eq!(graph["forks"], serde_json::json!([first_answer]));
+        assert_eq!(graph["roots"].as_array().unwrap().len(), 1);
+
+        let dot_path = env.root().join("graph.dot");
+        env.run_ok(&[
+            "export",
+            "--graph",
+            &session_id,
+            "--output",
+            dot_path.to_str().unwrap(),
+        ]);
+        let dot = fs::read_to_string(&dot_path).unwrap();
+        assert!(
+            dot.starts_with(&format!("digraph \"{session_id}\"")),
+            "{dot}"
+        );
+        assert!(dot.contains(r#"Try \"again\""#), "{dot}");
+        assert!(dot.contains(&format!("\"{first_answer}\" -> ")), "{dot}");
+    }
+
+    #[test]
+    fn test_export_graph_needs_a_session_without_a_terminal() {
+        let env = TestEnv::new().with_sessions(1);
+        let output = env.run(&["export", "--graph"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("A session ID is required"), "{stderr}");
+    }
+}
+
+/// `export --site` writes a static HTML browser of the synced sessions
+mod export_site {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    #[test]
+    fn test_export_site() {
+        let env = TestEnv::new().with_sessions(2);
+        assert!(env.run(&["sync", "--quiet"]).status.success());
+
+        let site = env.root().join("site");
+        let output = env.run(&["export", "--site", site.to_str().unwrap()]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let index = fs::read_to_string(site.join("index.html")).unwrap();
+        let ids = env.session_ids().to_vec();
+        for id in &ids {
+            assert!(
+                index.contains(&format!("href=\"sessions/{id}.html\"")),
+                "{index}"
+            );
+        }
+        // Newest session first
+        assert!(index.find(&ids[1]).unwrap() < index.find(&ids[0]).unwrap());
+
+        let page = fs::read_to_string(site.join(format!("sessions/{}.html", ids[0]))).unwrap();
+        assert!(page.contains("Question 2 in session 1"), "{page}");
+        assert!(page.contains("Answer 2 in session 1"), "{page}");
+        let search = fs::read_to_string(site.join("search-index.js")).unwrap();
+        assert!(search.contains("answer 2 in session 2"), "{search}");
+
+        // A stale page from an earlier export is removed when exporting again
+        fs::write(site.join("sessions/gone.html"), "").unwrap();
+        let output = env.run(&["export", "--site", site.to_str().unwrap()]);
+        assert!(output.status.success());
+        assert!(!site.join("sessions/gone.html").exists());
+
+        // A directory holding anything else is left alone
+        let other = env.root().join("other");
+        fs::create_dir_all(&other).unwrap();
+        fs::write(other.join("notes.txt"), "mine").unwrap();
+        let output = env.run(&["export", "--site", other.to_str().unwrap()]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("is not empty"));
+    }
+}
+
+/// `report --weekly` sums up the last week of sync activity
+mod weekly_report {
+    use claude_code_sync::test_support::TestEnv;
+    use std::fs;
+
+    #[test]
+    fn test_weekly_report() {
+        let env = TestEnv::new().with_sessions(2);
+        env.run_ok(&["sync", "--quiet"]);
+
+        let output = env.run_ok(&["report", "--weekly", "--format", "md"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let digest = &stdout[stdout.find("# Claude Code sync digest").expect(&stdout)..];
+        assert!(digest.contains("- 1 operations: 1 pull\n"), "{digest}");
+        assert!(
+            digest.contains("- 2 sessions added, 0 modified, 0 conflicts\n"),
+            "{digest}"
+        );
+        assert!(
+            digest.contains("- Sync repo: 2 session files, "),
+            "{digest}"
+        );
+        assert!(digest.contains("/home/project | 2 | 0 | 0 |\n"), "{digest}");
+
+        let out = env.root().join("digest.json");
+        env.run_ok(&[
+            "report",
+            "--weekly",
+            "--format",
+            "json",
+            "--output",
+            out.to_str().unwrap(),
+        ]);
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(json["operations"]["pull"], 1);
+        assert_eq!(json["repo"]["sessions"], 2);
+    }
+}
+
+/// `mcp-serve` answers Model Context Protocol requests over stdio
+mod mcp_serve {
+    use claude_code_sync::test_support::TestEnv;
+    use std::io::Write;
+    use std::process::Stdio;
+
+    #[test]
+    fn test_mcp_serve_answers_tool_calls() {
+        let env = TestEnv::new().with_sessions(2);
+        let sync = env.run(&["sync", "--quiet"]);
+        assert!(sync.status.success());
+
+        let requests = [
+            serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"protocolVersion": "2025-06-18", "capabilities": {}, "clientInfo": {"name": "test", "version": "1"}}}),
+            serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+            serde_json::json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": {"name": "search_history", "arguments": {"query": "answer 2 session 2"}}}),
+        ];
+        let mut child = env
+            .command(env!("CARGO_BIN_EXE_claude-code-sync"))
+            .arg("mcp-serve")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        for request in &requests {
+            writeln!(stdin, "{request}").unwrap();
+        }
+        drop(stdin);
+        let output = child.wait_with_output().unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Every line on stdout is a response; the notification gets none
+        let responses: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["result"]["protocolVersion"], "2025-06-18");
+        let tools: Vec<&str> = responses[1]["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(tools, ["list_projects", "search_history", "get_session"]);
+
+        let result = &responses[2]["result"];
+        assert_eq!(result["isError"], false);
+        let found: serde_json::Value =
+            serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap();
+        // Words match anywhere in a message, newest first
+        let snippets: Vec<&str> = found["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["snippet"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            snippets,
+            [
+                "Answer 2 in session 2",
+                "Answer 1 in session 2",
+                "Answer 2 in session 1"
+            ]
+        );
+    }
+}
+
+/// `serve` answers HTTP requests about the synced history
+mod serve {
+    use claude_code_sync::test_support::TestEnv;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::process::{Child, Stdio};
+    use std::thread;
+
+    /// Kills the server when a test ends, passing or not
+    struct Server(Child);
+
+    /// Start `serve` on any free port, returning the server and its address
+    fn start(env: &TestEnv, token: Option<&str>) -> (Server, String) {
+        let mut command = env.command(env!("CARGO_BIN_EXE_claude-code-sync"));
+        command
+            .args(["serve", "--listen", "127.0.0.1:0"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        if let Some(token) = token {
+            command.env(claude_code_sync::server::API_TOKEN_ENV_VAR, token);
+        }
+        let mut server = Server(command.spawn().unwrap());
+        let mut lines = BufReader::new(server.0.stdout.take().unwrap()).lines();
+        let address = lines
+            .by_ref()
+            .map(|line| line.unwrap())
+            .find_map(|line| {
+                line.strip_prefix("Listening on http://")
+                    .map(str::to_string)
+            })
+            .expect("server never said where it listens");
+        // Keep reading, so the server can go on writing to stdout
+        thread::spawn(move || lines.for_each(drop));
+        (server, address)
+    }
+
+    impl Drop for Server {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    /// Send a request and return the status code and JSON body
+    fn request(address: &str, head: &str) -> (u16, serde_json::Value) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{head}\r\nHost: {address}\r\nContent-Length: 0\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_serve_answers_requests() {
+        let env = TestEnv::new().with_sessions(2);
+        let sync = env.run(&["sync", "--quiet"]);
+        assert!(sync.status.success());
+
+        let (_server, address) = start(&env, Some("s3cret"));
+
+        let (status, body) = request(&address, "GET /sessions HTTP/1.1");
+        assert_eq!(status, 200);
+        assert_eq!(body["total"], 2);
+        let session_id = body["items"][0]["session_id"].as_str().unwrap().to_string();
+        assert_eq!(session_id, env.session_ids()[1]);
+
+        let (status, body) = request(
+            &address,
+            &format!("GET /sessions/{session_id}/entries?limit=1 HTTP/1.1"),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body["total"], 4);
+        assert_eq!(body["items"].as_array().unwrap().len(), 1);
+
+        let (status, body) = request(&address, "GET /search?q=QUESTION+in HTTP/1.1");
+        assert_eq!(status, 200);
+        assert_eq!(body["total_matches"], 4);
+        assert!(body["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|m| m["snippet"].as_str().unwrap().starts_with("Question ")));
+
+        let (status, body) = request(&address, "GET /status HTTP/1.1");
+        assert_eq!(status, 200);
+        assert_eq!(body["sessions"], 2);
+        assert!(body["last_operation"]["timestamp"].is_string());
+
+        let (status, _) = request(&address, "POST /sync HTTP/1.1");
+        assert_eq!(status, 401);
+        let (status, body) = request(
+            &address,
+            "POST /sync HTTP/1.1\r\nAuthorization: Bearer s3cret",
+        );
+        assert_eq!(status, 200, "{body}");
+        assert_eq!(body["outcome"], "complete");
+    }
+
+    #[test]
+    fn test_serve_holds_back_syncs_within_minimum_interval() {
+        let env = TestEnv::new()
+            .with_sessions(1)
+            .with_filter(|f| f.min_sync_interval_secs = 3600);
+        env.run_ok(&["sync", "--quiet"]);
+        let (_server, address) = start(&env, Some("s3cret"));
+
+        let (status, body) = request(
+            &address,
+            "POST /sync HTTP/1.1\r\nAuthorization: Bearer s3cret",
+        );
+        assert_eq!(status, 429, "{body}");
+        assert!(body["retry_after_secs"].as_u64().unwrap() > 3500, "{body}");
+    }
+
+    #[test]
+    fn test_serve_refuses_foreign_hosts() {
+        let env = TestEnv::new().with_sessions(1);
+        let (_server, address) = start(&env, None);
+
+        let mut stream = TcpStream::connect(&address).unwrap();
+        write!(
+            stream,
+            "GET /status HTTP/1.1\r\nHost: attacker.example\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 403 "), "{response}");
+
+        // Scrapers name the host as configured, and metrics hold no history
+        let mut stream = TcpStream::connect(&address).unwrap();
+        write!(
+            stream,
+            "GET /metrics HTTP/1.1\r\nHost: box.lan:7878\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 "), "{response}");
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(
+            response.contains("\nclaude_code_sync_sessions 0\n"),
+            "{response}"
+        );
+    }
+}
@@ -55,6 +55,7 @@ fn test_sync_state_with_cloned_flag() -> Result<()> {
         sync_repo_path: repo_path.clone(),
         has_remote: true,
         is_cloned_repo: true,
+        rsync_remote: None,
     };
 
     let serialized = serde_json::to_string(&state)?;
@@ -202,8 +203,8 @@ fn test_config_directory_structure() -> Result<()> {
     assert!(snapshots_dir.exists());
     assert!(snapshots_dir.is_dir());
 
-    // Verify snapshots is a subdirectory of config
-    assert!(snapshots_dir.starts_with(&config_dir));
+    // Snapshots are state, kept in the state directory
+    assert!(snapshots_dir.starts_with(ConfigManager::state_dir()?));
 
     Ok(())
 }
@@ -20,6 +20,7 @@ fn create_entry(
         version: None,
         git_branch: None,
         extra: serde_json::Value::Null,
+        raw_line: None,
     }
 }
 
@@ -382,6 +383,7 @@ fn test_mixed_uuid_and_non_uuid_entries() {
                 version: None,
                 git_branch: None,
                 extra: serde_json::Value::Null,
+                raw_line: None,
             },
             create_entry("B", Some("A"), "2025-01-01T00:01:00Z", "Assistant response"),
         ],
@@ -84,6 +84,7 @@ fn test_message_selection_by_verbosity() {
     let verbosity = VerbosityLevel::Quiet;
     let message = match verbosity {
         VerbosityLevel::Quiet => "Brief",
+        VerbosityLevel::Summary => "One line",
         VerbosityLevel::Normal => "Normal message",
         VerbosityLevel::Verbose => "Detailed verbose message",
     };
@@ -92,6 +93,7 @@ fn test_message_selection_by_verbosity() {
     let verbosity = VerbosityLevel::Verbose;
     let message = match verbosity {
         VerbosityLevel::Quiet => "Brief",
+        VerbosityLevel::Summary => "One line",
         VerbosityLevel::Normal => "Normal message",
         VerbosityLevel::Verbose => "Detailed verbose message",
     };
@@ -182,12 +184,14 @@ fn test_verbosity_as_parameter() {
     fn process_with_verbosity(v: VerbosityLevel) -> &'static str {
         match v {
             VerbosityLevel::Quiet => "quiet",
+            VerbosityLevel::Summary => "summary",
             VerbosityLevel::Normal => "normal",
             VerbosityLevel::Verbose => "verbose",
         }
     }
 
     assert_eq!(process_with_verbosity(VerbosityLevel::Quiet), "quiet");
+    assert_eq!(process_with_verbosity(VerbosityLevel::Summary), "summary");
     assert_eq!(process_with_verbosity(VerbosityLevel::Normal), "normal");
     assert_eq!(process_with_verbosity(VerbosityLevel::Verbose), "verbose");
 }
@@ -276,3 +280,11 @@ fn test_combined_interactive_verbosity_logic() {
     assert!(!should_show_preview);
     assert!(!should_show_details);
 }
+
+/// Test that verbosity levels are ordered from least to most output
+#[test]
+fn test_verbosity_ordering() {
+    assert!(VerbosityLevel::Quiet < VerbosityLevel::Summary);
+    assert!(VerbosityLevel::Summary < VerbosityLevel::Normal);
+    assert!(VerbosityLevel::Normal < VerbosityLevel::Verbose);
+}